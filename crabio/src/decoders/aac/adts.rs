@@ -0,0 +1,709 @@
+//! ADTS, the frame format of `.aac` files and most AAC web radio streams.
+//!
+//! Each ADTS frame is a 7 byte header, a CRC if the frame is protected, and 1 to 4 raw data
+//! blocks. The header starts with a 12-bit sync word, which random data, tags and cover art
+//! are full of, and says little that can be checked on its own: a layer that has to be 0, a
+//! sample rate index and a frame length. [`AdtsSync`] therefore only declares lock when a run
+//! of consecutive headers agree on version, profile, sample rate, channel configuration,
+//! blocks per frame and bitrate mode, each found where the previous one says its frame ends. In lock every frame is checked
+//! again, its header and the next one, and a frame that does not fit is reported as lost sync
+//! instead of being decoded.
+//!
+//! The decoder itself takes the frames through [`AACDecoder::decode_adts_frame`], which sets
+//! it up from the header ([`AdtsFormat::audio_specific_config`]) and can check the CRC.
+
+use super::{AACDecoder, AacError, DecodedFrame, ElementId, NUM_SAMPLE_RATES, SAMP_RATE_TAB};
+use crate::{
+    metadata,
+    utils::crc16::{crc16, crc16_bits},
+};
+
+/// Bytes of the fixed and variable header, without the CRC.
+pub const ADTS_HEADER_BYTES: usize = 7;
+/// Consecutive frames [`AdtsSync::default`] wants to see before declaring lock.
+pub const DEFAULT_LOCK_FRAMES: usize = 3;
+
+/* bits of the first element of a channel stream protected by the CRC, and of the second
+ * channel stream of a CPE
+ */
+const CRC_BITS_ELEMENT: usize = 192;
+const CRC_BITS_SECOND_CHANNEL: usize = 128;
+
+/// Header fields that stay the same through a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdtsFormat {
+    /// MPEG-2 AAC rather than MPEG-4; the two only differ in the profiles allowed
+    pub mpeg2: bool,
+    /// audio object type minus 1: 0 = Main, 1 = LC, 2 = SSR, 3 = LTP (MPEG-4 only)
+    pub profile: u8,
+    /// index into [`SAMP_RATE_TAB`]
+    pub samp_rate_idx: u8,
+    /// 0 if a program config element in the stream gives the channels
+    pub channel_config: u8,
+    /// frames carry a CRC (protection_absent is clear)
+    pub protected: bool,
+}
+
+impl AdtsFormat {
+    /// Sample rate of the AAC core, in Hz; HE-AAC streams are output at twice this.
+    pub fn samp_rate(&self) -> i32 {
+        SAMP_RATE_TAB[self.samp_rate_idx as usize]
+    }
+
+    /// The AudioSpecificConfig an MP4 file would carry for the stream: object type, sample
+    /// rate index, channel configuration and a GASpecificConfig for 1024-sample frames.
+    ///
+    /// HE-AAC is not signalled in ADTS, the decoder finds the SBR data in the first block
+    /// (implicit signalling).
+    pub fn audio_specific_config(&self) -> [u8; 2] {
+        let object_type = self.profile + 1;
+        [
+            (object_type << 3) | (self.samp_rate_idx >> 1),
+            (self.samp_rate_idx << 7) | (self.channel_config << 3),
+        ]
+    }
+}
+
+/// ADTS frame header read on its own, without setting up a decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdtsHeader {
+    pub format: AdtsFormat,
+    /// bytes in the frame, header included
+    pub frame_bytes: usize,
+    /// state of the bit reservoir in 32-bit words per channel, 0x7ff for variable bitrate
+    pub buffer_fullness: u16,
+    /// raw data blocks in the frame, 1 to 4
+    pub raw_blocks: usize,
+}
+
+impl AdtsHeader {
+    /// Parses the header at the start of `header`, which needs [`ADTS_HEADER_BYTES`].
+    ///
+    /// Rejects a layer other than 0, the profile MPEG-2 reserves, sample rate indices the
+    /// decoder has no tables for, and frames too short for their own header.
+    pub fn parse(header: &[u8]) -> Option<Self> {
+        let header = header.get(..ADTS_HEADER_BYTES)?;
+        if header[0] != 0xff || header[1] & 0xf6 != 0xf0 {
+            return None;
+        }
+        let format = AdtsFormat {
+            mpeg2: header[1] & 0x08 != 0,
+            profile: header[2] >> 6,
+            samp_rate_idx: (header[2] >> 2) & 0x0f,
+            channel_config: ((header[2] & 0x01) << 2) | (header[3] >> 6),
+            protected: header[1] & 0x01 == 0,
+        };
+        if format.samp_rate_idx as usize >= NUM_SAMPLE_RATES || format.mpeg2 && format.profile == 3
+        {
+            return None;
+        }
+        let fields = AdtsHeader {
+            format,
+            frame_bytes: ((header[3] as usize & 0x03) << 11)
+                | ((header[4] as usize) << 3)
+                | (header[5] as usize >> 5),
+            buffer_fullness: ((header[5] as u16 & 0x1f) << 6) | (header[6] as u16 >> 2),
+            raw_blocks: (header[6] as usize & 0x03) + 1,
+        };
+        (fields.frame_bytes > fields.header_bytes()).then_some(fields)
+    }
+
+    /// Whether the buffer fullness signals variable bitrate.
+    pub fn is_vbr(&self) -> bool {
+        self.buffer_fullness == 0x7ff
+    }
+
+    /// Bytes in front of the first raw data block: the header, and if the frame is protected
+    /// the positions of the other blocks and the CRC.
+    pub fn header_bytes(&self) -> usize {
+        if self.format.protected {
+            ADTS_HEADER_BYTES + 2 * (self.raw_blocks - 1) + 2
+        } else {
+            ADTS_HEADER_BYTES
+        }
+    }
+
+    /// Checks the CRC of a protected frame with several raw data blocks, which covers the
+    /// header and the block positions; `frame` has to hold [`AdtsHeader::header_bytes`].
+    ///
+    /// The CRC of a frame with a single block also covers parts of the block, which only the
+    /// decoder can find ([`AACDecoder::decode_adts_frame_verified`]). For those frames, and
+    /// unprotected ones, this is always true.
+    pub fn header_crc_matches(&self, frame: &[u8]) -> bool {
+        if !self.format.protected || self.raw_blocks == 1 {
+            return true;
+        }
+        let crc_at = self.header_bytes() - 2;
+        let computed = crc16(0xffff, &frame[..crc_at]);
+        frame[crc_at..crc_at + 2] == computed.to_be_bytes()
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    AACFindSyncWord
+ *
+ * Description: locate the next byte-aligned ADTS sync word in the stream
+ *
+ * Inputs:      buffer to search for sync word
+ *
+ * Outputs:     none
+ *
+ * Return:      the buffer from the first sync word on
+ *              None if there is no sync word in the buffer
+ *
+ * Notes:       besides the 12 sync bits the layer has to be 0, so MPEG audio frames are not
+ *                taken for ADTS; whether a header follows is for AdtsHeader::parse() to say
+ **********************************************************************************************************************/
+pub fn adts_find_sync_word(data: &[u8]) -> Option<&[u8]> {
+    let mut tail = data;
+
+    while tail.len() >= 2 {
+        if tail[0] == 0xff && (tail[1] & 0xf6) == 0xf0 {
+            return Some(tail);
+        }
+        tail = &tail[1..];
+    }
+    None
+}
+
+/* the running CRC of a protected block and where the decoder is in the current element */
+pub(super) struct CrcState {
+    pub crc: u16,
+    /* start of the protected bits of the current element, the bits after its id */
+    element_start: usize,
+}
+
+impl CrcState {
+    pub(super) fn new(crc: u16) -> Self {
+        CrcState {
+            crc,
+            element_start: 0,
+        }
+    }
+
+    /* feeds the bits from start to end into the CRC, at most max_bits of them and zero padded
+     * to max_bits if there are fewer (max_bits == 0: all of them)
+     */
+    fn region(&mut self, block: &[u8], start: usize, end: usize, max_bits: usize) {
+        let n_bits = end.saturating_sub(start);
+        if max_bits == 0 {
+            self.crc = crc16_bits(self.crc, block, start, n_bits);
+            return;
+        }
+        self.crc = crc16_bits(self.crc, block, start, n_bits.min(max_bits));
+        self.crc = crc16_bits(self.crc, &[], 0, max_bits.saturating_sub(n_bits));
+    }
+
+    /***********************************************************************************************************************
+     * Function:    CrcElement
+     *
+     * Description: feed the protected bits of a syntactic element into the CRC of an ADTS frame
+     *
+     * Inputs:      raw data block
+     *              element id
+     *              bit position of the element id
+     *              bit position after the element header (after the element id, instance tag and
+     *                for a CPE the common ICS info)
+     *
+     * Notes:       data stream and program config elements are protected whole; SCE, LFE and CPE
+     *                elements are handled channel by channel in CrcChannel, fill elements (with
+     *                SBR data, which has its own CRC) are not protected; the element ids
+     *                themselves never are
+     **********************************************************************************************************************/
+    pub(super) fn element(&mut self, block: &[u8], id: ElementId, id_start: usize, end: usize) {
+        self.element_start = id_start + super::NUM_SYN_ID_BITS as usize;
+        if matches!(id, ElementId::Dse | ElementId::Pce) {
+            self.region(block, self.element_start, end, 0);
+        }
+    }
+
+    /***********************************************************************************************************************
+     * Function:    CrcChannel
+     *
+     * Description: feed the protected bits of a channel stream into the CRC of an ADTS frame
+     *
+     * Inputs:      raw data block
+     *              channel of the element
+     *              bit positions of the start and end of the individual channel stream
+     *
+     * Notes:       the first channel is protected together with the element header, the first
+     *                192 bits from the instance tag on; the second channel of a CPE the first
+     *                128 bits of its own; shorter ones are padded with zeros
+     **********************************************************************************************************************/
+    pub(super) fn channel(&mut self, block: &[u8], ch: usize, start: usize, end: usize) {
+        if ch == 0 {
+            self.region(block, self.element_start, end, CRC_BITS_ELEMENT);
+        } else {
+            self.region(block, start, end, CRC_BITS_SECOND_CHANNEL);
+        }
+    }
+}
+
+/// Outcome of checking bytes that may not all be there yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Check<T> {
+    Valid(T),
+    Invalid,
+    /// the input ends before it can tell
+    NeedMore,
+}
+
+/// The stream sync is locked to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lock {
+    format: AdtsFormat,
+    /// encoders write the same number of blocks to every frame, and either always signal
+    /// variable bitrate in the buffer fullness or never
+    raw_blocks: usize,
+    vbr: bool,
+}
+
+impl Lock {
+    /// Reads the header at the start of `data`, which has to continue the locked stream.
+    fn header(&self, data: &[u8]) -> Check<AdtsHeader> {
+        if data.len() < ADTS_HEADER_BYTES {
+            return Check::NeedMore;
+        }
+        match AdtsHeader::parse(data) {
+            Some(header)
+                if header.format == self.format
+                    && header.raw_blocks == self.raw_blocks
+                    && header.is_vbr() == self.vbr =>
+            {
+                if data.len() < header.header_bytes() {
+                    Check::NeedMore
+                } else if header.header_crc_matches(data) {
+                    Check::Valid(header)
+                } else {
+                    Check::Invalid
+                }
+            }
+            _ => Check::Invalid,
+        }
+    }
+
+    /// Checks the frame at the start of `data` and where it says the next one starts: a
+    /// consistent header or a tag do.
+    fn check_frame(&self, data: &[u8]) -> Check<usize> {
+        let header = match self.header(data) {
+            Check::Valid(header) => header,
+            Check::Invalid => return Check::Invalid,
+            Check::NeedMore => return Check::NeedMore,
+        };
+        let next = match data.get(header.frame_bytes..) {
+            Some(next) => next,
+            None => return Check::NeedMore,
+        };
+        if metadata::tag_bytes(next).is_some() {
+            return Check::Valid(header.frame_bytes);
+        }
+        match self.header(next) {
+            Check::Valid(_) => Check::Valid(header.frame_bytes),
+            Check::Invalid => Check::Invalid,
+            Check::NeedMore => Check::NeedMore,
+        }
+    }
+}
+
+/// What [`AdtsSync::decode_frame`] did with the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// not locked, input that cannot start a locked run of frames was consumed
+    Searching,
+    /// lock declared, the block decoded is the first of the run that confirmed it
+    Locked,
+    /// block decoded in lock
+    InSync,
+    /// the frame at the start of the input does not continue the locked stream: nothing was
+    /// consumed, the next call searches from there
+    Lost,
+}
+
+/// Result of [`AdtsSync::decode_frame`].
+#[derive(Debug, Clone, Copy)]
+pub struct SyncedFrame {
+    pub event: SyncEvent,
+    pub frame: DecodedFrame,
+    /// why the block was output as silence, `None` if it was decoded
+    pub error: Option<AacError>,
+}
+
+/// Sync state of an ADTS stream read through [`AdtsSync::decode_frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdtsSync {
+    lock_frames: usize,
+    verify_crc: bool,
+    lock: Option<Lock>,
+    /// rest of a tag longer than the input it was found in
+    tag_bytes_left: usize,
+    /// no input follows what the caller has, see [`AdtsSync::finish`]
+    at_eof: bool,
+}
+
+impl Default for AdtsSync {
+    fn default() -> Self {
+        AdtsSync::new(DEFAULT_LOCK_FRAMES)
+    }
+}
+
+impl AdtsSync {
+    /// Sync that declares lock after `lock_frames` consecutive consistent frames (at least 1,
+    /// which only checks the frame and the header after it). CRCs are not checked.
+    pub fn new(lock_frames: usize) -> Self {
+        AdtsSync {
+            lock_frames: lock_frames.max(1),
+            verify_crc: false,
+            lock: None,
+            tag_bytes_left: 0,
+            at_eof: false,
+        }
+    }
+
+    /// Checks the CRC of protected frames while decoding them, like
+    /// [`AACDecoder::decode_adts_frame_verified`]; a block failing it is output as silence.
+    pub fn set_verify_crc(&mut self, verify: bool) {
+        self.verify_crc = verify;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// Format of the stream sync is locked to.
+    pub fn format(&self) -> Option<AdtsFormat> {
+        self.lock.map(|lock| lock.format)
+    }
+
+    /// Drops the lock, e.g. after the caller skipped input; flush the decoder as well, the
+    /// next frame starts anew.
+    pub fn reset(&mut self) {
+        self.lock = None;
+        self.tag_bytes_left = 0;
+    }
+
+    /// Marks the end of the stream: the input of the next calls is all that is left of it. A
+    /// run of frames cut short by the end of the input then locks if its first frame is whole,
+    /// so clips shorter than the run, and the last frames after a lost sync, are decoded too.
+    pub fn finish(&mut self) {
+        self.at_eof = true;
+    }
+
+    /* consumes as much of a tag of `tag_bytes` as `input` holds, the rest in later calls */
+    fn skip_tag(
+        &mut self,
+        dec: &AACDecoder,
+        input: &[u8],
+        tag_bytes: usize,
+        event: SyncEvent,
+    ) -> SyncedFrame {
+        let consumed = tag_bytes.min(input.len());
+        self.tag_bytes_left = tag_bytes - consumed;
+        no_output(dec, event, consumed)
+    }
+
+    /// Checks whether `lock_frames` consistent frames start at the start of `data`.
+    fn check_run(&self, data: &[u8]) -> Check<Lock> {
+        if data.len() < ADTS_HEADER_BYTES {
+            return if self.at_eof {
+                Check::Invalid
+            } else {
+                Check::NeedMore
+            };
+        }
+        let Some(first) = AdtsHeader::parse(data) else {
+            return Check::Invalid;
+        };
+        let lock = Lock {
+            format: first.format,
+            raw_blocks: first.raw_blocks,
+            vbr: first.is_vbr(),
+        };
+
+        let mut offset = 0;
+        for _ in 0..self.lock_frames {
+            let rest = &data[offset..];
+            if offset > 0 && metadata::tag_bytes(rest).is_some() {
+                /* the stream ends before the run does */
+                break;
+            }
+            match lock.check_frame(rest) {
+                Check::Valid(frame_bytes) => offset += frame_bytes,
+                Check::Invalid => return Check::Invalid,
+                Check::NeedMore if self.at_eof => {
+                    /* the stream ends in this frame or right after it */
+                    let whole = match lock.header(rest) {
+                        Check::Valid(header) => header.frame_bytes <= rest.len(),
+                        _ => false,
+                    };
+                    if offset == 0 && !whole {
+                        return Check::Invalid;
+                    }
+                    break;
+                }
+                Check::NeedMore => return Check::NeedMore,
+            }
+        }
+        Check::Valid(lock)
+    }
+
+    /// Decodes the next raw data block of `input` like [`AACDecoder::decode_adts_frame`], but
+    /// only in lock.
+    ///
+    /// Until lock is declared input is consumed without output ([`SyncEvent::Searching`]); a
+    /// run of frames cut short by the end of `input` is an `InDataUnderflow`, to be retried
+    /// with more input, or locks after [`AdtsSync::finish`]. In lock each frame is checked
+    /// before its first block is decoded, one that does not fit drops the lock
+    /// ([`SyncEvent::Lost`]). A block that fails to decode
+    /// is consumed with the rest of its frame and output as silence, see
+    /// [`SyncedFrame::error`]. Tags between frames are consumed, one longer than `input` over
+    /// several calls.
+    pub fn decode_frame(
+        &mut self,
+        dec: &mut AACDecoder,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<SyncedFrame, AacError> {
+        if self.tag_bytes_left > 0 {
+            let event = if self.lock.is_some() {
+                SyncEvent::InSync
+            } else {
+                SyncEvent::Searching
+            };
+            return Ok(self.skip_tag(dec, input, self.tag_bytes_left, event));
+        }
+        let mid_frame = dec.m_AACDecInfo.adtsBlocksLeft > 0;
+        if let Some(lock) = self.lock {
+            if !mid_frame {
+                if let Some(tag_bytes) = metadata::tag_bytes(input) {
+                    return Ok(self.skip_tag(dec, input, tag_bytes, SyncEvent::InSync));
+                }
+                if lock.check_frame(input) == Check::Invalid {
+                    self.lock = None;
+                    return Ok(no_output(dec, SyncEvent::Lost, 0));
+                }
+            }
+            return self.decode_locked(dec, input, out, SyncEvent::InSync);
+        }
+        if mid_frame {
+            /* blocks of a frame decoded before a reset */
+            dec.m_AACDecInfo.adtsBlocksLeft = 0;
+        }
+
+        for start in 0..input.len() {
+            let rest = &input[start..];
+            if let Some(tag_bytes) = metadata::tag_bytes(rest) {
+                if start > 0 {
+                    return Ok(no_output(dec, SyncEvent::Searching, start));
+                }
+                return Ok(self.skip_tag(dec, input, tag_bytes, SyncEvent::Searching));
+            }
+            match self.check_run(rest) {
+                Check::Invalid => continue,
+                Check::NeedMore if start == 0 => {
+                    return Err(AacError::InDataUnderflow { offset: 0 });
+                }
+                Check::Valid(lock) if start == 0 => {
+                    self.lock = Some(lock);
+                    return self.decode_locked(dec, input, out, SyncEvent::Locked);
+                }
+                /* consume up to the candidate, it is checked again at the start of the input */
+                Check::Valid(_) | Check::NeedMore => {
+                    return Ok(no_output(dec, SyncEvent::Searching, start));
+                }
+            }
+        }
+        /* keep the last bytes, they may start a header, unless no more input follows */
+        let searched = if self.at_eof {
+            input.len()
+        } else {
+            input.len().saturating_sub(ADTS_HEADER_BYTES - 1)
+        };
+        Ok(no_output(dec, SyncEvent::Searching, searched))
+    }
+
+    /// Decodes a block in lock. One that fails is consumed with the rest of its frame and
+    /// replaced by silence.
+    fn decode_locked(
+        &self,
+        dec: &mut AACDecoder,
+        input: &[u8],
+        out: &mut [i16],
+        event: SyncEvent,
+    ) -> Result<SyncedFrame, AacError> {
+        let mut consumed = 0;
+        let (samples_written, error) =
+            match dec.decode_adts(input, out, self.verify_crc, &mut consumed) {
+                Ok(samples) => (samples, None),
+                Err(e) if consumed == 0 => return Err(e),
+                Err(e) => {
+                    let n_samps = dec.output_samps().min(out.len());
+                    out[..n_samps].fill(0);
+                    (n_samps, Some(e))
+                }
+            };
+        Ok(SyncedFrame {
+            event,
+            frame: DecodedFrame {
+                bytes_consumed: consumed,
+                samples_written,
+                info: dec.m_AACFrameInfo,
+            },
+            error,
+        })
+    }
+}
+
+fn no_output(dec: &AACDecoder, event: SyncEvent, bytes_consumed: usize) -> SyncedFrame {
+    SyncedFrame {
+        event,
+        frame: DecodedFrame {
+            bytes_consumed,
+            samples_written: 0,
+            info: dec.m_AACFrameInfo,
+        },
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::boxed::Box;
+
+    /* MPEG-4 AAC-LC, 44.1 kHz, stereo, unprotected, 300 byte frames of one block */
+    const HEADER: [u8; 7] = [0xff, 0xf1, 0x50, 0x80, 0x25, 0x9f, 0xfc];
+
+    /// `frames` frames with `HEADER`, their payload left zero.
+    fn stream(frames: usize) -> [u8; 4 * 300] {
+        let mut data = [0u8; 4 * 300];
+        for frame in 0..frames {
+            data[frame * 300..frame * 300 + 7].copy_from_slice(&HEADER);
+        }
+        data
+    }
+
+    #[test]
+    fn parses_header_fields() {
+        let header = AdtsHeader::parse(&HEADER).unwrap();
+        assert_eq!(
+            header.format,
+            AdtsFormat {
+                mpeg2: false,
+                profile: 1,
+                samp_rate_idx: 4,
+                channel_config: 2,
+                protected: false,
+            }
+        );
+        assert_eq!(header.format.samp_rate(), 44100);
+        assert_eq!((header.frame_bytes, header.header_bytes()), (300, 7));
+        assert_eq!((header.buffer_fullness, header.raw_blocks), (0x7ff, 1));
+        // the AudioSpecificConfig of an MP4 file with this stream
+        assert_eq!(header.format.audio_specific_config(), [0x12, 0x10]);
+
+        // MPEG-2, protected, 4 blocks: block positions and CRC follow the header
+        let header = AdtsHeader::parse(&[0xff, 0xf8, 0x58, 0x40, 0x25, 0x9f, 0xff]).unwrap();
+        assert!(header.format.mpeg2 && header.format.protected);
+        assert_eq!((header.format.channel_config, header.raw_blocks), (1, 4));
+        assert_eq!(header.header_bytes(), 7 + 3 * 2 + 2);
+        assert_eq!(header.format.audio_specific_config(), [0x13, 0x08]);
+
+        // layer 1, reserved sample rate, MPEG-2 profile 3, frames shorter than their header
+        for bad in [
+            [0xff, 0xf3, 0x50, 0x80, 0x25, 0x9f, 0xfc],
+            [0xff, 0xf1, 0x74, 0x80, 0x25, 0x9f, 0xfc],
+            [0xff, 0xf9, 0xd0, 0x80, 0x25, 0x9f, 0xfc],
+            [0xff, 0xf1, 0x50, 0x80, 0x00, 0xff, 0xfc],
+            [0xff, 0xf0, 0x50, 0x80, 0x01, 0x1f, 0xfc],
+        ] {
+            assert_eq!(AdtsHeader::parse(&bad), None, "{bad:x?}");
+        }
+    }
+
+    #[test]
+    fn finds_sync_words_of_adts_only() {
+        // an MPEG-1 Layer III header has the 12 sync bits too
+        let data = [0x00, 0xff, 0xfb, 0x90, 0xff, 0xf1, 0x50];
+        let tail = adts_find_sync_word(&data).unwrap();
+        assert_eq!(tail.len(), 3);
+        assert_eq!(adts_find_sync_word(&data[..5]), None);
+    }
+
+    #[test]
+    fn checks_the_header_crc_of_frames_with_several_blocks() {
+        // protected, 2 blocks: header, the position of the second block and the CRC
+        let mut frame = [0u8; 64];
+        frame[..7].copy_from_slice(&[0xff, 0xf0, 0x50, 0x80, 0x08, 0x1f, 0xfd]);
+        frame[7..9].copy_from_slice(&[0x00, 0x1c]);
+        let crc = crc16(0xffff, &frame[..9]);
+        frame[9..11].copy_from_slice(&crc.to_be_bytes());
+        let header = AdtsHeader::parse(&frame).unwrap();
+        assert_eq!((header.frame_bytes, header.header_bytes()), (64, 11));
+        assert!(header.header_crc_matches(&frame));
+        frame[8] ^= 0x01;
+        assert!(!header.header_crc_matches(&frame));
+    }
+
+    #[test]
+    fn locks_on_a_run_of_consistent_frames() {
+        let sync = AdtsSync::new(3);
+        let data = stream(4);
+        assert!(matches!(sync.check_run(&data), Check::Valid(_)));
+        // the third header is missing
+        assert_eq!(sync.check_run(&stream(2)), Check::Invalid);
+        // cut short: more input needed to tell
+        assert_eq!(sync.check_run(&data[..650]), Check::NeedMore);
+        // the second frame changes the sample rate, then the channel configuration
+        let mut changed = data;
+        changed[302] ^= 0x04;
+        assert_eq!(sync.check_run(&changed), Check::Invalid);
+        let mut changed = data;
+        changed[303] &= 0x3f;
+        assert_eq!(sync.check_run(&changed), Check::Invalid);
+        // the second frame is longer than the first says
+        let mut longer = data;
+        longer[4] += 1;
+        assert_eq!(sync.check_run(&longer), Check::Invalid);
+        // a stream ending in a tag
+        let mut tagged = stream(2);
+        tagged[600..603].copy_from_slice(b"TAG");
+        assert!(matches!(sync.check_run(&tagged), Check::Valid(_)));
+    }
+
+    #[test]
+    fn locks_on_a_short_run_at_the_end_of_the_stream() {
+        let mut sync = AdtsSync::new(3);
+        sync.finish();
+        let data = stream(3);
+        // one or two whole frames, or two and the start of the third
+        for end in [300, 600, 650] {
+            assert!(
+                matches!(sync.check_run(&data[..end]), Check::Valid(_)),
+                "{end}"
+            );
+        }
+        // not even one whole frame, or the third header is missing
+        assert_eq!(sync.check_run(&data[..250]), Check::Invalid);
+        assert_eq!(sync.check_run(&data[..5]), Check::Invalid);
+        assert_eq!(sync.check_run(&stream(2)), Check::Invalid);
+    }
+
+    #[test]
+    fn searching_skips_false_syncs() {
+        let mut dec = Box::<AACDecoder>::default();
+        let mut sync = AdtsSync::default();
+        let mut out = [0i16; 0];
+        // a lone header and noise in front of the stream
+        let mut data = [0u8; 1300];
+        data[..7].copy_from_slice(&HEADER);
+        data[40..41].copy_from_slice(&[0xff]);
+        data[100..].copy_from_slice(&stream(4));
+        let frame = sync.decode_frame(&mut dec, &data, &mut out).unwrap();
+        assert_eq!(
+            (frame.event, frame.frame.bytes_consumed),
+            (SyncEvent::Searching, 100)
+        );
+        assert!(!sync.is_locked());
+    }
+}
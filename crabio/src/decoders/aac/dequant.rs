@@ -0,0 +1,228 @@
+//! Inverse quantisation of the spectral coefficients (14496-3, 4.6.1 and 4.6.2).
+//!
+//! The pow(x, 4/3) tables and polynomial are shared with the Layer III dequantizer; the
+//! scalefactor step is 2^(1/4) here as well, but counted upwards from 2^0.
+
+use super::{
+    AACDecoder, FBITS_OUT_DQ_OFF, NSAMPS_LONG, NSAMPS_SHORT, SF_OFFSET, WindowSequence, sfb_table,
+};
+use crate::mp3::dequant::{POLY43HI, POLY43LO, POW2EXP, POW2FRAC, POW43};
+use crate::mp3_decoder::{SQRTHALF, mulshift_32};
+
+/* pow(2, i/4.0) for i = [0,1,2,3], format = Q30 */
+const POW14: [i32; 4] = [0x40000000, 0x4c1bf829, 0x5a82799a, 0x6ba27e65];
+
+/* pow(2, i/4.0) * pow(j, 4.0/3.0) for i = [0,1,2,3],  j = [0,1,2,...,15]
+ * format = Q28 for j = [0-3], Q25 for j = [4-15]
+ */
+const POW43_14: [[i32; 16]; 4] = [
+    [
+        0x00000000, 0x10000000, 0x285145f3, 0x453a5cdb, /* Q28 */
+        0x0cb2ff53, 0x111989d6, 0x15ce31c8, 0x1ac7f203, /* Q25 */
+        0x20000000, 0x257106b9, 0x2b16b4a3, 0x30ed74b4, /* Q25 */
+        0x36f23fa5, 0x3d227bd3, 0x437be656, 0x49fc823c, /* Q25 */
+    ],
+    [
+        0x00000000, 0x1306fe0a, 0x2ff221af, 0x52538f52, 0x0f1a1bf4, 0x1455ccc2, 0x19ee62a8,
+        0x1fd92396, 0x260dfc14, 0x2c8694d8, 0x333dcb29, 0x3a2f5c7a, 0x4157aed5, 0x48b3aaa3,
+        0x50409f76, 0x57fc3010,
+    ],
+    [
+        0x00000000, 0x16a09e66, 0x39047c0f, 0x61e734aa, 0x11f59ac4, 0x182ec633, 0x1ed66a45,
+        0x25dfc55a, 0x2d413ccd, 0x34f3462d, 0x3cefc603, 0x4531ab69, 0x4db4adf8, 0x56752054,
+        0x5f6fcfcd, 0x68a1eca1,
+    ],
+    [
+        0x00000000, 0x1ae89f99, 0x43ce3e4b, 0x746d57b2, 0x155b8109, 0x1cc21cdc, 0x24ac1839,
+        0x2d0a479e, 0x35d13f33, 0x3ef80748, 0x48775c93, 0x524938cd, 0x5c68841d, 0x66d0df0a,
+        0x717e7bfe, 0x7c6e0305,
+    ],
+];
+
+/* y << shift, saturating to 0x7fffffff (y >= 0), or y >> -shift */
+#[inline(always)]
+fn scale_int(y: i32, shift: i32) -> i32 {
+    if shift <= 0 {
+        let shift = (-shift).min(31);
+        if y > (0x7fffffff >> shift) {
+            0x7fffffff /* clip (rare) */
+        } else {
+            y << shift
+        }
+    } else {
+        y >> shift.min(31)
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DequantBlock
+ *
+ * Description: dequantize one block of transform coefficients (in-place)
+ *
+ * Inputs:      quantized transform coefficients, range = [0, 8191]
+ *              scalefactor for this block of data, range = [0, 256]
+ *
+ * Outputs:     dequantized transform coefficients in Q(FBITS_OUT_DQ_OFF)
+ *
+ * Return:      guard bit mask (OR of abs value of all dequantized coefs)
+ *
+ * Notes:       applies dequant formula y = pow(x, 4.0/3.0) * pow(2, (scale - 100)/4.0)
+ *                * pow(2, FBITS_OUT_DQ_OFF)
+ *              clips outputs to Q(FBITS_OUT_DQ_OFF)
+ *              output has no minimum number of guard bits
+ **********************************************************************************************************************/
+pub fn dequant_block(buf: &mut [i32], scale: i32) -> i32 {
+    let scale = scale - SF_OFFSET; /* new range = [-100, 156] */
+
+    /* with two's complement numbers, scalei/scalef factorization works for pos and neg values of scale:
+     *  [+4...+7] >> 2 = +1, [ 0...+3] >> 2 = 0, [-4...-1] >> 2 = -1, [-8...-5] >> 2 = -2 ...
+     *  (-1 & 0x3) = 3, (-2 & 0x3) = 2, (-3 & 0x3) = 1, (0 & 0x3) = 0
+     *
+     * Example: 2^(-5/4) = 2^(-1) * 2^(-1/4) = 2^-2 * 2^(3/4)
+     */
+    let tab16 = &POW43_14[(scale & 0x3) as usize];
+    let scalef = POW14[(scale & 0x3) as usize];
+    let scalei = (scale >> 2) + FBITS_OUT_DQ_OFF;
+
+    /* cache first 4 values:
+     * tab16[j] = Q28 for j = [0,3]
+     * tab4[x] = x^(4.0/3.0) * 2^(0.25*scale), Q(FBITS_OUT_DQ_OFF)
+     */
+    let shift = 28 - scalei;
+    let tab4 = if shift > 31 {
+        [0; 4]
+    } else {
+        [
+            0,
+            scale_int(tab16[1], shift),
+            scale_int(tab16[2], shift),
+            scale_int(tab16[3], shift),
+        ]
+    };
+
+    let mut gb_mask = 0;
+    for coef in buf.iter_mut() {
+        let i_samp = *coef;
+        let x = i_samp.unsigned_abs();
+
+        let y = if x < 4 {
+            tab4[x as usize]
+        } else if x < 16 {
+            /* result: y = Q25 (tab16 = Q25) */
+            scale_int(tab16[x as usize], 25 - scalei)
+        } else if x < 64 {
+            /* result: y = Q21 (pow43tab[j] = Q23, scalef = Q30) */
+            scale_int(mulshift_32(POW43[x as usize - 16], scalef), 21 - scalei)
+        } else {
+            /* normalize to [0x40000000, 0x7fffffff]
+             * input x = [64, 8191] = [64, 2^13-1]
+             * ranges:
+             *  shift = 7:   64 -  127
+             *  shift = 6:  128 -  255
+             *  shift = 5:  256 -  511
+             *  shift = 4:  512 - 1023
+             *  shift = 3: 1024 - 2047
+             *  shift = 2: 2048 - 4095
+             *  shift = 1: 4096 - 8191
+             */
+            let mut x = x.min(0x3fff) << 17;
+            let mut shift = 0;
+            if x < 0x08000000 {
+                x <<= 4;
+                shift += 4;
+            }
+            if x < 0x20000000 {
+                x <<= 2;
+                shift += 2;
+            }
+            if x < 0x40000000 {
+                x <<= 1;
+                shift += 1;
+            }
+
+            let coef = if x < SQRTHALF { &POLY43LO } else { &POLY43HI };
+
+            /* polynomial */
+            let x = x as i32;
+            let mut y = coef[0] as i32;
+            y = mulshift_32(y, x) + coef[1] as i32;
+            y = mulshift_32(y, x) + coef[2] as i32;
+            y = mulshift_32(y, x) + coef[3] as i32;
+            y = mulshift_32(y, x) + coef[4] as i32;
+            y = mulshift_32(y, POW2FRAC[shift]) << 3;
+
+            /* fractional scale
+             * result: y = Q21 (pow43tab[j] = Q23, scalef = Q30)
+             */
+            y = mulshift_32(y, scalef); /* now y is Q24 */
+            scale_int(y, 24 - scalei - POW2EXP[shift])
+        };
+
+        /* sign and store (gbMask used to count GB's) */
+        gb_mask |= y;
+        *coef = if i_samp < 0 { -y } else { y };
+    }
+
+    gb_mask
+}
+
+/***********************************************************************************************************************
+ * Function:    AACDequantize
+ *
+ * Description: dequantize all transform coefficients for one channel
+ *
+ * Inputs:      index of current channel
+ *
+ * Outputs:     dequantized coefficients
+ *              flags indicating if intensity and/or PNS is active
+ *              minimum guard bit count for dequantized coefficients
+ *
+ * Return:      none
+ *
+ * Notes:       short blocks were already deinterleaved by the Huffman decoder
+ **********************************************************************************************************************/
+pub fn aac_dequantize(dec: &mut AACDecoder, ch: usize) {
+    let psi = &mut dec.m_PSInfoBase;
+    let ics = psi.icsInfo[psi.ics_index(ch)];
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let n_samps = if ics.winSequence == WindowSequence::EightShort {
+        NSAMPS_SHORT
+    } else {
+        NSAMPS_LONG
+    };
+    let max_sfb = ics.maxSFB as usize;
+
+    psi.intensityUsed[ch] = 0;
+    psi.pnsUsed[ch] = 0;
+    let mut gb_mask = 0;
+    let mut win = 0;
+    for gp in 0..ics.numWinGroup as usize {
+        let band_info = gp * max_sfb..(gp + 1) * max_sfb;
+        for _ in 0..ics.winGroupLen[gp] {
+            let coef = &mut psi.coef[ch][win * n_samps..(win + 1) * n_samps];
+            for ((sfb, &cb), &sf) in psi.sfbCodeBook[ch][band_info.clone()]
+                .iter()
+                .enumerate()
+                .zip(&psi.scaleFactors[ch][band_info.clone()])
+            {
+                /* dequantize one scalefactor band (not necessary if codebook is intensity or PNS)
+                 * for zero codebook, still run dequantizer in case non-zero pulse data was added
+                 */
+                match cb {
+                    0..=11 => {
+                        let band = sfb_tab[sfb] as usize..sfb_tab[sfb + 1] as usize;
+                        gb_mask |= dequant_block(&mut coef[band], sf as i32);
+                    }
+                    13 => psi.pnsUsed[ch] = 1,
+                    14 | 15 => psi.intensityUsed[ch] = 1, /* should only happen if ch == 1 */
+                    _ => {}
+                }
+            }
+            win += 1;
+        }
+    }
+    dec.m_AACDecInfo.pnsUsed |= psi.pnsUsed[ch]; /* set flag if PNS used for any channel */
+
+    /* calculate number of guard bits in dequantized data */
+    psi.gbCurrent[ch] = gb_mask.leading_zeros() as i32 - 1;
+}
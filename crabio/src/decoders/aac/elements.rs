@@ -0,0 +1,205 @@
+//! Syntactic elements of a raw data block (14496-3, table 4.3).
+//!
+//! Only the element headers are read here; the individual channel streams of SCE, CPE and LFE
+//! elements are decoded by [`super::ics::decode_noiseless_data`]. Data stream and program
+//! config elements are skipped; the payload of a fill element is kept for [`super::sbr`].
+
+use super::ics::decode_ics_info;
+use super::{AACDecoder, ElementId, NUM_INST_TAG_BITS, NUM_SYN_ID_BITS};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+/***********************************************************************************************************************
+ * Function:    DecodeChannelPairElement
+ *
+ * Description: decode one CPE
+ *
+ * Inputs:      bitstream positioned after the element id
+ *
+ * Outputs:     updated element instance tag
+ *              updated commonWin
+ *              updated ICS info, if commonWin == 1
+ *              updated mid-side stereo info, if commonWin == 1
+ *
+ * Return:      Ok, or Err if the ICS info is invalid
+ *
+ * Notes:       doesn't decode individual channel stream (part of DecodeNoiselessData)
+ *              msMaskPresent = 0 means no M/S coding
+ *                            = 1 means msMaskBits contains 1 bit per SFB to toggle M/S coding
+ *                            = 2 means all SFB's are M/S coded (so msMaskBits is not needed)
+ *              the mask bit of band sfb in group gp is bit (gp * maxSFB + sfb), lsb first
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_channel_pair_element(
+    dec: &mut AACDecoder,
+    bsi: &mut BitStreamInfo,
+) -> Result<(), ()> {
+    dec.m_AACDecInfo.currInstTag = bsi.get_bits(NUM_INST_TAG_BITS) as i32;
+
+    let psi = &mut dec.m_PSInfoBase;
+    psi.commonWin = bsi.get_bits(1) as i32;
+    if psi.commonWin != 0 {
+        decode_ics_info(&mut psi.icsInfo[0], psi.sampRateIdx, bsi)?;
+        psi.msMaskPresent = bsi.get_bits(2) as i32;
+        if psi.msMaskPresent == 1 {
+            let ics = &psi.icsInfo[0];
+            let n_bits = ics.numWinGroup as usize * ics.maxSFB as usize;
+            psi.msMaskBits.fill(0);
+            for i in 0..n_bits {
+                psi.msMaskBits[i >> 3] |= (bsi.get_bits(1) as u8) << (i & 0x07);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeDataStreamElement
+ *
+ * Description: skip one DSE
+ *
+ * Inputs:      bitstream positioned after the element id
+ *
+ * Outputs:     updated element instance tag
+ *
+ * Return:      none
+ *
+ * Notes:       the data bytes are ancillary data, which nothing downstream uses
+ **********************************************************************************************************************/
+pub fn decode_data_stream_element(dec: &mut AACDecoder, bsi: &mut BitStreamInfo) {
+    dec.m_AACDecInfo.currInstTag = bsi.get_bits(NUM_INST_TAG_BITS) as i32;
+    let byte_align = bsi.get_bits(1);
+    let mut data_count = bsi.get_bits(8);
+    if data_count == 255 {
+        data_count += bsi.get_bits(8);
+    }
+
+    if byte_align != 0 {
+        bsi.byte_align_bitstream();
+    }
+    for _ in 0..data_count {
+        bsi.get_bits(8);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeProgramConfigElement
+ *
+ * Description: skip one PCE
+ *
+ * Inputs:      bitstream positioned after the element id
+ *
+ * Outputs:     none
+ *
+ * Return:      none
+ *
+ * Notes:       the channel configuration comes from the stream parameters, so the element
+ *                is only parsed to find its end (14496-3, table 4.2)
+ **********************************************************************************************************************/
+pub fn decode_program_config_element(bsi: &mut BitStreamInfo) {
+    bsi.get_bits(4 + 2 + 4); /* element_instance_tag, object_type, sampling_frequency_index */
+    let num_front = bsi.get_bits(4);
+    let num_side = bsi.get_bits(4);
+    let num_back = bsi.get_bits(4);
+    let num_lfe = bsi.get_bits(2);
+    let num_assoc_data = bsi.get_bits(3);
+    let num_valid_cc = bsi.get_bits(4);
+
+    /* mono, stereo and matrix mixdown: present flag, then element number or index */
+    if bsi.get_bits(1) != 0 {
+        bsi.get_bits(4);
+    }
+    if bsi.get_bits(1) != 0 {
+        bsi.get_bits(4);
+    }
+    if bsi.get_bits(1) != 0 {
+        bsi.get_bits(2 + 1);
+    }
+
+    /* is_cpe / cc_ind_sw flag and tag select, tag select only for LFE and data elements */
+    for _ in 0..num_front + num_side + num_back + num_valid_cc {
+        bsi.get_bits(1 + 4);
+    }
+    for _ in 0..num_lfe + num_assoc_data {
+        bsi.get_bits(4);
+    }
+
+    bsi.byte_align_bitstream();
+    /* eat comment bytes and throw away */
+    let comment_bytes = bsi.get_bits(8);
+    for _ in 0..comment_bytes {
+        bsi.get_bits(8);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeFillElement
+ *
+ * Description: decode one fill element
+ *
+ * Inputs:      bitstream positioned after the element id
+ *                (14496-3, table 4.11)
+ *
+ * Outputs:     updated element instance tag
+ *              number of fill bytes (fillCount) and the bytes themselves (fillBuf)
+ *              extension type (fillExtType), 0 if there are no fill bytes
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn decode_fill_element(dec: &mut AACDecoder, bsi: &mut BitStreamInfo) {
+    let mut fill_count = bsi.get_bits(4) as usize;
+    if fill_count == 15 {
+        fill_count += bsi.get_bits(8) as usize;
+        fill_count -= 1;
+    }
+
+    let info = &mut dec.m_AACDecInfo;
+    info.fillCount = fill_count;
+    for b in &mut dec.m_PSInfoBase.fillBuf[..fill_count] {
+        *b = bsi.get_bits(8) as u8;
+    }
+    info.fillExtType = if fill_count > 0 {
+        dec.m_PSInfoBase.fillBuf[0] >> 4
+    } else {
+        0
+    };
+
+    info.currInstTag = -1; /* fill elements don't have instance tag */
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeNextElement
+ *
+ * Description: decode next syntactic element in AAC frame
+ *
+ * Inputs:      bitstream positioned at the next element
+ *
+ * Outputs:     type of element decoded (currBlockID)
+ *              type of element decoded last time (prevBlockID)
+ *              updated decoder state, depending on which element was decoded
+ *
+ * Return:      Ok, or Err for an invalid or unsupported (coupling channel) element
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_next_element(dec: &mut AACDecoder, bsi: &mut BitStreamInfo) -> Result<(), ()> {
+    let info = &mut dec.m_AACDecInfo;
+    info.prevBlockID = info.currBlockID;
+    info.currBlockID = ElementId::from_bits(bsi.get_bits(NUM_SYN_ID_BITS));
+
+    /* set defaults (could be overwritten by DecodeXXXElement(), depending on currBlockID) */
+    dec.m_PSInfoBase.commonWin = 0;
+
+    match dec.m_AACDecInfo.currBlockID {
+        ElementId::Sce | ElementId::Lfe => {
+            dec.m_AACDecInfo.currInstTag = bsi.get_bits(NUM_INST_TAG_BITS) as i32;
+        }
+        ElementId::Cpe => decode_channel_pair_element(dec, bsi)?,
+        ElementId::Dse => decode_data_stream_element(dec, bsi),
+        ElementId::Pce => decode_program_config_element(bsi),
+        ElementId::Fil => decode_fill_element(dec, bsi),
+        ElementId::End => {}
+        ElementId::Cce | ElementId::Invalid => return Err(()),
+    }
+
+    Ok(())
+}
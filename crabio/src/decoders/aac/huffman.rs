@@ -0,0 +1,613 @@
+//! Huffman decoding of AAC scalefactors and spectral data (14496-3, 4.6.3 and appendix
+//! 4.A.1).
+//!
+//! All codebooks are canonical, so a table is just the number of codewords of each length
+//! and the symbols in codeword order. Spectral symbols are packed: the quantised values,
+//! sign-extended bitfields, and above them the number of sign bits that follow the codeword.
+
+use super::{AACDecoder, NSAMPS_LONG, NSAMPS_SHORT, PulseInfo, sfb_table};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+pub const HUFFTAB_SPEC_OFFSET: usize = 1;
+pub const MAX_HUFF_BITS: usize = 20;
+
+/* largest escape_sequence is 8 ones, a zero and a 12-bit word, so values are < 8192 */
+const ESC_MAX_BITS: u32 = 12;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HuffInfo {
+    pub max_bits: u32,              /* number of bits in longest codeword */
+    pub count: [u8; MAX_HUFF_BITS], /* count[i] = number of codes with length i+1 bits */
+    pub offset: usize,              /* offset into symbol table */
+}
+
+/* table 0 not used */
+pub const HUFF_TAB_SPEC_INFO: [HuffInfo; 11] = [
+    HuffInfo {
+        max_bits: 11,
+        count: [
+            1, 0, 0, 0, 8, 0, 24, 0, 24, 8, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 0,
+    },
+    HuffInfo {
+        max_bits: 9,
+        count: [
+            0, 0, 1, 1, 7, 24, 15, 19, 14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 81,
+    },
+    HuffInfo {
+        max_bits: 16,
+        count: [1, 0, 0, 4, 2, 6, 3, 5, 15, 15, 8, 9, 3, 3, 5, 2, 0, 0, 0, 0],
+        offset: 162,
+    },
+    HuffInfo {
+        max_bits: 12,
+        count: [
+            0, 0, 0, 10, 6, 0, 9, 21, 8, 14, 11, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 243,
+    },
+    HuffInfo {
+        max_bits: 13,
+        count: [
+            1, 0, 0, 4, 4, 0, 4, 12, 12, 12, 18, 10, 4, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 324,
+    },
+    HuffInfo {
+        max_bits: 11,
+        count: [
+            0, 0, 0, 9, 0, 16, 13, 8, 23, 8, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 405,
+    },
+    HuffInfo {
+        max_bits: 12,
+        count: [
+            1, 0, 2, 1, 0, 4, 5, 10, 14, 15, 8, 4, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 486,
+    },
+    HuffInfo {
+        max_bits: 10,
+        count: [
+            0, 0, 1, 5, 7, 10, 14, 15, 8, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 550,
+    },
+    HuffInfo {
+        max_bits: 15,
+        count: [
+            1, 0, 2, 1, 0, 4, 3, 8, 11, 20, 31, 38, 32, 14, 4, 0, 0, 0, 0, 0,
+        ],
+        offset: 614,
+    },
+    HuffInfo {
+        max_bits: 12,
+        count: [
+            0, 0, 0, 3, 8, 14, 17, 25, 31, 41, 22, 8, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 783,
+    },
+    HuffInfo {
+        max_bits: 12,
+        count: [
+            0, 0, 0, 2, 6, 7, 16, 59, 55, 95, 43, 6, 0, 0, 0, 0, 0, 0, 0, 0,
+        ],
+        offset: 952,
+    },
+];
+
+pub const HUFF_TAB_SPEC: [i16; 1241] = [
+    /* spectrum table 1 [81] (signed) */
+    0x0000, 0x0200, 0x0e00, 0x0007, 0x0040, 0x0001, 0x0038, 0x0008, 0x01c0, 0x03c0, 0x0e40, 0x0039,
+    0x0078, 0x01c8, 0x000f, 0x0240, 0x003f, 0x0fc0, 0x01f8, 0x0238, 0x0047, 0x0e08, 0x0009, 0x0208,
+    0x01c1, 0x0048, 0x0041, 0x0e38, 0x0201, 0x0e07, 0x0207, 0x0e01, 0x01c7, 0x0278, 0x0e78, 0x03c8,
+    0x004f, 0x0079, 0x01c9, 0x01cf, 0x03f8, 0x0239, 0x007f, 0x0e48, 0x0e0f, 0x0fc8, 0x01f9, 0x03c1,
+    0x03c7, 0x0e47, 0x0ff8, 0x01ff, 0x0049, 0x020f, 0x0241, 0x0e41, 0x0248, 0x0fc1, 0x0e3f, 0x0247,
+    0x023f, 0x0e39, 0x0fc7, 0x0e09, 0x0209, 0x03cf, 0x0e79, 0x0e4f, 0x03f9, 0x0249, 0x0fc9, 0x027f,
+    0x0fcf, 0x0fff, 0x0279, 0x03c9, 0x0e49, 0x0e7f, 0x0ff9, 0x03ff, 0x024f,
+    /* spectrum table 2 [81] (signed) */
+    0x0000, 0x0200, 0x0e00, 0x0001, 0x0038, 0x0007, 0x01c0, 0x0008, 0x0040, 0x01c8, 0x0e40, 0x0078,
+    0x000f, 0x0047, 0x0039, 0x0e07, 0x03c0, 0x0238, 0x0fc0, 0x003f, 0x0208, 0x0201, 0x01c1, 0x0e08,
+    0x0041, 0x01f8, 0x0e01, 0x01c7, 0x0e38, 0x0240, 0x0048, 0x0009, 0x0207, 0x0079, 0x0239, 0x0e78,
+    0x01cf, 0x03c8, 0x0247, 0x0209, 0x0e48, 0x01f9, 0x0248, 0x0e0f, 0x0ff8, 0x0e39, 0x03f8, 0x0278,
+    0x03c1, 0x0e47, 0x0fc8, 0x0e09, 0x0fc1, 0x0fc7, 0x01ff, 0x020f, 0x023f, 0x007f, 0x0049, 0x0e41,
+    0x0e3f, 0x004f, 0x03c7, 0x01c9, 0x0241, 0x03cf, 0x0e79, 0x03f9, 0x0fff, 0x0e4f, 0x0e49, 0x0249,
+    0x0fcf, 0x03c9, 0x0e7f, 0x0fc9, 0x027f, 0x03ff, 0x0ff9, 0x0279, 0x024f,
+    /* spectrum table 3 [81] (unsigned) */
+    0x0000, 0x1200, 0x1001, 0x1040, 0x1008, 0x2240, 0x2009, 0x2048, 0x2041, 0x2208, 0x3049, 0x2201,
+    0x3248, 0x4249, 0x3209, 0x3241, 0x1400, 0x1002, 0x200a, 0x2440, 0x3288, 0x2011, 0x3051, 0x2280,
+    0x304a, 0x3448, 0x1010, 0x2088, 0x2050, 0x1080, 0x2042, 0x2408, 0x4289, 0x3089, 0x3250, 0x4251,
+    0x3281, 0x2210, 0x3211, 0x2081, 0x4449, 0x424a, 0x3441, 0x320a, 0x2012, 0x3052, 0x3488, 0x3290,
+    0x2202, 0x2401, 0x3091, 0x2480, 0x4291, 0x3242, 0x3409, 0x4252, 0x4489, 0x2090, 0x308a, 0x3212,
+    0x3481, 0x3450, 0x3490, 0x3092, 0x4491, 0x4451, 0x428a, 0x4292, 0x2082, 0x2410, 0x3282, 0x3411,
+    0x444a, 0x3442, 0x4492, 0x448a, 0x4452, 0x340a, 0x2402, 0x3482, 0x3412,
+    /* spectrum table 4 [81] (unsigned) */
+    0x4249, 0x3049, 0x3241, 0x3248, 0x3209, 0x1200, 0x2240, 0x0000, 0x2009, 0x2208, 0x2201, 0x2048,
+    0x1001, 0x2041, 0x1008, 0x1040, 0x4449, 0x4251, 0x4289, 0x424a, 0x3448, 0x3441, 0x3288, 0x3409,
+    0x3051, 0x304a, 0x3250, 0x3089, 0x320a, 0x3281, 0x3242, 0x3211, 0x2440, 0x2408, 0x2280, 0x2401,
+    0x2042, 0x2088, 0x200a, 0x2050, 0x2081, 0x2202, 0x2011, 0x2210, 0x1400, 0x1002, 0x1080, 0x1010,
+    0x4291, 0x4489, 0x4451, 0x4252, 0x428a, 0x444a, 0x3290, 0x3488, 0x3450, 0x3091, 0x3052, 0x3481,
+    0x308a, 0x3411, 0x3212, 0x4491, 0x3282, 0x340a, 0x3442, 0x4292, 0x4452, 0x448a, 0x2090, 0x2480,
+    0x2012, 0x2410, 0x2082, 0x2402, 0x4492, 0x3092, 0x3490, 0x3482, 0x3412,
+    /* spectrum table 5 [81] (signed) */
+    0x0000, 0x03e0, 0x0020, 0x0001, 0x001f, 0x003f, 0x03e1, 0x03ff, 0x0021, 0x03c0, 0x0002, 0x0040,
+    0x001e, 0x03df, 0x0041, 0x03fe, 0x0022, 0x03c1, 0x005f, 0x03e2, 0x003e, 0x03a0, 0x0060, 0x001d,
+    0x0003, 0x03bf, 0x0023, 0x0061, 0x03fd, 0x03a1, 0x007f, 0x003d, 0x03e3, 0x03c2, 0x0042, 0x03de,
+    0x005e, 0x03be, 0x007e, 0x03c3, 0x005d, 0x0062, 0x0043, 0x03a2, 0x03dd, 0x001c, 0x0380, 0x0081,
+    0x0080, 0x039f, 0x0004, 0x009f, 0x03fc, 0x0024, 0x03e4, 0x0381, 0x003c, 0x007d, 0x03bd, 0x03a3,
+    0x03c4, 0x039e, 0x0082, 0x005c, 0x0044, 0x0063, 0x0382, 0x03dc, 0x009e, 0x007c, 0x039d, 0x0383,
+    0x0064, 0x03a4, 0x0083, 0x009d, 0x03bc, 0x009c, 0x0384, 0x0084, 0x039c,
+    /* spectrum table 6 [81] (signed) */
+    0x0000, 0x0020, 0x001f, 0x0001, 0x03e0, 0x0021, 0x03e1, 0x003f, 0x03ff, 0x005f, 0x0041, 0x03c1,
+    0x03df, 0x03c0, 0x03e2, 0x0040, 0x003e, 0x0022, 0x001e, 0x03fe, 0x0002, 0x005e, 0x03c2, 0x03de,
+    0x0042, 0x03a1, 0x0061, 0x007f, 0x03e3, 0x03bf, 0x0023, 0x003d, 0x03fd, 0x0060, 0x03a0, 0x001d,
+    0x0003, 0x0062, 0x03be, 0x03c3, 0x0043, 0x007e, 0x005d, 0x03dd, 0x03a2, 0x0063, 0x007d, 0x03bd,
+    0x03a3, 0x003c, 0x03fc, 0x0081, 0x0381, 0x039f, 0x0024, 0x009f, 0x03e4, 0x001c, 0x0382, 0x039e,
+    0x0044, 0x03dc, 0x0380, 0x0082, 0x009e, 0x03c4, 0x0080, 0x005c, 0x0004, 0x03bc, 0x03a4, 0x007c,
+    0x009d, 0x0064, 0x0083, 0x0383, 0x039d, 0x0084, 0x0384, 0x039c, 0x009c,
+    /* spectrum table 7 [64] (unsigned) */
+    0x0000, 0x0420, 0x0401, 0x0821, 0x0841, 0x0822, 0x0440, 0x0402, 0x0861, 0x0823, 0x0842, 0x0460,
+    0x0403, 0x0843, 0x0862, 0x0824, 0x0881, 0x0825, 0x08a1, 0x0863, 0x0844, 0x0404, 0x0480, 0x0882,
+    0x0845, 0x08a2, 0x0405, 0x08c1, 0x04a0, 0x0826, 0x0883, 0x0865, 0x0864, 0x08a3, 0x0846, 0x08c2,
+    0x0827, 0x0866, 0x0406, 0x04c0, 0x0884, 0x08e1, 0x0885, 0x08e2, 0x08a4, 0x08c3, 0x0847, 0x08e3,
+    0x08c4, 0x08a5, 0x0886, 0x0867, 0x04e0, 0x0407, 0x08c5, 0x08a6, 0x08e4, 0x0887, 0x08a7, 0x08e5,
+    0x08e6, 0x08c6, 0x08c7, 0x08e7, /* spectrum table 8 [64] (unsigned) */
+    0x0821, 0x0841, 0x0420, 0x0822, 0x0401, 0x0842, 0x0000, 0x0440, 0x0402, 0x0861, 0x0823, 0x0862,
+    0x0843, 0x0863, 0x0881, 0x0824, 0x0882, 0x0844, 0x0460, 0x0403, 0x0883, 0x0864, 0x08a2, 0x08a1,
+    0x0845, 0x0825, 0x08a3, 0x0865, 0x0884, 0x08a4, 0x0404, 0x0885, 0x0480, 0x0846, 0x08c2, 0x08c1,
+    0x0826, 0x0866, 0x08c3, 0x08a5, 0x04a0, 0x08c4, 0x0405, 0x0886, 0x08e1, 0x08e2, 0x0847, 0x08c5,
+    0x08e3, 0x0827, 0x08a6, 0x0867, 0x08c6, 0x08e4, 0x04c0, 0x0887, 0x0406, 0x08e5, 0x08e6, 0x08c7,
+    0x08a7, 0x04e0, 0x0407, 0x08e7, /* spectrum table 9 [169] (unsigned) */
+    0x0000, 0x0420, 0x0401, 0x0821, 0x0841, 0x0822, 0x0440, 0x0402, 0x0861, 0x0842, 0x0823, 0x0460,
+    0x0403, 0x0843, 0x0862, 0x0824, 0x0881, 0x0844, 0x0825, 0x0882, 0x0863, 0x0404, 0x0480, 0x08a1,
+    0x0845, 0x0826, 0x0864, 0x08a2, 0x08c1, 0x0883, 0x0405, 0x0846, 0x04a0, 0x0827, 0x0865, 0x0828,
+    0x0901, 0x0884, 0x08a3, 0x08c2, 0x08e1, 0x0406, 0x0902, 0x0848, 0x0866, 0x0847, 0x0885, 0x0921,
+    0x0829, 0x08e2, 0x04c0, 0x08a4, 0x08c3, 0x0903, 0x0407, 0x0922, 0x0868, 0x0886, 0x0867, 0x0408,
+    0x0941, 0x08c4, 0x0849, 0x08a5, 0x0500, 0x04e0, 0x08e3, 0x0942, 0x0923, 0x0904, 0x082a, 0x08e4,
+    0x08c5, 0x08a6, 0x0888, 0x0887, 0x0869, 0x0961, 0x08a8, 0x0520, 0x0905, 0x0943, 0x084a, 0x0409,
+    0x0962, 0x0924, 0x08c6, 0x0981, 0x0889, 0x0906, 0x082b, 0x0925, 0x0944, 0x08a7, 0x08e5, 0x084b,
+    0x082c, 0x0982, 0x0963, 0x086a, 0x08a9, 0x08c7, 0x0907, 0x0964, 0x040a, 0x08e6, 0x0983, 0x0540,
+    0x0945, 0x088a, 0x08c8, 0x084c, 0x0926, 0x0927, 0x088b, 0x0560, 0x08c9, 0x086b, 0x08aa, 0x0908,
+    0x08e8, 0x0985, 0x086c, 0x0965, 0x08e7, 0x0984, 0x0966, 0x0946, 0x088c, 0x08e9, 0x08ab, 0x040b,
+    0x0986, 0x08ca, 0x0580, 0x0947, 0x08ac, 0x08ea, 0x0928, 0x040c, 0x0967, 0x0909, 0x0929, 0x0948,
+    0x08eb, 0x0987, 0x08cb, 0x090b, 0x0968, 0x08ec, 0x08cc, 0x090a, 0x0949, 0x090c, 0x092a, 0x092b,
+    0x092c, 0x094b, 0x0989, 0x094a, 0x0969, 0x0988, 0x096a, 0x098a, 0x098b, 0x094c, 0x096b, 0x096c,
+    0x098c, /* spectrum table 10 [169] (unsigned) */
+    0x0821, 0x0822, 0x0841, 0x0842, 0x0420, 0x0401, 0x0823, 0x0862, 0x0861, 0x0843, 0x0863, 0x0440,
+    0x0402, 0x0844, 0x0882, 0x0824, 0x0881, 0x0000, 0x0883, 0x0864, 0x0460, 0x0403, 0x0884, 0x0845,
+    0x08a2, 0x0825, 0x08a1, 0x08a3, 0x0865, 0x08a4, 0x0885, 0x08c2, 0x0846, 0x08c3, 0x0480, 0x08c1,
+    0x0404, 0x0826, 0x0866, 0x08a5, 0x08c4, 0x0886, 0x08c5, 0x08e2, 0x0867, 0x0847, 0x08a6, 0x0902,
+    0x08e3, 0x04a0, 0x08e1, 0x0405, 0x0901, 0x0827, 0x0903, 0x08e4, 0x0887, 0x0848, 0x08c6, 0x08e5,
+    0x0828, 0x0868, 0x0904, 0x0888, 0x08a7, 0x0905, 0x08a8, 0x08e6, 0x08c7, 0x0922, 0x04c0, 0x08c8,
+    0x0923, 0x0869, 0x0921, 0x0849, 0x0406, 0x0906, 0x0924, 0x0889, 0x0942, 0x0829, 0x08e7, 0x0907,
+    0x0925, 0x08e8, 0x0943, 0x08a9, 0x0944, 0x084a, 0x0941, 0x086a, 0x0926, 0x08c9, 0x0500, 0x088a,
+    0x04e0, 0x0962, 0x08e9, 0x0963, 0x0946, 0x082a, 0x0961, 0x0927, 0x0407, 0x0908, 0x0945, 0x086b,
+    0x08aa, 0x0909, 0x0965, 0x0408, 0x0964, 0x084b, 0x08ea, 0x08ca, 0x0947, 0x088b, 0x082b, 0x0982,
+    0x0928, 0x0983, 0x0966, 0x08ab, 0x0984, 0x0967, 0x0985, 0x086c, 0x08cb, 0x0520, 0x0948, 0x0540,
+    0x0981, 0x0409, 0x088c, 0x0929, 0x0986, 0x084c, 0x090a, 0x092a, 0x082c, 0x0968, 0x0987, 0x08eb,
+    0x08ac, 0x08cc, 0x0949, 0x090b, 0x0988, 0x040a, 0x08ec, 0x0560, 0x094a, 0x0969, 0x096a, 0x040b,
+    0x096b, 0x092b, 0x094b, 0x0580, 0x090c, 0x0989, 0x094c, 0x092c, 0x096c, 0x098b, 0x040c, 0x098a,
+    0x098c, /* spectrum table 11 [289] (unsigned) */
+    0x0000, 0x2041, 0x2410, 0x1040, 0x1001, 0x2081, 0x2042, 0x2082, 0x2043, 0x20c1, 0x20c2, 0x1080,
+    0x2083, 0x1002, 0x20c3, 0x2101, 0x2044, 0x2102, 0x2084, 0x2103, 0x20c4, 0x10c0, 0x1003, 0x2141,
+    0x2142, 0x2085, 0x2104, 0x2045, 0x2143, 0x20c5, 0x2144, 0x2105, 0x2182, 0x2086, 0x2181, 0x2183,
+    0x20c6, 0x2046, 0x2110, 0x20d0, 0x2405, 0x2403, 0x2404, 0x2184, 0x2406, 0x1100, 0x2106, 0x1004,
+    0x2090, 0x2145, 0x2150, 0x2407, 0x2402, 0x2408, 0x2087, 0x21c2, 0x20c7, 0x2185, 0x2146, 0x2190,
+    0x240a, 0x21c3, 0x21c1, 0x2409, 0x21d0, 0x2050, 0x2047, 0x2107, 0x240b, 0x21c4, 0x240c, 0x2210,
+    0x2401, 0x2186, 0x2250, 0x2088, 0x2147, 0x2290, 0x240d, 0x2203, 0x2202, 0x20c8, 0x1140, 0x240e,
+    0x22d0, 0x21c5, 0x2108, 0x2187, 0x21c6, 0x1005, 0x2204, 0x240f, 0x2310, 0x2048, 0x2201, 0x2390,
+    0x2148, 0x2350, 0x20c9, 0x2205, 0x21c7, 0x2089, 0x2206, 0x2242, 0x2243, 0x23d0, 0x2109, 0x2188,
+    0x1180, 0x2244, 0x2149, 0x2207, 0x21c8, 0x2049, 0x2283, 0x1006, 0x2282, 0x2241, 0x2245, 0x210a,
+    0x208a, 0x2246, 0x20ca, 0x2189, 0x2284, 0x2208, 0x2285, 0x2247, 0x22c3, 0x204a, 0x11c0, 0x2286,
+    0x21c9, 0x20cb, 0x214a, 0x2281, 0x210b, 0x22c2, 0x2342, 0x218a, 0x2343, 0x208b, 0x1400, 0x214b,
+    0x22c5, 0x22c4, 0x2248, 0x21ca, 0x2209, 0x1010, 0x210d, 0x1007, 0x20cd, 0x22c6, 0x2341, 0x2344,
+    0x2303, 0x208d, 0x2345, 0x220a, 0x218b, 0x2288, 0x2287, 0x2382, 0x2304, 0x204b, 0x210c, 0x22c1,
+    0x20cc, 0x204d, 0x2302, 0x21cb, 0x20ce, 0x214c, 0x214d, 0x2384, 0x210e, 0x22c7, 0x2383, 0x2305,
+    0x2346, 0x2306, 0x1200, 0x22c8, 0x208c, 0x2249, 0x2385, 0x218d, 0x228a, 0x23c2, 0x220b, 0x224a,
+    0x2386, 0x2289, 0x214e, 0x22c9, 0x2381, 0x208e, 0x218c, 0x204c, 0x2348, 0x1008, 0x2347, 0x21cc,
+    0x2307, 0x21cd, 0x23c3, 0x2301, 0x218e, 0x208f, 0x23c5, 0x23c4, 0x204e, 0x224b, 0x210f, 0x2387,
+    0x220d, 0x2349, 0x220c, 0x214f, 0x20cf, 0x228b, 0x22ca, 0x2308, 0x23c6, 0x23c7, 0x220e, 0x23c1,
+    0x21ce, 0x1240, 0x1009, 0x224d, 0x224c, 0x2309, 0x2388, 0x228d, 0x2389, 0x230a, 0x218f, 0x21cf,
+    0x224e, 0x23c8, 0x22cb, 0x22ce, 0x204f, 0x228c, 0x228e, 0x234b, 0x234a, 0x22cd, 0x22cc, 0x220f,
+    0x238b, 0x234c, 0x230d, 0x23c9, 0x238a, 0x1280, 0x230b, 0x224f, 0x100a, 0x230c, 0x12c0, 0x230e,
+    0x228f, 0x234d, 0x100d, 0x238c, 0x23ca, 0x23cb, 0x22cf, 0x238d, 0x1340, 0x100b, 0x234e, 0x23cc,
+    0x23cd, 0x230f, 0x1380, 0x238e, 0x234f, 0x1300, 0x238f, 0x100e, 0x100c, 0x23ce, 0x13c0, 0x100f,
+    0x23cf,
+];
+
+pub const HUFF_TAB_SCALE_FACT_INFO: HuffInfo = HuffInfo {
+    max_bits: 19,
+    count: [1, 0, 1, 3, 2, 4, 3, 5, 4, 6, 6, 6, 5, 8, 4, 7, 3, 7, 46, 0],
+    offset: 0,
+};
+
+/* note - includes offset of -60 (4.6.2.3 in spec) */
+pub const HUFF_TAB_SCALE_FACT: [i16; 121] = [
+    /* scale factor table [121] */
+    0, -1, 1, -2, 2, -3, 3, -4, 4, -5, 5, 6, -6, 7, -7, 8, -8, 9, -9, 10, -10, -11, 11, 12, -12, 13,
+    -13, 14, -14, 16, 15, 17, 18, -15, -17, -16, 19, -18, -19, 20, -20, 21, -21, 22, -22, 23, -23,
+    -25, 25, -27, -24, -26, 24, -28, 27, 29, -30, -29, 26, -31, -34, -33, -32, -36, 28, -35, -38,
+    -37, 30, -39, -41, -57, -59, -58, -60, 38, 39, 40, 41, 42, 57, 37, 31, 32, 33, 34, 35, 36, 44,
+    51, 52, 53, 54, 55, 56, 50, 45, 46, 47, 48, 49, 58, -54, -52, -51, -50, -55, 43, 60, 59, -56,
+    -53, -45, -44, -42, -40, -43, -49, -48, -46, -47,
+];
+
+/***********************************************************************************************************************
+ * Function:    DecodeHuffmanScalar
+ *
+ * Description: decode one Huffman symbol from bitstream
+ *
+ * Inputs:      Huffman table and info struct
+ *              left-aligned bit buffer with >= huffTabInfo.max_bits bits
+ *
+ * Outputs:     none
+ *
+ * Return:      number of bits in symbol, decoded symbol
+ *
+ * Notes:       assumes canonical Huffman codes:
+ *                first CW always 0, we have "count" CW's of length "nBits" bits
+ *                starting CW for codes of length nBits+1 =
+ *                  (startCW[nBits] + count[nBits]) << 1
+ *                if there are no codes at nBits, then we just keep << 1 each time
+ *                  (since count[nBits] = 0)
+ *              all tables are complete, the fallback for a bit buffer matching no codeword
+ *                is never taken
+ **********************************************************************************************************************/
+pub fn decode_huffman_scalar(huff_tab: &[i16], info: &HuffInfo, bit_buf: u32) -> (u32, i32) {
+    let mut map = info.offset;
+    let mut start = 0u32;
+    let mut count = 0u32;
+    for (len, &n) in info.count.iter().enumerate() {
+        start = (start + count) << 1;
+        map += count as usize;
+        count = n as u32;
+        let t = (bit_buf >> (31 - len)).wrapping_sub(start);
+        if t < count {
+            return (len as u32 + 1, huff_tab[map + t as usize] as i32);
+        }
+    }
+    (info.max_bits, 0)
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeOneScaleFactor
+ *
+ * Description: decode one scalefactor using scalefactor Huffman codebook
+ *
+ * Inputs:      bitstream positioned at the codeword
+ *
+ * Outputs:     none
+ *
+ * Return:      one decoded scalefactor, including index_offset of -60
+ **********************************************************************************************************************/
+pub fn decode_one_scale_factor(bsi: &mut BitStreamInfo) -> i32 {
+    let info = &HUFF_TAB_SCALE_FACT_INFO;
+    let bit_buf = bsi.get_bits_no_advance(info.max_bits) << (32 - info.max_bits);
+    let (n_bits, val) = decode_huffman_scalar(&HUFF_TAB_SCALE_FACT, info, bit_buf);
+    bsi.advance_bitstream(n_bits);
+    val
+}
+
+/* apply the next sign bit in bit_buf to a non-zero value (1 = negative) */
+#[inline(always)]
+fn apply_sign(v: &mut i32, bit_buf: &mut u32) {
+    if *v != 0 {
+        let s = (*bit_buf as i32) >> 31;
+        *v = (*v ^ s) - s;
+        *bit_buf <<= 1;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackQuads
+ *
+ * Description: decode a section of 4-way vector Huffman coded coefficients
+ *
+ * Inputs       index of Huffman codebook
+ *              buffer for the coefficients
+ *
+ * Outputs:     coef.len() coefficients
+ *
+ * Return:      none
+ *
+ * Notes:       assumes coef.len() is always a multiple of 4 because all scalefactor bands
+ *                are a multiple of 4 coefficients long
+ **********************************************************************************************************************/
+pub fn unpack_quads(bsi: &mut BitStreamInfo, cb: usize, coef: &mut [i32]) {
+    let info = &HUFF_TAB_SPEC_INFO[cb - HUFFTAB_SPEC_OFFSET];
+    let max_bits = info.max_bits + 4;
+    for quad in coef.chunks_exact_mut(4) {
+        /* decode quad */
+        let mut bit_buf = bsi.get_bits_no_advance(max_bits) << (32 - max_bits);
+        let (n_code_bits, val) = decode_huffman_scalar(&HUFF_TAB_SPEC, info, bit_buf);
+
+        let mut w = (val << 20) >> 29; /* bits 11-9, sign-extend */
+        let mut x = (val << 23) >> 29; /* bits  8-6, sign-extend */
+        let mut y = (val << 26) >> 29; /* bits  5-3, sign-extend */
+        let mut z = (val << 29) >> 29; /* bits  2-0, sign-extend */
+
+        bit_buf <<= n_code_bits;
+        let n_sign_bits = ((val as u32) << 17) >> 29; /* bits 14-12, unsigned */
+
+        bsi.advance_bitstream(n_code_bits + n_sign_bits);
+        if n_sign_bits != 0 {
+            apply_sign(&mut w, &mut bit_buf);
+            apply_sign(&mut x, &mut bit_buf);
+            apply_sign(&mut y, &mut bit_buf);
+            apply_sign(&mut z, &mut bit_buf);
+        }
+        quad.copy_from_slice(&[w, x, y, z]);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackPairsNoEsc
+ *
+ * Description: decode a section of 2-way vector Huffman coded coefficients,
+ *                using non-esc tables (5 through 10)
+ *
+ * Inputs       index of Huffman codebook (must not be the escape codebook)
+ *              buffer for the coefficients
+ *
+ * Outputs:     coef.len() coefficients
+ *
+ * Return:      none
+ *
+ * Notes:       assumes coef.len() is always a multiple of 2 because all scalefactor bands
+ *                are a multiple of 4 coefficients long
+ **********************************************************************************************************************/
+pub fn unpack_pairs_no_esc(bsi: &mut BitStreamInfo, cb: usize, coef: &mut [i32]) {
+    let info = &HUFF_TAB_SPEC_INFO[cb - HUFFTAB_SPEC_OFFSET];
+    let max_bits = info.max_bits + 2;
+    for pair in coef.chunks_exact_mut(2) {
+        /* decode pair */
+        let mut bit_buf = bsi.get_bits_no_advance(max_bits) << (32 - max_bits);
+        let (n_code_bits, val) = decode_huffman_scalar(&HUFF_TAB_SPEC, info, bit_buf);
+
+        let mut y = (val << 22) >> 27; /* bits  9-5, sign-extend */
+        let mut z = (val << 27) >> 27; /* bits  4-0, sign-extend */
+
+        bit_buf <<= n_code_bits;
+        let n_sign_bits = ((val as u32) << 20) >> 30; /* bits 11-10, unsigned */
+        bsi.advance_bitstream(n_code_bits + n_sign_bits);
+        if n_sign_bits != 0 {
+            apply_sign(&mut y, &mut bit_buf);
+            apply_sign(&mut z, &mut bit_buf);
+        }
+        pair.copy_from_slice(&[y, z]);
+    }
+}
+
+/* escape_sequence (14496-3, table 4.57): N ones, a zero, then an (N + 4)-bit word */
+fn decode_escape(bsi: &mut BitStreamInfo) -> Result<i32, ()> {
+    let mut n = 4;
+    while bsi.get_bits(1) == 1 {
+        n += 1;
+        if n > ESC_MAX_BITS {
+            return Err(());
+        }
+    }
+    Ok((1 << n) + bsi.get_bits(n) as i32)
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackPairsEsc
+ *
+ * Description: decode a section of 2-way vector Huffman coded coefficients,
+ *                using esc table (11)
+ *
+ * Inputs       index of Huffman codebook (must be the escape codebook)
+ *              buffer for the coefficients
+ *
+ * Outputs:     coef.len() coefficients
+ *
+ * Return:      Ok, or Err for an escape sequence longer than the spec allows
+ *
+ * Notes:       assumes coef.len() is always a multiple of 2 because all scalefactor bands
+ *                are a multiple of 4 coefficients long
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn unpack_pairs_esc(bsi: &mut BitStreamInfo, cb: usize, coef: &mut [i32]) -> Result<(), ()> {
+    let info = &HUFF_TAB_SPEC_INFO[cb - HUFFTAB_SPEC_OFFSET];
+    let max_bits = info.max_bits + 2;
+    for pair in coef.chunks_exact_mut(2) {
+        /* decode pair with escape value */
+        let mut bit_buf = bsi.get_bits_no_advance(max_bits) << (32 - max_bits);
+        let (n_code_bits, val) = decode_huffman_scalar(&HUFF_TAB_SPEC, info, bit_buf);
+
+        let mut y = (val << 20) >> 26; /* bits 11-6, sign-extend */
+        let mut z = (val << 26) >> 26; /* bits  5-0, sign-extend */
+
+        bit_buf <<= n_code_bits;
+        let n_sign_bits = ((val as u32) << 18) >> 30; /* bits 13-12, unsigned */
+        bsi.advance_bitstream(n_code_bits + n_sign_bits);
+
+        if y == 16 {
+            y = decode_escape(bsi)?;
+        }
+        if z == 16 {
+            z = decode_escape(bsi)?;
+        }
+
+        if n_sign_bits != 0 {
+            apply_sign(&mut y, &mut bit_buf);
+            apply_sign(&mut z, &mut bit_buf);
+        }
+        pair.copy_from_slice(&[y, z]);
+    }
+    Ok(())
+}
+
+/* decode one scalefactor band coded with codebook cb, zeros unless cb is in [1, 11] */
+fn unpack_band(bsi: &mut BitStreamInfo, cb: u8, coef: &mut [i32]) -> Result<(), ()> {
+    match cb as usize {
+        1..=4 => unpack_quads(bsi, cb as usize, coef),
+        5..=10 => unpack_pairs_no_esc(bsi, cb as usize, coef),
+        11 => unpack_pairs_esc(bsi, cb as usize, coef)?,
+        _ => coef.fill(0),
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSpectrumLong
+ *
+ * Description: decode transform coefficients for frame with one long block
+ *
+ * Inputs:      bitstream positioned at the spectral data
+ *              index of current channel
+ *
+ * Outputs:     decoded, quantized coefficients for this channel
+ *
+ * Return:      Ok, or Err for a bad escape sequence or pulses past the end of the spectrum
+ *
+ * Notes:       adds in pulse data if present
+ *              fills coefficient buffer with zeros in any region not coded with
+ *                codebook in range [1, 11] (including sfb's above sfbMax)
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_spectrum_long(
+    dec: &mut AACDecoder,
+    bsi: &mut BitStreamInfo,
+    ch: usize,
+) -> Result<(), ()> {
+    let psi = &mut dec.m_PSInfoBase;
+    let ics = psi.icsInfo[psi.ics_index(ch)];
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let coef = &mut psi.coef[ch];
+
+    /* decode long block */
+    let max_sfb = ics.maxSFB as usize;
+    for (sfb, &cb) in psi.sfbCodeBook[ch][..max_sfb].iter().enumerate() {
+        unpack_band(
+            bsi,
+            cb,
+            &mut coef[sfb_tab[sfb] as usize..sfb_tab[sfb + 1] as usize],
+        )?;
+    }
+
+    /* fill with zeros above maxSFB */
+    coef[sfb_tab[max_sfb] as usize..].fill(0);
+
+    /* add pulse data, if present */
+    let pi: &PulseInfo = &dec.m_PulseInfo[ch];
+    if pi.pulseDataPresent != 0 {
+        let mut offset = sfb_tab[pi.startSFB as usize] as usize;
+        for i in 0..pi.numPulse as usize {
+            offset += pi.offset[i] as usize;
+            if offset >= NSAMPS_LONG {
+                return Err(());
+            }
+            if coef[offset] > 0 {
+                coef[offset] += pi.amp[i] as i32;
+            } else {
+                coef[offset] -= pi.amp[i] as i32;
+            }
+        }
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSpectrumShort
+ *
+ * Description: decode transform coefficients for frame with eight short blocks
+ *
+ * Inputs:      bitstream positioned at the spectral data
+ *              index of current channel
+ *
+ * Outputs:     decoded, quantized coefficients for this channel
+ *
+ * Return:      Ok, or Err for a bad escape sequence
+ *
+ * Notes:       fills coefficient buffer with zeros in any region not coded with
+ *                codebook in range [1, 11] (including sfb's above sfbMax)
+ *              deinterleaves window groups into 8 windows
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_spectrum_short(
+    dec: &mut AACDecoder,
+    bsi: &mut BitStreamInfo,
+    ch: usize,
+) -> Result<(), ()> {
+    let psi = &mut dec.m_PSInfoBase;
+    let ics = psi.icsInfo[psi.ics_index(ch)];
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let coef = &mut psi.coef[ch];
+
+    /* decode short blocks, deinterleaving in-place */
+    let max_sfb = ics.maxSFB as usize;
+    let mut win_base = 0;
+    for (gp, codebooks) in psi.sfbCodeBook[ch]
+        .chunks_exact(max_sfb.max(1))
+        .take(ics.numWinGroup as usize)
+        .enumerate()
+    {
+        let group_len = ics.winGroupLen[gp] as usize;
+        for (sfb, &cb) in codebooks[..max_sfb].iter().enumerate() {
+            for win in win_base..win_base + group_len {
+                let band = win * NSAMPS_SHORT + sfb_tab[sfb] as usize
+                    ..win * NSAMPS_SHORT + sfb_tab[sfb + 1] as usize;
+                unpack_band(bsi, cb, &mut coef[band])?;
+            }
+        }
+
+        /* fill with zeros above maxSFB */
+        for win in win_base..win_base + group_len {
+            coef[win * NSAMPS_SHORT + sfb_tab[max_sfb] as usize..(win + 1) * NSAMPS_SHORT].fill(0);
+        }
+        win_base += group_len;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* every codeword count must fill the code space exactly (Kraft sum of 1) */
+    fn assert_complete(info: &HuffInfo, n_symbols: usize) {
+        let kraft: u64 = info
+            .count
+            .iter()
+            .enumerate()
+            .map(|(len, &n)| (n as u64) << (MAX_HUFF_BITS - 1 - len))
+            .sum();
+        assert_eq!(kraft, 1 << MAX_HUFF_BITS);
+        assert_eq!(
+            info.count.iter().map(|&n| n as usize).sum::<usize>(),
+            n_symbols
+        );
+        assert_eq!(
+            info.count[info.max_bits as usize - 1..]
+                .iter()
+                .filter(|&&n| n != 0)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn codebooks_are_complete() {
+        let sizes = [81, 81, 81, 81, 81, 81, 64, 64, 169, 169, 289];
+        for (info, n) in HUFF_TAB_SPEC_INFO.iter().zip(sizes) {
+            assert_complete(info, n);
+        }
+        assert_eq!(HUFF_TAB_SPEC_INFO[10].offset + 289, HUFF_TAB_SPEC.len());
+        assert_complete(&HUFF_TAB_SCALE_FACT_INFO, HUFF_TAB_SCALE_FACT.len());
+    }
+
+    #[test]
+    fn decodes_scalefactor_and_escape_codewords() {
+        /* scalefactor deltas 0 ("0") and -1 ("100"), then an escape with prefix "11110": 8 bits over 256 */
+        let bytes = [0b0100_1111, 0b0101_0101, 0b1000_0000];
+        let mut bsi = BitStreamInfo::from_slice(&bytes);
+        assert_eq!(decode_one_scale_factor(&mut bsi), 0);
+        assert_eq!(decode_one_scale_factor(&mut bsi), -1);
+        assert_eq!(decode_escape(&mut bsi), Ok(256 + 0x0ab));
+    }
+}
@@ -0,0 +1,411 @@
+//! Individual channel stream side info: ICS info, section data, scalefactors, pulse and TNS
+//! data (14496-3, tables 4.6 and 4.50 to 4.55).
+
+use super::huffman::{decode_one_scale_factor, decode_spectrum_long, decode_spectrum_short};
+use super::{
+    AACDecoder, ICSInfo, MAX_TNS_COEFS, MAX_TNS_FILTERS, NWINDOWS_SHORT, PRED_SFB_MAX, PulseInfo,
+    SFBAND_TOTAL_LONG, SFBAND_TOTAL_SHORT, TNSInfo, WindowSequence,
+};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+/* bitstream field lengths for gain control data:
+ *   GAIN_BITS[winSequence][0] = maxWindow (how many gain windows there are)
+ *   GAIN_BITS[winSequence][1] = locBitsZero (bits for alocCode if window == 0)
+ *   GAIN_BITS[winSequence][2] = locBits (bits for alocCode if window != 0)
+ */
+const GAIN_BITS: [[u32; 3]; 4] = [
+    [1, 5, 5], /* long */
+    [2, 4, 2], /* start */
+    [8, 2, 2], /* short */
+    [2, 4, 5], /* stop */
+];
+
+/***********************************************************************************************************************
+ * Function:    DecodeICSInfo
+ *
+ * Description: decode individual channel stream info
+ *
+ * Inputs:      sample rate index
+ *
+ * Outputs:     updated icsInfo struct
+ *
+ * Return:      Ok, or Err if maxSFB is beyond the last scalefactor band
+ *
+ * Notes:       prediction data (main profile) is skipped
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_ics_info(
+    ics: &mut ICSInfo,
+    samp_rate_idx: usize,
+    bsi: &mut BitStreamInfo,
+) -> Result<(), ()> {
+    ics.icsResBit = bsi.get_bits(1) as u8;
+    ics.winSequence = WindowSequence::from_bits(bsi.get_bits(2));
+    ics.winShape = bsi.get_bits(1) as u8;
+    if ics.winSequence == WindowSequence::EightShort {
+        /* short block */
+        ics.maxSFB = bsi.get_bits(4) as u8;
+        ics.sfGroup = bsi.get_bits(7) as u8;
+        ics.numWinGroup = 1;
+        ics.winGroupLen = [0; 8];
+        ics.winGroupLen[0] = 1;
+        let mut mask = 0x40; /* start with bit 6 */
+        for _ in 0..7 {
+            if ics.sfGroup & mask != 0 {
+                ics.winGroupLen[ics.numWinGroup as usize - 1] += 1;
+            } else {
+                ics.numWinGroup += 1;
+                ics.winGroupLen[ics.numWinGroup as usize - 1] = 1;
+            }
+            mask >>= 1;
+        }
+        if ics.maxSFB > SFBAND_TOTAL_SHORT[samp_rate_idx] {
+            return Err(());
+        }
+    } else {
+        /* long block */
+        ics.maxSFB = bsi.get_bits(6) as u8;
+        ics.predictorDataPresent = bsi.get_bits(1) as u8;
+        if ics.predictorDataPresent != 0 {
+            if bsi.get_bits(1) != 0 {
+                bsi.get_bits(5); /* predictor_reset_group_number */
+            }
+            for _ in 0..ics.maxSFB.min(PRED_SFB_MAX[samp_rate_idx]) {
+                bsi.get_bits(1); /* prediction_used */
+            }
+        }
+        ics.numWinGroup = 1;
+        ics.winGroupLen = [0; 8];
+        ics.winGroupLen[0] = 1;
+        if ics.maxSFB > SFBAND_TOTAL_LONG[samp_rate_idx] {
+            return Err(());
+        }
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSectionData
+ *
+ * Description: decode section data (scale factor band groupings and
+ *                associated Huffman codebooks)
+ *
+ * Inputs:      window sequence (short or long blocks)
+ *              number of window groups (1 for long blocks, 1-8 for short blocks)
+ *              max coded scalefactor band
+ *
+ * Outputs:     index of Huffman codebook for each scalefactor band in each section
+ *
+ * Return:      Ok, or Err for a section running past maxSFB or the reserved codebook 12
+ *
+ * Notes:       sectCB, sectEnd, sfbCodeBook, ordered by window groups for short blocks
+ *              the section length escape loop ends at the end of the data, where the bits
+ *                read as 0
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_section_data(
+    bsi: &mut BitStreamInfo,
+    win_sequence: WindowSequence,
+    num_win_grp: usize,
+    max_sfb: usize,
+    sfb_code_book: &mut [u8],
+) -> Result<(), ()> {
+    let sect_len_bits = if win_sequence == WindowSequence::EightShort {
+        3
+    } else {
+        5
+    };
+    let sect_escape_val = (1 << sect_len_bits) - 1;
+
+    for group in sfb_code_book
+        .chunks_exact_mut(max_sfb.max(1))
+        .take(num_win_grp)
+    {
+        let mut sfb = 0;
+        while sfb < max_sfb {
+            let cb = bsi.get_bits(4) as u8; /* next section codebook */
+            if cb == 12 {
+                return Err(());
+            }
+            let mut sect_len = 0;
+            loop {
+                let sect_len_incr = bsi.get_bits(sect_len_bits) as usize;
+                sect_len += sect_len_incr;
+                if sect_len_incr != sect_escape_val || sfb + sect_len > max_sfb {
+                    break;
+                }
+            }
+            if sfb + sect_len > max_sfb || bsi.overrun > 0 {
+                return Err(());
+            }
+
+            group[sfb..sfb + sect_len].fill(cb);
+            sfb += sect_len;
+        }
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeScaleFactors
+ *
+ * Description: decode scalefactors, PNS energy, and intensity stereo weights
+ *
+ * Inputs:      number of window groups (1 for long blocks, 1-8 for short blocks)
+ *              max coded scalefactor band
+ *              global gain (starting value for differential scalefactor coding)
+ *              index of Huffman codebook for each scalefactor band in each section
+ *
+ * Outputs:     decoded scalefactor for each section
+ *
+ * Return:      none
+ *
+ * Notes:       sfbCodeBook, scaleFactors ordered by window groups for short blocks
+ *              for section with codebook 13, scaleFactors buffer has decoded PNS
+ *                energy instead of regular scalefactor
+ *              for section with codebook 14 or 15, scaleFactors buffer has intensity
+ *                stereo weight instead of regular scalefactor
+ **********************************************************************************************************************/
+pub fn decode_scale_factors(
+    bsi: &mut BitStreamInfo,
+    n_bands: usize,
+    global_gain: i32,
+    sfb_code_book: &[u8],
+    scale_factors: &mut [i16],
+) {
+    /* starting values for differential coding */
+    let mut sf = global_gain;
+    let mut is = 0;
+    let mut nrg = global_gain - 90 - 256;
+    let mut npf = true;
+
+    for (&sfb_cb, scale_factor) in sfb_code_book
+        .iter()
+        .zip(scale_factors.iter_mut())
+        .take(n_bands)
+    {
+        *scale_factor = match sfb_cb {
+            14 | 15 => {
+                /* intensity stereo - differential coding */
+                is += decode_one_scale_factor(bsi);
+                is as i16
+            }
+            13 => {
+                /* PNS - first energy is directly coded, rest are Huffman coded (npf = noise_pcm_flag) */
+                if npf {
+                    nrg += bsi.get_bits(9) as i32;
+                    npf = false;
+                } else {
+                    nrg += decode_one_scale_factor(bsi);
+                }
+                nrg as i16
+            }
+            1..=11 => {
+                /* regular (non-zero) region - differential coding */
+                sf += decode_one_scale_factor(bsi);
+                sf as i16
+            }
+            /* inactive scalefactor band if codebook 0 */
+            _ => 0,
+        };
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodePulseInfo
+ *
+ * Description: decode pulse information
+ *
+ * Inputs:      bitstream positioned after pulse_data_present
+ *
+ * Outputs:     updated PulseInfo struct
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn decode_pulse_info(pi: &mut PulseInfo, bsi: &mut BitStreamInfo) {
+    pi.numPulse = bsi.get_bits(2) as u8 + 1; /* add 1 here */
+    pi.startSFB = bsi.get_bits(6) as u8;
+    for i in 0..pi.numPulse as usize {
+        pi.offset[i] = bsi.get_bits(5) as u8;
+        pi.amp[i] = bsi.get_bits(4) as u8;
+    }
+}
+
+/* read the coefficients of one filter, sign-extended from coef_bits (2, 3, or 4) */
+fn decode_tns_coefs(bsi: &mut BitStreamInfo, coef_bits: u32, coef: &mut [i8]) {
+    for c in coef {
+        *c = ((bsi.get_bits(coef_bits) as i8) << (8 - coef_bits)) >> (8 - coef_bits);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeTNSInfo
+ *
+ * Description: decode TNS filter information
+ *
+ * Inputs:      window sequence (short or long blocks)
+ *
+ * Outputs:     updated TNSInfo struct, with the (signed) filter coefficients
+ *
+ * Return:      none
+ *
+ * Notes:       length, order and dir are stored for every filter, in bitstream order; the
+ *                coefficients of all filters follow each other in coef
+ **********************************************************************************************************************/
+pub fn decode_tns_info(bsi: &mut BitStreamInfo, win_sequence: WindowSequence, ti: &mut TNSInfo) {
+    let mut filt = 0;
+    let mut n_coefs = 0;
+    let (n_windows, num_filt_bits, length_bits, order_bits) =
+        if win_sequence == WindowSequence::EightShort {
+            (NWINDOWS_SHORT, 1, 4, 3)
+        } else {
+            (1, 2, 6, 5)
+        };
+
+    for w in 0..n_windows {
+        ti.numFilt[w] = bsi.get_bits(num_filt_bits) as u8;
+        if ti.numFilt[w] != 0 {
+            ti.coefRes[w] = bsi.get_bits(1) as u8 + 3;
+        }
+        for _ in 0..ti.numFilt[w] {
+            ti.length[filt] = bsi.get_bits(length_bits) as u8;
+            ti.order[filt] = bsi.get_bits(order_bits) as u8;
+            ti.dir[filt] = 0;
+            let order = ti.order[filt] as usize;
+            if order != 0 {
+                ti.dir[filt] = bsi.get_bits(1) as u8;
+                let compress = bsi.get_bits(1);
+                let coef_bits = ti.coefRes[w] as u32 - compress; /* 2, 3, or 4 */
+                decode_tns_coefs(bsi, coef_bits, &mut ti.coef[n_coefs..n_coefs + order]);
+                n_coefs += order;
+            }
+            filt += 1;
+        }
+    }
+    debug_assert!(filt <= MAX_TNS_FILTERS && n_coefs <= MAX_TNS_COEFS);
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeGainControlInfo
+ *
+ * Description: skip gain control information (SSR profile only)
+ *
+ * Inputs:      window sequence (short or long blocks)
+ *
+ * Outputs:     none
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn decode_gain_control_info(bsi: &mut BitStreamInfo, win_sequence: WindowSequence) {
+    let max_band = bsi.get_bits(2);
+    let [max_win, loc_bits_zero, loc_bits] = GAIN_BITS[win_sequence as usize];
+
+    for _ in 1..=max_band {
+        for wd in 0..max_win {
+            let adj_num = bsi.get_bits(3);
+            for _ in 0..adj_num {
+                bsi.get_bits(4); /* alevcode */
+                bsi.get_bits(if wd == 0 { loc_bits_zero } else { loc_bits });
+            }
+        }
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeICS
+ *
+ * Description: decode individual channel stream
+ *
+ * Inputs:      index of current channel
+ *
+ * Outputs:     updated section data, scale factor data, pulse data, TNS data
+ *
+ * Return:      Ok, or Err for invalid ICS info, section data or pulse data
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_ics(dec: &mut AACDecoder, bsi: &mut BitStreamInfo, ch: usize) -> Result<(), ()> {
+    let psi = &mut dec.m_PSInfoBase;
+    let ics_idx = psi.ics_index(ch);
+
+    let global_gain = bsi.get_bits(8) as i32;
+    if psi.commonWin == 0 {
+        decode_ics_info(&mut psi.icsInfo[ics_idx], psi.sampRateIdx, bsi)?;
+    }
+    let ics = psi.icsInfo[ics_idx];
+    let num_win_grp = ics.numWinGroup as usize;
+    let max_sfb = ics.maxSFB as usize;
+
+    decode_section_data(
+        bsi,
+        ics.winSequence,
+        num_win_grp,
+        max_sfb,
+        &mut psi.sfbCodeBook[ch],
+    )?;
+
+    decode_scale_factors(
+        bsi,
+        num_win_grp * max_sfb,
+        global_gain,
+        &psi.sfbCodeBook[ch],
+        &mut psi.scaleFactors[ch],
+    );
+
+    let pi = &mut dec.m_PulseInfo[ch];
+    pi.pulseDataPresent = bsi.get_bits(1) as u8;
+    if pi.pulseDataPresent != 0 {
+        /* pulses are not allowed in short blocks (14496-3, 4.6.3.3) */
+        if ics.winSequence == WindowSequence::EightShort {
+            return Err(());
+        }
+        decode_pulse_info(pi, bsi);
+        if pi.startSFB as usize > max_sfb {
+            return Err(());
+        }
+    }
+
+    let ti = &mut psi.tnsInfo[ch];
+    ti.tnsDataPresent = bsi.get_bits(1) as u8;
+    if ti.tnsDataPresent != 0 {
+        decode_tns_info(bsi, ics.winSequence, ti);
+    }
+
+    if bsi.get_bits(1) != 0 {
+        decode_gain_control_info(bsi, ics.winSequence);
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeNoiselessData
+ *
+ * Description: decode noiseless data (side info and transform coefficients)
+ *
+ * Inputs:      bitstream positioned at the start of individual channel stream
+ *                (14496-3, table 4.44)
+ *              index of current channel
+ *
+ * Outputs:     updated global gain, section data, scale factor data, pulse data,
+ *                TNS data, and spectral data
+ *
+ * Return:      Ok, or Err if the channel stream is invalid
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_noiseless_data(
+    dec: &mut AACDecoder,
+    bsi: &mut BitStreamInfo,
+    ch: usize,
+) -> Result<(), ()> {
+    decode_ics(dec, bsi, ch)?;
+
+    let psi = &dec.m_PSInfoBase;
+    if psi.icsInfo[psi.ics_index(ch)].winSequence == WindowSequence::EightShort {
+        decode_spectrum_short(dec, bsi, ch)?;
+    } else {
+        decode_spectrum_long(dec, bsi, ch)?;
+    }
+
+    /* set flag if TNS used for any channel */
+    dec.m_AACDecInfo.tnsUsed |= dec.m_PSInfoBase.tnsInfo[ch].tnsDataPresent as i32;
+    Ok(())
+}
@@ -0,0 +1,668 @@
+//! LATM and LOAS, the AAC transport of DVB and of many broadcaster relays.
+//!
+//! LATM packs the raw data blocks (access units) of one or more streams into
+//! AudioMuxElements, set up by a StreamMuxConfig: which streams there are, their
+//! AudioSpecificConfigs and how the payload lengths are coded. The config either comes in
+//! band, at the start of the elements that carry it, or out of band, e.g. in the `config`
+//! parameter of an MP4A-LATM RTP stream. LOAS puts each element behind a 3 byte header, an
+//! 11-bit sync word and the element length, so the elements can be found in a byte stream.
+//!
+//! Nothing in an element is byte aligned, [`LatmDemuxer`] therefore copies the access units
+//! of the first stream (program 0, layer 0) into a buffer of its own before they go to
+//! [`AACDecoder::decode_frame`]; the decoder is set up from the AudioSpecificConfig of that
+//! stream whenever it changes. Only the syntax real streams use is supported: payload
+//! lengths in bytes (frameLengthType 0) and all streams with the same time framing.
+
+use super::{AACDecoder, AOT_PS, AOT_SBR, AacError, DecodedFrame, elements};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+/// Bytes of the LOAS header: sync word and element length.
+pub const LOAS_HEADER_BYTES: usize = 3;
+/// Longest AudioMuxElement a LOAS frame holds, and the longest [`LatmDemuxer`] takes.
+pub const MAX_AUDIO_MUX_ELEMENT_BYTES: usize = 0x1fff;
+/// Access units of one stream in an AudioMuxElement, one per subframe.
+pub const MAX_SUBFRAMES: usize = 64;
+/// Longest AudioSpecificConfig kept; longer ones only carry a program config element with
+/// a comment, or fill bits.
+pub const MAX_ASC_BYTES: usize = 64;
+
+const LOAS_SYNC_WORD: u32 = 0x2b7;
+
+/// The parts of a StreamMuxConfig the demuxer needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamMuxConfig {
+    /// 0 or 1; version 1 gives the lengths of the AudioSpecificConfigs
+    pub audio_mux_version: u8,
+    /// access units of each stream in an AudioMuxElement, 1 to [`MAX_SUBFRAMES`]
+    pub num_subframes: usize,
+    /// streams over all programs and layers, the first is the one decoded
+    pub num_streams: usize,
+    asc: [u8; MAX_ASC_BYTES],
+    asc_bytes: usize,
+}
+
+impl StreamMuxConfig {
+    /// Parses an out-of-band StreamMuxConfig, starting at the first bit of `config`.
+    pub fn parse(config: &[u8]) -> Result<Self, AacError> {
+        let mut bsi = BitStreamInfo::from_slice(config);
+        let parsed = StreamMuxConfig::read(&mut bsi)?;
+        if bsi.calc_bits_used(config, 0) > config.len() as i32 * 8 {
+            return Err(AacError::StreamMuxConfig);
+        }
+        Ok(parsed)
+    }
+
+    /// AudioSpecificConfig of the first stream, for
+    /// [`AACDecoder::set_audio_specific_config`]; bits after its end are zero.
+    pub fn audio_specific_config(&self) -> &[u8] {
+        &self.asc[..self.asc_bytes]
+    }
+
+    /***********************************************************************************************************************
+     * Function:    StreamMuxConfig
+     *
+     * Description: read a StreamMuxConfig
+     *
+     * Inputs:      bitstream positioned at audioMuxVersion
+     *
+     * Outputs:     bitstream positioned after the config, possibly past the end of the data
+     *
+     * Return:      the config, or Err if it is one the demuxer doesn't support
+     *
+     * Notes:       audioMuxVersionA 1 has no syntax defined, streams without the same time
+     *                framing and frame length types other than 0 (fixed lengths, CELP and
+     *                HVXC) are not supported
+     *              the AudioSpecificConfig of every stream has to be read to get past it,
+     *                version 0 doesn't give its length
+     **********************************************************************************************************************/
+    fn read(bsi: &mut BitStreamInfo) -> Result<Self, AacError> {
+        let audio_mux_version = bsi.get_bits(1) as u8;
+        if audio_mux_version == 1 {
+            if bsi.get_bits(1) != 0 {
+                return Err(AacError::StreamMuxConfig); /* audioMuxVersionA */
+            }
+            latm_get_value(bsi); /* taraBufferFullness */
+        }
+        if bsi.get_bits(1) == 0 {
+            return Err(AacError::StreamMuxConfig); /* allStreamsSameTimeFraming */
+        }
+        let mut config = StreamMuxConfig {
+            audio_mux_version,
+            num_subframes: bsi.get_bits(6) as usize + 1,
+            num_streams: 0,
+            asc: [0; MAX_ASC_BYTES],
+            asc_bytes: 0,
+        };
+
+        let num_program = bsi.get_bits(4) + 1;
+        for prog in 0..num_program {
+            let num_layer = bsi.get_bits(3) + 1;
+            for lay in 0..num_layer {
+                let use_same_config = (prog > 0 || lay > 0) && bsi.get_bits(1) != 0;
+                if !use_same_config {
+                    let mut asc = [0; MAX_ASC_BYTES];
+                    let asc_bytes = read_audio_specific_config(bsi, audio_mux_version, &mut asc)?;
+                    if config.num_streams == 0 {
+                        config.asc = asc;
+                        config.asc_bytes = asc_bytes;
+                    }
+                }
+                if bsi.get_bits(3) != 0 {
+                    return Err(AacError::StreamMuxConfig); /* frameLengthType */
+                }
+                bsi.get_bits(8); /* latmBufferFullness */
+                config.num_streams += 1;
+            }
+        }
+
+        if bsi.get_bits(1) != 0 {
+            /* otherDataPresent, the other data follows the payloads and is not needed */
+            if audio_mux_version == 1 {
+                latm_get_value(bsi);
+            } else {
+                while {
+                    let esc = bsi.get_bits(1);
+                    bsi.get_bits(8);
+                    esc != 0
+                } {}
+            }
+        }
+        if bsi.get_bits(1) != 0 {
+            bsi.get_bits(8); /* crcCheckSum */
+        }
+        Ok(config)
+    }
+}
+
+/* LatmGetValue: a value of 1 to 4 bytes, the number given first */
+fn latm_get_value(bsi: &mut BitStreamInfo) -> u32 {
+    let bytes_for_value = bsi.get_bits(2);
+    let mut value = 0;
+    for _ in 0..=bytes_for_value {
+        value = (value << 8) | bsi.get_bits(8);
+    }
+    value
+}
+
+/* copies n_bits bits to the start of out, the last byte padded with zeros */
+fn copy_bits(bsi: &mut BitStreamInfo, n_bits: usize, out: &mut [u8]) {
+    for (i, byte) in out[..n_bits.div_ceil(8)].iter_mut().enumerate() {
+        let bits = (n_bits - 8 * i).min(8) as u32;
+        *byte = (bsi.get_bits(bits) << (8 - bits)) as u8;
+    }
+}
+
+fn skip_bits(bsi: &mut BitStreamInfo, mut n_bits: usize) {
+    while n_bits > 0 {
+        let n = n_bits.min(32);
+        bsi.get_bits(n as u32);
+        n_bits -= n;
+    }
+}
+
+/* reads an AudioSpecificConfig into asc, byte aligned; returns its length in bytes */
+fn read_audio_specific_config(
+    bsi: &mut BitStreamInfo,
+    audio_mux_version: u8,
+    asc: &mut [u8; MAX_ASC_BYTES],
+) -> Result<usize, AacError> {
+    let n_bits = if audio_mux_version == 1 {
+        latm_get_value(bsi) as usize
+    } else {
+        /* the config has to be parsed to find its end, from a byte aligned copy since the
+         * byte alignment in a program config element is relative to its start
+         */
+        copy_bits(&mut bsi.clone(), MAX_ASC_BYTES * 8, asc);
+        audio_specific_config_bits(asc).ok_or(AacError::StreamMuxConfig)?
+    };
+    let kept_bits = n_bits.min(MAX_ASC_BYTES * 8);
+    *asc = [0; MAX_ASC_BYTES];
+    copy_bits(bsi, kept_bits, asc);
+    skip_bits(bsi, n_bits - kept_bits);
+    Ok(kept_bits.div_ceil(8))
+}
+
+/***********************************************************************************************************************
+ * Function:    AudioSpecificConfigBits
+ *
+ * Description: find the length of an AudioSpecificConfig
+ *
+ * Inputs:      buffer starting at the config
+ *
+ * Outputs:     none
+ *
+ * Return:      length of the config in bits, None for object types without a GASpecificConfig
+ *                and configs that don't fit in the buffer
+ *
+ * Notes:       SBR and PS signalled hierarchically (object type 5 or 29) are read, the
+ *                backward compatible sync extension can't be told from the data after the
+ *                config and is not
+ **********************************************************************************************************************/
+fn audio_specific_config_bits(asc: &[u8]) -> Option<usize> {
+    let mut bsi = BitStreamInfo::from_slice(asc);
+    let get_object_type = |bsi: &mut BitStreamInfo| match bsi.get_bits(5) {
+        31 => 32 + bsi.get_bits(6),
+        aot => aot,
+    };
+    let skip_samp_rate = |bsi: &mut BitStreamInfo| {
+        if bsi.get_bits(4) == 0x0f {
+            bsi.get_bits(24);
+        }
+    };
+
+    let mut object_type = get_object_type(&mut bsi);
+    skip_samp_rate(&mut bsi);
+    let channel_config = bsi.get_bits(4);
+    if object_type == AOT_SBR || object_type == AOT_PS {
+        skip_samp_rate(&mut bsi);
+        object_type = get_object_type(&mut bsi);
+    }
+    /* AAC Main, LC, SSR, LTP, scalable and TwinVQ */
+    if !matches!(object_type, 1..=4 | 6 | 7) {
+        return None;
+    }
+
+    /* GASpecificConfig */
+    bsi.get_bits(1); /* frameLengthFlag */
+    if bsi.get_bits(1) != 0 {
+        bsi.get_bits(14); /* coreCoderDelay */
+    }
+    let extension_flag = bsi.get_bits(1);
+    if channel_config == 0 {
+        elements::decode_program_config_element(&mut bsi);
+    }
+    if object_type == 6 {
+        bsi.get_bits(3); /* layerNr */
+    }
+    if extension_flag != 0 {
+        bsi.get_bits(1); /* extensionFlag3 */
+    }
+
+    let n_bits = bsi.calc_bits_used(asc, 0) as usize;
+    (n_bits <= asc.len() * 8).then_some(n_bits)
+}
+
+/***********************************************************************************************************************
+ * Function:    LOASFindSyncWord
+ *
+ * Description: locate the next byte-aligned LOAS sync word in the stream
+ *
+ * Inputs:      buffer to search for sync word
+ *
+ * Outputs:     none
+ *
+ * Return:      the buffer from the first sync word on
+ *              None if there is no sync word in the buffer
+ *
+ * Notes:       11 sync bits are easily found in other data; loas_frame_bytes() and parsing the
+ *                element tell whether it is a frame
+ **********************************************************************************************************************/
+pub fn loas_find_sync_word(data: &[u8]) -> Option<&[u8]> {
+    let mut tail = data;
+
+    while tail.len() >= 2 {
+        if tail[0] == 0x56 && (tail[1] & 0xe0) == 0xe0 {
+            return Some(tail);
+        }
+        tail = &tail[1..];
+    }
+    None
+}
+
+/// Bytes in the LOAS frame (AudioSyncStream) at the start of `header`, header included, or
+/// `None` if there is no LOAS header or it gives an empty element.
+pub fn loas_frame_bytes(header: &[u8]) -> Option<usize> {
+    let header = header.get(..LOAS_HEADER_BYTES)?;
+    let word = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+    let element_bytes = (word & 0x1fff) as usize;
+    (word >> 13 == LOAS_SYNC_WORD && element_bytes > 0).then_some(LOAS_HEADER_BYTES + element_bytes)
+}
+
+/// Splits AudioMuxElements into access units and hands them, with the AudioSpecificConfig of
+/// their stream, to a decoder.
+pub struct LatmDemuxer {
+    config: Option<StreamMuxConfig>,
+    /* the decoder is not set up for the config yet */
+    config_pending: bool,
+    /* access units of the last element, as (start, length) in buf */
+    buf: [u8; MAX_AUDIO_MUX_ELEMENT_BYTES],
+    units: [(usize, usize); MAX_SUBFRAMES],
+    n_units: usize,
+    next_unit: usize,
+}
+
+impl Default for LatmDemuxer {
+    fn default() -> Self {
+        LatmDemuxer::new()
+    }
+}
+
+impl LatmDemuxer {
+    pub const fn new() -> Self {
+        LatmDemuxer {
+            config: None,
+            config_pending: false,
+            buf: [0; MAX_AUDIO_MUX_ELEMENT_BYTES],
+            units: [(0, 0); MAX_SUBFRAMES],
+            n_units: 0,
+            next_unit: 0,
+        }
+    }
+
+    /// Sets the StreamMuxConfig for elements without one in band (`mux_config_present`
+    /// false in [`LatmDemuxer::parse_audio_mux_element`]), see [`StreamMuxConfig::parse`].
+    pub fn set_stream_mux_config(&mut self, config: &[u8]) -> Result<(), AacError> {
+        self.update_config(StreamMuxConfig::parse(config)?);
+        Ok(())
+    }
+
+    /// The config the demuxer works with, from the last element that carried one or set out
+    /// of band.
+    pub fn stream_mux_config(&self) -> Option<&StreamMuxConfig> {
+        self.config.as_ref()
+    }
+
+    /// Drops the access units not decoded yet and the config; the decoder is set up again
+    /// from the next one.
+    pub fn reset(&mut self) {
+        self.config = None;
+        self.config_pending = false;
+        self.n_units = 0;
+        self.next_unit = 0;
+    }
+
+    fn update_config(&mut self, config: StreamMuxConfig) {
+        if self
+            .config
+            .is_none_or(|c| c.audio_specific_config() != config.audio_specific_config())
+        {
+            self.config_pending = true;
+        }
+        self.config = Some(config);
+    }
+
+    /// Reads the AudioMuxElement `element` and keeps the access units of its first stream,
+    /// replacing those of the previous element; returns how many there are.
+    ///
+    /// `mux_config_present` is true for in-band configs (always so in LOAS): the element
+    /// then starts with useSameStreamMux and possibly a new StreamMuxConfig. Access units of
+    /// the other streams and other data are skipped.
+    pub fn parse_audio_mux_element(
+        &mut self,
+        element: &[u8],
+        mux_config_present: bool,
+    ) -> Result<usize, AacError> {
+        self.n_units = 0;
+        self.next_unit = 0;
+        if element.len() > MAX_AUDIO_MUX_ELEMENT_BYTES {
+            return Err(AacError::AudioMuxElement {
+                offset: MAX_AUDIO_MUX_ELEMENT_BYTES,
+            });
+        }
+        let element_bits = element.len() * 8;
+        let mut bsi = BitStreamInfo::from_slice(element);
+        let bits_used = |bsi: &BitStreamInfo| bsi.calc_bits_used(element, 0) as usize;
+
+        if mux_config_present && bsi.get_bits(1) == 0 {
+            let config = StreamMuxConfig::read(&mut bsi)?;
+            if bits_used(&bsi) > element_bits {
+                return Err(AacError::AudioMuxElement { offset: 0 });
+            }
+            self.update_config(config);
+        }
+        let config = self.config.ok_or(AacError::StreamMuxConfig)?;
+
+        let mut buf_used = 0;
+        for unit in &mut self.units[..config.num_subframes] {
+            let subframe_start = bits_used(&bsi);
+
+            /* PayloadLengthInfo, a length in bytes for each stream */
+            let mut first_bytes = 0;
+            let mut other_bytes = 0;
+            for stream in 0..config.num_streams {
+                let mut slot_bytes = 0;
+                while {
+                    let tmp = bsi.get_bits(8) as usize;
+                    slot_bytes += tmp;
+                    tmp == 255
+                } {}
+                if stream == 0 {
+                    first_bytes = slot_bytes;
+                } else {
+                    other_bytes += slot_bytes;
+                }
+            }
+
+            /* PayloadMux */
+            if bits_used(&bsi) + 8 * (first_bytes + other_bytes) > element_bits {
+                return Err(AacError::AudioMuxElement {
+                    offset: subframe_start / 8,
+                });
+            }
+            copy_bits(&mut bsi, 8 * first_bytes, &mut self.buf[buf_used..]);
+            skip_bits(&mut bsi, 8 * other_bytes);
+            *unit = (buf_used, first_bytes);
+            buf_used += first_bytes;
+        }
+
+        self.n_units = config.num_subframes;
+        Ok(self.n_units)
+    }
+
+    /// Access units of the last element not decoded yet.
+    pub fn pending_access_units(&self) -> usize {
+        self.n_units - self.next_unit
+    }
+
+    /// The next access unit of the last element, without decoding it.
+    pub fn next_access_unit(&mut self) -> Option<&[u8]> {
+        let (start, len) = *self.units[..self.n_units].get(self.next_unit)?;
+        self.next_unit += 1;
+        Some(&self.buf[start..start + len])
+    }
+
+    /// Decodes the next access unit of the last element with [`AACDecoder::decode_frame`],
+    /// after setting the decoder up from the AudioSpecificConfig if it changed. The access
+    /// unit is used up even if that fails.
+    ///
+    /// `bytes_consumed` of the result is the length of the access unit. With none left this
+    /// is an `InDataUnderflow`.
+    pub fn decode_access_unit(
+        &mut self,
+        dec: &mut AACDecoder,
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, AacError> {
+        let (start, len) = *self.units[..self.n_units]
+            .get(self.next_unit)
+            .ok_or(AacError::InDataUnderflow { offset: 0 })?;
+        self.next_unit += 1;
+        if self.config_pending {
+            let config = self.config.as_ref().ok_or(AacError::StreamMuxConfig)?;
+            dec.set_audio_specific_config(config.audio_specific_config())?;
+            self.config_pending = false;
+        }
+        dec.decode_frame(&self.buf[start..start + len], out)
+    }
+
+    /***********************************************************************************************************************
+     * Function:    LOASDecode
+     *
+     * Description: decode the next access unit of a LOAS stream
+     *
+     * Inputs:      decoder
+     *              buffer starting at a LOAS frame, or anywhere while access units of the
+     *                last frame are pending
+     *              output buffer, big enough for one block
+     *
+     * Outputs:     PCM of the access unit in out
+     *              number of input bytes used up (consumed): the whole frame when a new one
+     *                is read, 0 while access units of the last frame are decoded
+     *
+     * Return:      number of samples written to out, or the reason the access unit could not
+     *                be decoded
+     *
+     * Notes:       a frame with an invalid element is consumed, nothing is if there is no
+     *                LOAS header or the frame is not all in the buffer
+     **********************************************************************************************************************/
+    pub fn decode_loas(
+        &mut self,
+        dec: &mut AACDecoder,
+        input: &[u8],
+        out: &mut [i16],
+        consumed: &mut usize,
+    ) -> Result<usize, AacError> {
+        *consumed = 0;
+        if self.pending_access_units() == 0 {
+            let frame_bytes = loas_frame_bytes(input).ok_or(AacError::InvalidLoasHeader)?;
+            let frame = input
+                .get(..frame_bytes)
+                .ok_or(AacError::InDataUnderflow { offset: 0 })?;
+            *consumed = frame_bytes;
+            self.parse_audio_mux_element(&frame[LOAS_HEADER_BYTES..], true)?;
+        }
+        Ok(self.decode_access_unit(dec, out)?.samples_written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::{boxed::Box, vec::Vec};
+
+    /* packs (value, bits) fields MSB first, zero padded to a byte */
+    fn pack(fields: &[(u32, u32)]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut pos = 0;
+        for &(v, n) in fields {
+            for b in (0..n).rev() {
+                if pos % 8 == 0 {
+                    buf.push(0);
+                }
+                if (v >> b) & 1 != 0 {
+                    *buf.last_mut().unwrap() |= 0x80 >> (pos % 8);
+                }
+                pos += 1;
+            }
+        }
+        buf
+    }
+
+    /* AAC-LC, 48 kHz, stereo: 0x11 0x90 */
+    const ASC_LC: [(u32, u32); 6] = [(2, 5), (3, 4), (2, 4), (0, 1), (0, 1), (0, 1)];
+
+    /* StreamMuxConfig of version 0, one program and layer, AAC-LC 48 kHz stereo */
+    fn config_v0(num_subframes: u32) -> Vec<(u32, u32)> {
+        let mut fields = std::vec![(0, 1), (1, 1), (num_subframes - 1, 6), (0, 4), (0, 3)];
+        fields.extend(ASC_LC);
+        fields.extend([(0, 3), (0xff, 8), (0, 1), (0, 1)]);
+        fields
+    }
+
+    #[test]
+    fn reads_stream_mux_configs() {
+        let config = StreamMuxConfig::parse(&pack(&config_v0(1))).unwrap();
+        assert_eq!((config.audio_mux_version, config.num_subframes), (0, 1));
+        assert_eq!(config.num_streams, 1);
+        assert_eq!(config.audio_specific_config(), [0x11, 0x90]);
+
+        // version 1: tara buffer fullness, the ASC length, fill bits after the ASC, other
+        // data and a checksum; HE-AAC signalled with object type 5, two layers
+        let mut fields = std::vec![(1, 1), (0, 1), (0, 2), (0xff, 8), (1, 1), (3, 6), (0, 4)];
+        fields.push((1, 3));
+        let he_asc = [(5, 5), (6, 4), (1, 4), (3, 4), (2, 5), (0, 3)];
+        fields.extend([(0, 2), (25 + 3, 8)]);
+        fields.extend(he_asc);
+        fields.push((0b101, 3));
+        fields.extend([(0, 3), (0xff, 8)]);
+        fields.extend([(1, 1), (0, 3), (0xff, 8)]);
+        fields.extend([(1, 1), (0, 2), (40, 8), (1, 1), (0x5a, 8)]);
+        let config = StreamMuxConfig::parse(&pack(&fields)).unwrap();
+        assert_eq!((config.audio_mux_version, config.num_subframes), (1, 4));
+        assert_eq!(config.num_streams, 2);
+        assert_eq!(config.audio_specific_config(), [0x2b, 0x09, 0x88, 0x50]);
+
+        // a program config element instead of a channel configuration, its comment byte
+        // aligned relative to the ASC
+        let mut fields = std::vec![(0, 1), (1, 1), (0, 6), (0, 4), (0, 3)];
+        fields.extend([(2, 5), (3, 4), (0, 4), (0, 3)]);
+        fields.extend([
+            (0, 4),
+            (1, 2),
+            (3, 4),
+            (1, 4),
+            (0, 4),
+            (0, 4),
+            (0, 2),
+            (0, 3),
+        ]);
+        fields.extend([(0, 4), (0, 1), (0, 1), (0, 1), (1, 1), (0, 4)]);
+        fields.extend([(0, 1), (2, 8), (0xaa, 8), (0xbb, 8)]);
+        fields.extend([(0, 3), (0xff, 8), (0, 1), (0, 1)]);
+        let config = StreamMuxConfig::parse(&pack(&fields)).unwrap();
+        assert_eq!(config.audio_specific_config().len(), 10);
+        assert_eq!(config.audio_specific_config()[8..], [0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn rejects_unsupported_configs() {
+        let mut version_a = pack(&[(1, 1), (1, 1)]);
+        version_a.resize(8, 0);
+        let mut fixed_length = config_v0(1);
+        fixed_length[11] = (1, 3);
+        let mut other_framing = config_v0(1);
+        other_framing[1] = (0, 1);
+        let mut celp = config_v0(1);
+        celp[5] = (8, 5);
+        for config in [
+            version_a,
+            pack(&fixed_length),
+            pack(&other_framing),
+            pack(&celp),
+        ] {
+            assert_eq!(
+                StreamMuxConfig::parse(&config),
+                Err(AacError::StreamMuxConfig)
+            );
+        }
+        // cut short
+        assert_eq!(
+            StreamMuxConfig::parse(&pack(&config_v0(1))[..3]),
+            Err(AacError::StreamMuxConfig)
+        );
+    }
+
+    #[test]
+    fn splits_elements_into_access_units() {
+        let mut demux = Box::<LatmDemuxer>::default();
+        assert_eq!(
+            demux.parse_audio_mux_element(&[0x80, 0x01, 0x00], true),
+            Err(AacError::StreamMuxConfig)
+        );
+
+        // in band config, two subframes: 300 bytes (an escaped length) and 3 bytes
+        let mut fields = std::vec![(0, 1)];
+        fields.extend(config_v0(2));
+        fields.extend([(255, 8), (45, 8)]);
+        fields.extend((0..300).map(|i| (i as u32 & 0xff, 8)));
+        fields.extend([(3, 8), (0xab, 8), (0xcd, 8), (0xef, 8)]);
+        assert_eq!(demux.parse_audio_mux_element(&pack(&fields), true), Ok(2));
+        assert_eq!(demux.pending_access_units(), 2);
+        let unit = demux.next_access_unit().unwrap();
+        assert_eq!(unit.len(), 300);
+        assert!(unit.iter().enumerate().all(|(i, &b)| b == i as u8));
+        assert_eq!(demux.next_access_unit(), Some(&[0xab, 0xcd, 0xef][..]));
+        assert_eq!(demux.next_access_unit(), None);
+
+        // the same config, then none at all (out of band)
+        let element = pack(&[(1, 1), (1, 8), (0x12, 8), (1, 8), (0x34, 8)]);
+        assert_eq!(demux.parse_audio_mux_element(&element, true), Ok(2));
+        assert_eq!(demux.next_access_unit(), Some(&[0x12][..]));
+        let element_oob = [0x01, 0x80, 0x01, 0x24];
+        assert_eq!(demux.parse_audio_mux_element(&element_oob, false), Ok(2));
+        assert_eq!(demux.next_access_unit(), Some(&[0x80][..]));
+        assert_eq!(demux.next_access_unit(), Some(&[0x24][..]));
+
+        // lengths past the end of the element
+        assert_eq!(
+            demux.parse_audio_mux_element(&element[..4], true),
+            Err(AacError::AudioMuxElement { offset: 2 })
+        );
+        assert_eq!(demux.pending_access_units(), 0);
+    }
+
+    #[test]
+    fn skips_the_other_streams() {
+        // two programs, the second with the same config
+        let mut fields = std::vec![(0, 1), (0, 1), (1, 1), (0, 6), (1, 4), (0, 3)];
+        fields.extend(ASC_LC);
+        fields.extend([
+            (0, 3),
+            (0xff, 8),
+            (0, 3),
+            (1, 1),
+            (0, 3),
+            (0xff, 8),
+            (0, 1),
+            (0, 1),
+        ]);
+        fields.extend([(2, 8), (1, 8), (0x11, 8), (0x22, 8), (0x33, 8)]);
+        let mut demux = LatmDemuxer::new();
+        assert_eq!(demux.parse_audio_mux_element(&pack(&fields), true), Ok(1));
+        assert_eq!(demux.stream_mux_config().unwrap().num_streams, 2);
+        assert_eq!(demux.next_access_unit(), Some(&[0x11, 0x22][..]));
+    }
+
+    #[test]
+    fn finds_loas_frames() {
+        let data = [0x00, 0x56, 0x00, 0x56, 0xe0, 0x05];
+        let tail = loas_find_sync_word(&data).unwrap();
+        assert_eq!(tail.len(), 3);
+        assert_eq!(loas_frame_bytes(tail), Some(3 + 5));
+        assert_eq!(loas_frame_bytes(&[0x56, 0xff, 0xff]), Some(3 + 0x1fff));
+        // an empty element, cut short, no sync word
+        assert_eq!(loas_frame_bytes(&[0x56, 0xe0, 0x00]), None);
+        assert_eq!(loas_frame_bytes(&[0x56, 0xe0]), None);
+        assert_eq!(loas_frame_bytes(&[0x57, 0xe0, 0x05]), None);
+    }
+}
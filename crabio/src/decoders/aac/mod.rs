@@ -0,0 +1,1578 @@
+//! MPEG-4 AAC-LC and HE-AAC decoding (ISO/IEC 14496-3 subparts 4 and 4.6.18), ported from the
+//! Helix fixed-point decoder.
+//!
+//! [`AACDecoder::decode_frame`] decodes one raw data block, the payload of an MP4 sample.
+//! [`elements`] reads the syntactic elements of the block; for each channel element
+//! [`ics`] unpacks the individual channel stream and [`huffman`] its spectrum, which then
+//! goes through [`dequant`], [`stereo`] (M/S and intensity), [`pns`], [`tns`] and finally
+//! [`imdct`], the inverse transform with windowing and overlap-add. Once a stream carries
+//! SBR data, signalled in the AudioSpecificConfig or found in a fill element, [`sbr`] doubles
+//! the sample rate of the output. Parametric stereo (HE-AACv2) in the SBR data of a mono
+//! stream likewise turns the output to stereo, unless it is switched off with
+//! [`AACDecoder::set_parametric_stereo`].
+//!
+//! Outside MP4 the blocks come in [`adts`] frames, or in the AudioMuxElements of [`latm`] on
+//! broadcast relays.
+//!
+//! Main and SSR profile tools (prediction, gain control) and coupling channel elements are
+//! not supported.
+
+use crate::utils::{bit_stream_cache::BitStreamInfo, clip_to_short::clip_to_short, crc16::crc16};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::mem::MaybeUninit;
+
+pub mod adts;
+pub mod dequant;
+pub mod elements;
+pub mod huffman;
+pub mod ics;
+pub mod imdct;
+pub mod latm;
+pub mod pns;
+pub mod sbr;
+pub mod stereo;
+pub mod tns;
+
+pub const AAC_MAX_NCHANS: usize = 2; // max number of output channels
+pub const AAC_MAX_NSAMPS: usize = 1024; // samples per channel and raw data block
+pub const MAX_NCHANS_ELEM: usize = 2; // max number of channels in any single bitstream element
+pub const AAC_MAX_SBR_NSAMPS: usize = 2 * AAC_MAX_NSAMPS; // samples per channel and block with SBR
+pub const NSAMPS_LONG: usize = 1024;
+pub const NSAMPS_SHORT: usize = 128;
+pub const NWINDOWS_SHORT: usize = 8;
+pub const NUM_SAMPLE_RATES: usize = 12;
+pub const MAX_SF_BANDS: usize = 15 * 8; // max scalefactor bands per channel, 15 for each of 8 short windows
+pub const MAX_MS_MASK_BYTES: usize = (MAX_SF_BANDS + 7) >> 3;
+pub const MAX_WIN_GROUPS: usize = 8;
+pub const MAX_PULSES: usize = 4;
+pub const MAX_TNS_FILTERS: usize = 8; // 3 for a long window, 1 for each of 8 short windows
+pub const MAX_TNS_COEFS: usize = 3 * 31; // 3 filters of order 31 for a long window (8 * 7 for short ones)
+pub const FILL_BUF_SIZE: usize = 269;
+pub const NUM_SYN_ID_BITS: u32 = 3;
+pub const NUM_INST_TAG_BITS: u32 = 4;
+pub const SF_OFFSET: i32 = 100;
+pub const FBITS_OUT_DQ_OFF: i32 = 20 - 15; // (FBITS_OUT_DQ - SF_DQ_OFFSET)
+pub const FBITS_OUT_IMDCT: i32 = 3; // fraction bits of the windowed output
+pub const AAC_PROFILE_MAIN: i32 = 0;
+pub const AAC_PROFILE_LC: i32 = 1;
+pub const AAC_PROFILE_SSR: i32 = 2;
+
+/* audio object types and the SBR sync extension of an AudioSpecificConfig (14496-3, 1.6.2) */
+const AOT_AAC_LC: u32 = 2;
+const AOT_SBR: u32 = 5;
+const AOT_PS: u32 = 29;
+const SYNC_EXTENSION_TYPE: u32 = 0x2b7;
+
+pub const ERR_AAC_NONE: i32 = 0;
+pub const ERR_AAC_INDATA_UNDERFLOW: i32 = -1;
+pub const ERR_AAC_NULL_POINTER: i32 = -2;
+pub const ERR_AAC_INVALID_ADTS_HEADER: i32 = -3;
+pub const ERR_AAC_INVALID_ADIF_HEADER: i32 = -4;
+pub const ERR_AAC_INVALID_FRAME: i32 = -5;
+pub const ERR_AAC_MPEG4_UNSUPPORTED: i32 = -6;
+pub const ERR_AAC_CHANNEL_MAP: i32 = -7;
+pub const ERR_AAC_SYNTAX_ELEMENT: i32 = -8;
+pub const ERR_AAC_DEQUANT: i32 = -9;
+pub const ERR_AAC_STEREO_PROCESS: i32 = -10;
+pub const ERR_AAC_PNS: i32 = -11;
+pub const ERR_AAC_SHORT_BLOCK_DEINT: i32 = -12;
+pub const ERR_AAC_TNS: i32 = -13;
+pub const ERR_AAC_IMDCT: i32 = -14;
+pub const ERR_AAC_NCHANS_TOO_HIGH: i32 = -15;
+pub const ERR_AAC_SBR_INIT: i32 = -16;
+pub const ERR_AAC_SBR_BITSTREAM: i32 = -17;
+pub const ERR_AAC_SBR_DATA: i32 = -18;
+pub const ERR_AAC_SBR_PCM_FORMAT: i32 = -19;
+pub const ERR_AAC_SBR_NCHANS_TOO_HIGH: i32 = -20;
+pub const ERR_AAC_SBR_SINGLERATE_UNSUPPORTED: i32 = -21;
+pub const ERR_AAC_RAWBLOCK_PARAMS: i32 = -22;
+pub const ERR_AAC_UNKNOWN: i32 = -9999;
+
+/// Reason a raw data block could not be decoded.
+///
+/// Offsets are in bytes, relative to the start of the block passed to
+/// [`AACDecoder::decode_frame`]. [`AacError::code`] maps every variant back to one of the
+/// `ERR_AAC_*` codes used by the C side.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AacError {
+    /// block ends inside the element or channel stream starting at `offset`
+    InDataUnderflow { offset: usize },
+    /// output holds fewer than the `needed` samples of one block
+    OutputTooSmall { needed: usize },
+    /// sample rate not in the table of 14496-3 (given to [`AACDecoder::set_raw_block_params`])
+    InvalidSampleRate { samp_rate: i32 },
+    /// no stream parameters set, or not an AAC-LC stream
+    RawBlockParams,
+    /// bad SBR payload in the fill element starting at `offset`
+    SbrBitstream { offset: usize },
+    /// AudioSpecificConfig with down-sampled SBR, where the output keeps the core rate
+    SbrSingleRate,
+    /// bad or unsupported element or channel stream starting at `offset`
+    SyntaxElement { offset: usize },
+    /// block has more channels than the `n_chans` the decoder was set up for (at most 2)
+    NChansTooHigh { n_chans: usize },
+    /// input does not start with an ADTS header
+    InvalidAdtsHeader,
+    /// CRC-16 of a protected ADTS frame or block does not match the one in the frame
+    CrcMismatch { expected: u16, computed: u16 },
+    /// input does not start with a LOAS header
+    InvalidLoasHeader,
+    /// no StreamMuxConfig received yet, or one that is invalid or not supported
+    StreamMuxConfig,
+    /// payload lengths of the AudioMuxElement run past its end at `offset`, relative to the
+    /// start of the element
+    AudioMuxElement { offset: usize },
+}
+
+impl AacError {
+    /// Legacy `ERR_AAC_*` value for this error.
+    pub const fn code(&self) -> i32 {
+        match self {
+            AacError::InDataUnderflow { .. } => ERR_AAC_INDATA_UNDERFLOW,
+            AacError::OutputTooSmall { .. } => ERR_AAC_UNKNOWN,
+            AacError::InvalidSampleRate { .. } => ERR_AAC_INVALID_FRAME,
+            AacError::RawBlockParams => ERR_AAC_RAWBLOCK_PARAMS,
+            AacError::SbrBitstream { .. } => ERR_AAC_SBR_BITSTREAM,
+            AacError::SbrSingleRate => ERR_AAC_SBR_SINGLERATE_UNSUPPORTED,
+            AacError::SyntaxElement { .. } => ERR_AAC_SYNTAX_ELEMENT,
+            AacError::NChansTooHigh { .. } => ERR_AAC_NCHANS_TOO_HIGH,
+            AacError::InvalidAdtsHeader => ERR_AAC_INVALID_ADTS_HEADER,
+            AacError::CrcMismatch { .. } => ERR_AAC_INVALID_FRAME,
+            AacError::InvalidLoasHeader => ERR_AAC_INVALID_FRAME,
+            AacError::StreamMuxConfig => ERR_AAC_MPEG4_UNSUPPORTED,
+            AacError::AudioMuxElement { .. } => ERR_AAC_INVALID_FRAME,
+        }
+    }
+}
+
+impl From<AacError> for i32 {
+    fn from(e: AacError) -> i32 {
+        e.code()
+    }
+}
+
+impl core::fmt::Display for AacError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            AacError::InDataUnderflow { offset } => {
+                write!(f, "input underflow in the element at byte {offset}")
+            }
+            AacError::OutputTooSmall { needed } => {
+                write!(f, "output buffer too small, {needed} samples needed")
+            }
+            AacError::InvalidSampleRate { samp_rate } => {
+                write!(f, "invalid sample rate {samp_rate} Hz")
+            }
+            AacError::RawBlockParams => write!(f, "no AAC-LC raw block parameters set"),
+            AacError::SbrBitstream { offset } => {
+                write!(f, "invalid SBR data in the fill element at byte {offset}")
+            }
+            AacError::SbrSingleRate => write!(f, "down-sampled SBR is not supported"),
+            AacError::SyntaxElement { offset } => {
+                write!(f, "invalid syntax element at byte {offset}")
+            }
+            AacError::NChansTooHigh { n_chans } => {
+                write!(f, "too many channels in block, {n_chans} configured")
+            }
+            AacError::InvalidAdtsHeader => write!(f, "invalid ADTS header"),
+            AacError::CrcMismatch { expected, computed } => {
+                write!(
+                    f,
+                    "CRC mismatch: frame says {expected:#06x}, computed {computed:#06x}"
+                )
+            }
+            AacError::InvalidLoasHeader => write!(f, "invalid LOAS header"),
+            AacError::StreamMuxConfig => write!(f, "no usable StreamMuxConfig"),
+            AacError::AudioMuxElement { offset } => {
+                write!(f, "AudioMuxElement cut short at byte {offset}")
+            }
+        }
+    }
+}
+
+/* syntactic element type */
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementId {
+    Invalid = -1,
+    Sce = 0,
+    Cpe = 1,
+    Cce = 2,
+    Lfe = 3,
+    Dse = 4,
+    Pce = 5,
+    Fil = 6,
+    End = 7,
+}
+
+impl ElementId {
+    pub const fn from_bits(v: u32) -> Self {
+        match v & 0x07 {
+            0 => ElementId::Sce,
+            1 => ElementId::Cpe,
+            2 => ElementId::Cce,
+            3 => ElementId::Lfe,
+            4 => ElementId::Dse,
+            5 => ElementId::Pce,
+            6 => ElementId::Fil,
+            _ => ElementId::End,
+        }
+    }
+
+    /// Number of channels coded in an element of this type.
+    pub const fn num_chans(self) -> usize {
+        match self {
+            ElementId::Sce | ElementId::Lfe => 1,
+            ElementId::Cpe => 2,
+            _ => 0,
+        }
+    }
+}
+
+/* AAC file format */
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AacFormat {
+    #[default]
+    Unknown = 0,
+    Adts = 1,
+    Adif = 2,
+    Raw = 3,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowSequence {
+    #[default]
+    OnlyLong = 0,
+    LongStart = 1,
+    EightShort = 2,
+    LongStop = 3,
+}
+
+impl WindowSequence {
+    pub const fn from_bits(v: u32) -> Self {
+        match v & 0x03 {
+            0 => WindowSequence::OnlyLong,
+            1 => WindowSequence::LongStart,
+            2 => WindowSequence::EightShort,
+            _ => WindowSequence::LongStop,
+        }
+    }
+}
+
+pub const SAMP_RATE_TAB: [i32; NUM_SAMPLE_RATES] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000,
+];
+
+/* max scalefactor band for prediction (main profile only) */
+pub const PRED_SFB_MAX: [u8; NUM_SAMPLE_RATES] = [33, 33, 38, 40, 40, 40, 41, 41, 37, 37, 37, 34];
+
+/* total number of scale factor bands in one window */
+pub const SFBAND_TOTAL_SHORT: [u8; NUM_SAMPLE_RATES] =
+    [12, 12, 12, 14, 14, 14, 15, 15, 15, 15, 15, 15];
+pub const SFBAND_TOTAL_LONG: [u8; NUM_SAMPLE_RATES] =
+    [41, 41, 47, 49, 49, 51, 47, 47, 43, 43, 43, 40];
+
+/* scale factor band tables */
+const SFBAND_TAB_SHORT_OFFSET: [usize; NUM_SAMPLE_RATES] =
+    [0, 0, 0, 13, 13, 13, 28, 28, 44, 44, 44, 60];
+
+const SFBAND_TAB_SHORT: [u16; 76] = [
+    /* short block 64, 88, 96 kHz [13] (tables 4.5.24, 4.5.26) */
+    0, 4, 8, 12, 16, 20, 24, 32, 40, 48, 64, 92, 128,
+    /* short block 32, 44, 48 kHz [15] (table 4.5.15) */
+    0, 4, 8, 12, 16, 20, 28, 36, 44, 56, 68, 80, 96, 112, 128,
+    /* short block 22, 24 kHz [16] (table 4.5.22) */
+    0, 4, 8, 12, 16, 20, 24, 28, 36, 44, 52, 64, 76, 92, 108, 128,
+    /* short block 11, 12, 16 kHz [16] (table 4.5.20) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 40, 48, 60, 72, 88, 108, 128,
+    /* short block 8 kHz [16] (table 4.5.18) */
+    0, 4, 8, 12, 16, 20, 24, 28, 36, 44, 52, 60, 72, 88, 108, 128,
+];
+
+const SFBAND_TAB_LONG_OFFSET: [usize; NUM_SAMPLE_RATES] =
+    [0, 0, 42, 90, 90, 140, 192, 192, 240, 240, 240, 284];
+
+const SFBAND_TAB_LONG: [u16; 325] = [
+    /* long block 88, 96 kHz [42] (table 4.5.25) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44, 48, 52, 56, 64, 72, 80, 88, 96, 108, 120, 132, 144,
+    156, 172, 188, 212, 240, 276, 320, 384, 448, 512, 576, 640, 704, 768, 832, 896, 960, 1024,
+    /* long block 64 kHz [48] (table 4.5.13) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44, 48, 52, 56, 64, 72, 80, 88, 100, 112, 124, 140,
+    156, 172, 192, 216, 240, 268, 304, 344, 384, 424, 464, 504, 544, 584, 624, 664, 704, 744, 784,
+    824, 864, 904, 944, 984, 1024, /* long block 44, 48 kHz [50] (table 4.5.14) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 48, 56, 64, 72, 80, 88, 96, 108, 120, 132, 144, 160,
+    176, 196, 216, 240, 264, 292, 320, 352, 384, 416, 448, 480, 512, 544, 576, 608, 640, 672, 704,
+    736, 768, 800, 832, 864, 896, 928, 1024, /* long block 32 kHz [52] (table 4.5.16) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 48, 56, 64, 72, 80, 88, 96, 108, 120, 132, 144, 160,
+    176, 196, 216, 240, 264, 292, 320, 352, 384, 416, 448, 480, 512, 544, 576, 608, 640, 672, 704,
+    736, 768, 800, 832, 864, 896, 928, 960, 992, 1024,
+    /* long block 22, 24 kHz [48] (table 4.5.21) */
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44, 52, 60, 68, 76, 84, 92, 100, 108, 116, 124, 136,
+    148, 160, 172, 188, 204, 220, 240, 260, 284, 308, 336, 364, 396, 432, 468, 508, 552, 600, 652,
+    704, 768, 832, 896, 960, 1024, /* long block 11, 12, 16 kHz [44] (table 4.5.19) */
+    0, 8, 16, 24, 32, 40, 48, 56, 64, 72, 80, 88, 100, 112, 124, 136, 148, 160, 172, 184, 196, 212,
+    228, 244, 260, 280, 300, 320, 344, 368, 396, 424, 456, 492, 532, 572, 616, 664, 716, 772, 832,
+    896, 960, 1024, /* long block 8 kHz [41] (table 4.5.17) */
+    0, 12, 24, 36, 48, 60, 72, 84, 96, 108, 120, 132, 144, 156, 172, 188, 204, 220, 236, 252, 268,
+    288, 308, 328, 348, 372, 396, 420, 448, 476, 508, 544, 580, 620, 664, 712, 764, 820, 880, 944,
+    1024,
+];
+
+/// Scalefactor band boundaries of one window, `maxSFB + 1` entries cover `maxSFB` bands.
+pub fn sfb_table(samp_rate_idx: usize, win_sequence: WindowSequence) -> &'static [u16] {
+    if win_sequence == WindowSequence::EightShort {
+        let start = SFBAND_TAB_SHORT_OFFSET[samp_rate_idx];
+        &SFBAND_TAB_SHORT[start..=start + SFBAND_TOTAL_SHORT[samp_rate_idx] as usize]
+    } else {
+        let start = SFBAND_TAB_LONG_OFFSET[samp_rate_idx];
+        &SFBAND_TAB_LONG[start..=start + SFBAND_TOTAL_LONG[samp_rate_idx] as usize]
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(non_snake_case)]
+pub struct ICSInfo {
+    pub icsResBit: u8,
+    pub winSequence: WindowSequence,
+    pub winShape: u8, /* 0 = sine, 1 = Kaiser-Bessel derived */
+    pub maxSFB: u8,
+    pub sfGroup: u8,
+    pub predictorDataPresent: u8,
+    pub numWinGroup: u8,
+    pub winGroupLen: [u8; MAX_WIN_GROUPS],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(non_snake_case)]
+pub struct PulseInfo {
+    pub pulseDataPresent: u8,
+    pub numPulse: u8,
+    pub startSFB: u8,
+    pub offset: [u8; MAX_PULSES],
+    pub amp: [u8; MAX_PULSES],
+}
+
+/* filter fields are in bitstream order: one filter per short window that has one, or up to 3
+ * for the long window; coef holds order coefficients for each filter, back to back
+ */
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+#[allow(non_snake_case)]
+pub struct TNSInfo {
+    pub tnsDataPresent: u8,
+    pub numFilt: [u8; MAX_TNS_FILTERS], /* per window */
+    pub coefRes: [u8; MAX_TNS_FILTERS], /* per window */
+    pub length: [u8; MAX_TNS_FILTERS],
+    pub order: [u8; MAX_TNS_FILTERS],
+    pub dir: [u8; MAX_TNS_FILTERS],
+    pub coef: [i8; MAX_TNS_COEFS],
+}
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct AACDecInfo {
+    pub prevBlockID: ElementId, /* block information */
+    pub currBlockID: ElementId,
+    pub currInstTag: i32,
+    pub bitRate: i32, /* user-accessible info */
+    pub nChans: i32,
+    pub sampRate: i32,
+    pub profile: i32, /* 0: Main profile, 1: LowComplexity (LC), 2: ScalableSamplingRate (SSR), 3: reserved */
+    pub format: AacFormat,
+    pub tnsUsed: i32,
+    pub pnsUsed: i32,
+    pub frameCount: i32,
+    pub sbrEnabled: i32, /* set once SBR is signalled or found, the output is then at twice sampRate */
+    pub psEnabled: i32, /* set once PS is signalled or found in a mono stream, the output is then stereo */
+    /* payload of the last fill element, for SBR */
+    pub fillCount: usize,
+    pub fillExtType: u8,
+    /* ADTS frame being decoded: raw data blocks still to come and bytes up to its end */
+    pub adtsBlocksLeft: i32,
+    pub adtsBytesLeft: usize,
+    pub adtsProtected: bool,
+}
+
+/* state info struct for baseline (MPEG-4 LC) decoding */
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct PSInfoBase {
+    /* state information which is the same throughout whole frame */
+    pub nChans: i32,
+    pub sampRateIdx: usize,
+    /* state information which can be overwritten by subsequent elements within frame */
+    pub icsInfo: [ICSInfo; MAX_NCHANS_ELEM],
+    pub commonWin: i32,
+    pub scaleFactors: [[i16; MAX_SF_BANDS]; MAX_NCHANS_ELEM],
+    pub sfbCodeBook: [[u8; MAX_SF_BANDS]; MAX_NCHANS_ELEM],
+    pub msMaskPresent: i32,
+    pub msMaskBits: [u8; MAX_MS_MASK_BYTES], /* 1 bit per band, maxSFB bits for each window group */
+    pub pnsUsed: [i32; MAX_NCHANS_ELEM],
+    pub pnsLastVal: u32, /* state of the noise generator, carried across blocks */
+    pub intensityUsed: [i32; MAX_NCHANS_ELEM],
+    pub tnsInfo: [TNSInfo; MAX_NCHANS_ELEM],
+    pub gbCurrent: [i32; MAX_NCHANS_ELEM],
+    pub coef: [[i32; AAC_MAX_NSAMPS]; MAX_NCHANS_ELEM],
+    /* windowed output of the element, before rounding to 16-bit PCM (for postprocessing such as SBR) */
+    pub rawSampleBuf: [[i32; AAC_MAX_NSAMPS]; MAX_NCHANS_ELEM],
+    /* state information which must be saved for each element and used in next frame */
+    pub overlap: [[i32; AAC_MAX_NSAMPS]; AAC_MAX_NCHANS],
+    pub prevWinShape: [u8; AAC_MAX_NCHANS],
+    /* bytes of the last fill element, at most 15 + 255 - 1 */
+    pub fillBuf: [u8; FILL_BUF_SIZE],
+}
+
+impl PSInfoBase {
+    /// Index of the ICS info channel `ch` of the current element uses; both channels of a
+    /// CPE with a common window share the first.
+    pub fn ics_index(&self, ch: usize) -> usize {
+        if ch == 1 && self.commonWin == 1 {
+            0
+        } else {
+            ch
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(non_snake_case)]
+pub struct AACFrameInfo {
+    pub bitRate: i32,
+    pub nChans: i32,
+    pub sampRateCore: i32,
+    pub sampRateOut: i32,
+    pub bitsPerSample: i32,
+    pub outputSamps: i32,
+    pub profile: i32,
+    pub tnsUsed: i32,
+    pub pnsUsed: i32,
+}
+
+/// Result of a successful [`AACDecoder::decode_frame`] call.
+#[derive(Debug, Clone, Copy)]
+pub struct DecodedFrame {
+    /// bytes of input used by the raw data block (it ends byte aligned)
+    pub bytes_consumed: usize,
+    /// number of i16 values written to the output, interleaved LRLR... if stereo
+    pub samples_written: usize,
+    pub info: AACFrameInfo,
+}
+
+/// Error of a failed [`AACDecoder::decode_adts_frame`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub error: AacError,
+    /// bytes of input used by the block before it was given up: the rest of its frame, which
+    /// is dropped with it, or 0 if the header is invalid or the frame incomplete
+    pub bytes_consumed: usize,
+}
+
+impl From<DecodeError> for AacError {
+    fn from(e: DecodeError) -> AacError {
+        e.error
+    }
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({} bytes consumed)", self.error, self.bytes_consumed)
+    }
+}
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct AACDecoder {
+    pub m_AACDecInfo: AACDecInfo,
+    pub m_PSInfoBase: PSInfoBase,
+    pub m_PulseInfo: [PulseInfo; MAX_NCHANS_ELEM],
+    pub m_AACFrameInfo: AACFrameInfo,
+    pub m_PSInfoSBR: sbr::PSInfoSBR,
+    pub m_ParametricStereo: bool, /* decode PS data, else HE-AACv2 is output in mono */
+}
+
+impl Default for AACDecoder {
+    fn default() -> Self {
+        AACDecoder::new()
+    }
+}
+
+impl AACDecoder {
+    /// A decoder waiting for [`AACDecoder::set_raw_block_params`].
+    ///
+    /// This is a `const fn`, so a decoder can be a `static`. The struct is large (about 83 KB,
+    /// most of it the SBR filterbank and parametric stereo state); on targets with a small stack
+    /// build it in place with [`AACDecoder::init`] instead of returning it by value.
+    pub const fn new() -> Self {
+        let mut dec = MaybeUninit::<AACDecoder>::zeroed();
+        // SAFETY: the memory is zeroed, init_zeroed() sets the fields zero is not valid for
+        unsafe {
+            Self::init_zeroed(dec.as_mut_ptr());
+            dec.assume_init()
+        }
+    }
+
+    /// Initialises the decoder in `slot` in place, the way [`AACDecoder::new`] would, e.g. in
+    /// PSRAM or a `static` left uninitialised; the decoder is never copied through the stack.
+    pub fn init(slot: &mut MaybeUninit<AACDecoder>) -> &mut AACDecoder {
+        // SAFETY: as in new(), write_bytes() zeroes the whole struct
+        unsafe {
+            slot.as_mut_ptr().write_bytes(0, 1);
+            Self::init_zeroed(slot.as_mut_ptr());
+            slot.assume_init_mut()
+        }
+    }
+
+    /// A decoder on the heap, built in place.
+    #[cfg(feature = "alloc")]
+    pub fn new_boxed() -> Box<AACDecoder> {
+        let mut dec = Box::new_uninit();
+        AACDecoder::init(&mut dec);
+        // SAFETY: initialised by init()
+        unsafe { dec.assume_init() }
+    }
+
+    /* all-zero bytes are the state AACDecoder_AllocateBuffers() left on the C side, except for
+     * the element ids and the instance tag, which start out invalid, and PS, which is on
+     *
+     * SAFETY: dec points to a zeroed AACDecoder
+     */
+    const unsafe fn init_zeroed(dec: *mut AACDecoder) {
+        unsafe {
+            (&raw mut (*dec).m_AACDecInfo.prevBlockID).write(ElementId::Invalid);
+            (&raw mut (*dec).m_AACDecInfo.currBlockID).write(ElementId::Invalid);
+            (&raw mut (*dec).m_AACDecInfo.currInstTag).write(-1);
+            sbr::init_zeroed(&raw mut (*dec).m_PSInfoSBR);
+            (&raw mut (*dec).m_ParametricStereo).write(true);
+        }
+    }
+
+    /// Puts the decoder back in the state it starts in, stream parameters included. The
+    /// [`AACDecoder::set_parametric_stereo`] setting is kept.
+    pub fn reset(&mut self) {
+        let parametric_stereo = self.m_ParametricStereo;
+        let slot = self as *mut AACDecoder as *mut MaybeUninit<AACDecoder>;
+        // SAFETY: MaybeUninit<T> has the layout of T, and init() leaves a valid decoder
+        AACDecoder::init(unsafe { &mut *slot }).m_ParametricStereo = parametric_stereo;
+    }
+
+    /// Switches parametric stereo decoding on (the default) or off. Takes effect with the
+    /// next block.
+    ///
+    /// With PS, the HE-AACv2 coding of many low bitrate stations, a mono stream is output in
+    /// stereo, [`AACDecoder::channels`] is then 2. PS costs about as much as SBR again, a
+    /// hybrid filterbank, the decorrelator and a second synthesis QMF; without it such a stream
+    /// plays in mono at the cost of plain HE-AAC.
+    pub fn set_parametric_stereo(&mut self, enable: bool) {
+        self.m_ParametricStereo = enable;
+        if !enable {
+            self.m_AACDecInfo.psEnabled = 0;
+        }
+    }
+
+    pub fn parametric_stereo(&self) -> bool {
+        self.m_ParametricStereo
+    }
+
+    /// Forgets the overlap of the last block, e.g. after a seek, so the next block starts
+    /// from silence. The stream parameters are kept.
+    pub fn flush(&mut self) {
+        let info = &mut self.m_AACDecInfo;
+        info.prevBlockID = ElementId::Invalid;
+        info.currBlockID = ElementId::Invalid;
+        info.currInstTag = -1;
+        info.tnsUsed = 0;
+        info.pnsUsed = 0;
+        info.adtsBlocksLeft = 0;
+
+        let psi = &mut self.m_PSInfoBase;
+        psi.overlap = [[0; AAC_MAX_NSAMPS]; AAC_MAX_NCHANS];
+        psi.prevWinShape = [0; AAC_MAX_NCHANS];
+
+        sbr::init_sbr_state(&mut self.m_PSInfoSBR);
+    }
+
+    /***********************************************************************************************************************
+     * Function:    AACSetRawBlockParams
+     *
+     * Description: set internal state variables for decoding a stream of raw data blocks
+     *
+     * Inputs:      flag indicating source of parameters
+     *              nChans, sampRate, and profile 0 = main, 1 = LC, 2 = SSR, 3 = reserved
+     *
+     * Outputs:     updated codec state
+     *
+     * Return:      Ok, or the reason the parameters can't be decoded
+     *
+     * Notes:       if copyLast, the codec keeps the channels and sample rate it already has
+     *                (from an earlier call or a stream header) and only switches to raw blocks
+     *              otherwise it uses the values passed in, e.g. from the AudioSpecificConfig
+     *                of an MP4 file, with sampRate the rate of the AAC core; SBR is then off
+     *                until a block carries SBR data (implicit signalling)
+     **********************************************************************************************************************/
+    pub fn set_raw_block_params(
+        &mut self,
+        copy_last: bool,
+        n_chans: i32,
+        samp_rate: i32,
+        profile: i32,
+    ) -> Result<(), AacError> {
+        self.m_AACDecInfo.format = AacFormat::Raw;
+        self.m_AACDecInfo.adtsBlocksLeft = 0;
+        if !copy_last {
+            self.m_AACDecInfo.profile = profile;
+            self.m_AACDecInfo.sbrEnabled = 0;
+            self.m_AACDecInfo.psEnabled = 0;
+            sbr::init_sbr_state(&mut self.m_PSInfoSBR);
+            self.m_PSInfoBase.nChans = n_chans;
+            self.m_PSInfoBase.sampRateIdx = SAMP_RATE_TAB
+                .iter()
+                .position(|&rate| rate == samp_rate)
+                .ok_or(AacError::InvalidSampleRate { samp_rate })?;
+        }
+        self.m_AACDecInfo.nChans = self.m_PSInfoBase.nChans;
+        self.m_AACDecInfo.sampRate = SAMP_RATE_TAB[self.m_PSInfoBase.sampRateIdx];
+
+        if self.m_AACDecInfo.profile != AAC_PROFILE_LC {
+            return Err(AacError::RawBlockParams);
+        }
+        Ok(())
+    }
+
+    /***********************************************************************************************************************
+     * Function:    AACSetAudioSpecificConfig
+     *
+     * Description: set up decoding of raw data blocks from an AudioSpecificConfig
+     *
+     * Inputs:      AudioSpecificConfig, e.g. from the esds box of an MP4 file (14496-3, 1.6.2.1)
+     *
+     * Outputs:     updated codec state, as set_raw_block_params()
+     *
+     * Return:      Ok, or the reason the stream can't be decoded
+     *
+     * Notes:       supports AAC-LC (object type 2) and HE-AAC, signalled hierarchically (object
+     *                type 5 or 29, the core is always AAC-LC here) or backward compatible, with
+     *                the SBR sync extension after the GASpecificConfig
+     *              explicit signalling enables SBR right away, so the first block is already
+     *                output at the doubled rate
+     *              object type 29 (parametric stereo) with a mono channel configuration enables
+     *                PS right away as well, so the first block is already output in stereo
+     **********************************************************************************************************************/
+    pub fn set_audio_specific_config(&mut self, asc: &[u8]) -> Result<(), AacError> {
+        let bits_avail = asc.len() as i32 * 8;
+        let mut bsi = BitStreamInfo::from_slice(asc);
+
+        let get_object_type = |bsi: &mut BitStreamInfo| match bsi.get_bits(5) {
+            31 => 32 + bsi.get_bits(6),
+            aot => aot,
+        };
+        let get_samp_rate = |bsi: &mut BitStreamInfo| match bsi.get_bits(4) {
+            0x0f => bsi.get_bits(24) as i32,
+            idx => SAMP_RATE_TAB.get(idx as usize).copied().unwrap_or(0),
+        };
+
+        let mut object_type = get_object_type(&mut bsi);
+        let samp_rate = get_samp_rate(&mut bsi);
+        let channel_config = bsi.get_bits(4);
+        let ps_present = object_type == AOT_PS && channel_config == 1;
+        let mut sbr_present = false;
+        if object_type == AOT_SBR || object_type == AOT_PS {
+            /* the extension sampling frequency is the output rate, down-sampled SBR keeps the core rate */
+            if get_samp_rate(&mut bsi) != 2 * samp_rate {
+                return Err(AacError::SbrSingleRate);
+            }
+            sbr_present = true;
+            object_type = get_object_type(&mut bsi);
+        }
+        if bsi.calc_bits_used(asc, 0) > bits_avail {
+            return Err(AacError::InDataUnderflow { offset: 0 });
+        }
+        if object_type != AOT_AAC_LC {
+            return Err(AacError::RawBlockParams);
+        }
+
+        /* GASpecificConfig: only 1024-sample frames, no layers */
+        if bsi.get_bits(1) != 0 {
+            return Err(AacError::RawBlockParams);
+        }
+        if bsi.get_bits(1) != 0 {
+            bsi.get_bits(14); /* coreCoderDelay */
+        }
+        if bsi.get_bits(1) != 0 {
+            return Err(AacError::RawBlockParams); /* extensionFlag, only for ER object types */
+        }
+        if bsi.calc_bits_used(asc, 0) > bits_avail {
+            return Err(AacError::InDataUnderflow { offset: 0 });
+        }
+
+        /* backward compatible signalling, the sync extension only counts if it is complete */
+        if !sbr_present
+            && bits_avail - bsi.calc_bits_used(asc, 0) >= 16
+            && bsi.get_bits(11) == SYNC_EXTENSION_TYPE
+            && get_object_type(&mut bsi) == AOT_SBR
+            && bsi.get_bits(1) != 0
+        {
+            if get_samp_rate(&mut bsi) != 2 * samp_rate {
+                return Err(AacError::SbrSingleRate);
+            }
+            sbr_present = bsi.calc_bits_used(asc, 0) <= bits_avail;
+        }
+
+        if !(1..=AAC_MAX_NCHANS as u32).contains(&channel_config) {
+            return Err(AacError::NChansTooHigh {
+                n_chans: channel_config as usize,
+            });
+        }
+        self.set_raw_block_params(false, channel_config as i32, samp_rate, AAC_PROFILE_LC)?;
+        if sbr_present {
+            if self.m_PSInfoBase.sampRateIdx < 3 {
+                return Err(AacError::InvalidSampleRate {
+                    samp_rate: 2 * samp_rate,
+                });
+            }
+            self.m_AACDecInfo.sbrEnabled = 1;
+            if ps_present && self.m_ParametricStereo {
+                self.m_AACDecInfo.psEnabled = 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sample rate of the decoded output, in Hz; twice the core rate once SBR is enabled.
+    pub fn samp_rate(&self) -> i32 {
+        self.m_AACDecInfo.sampRate << (self.m_AACDecInfo.sbrEnabled != 0) as u32
+    }
+
+    /// Whether the stream uses SBR (HE-AAC), so the output is at twice the core rate.
+    pub fn sbr_enabled(&self) -> bool {
+        self.m_AACDecInfo.sbrEnabled != 0
+    }
+
+    /// Channels of the PCM output; 2 for a mono stream with parametric stereo.
+    pub fn channels(&self) -> i32 {
+        if self.m_AACDecInfo.psEnabled != 0 {
+            2
+        } else {
+            self.m_AACDecInfo.nChans
+        }
+    }
+
+    pub fn profile(&self) -> i32 {
+        self.m_AACDecInfo.profile
+    }
+
+    pub fn format(&self) -> AacFormat {
+        self.m_AACDecInfo.format
+    }
+
+    /// Number of i16 values one block decodes to, all channels; 2048 per channel with SBR.
+    ///
+    /// With implicit signalling this doubles at the first block that carries SBR data, so
+    /// size the output for [`AAC_MAX_SBR_NSAMPS`] per channel if the stream may be HE-AAC,
+    /// and for 2 channels if a mono stream may carry parametric stereo.
+    pub fn output_samps(&self) -> usize {
+        let n_samps = if self.sbr_enabled() {
+            AAC_MAX_SBR_NSAMPS
+        } else {
+            AAC_MAX_NSAMPS
+        };
+        self.channels().max(0) as usize * n_samps
+    }
+
+    /// Decodes one raw data block from `input` into `out`.
+    ///
+    /// `input` has to start at the block, e.g. an MP4 sample, and [`AACDecoder::set_raw_block_params`]
+    /// must have been called first. `out` must have room for [`AACDecoder::output_samps`]
+    /// samples (see there for HE-AAC streams); stereo output is interleaved LRLRLR... A single channel element in a stereo
+    /// configuration is output on both channels.
+    ///
+    /// On error the output is undefined, and the decoder is ready for the next block.
+    pub fn decode_frame(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, AacError> {
+        let mut bytes_consumed = 0;
+        let samples_written = self.decode(input, out, &mut bytes_consumed, None)?;
+        Ok(DecodedFrame {
+            bytes_consumed,
+            samples_written,
+            info: self.m_AACFrameInfo,
+        })
+    }
+
+    /// Decodes the next raw data block of an ADTS stream from `input` into `out`.
+    ///
+    /// `input` has to start at an ADTS header (see [`adts::adts_find_sync_word`], and
+    /// [`adts::AdtsSync`] for finding one reliably) and hold the whole frame. The decoder is
+    /// set up from the header, again whenever the format changes, so no stream parameters
+    /// are needed. A frame with several blocks takes one call per block, `input` then starts
+    /// where the last call stopped; the last block consumes the frame up to its end. `out` is
+    /// sized as for [`AACDecoder::decode_frame`].
+    ///
+    /// A block that fails to decode is consumed with the rest of its frame, see
+    /// [`DecodeError::bytes_consumed`].
+    pub fn decode_adts_frame(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, DecodeError> {
+        let mut bytes_consumed = 0;
+        match self.decode_adts(input, out, false, &mut bytes_consumed) {
+            Ok(samples_written) => Ok(DecodedFrame {
+                bytes_consumed,
+                samples_written,
+                info: self.m_AACFrameInfo,
+            }),
+            Err(error) => Err(DecodeError {
+                error,
+                bytes_consumed,
+            }),
+        }
+    }
+
+    /// [`AACDecoder::decode_adts_frame`] that checks the CRC-16 of protected frames: over the
+    /// header and, for a frame with a single block, the start of each channel stream and the
+    /// whole of data stream and program config elements; a frame with several blocks has a
+    /// CRC for the header and one for each block.
+    ///
+    /// A block failing the check is consumed and output as silence, and
+    /// [`AacError::CrcMismatch`] is returned.
+    pub fn decode_adts_frame_verified(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, DecodeError> {
+        let mut bytes_consumed = 0;
+        match self.decode_adts(input, out, true, &mut bytes_consumed) {
+            Ok(samples_written) => Ok(DecodedFrame {
+                bytes_consumed,
+                samples_written,
+                info: self.m_AACFrameInfo,
+            }),
+            Err(error) => Err(DecodeError {
+                error,
+                bytes_consumed,
+            }),
+        }
+    }
+
+    /***********************************************************************************************************************
+     * Function:    AACDecodeADTS
+     *
+     * Description: decode the next raw data block of an ADTS frame
+     *
+     * Inputs:      buffer starting at an ADTS header, or at the next block of the frame the last
+     *                call started
+     *              PCM output buffer, as for AACDecode
+     *              flag to check the CRC of protected frames
+     *
+     * Outputs:     PCM data in outbuf, as for AACDecode
+     *              number of bytes of input used, also on error: 0 if the header is invalid or the
+     *                frame incomplete, else the rest of the frame
+     *
+     * Return:      number of PCM samples written, or the reason the block was dropped
+     *
+     * Notes:       the decoder is set up from the AudioSpecificConfig the header makes, unless it
+     *                already decodes ADTS frames of that format; HE-AAC then switches on at the
+     *                first block with SBR data, as in raw blocks with implicit signalling
+     *              a block failing the CRC is output as silence
+     **********************************************************************************************************************/
+    pub fn decode_adts(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+        verify_crc: bool,
+        consumed: &mut usize,
+    ) -> Result<usize, AacError> {
+        *consumed = 0;
+        let mut offset = 0;
+        /* CRC over the header, for a protected frame with a single block */
+        let mut header_crc = None;
+        if self.m_AACDecInfo.adtsBlocksLeft <= 0 {
+            let header = adts::AdtsHeader::parse(input).ok_or(AacError::InvalidAdtsHeader)?;
+            if input.len() < header.frame_bytes {
+                return Err(AacError::InDataUnderflow { offset: 0 });
+            }
+            *consumed = header.frame_bytes;
+            self.set_adts_format(&header.format)?;
+            offset = header.header_bytes();
+            if verify_crc && !header.header_crc_matches(input) {
+                let computed = crc16(0xffff, &input[..offset - 2]);
+                return Err(self.crc_mismatch(&input[offset - 2..], computed, out));
+            }
+            if header.format.protected && header.raw_blocks == 1 {
+                header_crc = Some(crc16(0xffff, &input[..adts::ADTS_HEADER_BYTES]));
+            }
+            let info = &mut self.m_AACDecInfo;
+            info.adtsBlocksLeft = header.raw_blocks as i32;
+            info.adtsBytesLeft = header.frame_bytes - offset;
+            info.adtsProtected = header.format.protected;
+        } else if input.len() < self.m_AACDecInfo.adtsBytesLeft {
+            return Err(AacError::InDataUnderflow { offset: 0 });
+        }
+
+        let frame_end = offset + self.m_AACDecInfo.adtsBytesLeft;
+        let block = &input[offset..frame_end];
+        *consumed = frame_end;
+        self.m_AACDecInfo.adtsBlocksLeft -= 1;
+        let last_block = self.m_AACDecInfo.adtsBlocksLeft == 0;
+        /* in a protected frame with several blocks each has its own CRC, after it */
+        let block_crc = self.m_AACDecInfo.adtsProtected && header_crc.is_none();
+        let mut crc = match header_crc {
+            Some(crc) => Some(adts::CrcState::new(crc)),
+            None if block_crc => Some(adts::CrcState::new(0xffff)),
+            None => None,
+        }
+        .filter(|_| verify_crc);
+
+        let mut bytes = 0;
+        let samples = self
+            .decode(block, out, &mut bytes, crc.as_mut())
+            .inspect_err(|_| self.m_AACDecInfo.adtsBlocksLeft = 0)?;
+
+        /* the CRC the frame carries for the block, behind the header or the block */
+        let crc_at = if block_crc {
+            bytes += 2;
+            offset + bytes - 2
+        } else {
+            offset.saturating_sub(2)
+        };
+        if !last_block {
+            if bytes >= block.len() {
+                self.m_AACDecInfo.adtsBlocksLeft = 0;
+                return Err(AacError::InDataUnderflow { offset });
+            }
+            self.m_AACDecInfo.adtsBytesLeft -= bytes;
+            *consumed = offset + bytes;
+        }
+        if let Some(crc) = crc {
+            let crc_word = input.get(crc_at..crc_at + 2).unwrap_or(&[0, 0]);
+            if crc_word != crc.crc.to_be_bytes() {
+                return Err(self.crc_mismatch(crc_word, crc.crc, out));
+            }
+        }
+        Ok(samples)
+    }
+
+    /***********************************************************************************************************************
+     * Function:    AACDecode
+     *
+     * Description: decode AAC frame
+     *
+     * Inputs:      buffer starting at a raw data block
+     *              PCM output buffer, big enough to hold one frame of decoded PCM samples
+     *              running CRC of a protected ADTS frame, None if not checked
+     *
+     * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+     *                number of output samples = 1024 per channel, 2048 with SBR
+     *              number of bytes of input used
+     *              the CRC updated with the protected bits of the block
+     *
+     * Return:      number of PCM samples written, or the reason the block was dropped
+     **********************************************************************************************************************/
+    fn decode(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+        consumed: &mut usize,
+        mut crc: Option<&mut adts::CrcState>,
+    ) -> Result<usize, AacError> {
+        if !matches!(self.m_AACDecInfo.format, AacFormat::Raw | AacFormat::Adts) {
+            return Err(AacError::RawBlockParams);
+        }
+        self.prepare_raw_block();
+
+        /* check for valid number of channels */
+        let n_chans = self.m_AACDecInfo.nChans;
+        if n_chans > AAC_MAX_NCHANS as i32 || n_chans <= 0 {
+            return Err(AacError::NChansTooHigh {
+                n_chans: n_chans.max(0) as usize,
+            });
+        }
+        let n_chans = n_chans as usize;
+
+        /* will be set later if active in this frame */
+        self.m_AACDecInfo.tnsUsed = 0;
+        self.m_AACDecInfo.pnsUsed = 0;
+
+        /* the output of a channel element waits for the next element, which may be the fill
+         * element with its SBR data; the frame size is fixed by the first element output, after
+         * its SBR data (PS data in it may switch the output to stereo)
+         */
+        let mut pending: Option<(usize, usize)> = None;
+        let mut n_samps = 0;
+        let mut out_chans = n_chans;
+        let bits_avail = input.len() as i32 * 8;
+        let mut bsi = BitStreamInfo::from_slice(input);
+        let mut base_chan = 0;
+        loop {
+            /* parse next syntactic element */
+            let start = bsi.calc_bits_used(input, 0);
+            elements::decode_next_element(self, &mut bsi).map_err(|()| {
+                AacError::SyntaxElement {
+                    offset: start as usize / 8,
+                }
+            })?;
+            if bsi.calc_bits_used(input, 0) > bits_avail {
+                return Err(AacError::InDataUnderflow {
+                    offset: start as usize / 8,
+                });
+            }
+            if let Some(crc) = crc.as_deref_mut() {
+                let end = bsi.calc_bits_used(input, 0) as usize;
+                crc.element(input, self.m_AACDecInfo.currBlockID, start as usize, end);
+            }
+
+            /* SBR data follows the SCE or CPE it belongs to, its presence enables SBR for the
+             * rest of the stream (implicit signalling) if the output rate would be valid
+             */
+            let info = &mut self.m_AACDecInfo;
+            let sbr_fill = info.currBlockID == ElementId::Fil
+                && matches!(info.prevBlockID, ElementId::Sce | ElementId::Cpe)
+                && matches!(info.fillExtType, sbr::EXT_SBR_DATA | sbr::EXT_SBR_DATA_CRC);
+            if sbr_fill && self.m_PSInfoBase.sampRateIdx >= 3 {
+                info.sbrEnabled = 1;
+            }
+
+            if let Some((ch_base, ch_block)) = pending.take() {
+                let frame_sbr = if n_samps == 0 {
+                    self.sbr_enabled()
+                } else {
+                    n_samps > out_chans * AAC_MAX_NSAMPS
+                };
+                if sbr_fill && frame_sbr {
+                    sbr::decode_sbr_bitstream(self, ch_base).map_err(|()| {
+                        AacError::SbrBitstream {
+                            offset: start as usize / 8,
+                        }
+                    })?;
+                }
+                if n_samps == 0 {
+                    n_samps = self.output_samps();
+                    out_chans = self.channels() as usize;
+                    if out.len() < n_samps {
+                        return Err(AacError::OutputTooSmall { needed: n_samps });
+                    }
+                }
+                let ps_out = out_chans > n_chans;
+                self.output_element(
+                    ch_base,
+                    ch_block,
+                    frame_sbr,
+                    sbr_fill,
+                    ps_out,
+                    &mut out[..n_samps],
+                );
+            }
+
+            let element_chans = self.m_AACDecInfo.currBlockID.num_chans();
+            if base_chan + element_chans > n_chans {
+                return Err(AacError::NChansTooHigh { n_chans });
+            }
+
+            /* noiseless decoder and dequantizer */
+            for ch in 0..element_chans {
+                let start = bsi.calc_bits_used(input, 0);
+                ics::decode_noiseless_data(self, &mut bsi, ch).map_err(|()| {
+                    AacError::SyntaxElement {
+                        offset: start as usize / 8,
+                    }
+                })?;
+                if bsi.calc_bits_used(input, 0) > bits_avail {
+                    return Err(AacError::InDataUnderflow {
+                        offset: start as usize / 8,
+                    });
+                }
+                if let Some(crc) = crc.as_deref_mut() {
+                    let end = bsi.calc_bits_used(input, 0) as usize;
+                    crc.channel(input, ch, start as usize, end);
+                }
+                dequant::aac_dequantize(self, ch);
+            }
+
+            /* mid-side and intensity stereo */
+            if self.m_AACDecInfo.currBlockID == ElementId::Cpe {
+                stereo::stereo_process(self);
+            }
+
+            /* PNS, TNS, inverse transform */
+            for ch in 0..element_chans {
+                pns::pns(self, ch);
+                tns::tns_filter(self, ch);
+                imdct::imdct(self, ch, base_chan + ch);
+            }
+
+            if element_chans > 0 {
+                pending = Some((base_chan, element_chans));
+            }
+            base_chan += element_chans;
+            if self.m_AACDecInfo.currBlockID == ElementId::End {
+                break;
+            }
+        }
+
+        if n_samps == 0 {
+            n_samps = self.output_samps();
+            out_chans = self.channels() as usize;
+            if out.len() < n_samps {
+                return Err(AacError::OutputTooSmall { needed: n_samps });
+            }
+        }
+        let out = &mut out[..n_samps];
+
+        /* a stereo configuration with a single channel element plays it on both sides (with PS
+         * both are already written), a block without any channel element is silence
+         */
+        if base_chan == 0 {
+            out.fill(0);
+        } else if base_chan < n_chans {
+            for frame in out.chunks_exact_mut(n_chans) {
+                frame[1] = frame[0];
+            }
+        }
+
+        /* byte align after each raw_data_block */
+        let bytes = (bsi.calc_bits_used(input, 0) as usize).div_ceil(8);
+        *consumed = bytes;
+
+        let info = &mut self.m_AACDecInfo;
+        info.frameCount += 1;
+        info.bitRate = (bytes as u64 * 8 * info.sampRate as u64 / AAC_MAX_NSAMPS as u64) as i32;
+        self.m_AACFrameInfo = AACFrameInfo {
+            bitRate: info.bitRate,
+            nChans: out_chans as i32,
+            sampRateCore: info.sampRate,
+            sampRateOut: info.sampRate << (info.sbrEnabled != 0) as u32,
+            bitsPerSample: 16,
+            outputSamps: n_samps as i32,
+            profile: info.profile,
+            tnsUsed: info.tnsUsed,
+            pnsUsed: info.pnsUsed,
+        };
+
+        Ok(n_samps)
+    }
+
+    /* round the element in rawSampleBuf to PCM, through SBR if the frame is output at the SBR
+     * rate (just the QMF banks, if the element has no SBR data), the SBR data is already decoded
+     * ps_out: the frame is output in stereo through parametric stereo
+     */
+    fn output_element(
+        &mut self,
+        ch_base: usize,
+        ch_block: usize,
+        frame_sbr: bool,
+        sbr_fill: bool,
+        ps_out: bool,
+        out: &mut [i16],
+    ) {
+        if !frame_sbr {
+            let n_chans = self.m_AACDecInfo.nChans as usize;
+            for ch in 0..ch_block {
+                output_channel(
+                    &self.m_PSInfoBase.rawSampleBuf[ch],
+                    out,
+                    ch_base + ch,
+                    n_chans,
+                );
+            }
+            return;
+        }
+
+        sbr::decode_sbr_data(self, ch_base, ch_block, sbr_fill, ps_out, out);
+    }
+
+    /* sets the decoder up for ADTS frames of format, unless it already decodes those */
+    fn set_adts_format(&mut self, format: &adts::AdtsFormat) -> Result<(), AacError> {
+        let info = &self.m_AACDecInfo;
+        if info.format == AacFormat::Adts
+            && info.profile == format.profile as i32
+            && info.nChans == format.channel_config as i32
+            && self.m_PSInfoBase.sampRateIdx == format.samp_rate_idx as usize
+        {
+            return Ok(());
+        }
+        self.set_audio_specific_config(&format.audio_specific_config())?;
+        self.m_AACDecInfo.format = AacFormat::Adts;
+        Ok(())
+    }
+
+    /* silences the output of a block that failed its CRC, crc_word is the CRC the frame carries */
+    fn crc_mismatch(&self, crc_word: &[u8], computed: u16, out: &mut [i16]) -> AacError {
+        let n_samps = self.output_samps().min(out.len());
+        out[..n_samps].fill(0);
+        AacError::CrcMismatch {
+            expected: u16::from_be_bytes([crc_word[0], crc_word[1]]),
+            computed,
+        }
+    }
+
+    /* reset per-block state variables for raw blocks (no ADTS/ADIF headers) */
+    fn prepare_raw_block(&mut self) {
+        /* syntactic element fields will be read from bitstream for each element */
+        self.m_AACDecInfo.prevBlockID = ElementId::Invalid;
+        self.m_AACDecInfo.currBlockID = ElementId::Invalid;
+        self.m_AACDecInfo.currInstTag = -1;
+
+        /* fill in user-accessible data */
+        self.m_AACDecInfo.bitRate = 0;
+    }
+}
+
+/* round the windowed samples of one channel to PCM, interleaved */
+fn output_channel(raw: &[i32; AAC_MAX_NSAMPS], out: &mut [i16], ch_out: usize, n_chans: usize) {
+    for (pcm, &x) in out[ch_out..].iter_mut().step_by(n_chans).zip(raw.iter()) {
+        *pcm = clip_to_short(x.wrapping_add(1 << (FBITS_OUT_IMDCT - 1)), FBITS_OUT_IMDCT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::boxed::Box;
+
+    #[test]
+    fn raw_block_params_validate_rate_and_profile() {
+        let mut dec = Box::<AACDecoder>::default();
+        assert_eq!(
+            dec.set_raw_block_params(false, 2, 44100, AAC_PROFILE_LC),
+            Ok(())
+        );
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.output_samps()),
+            (44100, 2, 2048)
+        );
+        assert_eq!(
+            dec.set_raw_block_params(false, 2, 44000, AAC_PROFILE_LC),
+            Err(AacError::InvalidSampleRate { samp_rate: 44000 })
+        );
+        assert_eq!(
+            dec.set_raw_block_params(false, 1, 8000, AAC_PROFILE_MAIN),
+            Err(AacError::RawBlockParams)
+        );
+        /* copyLast keeps the previous rate and channels */
+        dec.set_raw_block_params(false, 1, 22050, AAC_PROFILE_LC)
+            .unwrap();
+        dec.set_raw_block_params(true, 2, 96000, AAC_PROFILE_LC)
+            .unwrap();
+        assert_eq!((dec.samp_rate(), dec.channels()), (22050, 1));
+    }
+
+    #[test]
+    fn random_blocks_do_not_panic() {
+        let mut dec = Box::<AACDecoder>::default();
+        let mut out = [0i16; AAC_MAX_NCHANS * AAC_MAX_SBR_NSAMPS];
+        let mut input = [0u8; 768];
+        let mut seed = 0x1234_5678u32;
+        for iter in 0..400 {
+            let n_chans = 1 + iter % 2;
+            dec.set_raw_block_params(
+                false,
+                n_chans as i32,
+                SAMP_RATE_TAB[iter % NUM_SAMPLE_RATES],
+                AAC_PROFILE_LC,
+            )
+            .unwrap();
+            for b in input.iter_mut() {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                *b = (seed >> 24) as u8;
+            }
+            /* mostly start with a channel element so the noiseless decoder gets to run */
+            if iter % 4 != 0 {
+                input[0] = (input[0] & 0x1f) | if n_chans == 2 { 0x20 } else { 0x00 };
+            }
+            if let Ok(frame) = dec.decode_frame(&input[..iter % input.len() + 1], &mut out) {
+                assert!(frame.bytes_consumed <= iter % input.len() + 1);
+                assert_eq!(frame.samples_written, dec.output_samps());
+            }
+        }
+    }
+
+    /* pack (value, bits) pairs MSB first, zero padded to a whole byte */
+    fn pack(fields: &[(u32, u32)]) -> std::vec::Vec<u8> {
+        let mut buf = std::vec::Vec::new();
+        let mut pos = 0;
+        for &(v, n) in fields {
+            for b in (0..n).rev() {
+                if pos % 8 == 0 {
+                    buf.push(0);
+                }
+                if (v >> b) & 1 != 0 {
+                    *buf.last_mut().unwrap() |= 0x80 >> (pos % 8);
+                }
+                pos += 1;
+            }
+        }
+        buf
+    }
+
+    /* a silent SCE (no scalefactor bands), then a fill element with the given payload */
+    fn sce_with_fill(payload: &[u8]) -> std::vec::Vec<u8> {
+        let mut fields = std::vec![(0, 3), (0, 4), (100, 8), (0, 1 + 2 + 1 + 6 + 1), (0, 3)];
+        fields.push((ElementId::Fil as u32, 3));
+        if payload.len() < 15 {
+            fields.push((payload.len() as u32, 4));
+        } else {
+            fields.extend([(15, 4), (payload.len() as u32 - 14, 8)]);
+        }
+        fields.extend(payload.iter().map(|&b| (b as u32, 8)));
+        fields.push((ElementId::End as u32, 3));
+        pack(&fields)
+    }
+
+    #[test]
+    fn audio_specific_config_sets_up_sbr() {
+        let mut dec = Box::<AACDecoder>::default();
+        dec.set_audio_specific_config(&[0x12, 0x10]).unwrap(); /* LC, 44100, stereo */
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.sbr_enabled()),
+            (44100, 2, false)
+        );
+
+        /* HE-AAC, 24000 core and 48000 output, stereo */
+        dec.set_audio_specific_config(&[0x2b, 0x11, 0x88, 0x00])
+            .unwrap();
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.sbr_enabled()),
+            (48000, 2, true)
+        );
+        assert_eq!(dec.output_samps(), 2 * AAC_MAX_SBR_NSAMPS);
+
+        /* HE-AACv2 signalling, a mono core output in stereo unless PS is off */
+        dec.set_audio_specific_config(&[0xeb, 0x8a, 0x08, 0x00])
+            .unwrap();
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.sbr_enabled()),
+            (44100, 2, true)
+        );
+        assert_eq!(dec.output_samps(), 2 * AAC_MAX_SBR_NSAMPS);
+        dec.set_parametric_stereo(false);
+        dec.reset();
+        dec.set_audio_specific_config(&[0xeb, 0x8a, 0x08, 0x00])
+            .unwrap();
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.sbr_enabled()),
+            (44100, 1, true)
+        );
+        dec.set_parametric_stereo(true);
+
+        /* LC 22050 mono with the backward compatible SBR extension */
+        dec.set_audio_specific_config(&[0x13, 0x88, 0x56, 0xe5, 0xa0])
+            .unwrap();
+        assert_eq!(
+            (dec.samp_rate(), dec.channels(), dec.sbr_enabled()),
+            (44100, 1, true)
+        );
+        /* the same without the extension resets SBR */
+        dec.set_audio_specific_config(&[0x13, 0x88]).unwrap();
+        assert_eq!((dec.samp_rate(), dec.sbr_enabled()), (22050, false));
+
+        assert_eq!(
+            dec.set_audio_specific_config(&[0x2b, 0x13, 0x08, 0x00]),
+            Err(AacError::SbrSingleRate)
+        );
+        assert_eq!(
+            dec.set_audio_specific_config(&[0x0a, 0x10]), /* AAC Main */
+            Err(AacError::RawBlockParams)
+        );
+        assert_eq!(
+            dec.set_audio_specific_config(&[0x12]),
+            Err(AacError::InDataUnderflow { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn sbr_fill_element_enables_sbr() {
+        let mut dec = Box::<AACDecoder>::default();
+        dec.set_raw_block_params(false, 1, 22050, AAC_PROFILE_LC)
+            .unwrap();
+        let mut out = [1i16; AAC_MAX_SBR_NSAMPS];
+
+        /* a fill element that isn't SBR changes nothing */
+        let block = sce_with_fill(&[0x00, 0xa5]);
+        let frame = dec.decode_frame(&block, &mut out).unwrap();
+        assert_eq!(
+            (frame.samples_written, frame.info.sampRateOut),
+            (AAC_MAX_NSAMPS, 22050)
+        );
+
+        /* SBR data without a header yet: upsampled only, at twice the rate from now on */
+        let block = sce_with_fill(&[(sbr::EXT_SBR_DATA << 4), 0x00]);
+        let frame = dec.decode_frame(&block, &mut out).unwrap();
+        assert_eq!(frame.bytes_consumed, block.len());
+        assert_eq!(
+            (frame.samples_written, frame.info.sampRateOut),
+            (AAC_MAX_SBR_NSAMPS, 44100)
+        );
+        assert_eq!(frame.info.sampRateCore, 22050);
+        assert!(out.iter().all(|&x| x == 0));
+        assert_eq!(dec.samp_rate(), 44100);
+
+        let block = sce_with_fill(&[0x00, 0xa5]);
+        assert_eq!(
+            dec.decode_frame(&block, &mut out).unwrap().samples_written,
+            AAC_MAX_SBR_NSAMPS
+        );
+        assert_eq!(
+            dec.decode_frame(&block, &mut out[..AAC_MAX_NSAMPS])
+                .unwrap_err(),
+            AacError::OutputTooSmall {
+                needed: AAC_MAX_SBR_NSAMPS
+            }
+        );
+
+        /* above 96 kHz output SBR can't be enabled, it stays at the core rate */
+        dec.set_raw_block_params(false, 1, 64000, AAC_PROFILE_LC)
+            .unwrap();
+        let block = sce_with_fill(&[(sbr::EXT_SBR_DATA << 4), 0x00]);
+        assert_eq!(
+            dec.decode_frame(&block, &mut out).unwrap().samples_written,
+            AAC_MAX_NSAMPS
+        );
+    }
+
+    #[test]
+    fn random_sbr_data_does_not_panic() {
+        let mut dec = Box::<AACDecoder>::default();
+        let mut out = [0i16; 2 * AAC_MAX_SBR_NSAMPS];
+        let mut payload = [0u8; 40];
+        let mut seed = 0x8765_4321u32;
+        dec.set_raw_block_params(false, 1, 24000, AAC_PROFILE_LC)
+            .unwrap();
+        for iter in 0..2000 {
+            for b in payload.iter_mut() {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                *b = (seed >> 24) as u8;
+            }
+            /* SBR data with a header: bs_amp_res, start and stop frequency, crossover band */
+            payload[0] = (sbr::EXT_SBR_DATA << 4) | 0x08 | (payload[0] & 0x07);
+            let len = 2 + iter % (payload.len() - 1);
+            match dec.decode_frame(&sce_with_fill(&payload[..len]), &mut out) {
+                /* stereo once a payload happens to carry a valid PS header */
+                Ok(frame) => assert_eq!(
+                    frame.samples_written,
+                    frame.info.nChans as usize * AAC_MAX_SBR_NSAMPS
+                ),
+                Err(e) => assert!(matches!(e, AacError::SbrBitstream { .. }), "{e}"),
+            }
+        }
+    }
+
+    /* ADTS header of an LC 44.1 kHz frame of frame_bytes with blocks raw data blocks */
+    fn adts_header(channel_config: u8, protected: bool, frame_bytes: usize, blocks: u8) -> [u8; 7] {
+        [
+            0xff,
+            if protected { 0xf0 } else { 0xf1 },
+            0x50 | (channel_config >> 2),
+            (channel_config << 6) | (frame_bytes >> 11) as u8,
+            (frame_bytes >> 3) as u8,
+            ((frame_bytes as u8 & 0x07) << 5) | 0x1f,
+            0xfc | (blocks - 1),
+        ]
+    }
+
+    /* a silent SCE, alone in the block: 26 bits after the element id, 4 bytes in all */
+    fn silent_sce_block() -> std::vec::Vec<u8> {
+        pack(&[
+            (0, 3),
+            (0, 4),
+            (100, 8),
+            (0, 11),
+            (0, 3),
+            (ElementId::End as u32, 3),
+        ])
+    }
+
+    #[test]
+    fn adts_frames_set_up_the_decoder() {
+        let mut dec = Box::<AACDecoder>::default();
+        let mut out = [1i16; 2 * AAC_MAX_SBR_NSAMPS];
+        let block = silent_sce_block();
+
+        let mut frame = adts_header(1, false, 11, 1).to_vec();
+        frame.extend_from_slice(&block);
+        let decoded = dec.decode_adts_frame(&frame, &mut out).unwrap();
+        assert_eq!(
+            (decoded.bytes_consumed, decoded.samples_written),
+            (11, 1024)
+        );
+        assert_eq!(dec.format(), AacFormat::Adts);
+        assert_eq!((dec.samp_rate(), dec.channels()), (44100, 1));
+        assert!(out[..1024].iter().all(|&x| x == 0));
+
+        /* two blocks, one call each; the last one takes the padding at the end of the frame */
+        let mut frame = adts_header(1, false, 17, 2).to_vec();
+        frame.extend_from_slice(&block);
+        frame.extend_from_slice(&block);
+        frame.extend_from_slice(&[0, 0]);
+        let first = dec.decode_adts_frame(&frame, &mut out).unwrap();
+        assert_eq!(first.bytes_consumed, 11);
+        let second = dec.decode_adts_frame(&frame[11..], &mut out).unwrap();
+        assert_eq!((second.bytes_consumed, second.samples_written), (6, 1024));
+
+        /* a new channel configuration sets the decoder up again */
+        let mut frame = adts_header(2, false, 11, 1).to_vec();
+        frame.extend_from_slice(&block);
+        let decoded = dec.decode_adts_frame(&frame, &mut out).unwrap();
+        assert_eq!((dec.channels(), decoded.samples_written), (2, 2048));
+
+        let mut consumed = 1;
+        assert_eq!(
+            dec.decode_adts(&frame[..10], &mut out, false, &mut consumed),
+            Err(AacError::InDataUnderflow { offset: 0 })
+        );
+        assert_eq!(consumed, 0);
+        assert_eq!(
+            dec.decode_adts_frame(&block, &mut out).unwrap_err(),
+            DecodeError {
+                error: AacError::InvalidAdtsHeader,
+                bytes_consumed: 0
+            }
+        );
+        /* raw blocks again once the parameters are set */
+        dec.set_raw_block_params(false, 1, 44100, AAC_PROFILE_LC)
+            .unwrap();
+        assert_eq!(
+            dec.decode_frame(&block, &mut out).unwrap().bytes_consumed,
+            4
+        );
+    }
+
+    #[test]
+    fn adts_crc_is_checked() {
+        let mut dec = Box::<AACDecoder>::default();
+        let mut out = [1i16; AAC_MAX_SBR_NSAMPS];
+        let block = silent_sce_block();
+
+        /* header, then the SCE from its instance tag on, zero padded to 192 bits */
+        let mut frame = adts_header(1, true, 13, 1).to_vec();
+        let mut crc = crc16(0xffff, &frame);
+        crc = crate::utils::crc16::crc16_bits(crc, &block, 3, 26);
+        crc = crate::utils::crc16::crc16_bits(crc, &[], 0, 192 - 26);
+        frame.extend_from_slice(&crc.to_be_bytes());
+        frame.extend_from_slice(&block);
+        let decoded = dec.decode_adts_frame_verified(&frame, &mut out).unwrap();
+        assert_eq!(
+            (decoded.bytes_consumed, decoded.samples_written),
+            (13, 1024)
+        );
+
+        /* a damaged global gain is only noticed when checking */
+        frame[10] ^= 0x04;
+        assert!(dec.decode_adts_frame(&frame, &mut out).is_ok());
+        let e = dec
+            .decode_adts_frame_verified(&frame, &mut out)
+            .unwrap_err();
+        assert!(matches!(e.error, AacError::CrcMismatch { expected, .. } if expected == crc));
+        assert_eq!(e.bytes_consumed, 13);
+        assert!(out[..1024].iter().all(|&x| x == 0));
+    }
+}
@@ -0,0 +1,234 @@
+//! Perceptual noise substitution (14496-3, 4.6.13): scalefactor bands coded with the noise
+//! codebook are filled with random values scaled to the transmitted energy.
+
+use super::{
+    AACDecoder, ElementId, FBITS_OUT_DQ_OFF, NSAMPS_LONG, NSAMPS_SHORT, WindowSequence, sfb_table,
+};
+use crate::mp3_decoder::mulshift_32;
+
+const NUM_ITER_INVSQRT: usize = 4;
+const X0_COEF_2: i32 = 0xc0000000u32 as i32; /* Q29: -2.0 */
+const X0_OFF_2: i32 = 0x60000000; /* Q29:  3.0 */
+const Q26_3: i32 = 0x0c000000; /* Q26:  3.0 */
+
+/* pow(2, i/4.0) for i = [0,1,2,3], format = Q30 */
+const POW14: [i32; 4] = [0x40000000, 0x4c1bf829, 0x5a82799a, 0x6ba27e65];
+
+/***********************************************************************************************************************
+ * Function:    Get32BitVal
+ *
+ * Description: generate 32-bit unsigned random number
+ *
+ * Inputs:      last number calculated (seed, first time through)
+ *
+ * Outputs:     new number, saved in *last
+ *
+ * Return:      32-bit number, uniformly distributed between [0, 2^32)
+ *
+ * Notes:       uses simple linear congruential generator
+ **********************************************************************************************************************/
+pub fn get_32bit_val(last: &mut u32) -> u32 {
+    /* use same coefs as MPEG reference code (classic LCG) */
+    *last = last.wrapping_mul(1664525).wrapping_add(1013904223);
+    *last
+}
+
+/***********************************************************************************************************************
+ * Function:    InvRootR
+ *
+ * Description: use Newton's method to solve for x = 1/sqrt(r)
+ *
+ * Inputs:      r in Q30 format, range = [0.25, 1] (normalize inputs to this range)
+ *
+ * Outputs:     none
+ *
+ * Return:      x = Q29, range = (1, 2)
+ *
+ * Notes:       guaranteed to converge and not overflow for any r in this range
+ *
+ *              xn+1  = xn - f(xn)/f'(xn)
+ *              f(x)  = 1/sqrt(r) - x = 0 (find root)
+ *                    = 1/x^2 - r
+ *              f'(x) = -2/x^3
+ *
+ *              so xn+1 = xn/2 * (3 - r*xn^2)
+ *
+ *              NUM_ITER_INVSQRT = 3, maxDiff = 1.3747e-02
+ *              NUM_ITER_INVSQRT = 4, maxDiff = 3.9832e-04
+ **********************************************************************************************************************/
+pub fn inv_root_r(r: i32) -> i32 {
+    /* use linear equation for initial guess
+     * x0 = -2*r + 3 (so x0 always >= correct answer in range [0.25, 1))
+     * xn = Q29 (at every step)
+     */
+    let mut xn = (mulshift_32(r, X0_COEF_2) << 2).wrapping_add(X0_OFF_2);
+
+    for _ in 0..NUM_ITER_INVSQRT {
+        let t = mulshift_32(xn, xn); /* Q26 = Q29*Q29 */
+        let t = Q26_3.wrapping_sub(mulshift_32(r, t) << 2); /* Q26 = Q26 - (Q31*Q26 << 1) */
+        xn = mulshift_32(xn, t) << (6 - 1); /* Q29 = (Q29*Q26 << 6), and -1 for division by 2 */
+    }
+
+    /* clip to range (1.0, 2.0)
+     * (because of rounding, this can converge to xn slightly > 2.0 when r is near 0.25)
+     */
+    if xn >> 30 != 0 { (1 << 30) - 1 } else { xn }
+}
+
+/***********************************************************************************************************************
+ * Function:    ScaleNoiseVector
+ *
+ * Description: apply scaling to vector of noise coefficients for one scalefactor band
+ *
+ * Inputs:      unscaled coefficients, range = [-2^15, 2^15)
+ *              scalefactor for this band (i.e. noise energy)
+ *
+ * Outputs:     coefficients in Q(FBITS_OUT_DQ_OFF)
+ *
+ * Return:      guard bit mask (OR of abs value of all noise coefs)
+ **********************************************************************************************************************/
+pub fn scale_noise_vector(coef: &mut [i32], sf: i32) -> u32 {
+    /* max width = max SFB width = 96, so energy can gain < 2^7 bits in accumulation */
+    let energy: i32 = coef.iter().map(|&spec| (spec * spec) >> 8).sum();
+
+    /* unless the band is 1 wide (or the number generator is broken...), this should not happen */
+    if energy == 0 {
+        return 0; /* coef[i] must = 0 for all i, so gbMask = 0 */
+    }
+
+    /* pow(2, sf/4) * pow(2, FBITS_OUT_DQ_OFF) */
+    let mut scalef = POW14[(sf & 0x3) as usize];
+    let mut scalei = (sf >> 2) + FBITS_OUT_DQ_OFF;
+
+    /* energy has implied factor of 2^-8 since we shifted the accumulator
+     * normalize energy to range [0.25, 1.0), calculate 1/sqrt(1), and denormalize
+     *   i.e. divide input by 2^(30-z) and convert to Q30
+     *        output of 1/sqrt(i) now has extra factor of 2^((30-z)/2)
+     *        for energy > 0, z is an even number between 0 and 28
+     * final scaling of invSqrtEnergy:
+     *  2^(15 - z/2) to compensate for implicit 2^(30-z) factor in input
+     *  +4 to compensate for implicit 2^-8 factor in input
+     */
+    let z = (energy.leading_zeros() as i32 - 2) & !1; /* energy has at least 2 leading zeros, force even */
+    let mut inv_sqrt_energy = inv_root_r(energy << z); /* energy << z must be in range [0x10000000, 0x40000000] */
+    scalei -= 15 - z / 2 + 4; /* nInt = 1/sqrt(energy) in Q29 */
+
+    /* normalize for final scaling */
+    let z = inv_sqrt_energy.leading_zeros() as i32 - 1;
+    inv_sqrt_energy <<= z;
+    scalei -= z - 3 - 2; /* -2 for scalef, z-3 for invSqrtEnergy */
+    scalef = mulshift_32(scalef, inv_sqrt_energy); /* scalef (input) = Q30, invSqrtEnergy = Q29 * 2^z */
+
+    let mut gb_mask = 0;
+    if scalei < 0 {
+        let scalei = (-scalei).min(31);
+        for c in coef.iter_mut() {
+            *c = mulshift_32(*c, scalef) >> scalei;
+            gb_mask |= c.unsigned_abs();
+        }
+    } else {
+        /* for scalei <= 16, no clipping possible (coef[i] is < 2^15 before scaling)
+         * for scalei > 16, just saturate exponent (rare)
+         *   scalef is close to full-scale (since we normalized invSqrtEnergy)
+         * remember, we are just producing noise here
+         */
+        let scalei = scalei.min(16);
+        for c in coef.iter_mut() {
+            *c = mulshift_32(*c << scalei, scalef);
+            gb_mask |= c.unsigned_abs();
+        }
+    }
+
+    gb_mask
+}
+
+/***********************************************************************************************************************
+ * Function:    GenerateNoiseVector
+ *
+ * Description: create vector of noise coefficients for one scalefactor band
+ *
+ * Inputs:      seed for number generator
+ *
+ * Outputs:     buffer of coefficients, range = [-2^15, 2^15)
+ *              updated seed for number generator
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn generate_noise_vector(coef: &mut [i32], last: &mut u32) {
+    for c in coef {
+        *c = (get_32bit_val(last) as i32) >> 16;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    PNS
+ *
+ * Description: apply perceptual noise substitution, if enabled (MPEG-4 only)
+ *
+ * Inputs:      index of current channel
+ *
+ * Outputs:     shaped noise in scalefactor bands where PNS is active
+ *              updated minimum guard bit count for this channel
+ *
+ * Return:      none
+ *
+ * Notes:       in a CPE with a common window, noise in a band that is M/S coded and noise in
+ *                both channels is the same for both channels (correlated), generated once
+ *                for channel 0 and copied to channel 1
+ **********************************************************************************************************************/
+pub fn pns(dec: &mut AACDecoder, ch: usize) {
+    let psi = &mut dec.m_PSInfoBase;
+    if psi.pnsUsed[ch] == 0 {
+        return;
+    }
+
+    let ics = psi.icsInfo[psi.ics_index(ch)];
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let n_samps = if ics.winSequence == WindowSequence::EightShort {
+        NSAMPS_SHORT
+    } else {
+        NSAMPS_LONG
+    };
+    let max_sfb = ics.maxSFB as usize;
+    let check_corr = dec.m_AACDecInfo.currBlockID == ElementId::Cpe && psi.commonWin == 1;
+
+    let mut gb_mask = 0;
+    let mut win = 0;
+    for gp in 0..ics.numWinGroup as usize {
+        for _ in 0..ics.winGroupLen[gp] {
+            for sfb in 0..max_sfb {
+                let idx = gp * max_sfb + sfb;
+                if psi.sfbCodeBook[ch][idx] != 13 {
+                    continue;
+                }
+                let band = win * n_samps + sfb_tab[sfb] as usize
+                    ..win * n_samps + sfb_tab[sfb + 1] as usize;
+                let [coef_l, coef_r] = &mut psi.coef;
+                if ch == 0 {
+                    /* generate new vector, copy into ch 1 if it's possible that the channels will be correlated
+                     * if ch 1 has PNS enabled for this SFB but it's uncorrelated (i.e. ms_used == 0),
+                     *    the copied values will be overwritten when we process ch 1
+                     */
+                    generate_noise_vector(&mut coef_l[band.clone()], &mut psi.pnsLastVal);
+                    if check_corr && psi.sfbCodeBook[1][idx] == 13 {
+                        coef_r[band.clone()].copy_from_slice(&coef_l[band.clone()]);
+                    }
+                } else {
+                    /* generate new vector if no correlation between channels */
+                    let ms = psi.msMaskPresent == 2
+                        || (psi.msMaskPresent == 1
+                            && (psi.msMaskBits[idx >> 3] >> (idx & 0x07)) & 0x01 != 0);
+                    if !(check_corr && psi.sfbCodeBook[0][idx] == 13 && ms) {
+                        generate_noise_vector(&mut coef_r[band.clone()], &mut psi.pnsLastVal);
+                    }
+                }
+                gb_mask |=
+                    scale_noise_vector(&mut psi.coef[ch][band], psi.scaleFactors[ch][idx] as i32);
+            }
+            win += 1;
+        }
+    }
+
+    /* update guard bit count if necessary */
+    psi.gbCurrent[ch] = psi.gbCurrent[ch].min(gb_mask.leading_zeros() as i32 - 1);
+}
@@ -0,0 +1,203 @@
+//! 32-point complex FFT used by the 64-band QMF synthesis filterbank.
+
+use crate::mp3_decoder::{SQRTHALF, mulshift_32};
+
+/* twiddle table for radix 4 pass, format = Q31 */
+const TWID_TAB_ODD32: [u32; 48] = [
+    0x40000000, 0x00000000, 0x40000000, 0x00000000, 0x40000000, 0x00000000, 0x539eba45, 0xe7821d59,
+    0x4b418bbe, 0xf383a3e2, 0x58c542c5, 0xdc71898d, 0x5a82799a, 0xd2bec333, 0x539eba45, 0xe7821d59,
+    0x539eba45, 0xc4df2862, 0x539eba45, 0xc4df2862, 0x58c542c5, 0xdc71898d, 0x3248d382, 0xc13ad060,
+    0x40000000, 0xc0000000, 0x5a82799a, 0xd2bec333, 0x00000000, 0xd2bec333, 0x22a2f4f8, 0xc4df2862,
+    0x58c542c5, 0xcac933ae, 0xcdb72c7e, 0xf383a3e2, 0x00000000, 0xd2bec333, 0x539eba45, 0xc4df2862,
+    0xac6145bb, 0x187de2a7, 0xdd5d0b08, 0xe7821d59, 0x4b418bbe, 0xc13ad060, 0xa73abd3b, 0x3536cc52,
+];
+
+/* index pairs swapped by BitReverse32, in units of complex samples */
+const BITREV_PAIRS_32: [(usize, usize); 12] = [
+    (1, 16),
+    (2, 8),
+    (3, 24),
+    (5, 20),
+    (6, 12),
+    (7, 28),
+    (9, 18),
+    (11, 26),
+    (13, 22),
+    (15, 30),
+    (19, 25),
+    (23, 29),
+];
+
+/***********************************************************************************************************************
+ * Function:    BitReverse32
+ *
+ * Description: Ken's fast in-place bit reverse
+ *
+ * Inputs:      buffer of 32 complex samples
+ *
+ * Outputs:     bit-reversed samples in same buffer
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn bit_reverse_32(x: &mut [i32]) {
+    for &(a, b) in &BITREV_PAIRS_32 {
+        x.swap(2 * a, 2 * b);
+        x.swap(2 * a + 1, 2 * b + 1);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    R8FirstPass32
+ *
+ * Description: radix-8 trivial pass for decimation-in-time FFT (log2(N) = 5)
+ *
+ * Inputs:      buffer of (bit-reversed) samples
+ *
+ * Outputs:     processed samples in same buffer
+ *
+ * Return:      none
+ *
+ * Notes:       assumes 3 guard bits, gains 1 integer bit
+ *              guard bits out = guard bits in - 3 (if inputs are full scale)
+ *                or guard bits in - 2 (if inputs bounded to +/- sqrt(2)/2)
+ **********************************************************************************************************************/
+fn r8_first_pass_32(x: &mut [i32]) {
+    const SQRTHALF_I: i32 = SQRTHALF as i32;
+
+    for x in x[..64].chunks_exact_mut(16) {
+        let (r2, r3) = (x[8] - x[10], x[9] - x[11]);
+        let (r6, r7) = (x[12] - x[14], x[13] - x[15]);
+        let r10 = x[8] + x[10];
+        let r11 = x[9] + x[11];
+        let r12 = x[12] + x[14];
+        let r14 = x[13] + x[15];
+
+        let (r4, r5) = (r2 - r7, r2 + r7);
+        let (r8, r9) = (r3 - r6, r3 + r6);
+
+        let q2 = mulshift_32(SQRTHALF_I, r4 - r9);
+        let q3 = mulshift_32(SQRTHALF_I, r4 + r9);
+        let q6 = mulshift_32(SQRTHALF_I, r5 - r8);
+        let q7 = mulshift_32(SQRTHALF_I, r5 + r8);
+
+        let s4 = (r10 + r12) >> 1;
+        let s5 = (r10 - r12) >> 1;
+        let s8 = (r11 + r14) >> 1;
+        let s9 = (r11 - r14) >> 1;
+
+        /* real parts of the first four inputs */
+        let (a0, a1, a2, a3) = (x[0], x[2], x[4], x[6]);
+        let e = (a0 + a1) + (a2 + a3);
+        x[0] = s4 + (e >> 1);
+        x[8] = (e >> 1) - s4;
+        let e = e - 2 * (a2 + a3);
+        x[4] = (e >> 1) + s9;
+        x[12] = (e >> 1) - s9;
+        let re0 = e + (a2 + a3) - 2 * a1;
+        let re1 = (a2 + a3) - 2 * a3;
+
+        /* imaginary parts of the first four inputs */
+        let (b0, b1, b2, b3) = (x[1], x[3], x[5], x[7]);
+        let f = (b0 + b1) + (b2 + b3);
+        x[1] = s8 + (f >> 1);
+        x[9] = (f >> 1) - s8;
+        let f = f - 2 * (b2 + b3);
+        x[5] = (f >> 1) - s5;
+        x[13] = (f >> 1) + s5;
+        let im0 = f + (b2 + b3) - 2 * b1;
+        let im1 = (b2 + b3) - 2 * b3;
+
+        let t9 = re0 - im1;
+        let t10 = re0 + im1;
+        let t14 = im0 + re1;
+        let t4 = im0 - re1;
+
+        x[2] = (t10 >> 1) + q7;
+        x[3] = (t4 >> 1) - q6;
+        x[6] = (t9 >> 1) - q2;
+        x[7] = (t14 >> 1) - q3;
+        x[10] = (t10 >> 1) - q7;
+        x[11] = (t4 >> 1) + q6;
+        x[14] = (t9 >> 1) + q2;
+        x[15] = (t14 >> 1) + q3;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    R4Core32
+ *
+ * Description: radix-4 pass for 32-point decimation-in-time FFT
+ *
+ * Inputs:      buffer of samples
+ *
+ * Outputs:     processed samples in same buffer
+ *
+ * Return:      none
+ *
+ * Notes:       gain 2 integer bits
+ *              guard bits out = guard bits in - 1 (if inputs are full scale)
+ *              uses 3-mul, 3-add butterflies instead of 4-mul, 2-add
+ **********************************************************************************************************************/
+fn r4_core_32(x: &mut [i32]) {
+    /* one leg of the butterfly: (cos*r + sin*i, cos*i - sin*r) */
+    #[inline(always)]
+    fn twiddle(w: &[u32], r: i32, i: i32) -> (i32, i32) {
+        let (ws, wi) = (w[0] as i32, w[1] as i32);
+        let wd = ws.wrapping_add(wi.wrapping_mul(2));
+        let t = mulshift_32(wi, r + i);
+        (mulshift_32(wd, r) - t, mulshift_32(ws, i) + t)
+    }
+
+    for (n, w) in TWID_TAB_ODD32.chunks_exact(6).enumerate() {
+        let a = 2 * n;
+        let (r4, r5) = twiddle(&w[0..2], x[a + 16], x[a + 17]);
+        let (r6, r7) = twiddle(&w[2..4], x[a + 32], x[a + 33]);
+        let (r8, r9) = twiddle(&w[4..6], x[a + 48], x[a + 49]);
+
+        let (r2, r3) = (x[a], x[a + 1]);
+
+        let r12 = r6 + r8;
+        let r8 = r6 - r8;
+        let r14 = r9 - r7;
+        let r9 = r9 + r7;
+
+        let r6 = (r2 >> 2) - r4;
+        let r7 = (r3 >> 2) - r5;
+        let r4 = r4 + (r2 >> 2);
+        let r5 = r5 + (r3 >> 2);
+
+        x[a] = r4 + r12;
+        x[a + 1] = r5 + r9;
+        x[a + 16] = r6 - r14;
+        x[a + 17] = r7 - r8;
+        x[a + 32] = r4 - r12;
+        x[a + 33] = r5 - r9;
+        x[a + 48] = r6 + r14;
+        x[a + 49] = r7 + r8;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    FFT32C
+ *
+ * Description: Ken's very fast in-place radix-4 decimation-in-time FFT
+ *
+ * Inputs:      buffer of 32 complex samples (before bit-reversal)
+ *
+ * Outputs:     processed samples in same buffer
+ *
+ * Return:      none
+ *
+ * Notes:       assumes 3 guard bits in, gains 3 integer bits
+ *              guard bits out = guard bits in - 2
+ *              (guard bit analysis includes assumptions about steps immediately
+ *               before and after, i.e. PreMul and PostMul for DCT)
+ **********************************************************************************************************************/
+pub fn fft32c(x: &mut [i32]) {
+    /* decimation in time */
+    bit_reverse_32(x);
+
+    /* 32-point complex FFT */
+    r8_first_pass_32(x); /* gain 1 int bit,  lose 2 GB (making assumptions about input) */
+    r4_core_32(x); /* gain 2 int bits, lose 0 GB (making assumptions about input) */
+}
@@ -0,0 +1,660 @@
+//! SBR frequency band tables (14496-3, 4.6.18.3.2): the master table from the header, the
+//! high/low resolution, noise floor and limiter tables derived from it, and the patches
+//! used by HF generation (4.6.18.6.3).
+//!
+//! Headers that would need tables outside the limits of 4.6.18.3.6 are rejected.
+
+use super::math::{LOG2_TAB, ratio_pow_inv};
+use super::{
+    MAX_NUM_NOISE_FLOOR_BANDS, MAX_NUM_PATCHES, MAX_QMF_BANDS, NUM_SAMPLE_RATES_SBR, SBRFreq,
+    SBRHeader,
+};
+use crate::mp3_decoder::mulshift_32;
+
+/* k0Tab[sampRateIdx][k] = k0 = startMin + offset(bs_start_freq) for given sample rate (4.6.18.3.2.1)
+ * downsampled (single-rate) SBR not currently supported
+ */
+const K0_TAB: [[u8; 16]; NUM_SAMPLE_RATES_SBR] = [
+    [5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 16, 18, 20, 23, 27, 31], /* 96 kHz */
+    [5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 16, 18, 20, 23, 27, 31], /* 88 kHz */
+    [6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 19, 21, 23, 26, 30], /* 64 kHz */
+    [7, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 20, 22, 24, 27, 31], /* 48 kHz */
+    [
+        8, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 21, 23, 25, 28, 32,
+    ], /* 44 kHz */
+    [
+        10, 12, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 25, 27, 29, 32,
+    ], /* 32 kHz */
+    [
+        11, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 25, 27, 29, 32,
+    ], /* 24 kHz */
+    [
+        12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 26, 28, 30,
+    ], /* 22 kHz */
+    [
+        16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    ], /* 16 kHz */
+];
+
+/* k2Tab[sampRateIdx][k] = stopVector(bs_stop_freq) for given sample rate, bs_stop_freq = [0, 13] (4.6.18.3.2.1)
+ * downsampled (single-rate) SBR not currently supported
+ */
+const K2_TAB: [[u8; 14]; NUM_SAMPLE_RATES_SBR] = [
+    [13, 15, 17, 19, 21, 24, 27, 31, 35, 39, 44, 50, 57, 64], /* 96 kHz */
+    [15, 17, 19, 21, 23, 26, 29, 33, 37, 41, 46, 51, 57, 64], /* 88 kHz */
+    [20, 22, 24, 26, 28, 31, 34, 37, 41, 45, 49, 54, 59, 64], /* 64 kHz */
+    [21, 23, 25, 27, 29, 32, 35, 38, 41, 45, 49, 54, 59, 64], /* 48 kHz */
+    [23, 25, 27, 29, 31, 34, 37, 40, 43, 47, 51, 55, 59, 64], /* 44 kHz */
+    [32, 34, 36, 38, 40, 42, 44, 46, 49, 52, 55, 58, 61, 64], /* 32 kHz */
+    [32, 34, 36, 38, 40, 42, 44, 46, 49, 52, 55, 58, 61, 64], /* 24 kHz */
+    [35, 36, 38, 40, 42, 44, 46, 48, 50, 52, 55, 58, 61, 64], /* 22 kHz */
+    [48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 60, 62, 64], /* 16 kHz */
+];
+
+/* NINT(2.048E6 / Fs) (figure 4.47)
+ * downsampled (single-rate) SBR not currently supported
+ */
+const GOAL_SB_TAB: [u8; NUM_SAMPLE_RATES_SBR] = [21, 23, 32, 43, 46, 64, 85, 93, 128];
+
+/* mBandTab[i] = temp1[i] / 2 */
+const M_BAND_TAB: [i32; 3] = [6, 5, 4];
+
+/* invWarpTab[i] = 1.0 / temp2[i], Q30 (see 4.6.18.3.2.1) */
+const INV_WARP_TAB: [i32; 2] = [0x40000000, 0x313b13b1];
+
+/* limiter bands per octave, [1.2, 2.0, 3.0] * 100 */
+const LIM_BANDS_PER_OCTAVE: [i32; 3] = [120, 200, 300];
+
+/***********************************************************************************************************************
+ * Function:    BubbleSort
+ *
+ * Description: in-place sort of uint8_ts
+ *
+ * Inputs:      buffer of elements to sort
+ *
+ * Outputs:     sorted buffer
+ *
+ * Return:      none
+ *
+ * Notes:       the lists are short (at most 29 entries), and the exact sort order of equal
+ *                elements doesn't matter, so a simple bubble sort is all we need
+ **********************************************************************************************************************/
+fn bubble_sort(v: &mut [u8]) {
+    let mut n_items = v.len();
+    while n_items >= 2 {
+        for i in 0..n_items - 1 {
+            if v[i + 1] < v[i] {
+                v.swap(i, i + 1);
+            }
+        }
+        n_items -= 1;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcFreqMasterScaleZero
+ *
+ * Description: calculate master frequency table when freqScale == 0
+ *                (4.6.18.3.2.1, figure 4.39)
+ *
+ * Inputs:      alterScale flag
+ *              index of first QMF subband in master freq table (k0)
+ *              index of last QMF subband (k2)
+ *
+ * Outputs:     master frequency table
+ *
+ * Return:      number of bands in master frequency table, or Err if it has more than
+ *                MAX_QMF_BANDS bands
+ *
+ * Notes:       assumes k2 > k0
+ **********************************************************************************************************************/
+fn calc_freq_master_scale_zero(
+    freq_master: &mut [u8; MAX_QMF_BANDS + 1],
+    alter_scale: u8,
+    k0: i32,
+    k2: i32,
+) -> Result<usize, ()> {
+    let (dk, n_bands) = if alter_scale != 0 {
+        (2, 2 * ((k2 - k0 + 2) >> 2))
+    } else {
+        (1, 2 * ((k2 - k0) >> 1))
+    };
+
+    if n_bands <= 0 {
+        return Ok(0);
+    }
+    let n_bands = n_bands as usize;
+    if n_bands > MAX_QMF_BANDS {
+        return Err(());
+    }
+
+    let mut v_dk = [dk; MAX_QMF_BANDS];
+
+    /* k2 - k2Achieved is in [-2, 1], so at most two deltas change */
+    let k2_diff = k2 - (k0 + n_bands as i32 * dk);
+    if k2_diff > 0 {
+        for d in v_dk[..n_bands].iter_mut().rev().take(k2_diff as usize) {
+            *d += 1;
+        }
+    } else if k2_diff < 0 {
+        for d in v_dk[..n_bands]
+            .iter_mut()
+            .take(k2_diff.unsigned_abs() as usize)
+        {
+            *d -= 1;
+        }
+    }
+
+    freq_master[0] = k0 as u8;
+    for k in 1..=n_bands {
+        freq_master[k] = (freq_master[k - 1] as i32 + v_dk[k - 1]) as u8;
+    }
+
+    Ok(n_bands)
+}
+
+/* geometric band deltas for one region of the master table (start at freq_master[0] = k_lo,
+ * go to k_hi in n_bands steps), written in place to freq_master[1..=n_bands]
+ */
+fn calc_region_deltas(delta: &mut [u8], k_hi: i32, k_lo: i32, n_bands: usize) {
+    /* tested for all valid combinations of k0, k1, nBands (from sampRate, freqScale, alterScale)
+     * roundoff error can be a problem with fixpt (e.g. pCurr = 12.499999 instead of 12.50003)
+     *   because successive multiplication always undershoots a little bit, but this
+     *   doesn't occur in any of the ratios we encounter from the valid k0/k1 bands in the spec
+     */
+    let t = ratio_pow_inv(k_hi, k_lo, n_bands as i32);
+    let mut p_curr = k_lo << 24;
+    let mut v_last = k_lo;
+    for d in &mut delta[..n_bands] {
+        p_curr = mulshift_32(p_curr, t) << 8; /* keep in Q24 */
+        let v_curr = (p_curr + (1 << 23)) >> 24;
+        *d = (v_curr - v_last) as u8;
+        v_last = v_curr;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcFreqMaster
+ *
+ * Description: calculate master frequency table when freqScale > 0
+ *                (4.6.18.3.2.1, figure 4.39)
+ *
+ * Inputs:      alterScale flag
+ *              freqScale flag
+ *              index of first QMF subband in master freq table (k0)
+ *              index of last QMF subband (k2)
+ *
+ * Outputs:     master frequency table
+ *
+ * Return:      number of bands in master frequency table
+ *
+ * Notes:       assumes k2 > k0, so there are at most 12 + 32 bands
+ **********************************************************************************************************************/
+fn calc_freq_master(
+    freq_master: &mut [u8; MAX_QMF_BANDS + 1],
+    freq_scale: u8,
+    alter_scale: u8,
+    k0: i32,
+    k2: i32,
+) -> usize {
+    let bands = M_BAND_TAB[freq_scale as usize - 1];
+    let inv_warp = INV_WARP_TAB[alter_scale as usize];
+
+    /* tested for all k0 = [5, 64], k2 = [k0, 64] */
+    let (two_regions, k1) = if k2 * 10000 > 22449 * k0 {
+        (true, 2 * k0)
+    } else {
+        (false, k2)
+    };
+
+    /* tested for all k0 = [5, 64], k1 = [k0, 64], freqScale = [1,3] */
+    let t = (LOG2_TAB[k1 as usize] - LOG2_TAB[k0 as usize]) >> 3; /* log2(k1/k0), Q28 to Q25 */
+    let n_bands0 = (2 * (((bands * t) + (1 << 24)) >> 25)) as usize; /* multiply by bands/2, round to nearest int (mBandTab has factor of 1/2 rolled in) */
+
+    calc_region_deltas(&mut freq_master[1..], k1, k0, n_bands0);
+
+    /* sort the deltas and find max delta for first region */
+    bubble_sort(&mut freq_master[1..=n_bands0]);
+    let v_dk0_max = freq_master[1..=n_bands0]
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(freq_master[1]);
+
+    /* fill master frequency table with bands from first region */
+    freq_master[0] = k0 as u8;
+    for k in 1..=n_bands0 {
+        freq_master[k] = freq_master[k].wrapping_add(freq_master[k - 1]);
+    }
+
+    /* if only one region, then the table is complete */
+    if !two_regions {
+        return n_bands0;
+    }
+
+    /* tested for all k1 = [10, 64], k2 = [k0, 64], freqScale = [1,3] */
+    let t = (LOG2_TAB[k2 as usize] - LOG2_TAB[k1 as usize]) >> 3; /* log2(k2/k1), Q28 to Q25 */
+    let t = mulshift_32(bands * t, inv_warp) << 2; /* multiply by bands/2, divide by warp factor, keep Q25 */
+    let n_bands1 = (2 * ((t + (1 << 24)) >> 25)) as usize; /* round to nearest int */
+
+    /* see comments above for calculations in first region */
+    let v_delta = &mut freq_master[n_bands0 + 1..n_bands0 + 1 + n_bands1];
+    calc_region_deltas(v_delta, k2, k1, n_bands1);
+
+    /* sort the deltas, adjusting first and last if the second region has smaller deltas than the first */
+    if v_delta
+        .iter()
+        .copied()
+        .min()
+        .is_some_and(|v_dk1_min| v_dk1_min < v_dk0_max)
+    {
+        bubble_sort(v_delta);
+        let spread = (v_delta[n_bands1 - 1] as i32 - v_delta[0] as i32) >> 1;
+        let change = (v_dk0_max as i32 - v_delta[0] as i32).min(spread);
+        v_delta[0] = (v_delta[0] as i32 + change) as u8;
+        v_delta[n_bands1 - 1] = (v_delta[n_bands1 - 1] as i32 - change) as u8;
+    }
+    bubble_sort(v_delta);
+
+    /* fill master frequency table with bands from second region
+     * Note: freqMaster[nBands0] = k1
+     */
+    for k in n_bands0 + 1..=n_bands0 + n_bands1 {
+        freq_master[k] = freq_master[k].wrapping_add(freq_master[k - 1]);
+    }
+
+    n_bands0 + n_bands1
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcFreqNoise
+ *
+ * Description: calculate noise floor frequency table (4.6.18.3.2.2)
+ *
+ * Inputs:      low resolution frequency table
+ *              number of bands in low resolution frequency table
+ *              index of starting QMF subband for SBR (kStart)
+ *              index of last QMF subband (k2)
+ *              number of noise bands
+ *
+ * Outputs:     noise floor frequency table
+ *
+ * Return:      number of bands in noise floor frequency table, or Err if there would be more
+ *                than MAX_NUM_NOISE_FLOOR_BANDS (4.6.18.3.6)
+ **********************************************************************************************************************/
+fn calc_freq_noise(
+    freq_noise: &mut [u8; MAX_NUM_NOISE_FLOOR_BANDS + 1],
+    freq_low: &[u8],
+    n_low: usize,
+    k_start: usize,
+    k2: i32,
+    noise_bands: u8,
+) -> Result<usize, ()> {
+    let l_top = LOG2_TAB[k2 as usize];
+    let l_bottom = LOG2_TAB[k_start];
+    let n_q = noise_bands as i32 * ((l_top - l_bottom) >> 2); /* Q28 to Q26, noiseBands = [0,3] */
+    let n_q = ((n_q + (1 << 25)) >> 26).max(1) as usize;
+    if n_q > MAX_NUM_NOISE_FLOOR_BANDS {
+        return Err(());
+    }
+
+    let mut i_last = 0;
+    freq_noise[0] = freq_low[0];
+    for (k, f) in (1..=n_q).zip(&mut freq_noise[1..]) {
+        let i = i_last + (n_low - i_last) / (n_q + 1 - k); /* truncating division */
+        *f = freq_low[i];
+        i_last = i;
+    }
+
+    Ok(n_q)
+}
+
+/***********************************************************************************************************************
+ * Function:    BuildPatches
+ *
+ * Description: build high frequency patches (4.6.18.6.3)
+ *
+ * Inputs:      master frequency table
+ *              number of bands in master frequency table
+ *              index of first QMF subband in master freq table (k0)
+ *              index of starting QMF subband for SBR (kStart)
+ *              number of QMF bands in high resolution frequency table
+ *              sample rate index
+ *
+ * Outputs:     starting subband for each patch
+ *              number of subbands in each patch
+ *
+ * Return:      number of patches, or Err if the patches don't fit in MAX_NUM_PATCHES or can't
+ *                cover the SBR range
+ **********************************************************************************************************************/
+fn build_patches(sbr_freq: &mut SBRFreq, k0: i32, samp_rate_idx: usize) -> Result<usize, ()> {
+    let freq_master = &sbr_freq.freqMaster;
+    let n_master = sbr_freq.nMaster;
+    let k_start = sbr_freq.kStart as i32;
+    let k_end = k_start + sbr_freq.numQMFBands as i32;
+    let goal_sb = GOAL_SB_TAB[samp_rate_idx] as i32;
+
+    let mut msb = k0;
+    let mut usb = k_start;
+    let mut num_patches = 0;
+
+    if n_master == 0 {
+        sbr_freq.patchNumSubbands[0] = 0;
+        sbr_freq.patchStartSubband[0] = 0;
+        return Ok(0);
+    }
+
+    let mut k = if goal_sb < k_end {
+        /* freqMaster[nMaster] = kStart + numQMFBands > goalSB, so this stops inside the table */
+        freq_master
+            .iter()
+            .position(|&f| f as i32 >= goal_sb)
+            .unwrap_or(n_master)
+    } else {
+        n_master
+    };
+
+    loop {
+        let mut j = k;
+        let (mut sb, mut odd_flag);
+        loop {
+            sb = freq_master[j] as i32;
+            odd_flag = (sb - 2 + k0) & 0x01;
+            if sb <= k0 - 1 + msb - odd_flag || j == 0 {
+                break;
+            }
+            j -= 1;
+        }
+
+        let num_subbands = (sb - usb).max(0);
+        sbr_freq.patchNumSubbands[num_patches] = num_subbands as u8;
+        sbr_freq.patchStartSubband[num_patches] = (k0 - odd_flag - num_subbands) as u8;
+
+        /* from MPEG reference code - slightly different from spec */
+        if num_subbands < 3 && num_patches > 0 {
+            break;
+        }
+
+        if num_subbands > 0 {
+            usb = sb;
+            msb = sb;
+            num_patches += 1;
+        } else if msb == k_start {
+            /* nothing left to copy from, this would never terminate */
+            return Err(());
+        } else {
+            msb = k_start;
+        }
+
+        if (freq_master[k] as i32) - sb < 3 {
+            k = n_master;
+        }
+
+        if sb == k_end || num_patches > MAX_NUM_PATCHES {
+            break;
+        }
+    }
+
+    if num_patches > MAX_NUM_PATCHES {
+        return Err(());
+    }
+    Ok(num_patches)
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcFreqLimiter
+ *
+ * Description: calculate limiter frequency table (4.6.18.3.2.3)
+ *
+ * Inputs:      number of subbands in each patch
+ *              low resolution frequency table
+ *              number of bands in low resolution frequency table
+ *              index of starting QMF subband for SBR (kStart)
+ *              number of limiter bands
+ *              number of patches
+ *
+ * Outputs:     limiter frequency table
+ *
+ * Return:      number of bands in limiter frequency table
+ **********************************************************************************************************************/
+fn calc_freq_limiter(sbr_freq: &mut SBRFreq, limiter_bands: u8) -> usize {
+    let n_low = sbr_freq.nLow;
+    let k_start = sbr_freq.kStart as u8;
+    let num_patches = sbr_freq.numPatches as usize;
+    let freq_low = sbr_freq.freqLow;
+    let freq_limiter = &mut sbr_freq.freqLimiter;
+
+    /* simple case */
+    if limiter_bands == 0 {
+        freq_limiter[0] = freq_low[0].wrapping_sub(k_start);
+        freq_limiter[1] = freq_low[n_low].wrapping_sub(k_start);
+        return 1;
+    }
+
+    let bands = LIM_BANDS_PER_OCTAVE[limiter_bands as usize - 1];
+    let mut patch_borders = [0u8; MAX_NUM_PATCHES + 1];
+    patch_borders[0] = k_start;
+
+    /* from MPEG reference code - slightly different from spec (top border) */
+    let mut k = 1;
+    while k < num_patches {
+        patch_borders[k] = patch_borders[k - 1].wrapping_add(sbr_freq.patchNumSubbands[k - 1]);
+        k += 1;
+    }
+    patch_borders[k] = freq_low[n_low];
+    let patch_borders = &patch_borders[..=num_patches];
+
+    freq_limiter[..=n_low].copy_from_slice(&freq_low[..=n_low]);
+    freq_limiter[n_low + 1..n_low + num_patches].copy_from_slice(&patch_borders[1..num_patches]);
+
+    let mut n_limiter = n_low + num_patches - 1; /* nLow >= 1 */
+    bubble_sort(&mut freq_limiter[..=n_limiter]);
+
+    let mut k = 1;
+    while k <= n_limiter {
+        let n_octaves = LOG2_TAB[freq_limiter[k] as usize] - LOG2_TAB[freq_limiter[k - 1] as usize]; /* Q28 */
+        let n_octaves = (n_octaves >> 9) * bands; /* Q19, max bands = 300 < 2^9 */
+        if n_octaves < (49 << 19) {
+            /* compare with 0.49*100, in Q19 */
+            if freq_limiter[k] == freq_limiter[k - 1] || !patch_borders.contains(&freq_limiter[k]) {
+                freq_limiter.copy_within(k + 1..=n_limiter, k);
+                n_limiter -= 1;
+            } else if !patch_borders.contains(&freq_limiter[k - 1]) {
+                freq_limiter.copy_within(k..=n_limiter, k - 1);
+                n_limiter -= 1;
+            } else {
+                k += 1;
+            }
+        } else {
+            k += 1;
+        }
+    }
+
+    /* store limiter boundaries as offsets from kStart */
+    for f in &mut freq_limiter[..=n_limiter] {
+        *f = f.wrapping_sub(k_start);
+    }
+
+    n_limiter
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcFreqTables
+ *
+ * Description: calulate master and derived frequency tables, and patches
+ *
+ * Inputs:      initialized SBRHeader struct for this SCE/CPE block
+ *              sample rate index of output sample rate (after SBR)
+ *
+ * Outputs:     master and derived frequency tables, and patches
+ *
+ * Return:      Ok, or Err if the header gives tables outside the limits of 4.6.18.3.6
+ *                (the tables are then only partially updated)
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn calc_freq_tables(
+    sbr_hdr: &SBRHeader,
+    sbr_freq: &mut SBRFreq,
+    samp_rate_idx: usize,
+) -> Result<(), ()> {
+    if samp_rate_idx >= NUM_SAMPLE_RATES_SBR {
+        return Err(());
+    }
+    let k0 = K0_TAB[samp_rate_idx][sbr_hdr.startFreq as usize] as i32;
+    let k2 = match sbr_hdr.stopFreq {
+        14 => 2 * k0,
+        15 => 3 * k0,
+        f => K2_TAB[samp_rate_idx][f as usize] as i32,
+    }
+    .min(64);
+    if k2 <= k0 {
+        return Err(());
+    }
+
+    /* calculate master frequency table */
+    sbr_freq.nMaster = if sbr_hdr.freqScale == 0 {
+        calc_freq_master_scale_zero(&mut sbr_freq.freqMaster, sbr_hdr.alterScale, k0, k2)?
+    } else {
+        calc_freq_master(
+            &mut sbr_freq.freqMaster,
+            sbr_hdr.freqScale,
+            sbr_hdr.alterScale,
+            k0,
+            k2,
+        )
+    };
+
+    /* a band of width zero is a broken header (rounding can give zero deltas for narrow ranges) */
+    if sbr_freq.freqMaster[..=sbr_freq.nMaster]
+        .windows(2)
+        .any(|w| w[0] >= w[1])
+    {
+        return Err(());
+    }
+
+    /* calculate high frequency table and related parameters */
+    let cross_over_band = sbr_hdr.crossOverBand as usize;
+    if sbr_freq.nMaster <= cross_over_band {
+        return Err(());
+    }
+    let n_high = sbr_freq.nMaster - cross_over_band;
+    sbr_freq.freqHigh[..=n_high]
+        .copy_from_slice(&sbr_freq.freqMaster[cross_over_band..=sbr_freq.nMaster]);
+    sbr_freq.nHigh = n_high;
+    sbr_freq.kStart = sbr_freq.freqHigh[0] as usize;
+    sbr_freq.numQMFBands = (sbr_freq.freqHigh[n_high] as usize).wrapping_sub(sbr_freq.kStart);
+
+    /* the analysis QMF only has 32 bands to start from, and the per-band buffers hold MAX_QMF_BANDS */
+    if sbr_freq.kStart > 32 || sbr_freq.numQMFBands > MAX_QMF_BANDS {
+        return Err(());
+    }
+
+    /* calculate low frequency table */
+    let n_low = n_high - (n_high >> 1);
+    let odd_flag = n_high & 0x01;
+    sbr_freq.freqLow[0] = sbr_freq.freqHigh[0];
+    for k in 1..=n_low {
+        sbr_freq.freqLow[k] = sbr_freq.freqHigh[2 * k - odd_flag];
+    }
+    sbr_freq.nLow = n_low;
+
+    /* calculate noise floor frequency table */
+    sbr_freq.numNoiseFloorBands = calc_freq_noise(
+        &mut sbr_freq.freqNoise,
+        &sbr_freq.freqLow,
+        n_low,
+        sbr_freq.kStart,
+        k2,
+        sbr_hdr.noiseBands,
+    )?;
+
+    /* calculate limiter table */
+    sbr_freq.numPatches = build_patches(sbr_freq, k0, samp_rate_idx)? as u8;
+    sbr_freq.nLimiter = calc_freq_limiter(sbr_freq, sbr_hdr.limiterBands);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(start_freq: u8, stop_freq: u8, freq_scale: u8, alter_scale: u8) -> SBRHeader {
+        SBRHeader {
+            count: 1,
+            ampRes: 1,
+            startFreq: start_freq,
+            stopFreq: stop_freq,
+            crossOverBand: 0,
+            resBitsHdr: 0,
+            hdrExtra1: 0,
+            hdrExtra2: 0,
+            freqScale: freq_scale,
+            alterScale: alter_scale,
+            noiseBands: 2,
+            limiterBands: 2,
+            limiterGains: 2,
+            interpFreq: 1,
+            smoothMode: 1,
+        }
+    }
+
+    #[test]
+    fn default_header_tables_are_consistent() {
+        /* 44.1 kHz output from a 22.05 kHz core, startFreq 5, stopFreq 9, the defaults of most encoders */
+        let hdr = header(5, 9, 2, 1);
+        let mut freq = SBRFreq::default();
+        calc_freq_tables(&hdr, &mut freq, 4).unwrap();
+
+        assert_eq!(freq.kStart, 14);
+        let master = &freq.freqMaster[..=freq.nMaster];
+        assert!(master.windows(2).all(|w| w[0] < w[1]), "{master:?}");
+        assert_eq!(freq.freqHigh[0] as usize, freq.kStart);
+        assert_eq!(
+            freq.freqHigh[freq.nHigh] as usize,
+            freq.kStart + freq.numQMFBands
+        );
+        assert_eq!(freq.freqLow[freq.nLow], freq.freqHigh[freq.nHigh]);
+        assert_eq!(
+            freq.freqNoise[freq.numNoiseFloorBands],
+            freq.freqHigh[freq.nHigh]
+        );
+        assert_eq!(freq.freqLimiter[0], 0);
+        assert_eq!(freq.freqLimiter[freq.nLimiter] as usize, freq.numQMFBands);
+
+        /* the patches copy down into the core band and cover the SBR range without gaps */
+        let n = freq.numPatches as usize;
+        assert!(n >= 1);
+        let covered: usize = freq.patchNumSubbands[..n].iter().map(|&s| s as usize).sum();
+        assert_eq!(covered, freq.numQMFBands);
+        for p in 0..n {
+            let top = freq.patchStartSubband[p] as usize + freq.patchNumSubbands[p] as usize;
+            assert!(top <= freq.kStart, "patch {p} reads above kStart");
+        }
+    }
+
+    #[test]
+    fn every_header_is_either_rejected_or_in_bounds() {
+        for samp_rate_idx in 0..NUM_SAMPLE_RATES_SBR {
+            for start_freq in 0..16 {
+                for stop_freq in 0..16 {
+                    for (freq_scale, alter_scale) in [(0, 0), (0, 1), (1, 0), (2, 1), (3, 1)] {
+                        for cross_over_band in [0, 3, 7] {
+                            let mut hdr = header(start_freq, stop_freq, freq_scale, alter_scale);
+                            hdr.crossOverBand = cross_over_band;
+                            hdr.noiseBands = 3;
+                            let mut freq = SBRFreq::default();
+                            if calc_freq_tables(&hdr, &mut freq, samp_rate_idx).is_ok() {
+                                assert!(freq.kStart + freq.numQMFBands <= 64);
+                                let master = &freq.freqMaster[..=freq.nMaster];
+                                assert!(master.windows(2).all(|w| w[0] < w[1]));
+                                assert!(freq.numNoiseFloorBands <= MAX_NUM_NOISE_FLOOR_BANDS);
+                                assert!(freq.numPatches as usize <= MAX_NUM_PATCHES);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,903 @@
+//! HF adjustment (14496-3, 4.6.18.7): envelope estimation, gain calculation with limiter and
+//! boost, and mapping of the adjusted signal, sinusoids and noise into the QMF matrix.
+
+use super::math::{clip_2n_shift30, inv_r_normalized, sqrt_fix};
+use super::{
+    ACC_SCALE, FBITS_GLIM_BOOST, FBITS_OUT_DQ_ENV, FBITS_OUT_DQ_NOISE, FBITS_OUT_QMFA,
+    FBITS_QLIM_BOOST, GBOOST_MAX, HF_ADJ, MAX_NUM_SMOOTH_COEFS, MIN_GBITS_IN_QMFS, PSInfoSBR,
+    SBR_GRID_FIXVAR, SBR_GRID_VARFIX, SBR_GRID_VARVAR,
+};
+use crate::mp3_decoder::{clip_2n, madd_64, mulshift_32};
+
+/* invBandTab[i] = 1.0 / (i + 1), Q31 */
+const INV_BAND_TAB: [i32; 64] = [
+    0x7fffffff, 0x40000000, 0x2aaaaaab, 0x20000000, 0x1999999a, 0x15555555, 0x12492492, 0x10000000,
+    0x0e38e38e, 0x0ccccccd, 0x0ba2e8ba, 0x0aaaaaab, 0x09d89d8a, 0x09249249, 0x08888889, 0x08000000,
+    0x07878788, 0x071c71c7, 0x06bca1af, 0x06666666, 0x06186186, 0x05d1745d, 0x0590b216, 0x05555555,
+    0x051eb852, 0x04ec4ec5, 0x04bda12f, 0x04924925, 0x0469ee58, 0x04444444, 0x04210842, 0x04000000,
+    0x03e0f83e, 0x03c3c3c4, 0x03a83a84, 0x038e38e4, 0x03759f23, 0x035e50d8, 0x03483483, 0x03333333,
+    0x031f3832, 0x030c30c3, 0x02fa0be8, 0x02e8ba2f, 0x02d82d83, 0x02c8590b, 0x02b93105, 0x02aaaaab,
+    0x029cbc15, 0x028f5c29, 0x02828283, 0x02762762, 0x026a439f, 0x025ed098, 0x0253c825, 0x02492492,
+    0x023ee090, 0x0234f72c, 0x022b63cc, 0x02222222, 0x02192e2a, 0x02108421, 0x02082082, 0x02000000,
+];
+
+/* squared version of table in 4.6.18.7.5, Q30 (0x80000000 = sentinel for GMAX) */
+const LIM_GAIN_TAB: [u32; 4] = [0x20138ca7, 0x40000000, 0x7fb27dce, 0x80000000];
+const GAIN_MAX_SENTINEL: i32 = LIM_GAIN_TAB[3] as i32;
+
+/* hSmooth table from 4.7.18.7.6, format = Q31 */
+const H_SMOOTH_COEF: [i32; MAX_NUM_SMOOTH_COEFS] =
+    [0x2aaaaaab, 0x2697a512, 0x1becfa68, 0x0ebdb043, 0x04130598];
+
+/* random noise (14496-3, 4.A.6.2), 512 complex values interleaved re/im, format = Q31 */
+const NOISE_TAB: [u32; 1024] = [
+    0x8010fd38, 0xb3dc7948, 0x7c4e2301, 0xa9904192, 0x121622a7, 0x86489625, 0xc3d53d25, 0xd0343fa9,
+    0x674d6f70, 0x25f4e9fd, 0xce1a8c8b, 0x72a726c5, 0xfea6efc6, 0xaa4adb1a, 0x8b2dd628, 0xf14029e4,
+    0x46321c1a, 0x604889a0, 0x33363b63, 0x815ed069, 0x802b4315, 0x8f2bf7f3, 0x85b86073, 0x745cfb46,
+    0xc57886b3, 0xb76731f0, 0xa2a66772, 0x828ca631, 0x60cc145e, 0x1ad1010f, 0x090c83d4, 0x9bd7ba87,
+    0x5f5aeea2, 0x8b4dbd99, 0x848e7b1e, 0x86bb9fa2, 0x26f18ae5, 0xc0b81194, 0x553407bf, 0x52c17953,
+    0x755f468d, 0x166b04f8, 0xa5687981, 0x4343248b, 0xa6558d5e, 0xc5f6fab7, 0x80a4fb8c, 0x8cb53cb7,
+    0x7da68a54, 0x9cd8df8a, 0xba05376c, 0xfcb58ee2, 0xfdd657a4, 0x005e35ca, 0x91c75c55, 0x367651e6,
+    0x816abf85, 0x8f831c4f, 0x423f9c9c, 0x55aa919e, 0x80779834, 0xb59f4244, 0x800a095c, 0x7de9e0cc,
+    0x46bda5cb, 0x4c184464, 0x2c438f71, 0x797216b5, 0x5035cee6, 0xa0c3a26e, 0x9d3f95fa, 0xd4a100c0,
+    0x8ac30dac, 0x04b87397, 0x9e5ac516, 0x8b0b442e, 0x66210ad6, 0x88ba7598, 0x45b9bd33, 0xf0be5087,
+    0x9261b85e, 0x364f6a31, 0x891c4b50, 0x23ad08ce, 0xf10366a6, 0x80414276, 0x1b562e06, 0x8be21591,
+    0x9e798195, 0x7fb4045c, 0x7d9506cf, 0x854e691f, 0x9207f092, 0x7a94c9d5, 0x88911536, 0x3f45cc61,
+    0x27059279, 0xa5b57109, 0x6d2bb67b, 0x3bdc5379, 0x74e662d8, 0x80348f8c, 0xf875e638, 0x5a8caea1,
+    0x2459ae75, 0x2c54b939, 0x79ee3203, 0xb9bc8683, 0x9b6f630c, 0x9f45b351, 0x8563b2b9, 0xe5dbba41,
+    0x697c7d0d, 0x7bb7c90e, 0xac900866, 0x8e6b5177, 0x8822dd37, 0x7fd5a91e, 0x7506da05, 0x82302aca,
+    0xa5e4be04, 0x4b4288eb, 0x00b8bc9f, 0x4f1033e4, 0x7200d612, 0x43900c8c, 0xa815b900, 0x676ed1d4,
+    0x5c5f23b2, 0xa758ee11, 0xaf73abfa, 0x11714ec0, 0x265239e0, 0xc50de679, 0x8a84e341, 0xa1438354,
+    0x7f1a341f, 0x343ec96b, 0x696e71b0, 0xa13bde39, 0x81e75094, 0x80091111, 0x853a73bf, 0x80f9c1ee,
+    0xe4980086, 0x886a8e28, 0xa7e89426, 0xdd93edd7, 0x7592100d, 0x0bfa8123, 0x850a26d4, 0x2e34f395,
+    0x421b6c00, 0xa4a462e4, 0x4e3f5090, 0x3c189f4c, 0x3c971a56, 0xdd0376d2, 0x747a5367, 0x7bcbc9d7,
+    0x3966be6a, 0x7efda616, 0x55445e15, 0x7ba2ab3f, 0x5fe684f2, 0x8cf42af9, 0x808c61c3, 0x4390c27b,
+    0x7cac62ff, 0xea6cab22, 0x5d0902ad, 0xc27b7208, 0x7a27389d, 0x5820a357, 0xa29bbe59, 0x9df0f1fd,
+    0x92bd67e5, 0x7195b587, 0x97cac65b, 0x8339807e, 0x8f72d832, 0x5fad8685, 0xa462d9d3, 0x81d46214,
+    0x6ae93e1d, 0x6b23a5b9, 0xc2732874, 0x81795268, 0x7c568cb6, 0x668513ea, 0x428d024e, 0x66b78b3a,
+    0xfee9ef03, 0x9ddcbb82, 0xa605f07e, 0x46dc55e0, 0x85415054, 0xc89ec271, 0x7c42edfb, 0x0befe59b,
+    0x89b8f607, 0x6d732a1a, 0xa7081ebd, 0x7e403258, 0x21feeb7b, 0x5dd7a1e7, 0x23e3a31a, 0x129bc896,
+    0xa11a6b54, 0x7f1e031c, 0xfdc1a4d1, 0x96402e53, 0xb9700f1a, 0x8168ecd6, 0x7d63d3cc, 0x87a70d65,
+    0x81075a7a, 0x55c8caa7, 0xa95d00b5, 0x102b1652, 0x0bb30215, 0xe5b63237, 0xa446ca44, 0x82d4c333,
+    0x67b2e094, 0x44c3d661, 0x33fd6036, 0xde1ea2a1, 0xa95e8e47, 0x78f66eb9, 0x6f2aef1e, 0xe8887247,
+    0x80a3b70e, 0xfca0d9d3, 0x6bf0fd20, 0x0d5226de, 0xf4341c87, 0x5902df05, 0x7ff1a38d, 0xf02e5a5b,
+    0x99f129af, 0x8ac63d01, 0x7b53f599, 0x7bb32532, 0x99ac59b0, 0x5255a80f, 0xf1320a41, 0x2497aa5c,
+    0xcce60bd8, 0x787c634b, 0x7ed58c5b, 0x8a28eb3a, 0x24a5e647, 0x8b79a2c1, 0x955f5ce5, 0xa9d12bc4,
+    0x7a1e20c6, 0x3eeda7ac, 0xf7be823a, 0x042924ce, 0x808b3f03, 0x364248da, 0xac2895e5, 0x69a8b5fa,
+    0x97fe8b63, 0xbdeac9aa, 0x8073e0ad, 0x6c25dba7, 0x005e51d2, 0x52e74389, 0x59d3988c, 0xe5d1f39c,
+    0x7b57dc91, 0x341adbe7, 0xa7d42b8d, 0x74e9f335, 0xd35bf7d8, 0x5b7c0a4b, 0x75bc0874, 0x552129bf,
+    0x8144b70d, 0x6de93bbb, 0x5825f14b, 0x473ec5ca, 0x80a8f37c, 0xe6552d69, 0x7898360b, 0x806379b0,
+    0xa9b59339, 0x3f6bf60c, 0xc367d731, 0x920ade99, 0x125592f7, 0x877e5ed1, 0xda895d95, 0x075f2ece,
+    0x380e5f5e, 0x9b006b62, 0xd17a6dd2, 0x530a0e13, 0xf4cc9a14, 0x7d0a0ed4, 0x847c6e3f, 0xbaee4975,
+    0x47131163, 0x64fb2cac, 0x5e2100a6, 0x7b756a42, 0xd87609f4, 0x98bfe48c, 0x0493745e, 0x836c5784,
+    0x7e5ccb40, 0x3df6b476, 0x97700d28, 0x8bbd93fd, 0x56de9cdb, 0x680b4e65, 0xebc3d90e, 0x6d286793,
+    0x6753712e, 0xe05c98a7, 0x3d2b6b85, 0xc4b18ddb, 0x7b59b869, 0x31435688, 0x811888e9, 0xe011ee7a,
+    0x6a5844f9, 0x86ae35ea, 0xb4cbc10b, 0x01a6f5d6, 0x7a49ed64, 0x927caa49, 0x847ddaed, 0xae0d9bb6,
+    0x836bdb04, 0x0fd810a6, 0x74fe126b, 0x4a346b5f, 0x80184d36, 0x5afd153c, 0x90cc8102, 0xe606d0e6,
+    0xde69aa58, 0xa89f1222, 0xe06df715, 0x8fd16144, 0x0317c3e8, 0x22ce92fc, 0x690c3eca, 0x93166f02,
+    0x71573414, 0x8d43cffb, 0xe8bd0bb6, 0xde86770f, 0x0bf99a41, 0x4633a661, 0xba064108, 0x7adafae3,
+    0x2f6cde5d, 0xb350a52c, 0xa5ebfb0b, 0x74c57b46, 0xd3b603b5, 0x80b70892, 0xa7f7fa53, 0xd94b566c,
+    0xdda3fd86, 0x6a635793, 0x3ed005ca, 0xc5f087d8, 0x31e3a746, 0x7a4278f9, 0x82def1f9, 0x06caa2b2,
+    0xe9d2c349, 0x8940e7f7, 0x7feef8dd, 0x4a9b01f0, 0xacde69f8, 0x57ddc280, 0xf09e4ba4, 0xb6d9f729,
+    0xb48c18f2, 0xd3654aa9, 0xca7a03c8, 0x14d57545, 0x7fda87a5, 0x0e411366, 0xb77d0df0, 0x8c2aa467,
+    0x787f2590, 0x2d292db1, 0x9f12682c, 0x44ac364d, 0x1a4b31a6, 0x871f7ded, 0x7ff99167, 0x6630a1d5,
+    0x25385eb9, 0x2d4dd549, 0xaf8a7004, 0x319ebe0f, 0x379ab730, 0x81dc56a4, 0x822d8523, 0x1ae8554c,
+    0x18fa0786, 0x875f7de4, 0x85ca350f, 0x7de818dc, 0x7786a38f, 0xa5456355, 0x92e60f88, 0xf5526122,
+    0x916039bc, 0xc561e2de, 0x31c42042, 0x7c82e290, 0x75d158b2, 0xb015bda1, 0x7220c750, 0x46565441,
+    0xd0da1fdd, 0x7b777481, 0x782e73c6, 0x8cd72b7b, 0x7f1006aa, 0xfb30e51e, 0x87994818, 0x34e7c7db,
+    0x7faae06b, 0xea74fbc0, 0xd20c7af4, 0xc44f396b, 0x06b4234e, 0xdf2e2a93, 0x2efb07c8, 0xce861911,
+    0x7550ea05, 0xd8d90bbb, 0x58522eec, 0x746b3520, 0xce844ce9, 0x7f5cacc3, 0xda8f17e0, 0x2fedf9cb,
+    0xb2f77ec4, 0x6f13f4c0, 0x834de085, 0x7b7ace4b, 0x713b16ac, 0x499c5ab0, 0x06a7961d, 0x1b39a48a,
+    0xbb853e6e, 0x7c781cc1, 0xc0baebf5, 0x7dace394, 0x815ceebc, 0xcc7b27d4, 0x8274b181, 0xa2be40a2,
+    0xdd01d5dc, 0x7fefeb14, 0x0813ec78, 0xba3077cc, 0xe5cf1e1c, 0xedcfacae, 0x54c43a9b, 0x5cd62a42,
+    0x93806b55, 0x03095c5b, 0x8e076ae3, 0x71bfcd2a, 0x7ac1989b, 0x623bc71a, 0x5e15d4d2, 0xfb341dd1,
+    0xd75dfbca, 0xd0da32be, 0xd4569063, 0x337869da, 0x3d30606a, 0xcd89cca2, 0x7dd2ae36, 0x028c03cd,
+    0xd85e052c, 0xe8dc9ec5, 0x7ffd9241, 0xde5bf4c6, 0x88c4b235, 0x8228be2e, 0x7fe6ec64, 0x996abe6a,
+    0xdeb0666d, 0x9eb86611, 0xd249b922, 0x18b3e26b, 0x80211168, 0x5f8bb99c, 0x6ecb0dd2, 0x4728ff8d,
+    0x2ac325b8, 0x6e5169d2, 0x7ebbd68d, 0x05e41d17, 0xaaa19f28, 0x8ab238a6, 0x51f105be, 0x140809cc,
+    0x7f7345d9, 0x3aae5a9d, 0xaecec6e4, 0x1afb3473, 0xf6229ed1, 0x8d55f467, 0x7e32003a, 0x70f30c14,
+    0x6686f33f, 0xd0d45ed8, 0x644fab57, 0x3a3fbbd3, 0x0b255fc4, 0x679a1701, 0x90e17b6e, 0x325d537b,
+    0xcd7b9b87, 0xaa7be2a2, 0x7d47c966, 0xa33dbce5, 0x8659c3bb, 0x72a41367, 0x15c446e0, 0x45fe8b0a,
+    0x9d8ddf26, 0x84d47643, 0x7fabe0da, 0x36a70122, 0x7a28ebfe, 0x7c29b8b8, 0x7f760406, 0xbabe4672,
+    0x23ea216e, 0x92bcc50a, 0x6d20dba2, 0xad5a7c7e, 0xbf3897f5, 0xabb793e1, 0x8391fc7e, 0xe270291c,
+    0x7a248d58, 0x80f8fd15, 0x83ef19f3, 0x5e6ece7d, 0x278430c1, 0x35239f4d, 0xe09c073b, 0x50e78cb5,
+    0xd4b811bd, 0xce834ee0, 0xf88aaa34, 0xf71da5a9, 0xe2b0a1d5, 0x7c3aef31, 0xe84eabca, 0x3ce25964,
+    0xf29336d3, 0x8fa78b2c, 0xa3fc3415, 0x63e1313d, 0x7fbc74e0, 0x7340bc93, 0x49ae583b, 0x8b79de4b,
+    0x25011ce9, 0x7b462279, 0x36007db0, 0x3da1599c, 0x77780772, 0xc845c9bb, 0x83ba68be, 0x6ee507d1,
+    0x2f0159b8, 0x5392c4ed, 0x98336ff6, 0x0b3c7f11, 0xde697aac, 0x893fc8d0, 0x6b83f8f3, 0x47799a0d,
+    0x801d9dfc, 0x8516a83e, 0x5f8d22ec, 0x0f8ba384, 0xa049dc4b, 0xdd920b05, 0x7a99bc9f, 0x9ad19344,
+    0x7a345dba, 0xf501a13f, 0x3e58bf19, 0x7fffaf9a, 0x3b4e1511, 0x0e08b991, 0x9e157620, 0x7230a326,
+    0x4977f9ff, 0x2d2bbae1, 0x607aa7fc, 0x7bc85d5f, 0xb441bbbe, 0x8d8fa5f2, 0x601cce26, 0xda1884f2,
+    0x81c82d64, 0x200b709c, 0xcbd36abe, 0x8cbdddd3, 0x55ab61d3, 0x7e3ee993, 0x833f18aa, 0xffc1aaea,
+    0x7362e16a, 0x7fb85db2, 0x904ee04c, 0x7f04dca6, 0x8ad7a046, 0xebe7d8f7, 0xfbc4c687, 0xd0609458,
+    0x093ed977, 0x8e546085, 0x7f5b8236, 0x7c47e118, 0xa01f2641, 0x7ffb3e48, 0x05de7cda, 0x7fc281b9,
+    0x8e0278fc, 0xd74e6d07, 0x94c24450, 0x7cf9e641, 0x2ad27871, 0x919fa815, 0x805fd205, 0x7758397f,
+    0xe2c7e02c, 0x1828e194, 0x5613d6fe, 0xfb55359f, 0xf9699516, 0x8978ee26, 0x7feebad9, 0x77d71d82,
+    0x55b28b60, 0x7e997600, 0x80821a6b, 0xc6d78af1, 0x691822ab, 0x7f6982a0, 0x7ef56f99, 0x5c307f40,
+    0xac6f8b76, 0x42cc8ba4, 0x782c61d9, 0xa0224dd0, 0x7bd234d1, 0x74576e3b, 0xe38cfe9a, 0x491e66ef,
+    0xc78291c5, 0x895bb87f, 0x924f7889, 0x71b89394, 0x757b779d, 0xc4a9c604, 0x5cdf7829, 0x8020e9df,
+    0x805e8245, 0x4a82c398, 0x6360bd62, 0x78bb60fc, 0x09e0d014, 0x4b0ea180, 0xb841978b, 0x69a0e864,
+    0x7df35977, 0x3284b0dd, 0x3cdc2efd, 0x57d31f5e, 0x541069cc, 0x1776e92e, 0x04309ea3, 0xa015eb2d,
+    0xce7bfabc, 0x41b638f8, 0x8365932e, 0x846ab44c, 0xbbcc80cb, 0x8afa6cac, 0x7fc422ea, 0x4e403fc0,
+    0xbfac9aee, 0x8e4c6709, 0x028e01fb, 0x6d160a9b, 0x7fe93004, 0x790f9cdc, 0x6a1f37a0, 0xf7e7ef30,
+    0xb4ea0f04, 0x7bf4c8e6, 0xe981701f, 0xc258a9d3, 0x6acbbfba, 0xef5479c7, 0x079c8bd8, 0x1a410f56,
+    0x6853b799, 0x86cd4f01, 0xc66e23b6, 0x34585565, 0x8d1fe00d, 0x7fcdba1a, 0x32c9717b, 0xa02f9f48,
+    0xf64940db, 0x5ed7d8f1, 0x61b823b2, 0x356f8918, 0xa0a7151e, 0x793fc969, 0x530beaeb, 0x34e93270,
+    0x4fc4ddb5, 0x88d58b6c, 0x36094774, 0xf620ac80, 0x03763a72, 0xf910c9a6, 0x6666fb2d, 0x752c8be8,
+    0x9a6dfdd8, 0xd1a7117d, 0x51c1b1d4, 0x0a67773d, 0x43b32a79, 0x4cdcd085, 0x5f067d30, 0x05bfe92a,
+    0x7ed7d203, 0xe71a3c85, 0x99127ce2, 0x8eb3cac4, 0xad4bbcea, 0x5c6a0fd0, 0x0eec04af, 0x94e95cd4,
+    0x8654f921, 0x83eabb5d, 0xb058d7ca, 0x69f12d3c, 0x03d881b2, 0x80558ef7, 0x82938cb3, 0x2ec0e1d6,
+    0x80044422, 0xd1e47051, 0x720fc6ff, 0x82b20316, 0x0d527b02, 0x63049a15, 0x7ad5b9ad, 0xd2a4641d,
+    0x41144f86, 0x7b04917a, 0x15c4a2c0, 0x9da07916, 0x211df54a, 0x7fdd09af, 0xfe924f3f, 0x7e132cfe,
+    0x9a1d18d6, 0x7c56508b, 0x80f0f0af, 0x8095ced6, 0x8037d0d7, 0x026719d1, 0xa55fec43, 0x2b1c7cb7,
+    0xa5cd5ac1, 0x77639fad, 0x7fcd8b62, 0x81a18c27, 0xaee4912e, 0xeae9eebe, 0xeb3081de, 0x8532aada,
+    0xc822362e, 0x86a649a9, 0x8031a71d, 0x7b319dc6, 0xea8022e6, 0x814bc5a9, 0x8f62f7a1, 0xa430ea17,
+    0x388deafb, 0x883b5185, 0x776fe13c, 0x801c683f, 0x87c11b98, 0xb7cbc644, 0x8e9ad3e8, 0x3cf5a10c,
+    0x7ff6a634, 0x949ef096, 0x9f84aa7c, 0x010af13f, 0x782d1de8, 0xf18e492a, 0x6cf63b01, 0x4301cd81,
+    0x32d15c9e, 0x68ad8cef, 0xd09bd2d6, 0x908c5c15, 0xd1e36260, 0x2c5bfdd0, 0x88765a99, 0x93deba1e,
+    0xac6ae342, 0xe865b84c, 0x0f4f2847, 0x7fdf0499, 0x78b1c9b3, 0x6a73261e, 0x601a96f6, 0xd2847933,
+    0x489aa888, 0xe12e8093, 0x3bfa5a5f, 0xd96ba5f7, 0x7c8f4c8d, 0x80940c6f, 0xcef9dd1a, 0x7e1a055f,
+    0x3483558b, 0x02b59cc4, 0x0c56333e, 0x05a5b813, 0x92d66287, 0x7516b679, 0x71bfe03f, 0x8056bf68,
+    0xc24d0724, 0x8416bcf3, 0x234afbdb, 0x4b0d6f9c, 0xaba97333, 0x4b4f42b6, 0x7e8343ab, 0x7ffe2603,
+    0xe590f73c, 0x45e10c76, 0xb07a6a78, 0xb35609d3, 0x1a027dfd, 0x90cb6e20, 0x82d3fe38, 0x7b409257,
+    0x0e395afa, 0x1b802093, 0xcb0c6c59, 0x241e17e7, 0x1ee3ea0a, 0x41a82302, 0xab04350a, 0xf570beb7,
+    0xbb444b9b, 0x83021459, 0x838d65dc, 0x1c439c84, 0x6fdcc454, 0xef9ef325, 0x18626c1c, 0x020d251f,
+    0xc4aae786, 0x8614cb48, 0xf6f53ca6, 0x8710dbab, 0x89abec0d, 0xf29d41c1, 0x94b50336, 0xfdd49178,
+    0x604658d1, 0x800e85be, 0xca1bb079, 0x7fa48eeb, 0xa3b7fafe, 0xd330436b, 0x64eb604c, 0x43a658ae,
+    0x7caa1337, 0xddd445e6, 0x7efbf955, 0xb706ec71, 0x624a6b53, 0x9e0e231f, 0x97097248, 0xa1e1a17a,
+    0x68dd2e44, 0x7f9d2e14, 0xddcc7074, 0x58324197, 0xc88fc426, 0x6d3640ae, 0x7ef83600, 0x759a0270,
+    0x98b6d854, 0xd63c9b84, 0x372474a2, 0xe3f18cfd, 0x56ab0bdb, 0x85c9be7e, 0x47dfcfeb, 0xa5830d41,
+    0x0ddd6283, 0xf4f480ad, 0x74c60e38, 0xab8943c3, 0xc1508fe7, 0x480cdc39, 0x8e097362, 0xa44793be,
+    0x538b7e18, 0x545f5b41, 0x56529175, 0x9771a97e, 0xc2da7421, 0xea8265f2, 0x805d1163, 0x883c5d28,
+    0x8ba94c48, 0x4f676e65, 0xf78735b3, 0xe1853671, 0x7f454f53, 0x18147f85, 0x7d09e15d, 0xdb4f3494,
+    0x795c8973, 0x83310632, 0x85d8061c, 0x9a1a0ebf, 0xc125583c, 0x2a1b1a95, 0x7fd9103f, 0x71e98c72,
+    0x40932ed7, 0x91ed227a, 0x3c5e560e, 0xe816dee9, 0xb0891b80, 0x600038ba, 0xc7d9a80d, 0x7fff5e09,
+    0x7e3f4351, 0xbb6b4424, 0xb14448d4, 0x8d6bb7e1, 0xfb153626, 0xa68ad537, 0xd9782006, 0xf62f6991,
+    0x359ba8c1, 0x02ccff0b, 0x91bf2256, 0x7ea71c4d, 0x560ce5df, 0xeeba289b, 0xa574c4e7, 0x9e04f6ee,
+    0x7860a5ec, 0x0b8db4a2, 0x968ba3d7, 0x0b6c77df, 0xd6f3157d, 0x402eff1a, 0x49b820b3, 0x8152aebb,
+    0xd180b0b6, 0x098604d4, 0x7ff92224, 0xede9c996, 0x89c58061, 0x829624c4, 0xc6e71ea7, 0xba94d915,
+    0x389c3cf6, 0x5b4c5a06, 0x04b335e6, 0x516a8aab, 0x42c8d7d9, 0x92b12af6, 0x86c8549f, 0xfda98acf,
+    0x819673b6, 0x69545dac, 0x6feaa230, 0x726e6d3f, 0x886ebdfe, 0x34f5730a, 0x7af63ba2, 0x77307bbf,
+    0x7cd80630, 0x6e45efe0, 0x7f8ad7eb, 0x59d7df99, 0x86c70946, 0xda233629, 0x753f6cbf, 0x825eeb40,
+];
+
+/* scale a 64-bit energy down to a positive 32-bit word, returns (energy, power of 2 scalefactor) */
+fn scale_energy(e_curr: u64) -> (i32, i32) {
+    let hi = (e_curr >> 32) as u32;
+    let lo = e_curr as u32;
+    if hi != 0 {
+        let n_scale = (32 - hi.leading_zeros() as i32) + 1;
+        (((lo >> n_scale) | (hi << (32 - n_scale))) as i32, n_scale)
+    } else if lo >> 31 != 0 {
+        ((lo >> 1) as i32, 1)
+    } else {
+        (lo as i32, 0)
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    EstimateEnvelope
+ *
+ * Description: estimate power of generated HF QMF bands in one time-domain envelope
+ *                (4.6.18.7.3)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *              index of current envelope
+ *
+ * Outputs:     power of each QMF subband, stored as integer (Q0) * 2^N, N >= 0
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn estimate_envelope(psi: &mut PSInfoSBR, ch_base: usize, ch: usize, env: usize) {
+    let sbr_hdr = &psi.sbrHdr[ch_base];
+    let sbr_grid = &psi.sbrGrid[ch_base + ch];
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let x_buf = &psi.XBuf;
+
+    /* estimate current envelope */
+    let i_start = sbr_grid.envTimeBorder[env] as usize + HF_ADJ;
+    let i_end = sbr_grid.envTimeBorder[env + 1] as usize + HF_ADJ;
+    let freq_band_tab = if sbr_grid.freqRes[env] != 0 {
+        &sbr_freq.freqHigh[..=sbr_freq.nHigh]
+    } else {
+        &sbr_freq.freqLow[..=sbr_freq.nLow]
+    };
+    let k_start = sbr_freq.kStart;
+    let inv_time = INV_BAND_TAB[(i_end - i_start) - 1];
+
+    let mut exp_max = 0;
+    if sbr_hdr.interpFreq != 0 {
+        for m in 0..sbr_freq.numQMFBands {
+            let mut e_curr = 0u64;
+            for row in &x_buf[i_start..i_end] {
+                /* scale to int before calculating power (precision not critical, and avoids overflow) */
+                let xre = row[2 * (k_start + m)] >> FBITS_OUT_QMFA;
+                let xim = row[2 * (k_start + m) + 1] >> FBITS_OUT_QMFA;
+                e_curr = madd_64(e_curr, xre, xre);
+                e_curr = madd_64(e_curr, xim, xim);
+            }
+
+            /* eCurr is now Q(64 - 2*FBITS_OUT_QMFA) (64-bit word)
+             * if energy is too big to fit in 32-bit word (> 2^31) scale down by power of 2
+             */
+            let (t, n_scale) = scale_energy(e_curr);
+            psi.eCurr[m] = mulshift_32(t, inv_time);
+            psi.eCurrExp[m] = (n_scale + 1) as u8; /* +1 for invFact = Q31 */
+            exp_max = exp_max.max(psi.eCurrExp[m]);
+        }
+    } else {
+        for band in freq_band_tab.windows(2) {
+            let (m_start, m_end) = (band[0] as usize, band[1] as usize);
+            let mut e_curr = 0u64;
+            for row in &x_buf[i_start..i_end] {
+                for m in m_start..m_end {
+                    let xre = row[2 * m] >> FBITS_OUT_QMFA;
+                    let xim = row[2 * m + 1] >> FBITS_OUT_QMFA;
+                    e_curr = madd_64(e_curr, xre, xre);
+                    e_curr = madd_64(e_curr, xim, xim);
+                }
+            }
+
+            let (t, n_scale) = scale_energy(e_curr);
+            let inv_fact = mulshift_32(INV_BAND_TAB[(m_end - m_start) - 1], inv_time) << 1;
+            let t = mulshift_32(t, inv_fact);
+
+            psi.eCurr[m_start - k_start..m_end - k_start].fill(t);
+            psi.eCurrExp[m_start - k_start..m_end - k_start].fill((n_scale + 1) as u8); /* +1 for invFact = Q31 */
+            exp_max = exp_max.max((n_scale + 1) as u8);
+        }
+    }
+    psi.eCurrExpMax = exp_max;
+}
+
+/***********************************************************************************************************************
+ * Function:    GetSMapped
+ *
+ * Description: calculate SMapped (4.6.18.7.2)
+ *
+ * Inputs:      initialized SBRGrid struct for this channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for this channel
+ *              index of current envelope
+ *              index of current QMF band
+ *              la flag for this envelope
+ *
+ * Outputs:     none
+ *
+ * Return:      true if a sinusoid is present in this band
+ **********************************************************************************************************************/
+fn get_s_mapped(psi: &PSInfoSBR, ch_base: usize, ch: usize, env: usize, band: usize) -> bool {
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan = &psi.sbrChan[ch_base + ch];
+
+    let (band_start, band_end) = if psi.sbrGrid[ch_base + ch].freqRes[env] != 0 {
+        /* high resolution */
+        (band, band + 1)
+    } else {
+        /* low resolution (see CalcFreqLow() for mapping) */
+        let odd_flag = sbr_freq.nHigh & 0x01;
+        (
+            if band > 0 { 2 * band - odd_flag } else { 0 },
+            2 * (band + 1) - odd_flag,
+        )
+    };
+
+    /* sMapped = 1 if sIndexMapped == 1 for any frequency in this band */
+    (band_start..band_end).any(|band| {
+        let r = (sbr_freq.freqHigh[band + 1] as usize + sbr_freq.freqHigh[band] as usize) >> 1;
+        sbr_chan.addHarmonic[1][band] != 0
+            && (env as i32 >= psi.la || sbr_chan.addHarmonic[0][r] == 1)
+    })
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcMaxGain
+ *
+ * Description: calculate max gain in one limiter band (4.6.18.7.5)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *              index of current envelope
+ *              index of current limiter band
+ *              number of fraction bits in dequantized envelope
+ *                (max = Q(FBITS_OUT_DQ_ENV - 6) = Q23, can go negative)
+ *
+ * Outputs:     updated gainMax, gainMaxFBits, and sumEOrigMapped in PSInfoSBR struct
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn calc_max_gain(
+    psi: &mut PSInfoSBR,
+    ch_base: usize,
+    ch: usize,
+    env: usize,
+    lim: usize,
+    fbits_dq: i32,
+) {
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let m_start = sbr_freq.freqLimiter[lim] as usize; /* these are offsets from kStart */
+    let m_end = sbr_freq.freqLimiter[lim + 1] as usize;
+    let freq_band_tab = if psi.sbrGrid[ch_base + ch].freqRes[env] != 0 {
+        &sbr_freq.freqHigh[..]
+    } else {
+        &sbr_freq.freqLow[..]
+    };
+    let k_start = sbr_freq.kStart as i32;
+
+    /* calculate max gain to apply to signal in this limiter band */
+    let mut sum_e_curr = 0i32;
+    let mut sum_e_orig_mapped = 0i32;
+    let mut e_curr_exp_max = psi.eCurrExpMax as i32;
+    let mut e_om_gain_max = psi.eOMGainMax;
+    let mut env_band = psi.envBand;
+    for m in m_start..m_end {
+        /* map current QMF band to appropriate envelope band */
+        if m as i32 == freq_band_tab[(env_band + 1) as usize] as i32 - k_start {
+            env_band += 1;
+            e_om_gain_max = psi.envDataDequant[ch][env][env_band as usize] >> ACC_SCALE; /* summing max 48 bands */
+        }
+        sum_e_orig_mapped = sum_e_orig_mapped.wrapping_add(e_om_gain_max);
+
+        /* easy test for overflow on ARM */
+        sum_e_curr += psi.eCurr[m] >> (e_curr_exp_max - psi.eCurrExp[m] as i32).min(31);
+        if sum_e_curr >> 30 != 0 {
+            sum_e_curr >>= 1;
+            e_curr_exp_max += 1;
+        }
+    }
+    psi.eOMGainMax = e_om_gain_max;
+    psi.envBand = env_band;
+
+    let limiter_gains = psi.sbrHdr[ch_base].limiterGains as usize;
+    psi.gainMaxFBits = 30; /* Q30 tables */
+    let gain_max = if sum_e_curr == 0 {
+        /* any non-zero numerator * 1/EPS_0 is > G_MAX */
+        if sum_e_orig_mapped == 0 {
+            LIM_GAIN_TAB[limiter_gains] as i32
+        } else {
+            GAIN_MAX_SENTINEL
+        }
+    } else if sum_e_orig_mapped == 0 {
+        /* 1/(any non-zero denominator) * EPS_0 * limGainTab[x] is appx. 0 */
+        0
+    } else if limiter_gains != 3 {
+        /* sumEOrigMapped = Q(fbitsDQ - ACC_SCALE), sumECurr = Q(-eCurrExpMax) */
+        let q = mulshift_32(sum_e_orig_mapped, LIM_GAIN_TAB[limiter_gains] as i32); /* Q(fbitsDQ - ACC_SCALE - 2), gainMax = Q30  */
+        let z = sum_e_curr.leading_zeros() as i32 - 1;
+        let r = inv_r_normalized(sum_e_curr << z); /* in =  Q(z - eCurrExpMax), out = Q(29 + 31 - z + eCurrExpMax) */
+        psi.gainMaxFBits = 26 - z + e_curr_exp_max + fbits_dq - ACC_SCALE;
+        mulshift_32(q, r) /* Q(29 + 31 - z + eCurrExpMax + fbitsDQ - ACC_SCALE - 2 - 32) */
+    } else {
+        GAIN_MAX_SENTINEL
+    };
+    psi.sumEOrigMapped = sum_e_orig_mapped;
+    psi.gainMax = gain_max;
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcNoiseDivFactors
+ *
+ * Description: calculate 1/(1+Q) and Q/(1+Q) (4.6.18.7.4; 4.6.18.7.5)
+ *
+ * Inputs:      dequantized noise floor scalefactor
+ *
+ * Outputs:     none
+ *
+ * Return:      (1/(1+Q), Q/(1+Q)), format = Q31
+ **********************************************************************************************************************/
+fn calc_noise_div_factors(q: i32) -> (i32, i32) {
+    /* 1 + Q_orig */
+    let mut qp1 = q >> 1;
+    qp1 += 1 << (FBITS_OUT_DQ_NOISE - 1); /* >> 1 to avoid overflow when adding 1.0 */
+    let z = qp1.leading_zeros() as i32 - 1; /* z <= 31 - FBITS_OUT_DQ_NOISE */
+    qp1 <<= z; /* Q(FBITS_OUT_DQ_NOISE + z) = Q31 * 2^-(31 - (FBITS_OUT_DQ_NOISE + z)) */
+    let t = inv_r_normalized(qp1) << 1; /* Q30 * 2^(31 - (FBITS_OUT_DQ_NOISE + z)), guaranteed not to overflow */
+
+    /* normalize to Q31 */
+    let s = 31 - (FBITS_OUT_DQ_NOISE - 1) - z - 1; /* clearly z >= 0, z <= (30 - (FBITS_OUT_DQ_NOISE - 1)) */
+    (t >> s, mulshift_32(t, q) << (32 - FBITS_OUT_DQ_NOISE - s))
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcComponentGains
+ *
+ * Description: calculate gain of envelope, sinusoids, and noise in one limiter band
+ *                (4.6.18.7.5)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *              index of current envelope
+ *              index of current limiter band
+ *              number of fraction bits in dequantized envelope
+ *
+ * Outputs:     gains for envelope, sinusoids and noise
+ *              number of fraction bits for envelope gain
+ *              sum of the total gain for each component in this band
+ *              other updated state variables
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn calc_component_gains(
+    psi: &mut PSInfoSBR,
+    ch_base: usize,
+    ch: usize,
+    env: usize,
+    lim: usize,
+    fbits_dq: i32,
+) {
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_grid = &psi.sbrGrid[ch_base + ch];
+    let m_start = sbr_freq.freqLimiter[lim] as usize; /* these are offsets from kStart */
+    let m_end = sbr_freq.freqLimiter[lim + 1] as usize;
+    let k_start = sbr_freq.kStart;
+
+    let gain_max = psi.gainMax;
+    let gain_max_fbits = psi.gainMaxFBits;
+
+    let env_i = env as i32;
+    let la_prev = psi.sbrChan[ch_base + ch].laPrev;
+    let d = env_i != psi.la && env_i != la_prev;
+    let hi_res = sbr_grid.freqRes[env] != 0;
+    let last_env = env + 1 == sbr_grid.numEnv as usize;
+
+    /* figure out which noise floor this envelope is in (only 1 or 2 noise floors allowed) */
+    let noise_floor = usize::from(
+        sbr_grid.numNoiseFloors == 2 && sbr_grid.noiseTimeBorder[1] <= sbr_grid.envTimeBorder[env],
+    );
+
+    psi.sumECurrGLim = 0;
+    psi.sumSM = 0;
+    psi.sumQM = 0;
+    /* calculate energy of noise to add in this limiter band */
+    for m in m_start..m_end {
+        let sbr_freq = &psi.sbrFreq[ch_base];
+        let freq_band_tab = if hi_res {
+            &sbr_freq.freqHigh[..]
+        } else {
+            &sbr_freq.freqLow[..]
+        };
+        let kk = (m + k_start) as i32;
+
+        if kk == sbr_freq.freqNoise[(psi.noiseFloorBand + 1) as usize] as i32 {
+            /* map current QMF band to appropriate noise floor band (NOTE: freqLimiter[0] == freqLow[0] = freqHigh[0]) */
+            psi.noiseFloorBand += 1;
+            (psi.qp1Inv, psi.qqp1Inv) = calc_noise_div_factors(
+                psi.noiseDataDequant[ch][noise_floor][psi.noiseFloorBand as usize],
+            );
+        }
+        if kk == sbr_freq.freqHigh[(psi.highBand + 1) as usize] as i32 {
+            psi.highBand += 1;
+        }
+        if kk == freq_band_tab[(psi.sBand + 1) as usize] as i32 {
+            psi.sBand += 1;
+            psi.sMapped = get_s_mapped(psi, ch_base, ch, env, psi.sBand as usize);
+        }
+
+        /* get sIndexMapped for this QMF subband */
+        let sbr_freq = &psi.sbrFreq[ch_base];
+        let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+        let high_band = psi.highBand as usize;
+        let mut s_index_mapped = false;
+        let r = (sbr_freq.freqHigh[high_band + 1] as usize + sbr_freq.freqHigh[high_band] as usize)
+            >> 1;
+        if m + k_start == r {
+            /* r = center frequency, deltaStep = (env >= la || sIndexMapped'(r, numEnv'-1) == 1) */
+            if env_i >= psi.la || sbr_chan.addHarmonic[0][r] == 1 {
+                s_index_mapped = sbr_chan.addHarmonic[1][high_band] != 0;
+            }
+        }
+
+        /* save sine flags from last envelope in this frame:
+         *   addHarmonic[0][0...63] = saved sine present flag from previous frame, for each QMF subband
+         *   addHarmonic[1][0...nHigh-1] = addHarmonic bit from current frame, for each high-res frequency band
+         * from MPEG reference code - slightly different from spec
+         *   (sIndexMapped'(m,LE'-1) can still be 0 when numEnv == psi->la)
+         */
+        if last_env {
+            sbr_chan.addHarmonic[0][m + k_start] = if m + k_start == r {
+                sbr_chan.addHarmonic[1][high_band]
+            } else {
+                0
+            };
+        }
+
+        let mut gain = psi.envDataDequant[ch][env][psi.sBand as usize];
+        let mut qm = mulshift_32(gain, psi.qqp1Inv) << 1;
+        let sm = if s_index_mapped {
+            mulshift_32(gain, psi.qp1Inv) << 1
+        } else {
+            0
+        };
+
+        /* three cases: (sMapped == 0 && delta == 1), (sMapped == 0 && delta == 0), (sMapped == 1) */
+        if d && !psi.sMapped {
+            gain = mulshift_32(psi.qp1Inv, gain) << 1;
+        } else if psi.sMapped {
+            gain = mulshift_32(psi.qqp1Inv, gain) << 1;
+        }
+
+        /* gain, qm, sm = Q(fbitsDQ), gainMax = Q(fbitsGainMax) */
+        let e_curr = psi.eCurr[m];
+        let (gain_scale, fbits_gain) = if e_curr > 0 {
+            let z = e_curr.leading_zeros() as i32 - 1;
+            let r = inv_r_normalized(e_curr << z); /* in = Q(z - eCurrExp), out = Q(29 + 31 - z + eCurrExp) */
+            /* out = Q(29 + 31 - z + eCurrExp + fbitsDQ - 32) */
+            (
+                mulshift_32(gain, r),
+                29 + 31 - z + psi.eCurrExp[m] as i32 + fbits_dq - 32,
+            )
+        } else {
+            /* if eCurr == 0, then gain is unchanged (divide by EPS = 1) */
+            (gain, fbits_dq)
+        };
+
+        /* see if gain for this band exceeds max gain */
+        let max_flag = gain_max != GAIN_MAX_SENTINEL
+            && if fbits_gain >= gain_max_fbits {
+                (gain_scale >> (fbits_gain - gain_max_fbits).min(31)) > gain_max
+            } else {
+                gain_scale > (gain_max >> (gain_max_fbits - fbits_gain).min(31))
+            };
+
+        if max_flag {
+            /* gainScale > gainMax, calculate ratio with 32/16 division */
+            let mut q = 0;
+            let mut r = gain_scale; /* guaranteed > 0, else maxFlag could not have been set */
+            let z = r.leading_zeros() as i32;
+            if z < 16 {
+                q = 16 - z;
+                r >>= q; /* out = Q(fbitsGain - q) */
+            }
+
+            let z = (gain_max.leading_zeros() as i32 - 1).max(0);
+            let mut r = if r > 0 { (gain_max << z) / r } else { 0 }; /* out = Q((fbitsGainMax + z) - (fbitsGain - q)) */
+            let q = (gain_max_fbits + z) - (fbits_gain - q); /* r = Q(q) */
+            if q > 30 {
+                r >>= (q - 30).min(31);
+            } else {
+                r = clip_2n_shift30(r, (30 - q).min(30)); /* let r = Q30 since range = [0.0, 1.0) (clip to 0x3fffffff = 0.99999) */
+            }
+
+            qm = mulshift_32(qm, r) << 2;
+            gain = mulshift_32(gain, r) << 2;
+            psi.gLimBuf[m] = gain_max;
+            psi.gLimFbits[m] = gain_max_fbits;
+        } else {
+            psi.gLimBuf[m] = gain_scale;
+            psi.gLimFbits[m] = fbits_gain;
+        }
+
+        /* sumSM, sumQM, sumECurrGLim = Q(fbitsDQ - ACC_SCALE) */
+        psi.smBuf[m] = sm;
+        psi.sumSM = psi.sumSM.wrapping_add(sm >> ACC_SCALE);
+
+        psi.qmLimBuf[m] = qm;
+        if d && sm == 0 {
+            psi.sumQM = psi.sumQM.wrapping_add(qm >> ACC_SCALE);
+        }
+
+        /* eCurr * gain^2 same as gain^2, before division by eCurr
+         * (but note that gain != 0 even if eCurr == 0, since it's divided by eps)
+         */
+        if e_curr != 0 {
+            psi.sumECurrGLim = psi.sumECurrGLim.wrapping_add(gain >> ACC_SCALE);
+        }
+    }
+}
+
+/* square root of q with fbits_in fraction bits, justified to Q(fbits_out) with clipping */
+fn sqrt_boost(q: i32, fbits_in: i32, fbits_out: i32) -> i32 {
+    let (r, z) = sqrt_fix(q, fbits_in);
+    let z = z - fbits_out;
+    if z >= 0 {
+        r >> z.min(31)
+    } else {
+        clip_2n_shift30(r, (-z).min(30))
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    ApplyBoost
+ *
+ * Description: calculate and apply boost factor for envelope, sinusoids, and noise
+ *                in this limiter band (4.6.18.7.5)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block
+ *              index of current limiter band
+ *              number of fraction bits in dequantized envelope
+ *
+ * Outputs:     envelope gain, sinusoids and noise after scaling by gBoost
+ *              format = Q(FBITS_GLIM_BOOST) for envelope gain,
+ *                     = Q(FBITS_QLIM_BOOST) for noise
+ *                     = Q(FBITS_OUT_QMFA) for sinusoids
+ *
+ * Return:      none
+ *
+ * Notes:       after scaling, each component has at least 1 GB
+ **********************************************************************************************************************/
+fn apply_boost(psi: &mut PSInfoSBR, ch_base: usize, lim: usize, fbits_dq: i32) {
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let m_start = sbr_freq.freqLimiter[lim] as usize; /* these are offsets from kStart */
+    let m_end = sbr_freq.freqLimiter[lim + 1] as usize;
+
+    let sum_e_orig_mapped = psi.sumEOrigMapped >> 1;
+    let r = (psi.sumECurrGLim >> 1) + (psi.sumSM >> 1) + (psi.sumQM >> 1); /* 1 GB fine (sm and qm are mutually exclusive in acc) */
+    let (mut g_boost, mut z) = if r < (1 << (31 - 28)) {
+        /* any non-zero numerator * 1/EPS_0 is > GBOOST_MAX
+         * round very small r to zero to avoid scaling problems
+         */
+        (
+            if sum_e_orig_mapped == 0 {
+                1 << 28
+            } else {
+                GBOOST_MAX
+            },
+            0,
+        )
+    } else if sum_e_orig_mapped == 0 {
+        /* 1/(any non-zero denominator) * EPS_0 is appx. 0 */
+        (0, 0)
+    } else {
+        /* numerator (sumEOrigMapped) and denominator (r) have same Q format (before << z) */
+        let z = r.leading_zeros() as i32 - 1; /* z = [0, 27] */
+        (mulshift_32(sum_e_orig_mapped, inv_r_normalized(r << z)), z)
+    };
+
+    /* gBoost = Q(28 - z) */
+    if g_boost > (GBOOST_MAX >> z) {
+        g_boost = GBOOST_MAX;
+        z = 0;
+    }
+    g_boost <<= z; /* gBoost = Q28, minimum 1 GB */
+
+    /* convert gain, noise, sinusoids to fixed Q format, clipping if necessary
+     *   (rare, usually only happens at very low bitrates, introduces slight
+     *    distortion into final HF mapping, but should be inaudible)
+     */
+    for m in m_start..m_end {
+        /* let gLimBoost = Q24, since in practice the max values are usually 16 to 20
+         *   unless limiterGains == 3 (limiter off) and eCurr ~= 0 (i.e. huge gain, but only
+         *   because the envelope has 0 power anyway)
+         */
+        let q = mulshift_32(psi.gLimBuf[m], g_boost) << 2; /* Q(gLimFbits) * Q(28) --> Q(gLimFbits[m]-2) */
+        psi.gLimBoost[m] = sqrt_boost(q, psi.gLimFbits[m] - 2, FBITS_GLIM_BOOST);
+
+        /* << by 14, since integer sqrt of x < 2^16, and we want to leave 1 GB */
+        let q = mulshift_32(psi.qmLimBuf[m], g_boost) << 2; /* Q(fbitsDQ) * Q(28) --> Q(fbitsDQ-2) */
+        psi.qmLimBoost[m] = sqrt_boost(q, fbits_dq - 2, FBITS_QLIM_BOOST);
+
+        /* justify for adding to signal (xBuf) later */
+        let q = mulshift_32(psi.smBuf[m], g_boost) << 2; /* Q(fbitsDQ) * Q(28) --> Q(fbitsDQ-2) */
+        psi.smBoost[m] = sqrt_boost(q, fbits_dq - 2, FBITS_OUT_QMFA);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcGain
+ *
+ * Description: calculate and apply proper gain to HF components in one envelope
+ *                (4.6.18.7.5)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *              index of current envelope
+ *
+ * Outputs:     envelope gain, sinusoids and noise after scaling
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn calc_gain(psi: &mut PSInfoSBR, ch_base: usize, ch: usize, env: usize) {
+    /* initialize to -1 so that mapping limiter bands to env/noise bands works right on first pass */
+    psi.envBand = -1;
+    psi.noiseFloorBand = -1;
+    psi.sBand = -1;
+    psi.highBand = -1;
+
+    let fbits_dq = FBITS_OUT_DQ_ENV - psi.envDataDequantScale[ch][env] as i32; /* Q(29 - optional scalefactor) */
+    for lim in 0..psi.sbrFreq[ch_base].nLimiter {
+        /* the QMF bands are divided into lim regions (consecutive, non-overlapping) */
+        calc_max_gain(psi, ch_base, ch, env, lim, fbits_dq);
+        calc_component_gains(psi, ch_base, ch, env, lim, fbits_dq);
+        apply_boost(psi, ch_base, lim, fbits_dq);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    MapHF
+ *
+ * Description: map HF components to proper QMF bands, with optional gain smoothing
+ *                filter (4.6.18.7.6)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *              index of current envelope
+ *              reset flag (can be non-zero for first envelope only)
+ *
+ * Outputs:     complete reconstructed subband QMF samples for this envelope
+ *
+ * Return:      none
+ *
+ * Notes:       ensures that output has >= MIN_GBITS_IN_QMFS guard bits,
+ *                so it's not necessary to check anything in the synth QMF
+ **********************************************************************************************************************/
+fn map_hf(psi: &mut PSInfoSBR, ch_base: usize, ch: usize, env: usize, hf_reset: bool) {
+    let sbr_grid = &psi.sbrGrid[ch_base + ch];
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+    let n_bands = sbr_freq.numQMFBands;
+    let k_start = sbr_freq.kStart;
+
+    let mut noise_tab_index = sbr_chan.noiseTabIndex;
+    let mut sin_index = sbr_chan.sinIndex;
+    let mut gain_noise_index = sbr_chan.gainNoiseIndex; /* oldest entries in filter delay buffer */
+
+    let h_sl = if psi.sbrHdr[ch_base].smoothMode != 0 {
+        0
+    } else {
+        4
+    };
+
+    if hf_reset {
+        noise_tab_index = 2; /* starts at 1, double since complex */
+        for _ in 0..h_sl {
+            sbr_chan.gTemp[gain_noise_index][..n_bands].copy_from_slice(&psi.gLimBoost[..n_bands]);
+            sbr_chan.qTemp[gain_noise_index][..n_bands].copy_from_slice(&psi.qmLimBoost[..n_bands]);
+            gain_noise_index = (gain_noise_index + 1) % MAX_NUM_SMOOTH_COEFS;
+        }
+    }
+
+    let env_i = env as i32;
+    let no_smoothing = env_i == psi.la || env_i == sbr_chan.laPrev;
+    let i_start = sbr_grid.envTimeBorder[env] as usize;
+    let i_end = sbr_grid.envTimeBorder[env + 1] as usize;
+    for i in i_start..i_end {
+        /* save new values in temp buffers (delay)
+         * we only store MAX_NUM_SMOOTH_COEFS most recent values,
+         *   so don't keep storing the same value over and over
+         */
+        if i - i_start < MAX_NUM_SMOOTH_COEFS {
+            sbr_chan.gTemp[gain_noise_index][..n_bands].copy_from_slice(&psi.gLimBoost[..n_bands]);
+            sbr_chan.qTemp[gain_noise_index][..n_bands].copy_from_slice(&psi.qmLimBoost[..n_bands]);
+        }
+
+        /* see 4.6.18.7.6 */
+        let row = &mut psi.XBuf[i + HF_ADJ][2 * k_start..2 * (k_start + n_bands)];
+        let mut gb_mask = 0u32;
+        for m in 0..n_bands {
+            if no_smoothing {
+                /* no smoothing filter for gain, and qFilt = 0 (only need to do once) */
+                if i == i_start {
+                    psi.gFiltLast[m] = sbr_chan.gTemp[gain_noise_index][m];
+                    psi.qFiltLast[m] = 0;
+                }
+            } else if h_sl == 0 {
+                /* no smoothing filter for gain, (only need to do once) */
+                if i == i_start {
+                    psi.gFiltLast[m] = sbr_chan.gTemp[gain_noise_index][m];
+                    psi.qFiltLast[m] = sbr_chan.qTemp[gain_noise_index][m];
+                }
+            } else if i - i_start < MAX_NUM_SMOOTH_COEFS {
+                /* apply smoothing filter to gain and noise (after MAX_NUM_SMOOTH_COEFS, it's always the same) */
+                let mut g_filt = 0;
+                let mut q_filt = 0;
+                let mut idx = gain_noise_index;
+                for &coef in &H_SMOOTH_COEF {
+                    /* sum(abs(hSmoothCoef[j])) for all j < 1.0 */
+                    g_filt += mulshift_32(sbr_chan.gTemp[idx][m], coef);
+                    q_filt += mulshift_32(sbr_chan.qTemp[idx][m], coef);
+                    idx = (idx + MAX_NUM_SMOOTH_COEFS - 1) % MAX_NUM_SMOOTH_COEFS;
+                }
+                psi.gFiltLast[m] = g_filt << 1; /* restore to Q(FBITS_GLIM_BOOST) (gain of filter < 1.0, so no overflow) */
+                psi.qFiltLast[m] = q_filt << 1; /* restore to Q(FBITS_QLIM_BOOST) */
+            }
+
+            let (smre, smim) = if psi.smBoost[m] != 0 {
+                /* add scaled signal and sinusoid, don't add noise (qFilt = 0)
+                 * sinIndex:  [0] xre += sm   [1] xim += sm*s   [2] xre -= sm   [3] xim -= sm*s
+                 */
+                let sm = if sin_index >= 2 {
+                    -psi.smBoost[m]
+                } else {
+                    psi.smBoost[m]
+                };
+                noise_tab_index += 2; /* noise filtered by 0, but still need to bump index */
+                if sin_index & 0x01 == 0 {
+                    (sm, 0)
+                } else if (m + k_start) & 0x01 != 0 {
+                    (0, -sm)
+                } else {
+                    (0, sm)
+                }
+            } else {
+                /* add scaled signal and scaled noise */
+                let q_filt = psi.qFiltLast[m];
+                let nre = NOISE_TAB[noise_tab_index] as i32;
+                let nim = NOISE_TAB[noise_tab_index + 1] as i32;
+                noise_tab_index += 2;
+                (
+                    mulshift_32(nre, q_filt) >> (FBITS_QLIM_BOOST - 1 - FBITS_OUT_QMFA),
+                    mulshift_32(nim, q_filt) >> (FBITS_QLIM_BOOST - 1 - FBITS_OUT_QMFA),
+                )
+            };
+            noise_tab_index &= 1023; /* 512 complex numbers */
+
+            let g_filt = psi.gFiltLast[m];
+            let xre =
+                clip_2n_shift30(mulshift_32(g_filt, row[2 * m]), 32 - FBITS_GLIM_BOOST) + smre;
+            let xim =
+                clip_2n_shift30(mulshift_32(g_filt, row[2 * m + 1]), 32 - FBITS_GLIM_BOOST) + smim;
+            row[2 * m] = xre;
+            row[2 * m + 1] = xim;
+
+            gb_mask |= xre.unsigned_abs() | xim.unsigned_abs();
+        }
+        /* update circular buffer index */
+        gain_noise_index = (gain_noise_index + 1) % MAX_NUM_SMOOTH_COEFS;
+        sin_index = (sin_index + 1) & 3;
+
+        /* ensure MIN_GBITS_IN_QMFS guard bits in output
+         * almost never occurs in practice, but checking here makes synth QMF logic very simple
+         */
+        if gb_mask >> (31 - MIN_GBITS_IN_QMFS) != 0 {
+            for x in row.iter_mut() {
+                *x = clip_2n(*x, 31 - MIN_GBITS_IN_QMFS);
+            }
+            gb_mask = gb_mask.min(1 << (31 - MIN_GBITS_IN_QMFS));
+        }
+        sbr_chan.gbMask[((i + HF_ADJ) >> 5) & 0x01] |= gb_mask;
+    }
+    sbr_chan.noiseTabIndex = noise_tab_index;
+    sbr_chan.sinIndex = sin_index;
+    sbr_chan.gainNoiseIndex = gain_noise_index;
+}
+
+/***********************************************************************************************************************
+ * Function:    AdjustHighFreq
+ *
+ * Description: adjust high frequencies and add noise and sinusoids (4.6.18.7)
+ *
+ * Inputs:      initialized PSInfoSBR struct
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *
+ * Outputs:     complete reconstructed subband QMF samples for this channel
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn adjust_high_freq(psi: &mut PSInfoSBR, ch_base: usize, ch: usize) {
+    let sbr_grid = &psi.sbrGrid[ch_base + ch];
+    let frame_class = sbr_grid.frameClass;
+    let pointer = sbr_grid.pointer as i32;
+    let num_env = sbr_grid.numEnv as usize;
+
+    /* derive la from table 4.159 */
+    psi.la = if (frame_class == SBR_GRID_FIXVAR || frame_class == SBR_GRID_VARVAR) && pointer > 0 {
+        num_env as i32 + 1 - pointer
+    } else if frame_class == SBR_GRID_VARFIX && pointer > 1 {
+        pointer - 1
+    } else {
+        -1
+    };
+
+    /* for each envelope, estimate gain and adjust SBR QMF bands */
+    let mut hf_reset = psi.sbrChan[ch_base + ch].reset != 0;
+    for env in 0..num_env {
+        estimate_envelope(psi, ch_base, ch, env);
+        calc_gain(psi, ch_base, ch, env);
+        map_hf(psi, ch_base, ch, env, hf_reset);
+        hf_reset = false; /* only set for first envelope after header reset */
+    }
+
+    /* set saved sine flags to 0 for QMF bands outside of current frequency range */
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+    let lo = sbr_freq.freqLimiter[0] as usize + sbr_freq.kStart;
+    let hi = sbr_freq.freqLimiter[sbr_freq.nLimiter] as usize + sbr_freq.kStart;
+    sbr_chan.addHarmonic[0][..lo].fill(0);
+    sbr_chan.addHarmonic[0][hi.min(64)..].fill(0);
+    sbr_chan.addHarmonicFlag[0] = sbr_chan.addHarmonicFlag[1];
+
+    /* save la for next frame */
+    sbr_chan.laPrev = if psi.la == num_env as i32 { 0 } else { -1 };
+}
@@ -0,0 +1,356 @@
+//! HF generation (14496-3, 4.6.18.6): linear prediction from the low band and patching into
+//! the high band of the QMF matrix.
+
+use super::math::{clip_2n_shift30, inv_r_normalized};
+use super::{
+    FBITS_LPCOEFS, FBITS_OUT_QMFA, HF_ADJ, MAG_16, NUM_TIME_SLOTS, PSInfoSBR, RELAX_COEF,
+    SAMPLES_PER_SLOT,
+};
+use crate::mp3_decoder::{madd_64, mulshift_32};
+
+/* number of QMF samples used for the covariance, including the 2 previous ones */
+const NUM_LPC_SAMPS: usize = NUM_TIME_SLOTS * SAMPLES_PER_SLOT + 6 + 2;
+
+/* newBWTab[prev invfMode][curr invfMode], format = Q31 (table 4.158) */
+const NEW_BW_TAB: [[i32; 4]; 4] = [
+    [0x00000000, 0x4ccccccd, 0x73333333, 0x7d70a3d7],
+    [0x4ccccccd, 0x60000000, 0x73333333, 0x7d70a3d7],
+    [0x00000000, 0x60000000, 0x73333333, 0x7d70a3d7],
+    [0x00000000, 0x60000000, 0x73333333, 0x7d70a3d7],
+];
+
+/***********************************************************************************************************************
+ * Function:    CVKernel1
+ *
+ * Description: kernel of covariance matrix calculation for p01, p11, p12, p22
+ *
+ * Inputs:      low-freq samples of one subband, starting at time index = 0
+ *
+ * Outputs:     none
+ *
+ * Return:      64-bit accumulators [p01re, p01im, p11re, p12re, p12im, p22re]
+ **********************************************************************************************************************/
+fn cv_kernel_1(x: &[[i32; 2]; NUM_LPC_SAMPS]) -> [i64; 6] {
+    let [mut x0re, mut x0im] = x[0];
+    let [mut x1re, mut x1im] = x[1];
+
+    let (mut p01re, mut p01im, mut p11re) = (0u64, 0u64, 0u64);
+    let mut p12re = madd_64(madd_64(0, x1re, x0re), x1im, x0im);
+    let mut p12im = madd_64(madd_64(0, x0re, x1im), -x0im, x1re);
+    let mut p22re = madd_64(madd_64(0, x0re, x0re), x0im, x0im);
+
+    for &[re, im] in &x[2..] {
+        x0re = x1re;
+        x0im = x1im;
+        x1re = re;
+        x1im = im;
+
+        p01re = madd_64(p01re, x1re, x0re);
+        p01re = madd_64(p01re, x1im, x0im);
+        p01im = madd_64(p01im, x0re, x1im);
+        p01im = madd_64(p01im, -x0im, x1re);
+        p11re = madd_64(p11re, x0re, x0re);
+        p11re = madd_64(p11re, x0im, x0im);
+    }
+
+    /* these can be derived by slight changes to account for boundary conditions */
+    p12re = p12re.wrapping_add(p01re);
+    p12re = madd_64(p12re, x1re, -x0re);
+    p12re = madd_64(p12re, x1im, -x0im);
+    p12im = p12im.wrapping_add(p01im);
+    p12im = madd_64(p12im, x0re, -x1im);
+    p12im = madd_64(p12im, x0im, x1re);
+    p22re = p22re.wrapping_add(p11re);
+    p22re = madd_64(p22re, x0re, -x0re);
+    p22re = madd_64(p22re, x0im, -x0im);
+
+    [p01re, p01im, p11re, p12re, p12im, p22re].map(|p| p as i64)
+}
+
+/***********************************************************************************************************************
+ * Function:    CVKernel2
+ *
+ * Description: kernel of covariance matrix calculation for p02
+ *
+ * Inputs:      low-freq samples of one subband, starting at time index = 0
+ *
+ * Outputs:     none
+ *
+ * Return:      64-bit accumulators [p02re, p02im]
+ **********************************************************************************************************************/
+fn cv_kernel_2(x: &[[i32; 2]; NUM_LPC_SAMPS]) -> [i64; 2] {
+    let (mut p02re, mut p02im) = (0u64, 0u64);
+
+    for w in x.windows(3).take(NUM_TIME_SLOTS * SAMPLES_PER_SLOT + 6) {
+        let ([x0re, x0im], [x2re, x2im]) = (w[0], w[2]);
+        p02re = madd_64(p02re, x2re, x0re);
+        p02re = madd_64(p02re, x2im, x0im);
+        p02im = madd_64(p02im, x0re, x2im);
+        p02im = madd_64(p02im, -x0im, x2re);
+    }
+
+    [p02re as i64, p02im as i64]
+}
+
+/* scale the 64-bit accumulators (2*FBITS_OUT_QMFA fraction bits) to 32-bit integers with 2 GB,
+ *   keeping the top 30 non-zero bits, returns the power of 2 scalefactor of the outputs
+ */
+fn normalize_covariance<const N: usize>(acc: [i64; N], out: &mut [i32; N]) -> i32 {
+    let mask = acc.iter().fold(0, |m, &p| m | p.unsigned_abs());
+    let n = 64 - mask.leading_zeros() as i32; /* number of non-zero bits in bottom of 64-bit word */
+
+    if n <= 30 {
+        let lo_shift = 30 - n;
+        for (o, &p) in out.iter_mut().zip(&acc) {
+            *o = (p << lo_shift) as i32;
+        }
+        -(lo_shift + 2 * FBITS_OUT_QMFA)
+    } else {
+        let shift = n - 30;
+        for (o, &p) in out.iter_mut().zip(&acc) {
+            *o = (p >> shift) as i32;
+        }
+        shift - 2 * FBITS_OUT_QMFA
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    CalcLPCoefs
+ *
+ * Description: calculate linear prediction coefficients for one subband (4.6.18.6.2)
+ *
+ * Inputs:      QMF matrix of low-freq samples
+ *              subband index
+ *              number of guard bits in input sample buffer
+ *
+ * Outputs:     none
+ *
+ * Return:      complex LP coefficients [a0re, a0im, a1re, a1im], format = Q29
+ *
+ * Notes:       output coefficients clipped to range (-4, 4)
+ *              if the complex coefficients have magnitude >= 4.0, they are all
+ *                set to 0 (see spec)
+ **********************************************************************************************************************/
+fn calc_lp_coefs(x_buf: &[[i32; 128]; 40], p: usize, gb: i32) -> [i32; 4] {
+    /* pre-scale to avoid overflow - probably never happens in practice (see QMFA)
+     *   max bit growth per accumulator = 38*2 = 76 mul-adds (X * X)
+     *   using 64-bit MADD, so if X has n guard bits, X*X has 2n+1 guard bits
+     *   gain 1 extra sign bit per multiply, so ensure ceil(log2(76/2) / 2) = 3 guard bits on inputs
+     */
+    let nd = (3 - gb).max(0);
+    let mut x = [[0; 2]; NUM_LPC_SAMPS];
+    for (xs, row) in x.iter_mut().zip(x_buf) {
+        *xs = [row[2 * p] >> nd, row[2 * p + 1] >> nd];
+    }
+
+    /* calculate covariance elements */
+    let mut p1 = [0; 6];
+    let mut p2 = [0; 2];
+    let n1 = normalize_covariance(cv_kernel_1(&x), &mut p1);
+    let n2 = normalize_covariance(cv_kernel_2(&x), &mut p2);
+
+    /* normalize everything to larger power of 2 scalefactor */
+    if n1 < n2 {
+        let nd = (n2 - n1).min(31);
+        p1.iter_mut().for_each(|p| *p >>= nd);
+    } else if n1 > n2 {
+        let nd = (n1 - n2).min(31);
+        p2.iter_mut().for_each(|p| *p >>= nd);
+    }
+    let [p01re, p01im, p11re, p12re, p12im, p22re] = p1;
+    let [p02re, p02im] = p2;
+
+    /* calculate determinant of covariance matrix (at least 1 GB in pXX) */
+    let mut d = mulshift_32(p12re, p12re) + mulshift_32(p12im, p12im);
+    d = mulshift_32(d, RELAX_COEF) << 1;
+    d = mulshift_32(p11re, p22re) - d;
+
+    let mut z_flag = false;
+    let (mut a0re, mut a0im, mut a1re, mut a1im) = (0, 0, 0, 0);
+    if d > 0 {
+        /* input =   Q31  d    = Q(-2*n1 - 32 + nd) = Q31 * 2^(31 + 2*n1 + 32 - nd)
+         * inverse = Q29  dInv = Q29 * 2^(-31 - 2*n1 - 32 + nd) = Q(29 + 31 + 2*n1 + 32 - nd)
+         *
+         * numerator has same Q format as d, since it's sum of normalized squares
+         * so num * inverse = Q(-2*n1 - 32) * Q(29 + 31 + 2*n1 + 32 - nd)
+         *                  = Q(29 + 31 - nd), drop low 32 in MULSHIFT32
+         *                  = Q(29 + 31 - 32 - nd) = Q(28 - nd)
+         */
+        let nd = d.leading_zeros() as i32 - 1;
+        let d_inv = inv_r_normalized(d << nd);
+
+        /* 1 GB in pXX */
+        let mut tre =
+            mulshift_32(p01re, p12re) - mulshift_32(p01im, p12im) - mulshift_32(p02re, p11re);
+        tre = mulshift_32(tre, d_inv);
+        let mut tim =
+            mulshift_32(p01re, p12im) + mulshift_32(p01im, p12re) - mulshift_32(p02im, p11re);
+        tim = mulshift_32(tim, d_inv);
+
+        /* if d is extremely small, just set coefs to 0 (would have poor precision anyway) */
+        if nd > 28
+            || (tre.unsigned_abs() >> (28 - nd)) >= 4
+            || (tim.unsigned_abs() >> (28 - nd)) >= 4
+        {
+            z_flag = true;
+        } else {
+            a1re = tre << (FBITS_LPCOEFS - 28 + nd); /* i.e. convert Q(28 - nd) to Q(29) */
+            a1im = tim << (FBITS_LPCOEFS - 28 + nd);
+        }
+    }
+
+    if p11re > 0 {
+        /* input =   Q31  p11re = Q(-n1 + nd) = Q31 * 2^(31 + n1 - nd)
+         * inverse = Q29  dInv  = Q29 * 2^(-31 - n1 + nd) = Q(29 + 31 + n1 - nd)
+         *
+         * numerator is Q(-n1 - 3)
+         * so num * inverse = Q(-n1 - 3) * Q(29 + 31 + n1 - nd)
+         *                  = Q(29 + 31 - 3 - nd), drop low 32 in MULSHIFT32
+         *                  = Q(29 + 31 - 3 - 32 - nd) = Q(25 - nd)
+         */
+        let nd = p11re.leading_zeros() as i32 - 1;
+        let d_inv = inv_r_normalized(p11re << nd);
+
+        /* a1re, a1im = Q29, so scaled by (n1 + 3) */
+        let mut tre = (p01re >> 3) + mulshift_32(p12re, a1re) + mulshift_32(p12im, a1im);
+        tre = -mulshift_32(tre, d_inv);
+        let mut tim = (p01im >> 3) - mulshift_32(p12im, a1re) + mulshift_32(p12re, a1im);
+        tim = -mulshift_32(tim, d_inv);
+
+        if nd > 25
+            || (tre.unsigned_abs() >> (25 - nd)) >= 4
+            || (tim.unsigned_abs() >> (25 - nd)) >= 4
+        {
+            z_flag = true;
+        } else {
+            a0re = tre << (FBITS_LPCOEFS - 25 + nd); /* i.e. convert Q(25 - nd) to Q(29) */
+            a0im = tim << (FBITS_LPCOEFS - 25 + nd);
+        }
+    }
+
+    /* see 4.6.18.6.2 - if magnitude of a0 or a1 >= 4 then a0 = a1 = 0
+     * i.e. a0re < 4, a0im < 4, a1re < 4, a1im < 4
+     * Q29*Q29 = Q26
+     */
+    if z_flag
+        || mulshift_32(a0re, a0re) + mulshift_32(a0im, a0im) >= MAG_16
+        || mulshift_32(a1re, a1re) + mulshift_32(a1im, a1im) >= MAG_16
+    {
+        return [0; 4];
+    }
+    [a0re, a0im, a1re, a1im]
+}
+
+/***********************************************************************************************************************
+ * Function:    GenerateHighFreq
+ *
+ * Description: generate high frequencies with SBR (4.6.18.6)
+ *
+ * Inputs:      initialized SBRGrid struct for this channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for this channel
+ *              index of first channel of the SCE/CPE block, index of current channel
+ *
+ * Outputs:     new high frequency samples starting at frequency kStart
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn generate_high_freq(psi: &mut PSInfoSBR, ch_base: usize, ch: usize) {
+    let sbr_grid = &psi.sbrGrid[ch_base + ch];
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+    let x_buf = &mut psi.XBuf;
+
+    /* calculate array of chirp factors */
+    for band in 0..sbr_freq.numNoiseFloorBands {
+        let c = sbr_chan.chirpFact[band]; /* previous (bwArray') */
+        let new_bw =
+            NEW_BW_TAB[sbr_chan.invfMode[0][band] as usize][sbr_chan.invfMode[1][band] as usize];
+
+        /* weighted average of new and old (can't overflow - total gain = 1.0) */
+        let mut t = if new_bw < c {
+            mulshift_32(new_bw, 0x60000000) + mulshift_32(0x20000000, c) /* new is smaller: 0.75*new + 0.25*old */
+        } else {
+            mulshift_32(new_bw, 0x74000000) + mulshift_32(0x0c000000, c) /* new is larger: 0.90625*new + 0.09375*old */
+        };
+        t <<= 1;
+
+        if t < 0x02000000 {
+            t = 0; /* below 0.015625, clip to 0 */
+        }
+        t = t.min(0x7f800000); /* clip to 0.99609375 */
+
+        /* save curr as prev for next time */
+        sbr_chan.chirpFact[band] = t;
+        sbr_chan.invfMode[0][band] = sbr_chan.invfMode[1][band];
+    }
+
+    let i_start = sbr_grid.envTimeBorder[0] as usize + HF_ADJ;
+    let i_end = sbr_grid.envTimeBorder[sbr_grid.numEnv as usize] as usize + HF_ADJ;
+
+    /* generate new high freqs from low freqs, patches, and chirp factors */
+    let mut k = sbr_freq.kStart;
+    let mut g = 0;
+    let mut bw = sbr_chan.chirpFact[g];
+    let mut bwsq = mulshift_32(bw, bw) << 1;
+
+    let gb = (sbr_chan.gbMask[0] | sbr_chan.gbMask[1]).leading_zeros() as i32 - 1; /* older 32 | newer 8 */
+
+    for patch in 0..sbr_freq.numPatches as usize {
+        for x in 0..sbr_freq.patchNumSubbands[patch] as usize {
+            /* map k to corresponding noise floor band */
+            if g + 1 < sbr_freq.numNoiseFloorBands && k >= sbr_freq.freqNoise[g + 1] as usize {
+                g += 1;
+                bw = sbr_chan.chirpFact[g]; /* Q31 */
+                bwsq = mulshift_32(bw, bw) << 1; /* Q31 */
+            }
+
+            let p = sbr_freq.patchStartSubband[patch] as usize + x; /* low QMF band */
+            if bw != 0 {
+                let [a0re, a0im, a1re, a1im] = calc_lp_coefs(x_buf, p, gb);
+
+                let a0re = mulshift_32(bw, a0re); /* Q31 * Q29 = Q28 */
+                let a0im = mulshift_32(bw, a0im);
+                let a1re = mulshift_32(bwsq, a1re);
+                let a1im = mulshift_32(bwsq, a1im);
+
+                let (mut x2re, mut x2im) =
+                    (x_buf[i_start - 2][2 * p], x_buf[i_start - 2][2 * p + 1]); /* XBuf[n-2] */
+                let (mut x1re, mut x1im) =
+                    (x_buf[i_start - 1][2 * p], x_buf[i_start - 1][2 * p + 1]); /* XBuf[n-1] */
+
+                for (i, row) in x_buf.iter_mut().enumerate().take(i_end).skip(i_start) {
+                    /* a0re/im, a1re/im are Q28 with at least 1 GB,
+                     *   so the summing for ACCre/im is fine (1 GB in, plus 1 from MULSHIFT32)
+                     */
+                    let mut acc_re = mulshift_32(x2re, a1re) - mulshift_32(x2im, a1im);
+                    let mut acc_im = mulshift_32(x2re, a1im) + mulshift_32(x2im, a1re);
+                    x2re = x1re;
+                    x2im = x1im;
+
+                    acc_re += mulshift_32(x1re, a0re) - mulshift_32(x1im, a0im);
+                    acc_im += mulshift_32(x1re, a0im) + mulshift_32(x1im, a0re);
+                    x1re = row[2 * p]; /* XBuf[n] */
+                    x1im = row[2 * p + 1];
+
+                    /* lost 4 fbits when scaling by a0re/im, a1re/im (Q28) */
+                    acc_re = clip_2n_shift30(acc_re, 4).wrapping_add(x1re);
+                    acc_im = clip_2n_shift30(acc_im, 4).wrapping_add(x1im);
+
+                    row[2 * k] = acc_re;
+                    row[2 * k + 1] = acc_im;
+
+                    /* update guard bit masks */
+                    sbr_chan.gbMask[(i >> 5) & 0x01] |=
+                        acc_re.unsigned_abs() | acc_im.unsigned_abs(); /* 0 if i < 32, 1 if i >= 32 */
+                }
+            } else {
+                for row in &mut x_buf[i_start..i_end] {
+                    row[2 * k] = row[2 * p];
+                    row[2 * k + 1] = row[2 * p + 1];
+                }
+            }
+            k += 1; /* high QMF band */
+        }
+    }
+}
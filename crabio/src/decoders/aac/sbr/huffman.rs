@@ -0,0 +1,525 @@
+//! Huffman decoding, dequantisation and uncoupling of the SBR envelope and noise floor
+//! scalefactors (14496-3, 4.6.18.3.3 - 4.6.18.3.5 and appendix 4.A.6.1).
+
+use super::super::huffman::{HuffInfo, decode_huffman_scalar};
+use super::{FBITS_OUT_DQ_NOISE, NOISE_FLOOR_OFFSET, PSInfoSBR};
+use crate::mp3_decoder::mulshift_32;
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+/* indices into HUFF_TAB_SBR_INFO */
+const HUFF_TAB_SBR_T_ENV15: usize = 0;
+const HUFF_TAB_SBR_F_ENV15: usize = 1;
+const HUFF_TAB_SBR_T_ENV15B: usize = 2;
+const HUFF_TAB_SBR_F_ENV15B: usize = 3;
+const HUFF_TAB_SBR_T_ENV30: usize = 4;
+const HUFF_TAB_SBR_F_ENV30: usize = 5;
+const HUFF_TAB_SBR_T_ENV30B: usize = 6;
+const HUFF_TAB_SBR_F_ENV30B: usize = 7;
+const HUFF_TAB_SBR_T_NOISE30: usize = 8;
+const HUFF_TAB_SBR_F_NOISE30: usize = HUFF_TAB_SBR_F_ENV30;
+const HUFF_TAB_SBR_T_NOISE30B: usize = 9;
+const HUFF_TAB_SBR_F_NOISE30B: usize = HUFF_TAB_SBR_F_ENV30B;
+
+pub const HUFF_TAB_SBR_INFO: [HuffInfo; 10] = [
+    HuffInfo {
+        max_bits: 19,
+        count: [0, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 2, 3, 4, 2, 7, 4, 8, 72, 0],
+        offset: 0,
+    },
+    HuffInfo {
+        max_bits: 20,
+        count: [
+            0, 2, 2, 2, 2, 2, 1, 3, 3, 2, 4, 4, 4, 3, 2, 5, 6, 13, 15, 46,
+        ],
+        offset: 121,
+    },
+    HuffInfo {
+        max_bits: 17,
+        count: [1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 2, 2, 0, 0, 1, 25, 10, 0, 0, 0],
+        offset: 242,
+    },
+    HuffInfo {
+        max_bits: 19,
+        count: [1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 3, 1, 0, 1, 1, 2, 1, 29, 2, 0],
+        offset: 291,
+    },
+    HuffInfo {
+        max_bits: 19,
+        count: [1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 2, 1, 2, 5, 1, 4, 2, 3, 34, 0],
+        offset: 340,
+    },
+    HuffInfo {
+        max_bits: 20,
+        count: [1, 1, 1, 1, 1, 1, 0, 2, 2, 2, 2, 2, 1, 2, 3, 4, 4, 7, 10, 16],
+        offset: 403,
+    },
+    HuffInfo {
+        max_bits: 14,
+        count: [1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1, 13, 2, 0, 0, 0, 0, 0, 0],
+        offset: 466,
+    },
+    HuffInfo {
+        max_bits: 14,
+        count: [1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 6, 8, 0, 0, 0, 0, 0, 0],
+        offset: 491,
+    },
+    HuffInfo {
+        max_bits: 14,
+        count: [1, 1, 1, 1, 1, 1, 0, 2, 0, 1, 1, 0, 51, 2, 0, 0, 0, 0, 0, 0],
+        offset: 516,
+    },
+    HuffInfo {
+        max_bits: 8,
+        count: [1, 1, 1, 0, 1, 1, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        offset: 579,
+    },
+];
+
+/* Huffman tables from appendix 4.A.6.1, includes offset of -LAV[i] for table i */
+pub const HUFF_TAB_SBR: [i16; 604] = [
+    /* SBR table sbr_tenv15 [121] (signed) */
+    0, -1, 1, -2, 2, -3, 3, -4, 4, -5, 5, -6, 6, -7, 7, -8, -9, 8, -10, 9, -11, 10, -12, -13, 11,
+    -14, 12, -15, -16, 13, -19, -18, -17, 14, -24, -20, 16, -26, -21, 15, -23, -25, -22, -60, -59,
+    -58, -57, -56, -55, -54, -53, -52, -51, -50, -49, -48, -47, -46, -45, -44, -43, -42, -41, -40,
+    -39, -38, -37, -36, -35, -34, -33, -32, -31, -30, -29, -28, -27, 17, 18, 19, 20, 21, 22, 23,
+    24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47,
+    48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60,
+    /* SBR table sbr_fenv15 [121] (signed) */
+    0, -1, 1, -2, -3, 2, -4, 3, -5, 4, -6, 5, -7, 6, -8, 7, -9, 8, -10, 9, -11, 10, 11, -12, 12,
+    -13, 13, 14, -14, -15, 15, 16, 17, -16, -17, -18, -19, 18, 19, -20, -21, 20, 21, -24, -23, -22,
+    -26, -28, 22, 23, 25, -41, -25, 26, 27, -30, -27, 24, 28, 44, -51, -46, -44, -43, -37, -33,
+    -31, -29, 30, 37, 42, 47, 48, -60, -59, -58, -57, -56, -55, -54, -53, -52, -50, -49, -48, -47,
+    -45, -42, -40, -39, -38, -36, -35, -34, -32, 29, 31, 32, 33, 34, 35, 36, 38, 39, 40, 41, 43,
+    45, 46, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60,
+    /* SBR table sbr_tenv15b [49] (signed) */
+    0, 1, -1, 2, -2, 3, -3, 4, -4, -5, 5, -6, 6, 7, -7, 8, -24, -23, -22, -21, -20, -19, -18, -17,
+    -16, -15, -14, -13, -12, -11, -10, -9, -8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21,
+    22, 23, 24, /* SBR table sbr_fenv15b [49] (signed) */
+    0, -1, 1, -2, 2, 3, -3, -4, 4, -5, 5, -6, 6, -7, 7, 8, -9, -8, -24, -23, -22, -21, -20, -19,
+    -18, -17, -16, -15, -14, -13, -12, -11, -10, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21,
+    22, 23, 24, /* SBR table sbr_tenv30 [63] (signed) */
+    0, -1, 1, -2, 2, -3, 3, -4, 4, -5, 5, -6, -7, 6, -8, 7, -9, -10, 8, 9, 10, -13, -11, -12, -14,
+    11, 12, -31, -30, -29, -28, -27, -26, -25, -24, -23, -22, -21, -20, -19, -18, -17, -16, -15,
+    13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    /* SBR table sbr_fenv30 [63] (signed) */
+    0, -1, 1, -2, 2, -3, 3, -4, 4, -5, 5, -6, 6, -7, 7, -8, 8, 9, -9, -10, 10, 11, -11, -12, 12, 13,
+    -13, -15, 14, 15, -14, 18, -18, -24, -19, 16, 17, -22, -21, -16, 20, 21, 22, 25, -23, -20, 24,
+    -31, -30, -29, -28, -27, -26, -25, -17, 19, 23, 26, 27, 28, 29, 30, 31,
+    /* SBR table sbr_tenv30b [25] (signed) */
+    0, 1, -1, -2, 2, 3, -3, -4, 4, -5, -12, -11, -10, -9, -8, -7, -6, 5, 6, 7, 8, 9, 10, 11, 12,
+    /* SBR table sbr_fenv30b [25] (signed) */
+    0, -1, 1, -2, 2, 3, -3, -4, 4, -5, 5, 6, -12, -11, -10, -9, -8, -7, -6, 7, 8, 9, 10, 11, 12,
+    /* SBR table sbr_tnoise30 [63] (signed) */
+    0, 1, -1, -2, 2, -3, 3, -4, 4, -5, 5, 11, -31, -30, -29, -28, -27, -26, -25, -24, -23, -22, -21,
+    -20, -19, -18, -17, -16, -15, -14, -13, -12, -11, -10, -9, -8, -7, -6, 6, 7, 8, 9, 10, 12, 13,
+    14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    /* SBR table sbr_tnoise30b [25] (signed) */
+    0, -1, 1, -2, 2, -12, -11, -10, -9, -8, -7, -6, -5, -4, -3, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12,
+];
+
+/* [1.0, sqrt(2)], format = Q29 (one guard bit for decoupling) */
+const ENV_DQ_TAB: [i32; 2] = [0x20000000, 0x2d413ccc];
+
+/* dqTabCouple[i] = 2 / (1 + 2^(12 - i)), format = Q30 */
+const DQ_TAB_COUPLE: [i32; 25] = [
+    0x0007ff80, 0x000ffe00, 0x001ff802, 0x003fe010, 0x007f8080, 0x00fe03f8, 0x01f81f82, 0x03e0f83e,
+    0x07878788, 0x0e38e38e, 0x1999999a, 0x2aaaaaab, 0x40000000, 0x55555555, 0x66666666, 0x71c71c72,
+    0x78787878, 0x7c1f07c2, 0x7e07e07e, 0x7f01fc08, 0x7f807f80, 0x7fc01ff0, 0x7fe007fe, 0x7ff00200,
+    0x7ff80080,
+];
+
+/***********************************************************************************************************************
+ * Function:    DecodeOneSymbol
+ *
+ * Description: dequantize one Huffman symbol from bitstream,
+ *                using table huffTabSBR[huffTabIndex]
+ *
+ * Inputs:      bitstream positioned at the codeword
+ *              index of Huffman table
+ *
+ * Outputs:     bitstream advanced by number of bits in codeword
+ *
+ * Return:      one decoded symbol
+ **********************************************************************************************************************/
+fn decode_one_symbol(bsi: &mut BitStreamInfo, huff_tab_index: usize) -> i32 {
+    let info = &HUFF_TAB_SBR_INFO[huff_tab_index];
+    let bit_buf = bsi.get_bits_no_advance(info.max_bits) << (32 - info.max_bits);
+    let (n_bits, val) = decode_huffman_scalar(&HUFF_TAB_SBR, info, bit_buf);
+    bsi.advance_bitstream(n_bits);
+    val
+}
+
+/***********************************************************************************************************************
+ * Function:    DequantizeEnvelope
+ *
+ * Description: dequantize envelope scalefactors
+ *
+ * Inputs:      amplitude resolution flag for this frame (0 or 1)
+ *              quantized envelope scalefactors, one per band
+ *
+ * Outputs:     dequantized envelope scalefactors
+ *
+ * Return:      extra int bits in output (6 + expMax)
+ *              in other words, output format = Q(FBITS_OUT_DQ_ENV - (6 + expMax))
+ *
+ * Notes:       dequantized scalefactors have at least 2 GB
+ **********************************************************************************************************************/
+fn dequantize_envelope(amp_res: u8, env_quant: &[i8], env_dequant: &mut [i32]) -> i32 {
+    if env_quant.is_empty() {
+        return 0;
+    }
+
+    /* scan for largest dequant value (do separately from envelope decoding to keep code cleaner) */
+    let exp_max = env_quant
+        .iter()
+        .map(|&e| e as i32)
+        .max()
+        .unwrap_or(0)
+        .max(0);
+
+    /* dequantized envelope gains
+     *   envDequant = 64*2^(envQuant / alpha) = 2^(6 + envQuant / alpha)
+     *     if ampRes == 0, alpha = 2 and range of envQuant = [0, 127]
+     *     if ampRes == 1, alpha = 1 and range of envQuant = [0, 63]
+     * also if coupling is on, envDequant is scaled by something in range [0, 2]
+     * so range of envDequant = [2^6, 2^69] (no coupling), [2^6, 2^70] (with coupling)
+     *
+     * typical range (from observation) of envQuant/alpha = [0, 27] --> largest envQuant ~= 2^33
+     * output: Q(29 - (6 + expMax))
+     *
+     * reference: 14496-3:2001(E)/4.6.18.3.5 and 14496-4:200X/FPDAM8/5.6.5.1.2.1.5
+     */
+    if amp_res != 0 {
+        for (dq, &exp) in env_dequant.iter_mut().zip(env_quant) {
+            let scalei = (exp_max - exp as i32).min(31);
+            *dq = ENV_DQ_TAB[0] >> scalei;
+        }
+        6 + exp_max
+    } else {
+        let exp_max = exp_max >> 1;
+        for (dq, &exp) in env_dequant.iter_mut().zip(env_quant) {
+            let scalei = (exp_max - (exp as i32 >> 1)).min(31);
+            *dq = ENV_DQ_TAB[(exp & 0x01) as usize] >> scalei;
+        }
+        6 + exp_max
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DequantizeNoise
+ *
+ * Description: dequantize noise scalefactors
+ *
+ * Inputs:      quantized noise scalefactors, one per band
+ *
+ * Outputs:     dequantized noise scalefactors, format = Q(FBITS_OUT_DQ_NOISE)
+ *
+ * Return:      none
+ *
+ * Notes:       dequantized scalefactors have at least 2 GB
+ **********************************************************************************************************************/
+fn dequantize_noise(noise_quant: &[i8], noise_dequant: &mut [i32]) {
+    /* dequantize noise floor gains (4.6.18.3.5):
+     *   noiseDequant = 2^(NOISE_FLOOR_OFFSET - noiseQuant)
+     *
+     * range of noiseQuant = [0, 30] (see 4.6.18.3.6), NOISE_FLOOR_OFFSET = 6
+     *   so range of noiseDequant = [2^-24, 2^6]
+     */
+    for (dq, &exp) in noise_dequant.iter_mut().zip(noise_quant) {
+        let scalei = NOISE_FLOOR_OFFSET - exp as i32 + FBITS_OUT_DQ_NOISE; /* 6 + 24 - exp, exp = [0,30] */
+        *dq = if scalei < 0 {
+            0
+        } else if scalei < 30 {
+            1 << scalei
+        } else {
+            0x3fffffff /* leave 2 GB */
+        };
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSBREnvelope
+ *
+ * Description: decode delta Huffman coded envelope scalefactors from bitstream
+ *
+ * Inputs:      bitstream positioned at the envelope data
+ *              initialized SBRGrid struct for this channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for this channel
+ *              index of current channel (0 for SCE, 0 or 1 for CPE)
+ *
+ * Outputs:     dequantized env scalefactors for left channel (before decoupling)
+ *              dequantized env scalefactors for right channel (if coupling off)
+ *                or raw decoded env scalefactors for right channel (if coupling on)
+ *
+ * Return:      none
+ *
+ * Notes:       the quantized values wrap around as 8-bit integers, as they do in the
+ *                reference decoder, if a broken stream takes them out of range
+ **********************************************************************************************************************/
+pub fn decode_sbr_envelope(
+    psi: &mut PSInfoSBR,
+    bsi: &mut BitStreamInfo,
+    ch_base: usize,
+    ch: usize,
+) {
+    let coupled = psi.couplingFlag != 0 && ch != 0;
+    let sbr_grid = &mut psi.sbrGrid[ch_base + ch];
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+
+    let (d_shift, huff_index_time, huff_index_freq, env_start_bits) =
+        match (coupled, sbr_grid.ampResFrame != 0) {
+            (true, true) => (1, HUFF_TAB_SBR_T_ENV30B, HUFF_TAB_SBR_F_ENV30B, 5),
+            (true, false) => (1, HUFF_TAB_SBR_T_ENV15B, HUFF_TAB_SBR_F_ENV15B, 6),
+            (false, true) => (0, HUFF_TAB_SBR_T_ENV30, HUFF_TAB_SBR_F_ENV30, 6),
+            (false, false) => (0, HUFF_TAB_SBR_T_ENV15, HUFF_TAB_SBR_F_ENV15, 7),
+        };
+
+    let next_sf =
+        |bsi: &mut BitStreamInfo, huff_index| (decode_one_symbol(bsi, huff_index) << d_shift) as i8;
+
+    /* range of envDataQuant[] = [0, 127] (see comments in DequantizeEnvelope() for reference) */
+    let num_env = sbr_grid.numEnv as usize;
+    for env in 0..num_env {
+        let freq_res = sbr_grid.freqRes[env];
+        let n_bands = if freq_res != 0 {
+            sbr_freq.nHigh
+        } else {
+            sbr_freq.nLow
+        };
+        let freq_res_prev = if env == 0 {
+            sbr_grid.freqResPrev
+        } else {
+            sbr_grid.freqRes[env - 1]
+        };
+        let last_env = if env == 0 {
+            (sbr_grid.numEnvPrev as usize).saturating_sub(1)
+        } else {
+            env - 1
+        }; /* first frame: 0 */
+
+        let prev = sbr_chan.envDataQuant[last_env];
+        let quant = &mut sbr_chan.envDataQuant[env];
+
+        if sbr_chan.deltaFlagEnv[env] == 0 {
+            /* delta coding in freq */
+            quant[0] = (bsi.get_bits(env_start_bits) << d_shift) as i8;
+            for band in 1..n_bands {
+                quant[band] = next_sf(bsi, huff_index_freq).wrapping_add(quant[band - 1]);
+            }
+        } else if freq_res == freq_res_prev {
+            /* delta coding in time - same freq resolution for both frames */
+            for band in 0..n_bands {
+                quant[band] = next_sf(bsi, huff_index_time).wrapping_add(prev[band]);
+            }
+        } else if freq_res == 0 && freq_res_prev == 1 {
+            /* delta coding in time - low freq resolution for new frame, high freq resolution for old frame */
+            for (q, &freq_low) in quant[..n_bands].iter_mut().zip(&sbr_freq.freqLow) {
+                *q = next_sf(bsi, huff_index_time);
+                if let Some(i) = sbr_freq.freqHigh[..sbr_freq.nHigh]
+                    .iter()
+                    .position(|&f| f == freq_low)
+                {
+                    *q = q.wrapping_add(prev[i]);
+                }
+            }
+        } else if freq_res == 1 && freq_res_prev == 0 {
+            /* delta coding in time - high freq resolution for new frame, low freq resolution for old frame */
+            for (q, &freq_high) in quant[..n_bands].iter_mut().zip(&sbr_freq.freqHigh) {
+                *q = next_sf(bsi, huff_index_time);
+                let low = &sbr_freq.freqLow[..=sbr_freq.nLow];
+                if let Some(i) = low
+                    .windows(2)
+                    .position(|w| w[0] <= freq_high && freq_high < w[1])
+                {
+                    *q = q.wrapping_add(prev[i]);
+                }
+            }
+        }
+
+        /* skip coupling channel */
+        if !coupled {
+            psi.envDataDequantScale[ch][env] = dequantize_envelope(
+                sbr_grid.ampResFrame,
+                &sbr_chan.envDataQuant[env][..n_bands],
+                &mut psi.envDataDequant[ch][env],
+            ) as i8;
+        }
+    }
+    sbr_grid.numEnvPrev = sbr_grid.numEnv;
+    sbr_grid.freqResPrev = sbr_grid.freqRes[num_env - 1];
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSBRNoise
+ *
+ * Description: decode delta Huffman coded noise scalefactors from bitstream
+ *
+ * Inputs:      bitstream positioned at the noise floor data
+ *              initialized SBRGrid struct for this channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for this channel
+ *              index of current channel (0 for SCE, 0 or 1 for CPE)
+ *
+ * Outputs:     dequantized noise scalefactors for left channel (before decoupling)
+ *              dequantized noise scalefactors for right channel (if coupling off)
+ *                or raw decoded noise scalefactors for right channel (if coupling on)
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn decode_sbr_noise(psi: &mut PSInfoSBR, bsi: &mut BitStreamInfo, ch_base: usize, ch: usize) {
+    let coupled = psi.couplingFlag != 0 && ch != 0;
+    let sbr_grid = &mut psi.sbrGrid[ch_base + ch];
+    let n_bands = psi.sbrFreq[ch_base].numNoiseFloorBands;
+    let sbr_chan = &mut psi.sbrChan[ch_base + ch];
+
+    let (d_shift, huff_index_time, huff_index_freq) = if coupled {
+        (1, HUFF_TAB_SBR_T_NOISE30B, HUFF_TAB_SBR_F_NOISE30B)
+    } else {
+        (0, HUFF_TAB_SBR_T_NOISE30, HUFF_TAB_SBR_F_NOISE30)
+    };
+    let next_sf =
+        |bsi: &mut BitStreamInfo, huff_index| (decode_one_symbol(bsi, huff_index) << d_shift) as i8;
+
+    for noise_floor in 0..sbr_grid.numNoiseFloors as usize {
+        let last_noise_floor = if noise_floor == 0 {
+            (sbr_grid.numNoiseFloorsPrev as usize).saturating_sub(1) /* first frame: 0 */
+        } else {
+            noise_floor - 1
+        };
+
+        let prev = sbr_chan.noiseDataQuant[last_noise_floor];
+        let quant = &mut sbr_chan.noiseDataQuant[noise_floor];
+        if sbr_chan.deltaFlagNoise[noise_floor] == 0 {
+            /* delta coding in freq */
+            quant[0] = (bsi.get_bits(5) << d_shift) as i8;
+            for band in 1..n_bands {
+                quant[band] = next_sf(bsi, huff_index_freq).wrapping_add(quant[band - 1]);
+            }
+        } else {
+            /* delta coding in time */
+            for band in 0..n_bands {
+                quant[band] = next_sf(bsi, huff_index_time).wrapping_add(prev[band]);
+            }
+        }
+
+        /* skip coupling channel */
+        if !coupled {
+            dequantize_noise(
+                &quant[..n_bands],
+                &mut psi.noiseDataDequant[ch][noise_floor],
+            );
+        }
+    }
+    sbr_grid.numNoiseFloorsPrev = sbr_grid.numNoiseFloors;
+}
+
+/***********************************************************************************************************************
+ * Function:    UncoupleSBREnvelope
+ *
+ * Description: scale dequantized envelope scalefactors according to channel
+ *                coupling rules
+ *
+ * Inputs:      initialized SBRGrid struct for the left channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for right channel including
+ *                quantized envelope scalefactors
+ *
+ * Outputs:     dequantized envelope data for left channel (after decoupling)
+ *              dequantized envelope data for right channel (after decoupling)
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn uncouple_sbr_envelope(psi: &mut PSInfoSBR, ch_base: usize) {
+    let sbr_grid = &psi.sbrGrid[ch_base];
+    let sbr_freq = &psi.sbrFreq[ch_base];
+    let sbr_chan_r = &psi.sbrChan[ch_base + 1];
+
+    let scalei = if sbr_grid.ampResFrame != 0 { 0 } else { 1 };
+    for env in 0..sbr_grid.numEnv as usize {
+        let n_bands = if sbr_grid.freqRes[env] != 0 {
+            sbr_freq.nHigh
+        } else {
+            sbr_freq.nLow
+        };
+        psi.envDataDequantScale[1][env] = psi.envDataDequantScale[0][env];
+        let [dq_l, dq_r] = &mut psi.envDataDequant;
+        for band in 0..n_bands {
+            /* clip E_1 to [0, 24] (scalefactors approach 0 or 2) */
+            let e_1 = (sbr_chan_r.envDataQuant[env][band] as i32 >> scalei).clamp(0, 24) as usize;
+
+            /* envDataDequant[0] has 1 GB, so << by 2 is okay */
+            dq_r[env][band] = mulshift_32(dq_l[env][band], DQ_TAB_COUPLE[24 - e_1]) << 2;
+            dq_l[env][band] = mulshift_32(dq_l[env][band], DQ_TAB_COUPLE[e_1]) << 2;
+        }
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UncoupleSBRNoise
+ *
+ * Description: scale dequantized noise floor scalefactors according to channel
+ *                coupling rules
+ *
+ * Inputs:      initialized SBRGrid struct for the left channel
+ *              initialized SBRFreq struct for this SCE/CPE block
+ *              initialized SBRChan struct for right channel including
+ *                quantized noise scalefactors
+ *
+ * Outputs:     dequantized noise data for left channel (after decoupling)
+ *              dequantized noise data for right channel (after decoupling)
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn uncouple_sbr_noise(psi: &mut PSInfoSBR, ch_base: usize) {
+    let num_noise_floors = psi.sbrGrid[ch_base].numNoiseFloors as usize;
+    let n_bands = psi.sbrFreq[ch_base].numNoiseFloorBands;
+    let sbr_chan_r = &psi.sbrChan[ch_base + 1];
+    let [dq_l, dq_r] = &mut psi.noiseDataDequant;
+
+    for noise_floor in 0..num_noise_floors {
+        for band in 0..n_bands {
+            /* Q_1 should be in range [0, 24] according to 4.6.18.3.6, but check to make sure */
+            let q_1 = (sbr_chan_r.noiseDataQuant[noise_floor][band] as i32).clamp(0, 24) as usize;
+
+            /* noiseDataDequant[0] has 1 GB, so << by 2 is okay */
+            dq_r[noise_floor][band] =
+                mulshift_32(dq_l[noise_floor][band], DQ_TAB_COUPLE[24 - q_1]) << 2;
+            dq_l[noise_floor][band] = mulshift_32(dq_l[noise_floor][band], DQ_TAB_COUPLE[q_1]) << 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::huffman::MAX_HUFF_BITS;
+    use super::*;
+
+    #[test]
+    fn codebooks_are_complete() {
+        let sizes = [121, 121, 49, 49, 63, 63, 25, 25, 63, 25];
+        for (info, n) in HUFF_TAB_SBR_INFO.iter().zip(sizes) {
+            let kraft: u64 = info
+                .count
+                .iter()
+                .enumerate()
+                .map(|(len, &c)| (c as u64) << (MAX_HUFF_BITS - 1 - len))
+                .sum();
+            assert_eq!(kraft, 1 << MAX_HUFF_BITS);
+            assert_eq!(info.count.iter().map(|&c| c as usize).sum::<usize>(), n);
+        }
+        assert_eq!(HUFF_TAB_SBR_INFO[9].offset + 25, HUFF_TAB_SBR.len());
+    }
+
+    #[test]
+    fn dequantizes_envelope_relative_to_largest() {
+        let mut dq = [0; 3];
+        /* ampRes 1: 2^(6 + q), so the largest value sits at 1.0 (Q29) and the rest below it */
+        assert_eq!(dequantize_envelope(1, &[3, 5, 4], &mut dq), 11);
+        assert_eq!(dq, [0x20000000 >> 2, 0x20000000, 0x20000000 >> 1]);
+        /* ampRes 0: half steps go through sqrt(2) */
+        assert_eq!(dequantize_envelope(0, &[3, 4, 1], &mut dq), 8);
+        assert_eq!(dq, [0x2d413ccc >> 1, 0x20000000, 0x2d413ccc >> 2]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Fixed-point helpers used by the SBR tools: (a/b)^(1/c), square root and reciprocal.
+
+use crate::mp3_decoder::mulshift_32;
+
+const Q28_2: i32 = 0x20000000; /* Q28: 2.0 */
+const Q28_15: i32 = 0x30000000; /* Q28: 1.5 */
+
+const NUM_ITER_IRN: usize = 5;
+const NUM_TERMS_RPI: usize = 5;
+const LOG2_EXP_INV: i32 = 0x58b90bfc; /* 1/log2(e), Q31 */
+
+/* invTab[x] = 1/(x+1), format = Q30 */
+const INV_TAB: [i32; NUM_TERMS_RPI] = [0x40000000, 0x20000000, 0x15555555, 0x10000000, 0x0ccccccd];
+
+/* log2Tab[x] = log2(x), format = Q28 */
+pub const LOG2_TAB: [i32; 65] = [
+    0x00000000, 0x00000000, 0x10000000, 0x195c01a3, 0x20000000, 0x25269e12, 0x295c01a3, 0x2ceaecfe,
+    0x30000000, 0x32b80347, 0x35269e12, 0x3759d4f8, 0x395c01a3, 0x3b350047, 0x3ceaecfe, 0x3e829fb6,
+    0x40000000, 0x41663f6f, 0x42b80347, 0x43f782d7, 0x45269e12, 0x4646eea2, 0x4759d4f8, 0x48608280,
+    0x495c01a3, 0x4a4d3c25, 0x4b350047, 0x4c1404ea, 0x4ceaecfe, 0x4dba4a47, 0x4e829fb6, 0x4f446359,
+    0x50000000, 0x50b5d69b, 0x51663f6f, 0x52118b11, 0x52b80347, 0x5359ebc5, 0x53f782d7, 0x549101ea,
+    0x55269e12, 0x55b88873, 0x5646eea2, 0x56d1fafd, 0x5759d4f8, 0x57dea15a, 0x58608280, 0x58df988f,
+    0x595c01a3, 0x59d5d9fd, 0x5a4d3c25, 0x5ac24113, 0x5b350047, 0x5ba58feb, 0x5c1404ea, 0x5c80730b,
+    0x5ceaecfe, 0x5d53847a, 0x5dba4a47, 0x5e1f4e51, 0x5e829fb6, 0x5ee44cd5, 0x5f446359, 0x5fa2f045,
+    0x60000000,
+];
+
+/* clip y to the range of a Q30 value scaled down by 2^n, then scale it up by 2^n (n = [0, 30]) */
+#[inline(always)]
+pub fn clip_2n_shift30(y: i32, n: i32) -> i32 {
+    let sign = y >> 31;
+    if sign != y >> (30 - n) {
+        sign ^ 0x3fffffff
+    } else {
+        y << n
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    RatioPowInv
+ *
+ * Description: use Taylor (MacLaurin) series expansion to calculate (a/b) ^ (1/c)
+ *
+ * Inputs:      a = [1, 64], b = [1, 64], c = [1, 64], a >= b
+ *
+ * Outputs:     none
+ *
+ * Return:      y = Q24, range ~= [0.015625, 64]
+ **********************************************************************************************************************/
+pub fn ratio_pow_inv(a: i32, b: i32, c: i32) -> i32 {
+    if !(1..=64).contains(&a) || !(1..=64).contains(&b) || !(1..=64).contains(&c) || a < b {
+        return 0;
+    }
+
+    let lna = mulshift_32(LOG2_TAB[a as usize], LOG2_EXP_INV) << 1; /* ln(a), Q28 */
+    let lnb = mulshift_32(LOG2_TAB[b as usize], LOG2_EXP_INV) << 1; /* ln(b), Q28 */
+    let p = (lna - lnb) / c; /* Q28 */
+
+    /* sum in Q24 */
+    let mut t = p >> 4; /* t = p^1 * 1/1! (Q24)*/
+    let mut y = (1 << 24) + t;
+
+    for &inv in &INV_TAB[1..] {
+        t = mulshift_32(inv, t) << 2;
+        t = mulshift_32(p, t) << 4; /* t = p^i * 1/i! (Q24) */
+        y += t;
+    }
+
+    y
+}
+
+/***********************************************************************************************************************
+ * Function:    SqrtFix
+ *
+ * Description: use binary search to calculate sqrt(q)
+ *
+ * Inputs:      q = Q30
+ *              number of fraction bits in input
+ *
+ * Outputs:     none
+ *
+ * Return:      (lo = Q(fBitsOut), number of fraction bits in output)
+ *
+ * Notes:       absolute precision varies depending on fBitsIn
+ *              normalizes input to range [0x200000000, 0x7fffffff] and takes
+ *                floor(sqrt(input)), and sets fBitsOut appropriately
+ **********************************************************************************************************************/
+pub fn sqrt_fix(q: i32, f_bits_in: i32) -> (i32, i32) {
+    if q <= 0 {
+        return (0, f_bits_in);
+    }
+
+    /* force even fBitsIn */
+    let z = f_bits_in & 0x01;
+    let mut q = q >> z;
+    let f_bits_in = f_bits_in - z;
+
+    /* for max precision, normalize to [0x20000000, 0x7fffffff] */
+    let z = (q.leading_zeros() as i32 - 1) >> 1;
+    q <<= 2 * z;
+
+    /* choose initial bounds */
+    let mut lo = if q >= 0x10000000 { 16384 } else { 1 }; /* (int)sqrt(0x10000000) */
+    let mut hi = 46340; /* (int)sqrt(0x7fffffff) */
+
+    /* do binary search with 32x32->32 multiply test */
+    loop {
+        let mid = (lo + hi) >> 1;
+        if mid * mid > q {
+            hi = mid - 1;
+        } else {
+            lo = mid + 1;
+        }
+        if hi < lo {
+            break;
+        }
+    }
+
+    (lo - 1, (f_bits_in + 2 * z) >> 1)
+}
+
+/***********************************************************************************************************************
+ * Function:    InvRNormalized
+ *
+ * Description: use Newton's method to solve for x = 1/r
+ *
+ * Inputs:      r = Q31, range = [0.5, 1) (normalize your inputs to this range)
+ *
+ * Outputs:     none
+ *
+ * Return:      x = Q29, range ~= [1.0, 2.0]
+ *
+ * Notes:       guaranteed to converge and not overflow for any r in [0.5, 1)
+ *
+ *              xn+1  = xn - f(xn)/f'(xn)
+ *              f(x)  = 1/r - x = 0 (find root)
+ *                    = 1/x - r
+ *              f'(x) = -1/x^2
+ *
+ *              so xn+1 = xn - (1/xn - r) / (-1/xn^2)
+ *                      = xn * (2 - r*xn)
+ *
+ *              NUM_ITER_IRN = 5, maxDiff = 3.0034e-08 (precision of about 24 bits)
+ **********************************************************************************************************************/
+pub fn inv_r_normalized(r: i32) -> i32 {
+    /* r =   [0.5, 1.0)
+     * 1/r = (1.0, 2.0]
+     *   so use 1.5 as initial guess
+     */
+    let mut xn = Q28_15;
+
+    /* xn = xn*(2.0 - r*xn) */
+    for _ in 0..NUM_ITER_IRN {
+        let t = mulshift_32(r, xn); /* Q31*Q29 = Q28 */
+        let t = Q28_2 - t; /* Q28 */
+        xn = mulshift_32(xn, t) << 4; /* Q29*Q28 << 4 = Q29 */
+    }
+
+    xn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_pow_inv_matches_float() {
+        for (a, b, c) in [(64, 1, 6), (32, 5, 3), (7, 7, 1), (48, 24, 2)] {
+            let want = (a as f64 / b as f64).powf(1.0 / c as f64);
+            let got = ratio_pow_inv(a, b, c) as f64 / (1 << 24) as f64;
+            assert!(
+                (got - want).abs() < 0.05 * want,
+                "({a}/{b})^(1/{c}) = {got}, want {want}"
+            );
+        }
+        assert_eq!(ratio_pow_inv(1, 2, 1), 0);
+    }
+
+    #[test]
+    fn sqrt_fix_matches_float() {
+        for (q, f_bits) in [(0x40000000, 30), (0x12345678, 20), (3, 0), (0x7fffffff, 7)] {
+            let (y, f_bits_out) = sqrt_fix(q, f_bits);
+            let want = (q as f64 / (1u64 << f_bits) as f64).sqrt();
+            let got = y as f64 / (1u64 << f_bits_out) as f64;
+            assert!(
+                (got - want).abs() < 1e-3 * want.max(1.0),
+                "sqrt({q:#x}) = {got}, want {want}"
+            );
+        }
+        assert_eq!(sqrt_fix(-5, 10), (0, 10));
+    }
+
+    #[test]
+    fn inv_r_normalized_matches_float() {
+        for r in [0x40000000, 0x55555555, 0x6ccccccd, 0x7fffffff] {
+            let want = (1u64 << 31) as f64 / r as f64;
+            let got = inv_r_normalized(r) as f64 / (1 << 29) as f64;
+            assert!((got - want).abs() < 1e-6, "1/{r:#x} = {got}, want {want}");
+        }
+    }
+}
@@ -0,0 +1,622 @@
+//! Spectral Band Replication (14496-3, 4.6.18), the HE-AAC tool that rebuilds the upper half
+//! of the spectrum from the AAC-LC core and a few kbit/s of side information.
+//!
+//! The core output of each channel element goes through a 32-band analysis QMF ([`qmf`]).
+//! The SBR payload of the fill element that follows the channel element is unpacked by
+//! [`side`] and [`huffman`] against the frequency tables of [`freq`]; [`hfgen`] patches the
+//! low band up into the high band and [`hfadj`] shapes it to the transmitted envelope and adds
+//! noise and sinusoids. A 64-band synthesis QMF then gives PCM at twice the core sample rate.
+//!
+//! Without an SBR header, e.g. before the first one of a stream, a channel element is only
+//! upsampled through the QMF banks so the output rate stays the same.
+//!
+//! For HE-AACv2 the extended data of the SBR payload of a mono stream carries parametric
+//! stereo; [`ps`] then turns the QMF samples of the channel into a left and a right channel
+//! before the synthesis QMF.
+//!
+//! Down-sampled (single rate) SBR is not supported.
+
+use super::{AAC_MAX_NCHANS, AACDecoder, ElementId, FBITS_OUT_IMDCT, MAX_NCHANS_ELEM};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+mod fft;
+mod freq;
+mod hfadj;
+mod hfgen;
+mod huffman;
+mod math;
+mod ps;
+mod qmf;
+mod side;
+
+pub const NUM_TIME_SLOTS: usize = 16;
+pub const SAMPLES_PER_SLOT: usize = 2; /* RATE in spec */
+pub const NUM_SAMPLE_RATES_SBR: usize = 9; /* downsampled (single-rate) mode unsupported, so only use Fs_sbr >= 16 kHz */
+
+pub const MAX_NUM_ENV: usize = 5;
+pub const MAX_NUM_NOISE_FLOORS: usize = 2;
+pub const MAX_NUM_NOISE_FLOOR_BANDS: usize = 5; /* max Nq, see 4.6.18.3.6 */
+pub const MAX_NUM_PATCHES: usize = 5;
+pub const MAX_NUM_SMOOTH_COEFS: usize = 5;
+
+pub const HF_GEN: usize = 8;
+pub const HF_ADJ: usize = 2;
+
+pub const MAX_QMF_BANDS: usize = 48; /* max QMF subbands covered by SBR (4.6.18.3.6) */
+
+pub const FBITS_IN_QMFA: i32 = 14;
+pub const FBITS_LOST_QMFA: i32 = 1 + 2 + 3 + 2 + 1; /* 1 from cTab, 2 in premul, 3 in FFT, 2 in postmul, 1 for implicit scaling by 2.0 */
+pub const FBITS_OUT_QMFA: i32 = FBITS_IN_QMFA - FBITS_LOST_QMFA;
+
+pub const MIN_GBITS_IN_QMFS: u32 = 2;
+pub const FBITS_IN_QMFS: i32 = FBITS_OUT_QMFA;
+pub const FBITS_LOST_DCT4_64: i32 = 2 + 3 + 2; /* 2 in premul, 3 in FFT, 2 in postmul */
+
+pub const FBITS_OUT_DQ_ENV: i32 = 29; /* dequantized env scalefactors are Q(29 - envDataDequantScale) */
+pub const FBITS_OUT_DQ_NOISE: i32 = 24; /* range of Q_orig = [2^-24, 2^6] */
+pub const NOISE_FLOOR_OFFSET: i32 = 6;
+
+/* see comments in ApplyBoost() */
+pub const FBITS_GLIM_BOOST: i32 = 24;
+pub const FBITS_QLIM_BOOST: i32 = 14;
+pub const GBOOST_MAX: i32 = 0x2830afd3; /* Q28, 1.584893192 squared */
+pub const ACC_SCALE: i32 = 6;
+
+pub const MAG_16: i32 = 16 * (1 << (32 - (2 * (32 - 29)))); /* i.e. 16 in Q26 format */
+pub const RELAX_COEF: i32 = 0x7ffff79c; /* 1.0 / (1.0 + 1e-6), Q31 */
+pub const FBITS_LPCOEFS: i32 = 29; /* Q29 for range of (-4, 4) */
+
+pub const FBITS_OUT_QMFS: i32 = FBITS_IN_QMFS - FBITS_LOST_DCT4_64 + 6 - 1; /* 1 for QMFS delay, 6 from cTab */
+pub const RND_VAL: i32 = 1 << (FBITS_OUT_QMFS - 1);
+pub const NUM_QMF_DELAY_BUFS: usize = 10;
+pub const DELAY_SAMPS_QMFA: usize = NUM_QMF_DELAY_BUFS * 32;
+pub const DELAY_SAMPS_QMFS: usize = NUM_QMF_DELAY_BUFS * 128;
+
+/* extension types of a fill element (14496-3, table 4.121) */
+pub const EXT_SBR_DATA: u8 = 0x0d;
+pub const EXT_SBR_DATA_CRC: u8 = 0x0e;
+
+/* frame classes of the time/frequency grid (table 4.61) */
+pub const SBR_GRID_FIXFIX: u8 = 0;
+pub const SBR_GRID_FIXVAR: u8 = 1;
+pub const SBR_GRID_VARFIX: u8 = 2;
+pub const SBR_GRID_VARVAR: u8 = 3;
+
+/* need one SBRHeader per element (SCE/CPE), updated only on new header */
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(non_snake_case)]
+pub struct SBRHeader {
+    pub count: i32, /* number of headers received, 0 = upsample only */
+
+    pub ampRes: u8,
+    pub startFreq: u8,
+    pub stopFreq: u8,
+    pub crossOverBand: u8,
+    pub resBitsHdr: u8,
+    pub hdrExtra1: u8,
+    pub hdrExtra2: u8,
+
+    pub freqScale: u8,
+    pub alterScale: u8,
+    pub noiseBands: u8,
+
+    pub limiterBands: u8,
+    pub limiterGains: u8,
+    pub interpFreq: u8,
+    pub smoothMode: u8,
+}
+
+/* need one SBRGrid per channel, updated every frame */
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(non_snake_case)]
+pub struct SBRGrid {
+    pub frameClass: u8,
+    pub ampResFrame: u8,
+    pub pointer: u8,
+
+    pub numEnv: u8,                                      /* L_E */
+    pub envTimeBorder: [u8; MAX_NUM_ENV + 1],            /* t_E, in QMF samples */
+    pub freqRes: [u8; MAX_NUM_ENV],                      /* r */
+    pub numNoiseFloors: u8,                              /* L_Q */
+    pub noiseTimeBorder: [u8; MAX_NUM_NOISE_FLOORS + 1], /* t_Q */
+
+    pub numEnvPrev: u8,
+    pub numNoiseFloorsPrev: u8,
+    pub freqResPrev: u8,
+}
+
+/* need one SBRFreq per element (SCE/CPE/LFE), updated only on header reset */
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+#[allow(non_snake_case)]
+pub struct SBRFreq {
+    pub kStart: usize, /* k_x */
+    pub nMaster: usize,
+    pub nHigh: usize,
+    pub nLow: usize,
+    pub nLimiter: usize,           /* N_l */
+    pub numQMFBands: usize,        /* M */
+    pub numNoiseFloorBands: usize, /* Nq */
+    pub kStartPrev: usize,
+    pub numQMFBandsPrev: usize,
+    pub freqMaster: [u8; MAX_QMF_BANDS + 1], /* not necessary to save this after derived tables are generated */
+    pub freqHigh: [u8; MAX_QMF_BANDS + 1],
+    pub freqLow: [u8; MAX_QMF_BANDS / 2 + 1], /* nLow = nHigh - (nHigh >> 1) */
+    pub freqNoise: [u8; MAX_NUM_NOISE_FLOOR_BANDS + 1],
+    pub freqLimiter: [u8; MAX_QMF_BANDS / 2 + MAX_NUM_PATCHES], /* max (intermediate) size = nLow + numPatches - 1 */
+
+    pub numPatches: u8,
+    pub patchNumSubbands: [u8; MAX_NUM_PATCHES + 1],
+    pub patchStartSubband: [u8; MAX_NUM_PATCHES + 1],
+}
+
+impl Default for SBRFreq {
+    fn default() -> Self {
+        SBRFreq {
+            kStart: 0,
+            nMaster: 0,
+            nHigh: 0,
+            nLow: 0,
+            nLimiter: 0,
+            numQMFBands: 0,
+            numNoiseFloorBands: 0,
+            kStartPrev: 0,
+            numQMFBandsPrev: 0,
+            freqMaster: [0; MAX_QMF_BANDS + 1],
+            freqHigh: [0; MAX_QMF_BANDS + 1],
+            freqLow: [0; MAX_QMF_BANDS / 2 + 1],
+            freqNoise: [0; MAX_NUM_NOISE_FLOOR_BANDS + 1],
+            freqLimiter: [0; MAX_QMF_BANDS / 2 + MAX_NUM_PATCHES],
+            numPatches: 0,
+            patchNumSubbands: [0; MAX_NUM_PATCHES + 1],
+            patchStartSubband: [0; MAX_NUM_PATCHES + 1],
+        }
+    }
+}
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct SBRChan {
+    pub reset: i32,
+    pub deltaFlagEnv: [u8; MAX_NUM_ENV],
+    pub deltaFlagNoise: [u8; MAX_NUM_NOISE_FLOORS],
+    pub envDataQuant: [[i8; MAX_QMF_BANDS]; MAX_NUM_ENV], /* range = [0, 127] */
+    pub noiseDataQuant: [[i8; MAX_NUM_NOISE_FLOOR_BANDS]; MAX_NUM_NOISE_FLOORS],
+
+    pub invfMode: [[u8; MAX_NUM_NOISE_FLOOR_BANDS]; 2], /* invfMode[0/1][band] = prev/curr */
+    pub chirpFact: [i32; MAX_NUM_NOISE_FLOOR_BANDS],    /* bwArray */
+    pub addHarmonicFlag: [u8; 2],                       /* addHarmonicFlag[0/1] = prev/curr */
+    pub addHarmonic: [[u8; 64]; 2],                     /* addHarmonic[0/1][band] = prev/curr */
+
+    pub gbMask: [u32; 2], /* gbMask[0/1] = XBuf[0-31]/XBuf[32-39] */
+    pub laPrev: i32,
+
+    pub noiseTabIndex: usize,
+    pub sinIndex: usize,
+    pub gainNoiseIndex: usize,
+    pub gTemp: [[i32; MAX_QMF_BANDS]; MAX_NUM_SMOOTH_COEFS],
+    pub qTemp: [[i32; MAX_QMF_BANDS]; MAX_NUM_SMOOTH_COEFS],
+}
+
+/* state info struct for SBR, zero is the state of a stream without any SBR header yet */
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct PSInfoSBR {
+    /* save for entire file */
+    pub frameCount: i32,
+    pub sampRateIdx: usize, /* of the output sample rate, < NUM_SAMPLE_RATES_SBR */
+
+    /* state info that must be saved for each channel */
+    pub sbrHdr: [SBRHeader; AAC_MAX_NCHANS],
+    pub sbrGrid: [SBRGrid; AAC_MAX_NCHANS],
+    pub sbrFreq: [SBRFreq; AAC_MAX_NCHANS],
+    pub sbrChan: [SBRChan; AAC_MAX_NCHANS],
+    pub ps: ps::ParametricStereo, /* for the single channel element of a mono stream */
+
+    /* temp variables, no need to save between blocks */
+    pub dataExtra: u8,
+    pub resBitsData: u8,
+    pub extendedDataPresent: u8,
+    pub extendedDataSize: i32,
+
+    pub envDataDequantScale: [[i8; MAX_NUM_ENV]; MAX_NCHANS_ELEM],
+    pub envDataDequant: [[[i32; MAX_QMF_BANDS]; MAX_NUM_ENV]; MAX_NCHANS_ELEM],
+    pub noiseDataDequant:
+        [[[i32; MAX_NUM_NOISE_FLOOR_BANDS]; MAX_NUM_NOISE_FLOORS]; MAX_NCHANS_ELEM],
+
+    pub eCurr: [i32; MAX_QMF_BANDS],
+    pub eCurrExp: [u8; MAX_QMF_BANDS],
+    pub eCurrExpMax: u8,
+    pub la: i32,
+
+    pub crcCheckWord: i32,
+    pub couplingFlag: i32,
+    pub envBand: i32,
+    pub eOMGainMax: i32,
+    pub gainMax: i32,
+    pub gainMaxFBits: i32,
+    pub noiseFloorBand: i32,
+    pub qp1Inv: i32,
+    pub qqp1Inv: i32,
+    pub sMapped: bool,
+    pub sBand: i32,
+    pub highBand: i32,
+
+    pub sumEOrigMapped: i32,
+    pub sumECurrGLim: i32,
+    pub sumSM: i32,
+    pub sumQM: i32,
+    pub gLimBoost: [i32; MAX_QMF_BANDS],
+    pub qmLimBoost: [i32; MAX_QMF_BANDS],
+    pub smBoost: [i32; MAX_QMF_BANDS],
+
+    pub smBuf: [i32; MAX_QMF_BANDS],
+    pub qmLimBuf: [i32; MAX_QMF_BANDS],
+    pub gLimBuf: [i32; MAX_QMF_BANDS],
+    pub gLimFbits: [i32; MAX_QMF_BANDS],
+
+    pub gFiltLast: [i32; MAX_QMF_BANDS],
+    pub qFiltLast: [i32; MAX_QMF_BANDS],
+
+    /* large buffers */
+    pub delayIdxQMFA: [usize; AAC_MAX_NCHANS],
+    pub delayQMFA: [[i32; DELAY_SAMPS_QMFA]; AAC_MAX_NCHANS],
+    pub delayIdxQMFS: [usize; AAC_MAX_NCHANS],
+    pub delayQMFS: [[i32; DELAY_SAMPS_QMFS]; AAC_MAX_NCHANS],
+    pub XBufDelay: [[[i32; 64 * 2]; HF_GEN]; AAC_MAX_NCHANS],
+    pub XBuf: [[i32; 64 * 2]; 32 + HF_GEN], /* [time slot][RE{X0}, IM{X0}, ... RE{X63}, IM{X63}] */
+}
+
+/* non-zero state variables of a zeroed PSInfoSBR
+ *
+ * SAFETY: psi points to a zeroed PSInfoSBR
+ */
+pub(super) const unsafe fn init_zeroed(psi: *mut PSInfoSBR) {
+    let mut ch = 0;
+    while ch < AAC_MAX_NCHANS {
+        unsafe {
+            (&raw mut (*psi).sbrChan[ch].reset).write(1);
+            (&raw mut (*psi).sbrChan[ch].laPrev).write(-1);
+        }
+        ch += 1;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    InitSBRState
+ *
+ * Description: initialize PSInfoSBR struct at start of stream or after flush
+ *
+ * Inputs:      none
+ *
+ * Outputs:     PSInfoSBR struct with proper initial state
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn init_sbr_state(psi: &mut PSInfoSBR) {
+    let psi = psi as *mut PSInfoSBR;
+    // SAFETY: all-zero bytes are a valid PSInfoSBR (integers, arrays of them and a bool)
+    unsafe {
+        psi.write_bytes(0, 1);
+        init_zeroed(psi);
+    }
+}
+
+/* the SBR header of this element is no longer valid, so upsample only until the next one and
+ * recalculate the frequency tables then, even if it has the same values
+ */
+fn drop_sbr_header(psi: &mut PSInfoSBR, ch_base: usize) {
+    psi.sbrHdr[ch_base].count = 0;
+    for sbr_chan in &mut psi.sbrChan[ch_base..] {
+        sbr_chan.reset = 1;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSBRBitstream
+ *
+ * Description: decode sideband information for SBR
+ *
+ * Inputs:      fill element with an SBR extension payload in fillBuf, following the
+ *                SCE or CPE (prevBlockID) it belongs to
+ *              base output channel (range = [0, nChans-1])
+ *
+ * Outputs:     initialized state structs (SBRHdr, SBRGrid, SBRFreq, SBRChan, PS state)
+ *              psEnabled set if the payload has valid PS data for a mono stream
+ *
+ * Return:      Ok, or Err if the payload is invalid
+ *
+ * Notes:       on error the element falls back to upsampling only, until the next SBR header
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn decode_sbr_bitstream(dec: &mut AACDecoder, ch_base: usize) -> Result<(), ()> {
+    let result = unpack_sbr_bitstream(dec, ch_base);
+    if result.is_err() {
+        drop_sbr_header(&mut dec.m_PSInfoSBR, ch_base);
+    }
+    result
+}
+
+fn unpack_sbr_bitstream(dec: &mut AACDecoder, ch_base: usize) -> Result<(), ()> {
+    let info = &dec.m_AACDecInfo;
+    let psi = &mut dec.m_PSInfoSBR;
+    let fill = &dec.m_PSInfoBase.fillBuf[..info.fillCount];
+    let ps_allowed = dec.m_ParametricStereo && dec.m_PSInfoBase.nChans == 1 && ch_base == 0;
+    let mut bsi = BitStreamInfo::from_slice(fill);
+
+    if bsi.get_bits(4) != info.fillExtType as u32 {
+        return Err(());
+    }
+    if info.fillExtType == EXT_SBR_DATA_CRC {
+        psi.crcCheckWord = bsi.get_bits(10) as i32;
+    }
+
+    let header_flag = bsi.get_bits(1);
+    if header_flag != 0 {
+        /* get sample rate index for output sample rate (2x base rate), the same table from index 3 on */
+        psi.sampRateIdx = match dec.m_PSInfoBase.sampRateIdx.checked_sub(3) {
+            Some(idx) if idx < NUM_SAMPLE_RATES_SBR => idx,
+            _ => return Err(()),
+        };
+
+        /* reset flag = 1 if header values changed */
+        if side::unpack_sbr_header(&mut bsi, &mut psi.sbrHdr[ch_base]) {
+            psi.sbrChan[ch_base].reset = 1;
+        }
+
+        /* first valid SBR header should always trigger CalcFreqTables(), since reset was set in InitSBRState() */
+        if psi.sbrChan[ch_base].reset != 0 {
+            freq::calc_freq_tables(
+                &psi.sbrHdr[ch_base],
+                &mut psi.sbrFreq[ch_base],
+                psi.sampRateIdx,
+            )?;
+        }
+
+        /* copy and reset state to right channel for CPE */
+        if info.prevBlockID == ElementId::Cpe {
+            psi.sbrChan[ch_base + 1].reset = psi.sbrChan[ch_base].reset;
+        }
+    }
+
+    /* if no header has been received, upsample only */
+    if psi.sbrHdr[ch_base].count == 0 {
+        return Ok(());
+    }
+
+    match info.prevBlockID {
+        ElementId::Sce => side::unpack_sbr_single_channel(psi, &mut bsi, ch_base, ps_allowed)?,
+        ElementId::Cpe => side::unpack_sbr_channel_pair(psi, &mut bsi, ch_base)?,
+        _ => return Err(()),
+    }
+
+    /* PS data in a mono stream turns the output to stereo for the rest of the stream (implicit signalling) */
+    if ps_allowed && psi.ps.start {
+        dec.m_AACDecInfo.psEnabled = 1;
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeSBRData
+ *
+ * Description: apply SBR to one frame of PCM data
+ *
+ * Inputs:      1024 samples per channel of decoded 32-bit PCM in rawSampleBuf, before SBR
+ *              base output channel (range = [0, nChans-1])
+ *              number of channels in the element
+ *              whether an SBR payload for the element was decoded, if not it is upsampled only
+ *              whether the element is output in stereo through parametric stereo
+ *              initialized state structs (SBRHdr, SBRGrid, SBRFreq, SBRChan, PS state)
+ *
+ * Outputs:     2048 samples per channel of decoded 16-bit PCM, after SBR, interleaved by a
+ *                factor of output channels
+ *
+ * Notes:       for PS output the right channel goes through the second synthesis QMF, it is a
+ *                copy of the left one until there is a valid PS header
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn decode_sbr_data(
+    dec: &mut AACDecoder,
+    ch_base: usize,
+    ch_block: usize,
+    sbr_payload: bool,
+    ps_out: bool,
+    out: &mut [i16],
+) {
+    let n_chans = dec.channels() as usize;
+    let raw = &dec.m_PSInfoBase.rawSampleBuf;
+    let psi = &mut dec.m_PSInfoSBR;
+
+    /* upsample only if we haven't received an SBR header yet or the element has no SBR data (e.g. LFE) */
+    let upsample_only = !sbr_payload || psi.sbrHdr[ch_base].count == 0;
+    let k_start = if upsample_only {
+        32
+    } else {
+        psi.sbrFreq[ch_base].kStart
+    };
+
+    for (ch, raw) in raw.iter().enumerate().take(ch_block) {
+        let c = ch_base + ch;
+
+        /* restore delay buffers (could use ring buffer or keep in temp buffer for nChans == 1) */
+        psi.XBuf[..HF_GEN].copy_from_slice(&psi.XBufDelay[c]);
+
+        /* step 1 - analysis QMF */
+        for (l, inbuf) in raw.chunks_exact(32).enumerate() {
+            let gb_mask = qmf::qmf_analysis(
+                inbuf,
+                &mut psi.delayQMFA[c],
+                &mut psi.XBuf[l + HF_GEN],
+                FBITS_OUT_IMDCT,
+                &mut psi.delayIdxQMFA[c],
+                k_start,
+            );
+            psi.sbrChan[c].gbMask[((l + HF_GEN) >> 5) & 0x01] |= gb_mask; /* gbIdx = (0 if i < 32), (1 if i >= 32) */
+        }
+
+        if upsample_only {
+            /* no SBR - just run synthesis QMF to upsample by 2x */
+            for l in 0..32 {
+                /* step 4 - synthesis QMF */
+                qmf::qmf_synthesis(
+                    &psi.XBuf[l + HF_ADJ],
+                    &mut psi.delayQMFS[c],
+                    &mut psi.delayIdxQMFS[c],
+                    32,
+                    &mut out[c + l * 64 * n_chans..],
+                    n_chans,
+                );
+                if ps_out {
+                    qmf::qmf_synthesis(
+                        &psi.XBuf[l + HF_ADJ],
+                        &mut psi.delayQMFS[1],
+                        &mut psi.delayIdxQMFS[1],
+                        32,
+                        &mut out[1 + l * 64 * n_chans..],
+                        n_chans,
+                    );
+                }
+            }
+        } else {
+            let sbr_freq = &psi.sbrFreq[ch_base];
+            let (k_start_prev, k_start) = (sbr_freq.kStartPrev, sbr_freq.kStart);
+            let env_start = psi.sbrGrid[c].envTimeBorder[0] as usize;
+
+            /* if previous frame had lower SBR starting freq than current, zero out the synthesized QMF
+             *   bands so they aren't used as sources for patching
+             * after patch generation, restore from delay buffer
+             * can only happen after header reset
+             */
+            for row in &mut psi.XBuf[..env_start + HF_ADJ] {
+                row[2 * k_start_prev.min(k_start)..2 * k_start].fill(0);
+            }
+
+            /* step 2 - HF generation */
+            hfgen::generate_high_freq(psi, ch_base, ch);
+
+            /* restore SBR bands that were cleared before patch generation (time slots 0, 1 no longer needed) */
+            for l in HF_ADJ..env_start + HF_ADJ {
+                let range = 2 * k_start_prev.min(k_start)..2 * k_start;
+                psi.XBuf[l][range.clone()].copy_from_slice(&psi.XBufDelay[c][l][range]);
+            }
+
+            /* step 3 - HF adjustment */
+            hfadj::adjust_high_freq(psi, ch_base, ch);
+
+            /* step 4 - synthesis QMF, after parametric stereo for PS output
+             * if new envelope starts mid-frame, use old settings until start of first envelope in this frame,
+             *   then new settings for rest of frame (usually the entire frame)
+             */
+            let sbr_freq = &psi.sbrFreq[ch_base];
+            for l in 0..32 {
+                let qmfs_bands = if l < env_start {
+                    sbr_freq.kStartPrev + sbr_freq.numQMFBandsPrev
+                } else {
+                    sbr_freq.kStart + sbr_freq.numQMFBands
+                };
+                if ps_out && psi.ps.start {
+                    let (mut left, mut right) = ([0; 64 * 2], [0; 64 * 2]);
+                    ps::apply_ps_slot(&mut psi.ps, &psi.XBuf, l, qmfs_bands, &mut left, &mut right);
+                    for (ch_out, inbuf) in [left, right].iter().enumerate() {
+                        qmf::qmf_synthesis(
+                            inbuf,
+                            &mut psi.delayQMFS[ch_out],
+                            &mut psi.delayIdxQMFS[ch_out],
+                            qmfs_bands,
+                            &mut out[ch_out + l * 64 * n_chans..],
+                            n_chans,
+                        );
+                    }
+                    continue;
+                }
+                qmf::qmf_synthesis(
+                    &psi.XBuf[l + HF_ADJ],
+                    &mut psi.delayQMFS[c],
+                    &mut psi.delayIdxQMFS[c],
+                    qmfs_bands,
+                    &mut out[c + l * 64 * n_chans..],
+                    n_chans,
+                );
+                if ps_out {
+                    qmf::qmf_synthesis(
+                        &psi.XBuf[l + HF_ADJ],
+                        &mut psi.delayQMFS[1],
+                        &mut psi.delayIdxQMFS[1],
+                        qmfs_bands,
+                        &mut out[1 + l * 64 * n_chans..],
+                        n_chans,
+                    );
+                }
+            }
+        }
+
+        /* save delay */
+        psi.XBufDelay[c].copy_from_slice(&psi.XBuf[32..]);
+        let sbr_chan = &mut psi.sbrChan[c];
+        sbr_chan.gbMask[0] = sbr_chan.gbMask[1];
+        sbr_chan.gbMask[1] = 0;
+
+        if !upsample_only {
+            sbr_chan.reset = 0;
+        }
+    }
+
+    let sbr_freq = &mut psi.sbrFreq[ch_base];
+    if upsample_only {
+        sbr_freq.kStartPrev = 32;
+        sbr_freq.numQMFBandsPrev = 0;
+    } else {
+        sbr_freq.kStartPrev = sbr_freq.kStart;
+        sbr_freq.numQMFBandsPrev = sbr_freq.numQMFBands;
+    }
+
+    if ch_base + ch_block == n_chans || ps_out {
+        psi.frameCount += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoders::aac::{AAC_MAX_NSAMPS, AAC_PROFILE_LC};
+    extern crate std;
+    use std::boxed::Box;
+
+    #[test]
+    fn upsampling_keeps_a_sine() {
+        let mut dec = Box::<AACDecoder>::default();
+        dec.set_raw_block_params(false, 1, 22050, AAC_PROFILE_LC)
+            .unwrap();
+        let mut out = [0i16; 2 * AAC_MAX_NSAMPS];
+        let (amp, period) = (8000.0, 40.0);
+        for frame in 0..4 {
+            for (i, x) in dec.m_PSInfoBase.rawSampleBuf[0].iter_mut().enumerate() {
+                let phase =
+                    (frame * AAC_MAX_NSAMPS + i) as f64 * 2.0 * core::f64::consts::PI / period;
+                *x = (amp * phase.sin() * (1 << FBITS_OUT_IMDCT) as f64) as i32;
+            }
+            decode_sbr_data(&mut dec, 0, 1, false, false, &mut out);
+        }
+
+        /* same sine at twice the rate, after the delay of the filterbanks */
+        let w = 2.0 * core::f64::consts::PI / (2.0 * period);
+        let (mut s, mut c) = (0.0, 0.0);
+        for (n, &y) in out.iter().enumerate() {
+            s += y as f64 * (w * n as f64).sin();
+            c += y as f64 * (w * n as f64).cos();
+        }
+        let fit = 2.0 * (s * s + c * c).sqrt() / out.len() as f64;
+        assert!((fit - amp).abs() < amp * 0.01, "amplitude {fit}");
+        let phase = c.atan2(s);
+        let residual: f64 = out
+            .iter()
+            .enumerate()
+            .map(|(n, &y)| (y as f64 - fit * (w * n as f64 + phase).sin()).powi(2))
+            .sum();
+        assert!((residual / out.len() as f64).sqrt() < amp * 0.01);
+    }
+}
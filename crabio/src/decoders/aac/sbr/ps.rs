@@ -0,0 +1,1405 @@
+//! Parametric Stereo (14496-3, 8.6.4 and annex 8.A), the HE-AACv2 tool that recreates a
+//! stereo image from the mono output of a single channel element with SBR.
+//!
+//! The PS payload travels in the extended data of the SBR payload. [`unpack_ps_data`] reads
+//! the inter-channel intensity and coherence differences (IID/ICC) and the optional phase
+//! differences (IPD/OPD) of up to 34 stereo bands and 5 envelopes per frame. For each QMF
+//! slot [`apply_ps_slot`] splits the three lowest QMF bands into 10 hybrid subbands, derives a
+//! decorrelated signal through all-pass filters and delays (ducked on transients) and mixes
+//! it with the mono signal into left and right, interpolating the mixing matrix of each of
+//! 20 stereo bands over the envelope.
+//!
+//! Only the 20-band hybrid filterbank is implemented, parameters for 34 stereo bands are
+//! mapped to 20 bands as in the baseline PS decoder.
+
+use super::side::skip_bits;
+use super::{HF_ADJ, HF_GEN, MIN_GBITS_IN_QMFS, NUM_TIME_SLOTS, SAMPLES_PER_SLOT};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+pub const PS_MAX_NUM_ENV: usize = 5;
+pub const PS_MAX_NR_IIDICC: usize = 34;
+pub const PS_MAX_NR_IPDOPD: usize = 17;
+
+const NUM_QMF_SLOTS: usize = NUM_TIME_SLOTS * SAMPLES_PER_SLOT;
+const NR_PAR_BANDS: usize = 20; /* stereo bands of the 20-band configuration */
+const NR_BANDS: usize = 71; /* hybrid bands: 10 from QMF bands 0-2, then QMF bands 3-63 */
+const NR_HYBRID_BANDS: usize = 10;
+const NR_ALLPASS_BANDS: usize = 30;
+const SHORT_DELAY_BAND: usize = 42;
+pub const NR_IPDOPD_BANDS: usize = 11;
+pub const PS_AP_LINKS: usize = 3;
+const PS_MAX_AP_DELAY: usize = 5;
+const PS_MAX_DELAY: usize = 14;
+const NUM_IID_STEPS: usize = 24; /* rows of the mixing tables, 8 for default and 16 for fine IID */
+const SLOT_CYCLE: usize = 70; /* lcm of the delay line lengths 2, 5 and 14 */
+
+/* QMF slots of history the hybrid filters need besides the 2 slots of XBuf before slot 0 */
+const HYBRID_HIST: usize = 4;
+const HYBRID_FILTER_LEN: usize = 13;
+
+/* bs_extension_id of PS data in the extended data of an SBR payload */
+pub const EXTENSION_ID_PS: u32 = 2;
+
+/* stereo bands of the parameters, from iid_mode or icc_mode */
+const NR_IIDICC_PAR_TAB: [u8; 6] = [10, 20, 34, 10, 20, 34];
+const NR_IPDOPD_PAR_TAB: [u8; 6] = [5, 11, 17, 5, 11, 17];
+
+/* envelopes per frame, [frame_class][num_env_idx] */
+const NUM_ENV_TAB: [[u8; 4]; 2] = [[0, 1, 2, 4], [1, 2, 3, 4]];
+
+/* stereo band of each hybrid band, 20-band configuration; the hybrid bands 0 and 1 are
+ * the negative frequencies of the lowest subbands
+ */
+const K_TO_I_20: [u8; NR_BANDS] = [
+    1, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 14, 15, 15, 15, 16, 16, 16, 16, 17, 17,
+    17, 17, 17, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 18, 19,
+    19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19, 19,
+];
+
+/* all-pass link coefficients a(m) of the decorrelator, format = Q31 */
+const AP_COEF: [i32; PS_AP_LINKS] = [0x53625ae4, 0x4848aef5, 0x3ea94d15];
+
+/* transient detection: decay of the peak power per slot (Q31), gain of 1.0 (Q30) */
+const PEAK_DECAY_FACTOR: i32 = 0x6209f096;
+const GAIN_ONE: i32 = 1 << 30;
+
+/* magnitude limit of the QMF samples into the synthesis filterbank */
+const MAX_QMFS_IN: i64 = (1 << (31 - MIN_GBITS_IN_QMFS)) - 1;
+
+/* need one ParametricStereo for the stream, zero is the state before the first PS header */
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct ParametricStereo {
+    pub start: bool, /* a valid PS header was received, cleared on errors */
+
+    /* header, kept until the next one */
+    pub enableIID: u8,
+    pub enableICC: u8,
+    pub enableExt: u8,
+    pub enableIPDOPD: u8,
+    pub iidQuant: u8, /* 1 = fine quantisation, 31 steps */
+    pub iccMode: u8,
+    pub nrIIDPar: u8,
+    pub nrICCPar: u8,
+    pub nrIPDOPDPar: u8,
+
+    /* frame */
+    pub frameClass: u8,
+    pub numEnv: u8,
+    pub numEnvPrev: u8,
+    pub borderPosition: [i8; PS_MAX_NUM_ENV + 1], /* last slot of each envelope, [0] = -1 */
+    pub iidPar: [[i8; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV],
+    pub iccPar: [[i8; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV],
+    pub ipdPar: [[i8; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV],
+    pub opdPar: [[i8; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV],
+
+    /* mixing, interpolated per slot */
+    pub env: usize,
+    pub ipdHist: [u8; NR_IPDOPD_BANDS], /* last two phase indices, for smoothing */
+    pub opdHist: [u8; NR_IPDOPD_BANDS],
+    pub hCurr: [[i32; 8]; NR_PAR_BANDS], /* RE{h11, h12, h21, h22}, IM{h11, h12, h21, h22}, Q30 */
+    pub hStep: [[i32; 8]; NR_PAR_BANDS],
+
+    /* transient detection, powers of the stereo bands */
+    pub peakDecayNrg: [i64; NR_PAR_BANDS],
+    pub powerSmooth: [i64; NR_PAR_BANDS],
+    pub peakDecayDiffSmooth: [i64; NR_PAR_BANDS],
+
+    /* filter and delay lines, [band][..][RE, IM] */
+    pub hybridHist: [[[i32; 2]; HYBRID_HIST]; 3],
+    pub apInDelay: [[[i32; 2]; 2]; NR_ALLPASS_BANDS],
+    pub apDelay: [[[[i32; 2]; PS_MAX_AP_DELAY]; PS_AP_LINKS]; NR_ALLPASS_BANDS],
+    pub longDelay: [[[i32; 2]; PS_MAX_DELAY]; SHORT_DELAY_BAND - NR_ALLPASS_BANDS],
+    pub shortDelay: [[i32; 2]; NR_BANDS - SHORT_DELAY_BAND],
+    pub slot: usize, /* position in the delay lines, modulo SLOT_CYCLE */
+}
+
+/* one PS Huffman codebook (annex 8.B) as codeword and length of each symbol, symbol i
+ * decodes to i - offset
+ */
+struct PSHuffTab {
+    bits: &'static [u8],
+    codes: &'static [u32],
+    max_bits: u32,
+    offset: i32,
+}
+
+const HUFF_IID_DF1: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IID_DF1_BITS,
+    codes: &HUFF_IID_DF1_CODES,
+    max_bits: 18,
+    offset: 30,
+};
+const HUFF_IID_DT1: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IID_DT1_BITS,
+    codes: &HUFF_IID_DT1_CODES,
+    max_bits: 16,
+    offset: 30,
+};
+const HUFF_IID_DF0: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IID_DF0_BITS,
+    codes: &HUFF_IID_DF0_CODES,
+    max_bits: 18,
+    offset: 14,
+};
+const HUFF_IID_DT0: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IID_DT0_BITS,
+    codes: &HUFF_IID_DT0_CODES,
+    max_bits: 20,
+    offset: 14,
+};
+const HUFF_ICC_DF: PSHuffTab = PSHuffTab {
+    bits: &HUFF_ICC_DF_BITS,
+    codes: &HUFF_ICC_DF_CODES,
+    max_bits: 14,
+    offset: 7,
+};
+const HUFF_ICC_DT: PSHuffTab = PSHuffTab {
+    bits: &HUFF_ICC_DT_BITS,
+    codes: &HUFF_ICC_DT_CODES,
+    max_bits: 14,
+    offset: 7,
+};
+const HUFF_IPD_DF: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IPD_DF_BITS,
+    codes: &HUFF_IPD_DF_CODES,
+    max_bits: 4,
+    offset: 0,
+};
+const HUFF_IPD_DT: PSHuffTab = PSHuffTab {
+    bits: &HUFF_IPD_DT_BITS,
+    codes: &HUFF_IPD_DT_CODES,
+    max_bits: 5,
+    offset: 0,
+};
+const HUFF_OPD_DF: PSHuffTab = PSHuffTab {
+    bits: &HUFF_OPD_DF_BITS,
+    codes: &HUFF_OPD_DF_CODES,
+    max_bits: 5,
+    offset: 0,
+};
+const HUFF_OPD_DT: PSHuffTab = PSHuffTab {
+    bits: &HUFF_OPD_DT_BITS,
+    codes: &HUFF_OPD_DT_CODES,
+    max_bits: 5,
+    offset: 0,
+};
+
+/* decode one symbol, the codes are not canonical so the codebook is searched */
+fn decode_ps_huffman(bsi: &mut BitStreamInfo, tab: &PSHuffTab) -> i32 {
+    let bit_buf = bsi.get_bits_no_advance(tab.max_bits);
+    for (sym, (&n_bits, &code)) in tab.bits.iter().zip(tab.codes).enumerate() {
+        if bit_buf >> (tab.max_bits - n_bits as u32) == code {
+            bsi.advance_bitstream(n_bits as u32);
+            return sym as i32 - tab.offset;
+        }
+    }
+
+    /* all codebooks are complete, never taken */
+    bsi.advance_bitstream(tab.max_bits);
+    0
+}
+
+/***********************************************************************************************************************
+ * Function:    ReadPSParams
+ *
+ * Description: Huffman decode the parameters of one envelope, differential in frequency or in
+ *                time (to the previous envelope, or to the last one of the previous frame)
+ *
+ * Inputs:      bitstream positioned at the first codeword
+ *              parameters of all envelopes, those of the previous frame in the first numEnvPrev
+ *              envelope, number of envelopes of the previous frame
+ *              number of parameters, codebook
+ *              mask for phase parameters (0x07) or 0 for IID/ICC
+ *
+ * Outputs:     parameters of the envelope
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+#[allow(clippy::too_many_arguments)]
+fn read_ps_params<const N: usize>(
+    bsi: &mut BitStreamInfo,
+    par: &mut [[i8; N]; PS_MAX_NUM_ENV],
+    e: usize,
+    num_env_prev: usize,
+    dt: bool,
+    num: usize,
+    tab: &PSHuffTab,
+    mask: i32,
+) {
+    let mut val = 0;
+    let e_prev = if e > 0 {
+        e - 1
+    } else {
+        num_env_prev.saturating_sub(1)
+    };
+    let prev = par[e_prev];
+    for (p, &p_prev) in par[e][..num].iter_mut().zip(&prev) {
+        let delta = decode_ps_huffman(bsi, tab);
+        val = if dt {
+            p_prev as i32 + delta
+        } else {
+            val + delta
+        };
+        if mask != 0 {
+            val &= mask;
+        }
+        *p = val.clamp(i8::MIN as i32, i8::MAX as i32) as i8;
+    }
+}
+
+/* PS extension data (ps_extension_id 0: IPD/OPD), returns the number of bits used */
+fn unpack_ps_extension(ps: &mut ParametricStereo, bsi: &mut BitStreamInfo, id: u32) -> i32 {
+    if id != 0 {
+        return 0;
+    }
+    let start = bsi.bytes;
+    let bits_start = bsi.calc_bits_used(start, 0);
+
+    let num_env_prev = ps.numEnvPrev as usize;
+    ps.enableIPDOPD = bsi.get_bits(1) as u8;
+    if ps.enableIPDOPD != 0 {
+        let num = ps.nrIPDOPDPar as usize;
+        for e in 0..ps.numEnv as usize {
+            let dt = bsi.get_bits(1) != 0;
+            let tab = if dt { &HUFF_IPD_DT } else { &HUFF_IPD_DF };
+            read_ps_params(bsi, &mut ps.ipdPar, e, num_env_prev, dt, num, tab, 0x07);
+            let dt = bsi.get_bits(1) != 0;
+            let tab = if dt { &HUFF_OPD_DT } else { &HUFF_OPD_DF };
+            read_ps_params(bsi, &mut ps.opdPar, e, num_env_prev, dt, num, tab, 0x07);
+        }
+    }
+    bsi.get_bits(1); /* reserved_ps */
+
+    bsi.calc_bits_used(start, 0) - bits_start
+}
+
+/* unpack ps_data(), without the final check of the number of bits used */
+fn unpack_ps_frame(ps: &mut ParametricStereo, bsi: &mut BitStreamInfo) -> Result<(), ()> {
+    let header = bsi.get_bits(1) != 0;
+    if header {
+        ps.enableIID = bsi.get_bits(1) as u8;
+        if ps.enableIID != 0 {
+            let iid_mode = bsi.get_bits(3) as usize;
+            if iid_mode > 5 {
+                return Err(());
+            }
+            ps.nrIIDPar = NR_IIDICC_PAR_TAB[iid_mode];
+            ps.iidQuant = (iid_mode > 2) as u8;
+            ps.nrIPDOPDPar = NR_IPDOPD_PAR_TAB[iid_mode];
+        }
+        ps.enableICC = bsi.get_bits(1) as u8;
+        if ps.enableICC != 0 {
+            ps.iccMode = bsi.get_bits(3) as u8;
+            if ps.iccMode > 5 {
+                return Err(());
+            }
+            ps.nrICCPar = NR_IIDICC_PAR_TAB[ps.iccMode as usize];
+        }
+        ps.enableExt = bsi.get_bits(1) as u8;
+    }
+
+    ps.frameClass = bsi.get_bits(1) as u8;
+    ps.numEnvPrev = ps.numEnv;
+    ps.numEnv = NUM_ENV_TAB[ps.frameClass as usize][bsi.get_bits(2) as usize];
+    let num_env = ps.numEnv as usize;
+    let num_env_prev = ps.numEnvPrev as usize;
+
+    ps.borderPosition[0] = -1;
+    if ps.frameClass != 0 {
+        for e in 1..=num_env {
+            ps.borderPosition[e] = bsi.get_bits(5) as i8;
+            if ps.borderPosition[e] < ps.borderPosition[e - 1] {
+                return Err(());
+            }
+        }
+    } else {
+        for e in 1..=num_env {
+            ps.borderPosition[e] = ((e * NUM_QMF_SLOTS) >> num_env.ilog2()) as i8 - 1;
+        }
+    }
+
+    let max_iid = 7 + 8 * ps.iidQuant as i8;
+    if ps.enableIID != 0 {
+        for e in 0..num_env {
+            let dt = bsi.get_bits(1) != 0;
+            let tab = match (dt, ps.iidQuant != 0) {
+                (false, false) => &HUFF_IID_DF0,
+                (false, true) => &HUFF_IID_DF1,
+                (true, false) => &HUFF_IID_DT0,
+                (true, true) => &HUFF_IID_DT1,
+            };
+            let num = ps.nrIIDPar as usize;
+            read_ps_params(bsi, &mut ps.iidPar, e, num_env_prev, dt, num, tab, 0);
+            if ps.iidPar[e][..num].iter().any(|v| v.abs() > max_iid) {
+                return Err(());
+            }
+        }
+    } else {
+        ps.iidPar = [[0; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV];
+    }
+
+    if ps.enableICC != 0 {
+        for e in 0..num_env {
+            let dt = bsi.get_bits(1) != 0;
+            let tab = if dt { &HUFF_ICC_DT } else { &HUFF_ICC_DF };
+            let num = ps.nrICCPar as usize;
+            read_ps_params(bsi, &mut ps.iccPar, e, num_env_prev, dt, num, tab, 0);
+            if ps.iccPar[e][..num].iter().any(|v| !(0..=7).contains(v)) {
+                return Err(());
+            }
+        }
+    } else {
+        ps.iccPar = [[0; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV];
+    }
+
+    /* IPD/OPD are only on in frames that carry them */
+    ps.enableIPDOPD = 0;
+    if ps.enableExt != 0 {
+        let mut cnt = bsi.get_bits(4) as i32;
+        if cnt == 15 {
+            cnt += bsi.get_bits(8) as i32;
+        }
+        cnt *= 8;
+        while cnt > 7 {
+            let id = bsi.get_bits(2);
+            cnt -= 2 + unpack_ps_extension(ps, bsi, id);
+        }
+        if cnt < 0 {
+            return Err(());
+        }
+        skip_bits(bsi, cnt);
+    }
+
+    /* the parameters have to reach the end of the frame, if the last envelope doesn't, add one
+     * with the parameters of the last envelope (of the previous frame if there is none)
+     */
+    if num_env == 0 || ps.borderPosition[num_env] < NUM_QMF_SLOTS as i8 - 1 {
+        let source = if num_env > 0 { num_env } else { num_env_prev };
+        if source > 0 && source - 1 != num_env {
+            ps.iidPar[num_env] = ps.iidPar[source - 1];
+            ps.iccPar[num_env] = ps.iccPar[source - 1];
+            ps.ipdPar[num_env] = ps.ipdPar[source - 1];
+            ps.opdPar[num_env] = ps.opdPar[source - 1];
+        }
+        let (iid, icc) = (&ps.iidPar[num_env], &ps.iccPar[num_env]);
+        if ps.enableIID != 0
+            && iid[..ps.nrIIDPar as usize]
+                .iter()
+                .any(|v| v.abs() > max_iid)
+        {
+            return Err(());
+        }
+        if ps.enableICC != 0
+            && icc[..ps.nrICCPar as usize]
+                .iter()
+                .any(|v| !(0..=7).contains(v))
+        {
+            return Err(());
+        }
+        ps.numEnv += 1;
+        ps.borderPosition[ps.numEnv as usize] = NUM_QMF_SLOTS as i8 - 1;
+    }
+
+    if ps.enableIPDOPD == 0 {
+        ps.ipdPar = [[0; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV];
+        ps.opdPar = [[0; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV];
+    }
+
+    if header {
+        if !ps.start {
+            /* start from plain mono, left = right */
+            for h in &mut ps.hCurr {
+                *h = [GAIN_ONE, GAIN_ONE, 0, 0, 0, 0, 0, 0];
+            }
+        }
+        ps.start = true;
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackPSData
+ *
+ * Description: unpack ps_data() from the extended data of an SBR payload
+ *
+ * Inputs:      bitstream positioned after bs_extension_id
+ *              number of bits left in the extended data
+ *
+ * Outputs:     updated ParametricStereo struct
+ *
+ * Return:      number of bits used, all of bitsLeft on error
+ *
+ * Notes:       on error PS is off until the next PS header
+ **********************************************************************************************************************/
+pub fn unpack_ps_data(ps: &mut ParametricStereo, bsi: &mut BitStreamInfo, bits_left: i32) -> i32 {
+    let start = bsi.bytes;
+    let bits_start = bsi.calc_bits_used(start, 0);
+    let result = unpack_ps_frame(ps, bsi);
+    let bits_used = bsi.calc_bits_used(start, 0) - bits_start;
+    if result.is_ok() && bits_used <= bits_left {
+        return bits_used;
+    }
+
+    ps.start = false;
+    ps.numEnv = 0;
+    ps.iidPar = [[0; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV];
+    ps.iccPar = [[0; PS_MAX_NR_IIDICC]; PS_MAX_NUM_ENV];
+    ps.ipdPar = [[0; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV];
+    ps.opdPar = [[0; PS_MAX_NR_IPDOPD]; PS_MAX_NUM_ENV];
+    skip_bits(bsi, bits_left - bits_used);
+    bits_left
+}
+
+/* parameters of one envelope for the 20 stereo bands, from 10 or 34 bands (5 or 17 for
+ * IPD/OPD, then only the first 11 bands are set)
+ */
+fn map_to_20_bands(par: &[i8], num: usize) -> [i8; NR_PAR_BANDS] {
+    let mut out = [0i8; NR_PAR_BANDS];
+    let p = |i: usize| par[i] as i32;
+    match num {
+        10 | 5 => {
+            for b in 0..num {
+                out[2 * b] = par[b];
+                out[2 * b + 1] = par[b];
+            }
+        }
+        34 | 17 => {
+            let mapped = [
+                (2 * p(0) + p(1)) / 3,
+                (p(1) + 2 * p(2)) / 3,
+                (2 * p(3) + p(4)) / 3,
+                (p(4) + 2 * p(5)) / 3,
+                (p(6) + p(7)) / 2,
+                (p(8) + p(9)) / 2,
+                p(10),
+                p(11),
+                (p(12) + p(13)) / 2,
+                (p(14) + p(15)) / 2,
+                p(16),
+            ];
+            for (o, m) in out.iter_mut().zip(mapped) {
+                *o = m as i8;
+            }
+            if num == 34 {
+                let mapped = [
+                    p(17),
+                    p(18),
+                    p(19),
+                    (p(20) + p(21)) / 2,
+                    (p(22) + p(23)) / 2,
+                    (p(24) + p(25)) / 2,
+                    (p(26) + p(27)) / 2,
+                    (p(28) + p(29) + p(30) + p(31)) / 4,
+                    (p(32) + p(33)) / 2,
+                ];
+                for (o, m) in out[11..].iter_mut().zip(mapped) {
+                    *o = m as i8;
+                }
+            }
+        }
+        _ => out[..num].copy_from_slice(&par[..num]),
+    }
+    out
+}
+
+#[inline(always)]
+fn mul_q30(a: i32, b: i32) -> i32 {
+    ((a as i64 * b as i64) >> 30) as i32
+}
+
+/* smoothed phase of the last three phase indices in idx, Q30 */
+fn smoothed_phase(idx: usize) -> [i32; 2] {
+    let (pd0, pd1, pd2) = (idx >> 6, (idx >> 3) & 0x07, idx & 0x07);
+    let d = (((pd0 + 8 - pd2) & 0x07) << 3) | ((pd1 + 8 - pd2) & 0x07);
+    let (s_re, s_im) = (PD_SMOOTH[2 * d] as i32, PD_SMOOTH[2 * d + 1] as i32);
+    let (r_re, r_im) = (PD_ROTATE[2 * pd2] as i32, PD_ROTATE[2 * pd2 + 1] as i32);
+    [
+        mul_q30(s_re, r_re) - mul_q30(s_im, r_im),
+        mul_q30(s_re, r_im) + mul_q30(s_im, r_re),
+    ]
+}
+
+/* mixing matrix (h11, h12, h21, h22) of one IID and ICC index, the tables only hold IID >= 0 */
+fn mix_coefs(iid: i32, icc: i32, iid_quant: bool, icc_mode: u8) -> [i32; 4] {
+    let row = iid.unsigned_abs() as usize + if iid_quant { 8 } else { 0 };
+    let tab = if icc_mode < 3 { &MIX_A } else { &MIX_B };
+    let h = &tab[(row * 8 + icc as usize) * 4..][..4];
+    let h = [h[0] as i32, h[1] as i32, h[2] as i32, h[3] as i32];
+    if iid < 0 {
+        [h[1], h[0], -h[3], -h[2]]
+    } else {
+        h
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    StartPSEnvelope
+ *
+ * Description: calculate the mixing matrices at the end of an envelope and the steps to get
+ *                there from the current ones, one step per slot of the envelope
+ *
+ * Inputs:      ParametricStereo struct with the parameters of the frame
+ *              index of the envelope
+ *
+ * Outputs:     updated hStep (hCurr if the envelope is empty) and phase history
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn start_ps_envelope(ps: &mut ParametricStereo, e: usize) {
+    let iid = map_to_20_bands(&ps.iidPar[e], ps.nrIIDPar as usize);
+    let icc = map_to_20_bands(&ps.iccPar[e], ps.nrICCPar as usize);
+    let ipd = map_to_20_bands(&ps.ipdPar[e], ps.nrIPDOPDPar as usize);
+    let opd = map_to_20_bands(&ps.opdPar[e], ps.nrIPDOPDPar as usize);
+    let len = (ps.borderPosition[e + 1] - ps.borderPosition[e]) as i32;
+
+    for b in 0..NR_PAR_BANDS {
+        let [h11, h12, h21, h22] =
+            mix_coefs(iid[b] as i32, icc[b] as i32, ps.iidQuant != 0, ps.iccMode);
+        let mut h = [h11, h12, h21, h22, 0, 0, 0, 0];
+        if ps.enableIPDOPD != 0 && b < NR_IPDOPD_BANDS {
+            let opd_idx = ps.opdHist[b] as usize * 8 + (opd[b] & 0x07) as usize;
+            let ipd_idx = ps.ipdHist[b] as usize * 8 + (ipd[b] & 0x07) as usize;
+            ps.opdHist[b] = (opd_idx & 0x3f) as u8;
+            ps.ipdHist[b] = (ipd_idx & 0x3f) as u8;
+
+            /* phase of the left channel is the OPD, that of the right one OPD - IPD */
+            let [opd_re, opd_im] = smoothed_phase(opd_idx);
+            let [ipd_re, ipd_im] = smoothed_phase(ipd_idx);
+            let adj_re = mul_q30(opd_re, ipd_re) + mul_q30(opd_im, ipd_im);
+            let adj_im = mul_q30(opd_im, ipd_re) - mul_q30(opd_re, ipd_im);
+            h = [
+                mul_q30(h11, opd_re),
+                mul_q30(h12, adj_re),
+                mul_q30(h21, opd_re),
+                mul_q30(h22, adj_re),
+                mul_q30(h11, opd_im),
+                mul_q30(h12, adj_im),
+                mul_q30(h21, opd_im),
+                mul_q30(h22, adj_im),
+            ];
+        }
+
+        if len > 0 {
+            /* a step over one slot can be outside the range of i32 (h is up to sqrt(2)), it
+             * wraps, and so does adding it to hCurr, which ends up on the target exactly
+             */
+            for ((step, &curr), &target) in ps.hStep[b].iter_mut().zip(&ps.hCurr[b]).zip(&h) {
+                *step = ((target as i64 - curr as i64) / len as i64) as i32;
+            }
+        } else {
+            ps.hCurr[b] = h;
+        }
+    }
+}
+
+/* multiply by a complex coefficient, Q31 */
+#[inline(always)]
+fn cmul_q31(x: [i32; 2], re: u32, im: u32) -> [i64; 2] {
+    let (re, im) = (re as i32 as i64, im as i32 as i64);
+    let (x_re, x_im) = (x[0] as i64, x[1] as i64);
+    [(x_re * re - x_im * im) >> 31, (x_re * im + x_im * re) >> 31]
+}
+
+#[inline(always)]
+fn sat_32(x: i64) -> i32 {
+    x.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+/***********************************************************************************************************************
+ * Function:    HybridAnalysis
+ *
+ * Description: split one QMF band into hybrid subbands, 8-band complex filterbank for QMF band
+ *                0 and 2-band real filterbank for QMF bands 1 and 2
+ *
+ * Inputs:      13 samples of the QMF band, centered on the current slot
+ *
+ * Outputs:     hybrid subbands, in the order of the 20-band configuration
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn hybrid_analysis_8(w: &[[i32; 2]; HYBRID_FILTER_LEN], out: &mut [[i32; 2]]) {
+    let mut t = [[0i64; 2]; 8];
+    for (q, t) in t.iter_mut().enumerate() {
+        let f = &HYBRID8_FILTER[q * 14..][..14];
+        let c = f[12] as i32 as i64;
+        let (mut re, mut im) = (c * w[6][0] as i64, c * w[6][1] as i64);
+        for j in 0..6 {
+            let (f_re, f_im) = (f[2 * j] as i32 as i64, f[2 * j + 1] as i32 as i64);
+            let (a, b) = (w[j], w[12 - j]);
+            re += f_re * (a[0] as i64 + b[0] as i64) - f_im * (a[1] as i64 - b[1] as i64);
+            im += f_re * (a[1] as i64 + b[1] as i64) + f_im * (a[0] as i64 - b[0] as i64);
+        }
+        *t = [re >> 31, im >> 31];
+    }
+
+    /* bands 6 and 7 are the negative frequencies, 2 + 5 and 3 + 4 are merged */
+    let bands = [t[6], t[7], t[0], t[1]];
+    let merged = [
+        [t[2][0] + t[5][0], t[2][1] + t[5][1]],
+        [t[3][0] + t[4][0], t[3][1] + t[4][1]],
+    ];
+    for (o, x) in out.iter_mut().zip(bands.iter().chain(&merged)) {
+        *o = [sat_32(x[0]), sat_32(x[1])];
+    }
+}
+
+fn hybrid_analysis_2(w: &[[i32; 2]; HYBRID_FILTER_LEN], out: &mut [[i32; 2]], reverse: bool) {
+    let c = HYBRID2_FILTER[6] as i32 as i64;
+    let (in_re, in_im) = (c * w[6][0] as i64, c * w[6][1] as i64);
+    let (mut op_re, mut op_im) = (0i64, 0i64);
+    for j in (0..6).step_by(2) {
+        let c = HYBRID2_FILTER[j + 1] as i32 as i64;
+        op_re += c * (w[j + 1][0] as i64 + w[11 - j][0] as i64);
+        op_im += c * (w[j + 1][1] as i64 + w[11 - j][1] as i64);
+    }
+    let sum = [sat_32((in_re + op_re) >> 31), sat_32((in_im + op_im) >> 31)];
+    let diff = [sat_32((in_re - op_re) >> 31), sat_32((in_im - op_im) >> 31)];
+    if reverse {
+        out[0] = diff;
+        out[1] = sum;
+    } else {
+        out[0] = sum;
+        out[1] = diff;
+    }
+}
+
+/* a * b for a >= 0 (i64) and b in Q31 */
+#[inline(always)]
+fn mul_i64_q31(a: i64, b: i32) -> i64 {
+    (a >> 31) * b as i64 + (((a & 0x7fff_ffff) * b as i64) >> 31)
+}
+
+/* a / b in Q30, for 0 <= a < b */
+fn ratio_q30(a: i64, b: i64) -> i32 {
+    let shift = (33 - b.leading_zeros() as i32).max(0);
+    let (a, b) = (a >> shift, b >> shift);
+    ((a << 30) / b) as i32
+}
+
+/***********************************************************************************************************************
+ * Function:    ApplyPSSlot
+ *
+ * Description: apply parametric stereo to one QMF slot of the mono SBR output
+ *
+ * Inputs:      ParametricStereo struct with the parameters of the frame
+ *              XBuf of the channel after HF adjustment, with the low band of the next 6 slots
+ *              index of the slot (range = [0, 31])
+ *              number of QMF bands to process
+ *
+ * Outputs:     QMF samples of the slot for the left and right channel, in the format of XBuf
+ *                with MIN_GBITS_IN_QMFS guard bits
+ *
+ * Return:      none
+ *
+ * Notes:       has to be called for all slots of a frame in order, the hybrid filter history
+ *                is saved after the last one
+ **********************************************************************************************************************/
+pub fn apply_ps_slot(
+    ps: &mut ParametricStereo,
+    x_buf: &[[i32; 64 * 2]; 32 + HF_GEN],
+    slot: usize,
+    n_qmf: usize,
+    left: &mut [i32; 64 * 2],
+    right: &mut [i32; 64 * 2],
+) {
+    let n_qmf = n_qmf.clamp(3, 64);
+    let n_bands = n_qmf + NR_HYBRID_BANDS - 3;
+
+    /* hybrid analysis, the filters look 6 slots back and 6 ahead */
+    let mut s = [[0i32; 2]; NR_BANDS];
+    for qmf in 0..3 {
+        let mut w = [[0i32; 2]; HYBRID_FILTER_LEN];
+        for (j, w) in w.iter_mut().enumerate() {
+            *w = match (slot + j).checked_sub(HYBRID_HIST) {
+                Some(row) => [x_buf[row][2 * qmf], x_buf[row][2 * qmf + 1]],
+                None => ps.hybridHist[qmf][slot + j],
+            };
+        }
+        match qmf {
+            0 => hybrid_analysis_8(&w, &mut s[0..6]),
+            1 => hybrid_analysis_2(&w, &mut s[6..8], true),
+            _ => hybrid_analysis_2(&w, &mut s[8..10], false),
+        }
+    }
+    let row = &x_buf[slot + HF_ADJ];
+    for (k, s) in s.iter_mut().enumerate().take(n_bands).skip(NR_HYBRID_BANDS) {
+        let qmf = k + 3 - NR_HYBRID_BANDS;
+        *s = [row[2 * qmf], row[2 * qmf + 1]];
+    }
+
+    /* transient detection, gain for the decorrelated signal of each stereo band */
+    let mut power = [0i64; NR_PAR_BANDS];
+    for (k, s) in s.iter().enumerate().take(n_bands) {
+        let (re, im) = ((s[0] >> 6) as i64, (s[1] >> 6) as i64);
+        power[K_TO_I_20[k] as usize] += re * re + im * im;
+    }
+    let mut gain = [GAIN_ONE; NR_PAR_BANDS];
+    for (i, (g, &p)) in gain.iter_mut().zip(&power).enumerate() {
+        let peak = mul_i64_q31(ps.peakDecayNrg[i], PEAK_DECAY_FACTOR).max(p);
+        ps.peakDecayNrg[i] = peak;
+        ps.powerSmooth[i] += (p - ps.powerSmooth[i]) >> 2;
+        ps.peakDecayDiffSmooth[i] += (peak - p - ps.peakDecayDiffSmooth[i]) >> 2;
+        let denom = ps.peakDecayDiffSmooth[i] + (ps.peakDecayDiffSmooth[i] >> 1);
+        if denom > ps.powerSmooth[i] {
+            *g = ratio_q30(ps.powerSmooth[i], denom);
+        }
+    }
+
+    /* decorrelation, all-pass filters for the low bands and plain delays above */
+    let n = ps.slot;
+    let mut d = [[0i32; 2]; NR_BANDS];
+    for (k, (d, &s)) in d.iter_mut().zip(&s).enumerate().take(n_bands) {
+        let x = if k < NR_ALLPASS_BANDS {
+            let phi = &PHI_FRACT[2 * k..];
+            let mut x = cmul_q31(ps.apInDelay[k][n % 2], phi[0], phi[1]);
+            ps.apInDelay[k][n % 2] = s;
+
+            /* g_decay_slope = 1 - 0.05 * (k - 10), clipped to [0, 1], Q31 */
+            let slope = ((NR_ALLPASS_BANDS - k) as i64 * (1 << 31) / 20).min(1 << 31);
+            let q_fract = &Q_FRACT_ALLPASS[2 * PS_AP_LINKS * k..];
+            for (m, ap_delay) in ps.apDelay[k].iter_mut().enumerate() {
+                let ag = (AP_COEF[m] as i64 * slope) >> 31;
+                let link = ap_delay[(n + PS_MAX_AP_DELAY - 3 - m) % PS_MAX_AP_DELAY];
+                let y = cmul_q31(link, q_fract[2 * m], q_fract[2 * m + 1]);
+                let y = [y[0] - ((ag * x[0]) >> 31), y[1] - ((ag * x[1]) >> 31)];
+                ap_delay[n % PS_MAX_AP_DELAY] = [
+                    sat_32(x[0] + ((ag * y[0]) >> 31)),
+                    sat_32(x[1] + ((ag * y[1]) >> 31)),
+                ];
+                x = [sat_32(y[0]) as i64, sat_32(y[1]) as i64];
+            }
+            x
+        } else if k < SHORT_DELAY_BAND {
+            let delay = &mut ps.longDelay[k - NR_ALLPASS_BANDS][n % PS_MAX_DELAY];
+            let x = *delay;
+            *delay = s;
+            [x[0] as i64, x[1] as i64]
+        } else {
+            let delay = &mut ps.shortDelay[k - SHORT_DELAY_BAND];
+            let x = *delay;
+            *delay = s;
+            [x[0] as i64, x[1] as i64]
+        };
+        let g = gain[K_TO_I_20[k] as usize] as i64;
+        let lim = 1i64 << 30;
+        *d = [
+            ((g * x[0]) >> 30).clamp(-lim, lim) as i32,
+            ((g * x[1]) >> 30).clamp(-lim, lim) as i32,
+        ];
+    }
+    ps.slot = (ps.slot + 1) % SLOT_CYCLE;
+
+    /* mixing matrices of the slot, interpolated over the envelope */
+    if slot == 0 {
+        ps.env = 0;
+        start_ps_envelope(ps, 0);
+    }
+    while slot as i8 > ps.borderPosition[ps.env + 1] {
+        ps.env += 1;
+        start_ps_envelope(ps, ps.env);
+    }
+    for (h, step) in ps.hCurr.iter_mut().zip(&ps.hStep) {
+        for (h, &step) in h.iter_mut().zip(step) {
+            *h = h.wrapping_add(step);
+        }
+    }
+
+    /* mixing and hybrid synthesis, the hybrid bands of a QMF band are added up */
+    left.fill(0);
+    right.fill(0);
+    let mut lo = [[[0i64; 2]; 3]; 2];
+    for k in 0..n_bands {
+        let h = &ps.hCurr[K_TO_I_20[k] as usize];
+        let (h_re, mut h_im) = ([h[0], h[1], h[2], h[3]], [h[4], h[5], h[6], h[7]]);
+        if k <= 1 {
+            h_im = h_im.map(|x| -x);
+        }
+        let (h_re, h_im) = (h_re.map(|x| x as i64), h_im.map(|x| x as i64));
+        let (s_re, s_im) = (s[k][0] as i64, s[k][1] as i64);
+        let (d_re, d_im) = (d[k][0] as i64, d[k][1] as i64);
+        let l = [
+            (h_re[0] * s_re + h_re[2] * d_re - h_im[0] * s_im - h_im[2] * d_im) >> 30,
+            (h_re[0] * s_im + h_re[2] * d_im + h_im[0] * s_re + h_im[2] * d_re) >> 30,
+        ];
+        let r = [
+            (h_re[1] * s_re + h_re[3] * d_re - h_im[1] * s_im - h_im[3] * d_im) >> 30,
+            (h_re[1] * s_im + h_re[3] * d_im + h_im[1] * s_re + h_im[3] * d_re) >> 30,
+        ];
+
+        let qmf = match k {
+            0..6 => 0,
+            6..8 => 1,
+            8..10 => 2,
+            _ => k + 3 - NR_HYBRID_BANDS,
+        };
+        if qmf < 3 {
+            for (acc, x) in lo.iter_mut().zip([l, r]) {
+                acc[qmf][0] += x[0];
+                acc[qmf][1] += x[1];
+            }
+        } else {
+            left[2 * qmf] = l[0].clamp(-MAX_QMFS_IN, MAX_QMFS_IN) as i32;
+            left[2 * qmf + 1] = l[1].clamp(-MAX_QMFS_IN, MAX_QMFS_IN) as i32;
+            right[2 * qmf] = r[0].clamp(-MAX_QMFS_IN, MAX_QMFS_IN) as i32;
+            right[2 * qmf + 1] = r[1].clamp(-MAX_QMFS_IN, MAX_QMFS_IN) as i32;
+        }
+    }
+    for (out, acc) in [left, right].into_iter().zip(&lo) {
+        for (out, x) in out.iter_mut().zip(acc.iter().flatten()) {
+            *out = (*x).clamp(-MAX_QMFS_IN, MAX_QMFS_IN) as i32;
+        }
+    }
+
+    /* after the last slot, empty envelopes at the end of the frame only set the matrices and
+     * the hybrid filter history is saved for the next frame
+     */
+    if slot == NUM_QMF_SLOTS - 1 {
+        while ps.env + 1 < ps.numEnv as usize {
+            ps.env += 1;
+            start_ps_envelope(ps, ps.env);
+        }
+        for (qmf, hist) in ps.hybridHist.iter_mut().enumerate() {
+            for (i, h) in hist.iter_mut().enumerate() {
+                let row = &x_buf[NUM_QMF_SLOTS - HYBRID_HIST + i];
+                *h = [row[2 * qmf], row[2 * qmf + 1]];
+            }
+        }
+    }
+}
+
+/* prototype g0 of the 8-band hybrid filterbank modulated to band q, taps 0-6 of 13 (the
+ * others are conjugate symmetric), [q][tap][RE, IM], format = Q31
+ */
+const HYBRID8_FILTER: [u32; 8 * 7 * 2] = [
+    0xff532109, 0x00acdef7, 0xfee34b5f, 0x02af570f, 0x00000000, 0x05d1eac2, 0x038f276e, 0x0897b86d,
+    0x08f26d36, 0x08f26d36, 0x0df26407, 0x05c6e77e, 0x10000000, 0x00000000, 0x00acdef7, 0x00acdef7,
+    0x02af570f, 0xfee34b5f, 0x00000000, 0xfa2e153e, 0xf7684793, 0xfc70d892, 0xf70d92ca, 0x08f26d36,
+    0x05c6e77e, 0x0df26407, 0x10000000, 0x00000000, 0x00acdef7, 0xff532109, 0xfd50a8f1, 0xfee34b5f,
+    0x00000000, 0x05d1eac2, 0x0897b86d, 0xfc70d892, 0xf70d92ca, 0xf70d92ca, 0xfa391882, 0x0df26407,
+    0x10000000, 0x00000000, 0xff532109, 0xff532109, 0x011cb4a1, 0x02af570f, 0x00000000, 0xfa2e153e,
+    0xfc70d892, 0x0897b86d, 0x08f26d36, 0xf70d92ca, 0xf20d9bf9, 0x05c6e77e, 0x10000000, 0x00000000,
+    0xff532109, 0x00acdef7, 0x011cb4a1, 0xfd50a8f1, 0x00000000, 0x05d1eac2, 0xfc70d892, 0xf7684793,
+    0x08f26d36, 0x08f26d36, 0xf20d9bf9, 0xfa391882, 0x10000000, 0x00000000, 0x00acdef7, 0x00acdef7,
+    0xfd50a8f1, 0x011cb4a1, 0x00000000, 0xfa2e153e, 0x0897b86d, 0x038f276e, 0xf70d92ca, 0x08f26d36,
+    0xfa391882, 0xf20d9bf9, 0x10000000, 0x00000000, 0x00acdef7, 0xff532109, 0x02af570f, 0x011cb4a1,
+    0x00000000, 0x05d1eac2, 0xf7684793, 0x038f276e, 0xf70d92ca, 0xf70d92ca, 0x05c6e77e, 0xf20d9bf9,
+    0x10000000, 0x00000000, 0xff532109, 0xff532109, 0xfee34b5f, 0xfd50a8f1, 0x00000000, 0xfa2e153e,
+    0x038f276e, 0xf7684793, 0x08f26d36, 0xf70d92ca, 0x0df26407, 0xfa391882, 0x10000000, 0x00000000,
+];
+
+/* prototype g1 of the 2-band hybrid filterbank, taps 0-6 of 13 (symmetric), format = Q31 */
+const HYBRID2_FILTER: [u32; 7] = [
+    0x00000000, 0x026e6c90, 0x00000000, 0xf6aa2f25, 0x00000000, 0x2729e766, 0x40000000,
+];
+
+/* fractional delay of the decorrelator input per all-pass band, exp(-i pi 0.39 f_center),
+ * [band][RE, IM], format = Q31
+ */
+const PHI_FRACT: [u32; NR_ALLPASS_BANDS * 2] = [
+    0x72b9baca, 0x38c35f85, 0x7e8073ae, 0x1386e8f4, 0x7e8073ae, 0xec79170c, 0x72b9baca, 0xc73ca07b,
+    0x5c44ee40, 0xa748e9ce, 0x3d39385b, 0x8f976992, 0x05067734, 0x80194350, 0xba914696, 0x9477d07e,
+    0x895cc359, 0xcff261f0, 0x834e4955, 0x1ce70e50, 0xcb537f5c, 0x74a8dcd1, 0x5beb9002, 0x5913aba7,
+    0x72f2914e, 0xc7b012c3, 0xf1f439e1, 0x80c5e32d, 0x838961e8, 0xe21e765a, 0xb9b99ecc, 0x6afbbc51,
+    0x4cda91e8, 0x665c1120, 0x7a576cee, 0xda5ce2fa, 0x0607958d, 0x80246067, 0x89be50c3, 0xcf043ab3,
+    0xa9dab9d8, 0x5eac3b52, 0x3be51fc9, 0x711f3b6f, 0x7eb91860, 0xedf6f2cc, 0x19f4ef21, 0x82a8d3b2,
+    0x92dcafe5, 0xbd1ec45c, 0x9c1adb3e, 0x5007f38d, 0x2976203f, 0x79195336, 0x7ffbf51e, 0x0202b287,
+    0x2d3ea9e7, 0x88435eb8, 0x9eab046f, 0xacdee2e8,
+];
+
+/* fractional delays of the all-pass links, exp(-i pi q(m) f_center) for
+ * q = [0.43, 0.75, 0.347], [band][link][RE, IM], format = Q31
+ */
+const Q_FRACT_ALLPASS: [u32; NR_ALLPASS_BANDS * PS_AP_LINKS * 2] = [
+    0x6fec9aaa, 0x3e1abec6, 0x5133cc94, 0x62f201ac, 0x7573deeb, 0x32e18cfb, 0x7e2defed, 0x1582f755,
+    0x7a7d055b, 0x25280c5e, 0x7ed03e2a, 0x116360a2, 0x7e2defed, 0xea7d08ab, 0x7a7d055b, 0xdad7f3a2,
+    0x7ed03e2a, 0xee9c9f5e, 0x6fec9aaa, 0xc1e5413a, 0x5133cc94, 0x9d0dfe54, 0x7573deeb, 0xcd1e7305,
+    0x55063951, 0xa051a5ab, 0x0c8bd35e, 0x809dc971, 0x636c0442, 0xaf61c447, 0x3084ca33, 0x898d4e33,
+    0xc3a94590, 0x8f1d343a, 0x4a0d6730, 0x979882b3, 0xf0f488d9, 0x80e321ff, 0x8275a0c0, 0xe70747c4,
+    0x1a72e379, 0x82c32b3c, 0xa4c842d2, 0xa63437df, 0xb8e31319, 0x6a6d98a4, 0xd5af016e, 0x873279c5,
+    0x80aaa6ae, 0xf2f423b2, 0x471cece7, 0x6a6d98a4, 0x9d2ead98, 0xaea47031, 0x9477d07e, 0x456eb96a,
+    0x7d8a5f40, 0xe70747c4, 0x8151df9d, 0xedaa8617, 0x0202b287, 0x7ffbf51e, 0xcf043ab3, 0x89be50c3,
+    0x9bfab4a1, 0x4fdfc183, 0x7d572c4e, 0x19f4ef21, 0xcf043ab3, 0x7641af3d, 0x1893b8fd, 0x7d9e4bf8,
+    0x34ac80a4, 0x8b57232f, 0x7641af3d, 0xcf043ab3, 0x7abf7944, 0x244a29ad, 0x99a3eee0, 0xb3256e18,
+    0x89be50c3, 0xcf043ab3, 0x58eeadda, 0xa3f0a587, 0x9eab046f, 0x53211d18, 0x30fbc54d, 0x7641af3d,
+    0xd77daec8, 0x869444d2, 0x3be51fc9, 0x711f3b6f, 0x30fbc54d, 0x89be50c3, 0x819b8536, 0xebc71fa7,
+    0x7b769e14, 0xde39686c, 0x89be50c3, 0x30fbc54d, 0xb3a121be, 0x66b87d65, 0xf9f86a73, 0x80246067,
+    0x7641af3d, 0x30fbc54d, 0x37c51905, 0x73362c90, 0x81e7f007, 0xe9fe3a2d, 0xcf043ab3, 0x89be50c3,
+    0x7ff16847, 0x03d1d1ef, 0xcf043ab3, 0x7641af3d, 0xcf043ab3, 0x7641af3d, 0x3e8b240e, 0x90520d04,
+    0x68b92144, 0x4999c5a8, 0x7641af3d, 0xcf043ab3, 0xb9e4a9bc, 0x94e809f9, 0x5eac3b52, 0xa9dab9d8,
+    0x89be50c3, 0xcf043ab3, 0x80a051ca, 0x0ca56fc9, 0xc094cf73, 0x90d0aebb, 0x30fbc54d, 0x7641af3d,
+    0xd051db6f, 0x76c9bbd1, 0x85a89312, 0x25a31d06, 0x30fbc54d, 0x89be50c3, 0x53483a15, 0x6133860f,
+    0x0a0af299, 0x7f9afcb9, 0x89be50c3, 0x30fbc54d, 0x7cb1b6ab, 0xe318f1b0, 0x7eb91860, 0x12090d34,
+    0x7641af3d, 0x30fbc54d, 0x2006ea38, 0x84124e81, 0x2d3ea9e7, 0x88435eb8, 0xcf043ab3, 0x89be50c3,
+    0xa0ec1d3a, 0xaa4d2e53, 0x950443af, 0xb9b99ecc, 0xcf043ab3, 0x7641af3d, 0x880d20e7, 0x2cae16a8,
+    0xa4146ffe, 0x5913aba7, 0x7641af3d, 0xcf043ab3, 0xf02826d1, 0x7f04068b, 0x42e13ba4, 0x6d23501b,
+    0x89be50c3, 0xcf043ab3, 0x694c48d5, 0x48c6a27d,
+];
+
+/* mixing matrices (h11, h12, h21, h22) of mixing procedure R_A (icc_mode < 3) for IID >= 0,
+ * [IID step][ICC index][h], the rows for fine IID quantisation follow the 8 default ones,
+ * format = Q30
+ */
+const MIX_A: [u32; NUM_IID_STEPS * 8 * 4] = [
+    0x40000000, 0x40000000, 0x00000000, 0x00000000, 0x3efbe321, 0x3efbe321, 0x0b5bdf1d, 0xf4a420e3,
+    0x3d6804db, 0x3d6804db, 0x1208f9a4, 0xedf7065c, 0x394281f2, 0x394281f2, 0x1c96b76b, 0xe3694895,
+    0x34ec7cfd, 0x34ec7cfd, 0x23fcb454, 0xdc034bac, 0x2d413ccd, 0x2d413ccd, 0x2d413ccd, 0xd2bec333,
+    0x1d033669, 0x1d033669, 0x390bd586, 0xc6f42a7a, 0x00000000, 0x00000000, 0x40000000, 0xc0000000,
+    0x46df3081, 0x384ba5cd, 0x00000000, 0x00000000, 0x46144b55, 0x371780e5, 0x0a91c3e4, 0xf46bc009,
+    0x44d8a0d4, 0x353a3217, 0x10d2b751, 0xedab32fa, 0x4197282a, 0x305a61b7, 0x1ad894d1, 0xe32b891e,
+    0x3e29835d, 0x2b4c4605, 0x220a17e8, 0xdc056fe6, 0x380912e5, 0x2277a18d, 0x2b644b1a, 0xd37d4d87,
+    0x2ac29c11, 0x1046666d, 0x3884d4a8, 0xca1bbe4a, 0x11bcee31, 0xf1e902c0, 0x449dbdd3, 0xc97f091a,
+    0x4c8be085, 0x304c299c, 0x00000000, 0x00000000, 0x4bf8754e, 0x2efadd3a, 0x095f23a8, 0xf4cbb747,
+    0x4b12bde8, 0x2cf1bb71, 0x0ef202cd, 0xee51574a, 0x48b20f6b, 0x27a738a8, 0x17f91ad4, 0xe46d4d3e,
+    0x462daa08, 0x2236fc69, 0x1e912be1, 0xdde97320, 0x41a4d9a0, 0x18d7b4c0, 0x275f7470, 0xd694db3d,
+    0x37a5ae9d, 0x061ee85d, 0x348fd077, 0xd01786ba, 0x240ad13a, 0xe9424599, 0x4387b0be, 0xd564346c,
+    0x52a3d467, 0x24e9f646, 0x00000000, 0x00000000, 0x52516fc6, 0x2397314a, 0x07495093, 0xf6346bc5,
+    0x51d0d8df, 0x218dbcf6, 0x0ba45a8b, 0xf09cb013, 0x507aee5e, 0x1c4dd7ca, 0x12c508e7, 0xe84dbcc6,
+    0x4f0f08ac, 0x16faac18, 0x181068ad, 0xe31c543b, 0x4c7a60eb, 0x0dfc9d5f, 0x1f4fff76, 0xddd6a8d7,
+    0x46b138a0, 0xfcda0040, 0x2acd2d2b, 0xdb38791b, 0x3ae943fb, 0xe5af7236, 0x39f49865, 0xe61cbc80,
+    0x564c2e5c, 0x1b4a293c, 0x00000000, 0x00000000, 0x5621982e, 0x1a1cdfb4, 0x055b1387, 0xf8127d99,
+    0x55df1300, 0x184e9707, 0x0890ec6a, 0xf3982e8f, 0x552ddd5f, 0x13afd2fc, 0x0dd93d85, 0xed19f614,
+    0x5470b97d, 0x0f0dbec5, 0x11ceb17b, 0xe93ce666, 0x5317e680, 0x075e0527, 0x174c1e8c, 0xe5b93c06,
+    0x500bdf94, 0xf954dc40, 0x203f27cd, 0xe589a0ae, 0x49b7f8e1, 0xe8b0288d, 0x2cdd581e, 0xf1d003e8,
+    0x58c29682, 0x11b5c099, 0x00000000, 0x00000000, 0x58b1dece, 0x10d1bdf6, 0x03677773, 0xfa745183,
+    0x5897be84, 0x0f74fe1b, 0x0572b4b7, 0xf75b0e69, 0x5852109c, 0x0bff20fc, 0x08d28841, 0xf2f8df45,
+    0x580790f8, 0x0890e81d, 0x0b5d56cf, 0xf07fde3e, 0x577f675d, 0x02f9efeb, 0x0eeabb5c, 0xee8abb3c,
+    0x5649bb44, 0xf936ade8, 0x14cd637b, 0xefa4530a, 0x53bf0b6a, 0xef4a5925, 0x1d68d5fa, 0xfa21ccbd,
+    0x59cd03f7, 0x0b4e2545, 0x00000000, 0x00000000, 0x59c6916e, 0x0aaeff1d, 0x02206ca1, 0xfc4d7e9a,
+    0x59bc7d7a, 0x09bc13c5, 0x036782a0, 0xfa406461, 0x59a199a7, 0x0755f830, 0x05842f29, 0xf765e2a2,
+    0x5984d4e4, 0x04f9c825, 0x071c58c5, 0xf5d93d32, 0x5950333c, 0x012d2ccd, 0x0958516a, 0xf4c19158,
+    0x58d841b0, 0xfabde16f, 0x0d11b009, 0xf5fdf626, 0x57db1c01, 0xf4f0897e, 0x1296a89a, 0xfda8eb9e,
+    0x5a5ded0a, 0x0514ea90, 0x00000000, 0x00000000, 0x5a5cb2ef, 0x04c79307, 0x00ee4298, 0xfe46214f,
+    0x5a5ac7e9, 0x0451c165, 0x017d733f, 0xfd52a981, 0x5a55a979, 0x03294236, 0x026a4aeb, 0xfc056e3e,
+    0x5a502f06, 0x0207adb9, 0x031d446b, 0xfb5763fc, 0x5a46280c, 0x003af516, 0x04186d37, 0xfaec6b9f,
+    0x5a2f4820, 0xfd46084a, 0x05bc50cd, 0xfbb62c44, 0x59fee595, 0xfaf06d79, 0x082eb2e4, 0xff8a34d9,
+    0x40000000, 0x40000000, 0x00000000, 0x00000000, 0x3efbe321, 0x3efbe321, 0x0b5bdf1d, 0xf4a420e3,
+    0x3d6804db, 0x3d6804db, 0x1208f9a4, 0xedf7065c, 0x394281f2, 0x394281f2, 0x1c96b76b, 0xe3694895,
+    0x34ec7cfd, 0x34ec7cfd, 0x23fcb454, 0xdc034bac, 0x2d413ccd, 0x2d413ccd, 0x2d413ccd, 0xd2bec333,
+    0x1d033669, 0x1d033669, 0x390bd586, 0xc6f42a7a, 0x00000000, 0x00000000, 0x40000000, 0xc0000000,
+    0x46df3081, 0x384ba5cd, 0x00000000, 0x00000000, 0x46144b55, 0x371780e5, 0x0a91c3e4, 0xf46bc009,
+    0x44d8a0d4, 0x353a3217, 0x10d2b751, 0xedab32fa, 0x4197282a, 0x305a61b7, 0x1ad894d1, 0xe32b891e,
+    0x3e29835d, 0x2b4c4605, 0x220a17e8, 0xdc056fe6, 0x380912e5, 0x2277a18d, 0x2b644b1a, 0xd37d4d87,
+    0x2ac29c11, 0x1046666d, 0x3884d4a8, 0xca1bbe4a, 0x11bcee31, 0xf1e902c0, 0x449dbdd3, 0xc97f091a,
+    0x4c8be085, 0x304c299c, 0x00000000, 0x00000000, 0x4bf8754e, 0x2efadd3a, 0x095f23a8, 0xf4cbb747,
+    0x4b12bde8, 0x2cf1bb71, 0x0ef202cd, 0xee51574a, 0x48b20f6b, 0x27a738a8, 0x17f91ad4, 0xe46d4d3e,
+    0x462daa08, 0x2236fc69, 0x1e912be1, 0xdde97320, 0x41a4d9a0, 0x18d7b4c0, 0x275f7470, 0xd694db3d,
+    0x37a5ae9d, 0x061ee85d, 0x348fd077, 0xd01786ba, 0x240ad13a, 0xe9424599, 0x4387b0be, 0xd564346c,
+    0x50ea74d5, 0x288dd233, 0x00000000, 0x00000000, 0x508550b0, 0x273637a4, 0x07fc7d7f, 0xf5a726a0,
+    0x4fe7885a, 0x2524af9d, 0x0cc0f2c8, 0xefb89d5f, 0x4e446417, 0x1fcc3cc0, 0x14889710, 0xe6d47ef1,
+    0x4c86f189, 0x1a5a35fa, 0x1a494fcb, 0xe12ccfde, 0x495ff0f0, 0x11187e81, 0x221c40fd, 0xdb39ba7f,
+    0x4256ddc3, 0xff2e78ce, 0x2e547a91, 0xd7744b23, 0x34283ae7, 0xe5dc0861, 0x3ddce05d, 0xe0fec27d,
+    0x541744a4, 0x217a28e9, 0x00000000, 0x00000000, 0x53d4b8a8, 0x2030a28c, 0x069b5d4d, 0xf6ce5eb2,
+    0x536cd3bd, 0x1e35fcb2, 0x0a8f8e0e, 0xf193b315, 0x52585cb3, 0x191fa210, 0x110bab40, 0xe9dfed08,
+    0x5131c7d4, 0x13fbf68c, 0x15e14da5, 0xe52459a4, 0x4f1a0d13, 0x0b5c0e1e, 0x1c88b44b, 0xe0824f39,
+    0x4a648f24, 0xfb2b4289, 0x27345604, 0xdedf8b95, 0x40b9f47b, 0xe63b61d1, 0x35aea124, 0xeaa0f5ef,
+    0x564c2e5c, 0x1b4a293c, 0x00000000, 0x00000000, 0x5621982e, 0x1a1cdfb4, 0x055b1387, 0xf8127d99,
+    0x55df1300, 0x184e9707, 0x0890ec6a, 0xf3982e8f, 0x552ddd5f, 0x13afd2fc, 0x0dd93d85, 0xed19f614,
+    0x5470b97d, 0x0f0dbec5, 0x11ceb17b, 0xe93ce666, 0x5317e680, 0x075e0527, 0x174c1e8c, 0xe5b93c06,
+    0x500bdf94, 0xf954dc40, 0x203f27cd, 0xe589a0ae, 0x49b7f8e1, 0xe8b0288d, 0x2cdd581e, 0xf1d003e8,
+    0x5852ca3c, 0x13c5ece4, 0x00000000, 0x00000000, 0x583d9b4f, 0x12cf1afd, 0x03d28424, 0xf9e6a39a,
+    0x581c811f, 0x115554c5, 0x061ddd7b, 0xf67c301a, 0x57c43c84, 0x0d9440e4, 0x09e6fd9b, 0xf1a07ef7,
+    0x5765e40d, 0x09d91d0d, 0x0cc07b04, 0xeeda9286, 0x56b986e2, 0x03bebcf6, 0x10ba86a1, 0xec95b27f,
+    0x5531e05f, 0xf8fb404c, 0x174ca7c3, 0xed83affe, 0x51fc8d9c, 0xeda53f8c, 0x20da057a, 0xf8a53b11,
+    0x5964d649, 0x0e2b0093, 0x00000000, 0x00000000, 0x595a7348, 0x0d6b20c0, 0x02b169a0, 0xfb73f004,
+    0x594a374e, 0x0c45fccf, 0x044f81b0, 0xf8ec1417, 0x591ee82d, 0x095f5830, 0x06fbad3d, 0xf560051d,
+    0x58f09569, 0x068235dd, 0x08ffc5af, 0xf36a5f2f, 0x589bde02, 0x01df9f24, 0x0bd2353f, 0xf1f4d96e,
+    0x57daf449, 0xf9e9f097, 0x10839dea, 0xf334b30f, 0x56447ccb, 0xf253d839, 0x176f43d4, 0xfc492e3b,
+    0x59f1fd03, 0x0a178ef2, 0x00000000, 0x00000000, 0x59ece855, 0x098737e8, 0x01e3b00f, 0xfcac89b8,
+    0x59e4f732, 0x08aafd19, 0x03064ec5, 0xfad4cc52, 0x59cfc612, 0x067ebd29, 0x04e6c866, 0xf8469c07,
+    0x59b9196b, 0x045c4268, 0x06519888, 0xf6e608da, 0x598f9c9a, 0x00ee907d, 0x084e3521, 0xf5f37acb,
+    0x59310a02, 0xfb28c1fe, 0x0b9ea300, 0xf7250d58, 0x58695294, 0xf6147fdb, 0x108a3f3a, 0xfe24e8e0,
+    0x5a39b8a1, 0x072ab79b, 0x00000000, 0x00000000, 0x5a373abe, 0x06c05bcb, 0x015343c2, 0xfd986648,
+    0x5a335599, 0x061e3b09, 0x021f2421, 0xfc446ec2, 0x5a28f092, 0x04859bff, 0x03704f73, 0xfa70942f,
+    0x5a1dd0ec, 0x02f58bb6, 0x046f0998, 0xf978fa96, 0x5a097552, 0x00768b40, 0x05d46141, 0xf8d91dd0,
+    0x59db0742, 0xfc51e707, 0x08295b52, 0xf9d9a166, 0x5978df07, 0xf8e499f6, 0x0ba285eb, 0xff136852,
+    0x5a5ded0a, 0x0514ea90, 0x00000000, 0x00000000, 0x5a5cb2ef, 0x04c79307, 0x00ee4298, 0xfe46214f,
+    0x5a5ac7e9, 0x0451c165, 0x017d733f, 0xfd52a981, 0x5a55a979, 0x03294236, 0x026a4aeb, 0xfc056e3e,
+    0x5a502f06, 0x0207adb9, 0x031d446b, 0xfb5763fc, 0x5a46280c, 0x003af516, 0x04186d37, 0xfaec6b9f,
+    0x5a2f4820, 0xfd46084a, 0x05bc50cd, 0xfbb62c44, 0x59fee595, 0xfaf06d79, 0x082eb2e4, 0xff8a34d9,
+    0x5a76e601, 0x02dc5943, 0x00000000, 0x00000000, 0x5a7684b8, 0x02afaa3f, 0x0084ab76, 0xff041ab9,
+    0x5a75eca4, 0x026ba4b1, 0x00d467ee, 0xfe79a538, 0x5a7456c1, 0x01c0bcb5, 0x01584e71, 0xfdbd3c05,
+    0x5a72a459, 0x011a3f8f, 0x01bbfede, 0xfd5c39dc, 0x5a6f892b, 0x0012771d, 0x0247ec78, 0xfd23e258,
+    0x5a6872de, 0xfe65d1bd, 0x0331f7db, 0xfda14c7e, 0x5a5973dd, 0xfd24951e, 0x048f79e3, 0xffdb14c7,
+    0x5a7ecff3, 0x019bf87d, 0x00000000, 0x00000000, 0x5a7eb192, 0x018279a3, 0x004a260d, 0xff7152a3,
+    0x5a7e8215, 0x015badb2, 0x0076b6ad, 0xff2300e0, 0x5a7e0357, 0x00fa49c3, 0x00c06f89, 0xfeb8c69c,
+    0x5a7d7bb1, 0x009b8d55, 0x00f8282e, 0xfe828656, 0x5a7c8360, 0x0005cdca, 0x01466012, 0xfe6411fb,
+    0x5a7a4cca, 0xff13f79d, 0x01c93915, 0xfeae5949, 0x5a759dc7, 0xfe643160, 0x028caf8f, 0xfff464b7,
+    0x5a81510b, 0x00e7b173, 0x00000000, 0x00000000, 0x5a814782, 0x00d93dc4, 0x00298b89, 0xffaf73c2,
+    0x5a81389a, 0x00c34129, 0x004283c2, 0xff83454f, 0x5a8110d1, 0x008c16e1, 0x006bd245, 0xff477499,
+    0x5a80e63c, 0x005678a0, 0x008b0ae9, 0xff290c3b, 0x5a80984a, 0x0001d426, 0x00b6dec4, 0xff185066,
+    0x5a7fe66f, 0xff799c87, 0x010030ca, 0xff43437a, 0x5a7e6e0c, 0xff1855f1, 0x016dba9d, 0xfffc57bc,
+    0x5a821bd1, 0x00824b8b, 0x00000000, 0x00000000, 0x5a8218d0, 0x007a21da, 0x00175031, 0xffd29b62,
+    0x5a82141f, 0x006db709, 0x0025533a, 0xffb9b80a, 0x5a820798, 0x004e9191, 0x003c8133, 0xff980f0a,
+    0x5a81fa2f, 0x00304f38, 0x004e065b, 0xff86fde0, 0x5a81e1a4, 0x000093bb, 0x00669e8a, 0xff7db4c8,
+    0x5a81a9a3, 0xffb3e937, 0x008fc3c4, 0xff963ae6, 0x5a81331d, 0xff7db5c4, 0x00cd3c91, 0xfffed88b,
+    0x5a825bf2, 0x00494567, 0x00000000, 0x00000000, 0x5a825aff, 0x0044ab63, 0x000d181d, 0xffe6716c,
+    0x5a825984, 0x003dab86, 0x0014f6d2, 0xffd86f1e, 0x5a825590, 0x002c1dad, 0x0021fbb9, 0xffc57f95,
+    0x5a825156, 0x001b10f4, 0x002bd2e1, 0xffbbe949, 0x5a824997, 0x00002ea9, 0x0039a33c, 0xffb6baa7,
+    0x5a8237ed, 0xffd50cc8, 0x0050bf6c, 0xffc4a31d, 0x5a821289, 0xffb6bad4, 0x00734661, 0xffffa2ae,
+];
+
+/* the same for mixing procedure R_B (icc_mode >= 3) */
+const MIX_B: [u32; NUM_IID_STEPS * 8 * 4] = [
+    0x40000000, 0x40000000, 0x00000000, 0x00000000, 0x3efbe321, 0x3efbe321, 0xf4a420e3, 0x0b5bdf1d,
+    0x3d6804db, 0x3d6804db, 0xedf7065c, 0x1208f9a4, 0x394281f2, 0x394281f2, 0xe3694895, 0x1c96b76b,
+    0x34ec7cfd, 0x34ec7cfd, 0xdc034bac, 0x23fcb454, 0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a,
+    0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a, 0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a,
+    0x46df3081, 0x384ba5cd, 0x00000000, 0x00000000, 0x4636cd7d, 0x36eeb15e, 0xf65ddef9, 0x0c502ede,
+    0x454378a0, 0x34ba7dda, 0xf0fcca91, 0x13b865a3, 0x4357c9e2, 0x2e2aae1d, 0xe9ea3d33, 0x20371568,
+    0x42e13ee6, 0x24da6b14, 0xe88ccfcc, 0x2a8e5f38, 0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb,
+    0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb, 0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb,
+    0x4c8be085, 0x304c299c, 0x00000000, 0x00000000, 0x4c2c3428, 0x2eb2f08c, 0xf871f9ba, 0x0c52ab25,
+    0x4badde8c, 0x2c163a5a, 0xf482a47a, 0x13b91dbe, 0x4af951c1, 0x24362294, 0xf0906e9a, 0x1ff582a4,
+    0x4b440968, 0x19a3f9b1, 0xf20e863b, 0x28edeabb, 0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee,
+    0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee, 0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee,
+    0x52a3d467, 0x24e9f646, 0x00000000, 0x00000000, 0x52820fd8, 0x2342ec42, 0xfb552a26, 0x0aebca67,
+    0x525aec62, 0x209999e6, 0xf925284d, 0x115123a1, 0x5239ecda, 0x190516d8, 0xf7bdd097, 0x1b24232e,
+    0x52618f23, 0x103e5abe, 0xf976caab, 0x2125dcea, 0x52a2320f, 0x024dc4ca, 0xfef90da1, 0x24d78a59,
+    0x52a2320f, 0x024dc4ca, 0xfef90da1, 0x24d78a59, 0x52a2320f, 0x024dc4ca, 0xfef90da1, 0x24d78a59,
+    0x564c2e5c, 0x1b4a293c, 0x00000000, 0x00000000, 0x5641fa37, 0x19d92ca0, 0xfd60a0f9, 0x08c069be,
+    0x563738ae, 0x179390d7, 0xfc3de466, 0x0dbe4b40, 0x5631663b, 0x1173e923, 0xfbc08499, 0x14faae7c,
+    0x563d3668, 0x0af5c615, 0xfcd2e30f, 0x18fdf985, 0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed,
+    0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed, 0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed,
+    0x58c29682, 0x11b5c099, 0x00000000, 0x00000000, 0x58c0c546, 0x10abd101, 0xfee09f9f, 0x05f9eb5a,
+    0x58befc4c, 0x0f10c041, 0xfe6b6775, 0x094f5aef, 0x58be5839, 0x0ae9f1d7, 0xfe48e348, 0x0df28b3a,
+    0x58c05b44, 0x06bdb644, 0xfec19025, 0x1060790c, 0x58c28a0b, 0x00ec0f76, 0xffd0f6ab, 0x11af9a47,
+    0x58c28a0b, 0x00ec0f76, 0xffd0f6ab, 0x11af9a47, 0x58c28a0b, 0x00ec0f76, 0xffd0f6ab, 0x11af9a47,
+    0x59cd03f7, 0x0b4e2545, 0x00000000, 0x00000000, 0x59ccb6a5, 0x0a9cf618, 0xff8a2810, 0x03e51884,
+    0x59cc6d0b, 0x098da49a, 0xff5b5cd7, 0x060b8ca4, 0x59cc57f5, 0x06dcc927, 0xff503c68, 0x08fbf3e5,
+    0x59ccab8a, 0x0436bde3, 0xff81fa97, 0x0a7d9719, 0x59cd0210, 0x00930817, 0xffed8383, 0x0b4a6889,
+    0x59cd0210, 0x00930817, 0xffed8383, 0x0b4a6889, 0x59cd0210, 0x00930817, 0xffed8383, 0x0b4a6889,
+    0x5a5ded0a, 0x0514ea90, 0x00000000, 0x00000000, 0x5a5de9e1, 0x04c36d5a, 0xffe81c94, 0x01c53075,
+    0x5a5de6ed, 0x0447506e, 0xffdec463, 0x02bde78e, 0x5a5de632, 0x030f5395, 0xffdcd4b7, 0x040ea508,
+    0x5a5de98f, 0x01df9460, 0xffe6ee40, 0x04b94ab0, 0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e,
+    0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e, 0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e,
+    0x40000000, 0x40000000, 0x00000000, 0x00000000, 0x3efbe321, 0x3efbe321, 0xf4a420e3, 0x0b5bdf1d,
+    0x3d6804db, 0x3d6804db, 0xedf7065c, 0x1208f9a4, 0x394281f2, 0x394281f2, 0xe3694895, 0x1c96b76b,
+    0x34ec7cfd, 0x34ec7cfd, 0xdc034bac, 0x23fcb454, 0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a,
+    0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a, 0x2e5f55f9, 0x2e5f55f9, 0xd3e41b76, 0x2c1be48a,
+    0x46df3081, 0x384ba5cd, 0x00000000, 0x00000000, 0x4636cd7d, 0x36eeb15e, 0xf65ddef9, 0x0c502ede,
+    0x454378a0, 0x34ba7dda, 0xf0fcca91, 0x13b865a3, 0x4357c9e2, 0x2e2aae1d, 0xe9ea3d33, 0x20371568,
+    0x42e13ee6, 0x24da6b14, 0xe88ccfcc, 0x2a8e5f38, 0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb,
+    0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb, 0x469f6e5f, 0x0783aeb0, 0xfa104c51, 0x37caaedb,
+    0x4c8be085, 0x304c299c, 0x00000000, 0x00000000, 0x4c2c3428, 0x2eb2f08c, 0xf871f9ba, 0x0c52ab25,
+    0x4badde8c, 0x2c163a5a, 0xf482a47a, 0x13b91dbe, 0x4af951c1, 0x24362294, 0xf0906e9a, 0x1ff582a4,
+    0x4b440968, 0x19a3f9b1, 0xf20e863b, 0x28edeabb, 0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee,
+    0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee, 0x4c814a25, 0x03ffbf34, 0xfd7bedee, 0x3021b4ee,
+    0x50ea74d5, 0x288dd233, 0x00000000, 0x00000000, 0x50b9a4e2, 0x26e1ab5b, 0xfa72c868, 0x0b86b526,
+    0x507ebbb3, 0x242b5cb6, 0xf7c23397, 0x12575c98, 0x5043e148, 0x1c46bd48, 0xf5c24bd7, 0x1d11f75a,
+    0x507b4cf8, 0x12bb2575, 0xf7a0f0bb, 0x23f81516, 0x50e78fdc, 0x02b45943, 0xfea5ba4f, 0x2876b5a1,
+    0x50e78fdc, 0x02b45943, 0xfea5ba4f, 0x2876b5a1, 0x50e78fdc, 0x02b45943, 0xfea5ba4f, 0x2876b5a1,
+    0x541744a4, 0x217a28e9, 0x00000000, 0x00000000, 0x54004d96, 0x1fdfec1c, 0xfc1de7fd, 0x0a3bca9a,
+    0x53e6a196, 0x1d501bd8, 0xfa59bccc, 0x102b9067, 0x53d42e4b, 0x162a51b8, 0xf95dcaa3, 0x19169899,
+    0x53ef453d, 0x0e2e7edd, 0xfae05432, 0x1e533101, 0x5416516a, 0x01fce7dd, 0xff35bfe4, 0x216b0969,
+    0x5416516a, 0x01fce7dd, 0xff35bfe4, 0x216b0969, 0x5416516a, 0x01fce7dd, 0xff35bfe4, 0x216b0969,
+    0x564c2e5c, 0x1b4a293c, 0x00000000, 0x00000000, 0x5641fa37, 0x19d92ca0, 0xfd60a0f9, 0x08c069be,
+    0x563738ae, 0x179390d7, 0xfc3de466, 0x0dbe4b40, 0x5631663b, 0x1173e923, 0xfbc08499, 0x14faae7c,
+    0x563d3668, 0x0af5c615, 0xfcd2e30f, 0x18fdf985, 0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed,
+    0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed, 0x564bd768, 0x0183fe90, 0xff857e2e, 0x1b3f60ed,
+    0x5852ca3c, 0x13c5ece4, 0x00000000, 0x00000000, 0x584ff7ac, 0x12a25f01, 0xfe9abed4, 0x069d1e25,
+    0x584d28b6, 0x10de03f0, 0xfe07697e, 0x0a518efb, 0x584c1026, 0x0c43984e, 0xfdd87f18, 0x0f82a573,
+    0x584f3783, 0x07985b7e, 0xfe6e0aba, 0x12419a4d, 0x5852b628, 0x010a699a, 0xffc470ac, 0x13bee8e6,
+    0x5852b628, 0x010a699a, 0xffc470ac, 0x13bee8e6, 0x5852b628, 0x010a699a, 0xffc470ac, 0x13bee8e6,
+    0x5964d649, 0x0e2b0093, 0x00000000, 0x00000000, 0x5964179c, 0x0d509cdc, 0xff475dc8, 0x04d78ee6,
+    0x59635fda, 0x0c00f0e7, 0xfefd44d8, 0x0786b397, 0x5963260b, 0x08a697e2, 0xfeea0361, 0x0b38682f,
+    0x5963f625, 0x0552e280, 0xff37d1ad, 0x0d213427, 0x5964d170, 0x00ba02a6, 0xffe28ee1, 0x0e263ab6,
+    0x5964d170, 0x00ba02a6, 0xffe28ee1, 0x0e263ab6, 0x5964d170, 0x00ba02a6, 0xffe28ee1, 0x0e263ab6,
+    0x59f1fd03, 0x0a178ef2, 0x00000000, 0x00000000, 0x59f1cbe7, 0x09787539, 0xffa20348, 0x037c9fc5,
+    0x59f19d5f, 0x08852926, 0xff7cd563, 0x0568ab97, 0x59f1907f, 0x061d037e, 0xff74489e, 0x08079a87,
+    0x59f1c565, 0x03c03f54, 0xff9bf9d3, 0x095e7a7c, 0x59f1fbd1, 0x0082d217, 0xfff1572b, 0x0a143e81,
+    0x59f1fbd1, 0x0082d217, 0xfff1572b, 0x0a143e81, 0x59f1fbd1, 0x0082d217, 0xfff1572b, 0x0a143e81,
+    0x5a39b8a1, 0x072ab79b, 0x00000000, 0x00000000, 0x5a39ac23, 0x06b8719b, 0xffd08622, 0x027d62ba,
+    0x5a39a067, 0x060a293e, 0xffbde1e3, 0x03dbb607, 0x5a399d61, 0x0452f721, 0xffb9e0ae, 0x05b72704,
+    0x5a39aabe, 0x02a63627, 0xffcdf185, 0x06a8c34a, 0x5a39b855, 0x005c511e, 0xfff8ad22, 0x072864a9,
+    0x5a39b855, 0x005c511e, 0xfff8ad22, 0x072864a9, 0x5a39b855, 0x005c511e, 0xfff8ad22, 0x072864a9,
+    0x5a5ded0a, 0x0514ea90, 0x00000000, 0x00000000, 0x5a5de9e1, 0x04c36d5a, 0xffe81c94, 0x01c53075,
+    0x5a5de6ed, 0x0447506e, 0xffdec463, 0x02bde78e, 0x5a5de632, 0x030f5395, 0xffdcd4b7, 0x040ea508,
+    0x5a5de98f, 0x01df9460, 0xffe6ee40, 0x04b94ab0, 0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e,
+    0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e, 0x5a5decf7, 0x0041406b, 0xfffc55d3, 0x0513475e,
+    0x5a76e601, 0x02dc5943, 0x00000000, 0x00000000, 0x5a76e5b0, 0x02ae4b5a, 0xfff86ce2, 0x00ff9b3f,
+    0x5a76e564, 0x02683791, 0xfff57854, 0x018bbdc1, 0x5a76e552, 0x01b85d39, 0xfff4df8b, 0x02492958,
+    0x5a76e5a8, 0x010d7942, 0xfff8138b, 0x02a8f804, 0x5a76e5ff, 0x0024a76c, 0xfffed7a4, 0x02db6e4b,
+    0x5a76e5ff, 0x0024a76c, 0xfffed7a4, 0x02db6e4b, 0x5a76e5ff, 0x0024a76c, 0xfffed7a4, 0x02db6e4b,
+    0x5a7ecff3, 0x019bf87d, 0x00000000, 0x00000000, 0x5a7ecfeb, 0x01820809, 0xfffd9a45, 0x008fdf9d,
+    0x5a7ecfe3, 0x015a92d2, 0xfffcab05, 0x00deb9b6, 0x5a7ecfe1, 0x00f79c86, 0xfffc7b1a, 0x0149411b,
+    0x5a7ecfea, 0x00977f92, 0xfffd7ea6, 0x017f1a6d, 0x5a7ecff3, 0x00149ae3, 0xffffa251, 0x019b747e,
+    0x5a7ecff3, 0x00149ae3, 0xffffa251, 0x019b747e, 0x5a7ecff3, 0x00149ae3, 0xffffa251, 0x019b747e,
+    0x5a81510b, 0x00e7b173, 0x00000000, 0x00000000, 0x5a81510a, 0x00d91962, 0xffff3dde, 0x0050ee1c,
+    0x5a815109, 0x00c2e6bf, 0xfffef236, 0x007d47ca, 0x5a815109, 0x008b3cea, 0xfffee319, 0x00b93013,
+    0x5a81510a, 0x00552fe2, 0xffff3532, 0x00d776e0, 0x5a81510b, 0x000b95f8, 0xffffe261, 0x00e7673f,
+    0x5a81510b, 0x000b95f8, 0xffffe261, 0x00e7673f, 0x5a81510b, 0x000b95f8, 0xffffe261, 0x00e7673f,
+    0x5a821bd1, 0x00824b8b, 0x00000000, 0x00000000, 0x5a821bd1, 0x007a1644, 0xffffc29b, 0x002d83bf,
+    0x5a821bd1, 0x006d9a47, 0xffffaaae, 0x004674c8, 0x5a821bd1, 0x004e4c6b, 0xffffa5e8, 0x00682517,
+    0x5a821bd1, 0x002fe72b, 0xffffbfde, 0x00792b76, 0x5a821bd1, 0x000683d4, 0xfffff6a2, 0x008221d2,
+    0x5a821bd1, 0x000683d4, 0xfffff6a2, 0x008221d2, 0x5a821bd1, 0x000683d4, 0xfffff6a2, 0x008221d2,
+    0x5a825bf2, 0x00494567, 0x00000000, 0x00000000, 0x5a825bf2, 0x0044a7b5, 0xffffec96, 0x00199874,
+    0x5a825bf2, 0x003da266, 0xffffe505, 0x00279f18, 0x5a825bf2, 0x002c07c5, 0xffffe382, 0x003a90e9,
+    0x5a825bf2, 0x001af008, 0xffffebb8, 0x004423c4, 0x5a825bf2, 0x0003a9e1, 0xfffffd0a, 0x00492df1,
+    0x5a825bf2, 0x0003a9e1, 0xfffffd0a, 0x00492df1, 0x5a825bf2, 0x0003a9e1, 0xfffffd0a, 0x00492df1,
+];
+
+/* phase smoothing of IPD/OPD, normalised 0.25 exp(i d0 pi/4) + 0.5 exp(i d1 pi/4) + 1 for
+ * the differences d0, d1 of the two previous phase indices to the current one, format = Q30
+ */
+const PD_SMOOTH: [u32; 64 * 2] = [
+    0x40000000, 0x00000000, 0x3e7fb9c3, 0x0dc7a38c, 0x3b6c2980, 0x17c4dd66, 0x3b89721d, 0x177b237c,
+    0x40000000, 0x00000000, 0x3b89721d, 0xe884dc84, 0x3b6c2980, 0xe83b229a, 0x3e7fb9c3, 0xf2385c74,
+    0x3fa5b336, 0x06b5c9a9, 0x3c78c5f7, 0x14f4cda2, 0x377ab6de, 0x1fe82181, 0x35cd6014, 0x22a8f834,
+    0x3dec256e, 0x102ca45b, 0x3e92d4d8, 0xf2902913, 0x3db6e14a, 0xef0c8c3d, 0x3f93c4fd, 0xf8a7e5e0,
+    0x3f2113fb, 0x0a858354, 0x3a73c714, 0x1a106456, 0x33333333, 0x26666666, 0x2ec7bb3f, 0x2bad1d36,
+    0x393e4b8b, 0x1c9f25c6, 0x3f31c041, 0xf5e08226, 0x3e16d092, 0xf07a4bdc, 0x3fd04302, 0xfb1e3265,
+    0x3f6fb838, 0x08798dbd, 0x3a59363d, 0x1a4ba746, 0x31702443, 0x28a4b027, 0x2a6e821a, 0x2fe97453,
+    0x3826966b, 0x1eb5b916, 0x3be5d2f0, 0xe9749444, 0x3b929a9b, 0xe89c2261, 0x3f4a3511, 0xf67e1626,
+    0x40000000, 0x00000000, 0x3cf2cec8, 0x1386c8d5, 0x35404fe1, 0x23803540, 0x2fc3d3c6, 0x2a98d8f0,
+    0x40000000, 0x00000000, 0x2fc3d3c6, 0xd5672710, 0x35404fe1, 0xdc7fcac0, 0x3cf2cec8, 0xec79372b,
+    0x3f6fb838, 0xf7867243, 0x3f4a3511, 0x0981e9da, 0x3b929a9b, 0x1763dd9f, 0x3be5d2f0, 0x168b6bbc,
+    0x3826966b, 0xe14a46ea, 0x2a6e821a, 0xd0168bad, 0x31702443, 0xd75b4fd9, 0x3a59363d, 0xe5b458ba,
+    0x3f2113fb, 0xf57a7cac, 0x3fd04302, 0x04e1cd9b, 0x3e16d092, 0x0f85b424, 0x3f31c041, 0x0a1f7dda,
+    0x393e4b8b, 0xe360da3a, 0x2ec7bb3f, 0xd452e2ca, 0x33333333, 0xd999999a, 0x3a73c714, 0xe5ef9baa,
+    0x3fa5b336, 0xf94a3657, 0x3f93c4fd, 0x07581a20, 0x3db6e14a, 0x10f373c3, 0x3e92d4d8, 0x0d6fd6ed,
+    0x3dec256e, 0xefd35ba5, 0x35cd6014, 0xdd5707cc, 0x377ab6de, 0xe017de7f, 0x3c78c5f7, 0xeb0b325e,
+];
+
+/* exp(i pd pi/4) of a phase index, format = Q30 */
+const PD_ROTATE: [u32; 8 * 2] = [
+    0x40000000, 0x00000000, 0x2d413ccd, 0x2d413ccd, 0x00000000, 0x40000000, 0xd2bec333, 0x2d413ccd,
+    0xc0000000, 0x00000000, 0xd2bec333, 0xd2bec333, 0x00000000, 0xc0000000, 0x2d413ccd, 0xd2bec333,
+];
+
+/* Huffman codebooks of annex 8.B, codeword lengths and codewords indexed by symbol */
+const HUFF_IID_DF1_BITS: [u8; 61] = [
+    18, 18, 18, 18, 18, 18, 18, 18, 18, 17, 18, 17, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10,
+    8, 7, 6, 5, 4, 3, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 11, 12, 13, 14, 14, 15, 16, 16, 17, 17, 18,
+    17, 18, 18, 18, 18, 18, 18, 18, 18, 18,
+];
+
+const HUFF_IID_DF1_CODES: [u32; 61] = [
+    0x1feb4, 0x1feb5, 0x1fd76, 0x1fd77, 0x1fd74, 0x1fd75, 0x1fe8a, 0x1fe8b, 0x1fe88, 0x0fe80,
+    0x1feb6, 0x0fe82, 0x0feb8, 0x07f42, 0x07fae, 0x03faf, 0x01fd1, 0x01fe9, 0x00fe9, 0x007ea,
+    0x007fb, 0x003fb, 0x001fb, 0x001ff, 0x0007c, 0x0003c, 0x0001c, 0x0000c, 0x00000, 0x00001,
+    0x00001, 0x00002, 0x00001, 0x0000d, 0x0001d, 0x0003d, 0x0007d, 0x000fc, 0x001fc, 0x003fc,
+    0x003f4, 0x007eb, 0x00fea, 0x01fea, 0x01fd6, 0x03fd0, 0x07faf, 0x07f43, 0x0feb9, 0x0fe83,
+    0x1feb7, 0x0fe81, 0x1fe89, 0x1fe8e, 0x1fe8f, 0x1fe8c, 0x1fe8d, 0x1feb2, 0x1feb3, 0x1feb0,
+    0x1feb1,
+];
+
+const HUFF_IID_DT1_BITS: [u8; 61] = [
+    16, 16, 16, 16, 16, 16, 16, 16, 16, 15, 15, 15, 15, 15, 15, 14, 14, 13, 13, 13, 12, 12, 11, 10,
+    9, 9, 7, 6, 5, 3, 1, 2, 5, 6, 7, 8, 9, 10, 11, 11, 12, 12, 13, 13, 14, 14, 15, 15, 15, 15, 16,
+    16, 16, 16, 16, 16, 16, 16, 16, 16, 16,
+];
+
+const HUFF_IID_DT1_CODES: [u32; 61] = [
+    0x04ed4, 0x04ed5, 0x04ece, 0x04ecf, 0x04ecc, 0x04ed6, 0x04ed8, 0x04f46, 0x04f60, 0x02718,
+    0x02719, 0x02764, 0x02765, 0x0276d, 0x027b1, 0x013b7, 0x013d6, 0x009c7, 0x009e9, 0x009ed,
+    0x004ee, 0x004f7, 0x00278, 0x00139, 0x0009a, 0x0009f, 0x00020, 0x00011, 0x0000a, 0x00003,
+    0x00001, 0x00000, 0x0000b, 0x00012, 0x00021, 0x0004c, 0x0009b, 0x0013a, 0x00279, 0x00270,
+    0x004ef, 0x004e2, 0x009ea, 0x009d8, 0x013d7, 0x013d0, 0x027b2, 0x027a2, 0x0271a, 0x0271b,
+    0x04f66, 0x04f67, 0x04f61, 0x04f47, 0x04ed9, 0x04ed7, 0x04ecd, 0x04ed2, 0x04ed3, 0x04ed0,
+    0x04ed1,
+];
+
+const HUFF_IID_DF0_BITS: [u8; 29] = [
+    17, 17, 17, 17, 16, 15, 13, 10, 9, 7, 6, 5, 4, 3, 1, 3, 4, 5, 6, 6, 8, 11, 13, 14, 14, 15, 17,
+    18, 18,
+];
+
+const HUFF_IID_DF0_CODES: [u32; 29] = [
+    0x1fffb, 0x1fffc, 0x1fffd, 0x1fffa, 0x0fffc, 0x07ffc, 0x01ffd, 0x003fe, 0x001fe, 0x0007e,
+    0x0003c, 0x0001d, 0x0000d, 0x00005, 0x00000, 0x00004, 0x0000c, 0x0001c, 0x0003d, 0x0003e,
+    0x000fe, 0x007fe, 0x01ffc, 0x03ffc, 0x03ffd, 0x07ffd, 0x1fffe, 0x3fffe, 0x3ffff,
+];
+
+const HUFF_IID_DT0_BITS: [u8; 29] = [
+    19, 19, 19, 20, 20, 20, 17, 15, 12, 10, 8, 6, 4, 2, 1, 3, 5, 7, 9, 11, 13, 14, 17, 19, 20, 20,
+    20, 20, 20,
+];
+
+const HUFF_IID_DT0_CODES: [u32; 29] = [
+    0x7fff9, 0x7fffa, 0x7fffb, 0xffff8, 0xffff9, 0xffffa, 0x1fffd, 0x07ffe, 0x00ffe, 0x003fe,
+    0x000fe, 0x0003e, 0x0000e, 0x00002, 0x00000, 0x00006, 0x0001e, 0x0007e, 0x001fe, 0x007fe,
+    0x01ffe, 0x03ffe, 0x1fffc, 0x7fff8, 0xffffb, 0xffffc, 0xffffd, 0xffffe, 0xfffff,
+];
+
+const HUFF_ICC_DF_BITS: [u8; 15] = [14, 14, 12, 10, 7, 5, 3, 1, 2, 4, 6, 8, 9, 11, 13];
+
+const HUFF_ICC_DF_CODES: [u32; 15] = [
+    0x03fff, 0x03ffe, 0x00ffe, 0x003fe, 0x0007e, 0x0001e, 0x00006, 0x00000, 0x00002, 0x0000e,
+    0x0003e, 0x000fe, 0x001fe, 0x007fe, 0x01ffe,
+];
+
+const HUFF_ICC_DT_BITS: [u8; 15] = [14, 13, 11, 9, 7, 5, 3, 1, 2, 4, 6, 8, 10, 12, 14];
+
+const HUFF_ICC_DT_CODES: [u32; 15] = [
+    0x03ffe, 0x01ffe, 0x007fe, 0x001fe, 0x0007e, 0x0001e, 0x00006, 0x00000, 0x00002, 0x0000e,
+    0x0003e, 0x000fe, 0x003fe, 0x00ffe, 0x03fff,
+];
+
+const HUFF_IPD_DF_BITS: [u8; 8] = [1, 3, 4, 4, 4, 4, 4, 4];
+
+const HUFF_IPD_DF_CODES: [u32; 8] = [
+    0x00001, 0x00000, 0x00006, 0x00004, 0x00002, 0x00003, 0x00005, 0x00007,
+];
+
+const HUFF_IPD_DT_BITS: [u8; 8] = [1, 3, 4, 5, 5, 4, 4, 3];
+
+const HUFF_IPD_DT_CODES: [u32; 8] = [
+    0x00001, 0x00002, 0x00002, 0x00003, 0x00002, 0x00000, 0x00003, 0x00003,
+];
+
+const HUFF_OPD_DF_BITS: [u8; 8] = [1, 3, 4, 4, 5, 5, 4, 3];
+
+const HUFF_OPD_DF_CODES: [u32; 8] = [
+    0x00001, 0x00001, 0x00006, 0x00004, 0x0000f, 0x0000e, 0x00005, 0x00000,
+];
+
+const HUFF_OPD_DT_BITS: [u8; 8] = [1, 3, 4, 5, 5, 4, 4, 3];
+
+const HUFF_OPD_DT_CODES: [u32; 8] = [
+    0x00001, 0x00002, 0x00001, 0x00007, 0x00006, 0x00000, 0x00002, 0x00003,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate std;
+    use std::boxed::Box;
+    use std::vec::Vec;
+
+    const ALL_TABS: [&PSHuffTab; 10] = [
+        &HUFF_IID_DF1,
+        &HUFF_IID_DT1,
+        &HUFF_IID_DF0,
+        &HUFF_IID_DT0,
+        &HUFF_ICC_DF,
+        &HUFF_ICC_DT,
+        &HUFF_IPD_DF,
+        &HUFF_IPD_DT,
+        &HUFF_OPD_DF,
+        &HUFF_OPD_DT,
+    ];
+
+    fn zeroed_ps() -> Box<ParametricStereo> {
+        // SAFETY: all-zero bytes are a valid ParametricStereo (integers, arrays of them and a bool)
+        unsafe { Box::new_zeroed().assume_init() }
+    }
+
+    /* MSB-first bit writer */
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        n_bits: usize,
+    }
+
+    impl BitWriter {
+        fn put(&mut self, n: u32, v: u32) {
+            for i in (0..n).rev() {
+                if self.n_bits.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                let bit = ((v >> i) & 1) as u8;
+                *self.bytes.last_mut().unwrap() |= bit << (7 - self.n_bits % 8);
+                self.n_bits += 1;
+            }
+        }
+
+        fn put_symbol(&mut self, tab: &PSHuffTab, val: i32) {
+            let sym = (val + tab.offset) as usize;
+            self.put(tab.bits[sym] as u32, tab.codes[sym]);
+        }
+    }
+
+    #[test]
+    fn huffman_codebooks_are_complete_prefix_codes() {
+        for tab in ALL_TABS {
+            let kraft: u64 = tab.bits.iter().map(|&n| 1u64 << (32 - n)).sum();
+            assert_eq!(kraft, 1 << 32);
+            assert_eq!(tab.max_bits, *tab.bits.iter().max().unwrap() as u32);
+            for (i, (&n, &c)) in tab.bits.iter().zip(tab.codes).enumerate() {
+                assert!(c < 1 << n);
+                for (&m, &d) in tab.bits.iter().zip(tab.codes).skip(i + 1) {
+                    let l = n.min(m) as u32;
+                    assert_ne!(c >> (n as u32 - l), d >> (m as u32 - l));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unpack_reads_iid_and_icc() {
+        let iid: [i8; 10] = [0, 1, 2, 3, -7, 7, 0, -1, -2, 4];
+        let icc: [i8; 10] = [0, 0, 1, 7, 7, 3, 2, 2, 0, 5];
+        let mut w = BitWriter::default();
+        w.put(1, 1); /* header */
+        w.put(4, 1 << 3); /* enable_iid, iid_mode 0 */
+        w.put(4, 1 << 3); /* enable_icc, icc_mode 0 */
+        w.put(1, 0); /* enable_ext */
+        w.put(3, 1); /* frame_class 0, one envelope */
+        w.put(1, 0); /* iid_dt */
+        let mut prev = 0;
+        for &v in &iid {
+            w.put_symbol(&HUFF_IID_DF0, (v - prev) as i32);
+            prev = v;
+        }
+        w.put(1, 0); /* icc_dt */
+        let mut prev = 0;
+        for &v in &icc {
+            w.put_symbol(&HUFF_ICC_DF, (v - prev) as i32);
+            prev = v;
+        }
+        let n_bits = w.n_bits as i32;
+
+        let mut ps = zeroed_ps();
+        let mut bsi = BitStreamInfo::from_slice(&w.bytes);
+        assert_eq!(unpack_ps_data(&mut ps, &mut bsi, n_bits + 7), n_bits);
+        assert!(ps.start);
+        assert_eq!(ps.numEnv, 1);
+        assert_eq!(ps.borderPosition[..2], [-1, 31]);
+        assert_eq!(ps.iidPar[0][..10], iid);
+        assert_eq!(ps.iccPar[0][..10], icc);
+
+        /* the same payload cut short is an error, and PS is off until the next header */
+        let mut bsi = BitStreamInfo::from_slice(&w.bytes);
+        assert_eq!(unpack_ps_data(&mut ps, &mut bsi, n_bits - 1), n_bits - 1);
+        assert!(!ps.start);
+        assert_eq!(ps.iidPar[0], [0; PS_MAX_NR_IIDICC]);
+    }
+
+    #[test]
+    fn mono_parameters_reproduce_the_qmf_input() {
+        let mut ps = zeroed_ps();
+        let mut bsi = BitStreamInfo::from_slice(&[0x80, 0x10]); /* header, nothing enabled, no envelope */
+        unpack_ps_data(&mut ps, &mut bsi, 16);
+        assert!(ps.start);
+
+        /* two frames of a continuous random signal, the first one fills the filter history */
+        let n_qmf = 40;
+        let mut rand = 1u32;
+        let signal: Vec<[i32; 128]> = (0..64 + 8)
+            .map(|_| {
+                core::array::from_fn(|_| {
+                    rand = rand.wrapping_mul(1664525).wrapping_add(1013904223);
+                    (rand as i32) >> 10
+                })
+            })
+            .collect();
+        let mut x_buf = [[0i32; 128]; 32 + HF_GEN];
+        let (mut left, mut right) = ([0; 128], [0; 128]);
+        for frame in 0..2 {
+            for (r, row) in x_buf.iter_mut().enumerate() {
+                *row = signal[32 * frame + r];
+            }
+            for slot in 0..NUM_QMF_SLOTS {
+                apply_ps_slot(&mut ps, &x_buf, slot, n_qmf, &mut left, &mut right);
+                assert_eq!(left, right);
+                if frame == 1 {
+                    for (&y, &x) in left.iter().zip(&x_buf[slot + HF_ADJ]).take(2 * n_qmf) {
+                        assert!((y - x).abs() <= 8, "slot {slot}: {y} vs {x}");
+                    }
+                    assert!(left[2 * n_qmf..].iter().all(|&y| y == 0));
+                }
+            }
+        }
+    }
+}
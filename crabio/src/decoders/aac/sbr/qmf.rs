@@ -0,0 +1,520 @@
+//! QMF filterbanks (14496-3, 4.6.18.4): the 32-band complex analysis bank that splits the
+//! core decoder output, and the 64-band synthesis bank that produces the double-rate PCM.
+//! Both run the modulation as a 64-point type-IV DCT on top of a 32-point complex FFT.
+
+use super::fft::fft32c;
+use super::math::clip_2n_shift30;
+use super::{FBITS_IN_QMFA, FBITS_OUT_QMFS, NUM_QMF_DELAY_BUFS, RND_VAL};
+use crate::mp3_decoder::{madd_64, mulshift_32};
+use crate::utils::clip_to_short::clip_to_short;
+
+/* PreMultiply64() table
+ * format = Q30
+ * reordered for sequential access
+ *
+ * for (i = 0; i < 64/4; i++) {
+ *   angle = (i + 0.25) * M_PI / nmdct;
+ *   x = (cos(angle) + sin(angle));
+ *   x =  sin(angle);
+ *
+ *   angle = (nmdct/2 - 1 - i + 0.25) * M_PI / nmdct;
+ *   x = (cos(angle) + sin(angle));
+ *   x = sin(angle);
+ * }
+ */
+const COS4SIN4_TAB64: [i32; 64] = [
+    0x40c7d2bd, 0x00c90e90, 0x424ff28f, 0x3ff4e5e0, 0x43cdd89a, 0x03ecadcf, 0x454149fc, 0x3fc395f9,
+    0x46aa0d6d, 0x070de172, 0x4807eb4b, 0x3f6af2e3, 0x495aada2, 0x0a2abb59, 0x4aa22036, 0x3eeb3347,
+    0x4bde1089, 0x0d415013, 0x4d0e4de2, 0x3e44a5ef, 0x4e32a956, 0x104fb80e, 0x4f4af5d1, 0x3d77b192,
+    0x50570819, 0x135410c3, 0x5156b6d9, 0x3c84d496, 0x5249daa2, 0x164c7ddd, 0x53304df6, 0x3b6ca4c4,
+    0x5409ed4b, 0x19372a64, 0x54d69714, 0x3a2fcee8, 0x55962bc0, 0x1c1249d8, 0x56488dc5, 0x38cf1669,
+    0x56eda1a0, 0x1edc1953, 0x57854ddd, 0x374b54ce, 0x580f7b19, 0x2192e09b, 0x588c1404, 0x35a5793c,
+    0x58fb0568, 0x2434f332, 0x595c3e2a, 0x33de87de, 0x59afaf4c, 0x26c0b162, 0x59f54bee, 0x31f79948,
+    0x5a2d0957, 0x29348937, 0x5a56deec, 0x2ff1d9c7, 0x5a72c63b, 0x2b8ef77d, 0x5a80baf6, 0x2dce88aa,
+];
+
+/* PostMultiply64() table
+ * format = Q30
+ * reordered for sequential access
+ *
+ * for (i = 0; i <= (32/2); i++) {
+ *   angle = i * M_PI / 64;
+ *   x = (cos(angle) + sin(angle));
+ *   x = sin(angle);
+ * }
+ */
+const COS1SIN1_TAB64: [i32; 34] = [
+    0x40000000, 0x00000000, 0x43103085, 0x0323ecbe, 0x45f704f7, 0x0645e9af, 0x48b2b335, 0x09640837,
+    0x4b418bbe, 0x0c7c5c1e, 0x4da1fab5, 0x0f8cfcbe, 0x4fd288dc, 0x1294062f, 0x51d1dc80, 0x158f9a76,
+    0x539eba45, 0x187de2a7, 0x553805f2, 0x1b5d100a, 0x569cc31b, 0x1e2b5d38, 0x57cc15bc, 0x20e70f32,
+    0x58c542c5, 0x238e7673, 0x5987b08a, 0x261feffa, 0x5a12e720, 0x2899e64a, 0x5a6690ae, 0x2afad269,
+    0x5a82799a, 0x2d413ccd,
+];
+
+/* coefficient table 4.A.87, format = Q31
+ * reordered as:
+ *   cTab[0],  cTab[64],  cTab[128], cTab[192], cTab[256],
+ *   cTab[2],  cTab[66],  cTab[130], cTab[194], cTab[258],
+ *   ...
+ *   cTab[64], cTab[128], cTab[192], cTab[256], cTab[320]
+ *
+ * NOTE: cTab[1, 2, ... , 318, 319] = cTab[639, 638, ... 322, 321]
+ *   except cTab[384] = -cTab[256], cTab[512] = -cTab[128]
+ */
+const C_TAB_A: [u32; 165] = [
+    0x00000000, 0x0055dba1, 0x01b2e41d, 0x09015651, 0x2e3a7532, 0xffed978a, 0x006090c4, 0x01fd3ba0,
+    0x08a24899, 0x311af3a4, 0xfff0065d, 0x006b47fa, 0x024bf7a1, 0x082f552e, 0x33ff670e, 0xffef7b8b,
+    0x0075fded, 0x029e35b4, 0x07a8127d, 0x36e69691, 0xffee1650, 0x00807994, 0x02f3e48d, 0x070bbf58,
+    0x39ce0477, 0xffecc31b, 0x008a7dd7, 0x034d01f0, 0x06593912, 0x3cb41219, 0xffeb50b2, 0x009424c6,
+    0x03a966bb, 0x0590a67d, 0x3f962fb8, 0xffe9ca76, 0x009d10bf, 0x04083fec, 0x04b0adcb, 0x4272a385,
+    0xffe88ba8, 0x00a520bb, 0x04694101, 0x03b8f8dc, 0x4547daea, 0xffe79e16, 0x00abe79e, 0x04cc2fcf,
+    0x02a99097, 0x4812f848, 0xffe6d466, 0x00b1978d, 0x05303f87, 0x01816e06, 0x4ad237a2, 0xffe65416,
+    0x00b5c867, 0x05950122, 0x0040c496, 0x4d83976c, 0xffe66dd0, 0x00b8394b, 0x05f9c051, 0xfee723c6,
+    0x5024d70e, 0xffe69423, 0x00b8c6b0, 0x065dd56a, 0xfd7475d8, 0x52b449de, 0xffe75361, 0x00b73ab0,
+    0x06c0f0c0, 0xfbe8f5bd, 0x552f8ff7, 0xffe85b4b, 0x00b36acd, 0x0721bf22, 0xfa44a069, 0x579505f5,
+    0xffea353a, 0x00acbd2f, 0x077fedb3, 0xf887507c, 0x59e2f69e, 0xffec8409, 0x00a3508f, 0x07da2b7f,
+    0xf6b1f3c3, 0x5c16d0ae, 0xffef2395, 0x0096dcc2, 0x08303897, 0xf4c473c6, 0x5e2f6367, 0xfff294c3,
+    0x00872c63, 0x0880ffdd, 0xf2bf6ea4, 0x602b0c7f, 0xfff681d6, 0x007400b8, 0x08cb4e23, 0xf0a3959f,
+    0x6207f220, 0xfffb42b0, 0x005d36df, 0x090ec1fc, 0xee71b2fe, 0x63c45243, 0x00007134, 0x00426f36,
+    0x0949eaac, 0xec2a3f5f, 0x655f63f2, 0x0006b1cf, 0x0023b989, 0x097c1ee8, 0xe9cea84a, 0x66d76725,
+    0x000d31b5, 0x0000e790, 0x09a3e163, 0xe75f8bb8, 0x682b39a4, 0x001471f8, 0xffda17f2, 0x09c0e59f,
+    0xe4de0cb0, 0x6959709d, 0x001c3549, 0xffaea5d6, 0x09d19ca9, 0xe24b8f66, 0x6a619c5e, 0x0024dd50,
+    0xff7ee3f1, 0x09d5560b, 0xdfa93ab5, 0x6b42a864, 0x002d8e42, 0xff4aabc8, 0x09caeb0f, 0xdcf898fb,
+    0x6bfbdd98, 0x003745f9, 0xff120d70, 0x09b18a1d, 0xda3b176a, 0x6c8c4c7a, 0x004103f4, 0xfed4bec3,
+    0x09881dc5, 0xd7722f04, 0x6cf4073e, 0x004b6c46, 0xfe933dc0, 0x094d7ec2, 0xd49fd55f, 0x6d32730f,
+    0x0055dba1, 0x01b2e41d, 0x09015651, 0x2e3a7532, 0x6d474e1d,
+];
+
+/* coefficient table 4.A.87, format = Q31
+ * reordered as cTab[0], cTab[64], cTab[128], ... cTab[576], cTab[1], cTab[65], cTab[129], ... cTab[639]
+ * keeping full table (not using symmetry) to allow sequential access in synth filter inner loop
+ */
+const C_TAB_S: [u32; 640] = [
+    0x00000000, 0x0055dba1, 0x01b2e41d, 0x09015651, 0x2e3a7532, 0x6d474e1d, 0xd1c58ace, 0x09015651,
+    0xfe4d1be3, 0x0055dba1, 0xffede50e, 0x005b5371, 0x01d78bfc, 0x08d3e41b, 0x2faa221c, 0x6d41d963,
+    0xd3337b3d, 0x09299ead, 0xfe70b8d1, 0x0050b177, 0xffed978a, 0x006090c4, 0x01fd3ba0, 0x08a24899,
+    0x311af3a4, 0x6d32730f, 0xd49fd55f, 0x094d7ec2, 0xfe933dc0, 0x004b6c46, 0xffefc9b9, 0x0065fde5,
+    0x02244a24, 0x086b1eeb, 0x328cc6f0, 0x6d18520e, 0xd60a46e5, 0x096d0e21, 0xfeb48d0d, 0x00465348,
+    0xfff0065d, 0x006b47fa, 0x024bf7a1, 0x082f552e, 0x33ff670e, 0x6cf4073e, 0xd7722f04, 0x09881dc5,
+    0xfed4bec3, 0x004103f4, 0xffeff6ca, 0x0070c8a5, 0x0274ba43, 0x07ee507c, 0x3572ec70, 0x6cc59bab,
+    0xd8d7f21f, 0x099ec3dc, 0xfef3f6ab, 0x003c1fa4, 0xffef7b8b, 0x0075fded, 0x029e35b4, 0x07a8127d,
+    0x36e69691, 0x6c8c4c7a, 0xda3b176a, 0x09b18a1d, 0xff120d70, 0x003745f9, 0xffeedfa4, 0x007b3875,
+    0x02c89901, 0x075ca90c, 0x385a49c4, 0x6c492217, 0xdb9b5b12, 0x09c018ce, 0xff2ef725, 0x00329ab6,
+    0xffee1650, 0x00807994, 0x02f3e48d, 0x070bbf58, 0x39ce0477, 0x6bfbdd98, 0xdcf898fb, 0x09caeb0f,
+    0xff4aabc8, 0x002d8e42, 0xffed651d, 0x0085c217, 0x03201116, 0x06b559c3, 0x3b415115, 0x6ba4629f,
+    0xde529086, 0x09d1fa23, 0xff6542d1, 0x00293718, 0xffecc31b, 0x008a7dd7, 0x034d01f0, 0x06593912,
+    0x3cb41219, 0x6b42a864, 0xdfa93ab5, 0x09d5560b, 0xff7ee3f1, 0x0024dd50, 0xffebe77b, 0x008f4bfc,
+    0x037ad438, 0x05f7fb90, 0x3e25b17e, 0x6ad73e8d, 0xe0fc421e, 0x09d52709, 0xff975c01, 0x002064f8,
+    0xffeb50b2, 0x009424c6, 0x03a966bb, 0x0590a67d, 0x3f962fb8, 0x6a619c5e, 0xe24b8f66, 0x09d19ca9,
+    0xffaea5d6, 0x001c3549, 0xffea9192, 0x0098b855, 0x03d8afe6, 0x05237f9d, 0x41058bc6, 0x69e29784,
+    0xe396a45d, 0x09cab9f2, 0xffc4e365, 0x0018703f, 0xffe9ca76, 0x009d10bf, 0x04083fec, 0x04b0adcb,
+    0x4272a385, 0x6959709d, 0xe4de0cb0, 0x09c0e59f, 0xffda17f2, 0x001471f8, 0xffe940f4, 0x00a1039c,
+    0x043889c6, 0x0437fb0a, 0x43de620a, 0x68c7269b, 0xe620c476, 0x09b3d77f, 0xffee183b, 0x0010bc63,
+    0xffe88ba8, 0x00a520bb, 0x04694101, 0x03b8f8dc, 0x4547daea, 0x682b39a4, 0xe75f8bb8, 0x09a3e163,
+    0x0000e790, 0x000d31b5, 0xffe83a07, 0x00a8739d, 0x049aa82f, 0x03343533, 0x46aea856, 0x6785c24d,
+    0xe89971b7, 0x099140a7, 0x00131c75, 0x0009aa3f, 0xffe79e16, 0x00abe79e, 0x04cc2fcf, 0x02a99097,
+    0x4812f848, 0x66d76725, 0xe9cea84a, 0x097c1ee8, 0x0023b989, 0x0006b1cf, 0xffe7746e, 0x00af374c,
+    0x04fe20be, 0x02186a91, 0x4973fef1, 0x661fd6b8, 0xeafee7f1, 0x0963ed46, 0x0033b927, 0x00039609,
+    0xffe6d466, 0x00b1978d, 0x05303f87, 0x01816e06, 0x4ad237a2, 0x655f63f2, 0xec2a3f5f, 0x0949eaac,
+    0x00426f36, 0x00007134, 0xffe6afee, 0x00b3d15c, 0x05626209, 0x00e42fa2, 0x4c2ca3df, 0x64964063,
+    0xed50a31d, 0x092d7970, 0x00504f41, 0xfffdfa25, 0xffe65416, 0x00b5c867, 0x05950122, 0x0040c496,
+    0x4d83976c, 0x63c45243, 0xee71b2fe, 0x090ec1fc, 0x005d36df, 0xfffb42b0, 0xffe681c6, 0x00b74c37,
+    0x05c76fed, 0xff96db90, 0x4ed62be3, 0x62ea6474, 0xef8d4d7b, 0x08edfeaa, 0x006928a0, 0xfff91fca,
+    0xffe66dd0, 0x00b8394b, 0x05f9c051, 0xfee723c6, 0x5024d70e, 0x6207f220, 0xf0a3959f, 0x08cb4e23,
+    0x007400b8, 0xfff681d6, 0xffe66fac, 0x00b8fe0d, 0x062bf5ec, 0xfe310657, 0x516eefb9, 0x611d58a3,
+    0xf1b461ab, 0x08a75da4, 0x007e0393, 0xfff48700, 0xffe69423, 0x00b8c6b0, 0x065dd56a, 0xfd7475d8,
+    0x52b449de, 0x602b0c7f, 0xf2bf6ea4, 0x0880ffdd, 0x00872c63, 0xfff294c3, 0xffe6fed4, 0x00b85f70,
+    0x068f8b44, 0xfcb1d740, 0x53f495aa, 0x5f30ff5f, 0xf3c4e887, 0x08594887, 0x008f87aa, 0xfff0e7ef,
+    0xffe75361, 0x00b73ab0, 0x06c0f0c0, 0xfbe8f5bd, 0x552f8ff7, 0x5e2f6367, 0xf4c473c6, 0x08303897,
+    0x0096dcc2, 0xffef2395, 0xffe80414, 0x00b58c8c, 0x06f1825d, 0xfb19b7bd, 0x56654bdd, 0x5d26be9b,
+    0xf5be0fa9, 0x08061671, 0x009da526, 0xffedc418, 0xffe85b4b, 0x00b36acd, 0x0721bf22, 0xfa44a069,
+    0x579505f5, 0x5c16d0ae, 0xf6b1f3c3, 0x07da2b7f, 0x00a3508f, 0xffec8409, 0xffe954d0, 0x00b06b68,
+    0x075112a2, 0xf96916f5, 0x58befacd, 0x5b001db8, 0xf79fa13a, 0x07ad8c26, 0x00a85e94, 0xffeb3849,
+    0xffea353a, 0x00acbd2f, 0x077fedb3, 0xf887507c, 0x59e2f69e, 0x59e2f69e, 0xf887507c, 0x077fedb3,
+    0x00acbd2f, 0xffea353a, 0xffeb3849, 0x00a85e94, 0x07ad8c26, 0xf79fa13a, 0x5b001db8, 0x58befacd,
+    0xf96916f5, 0x075112a2, 0x00b06b68, 0xffe954d0, 0xffec8409, 0x00a3508f, 0x07da2b7f, 0xf6b1f3c3,
+    0x5c16d0ae, 0x579505f5, 0xfa44a069, 0x0721bf22, 0x00b36acd, 0xffe85b4b, 0xffedc418, 0x009da526,
+    0x08061671, 0xf5be0fa9, 0x5d26be9b, 0x56654bdd, 0xfb19b7bd, 0x06f1825d, 0x00b58c8c, 0xffe80414,
+    0xffef2395, 0x0096dcc2, 0x08303897, 0xf4c473c6, 0x5e2f6367, 0x552f8ff7, 0xfbe8f5bd, 0x06c0f0c0,
+    0x00b73ab0, 0xffe75361, 0xfff0e7ef, 0x008f87aa, 0x08594887, 0xf3c4e887, 0x5f30ff5f, 0x53f495aa,
+    0xfcb1d740, 0x068f8b44, 0x00b85f70, 0xffe6fed4, 0xfff294c3, 0x00872c63, 0x0880ffdd, 0xf2bf6ea4,
+    0x602b0c7f, 0x52b449de, 0xfd7475d8, 0x065dd56a, 0x00b8c6b0, 0xffe69423, 0xfff48700, 0x007e0393,
+    0x08a75da4, 0xf1b461ab, 0x611d58a3, 0x516eefb9, 0xfe310657, 0x062bf5ec, 0x00b8fe0d, 0xffe66fac,
+    0xfff681d6, 0x007400b8, 0x08cb4e23, 0xf0a3959f, 0x6207f220, 0x5024d70e, 0xfee723c6, 0x05f9c051,
+    0x00b8394b, 0xffe66dd0, 0xfff91fca, 0x006928a0, 0x08edfeaa, 0xef8d4d7b, 0x62ea6474, 0x4ed62be3,
+    0xff96db90, 0x05c76fed, 0x00b74c37, 0xffe681c6, 0xfffb42b0, 0x005d36df, 0x090ec1fc, 0xee71b2fe,
+    0x63c45243, 0x4d83976c, 0x0040c496, 0x05950122, 0x00b5c867, 0xffe65416, 0xfffdfa25, 0x00504f41,
+    0x092d7970, 0xed50a31d, 0x64964063, 0x4c2ca3df, 0x00e42fa2, 0x05626209, 0x00b3d15c, 0xffe6afee,
+    0x00007134, 0x00426f36, 0x0949eaac, 0xec2a3f5f, 0x655f63f2, 0x4ad237a2, 0x01816e06, 0x05303f87,
+    0x00b1978d, 0xffe6d466, 0x00039609, 0x0033b927, 0x0963ed46, 0xeafee7f1, 0x661fd6b8, 0x4973fef1,
+    0x02186a91, 0x04fe20be, 0x00af374c, 0xffe7746e, 0x0006b1cf, 0x0023b989, 0x097c1ee8, 0xe9cea84a,
+    0x66d76725, 0x4812f848, 0x02a99097, 0x04cc2fcf, 0x00abe79e, 0xffe79e16, 0x0009aa3f, 0x00131c75,
+    0x099140a7, 0xe89971b7, 0x6785c24d, 0x46aea856, 0x03343533, 0x049aa82f, 0x00a8739d, 0xffe83a07,
+    0x000d31b5, 0x0000e790, 0x09a3e163, 0xe75f8bb8, 0x682b39a4, 0x4547daea, 0x03b8f8dc, 0x04694101,
+    0x00a520bb, 0xffe88ba8, 0x0010bc63, 0xffee183b, 0x09b3d77f, 0xe620c476, 0x68c7269b, 0x43de620a,
+    0x0437fb0a, 0x043889c6, 0x00a1039c, 0xffe940f4, 0x001471f8, 0xffda17f2, 0x09c0e59f, 0xe4de0cb0,
+    0x6959709d, 0x4272a385, 0x04b0adcb, 0x04083fec, 0x009d10bf, 0xffe9ca76, 0x0018703f, 0xffc4e365,
+    0x09cab9f2, 0xe396a45d, 0x69e29784, 0x41058bc6, 0x05237f9d, 0x03d8afe6, 0x0098b855, 0xffea9192,
+    0x001c3549, 0xffaea5d6, 0x09d19ca9, 0xe24b8f66, 0x6a619c5e, 0x3f962fb8, 0x0590a67d, 0x03a966bb,
+    0x009424c6, 0xffeb50b2, 0x002064f8, 0xff975c01, 0x09d52709, 0xe0fc421e, 0x6ad73e8d, 0x3e25b17e,
+    0x05f7fb90, 0x037ad438, 0x008f4bfc, 0xffebe77b, 0x0024dd50, 0xff7ee3f1, 0x09d5560b, 0xdfa93ab5,
+    0x6b42a864, 0x3cb41219, 0x06593912, 0x034d01f0, 0x008a7dd7, 0xffecc31b, 0x00293718, 0xff6542d1,
+    0x09d1fa23, 0xde529086, 0x6ba4629f, 0x3b415115, 0x06b559c3, 0x03201116, 0x0085c217, 0xffed651d,
+    0x002d8e42, 0xff4aabc8, 0x09caeb0f, 0xdcf898fb, 0x6bfbdd98, 0x39ce0477, 0x070bbf58, 0x02f3e48d,
+    0x00807994, 0xffee1650, 0x00329ab6, 0xff2ef725, 0x09c018ce, 0xdb9b5b12, 0x6c492217, 0x385a49c4,
+    0x075ca90c, 0x02c89901, 0x007b3875, 0xffeedfa4, 0x003745f9, 0xff120d70, 0x09b18a1d, 0xda3b176a,
+    0x6c8c4c7a, 0x36e69691, 0x07a8127d, 0x029e35b4, 0x0075fded, 0xffef7b8b, 0x003c1fa4, 0xfef3f6ab,
+    0x099ec3dc, 0xd8d7f21f, 0x6cc59bab, 0x3572ec70, 0x07ee507c, 0x0274ba43, 0x0070c8a5, 0xffeff6ca,
+    0x004103f4, 0xfed4bec3, 0x09881dc5, 0xd7722f04, 0x6cf4073e, 0x33ff670e, 0x082f552e, 0x024bf7a1,
+    0x006b47fa, 0xfff0065d, 0x00465348, 0xfeb48d0d, 0x096d0e21, 0xd60a46e5, 0x6d18520e, 0x328cc6f0,
+    0x086b1eeb, 0x02244a24, 0x0065fde5, 0xffefc9b9, 0x004b6c46, 0xfe933dc0, 0x094d7ec2, 0xd49fd55f,
+    0x6d32730f, 0x311af3a4, 0x08a24899, 0x01fd3ba0, 0x006090c4, 0xffed978a, 0x0050b177, 0xfe70b8d1,
+    0x09299ead, 0xd3337b3d, 0x6d41d963, 0x2faa221c, 0x08d3e41b, 0x01d78bfc, 0x005b5371, 0xffede50f,
+];
+
+/***********************************************************************************************************************
+ * Function:    PreMultiply64
+ *
+ * Description: pre-twiddle stage of 64-point DCT-IV
+ *
+ * Inputs:      buffer of 64 samples
+ *
+ * Outputs:     processed samples in same buffer
+ *
+ * Return:      none
+ *
+ * Notes:       minimum 1 GB in, 2 GB out, gains 2 int bits
+ *              gbOut = gbIn + 1
+ *              output is limited to sqrt(2)/2 plus GB in full GB
+ *              uses 3-mul, 3-add butterflies instead of 4-mul, 2-add
+ **********************************************************************************************************************/
+fn pre_multiply_64(zbuf: &mut [i32]) {
+    for (i, cs) in COS4SIN4_TAB64.chunks_exact(4).enumerate() {
+        /* cps2 = (cos+sin), sin2 = sin, cms2 = (cos-sin) */
+        let (cps2a, sin2a, cps2b, sin2b) = (cs[0], cs[1], cs[2], cs[3]);
+        let (lo, hi) = (2 * i, 63 - 2 * i);
+
+        let ar1 = zbuf[lo];
+        let ai2 = zbuf[lo + 1];
+        let ai1 = zbuf[hi];
+        let ar2 = zbuf[hi - 1];
+
+        /* gain 2 ints bit from MULSHIFT32 by Q30
+         * max per-sample gain (ignoring implicit scaling) = MAX(sin(angle)+cos(angle)) = 1.414
+         * i.e. gain 1 GB since worst case is sin(angle) = cos(angle) = 0.707 (Q30), gain 2 from
+         *   extra sign bits, and eat one in adding
+         */
+        let t = mulshift_32(sin2a, ar1 + ai1);
+        zbuf[lo + 1] = mulshift_32(cps2a, ai1) - t; /* cos*ai1 - sin*ar1 */
+        zbuf[lo] = mulshift_32(cps2a - 2 * sin2a, ar1) + t; /* cos*ar1 + sin*ai1 */
+
+        let t = mulshift_32(sin2b, ar2 + ai2);
+        zbuf[hi] = mulshift_32(cps2b, ai2) - t; /* cos*ai2 - sin*ar2 */
+        zbuf[hi - 1] = mulshift_32(cps2b - 2 * sin2b, ar2) + t; /* cos*ar2 + sin*ai2 */
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    PostMultiply64
+ *
+ * Description: post-twiddle stage of 64-point type-IV DCT
+ *
+ * Inputs:      buffer of 64 samples
+ *              number of output samples to calculate
+ *
+ * Outputs:     processed samples in same buffer
+ *
+ * Return:      none
+ *
+ * Notes:       minimum 1 GB in, 2 GB out, gains 2 int bits
+ *              gbOut = gbIn + 1
+ *              output is limited to sqrt(2)/2 plus GB in full GB
+ *              nSampsOut is rounded up to next multiple of 4, since we calculate
+ *                4 samples per loop
+ **********************************************************************************************************************/
+fn post_multiply_64(fft: &mut [i32], n_samps_out: usize) {
+    /* load coeffs for first pass
+     * cps2 = (cos+sin)/2, sin2 = sin/2, cms2 = (cos-sin)/2
+     */
+    let mut cps2 = COS1SIN1_TAB64[0];
+    let mut sin2 = COS1SIN1_TAB64[1];
+    let mut cms2 = cps2 - 2 * sin2;
+
+    for i in 0..n_samps_out.div_ceil(4) {
+        let (lo, hi) = (2 * i, 63 - 2 * i);
+        let ar1 = fft[lo];
+        let ai1 = fft[lo + 1];
+        let ar2 = fft[hi - 1];
+        let ai2 = fft[hi];
+
+        /* gain 2 int bits (multiplying by Q30), max gain = sqrt(2) */
+        let t = mulshift_32(sin2, ar1 + ai1);
+        fft[hi] = t - mulshift_32(cps2, ai1);
+        fft[lo] = t + mulshift_32(cms2, ar1);
+
+        cps2 = COS1SIN1_TAB64[2 * i + 2];
+        sin2 = COS1SIN1_TAB64[2 * i + 3];
+
+        let ai2 = -ai2;
+        let t = mulshift_32(sin2, ar2 + ai2);
+        fft[hi - 1] = t - mulshift_32(cps2, ai2);
+        cms2 = cps2 - 2 * sin2;
+        fft[lo + 1] = t + mulshift_32(cms2, ar2);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    QMFAnalysisConv
+ *
+ * Description: convolution kernel for analysis QMF
+ *
+ * Inputs:      delay buffer of size 32*10 = 320 real-valued PCM samples
+ *              index for delay ring buffer (range = [0, 9])
+ *
+ * Outputs:     64 consecutive 32-bit samples
+ *
+ * Return:      none
+ *
+ * Notes:       walks cTabA forwards from the start and backwards from the end, since the
+ *                reordered table stores only the first half of the symmetric window
+ **********************************************************************************************************************/
+fn qmf_analysis_conv(delay: &[i32; 320], d_idx: usize, u_buf: &mut [i32]) {
+    let mut d_off = d_idx * 32 + 31;
+    let mut c0 = 0;
+    let mut c1 = 33 * 5 - 1;
+
+    /* step back one delay line, wrapping around the ring buffer */
+    let prev = |d: usize| if d < 32 { d + 320 - 32 } else { d - 32 };
+
+    for k in 0..32 {
+        let (mut u64lo, mut u64hi) = (0u64, 0u64);
+        for j in 0..10 {
+            /* special first pass since we need to flip sign to create cTab[384], cTab[512] */
+            let c = if j < 5 {
+                c0 += 1;
+                C_TAB_A[c0 - 1] as i32
+            } else {
+                c1 -= 1;
+                let c = C_TAB_A[c1 + 1] as i32;
+                if k == 0 && (j == 6 || j == 8) {
+                    c.wrapping_neg()
+                } else {
+                    c
+                }
+            };
+            if j & 1 == 0 {
+                u64lo = madd_64(u64lo, c, delay[d_off]);
+            } else {
+                u64hi = madd_64(u64hi, c, delay[d_off]);
+            }
+            d_off = prev(d_off);
+        }
+
+        u_buf[k] = (u64lo >> 32) as i32;
+        u_buf[k + 32] = (u64hi >> 32) as i32;
+        d_off = d_off.wrapping_sub(1);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    QMFAnalysis
+ *
+ * Description: 32-subband analysis QMF (4.6.18.4.1)
+ *
+ * Inputs:      32 consecutive samples of decoded 32-bit PCM, format = Q(fBitsIn)
+ *              delay buffer of size 32*10 = 320 PCM samples
+ *              number of fraction bits in input PCM
+ *              index for delay ring buffer (range = [0, 9])
+ *              number of subbands to calculate (range = [0, 32])
+ *
+ * Outputs:     qmfaBands complex subband samples, format = Q(FBITS_OUT_QMFA)
+ *              updated delay buffer
+ *              updated delay index
+ *
+ * Return:      guard bit mask
+ *
+ * Notes:       output stored as RE{X0}, IM{X0}, RE{X1}, IM{X1}, ... RE{X31}, IM{X31}
+ *              output stored in int buffer of size 64*2 = 128
+ *                (zero-filled from XBuf[2*qmfaBands] to XBuf[127])
+ **********************************************************************************************************************/
+pub fn qmf_analysis(
+    inbuf: &[i32],
+    delay: &mut [i32; 320],
+    x_buf: &mut [i32; 128],
+    f_bits_in: i32,
+    delay_idx: &mut usize,
+    qmfa_bands: usize,
+) -> u32 {
+    /* overwrite oldest PCM with new PCM
+     * delay[n] has 1 GB after shifting (either << or >>)
+     */
+    let dst = &mut delay[*delay_idx * 32..][..32];
+    if f_bits_in > FBITS_IN_QMFA {
+        let shift = (f_bits_in - FBITS_IN_QMFA).min(31);
+        for (d, &x) in dst.iter_mut().zip(&inbuf[..32]) {
+            *d = x >> shift;
+        }
+    } else {
+        let shift = (FBITS_IN_QMFA - f_bits_in).min(30);
+        for (d, &x) in dst.iter_mut().zip(&inbuf[..32]) {
+            *d = clip_2n_shift30(x, shift);
+        }
+    }
+
+    /* use XBuf[128] as temp buffer for reordering */
+    let (u_buf, t_buf) = x_buf.split_at_mut(64);
+    qmf_analysis_conv(delay, *delay_idx, u_buf);
+
+    /* uBuf has at least 2 GB right now (1 from clipping to Q(FBITS_IN_QMFA), one from
+     *   the scaling by cTab (MULSHIFT32(*delayPtr--, *cPtr++), with net gain of < 1.0)
+     */
+    t_buf[0] = u_buf[0];
+    t_buf[1] = u_buf[1];
+    for n in 1..31 {
+        t_buf[2 * n] = -u_buf[64 - n];
+        t_buf[2 * n + 1] = u_buf[n + 1];
+    }
+    t_buf[2 * 31 + 1] = u_buf[32];
+    t_buf[2 * 31] = -u_buf[33];
+
+    /* fast in-place DCT-IV - only need 2*qmfaBands output samples */
+    pre_multiply_64(t_buf); /* 2 GB in, 3 GB out */
+    fft32c(t_buf); /* 3 GB in, 1 GB out */
+    post_multiply_64(t_buf, qmfa_bands * 2); /* 1 GB in, 2 GB out */
+
+    let mut gb_mask = 0;
+    for n in 0..qmfa_bands {
+        /* implicit scaling of 2 in our output Q format
+         * (writes stay in the low half of XBuf, tBuf is the high half)
+         */
+        x_buf[2 * n] = x_buf[64 + n];
+        x_buf[2 * n + 1] = -x_buf[127 - n];
+        gb_mask |= x_buf[2 * n].unsigned_abs() | x_buf[2 * n + 1].unsigned_abs();
+    }
+
+    /* fill top section with zeros for HF generation */
+    x_buf[2 * qmfa_bands..].fill(0);
+
+    *delay_idx = if *delay_idx == NUM_QMF_DELAY_BUFS - 1 {
+        0
+    } else {
+        *delay_idx + 1
+    };
+
+    /* minimum of 2 GB in output */
+    gb_mask
+}
+
+/***********************************************************************************************************************
+ * Function:    QMFSynthesisConv
+ *
+ * Description: final convolution kernel for synthesis QMF
+ *
+ * Inputs:      delay buffer of size 64*10 = 640 complex samples (1280 ints)
+ *              index for delay ring buffer (range = [0, 9])
+ *              number of channels
+ *
+ * Outputs:     64 consecutive 16-bit PCM samples, interleaved by factor of nChans
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn qmf_synthesis_conv(delay: &[i32; 1280], d_idx: usize, outbuf: &mut [i16], n_chans: usize) {
+    let mut d_off0 = d_idx * 128;
+    let mut d_off1 = if d_off0 == 0 { 1279 } else { d_off0 - 1 };
+
+    /* step back two delay lines, wrapping around the ring buffer */
+    let prev = |d: usize| if d < 256 { d + 1280 - 256 } else { d - 256 };
+
+    /* scaling note: total gain of coefs (cPtr[0]-cPtr[9] for any k) is < 2.0, so 1 GB in delay values is adequate */
+    for (cs, pcm) in C_TAB_S
+        .chunks_exact(10)
+        .zip(outbuf.iter_mut().step_by(n_chans))
+    {
+        let mut sum64 = 0u64;
+        for c in cs.chunks_exact(2) {
+            sum64 = madd_64(sum64, c[0] as i32, delay[d_off0]);
+            d_off0 = prev(d_off0);
+            sum64 = madd_64(sum64, c[1] as i32, delay[d_off1]);
+            d_off1 = prev(d_off1);
+        }
+
+        d_off0 += 1;
+        d_off1 -= 1;
+        *pcm = clip_to_short(((sum64 >> 32) as i32).wrapping_add(RND_VAL), FBITS_OUT_QMFS);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    QMFSynthesis
+ *
+ * Description: 64-subband synthesis QMF (4.6.18.4.2)
+ *
+ * Inputs:      64 consecutive complex subband QMF samples, format = Q(FBITS_IN_QMFS)
+ *              delay buffer of size 64*10 = 640 complex samples (1280 ints)
+ *              index for delay ring buffer (range = [0, 9])
+ *              number of QMF subbands to process (range = [0, 64])
+ *              number of channels
+ *
+ * Outputs:     64 consecutive 16-bit PCM samples, interleaved by factor of nChans
+ *              updated delay buffer
+ *              updated delay index
+ *
+ * Return:      none
+ *
+ * Notes:       assumes MIN_GBITS_IN_QMFS guard bits in input, either from
+ *                QMFAnalysis (if upsampling only) or from MapHF (if SBR on)
+ **********************************************************************************************************************/
+pub fn qmf_synthesis(
+    inbuf: &[i32; 128],
+    delay: &mut [i32; 1280],
+    delay_idx: &mut usize,
+    qmfs_bands: usize,
+    outbuf: &mut [i16],
+    n_chans: usize,
+) {
+    let d_idx = *delay_idx;
+    let t_buf: &mut [i32; 128] = (&mut delay[d_idx * 128..][..128]).try_into().unwrap();
+
+    /* reorder inputs to DCT-IV, only use first qmfsBands (complex) samples
+     * real parts of even bands and odd bands go to the low half (forwards),
+     * imaginary parts to the high half (backwards)
+     */
+    t_buf.fill(0);
+    for n in 0..qmfs_bands {
+        t_buf[n] = inbuf[2 * n];
+        t_buf[127 - n] = inbuf[2 * n + 1];
+    }
+
+    let (t_buf_lo, t_buf_hi) = t_buf.split_at_mut(64);
+
+    /* 2 GB in, 3 GB out */
+    pre_multiply_64(t_buf_lo);
+    pre_multiply_64(t_buf_hi);
+
+    /* 3 GB in, 1 GB out */
+    fft32c(t_buf_lo);
+    fft32c(t_buf_hi);
+
+    /* 1 GB in, 2 GB out */
+    post_multiply_64(t_buf_lo, 64);
+    post_multiply_64(t_buf_hi, 64);
+
+    /* could fuse with PostMultiply64 to avoid separate pass */
+    for n in 0..32 {
+        let (a0, a1) = (t_buf_lo[2 * n], t_buf_lo[2 * n + 1]);
+        let (b0, b1) = (t_buf_hi[2 * n], -t_buf_hi[2 * n + 1]);
+
+        t_buf_lo[2 * n] = b0 - a0;
+        t_buf_lo[2 * n + 1] = b1 - a1;
+        t_buf_hi[2 * n] = b0 + a0;
+        t_buf_hi[2 * n + 1] = b1 + a1;
+    }
+
+    qmf_synthesis_conv(delay, d_idx, outbuf, n_chans);
+
+    *delay_idx = if *delay_idx == NUM_QMF_DELAY_BUFS - 1 {
+        0
+    } else {
+        *delay_idx + 1
+    };
+}
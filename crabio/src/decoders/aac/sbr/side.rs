@@ -0,0 +1,585 @@
+//! Unpacking of the SBR header and the per-channel side information: time/frequency grid,
+//! delta coding flags, inverse filtering modes and sinusoids (14496-3, tables 4.62 - 4.67),
+//! and the extended data that may carry parametric stereo ([`super::ps`]).
+
+use super::huffman::{
+    decode_sbr_envelope, decode_sbr_noise, uncouple_sbr_envelope, uncouple_sbr_noise,
+};
+use super::{
+    MAX_NUM_ENV, MAX_QMF_BANDS, NUM_TIME_SLOTS, PSInfoSBR, SAMPLES_PER_SLOT, SBR_GRID_FIXFIX,
+    SBR_GRID_FIXVAR, SBR_GRID_VARFIX, SBRGrid, SBRHeader, ps,
+};
+use crate::utils::bit_stream_cache::BitStreamInfo;
+
+/* cLog2[i] = ceil(log2(i)) (disregard i == 0) */
+const C_LOG2: [u32; 9] = [0, 0, 1, 2, 2, 3, 3, 3, 3];
+
+/***********************************************************************************************************************
+ * Function:    UnpackSBRHeader
+ *
+ * Description: unpack SBR header (table 4.56)
+ *
+ * Inputs:      bitstream positioned at the start of the SBR header
+ *
+ * Outputs:     initialized SBRHeader struct for this SCE/CPE block
+ *
+ * Return:      true if frame reset is triggered, false otherwise
+ **********************************************************************************************************************/
+pub fn unpack_sbr_header(bsi: &mut BitStreamInfo, sbr_hdr: &mut SBRHeader) -> bool {
+    /* save previous values so we know whether to reset decoder */
+    let prev = *sbr_hdr;
+
+    sbr_hdr.ampRes = bsi.get_bits(1) as u8;
+    sbr_hdr.startFreq = bsi.get_bits(4) as u8;
+    sbr_hdr.stopFreq = bsi.get_bits(4) as u8;
+    sbr_hdr.crossOverBand = bsi.get_bits(3) as u8;
+    sbr_hdr.resBitsHdr = bsi.get_bits(2) as u8;
+    sbr_hdr.hdrExtra1 = bsi.get_bits(1) as u8;
+    sbr_hdr.hdrExtra2 = bsi.get_bits(1) as u8;
+
+    if sbr_hdr.hdrExtra1 != 0 {
+        sbr_hdr.freqScale = bsi.get_bits(2) as u8;
+        sbr_hdr.alterScale = bsi.get_bits(1) as u8;
+        sbr_hdr.noiseBands = bsi.get_bits(2) as u8;
+    } else {
+        /* defaults */
+        sbr_hdr.freqScale = 2;
+        sbr_hdr.alterScale = 1;
+        sbr_hdr.noiseBands = 2;
+    }
+
+    if sbr_hdr.hdrExtra2 != 0 {
+        sbr_hdr.limiterBands = bsi.get_bits(2) as u8;
+        sbr_hdr.limiterGains = bsi.get_bits(2) as u8;
+        sbr_hdr.interpFreq = bsi.get_bits(1) as u8;
+        sbr_hdr.smoothMode = bsi.get_bits(1) as u8;
+    } else {
+        /* defaults */
+        sbr_hdr.limiterBands = 2;
+        sbr_hdr.limiterGains = 2;
+        sbr_hdr.interpFreq = 1;
+        sbr_hdr.smoothMode = 1;
+    }
+    sbr_hdr.count = sbr_hdr.count.saturating_add(1);
+
+    /* if any of these have changed from previous frame, reset the SBR module */
+    sbr_hdr.startFreq != prev.startFreq
+        || sbr_hdr.stopFreq != prev.stopFreq
+        || sbr_hdr.freqScale != prev.freqScale
+        || sbr_hdr.alterScale != prev.alterScale
+        || sbr_hdr.crossOverBand != prev.crossOverBand
+        || sbr_hdr.noiseBands != prev.noiseBands
+}
+
+/* 2 bits per relative border, in units of 2 time slots */
+fn rel_border(bsi: &mut BitStreamInfo) -> i32 {
+    2 * bsi.get_bits(2) as i32 + 2
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackSBRGrid
+ *
+ * Description: unpack SBR grid (table 4.62)
+ *
+ * Inputs:      bitstream positioned at the start of the SBR grid
+ *              initialized SBRHeader struct for this SCE/CPE block
+ *
+ * Outputs:     initialized SBRGrid struct for this channel
+ *
+ * Return:      Ok, or Err if the grid is not valid, the SBRGrid struct is left untouched in that case
+ *
+ * Notes:       the reference decoder only asserts on these, but a broken stream would otherwise
+ *                give envelope borders outside of the QMF matrix
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn unpack_sbr_grid(
+    bsi: &mut BitStreamInfo,
+    sbr_hdr: &SBRHeader,
+    sbr_grid: &mut SBRGrid,
+) -> Result<(), ()> {
+    let mut grid = *sbr_grid;
+    let mut rel_bord_lead = [0; MAX_NUM_ENV];
+    let mut rel_bord_trail = [0; MAX_NUM_ENV];
+    let (abs_bord_lead, abs_bord_trail, num_rel_lead, num_rel_trail, middle_border);
+
+    grid.ampResFrame = sbr_hdr.ampRes;
+    grid.frameClass = bsi.get_bits(2) as u8;
+    match grid.frameClass {
+        SBR_GRID_FIXFIX => {
+            let num_env = 1usize << bsi.get_bits(2);
+            if num_env > MAX_NUM_ENV {
+                return Err(());
+            }
+            grid.numEnv = num_env as u8;
+            if num_env == 1 {
+                grid.ampResFrame = 0;
+            }
+
+            let freq_res = bsi.get_bits(1) as u8;
+            grid.freqRes[..num_env].fill(freq_res);
+
+            abs_bord_lead = 0;
+            abs_bord_trail = NUM_TIME_SLOTS as i32;
+            num_rel_lead = num_env - 1;
+            num_rel_trail = 0;
+
+            /* numEnv = 1, 2, or 4 */
+            rel_bord_lead[..num_rel_lead].fill(NUM_TIME_SLOTS as i32 / num_env as i32);
+            middle_border = num_env as i32 >> 1;
+        }
+        SBR_GRID_FIXVAR => {
+            let abs_border = bsi.get_bits(2) as i32 + NUM_TIME_SLOTS as i32;
+            let num_rel_border = bsi.get_bits(2) as usize;
+            let num_env = num_rel_border + 1;
+            grid.numEnv = num_env as u8;
+            for rel in &mut rel_bord_trail[..num_rel_border] {
+                *rel = rel_border(bsi);
+            }
+
+            grid.pointer = bsi.get_bits(C_LOG2[num_env + 1]) as u8;
+            for env in (0..num_env).rev() {
+                grid.freqRes[env] = bsi.get_bits(1) as u8;
+            }
+
+            abs_bord_lead = 0;
+            abs_bord_trail = abs_border;
+            num_rel_lead = 0;
+            num_rel_trail = num_rel_border;
+
+            middle_border = if grid.pointer > 1 {
+                num_env as i32 + 1 - grid.pointer as i32
+            } else {
+                num_env as i32 - 1
+            };
+        }
+        SBR_GRID_VARFIX => {
+            let abs_border = bsi.get_bits(2) as i32;
+            let num_rel_border = bsi.get_bits(2) as usize;
+            let num_env = num_rel_border + 1;
+            grid.numEnv = num_env as u8;
+            for rel in &mut rel_bord_lead[..num_rel_border] {
+                *rel = rel_border(bsi);
+            }
+
+            grid.pointer = bsi.get_bits(C_LOG2[num_env + 1]) as u8;
+            for env in 0..num_env {
+                grid.freqRes[env] = bsi.get_bits(1) as u8;
+            }
+
+            abs_bord_lead = abs_border;
+            abs_bord_trail = NUM_TIME_SLOTS as i32;
+            num_rel_lead = num_rel_border;
+            num_rel_trail = 0;
+
+            middle_border = match grid.pointer {
+                0 => 1,
+                1 => num_env as i32 - 1,
+                p => p as i32 - 1,
+            };
+        }
+        _ => {
+            /* SBR_GRID_VARVAR */
+            abs_bord_lead = bsi.get_bits(2) as i32; /* absBorder0 */
+            abs_bord_trail = bsi.get_bits(2) as i32 + NUM_TIME_SLOTS as i32; /* absBorder1 */
+            num_rel_lead = bsi.get_bits(2) as usize;
+            num_rel_trail = bsi.get_bits(2) as usize;
+
+            let num_env = num_rel_lead + num_rel_trail + 1;
+            if num_env > MAX_NUM_ENV {
+                return Err(());
+            }
+            grid.numEnv = num_env as u8;
+
+            for rel in &mut rel_bord_lead[..num_rel_lead] {
+                *rel = rel_border(bsi);
+            }
+            for rel in &mut rel_bord_trail[..num_rel_trail] {
+                *rel = rel_border(bsi);
+            }
+
+            grid.pointer = bsi.get_bits(C_LOG2[num_rel_lead + num_rel_trail + 2]) as u8;
+            for env in 0..num_env {
+                grid.freqRes[env] = bsi.get_bits(1) as u8;
+            }
+
+            middle_border = if grid.pointer > 1 {
+                num_env as i32 + 1 - grid.pointer as i32
+            } else {
+                num_env as i32 - 1
+            };
+        }
+    }
+
+    /* build time border vector */
+    let num_env = grid.numEnv as usize;
+    let mut borders = [0i32; MAX_NUM_ENV + 1];
+    borders[0] = abs_bord_lead;
+
+    let mut border = abs_bord_lead;
+    for (env, &rel) in rel_bord_lead[..num_rel_lead].iter().enumerate() {
+        border += rel;
+        borders[env + 1] = border;
+    }
+
+    let mut border = abs_bord_trail;
+    for (env, &rel) in (num_rel_lead + 1..num_env)
+        .rev()
+        .zip(&rel_bord_trail[..num_rel_trail])
+    {
+        border -= rel;
+        borders[env] = border;
+    }
+    borders[num_env] = abs_bord_trail;
+
+    /* borders must be increasing, else the envelopes would run backwards through the QMF matrix */
+    if borders[..=num_env].windows(2).any(|w| w[0] >= w[1])
+        || !(0..=num_env as i32).contains(&middle_border)
+    {
+        return Err(());
+    }
+    for (t, &b) in grid.envTimeBorder.iter_mut().zip(&borders[..=num_env]) {
+        *t = (b * SAMPLES_PER_SLOT as i32) as u8;
+    }
+
+    if num_env > 1 {
+        grid.numNoiseFloors = 2;
+        grid.noiseTimeBorder[0] = grid.envTimeBorder[0];
+        grid.noiseTimeBorder[1] = grid.envTimeBorder[middle_border as usize];
+        grid.noiseTimeBorder[2] = grid.envTimeBorder[num_env];
+    } else {
+        grid.numNoiseFloors = 1;
+        grid.noiseTimeBorder[0] = grid.envTimeBorder[0];
+        grid.noiseTimeBorder[1] = grid.envTimeBorder[1];
+    }
+
+    *sbr_grid = grid;
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackDeltaTimeFreq
+ *
+ * Description: unpack time/freq flags for delta coding of SBR envelopes (table 4.63)
+ *
+ * Inputs:      bitstream positioned at the start of the dt/df flags
+ *              index of channel
+ *
+ * Outputs:     delta flags for envelope and noise floors
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn unpack_delta_time_freq(psi: &mut PSInfoSBR, bsi: &mut BitStreamInfo, ch: usize) {
+    let sbr_grid = &psi.sbrGrid[ch];
+    let sbr_chan = &mut psi.sbrChan[ch];
+
+    for flag in &mut sbr_chan.deltaFlagEnv[..sbr_grid.numEnv as usize] {
+        *flag = bsi.get_bits(1) as u8;
+    }
+    for flag in &mut sbr_chan.deltaFlagNoise[..sbr_grid.numNoiseFloors as usize] {
+        *flag = bsi.get_bits(1) as u8;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackInverseFilterMode
+ *
+ * Description: unpack invf flags for chirp factor calculation (table 4.64)
+ *
+ * Inputs:      bitstream positioned at the start of the invf flags
+ *              number of noise floor bands
+ *
+ * Outputs:     invf flags for noise floor bands
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn unpack_inverse_filter_mode(bsi: &mut BitStreamInfo, mode: &mut [u8]) {
+    for m in mode {
+        *m = bsi.get_bits(2) as u8;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackSinusoids
+ *
+ * Description: unpack sinusoid (harmonic) flags for each SBR subband (table 4.67)
+ *
+ * Inputs:      bitstream positioned at the start of the sinusoid flags
+ *              number of high resolution SBR subbands (nHigh)
+ *              index of channel
+ *
+ * Outputs:     sinusoid flags for each SBR subband, zero-filled above nHigh
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+fn unpack_sinusoids(bsi: &mut BitStreamInfo, n_high: usize, sbr_chan: &mut super::SBRChan) {
+    sbr_chan.addHarmonicFlag[1] = bsi.get_bits(1) as u8;
+
+    let mut n = 0;
+    if sbr_chan.addHarmonicFlag[1] != 0 {
+        for flag in &mut sbr_chan.addHarmonic[1][..n_high] {
+            *flag = bsi.get_bits(1) as u8;
+        }
+        n = n_high;
+    }
+
+    /* zero out unused bands */
+    sbr_chan.addHarmonic[1][n..MAX_QMF_BANDS].fill(0);
+}
+
+/***********************************************************************************************************************
+ * Function:    CopyCouplingGrid
+ *
+ * Description: copy grid parameters from left to right for channel coupling
+ *
+ * Inputs:      initialized SBRGrid struct for left channel
+ *
+ * Outputs:     initialized SBRGrid struct for right channel
+ *
+ * Return:      none
+ *
+ * Notes:       numEnvPrev, numNoiseFloorsPrev, freqResPrev are updated in DecodeSBREnvelope()
+ *                and DecodeSBRNoise()
+ **********************************************************************************************************************/
+fn copy_coupling_grid(left: &SBRGrid, right: &mut SBRGrid) {
+    right.frameClass = left.frameClass;
+    right.ampResFrame = left.ampResFrame;
+    right.pointer = left.pointer;
+    right.numEnv = left.numEnv;
+    right.envTimeBorder = left.envTimeBorder;
+    right.freqRes = left.freqRes;
+    right.numNoiseFloors = left.numNoiseFloors;
+    right.noiseTimeBorder = left.noiseTimeBorder;
+}
+
+/* skip n_bits bits, any number of them */
+pub fn skip_bits(bsi: &mut BitStreamInfo, mut n_bits: i32) {
+    while n_bits > 0 {
+        bsi.advance_bitstream(n_bits.min(32) as u32);
+        n_bits -= 32;
+    }
+}
+
+/* extended data, parametric stereo if allowed for the element, anything else is skipped */
+fn unpack_extended_data(psi: &mut PSInfoSBR, bsi: &mut BitStreamInfo, ps_allowed: bool) {
+    psi.extendedDataPresent = bsi.get_bits(1) as u8;
+    if psi.extendedDataPresent != 0 {
+        psi.extendedDataSize = bsi.get_bits(4) as i32;
+        if psi.extendedDataSize == 15 {
+            psi.extendedDataSize += bsi.get_bits(8) as i32;
+        }
+
+        let mut bits_left = 8 * psi.extendedDataSize;
+        while bits_left > 7 {
+            let extension_id = bsi.get_bits(2);
+            bits_left -= 2;
+            if extension_id == ps::EXTENSION_ID_PS && ps_allowed {
+                bits_left -= ps::unpack_ps_data(&mut psi.ps, bsi, bits_left);
+            } else {
+                skip_bits(bsi, bits_left);
+                bits_left = 0;
+            }
+        }
+        skip_bits(bsi, bits_left);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackSBRSingleChannel
+ *
+ * Description: unpack sideband info (grid, delta flags, invf flags, envelope and
+ *                noise floor configuration, sinusoids) for a single channel
+ *
+ * Inputs:      bitstream positioned at the start of the sideband info
+ *              initialized PSInfoSBR struct (after parsing SBR header and building
+ *                frequency tables)
+ *              base output channel (range = [0, nChans-1])
+ *              whether PS data in the extended data is for this element
+ *
+ * Outputs:     updated PSInfoSBR struct (SBRGrid and SBRChan, PS state)
+ *
+ * Return:      Ok, or Err if the grid is not valid
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn unpack_sbr_single_channel(
+    psi: &mut PSInfoSBR,
+    bsi: &mut BitStreamInfo,
+    ch_base: usize,
+    ps_allowed: bool,
+) -> Result<(), ()> {
+    psi.dataExtra = bsi.get_bits(1) as u8;
+    if psi.dataExtra != 0 {
+        psi.resBitsData = bsi.get_bits(4) as u8;
+    }
+
+    unpack_sbr_grid(bsi, &psi.sbrHdr[ch_base], &mut psi.sbrGrid[ch_base])?;
+    unpack_delta_time_freq(psi, bsi, ch_base);
+    let n_q = psi.sbrFreq[ch_base].numNoiseFloorBands;
+    unpack_inverse_filter_mode(bsi, &mut psi.sbrChan[ch_base].invfMode[1][..n_q]);
+
+    decode_sbr_envelope(psi, bsi, ch_base, 0);
+    decode_sbr_noise(psi, bsi, ch_base, 0);
+
+    unpack_sinusoids(bsi, psi.sbrFreq[ch_base].nHigh, &mut psi.sbrChan[ch_base]);
+
+    unpack_extended_data(psi, bsi, ps_allowed);
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackSBRChannelPair
+ *
+ * Description: unpack sideband info (grid, delta flags, invf flags, envelope and
+ *                noise floor configuration, sinusoids) for a channel pair
+ *
+ * Inputs:      bitstream positioned at the start of the sideband info
+ *              base output channel (range = [0, nChans-1])
+ *
+ * Outputs:     updated PSInfoSBR struct (SBRGrid and SBRChan for both channels)
+ *
+ * Return:      Ok, or Err if a grid is not valid
+ **********************************************************************************************************************/
+#[allow(clippy::result_unit_err)]
+pub fn unpack_sbr_channel_pair(
+    psi: &mut PSInfoSBR,
+    bsi: &mut BitStreamInfo,
+    ch_base: usize,
+) -> Result<(), ()> {
+    psi.dataExtra = bsi.get_bits(1) as u8;
+    if psi.dataExtra != 0 {
+        psi.resBitsData = bsi.get_bits(4) as u8;
+        psi.resBitsData = bsi.get_bits(4) as u8;
+    }
+
+    let n_q = psi.sbrFreq[ch_base].numNoiseFloorBands;
+    psi.couplingFlag = bsi.get_bits(1) as i32;
+    if psi.couplingFlag != 0 {
+        unpack_sbr_grid(bsi, &psi.sbrHdr[ch_base], &mut psi.sbrGrid[ch_base])?;
+        let [left, right] = &mut psi.sbrGrid;
+        copy_coupling_grid(left, right);
+
+        unpack_delta_time_freq(psi, bsi, ch_base);
+        unpack_delta_time_freq(psi, bsi, ch_base + 1);
+
+        unpack_inverse_filter_mode(bsi, &mut psi.sbrChan[ch_base].invfMode[1][..n_q]);
+        let [left, right] = &mut psi.sbrChan;
+        right.invfMode[1][..n_q].copy_from_slice(&left.invfMode[1][..n_q]);
+
+        decode_sbr_envelope(psi, bsi, ch_base, 0);
+        decode_sbr_noise(psi, bsi, ch_base, 0);
+        decode_sbr_envelope(psi, bsi, ch_base, 1);
+        decode_sbr_noise(psi, bsi, ch_base, 1);
+
+        uncouple_sbr_envelope(psi, ch_base);
+        uncouple_sbr_noise(psi, ch_base);
+    } else {
+        unpack_sbr_grid(bsi, &psi.sbrHdr[ch_base], &mut psi.sbrGrid[ch_base])?;
+        unpack_sbr_grid(bsi, &psi.sbrHdr[ch_base], &mut psi.sbrGrid[ch_base + 1])?;
+        unpack_delta_time_freq(psi, bsi, ch_base);
+        unpack_delta_time_freq(psi, bsi, ch_base + 1);
+        unpack_inverse_filter_mode(bsi, &mut psi.sbrChan[ch_base].invfMode[1][..n_q]);
+        unpack_inverse_filter_mode(bsi, &mut psi.sbrChan[ch_base + 1].invfMode[1][..n_q]);
+
+        decode_sbr_envelope(psi, bsi, ch_base, 0);
+        decode_sbr_envelope(psi, bsi, ch_base, 1);
+        decode_sbr_noise(psi, bsi, ch_base, 0);
+        decode_sbr_noise(psi, bsi, ch_base, 1);
+    }
+
+    let n_high = psi.sbrFreq[ch_base].nHigh;
+    unpack_sinusoids(bsi, n_high, &mut psi.sbrChan[ch_base]);
+    unpack_sinusoids(bsi, n_high, &mut psi.sbrChan[ch_base + 1]);
+
+    unpack_extended_data(psi, bsi, false);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* pack (value, bits) pairs MSB first */
+    fn pack(fields: &[(u32, u32)]) -> [u8; 16] {
+        let mut buf = [0u8; 16];
+        let mut pos = 0;
+        for &(v, n) in fields {
+            for b in (0..n).rev() {
+                if (v >> b) & 1 != 0 {
+                    buf[pos / 8] |= 0x80 >> (pos % 8);
+                }
+                pos += 1;
+            }
+        }
+        buf
+    }
+
+    #[test]
+    fn fixfix_grid_splits_frame_evenly() {
+        let buf = pack(&[(0, 2), (2, 2), (1, 1)]); /* FIXFIX, 4 envelopes, high res */
+        let mut grid = SBRGrid::default();
+        unpack_sbr_grid(
+            &mut BitStreamInfo::from_slice(&buf),
+            &SBRHeader::default(),
+            &mut grid,
+        )
+        .unwrap();
+        assert_eq!(grid.numEnv, 4);
+        assert_eq!(grid.envTimeBorder[..5], [0, 8, 16, 24, 32]);
+        assert_eq!(grid.freqRes[..4], [1; 4]);
+        assert_eq!(grid.numNoiseFloors, 2);
+        assert_eq!(grid.noiseTimeBorder, [0, 16, 32]);
+    }
+
+    #[test]
+    fn bad_grids_are_rejected_and_leave_the_old_one() {
+        let mut grid = SBRGrid::default();
+        let good = pack(&[(0, 2), (0, 2), (0, 1)]); /* FIXFIX, 1 envelope */
+        unpack_sbr_grid(
+            &mut BitStreamInfo::from_slice(&good),
+            &SBRHeader::default(),
+            &mut grid,
+        )
+        .unwrap();
+        let saved = grid;
+
+        for bad in [
+            pack(&[(0, 2), (3, 2), (0, 1)]), /* FIXFIX with 8 envelopes */
+            pack(&[(3, 2), (0, 2), (0, 2), (3, 2), (3, 2)]), /* VARVAR with 7 envelopes */
+            /* VARVAR, lead border 3, trail border 16, 3 + 3 * 8 relative slots, past the trail */
+            pack(&[
+                (3, 2),
+                (3, 2),
+                (0, 2),
+                (3, 2),
+                (0, 2),
+                (3, 2),
+                (3, 2),
+                (3, 2),
+                (0, 3),
+                (0, 4),
+            ]),
+            /* FIXVAR with 3 relative borders and a pointer past the 4 envelopes */
+            pack(&[
+                (1, 2),
+                (0, 2),
+                (3, 2),
+                (0, 2),
+                (0, 2),
+                (0, 2),
+                (7, 3),
+                (0, 4),
+            ]),
+        ] {
+            assert!(
+                unpack_sbr_grid(
+                    &mut BitStreamInfo::from_slice(&bad),
+                    &SBRHeader::default(),
+                    &mut grid
+                )
+                .is_err(),
+                "{bad:?}"
+            );
+            assert_eq!(grid.envTimeBorder, saved.envTimeBorder);
+            assert_eq!(grid.numEnv, saved.numEnv);
+        }
+    }
+}
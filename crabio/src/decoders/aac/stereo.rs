@@ -0,0 +1,165 @@
+//! Joint stereo of channel pair elements: mid-side and intensity stereo (14496-3, 4.6.8).
+
+use super::{AACDecoder, ElementId, NSAMPS_LONG, NSAMPS_SHORT, WindowSequence, sfb_table};
+use crate::mp3_decoder::{clip_2n, mulshift_32};
+
+/* -pow(2, i/4.0) and pow(2, i/4.0) for i = [0,1,2,3], format = Q30, the sign chosen by the
+ * intensity codebook (14 = out of phase, 15 = in phase)
+ */
+const POW14: [[u32; 4]; 2] = [
+    [0xc0000000, 0xb3e407d7, 0xa57d8666, 0x945d819b],
+    [0x40000000, 0x4c1bf829, 0x5a82799a, 0x6ba27e65],
+];
+
+/* guard bits of a mask of OR'ed absolute values */
+fn guard_bits(mask: u32) -> i32 {
+    mask.leading_zeros() as i32 - 1
+}
+
+/***********************************************************************************************************************
+ * Function:    StereoProcessGroup
+ *
+ * Description: apply mid-side and intensity stereo to one window of transform coefficients
+ *
+ * Inputs:      dequantized transform coefficients for both channels
+ *              scalefactor band table
+ *              mid-side mask enabled flag
+ *              mid-side mask, one bit for each scalefactor band of the window group, lsb first
+ *              codebook indices for right channel, one for each band of the window group
+ *              scalefactors for right channel, range = [0, 256]
+ *
+ * Outputs:     updated transform coefficients in Q(FBITS_OUT_DQ_OFF)
+ *              updated minimum guard bit count for both channels
+ *
+ * Return:      none
+ *
+ * Notes:       assume no guard bits in input
+ *              gains 0 int bits
+ **********************************************************************************************************************/
+#[allow(clippy::too_many_arguments)]
+pub fn stereo_process_group(
+    coef_l: &mut [i32],
+    coef_r: &mut [i32],
+    sfb_tab: &[u16],
+    ms_mask_pres: i32,
+    ms_mask: &[u8],
+    ms_mask_offset: usize,
+    cb_right: &[u8],
+    sf_right: &[i16],
+    gb_current: &mut [i32; 2],
+) {
+    let mut gb_mask_l = 0u32;
+    let mut gb_mask_r = 0u32;
+
+    for (sfb, (&cb, &sf)) in cb_right.iter().zip(sf_right).enumerate() {
+        let band = sfb_tab[sfb] as usize..sfb_tab[sfb + 1] as usize;
+        let bit = ms_mask_offset + sfb;
+        let ms = ms_mask_pres == 2
+            || (ms_mask_pres == 1 && (ms_mask[bit >> 3] >> (bit & 0x07)) & 0x01 != 0);
+
+        if cb == 14 || cb == 15 {
+            /* intensity stereo */
+            let mut cb_idx = cb as usize;
+            if ms_mask_pres == 1 && ms {
+                cb_idx ^= 0x01; /* invert_intensity(): 14 becomes 15, or 15 becomes 14 */
+            }
+            let sf = -(sf as i32); /* negative since we use identity 0.5^(x) = 2^(-x) (see spec) */
+            cb_idx &= 0x01; /* choose - or + scale factor */
+            let scalef = POW14[cb_idx][(sf & 0x03) as usize] as i32;
+            let scalei = (sf >> 2) + 2; /* +2 to compensate for scalef = Q30 */
+
+            for (l, r) in coef_l[band.clone()].iter().zip(&mut coef_r[band]) {
+                let cr = if scalei > 0 {
+                    let scalei = scalei.min(30);
+                    clip_2n(mulshift_32(*l, scalef), (31 - scalei) as u32) << scalei
+                } else {
+                    mulshift_32(*l, scalef) >> (-scalei).min(31)
+                };
+                gb_mask_r |= cr.unsigned_abs();
+                *r = cr;
+            }
+        } else if cb != 13 && ms {
+            /* mid-side stereo (assumes no GB in inputs) */
+            for (l, r) in coef_l[band.clone()].iter_mut().zip(&mut coef_r[band]) {
+                let (mut cl, cr) = (*l, *r);
+                let sum;
+                if (cl.unsigned_abs() | cr.unsigned_abs()) >> 30 != 0 {
+                    /* avoid overflow (rare) */
+                    cl >>= 1;
+                    sum = clip_2n(cl + (cr >> 1), 30) << 1;
+                    cl = clip_2n(cl - (cr >> 1), 30) << 1;
+                } else {
+                    /* usual case */
+                    sum = cl + cr;
+                    cl -= cr;
+                }
+
+                *l = sum;
+                gb_mask_l |= sum.unsigned_abs();
+                *r = cl;
+                gb_mask_r |= cl.unsigned_abs();
+            }
+        }
+    }
+
+    gb_current[0] = gb_current[0].min(guard_bits(gb_mask_l));
+    gb_current[1] = gb_current[1].min(guard_bits(gb_mask_r));
+}
+
+/***********************************************************************************************************************
+ * Function:    StereoProcess
+ *
+ * Description: apply mid-side and intensity stereo, if enabled
+ *
+ * Inputs:      decoder after AACDequantize() of both channels of a CPE
+ *
+ * Outputs:     updated transform coefficients in Q(FBITS_OUT_DQ_OFF)
+ *              updated minimum guard bit count for both channels
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn stereo_process(dec: &mut AACDecoder) {
+    let psi = &mut dec.m_PSInfoBase;
+
+    /* mid-side and intensity stereo require common_window == 1 (see MPEG4 spec, Correction 2, 2004) */
+    if psi.commonWin != 1 || dec.m_AACDecInfo.currBlockID != ElementId::Cpe {
+        return;
+    }
+
+    /* nothing to do */
+    if psi.msMaskPresent == 0 && psi.intensityUsed[1] == 0 {
+        return;
+    }
+
+    let ics = psi.icsInfo[0];
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let n_samps = if ics.winSequence == WindowSequence::EightShort {
+        NSAMPS_SHORT
+    } else {
+        NSAMPS_LONG
+    };
+    let max_sfb = ics.maxSFB as usize;
+
+    /* do fused mid-side/intensity processing for each block (one long or eight short) */
+    let [coef_l, coef_r] = &mut psi.coef;
+    let mut win = 0;
+    for gp in 0..ics.numWinGroup as usize {
+        /* we use one bit per sfb, so there are maxSFB bits for each window group */
+        let bands = gp * max_sfb..(gp + 1) * max_sfb;
+        for _ in 0..ics.winGroupLen[gp] {
+            let samps = win * n_samps..(win + 1) * n_samps;
+            stereo_process_group(
+                &mut coef_l[samps.clone()],
+                &mut coef_r[samps],
+                sfb_tab,
+                psi.msMaskPresent,
+                &psi.msMaskBits,
+                bands.start,
+                &psi.sfbCodeBook[1][bands.clone()],
+                &psi.scaleFactors[1][bands.clone()],
+                &mut psi.gbCurrent,
+            );
+            win += 1;
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Temporal noise shaping (14496-3, 4.6.9): an all-pole filter run across the spectrum of
+//! each window, over the bands given by each filter.
+
+use super::{
+    AACDecoder, NSAMPS_LONG, NSAMPS_SHORT, NUM_SAMPLE_RATES, NWINDOWS_SHORT, WindowSequence,
+    sfb_table,
+};
+use crate::mp3_decoder::{madd_64, mulshift_32};
+
+const FBITS_LPC_COEFS: i32 = 20;
+
+/* TNS max bands (table 4.139) and max order (table 4.138), LC profile */
+const TNS_MAX_BANDS_SHORT: [u8; NUM_SAMPLE_RATES] = [9, 9, 10, 14, 14, 14, 14, 14, 14, 14, 14, 14];
+const TNS_MAX_ORDER_SHORT: usize = 7;
+const TNS_MAX_BANDS_LONG: [u8; NUM_SAMPLE_RATES] = [31, 31, 34, 40, 42, 51, 46, 46, 42, 42, 42, 39];
+const TNS_MAX_ORDER_LONG: usize = 12;
+
+/* inverse quantization tables for TNS filter coefficients, format = Q31
+ * negative (vs. spec) since we use MADD for filter kernel
+ */
+const INV_QUANT3: [u32; 16] = [
+    0x00000000, 0xc8767f65, 0x9becf22c, 0x83358feb, 0x83358feb, 0x9becf22c, 0xc8767f65, 0x00000000,
+    0x2bc750e9, 0x5246dd49, 0x6ed9eba1, 0x7e0e2e32, 0x7e0e2e32, 0x6ed9eba1, 0x5246dd49, 0x2bc750e9,
+];
+
+const INV_QUANT4: [u32; 16] = [
+    0x00000000, 0xe5632654, 0xcbf00dbe, 0xb4c373ee, 0xa0e0a15f, 0x9126145f, 0x8643c7b3, 0x80b381ac,
+    0x7f7437ad, 0x7b1d1a49, 0x7294b5f2, 0x66256db2, 0x563ba8aa, 0x4362210e, 0x2e3d2abb, 0x17851aad,
+];
+
+/***********************************************************************************************************************
+ * Function:    DecodeLPCCoefs
+ *
+ * Description: decode LPC coefficients for TNS
+ *
+ * Inputs:      resolution of coefficients (3 or 4 bits)
+ *              coefficients unpacked from bitstream, one for each tap
+ *
+ * Outputs:     LPC coefficients in Q(FBITS_LPC_COEFS), in 'a'
+ *
+ * Return:      none
+ *
+ * Notes:       assumes no guard bits in input transform coefficients
+ *              a[i] = Q(FBITS_LPC_COEFS), don't store a0 = 1.0
+ *                (so a[0] = first delay tap, etc.)
+ *              max abs(a[i]) < log2(order), so for max order = 20 a[i] < 4.4
+ *                (up to 3 bits of gain) so a[i] has at least 31 - FBITS_LPC_COEFS - 3
+ *                guard bits
+ *              to ensure no intermediate overflow in all-pole filter, set
+ *                FBITS_LPC_COEFS such that number of guard bits >= log2(max order)
+ **********************************************************************************************************************/
+pub fn decode_lpc_coefs(res: u8, filt_coef: &[i8], a: &mut [i32]) {
+    let inv_quant_tab = match res {
+        3 => &INV_QUANT3,
+        4 => &INV_QUANT4,
+        _ => return,
+    };
+
+    let mut b = [0i32; TNS_MAX_ORDER_LONG];
+    for (m, &c) in filt_coef.iter().enumerate() {
+        let t = inv_quant_tab[(c & 0x0f) as usize] as i32; /* t = Q31 */
+        for i in 0..m {
+            b[i] = a[i].wrapping_sub(mulshift_32(t, a[m - i - 1]) << 1);
+        }
+        a[..m].copy_from_slice(&b[..m]);
+        a[m] = t >> (31 - FBITS_LPC_COEFS);
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    FilterRegion
+ *
+ * Description: apply LPC filter to one region of coefficients
+ *
+ * Inputs:      transform coefficients of the region
+ *              direction flag (0 = upwards, 1 = downwards)
+ *              LPC coefficients in Q(FBITS_LPC_COEFS), one for each tap
+ *
+ * Outputs:     filtered transform coefficients
+ *
+ * Return:      guard bit mask (OR of abs value of all filtered transform coefs)
+ *
+ * Notes:       assumes no guard bits in input transform coefficients
+ *              gains 0 int bits
+ *              history buffer does not need to be preserved between regions
+ **********************************************************************************************************************/
+pub fn filter_region(audio_coef: &mut [i32], dir: u8, a: &[i32]) -> u32 {
+    let order = a.len();
+    /* init history to 0 every time */
+    let mut hist = [0i32; TNS_MAX_ORDER_LONG];
+    let mut gb_mask = 0;
+
+    let mut filter = |coef: &mut i32| {
+        /* sum64 = a0*y[n] = 1.0*y[n] */
+        let mut sum64 = ((*coef as i64) << FBITS_LPC_COEFS) as u64;
+
+        /* sum64 += (a1*y[n-1] + a2*y[n-2] + ... + a[order-1]*y[n-(order-1)]) */
+        for j in (1..order).rev() {
+            sum64 = madd_64(sum64, hist[j], a[j]);
+            hist[j] = hist[j - 1];
+        }
+        sum64 = madd_64(sum64, hist[0], a[0]);
+        let mut y = (sum64 >> FBITS_LPC_COEFS) as i32;
+
+        /* clip output (rare) */
+        let hi32 = (sum64 >> 32) as i32;
+        if (hi32 >> 31) != (hi32 >> (FBITS_LPC_COEFS - 1)) {
+            y = (hi32 >> 31) ^ 0x7fffffff;
+        }
+
+        hist[0] = y;
+        *coef = y;
+        gb_mask |= y.unsigned_abs();
+    };
+
+    if dir != 0 {
+        audio_coef.iter_mut().rev().for_each(&mut filter);
+    } else {
+        audio_coef.iter_mut().for_each(&mut filter);
+    }
+
+    gb_mask
+}
+
+/***********************************************************************************************************************
+ * Function:    TNSFilter
+ *
+ * Description: apply temporal noise shaping, if enabled
+ *
+ * Inputs:      index of current channel
+ *
+ * Outputs:     updated transform coefficients
+ *              updated minimum guard bit count for this channel
+ *
+ * Return:      none
+ *
+ * Notes:       filters with an order above the profile maximum use only the first taps; the
+ *                coefficients of the next filter still start after all of the transmitted ones
+ *              a filter whose region is empty (above maxSFB) still uses up its direction and
+ *                coefficients
+ **********************************************************************************************************************/
+pub fn tns_filter(dec: &mut AACDecoder, ch: usize) {
+    let psi = &mut dec.m_PSInfoBase;
+    let ics = psi.icsInfo[psi.ics_index(ch)];
+    let ti = &psi.tnsInfo[ch];
+
+    if ti.tnsDataPresent == 0 {
+        return;
+    }
+
+    let sfb_tab = sfb_table(psi.sampRateIdx, ics.winSequence);
+    let n_sfb = sfb_tab.len() - 1;
+    let (n_windows, win_len, max_order, tns_max_band) =
+        if ics.winSequence == WindowSequence::EightShort {
+            (
+                NWINDOWS_SHORT,
+                NSAMPS_SHORT,
+                TNS_MAX_ORDER_SHORT,
+                TNS_MAX_BANDS_SHORT[psi.sampRateIdx],
+            )
+        } else {
+            (
+                1,
+                NSAMPS_LONG,
+                TNS_MAX_ORDER_LONG,
+                TNS_MAX_BANDS_LONG[psi.sampRateIdx],
+            )
+        };
+    let tns_max_band = tns_max_band.min(ics.maxSFB) as usize;
+
+    let mut gb_mask = 0;
+    let mut filt = 0;
+    let mut filt_coef = &ti.coef[..];
+    let mut lpc = [0i32; TNS_MAX_ORDER_LONG];
+    for win in 0..n_windows {
+        let audio_coef = &mut psi.coef[ch][win * win_len..(win + 1) * win_len];
+        let mut bottom = n_sfb;
+        for _ in 0..ti.numFilt[win] {
+            let top = bottom;
+            bottom = top.saturating_sub(ti.length[filt] as usize);
+            let coded_order = ti.order[filt] as usize;
+            let order = coded_order.min(max_order);
+
+            if order != 0 {
+                let start = sfb_tab[bottom.min(tns_max_band)] as usize;
+                let end = sfb_tab[top.min(tns_max_band)] as usize;
+                if end > start {
+                    decode_lpc_coefs(ti.coefRes[win], &filt_coef[..order], &mut lpc);
+                    gb_mask |=
+                        filter_region(&mut audio_coef[start..end], ti.dir[filt], &lpc[..order]);
+                }
+            }
+            filt_coef = &filt_coef[coded_order..];
+            filt += 1;
+        }
+    }
+
+    /* update guard bit count if necessary */
+    psi.gbCurrent[ch] = psi.gbCurrent[ch].min(gb_mask.leading_zeros() as i32 - 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_filter_keeps_its_own_direction_and_coefficients() {
+        let mut dec = AACDecoder::new();
+        let psi = &mut dec.m_PSInfoBase;
+        psi.sampRateIdx = 4; /* 44.1 kHz, 49 long bands */
+        psi.icsInfo[0].winSequence = WindowSequence::OnlyLong;
+        psi.icsInfo[0].maxSFB = 20;
+        psi.gbCurrent[0] = 31;
+
+        /* filter 0 covers bands 44..49, all above maxSFB, with a coded order above the maximum;
+         * filter 1 covers bands 0..44 upwards, y[n] = x[n] - 0.43 * y[n - 1]
+         */
+        let ti = &mut psi.tnsInfo[0];
+        ti.tnsDataPresent = 1;
+        ti.numFilt[0] = 2;
+        ti.coefRes[0] = 3;
+        ti.length[..2].copy_from_slice(&[5, 44]);
+        ti.order[..2].copy_from_slice(&[14, 1]);
+        ti.dir[..2].copy_from_slice(&[1, 0]);
+        ti.coef[14] = 1;
+
+        let impulse = sfb_table(4, WindowSequence::OnlyLong)[10] as usize;
+        psi.coef[0][impulse] = 1 << 20;
+        tns_filter(&mut dec, 0);
+
+        let coef = &dec.m_PSInfoBase.coef[0];
+        assert_eq!(coef[impulse - 1], 0);
+        assert_eq!(coef[impulse], 1 << 20);
+        assert!(
+            coef[impulse + 1] < 0
+                && coef[impulse + 2] > 0
+                && coef[impulse + 2] < -coef[impulse + 1]
+        );
+    }
+}
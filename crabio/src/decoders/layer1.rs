@@ -0,0 +1,149 @@
+//! MPEG-1/2 Layer I decoding (ISO/IEC 11172-3, plus the low sampling frequencies of 13818-3).
+//!
+//! A Layer I frame is 12 time slots of 32 subband samples, each subband with its own
+//! allocation and a single scalefactor. [`MP3Decoder::decode`] unpacks the header and hands
+//! the frame to [`decode_layer1`], which requantises the samples into `m_IMDCTInfo.outBuf`
+//! and synthesises them with [`MP3Decoder::subband_blocks`].
+
+use super::{SCALEFACTORS, frame_bytes, requantise};
+use crate::{
+    mp3_decoder::{
+        GranuleIndex, MAX_NCHAN, MP3Decoder, Mp3Error, NBANDS, SAMPLES_PER_FRAME_TAB, StereoMode,
+        mp3_clear_bad_frame,
+    },
+    utils::bit_stream_cache::BitStreamInfo,
+};
+
+/// Time slots per frame, one sample of every subband each.
+const NSLOTS: usize = 12;
+/// Allocation code 11172-3 forbids (it would mean 16-bit samples).
+const ALLOC_FORBIDDEN: u8 = 15;
+
+/***********************************************************************************************************************
+ * Function:    DecodeLayer1
+ *
+ * Description: decode one Layer I frame, after the header was unpacked
+ *
+ * Inputs:      decoder with the header of this frame unpacked
+ *              buffer starting at the sync word of the frame
+ *              PCM output buffer, big enough for one frame
+ *              number of header bytes (4, or 6 with CRC)
+ *              flag indicating the input holds exactly one frame (useSize = true)
+ *
+ * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+ *                number of output samples = 384 * nChans
+ *              number of bytes of input used (the whole frame, once its size is known)
+ *
+ * Return:      number of PCM samples written, or the reason the frame was dropped
+ *
+ * Notes:       allocation code a means a + 1 bits and 2^(a + 1) - 1 steps per sample
+ **********************************************************************************************************************/
+pub fn decode_layer1(
+    dec: &mut MP3Decoder,
+    input: &[u8],
+    out: &mut [i16],
+    fh_bytes: usize,
+    use_size: bool,
+    consumed: &mut usize,
+) -> Result<usize, Mp3Error> {
+    let n_chans = dec.m_MP3DecInfo.nChans as usize;
+    let n_samps = SAMPLES_PER_FRAME_TAB[dec.m_MPEGVersion as usize]
+        [dec.m_MP3DecInfo.layer as usize - 1] as usize
+        * dec.output_chans() as usize;
+    if out.len() < n_samps {
+        return Err(Mp3Error::OutputTooSmall { needed: n_samps });
+    }
+    let out = &mut out[..n_samps];
+
+    let frame_bytes = match frame_bytes(dec, input, fh_bytes, use_size) {
+        Ok(frame_bytes) => frame_bytes,
+        Err(e) => {
+            mp3_clear_bad_frame(out);
+            return Err(e);
+        }
+    };
+    *consumed = frame_bytes;
+
+    let frame_data = &input[fh_bytes..frame_bytes];
+    let mut bsi = BitStreamInfo::from_slice(frame_data);
+    /* joint stereo: above the bound both channels share allocation and samples (intensity) */
+    let bound = if dec.m_sMode == StereoMode::Joint {
+        4 * (dec.m_FrameHeader.modeExt + 1)
+    } else {
+        NBANDS
+    };
+
+    /* bit allocation */
+    let mut allocation = [[0u8; NBANDS]; MAX_NCHAN];
+    for sb in 0..NBANDS {
+        let coded_chans = if sb < bound { n_chans } else { 1 };
+        for alloc in allocation.iter_mut().take(coded_chans) {
+            alloc[sb] = bsi.get_bits(4) as u8;
+            if alloc[sb] == ALLOC_FORBIDDEN {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidSideInfo { offset: fh_bytes });
+            }
+        }
+        if sb >= bound {
+            allocation[1][sb] = allocation[0][sb];
+        }
+    }
+
+    /* scalefactors, one per subband and channel for the whole frame */
+    let mut scalefactors = [[0u8; NBANDS]; MAX_NCHAN];
+    for sb in 0..NBANDS {
+        for ch in 0..n_chans {
+            if allocation[ch][sb] == 0 {
+                continue;
+            }
+            let sf = bsi.get_bits(6) as usize;
+            if sf >= SCALEFACTORS.len() {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidScaleFactors {
+                    granule: GranuleIndex::Granule0,
+                    channel: dec.m_MP3DecInfo.nChans.channels()[ch],
+                    offset: fh_bytes + (bsi.calc_bits_used(frame_data, 0) / 8) as usize,
+                });
+            }
+            scalefactors[ch][sb] = sf as u8;
+        }
+    }
+
+    /* samples, 12 time slots */
+    let mut m_out = [0i32; MAX_NCHAN];
+    for buf in dec.m_IMDCTInfo.outBuf.iter_mut().take(n_chans) {
+        buf[..NSLOTS].fill([0; NBANDS]);
+    }
+    for slot in 0..NSLOTS {
+        for sb in 0..NBANDS {
+            let coded_chans = if sb < bound { n_chans } else { 1 };
+            for (ch, alloc) in allocation.iter().enumerate().take(coded_chans) {
+                let alloc = alloc[sb] as u32;
+                if alloc == 0 {
+                    continue;
+                }
+                let steps = (2 << alloc) - 1;
+                let code = bsi.get_bits(alloc + 1);
+                let targets = if sb < bound { ch..ch + 1 } else { 0..n_chans };
+                for c in targets {
+                    let y = requantise(code, steps, SCALEFACTORS[scalefactors[c][sb] as usize]);
+                    dec.m_IMDCTInfo.outBuf[c][slot][sb] = y;
+                    m_out[c] |= y.abs();
+                }
+            }
+        }
+    }
+
+    for (gb, m) in dec.m_IMDCTInfo.gb.iter_mut().zip(m_out) {
+        *gb = (m.leading_zeros() as i32) - 1;
+    }
+    if dec.subband_blocks(out, NSLOTS) < 0 {
+        mp3_clear_bad_frame(out);
+        return Err(Mp3Error::InvalidSubband {
+            granule: GranuleIndex::Granule0,
+        });
+    }
+
+    dec.mp3_get_last_frame_info();
+    Ok(n_samps)
+}
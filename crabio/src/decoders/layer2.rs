@@ -0,0 +1,371 @@
+//! MPEG-1/2 Layer II decoding (ISO/IEC 11172-3, plus the low sampling frequencies of 13818-3).
+//!
+//! Layer II uses the same frame header as Layer III, so [`MP3Decoder::decode`] unpacks it as
+//! usual and hands Layer II frames to [`decode_layer2`]. The requantised subband samples are
+//! written to `m_IMDCTInfo.outBuf`, 18 time slots at a time, and run through
+//! [`MP3Decoder::subband`], the same `fdct_32`/`polyphase_*` filterbank Layer III uses.
+
+use super::{SCALEFACTORS, frame_bytes, requantise};
+use crate::{
+    mp3_decoder::{
+        BLOCK_SIZE, GranuleIndex, MAX_NCHAN, MP3Decoder, MPEGVersion, Mp3Error, NBANDS,
+        SAMPLES_PER_FRAME_TAB, StereoMode, mp3_clear_bad_frame,
+    },
+    utils::bit_stream_cache::BitStreamInfo,
+};
+
+/// Granules per frame, each holding 3 consecutive samples of every subband.
+const NGRANULES: usize = 12;
+/// Granules sharing one scalefactor (a frame has 3 scalefactor parts).
+const GRANULES_PER_PART: usize = 4;
+/// Granules decoded before the filterbank runs, to fill the 18 time slots of `outBuf`.
+const GRANULES_PER_BLOCK: usize = BLOCK_SIZE / 3;
+
+/* indexing = [quantisation class] (11172-3 Table B.4)
+ * (steps, bits per codeword, three samples grouped into one codeword)
+ */
+const QUANT_CLASSES: [(u32, u32, bool); 17] = [
+    (3, 5, true),
+    (5, 7, true),
+    (7, 3, false),
+    (9, 10, true),
+    (15, 4, false),
+    (31, 5, false),
+    (63, 6, false),
+    (127, 7, false),
+    (255, 8, false),
+    (511, 9, false),
+    (1023, 10, false),
+    (2047, 11, false),
+    (4095, 12, false),
+    (8191, 13, false),
+    (16383, 14, false),
+    (32767, 15, false),
+    (65535, 16, false),
+];
+
+/* indexing = [class row][allocation - 1]
+ * index into QUANT_CLASSES for each non-zero allocation code
+ */
+const CLASS_ROWS: [[u8; 15]; 6] = [
+    [0, 1, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 1, 2, 3, 4, 5, 16, 0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+    [0, 1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 16],
+    [0, 2, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+];
+
+/* indexing = [subband kind]
+ * (bits in the allocation code, row of CLASS_ROWS)
+ */
+const SUBBAND_KINDS: [(u32, usize); 8] = [
+    (2, 0),
+    (2, 3),
+    (3, 3),
+    (3, 1),
+    (4, 2),
+    (4, 3),
+    (4, 4),
+    (4, 5),
+];
+
+/// Bit allocation table: number of coded subbands and the kind of each one.
+struct AllocTable {
+    sblimit: usize,
+    kinds: [u8; 30],
+}
+
+const ALLOC_TABLES: [AllocTable; 5] = [
+    /* 11172-3 Table B.2a: 48 kHz, or 56 - 80 kbps per channel */
+    AllocTable {
+        sblimit: 27,
+        kinds: [
+            7, 7, 7, 6, 6, 6, 6, 6, 6, 6, 6, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 0, 0, 0, 0, 0, 0,
+            0,
+        ],
+    },
+    /* 11172-3 Table B.2b: 44.1 and 32 kHz, above 80 kbps per channel */
+    AllocTable {
+        sblimit: 30,
+        kinds: [
+            7, 7, 7, 6, 6, 6, 6, 6, 6, 6, 6, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 0, 0, 0, 0, 0, 0,
+            0,
+        ],
+    },
+    /* 11172-3 Table B.2c: 48 and 44.1 kHz, up to 48 kbps per channel */
+    AllocTable {
+        sblimit: 8,
+        kinds: [
+            5, 5, 2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0,
+        ],
+    },
+    /* 11172-3 Table B.2d: 32 kHz, up to 48 kbps per channel */
+    AllocTable {
+        sblimit: 12,
+        kinds: [
+            5, 5, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0,
+        ],
+    },
+    /* 13818-3 Table B.1: MPEG-2 (and 2.5) low sampling frequencies */
+    AllocTable {
+        sblimit: 30,
+        kinds: [
+            4, 4, 4, 4, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1,
+        ],
+    },
+];
+
+/// Picks the bit allocation table (11172-3 Table B.2, 13818-3 Table B.1) for this frame.
+fn select_alloc_table(
+    version: MPEGVersion,
+    n_chans: usize,
+    bitrate: i32,
+    samprate: i32,
+) -> &'static AllocTable {
+    if version != MPEGVersion::MPEG1 {
+        return &ALLOC_TABLES[4];
+    }
+    let per_channel = bitrate / n_chans as i32;
+    if per_channel <= 48000 {
+        if samprate == 32000 {
+            &ALLOC_TABLES[3]
+        } else {
+            &ALLOC_TABLES[2]
+        }
+    } else if per_channel <= 80000 || samprate == 48000 {
+        &ALLOC_TABLES[0]
+    } else {
+        &ALLOC_TABLES[1]
+    }
+}
+
+/// Reads the three sample codes of one subband in one granule.
+fn read_codes(bsi: &mut BitStreamInfo, (steps, bits, grouped): (u32, u32, bool)) -> [u32; 3] {
+    if grouped {
+        let mut c = bsi.get_bits(bits);
+        let mut codes = [0; 3];
+        for code in codes.iter_mut() {
+            *code = c % steps;
+            c /= steps;
+        }
+        codes
+    } else {
+        [bsi.get_bits(bits), bsi.get_bits(bits), bsi.get_bits(bits)]
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeLayer2
+ *
+ * Description: decode one Layer II frame, after the header was unpacked
+ *
+ * Inputs:      decoder with the header of this frame unpacked
+ *              buffer starting at the sync word of the frame
+ *              PCM output buffer, big enough for one frame
+ *              number of header bytes (4, or 6 with CRC)
+ *              flag indicating the input holds exactly one frame (useSize = true)
+ *
+ * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+ *                number of output samples = 1152 * nChans
+ *              number of bytes of input used (the whole frame, once its size is known)
+ *
+ * Return:      number of PCM samples written, or the reason the frame was dropped
+ *
+ * Notes:       no bit reservoir in Layer II, every frame is self-contained
+ *              ancillary data after the samples is skipped
+ **********************************************************************************************************************/
+pub fn decode_layer2(
+    dec: &mut MP3Decoder,
+    input: &[u8],
+    out: &mut [i16],
+    fh_bytes: usize,
+    use_size: bool,
+    consumed: &mut usize,
+) -> Result<usize, Mp3Error> {
+    let n_chans = dec.m_MP3DecInfo.nChans as usize;
+    let out_chans = dec.output_chans() as usize;
+    let n_samps = SAMPLES_PER_FRAME_TAB[dec.m_MPEGVersion as usize]
+        [dec.m_MP3DecInfo.layer as usize - 1] as usize
+        * out_chans;
+    if out.len() < n_samps {
+        return Err(Mp3Error::OutputTooSmall { needed: n_samps });
+    }
+    let out = &mut out[..n_samps];
+
+    let frame_bytes = match frame_bytes(dec, input, fh_bytes, use_size) {
+        Ok(frame_bytes) => frame_bytes,
+        Err(e) => {
+            mp3_clear_bad_frame(out);
+            return Err(e);
+        }
+    };
+    *consumed = frame_bytes;
+
+    let frame_data = &input[fh_bytes..frame_bytes];
+    let mut bsi = BitStreamInfo::from_slice(frame_data);
+    let table = select_alloc_table(
+        dec.m_MPEGVersion,
+        n_chans,
+        dec.m_MP3DecInfo.bitrate,
+        dec.m_MP3DecInfo.samprate,
+    );
+    let sblimit = table.sblimit;
+    /* joint stereo: above the bound both channels share allocation and samples (intensity) */
+    let bound = if dec.m_sMode == StereoMode::Joint {
+        (4 * (dec.m_FrameHeader.modeExt + 1)).min(sblimit)
+    } else {
+        sblimit
+    };
+
+    /* bit allocation */
+    let mut allocation = [[0u8; NBANDS]; MAX_NCHAN];
+    for sb in 0..sblimit {
+        let (bits, _) = SUBBAND_KINDS[table.kinds[sb] as usize];
+        if sb < bound {
+            for alloc in allocation.iter_mut().take(n_chans) {
+                alloc[sb] = bsi.get_bits(bits) as u8;
+            }
+        } else {
+            let shared = bsi.get_bits(bits) as u8;
+            allocation[0][sb] = shared;
+            allocation[1][sb] = shared;
+        }
+    }
+
+    /* scalefactor selection info */
+    let mut scfsi = [[0u8; NBANDS]; MAX_NCHAN];
+    for sb in 0..sblimit {
+        for ch in 0..n_chans {
+            if allocation[ch][sb] != 0 {
+                scfsi[ch][sb] = bsi.get_bits(2) as u8;
+            }
+        }
+    }
+
+    /* scalefactors, one per part of 4 granules, some of them shared according to scfsi */
+    let mut scalefactors = [[[0u8; 3]; NBANDS]; MAX_NCHAN];
+    for sb in 0..sblimit {
+        for ch in 0..n_chans {
+            if allocation[ch][sb] == 0 {
+                continue;
+            }
+            let sf = &mut scalefactors[ch][sb];
+            sf[0] = bsi.get_bits(6) as u8;
+            match scfsi[ch][sb] {
+                0 => {
+                    sf[1] = bsi.get_bits(6) as u8;
+                    sf[2] = bsi.get_bits(6) as u8;
+                }
+                1 => {
+                    sf[1] = sf[0];
+                    sf[2] = bsi.get_bits(6) as u8;
+                }
+                2 => {
+                    sf[1] = sf[0];
+                    sf[2] = sf[0];
+                }
+                _ => {
+                    sf[1] = bsi.get_bits(6) as u8;
+                    sf[2] = sf[1];
+                }
+            }
+            if sf.iter().any(|&s| s as usize >= SCALEFACTORS.len()) {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidScaleFactors {
+                    granule: GranuleIndex::Granule0,
+                    channel: dec.m_MP3DecInfo.nChans.channels()[ch],
+                    offset: fh_bytes + (bsi.calc_bits_used(frame_data, 0) / 8) as usize,
+                });
+            }
+        }
+    }
+
+    /* samples, 12 granules of 3 per subband, synthesised 6 granules (18 time slots) at a time */
+    let mut m_out = [0i32; MAX_NCHAN];
+    for gr in 0..NGRANULES {
+        let part = gr / GRANULES_PER_PART;
+        let slot = (gr % GRANULES_PER_BLOCK) * 3;
+        if slot == 0 {
+            for buf in dec.m_IMDCTInfo.outBuf.iter_mut().take(n_chans) {
+                *buf = [[0; NBANDS]; BLOCK_SIZE];
+            }
+            m_out = [0; MAX_NCHAN];
+        }
+        for sb in 0..sblimit {
+            let (_, row) = SUBBAND_KINDS[table.kinds[sb] as usize];
+            let coded_chans = if sb < bound { n_chans } else { 1 };
+            for (ch, alloc) in allocation.iter().enumerate().take(coded_chans) {
+                let alloc = alloc[sb] as usize;
+                if alloc == 0 {
+                    continue;
+                }
+                let class = QUANT_CLASSES[CLASS_ROWS[row][alloc - 1] as usize];
+                let codes = read_codes(&mut bsi, class);
+                let (steps, _, _) = class;
+                let targets = if sb < bound { ch..ch + 1 } else { 0..n_chans };
+                for c in targets {
+                    let sf = SCALEFACTORS[scalefactors[c][sb][part] as usize];
+                    for (s, &code) in codes.iter().enumerate() {
+                        let y = requantise(code, steps, sf);
+                        dec.m_IMDCTInfo.outBuf[c][slot + s][sb] = y;
+                        m_out[c] |= y.abs();
+                    }
+                }
+            }
+        }
+        if slot + 3 == BLOCK_SIZE {
+            for (gb, m) in dec.m_IMDCTInfo.gb.iter_mut().zip(m_out) {
+                *gb = (m.leading_zeros() as i32) - 1;
+            }
+            let block = gr / GRANULES_PER_BLOCK;
+            if dec.subband(&mut out[block * BLOCK_SIZE * NBANDS * out_chans..]) < 0 {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidSubband {
+                    granule: if block == 0 {
+                        GranuleIndex::Granule0
+                    } else {
+                        GranuleIndex::Granule1
+                    },
+                });
+            }
+        }
+    }
+
+    dec.mp3_get_last_frame_info();
+    Ok(n_samps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_alloc_table_by_rate_and_bitrate() {
+        let pick = |version, n_chans, bitrate, samprate| {
+            select_alloc_table(version, n_chans, bitrate, samprate).sblimit
+        };
+        assert_eq!(pick(MPEGVersion::MPEG1, 2, 192000, 48000), 27);
+        assert_eq!(pick(MPEGVersion::MPEG1, 2, 256000, 48000), 27);
+        assert_eq!(pick(MPEGVersion::MPEG1, 2, 256000, 44100), 30);
+        assert_eq!(pick(MPEGVersion::MPEG1, 1, 80000, 44100), 27);
+        assert_eq!(pick(MPEGVersion::MPEG1, 1, 48000, 44100), 8);
+        assert_eq!(pick(MPEGVersion::MPEG1, 2, 64000, 32000), 12);
+        // the low sampling frequency table ignores the bitrate
+        let lsf = select_alloc_table(MPEGVersion::MPEG2, 2, 256000, 22050);
+        assert_eq!((lsf.sblimit, lsf.kinds[0]), (30, 4));
+        let lsf = select_alloc_table(MPEGVersion::MPEG25, 1, 8000, 8000);
+        assert_eq!((lsf.sblimit, lsf.kinds[0]), (30, 4));
+    }
+
+    #[test]
+    fn ungroups_codewords_least_significant_first() {
+        // 9 steps, 10 bits: 2 + 9 * (7 + 9 * 4) = 389
+        let bytes = [(389u32 >> 2) as u8, ((389u32 & 3) << 6) as u8];
+        let mut bsi = BitStreamInfo::from_slice(&bytes);
+        assert_eq!(read_codes(&mut bsi, QUANT_CLASSES[3]), [2, 7, 4]);
+    }
+}
@@ -0,0 +1,115 @@
+//! Decoders for the MPEG audio layers that have no Layer III machinery: no Huffman coding,
+//! no bit reservoir, no hybrid filterbank. Layer I and II frames carry requantised subband
+//! samples straight into the synthesis filterbank of [`MP3Decoder::subband_blocks`].
+//!
+//! [`aac`] is the odd one out: a complete AAC decoder with its own state, sharing only the
+//! bitstream reader and some fixed-point helpers with the MPEG side.
+
+use crate::mp3_decoder::{LayerIndex, MP3Decoder, Mp3Error, mp3_find_free_sync};
+
+pub mod aac;
+pub mod layer1;
+pub mod layer2;
+
+/* indexing = [scalefactor index]
+ * 2^(1 - i/3), format = Q30 (index 63 is not allowed)
+ */
+pub(crate) const SCALEFACTORS: [u32; 63] = [
+    0x80000000, 0x6597fa95, 0x50a28be6, 0x40000000, 0x32cbfd4a, 0x285145f3, 0x20000000, 0x1965fea5,
+    0x1428a2fa, 0x10000000, 0x0cb2ff53, 0x0a14517d, 0x08000000, 0x06597fa9, 0x050a28be, 0x04000000,
+    0x032cbfd5, 0x0285145f, 0x02000000, 0x01965fea, 0x01428a30, 0x01000000, 0x00cb2ff5, 0x00a14518,
+    0x00800000, 0x006597fb, 0x0050a28c, 0x00400000, 0x0032cbfd, 0x00285146, 0x00200000, 0x001965ff,
+    0x001428a3, 0x00100000, 0x000cb2ff, 0x000a1451, 0x00080000, 0x00065980, 0x00050a29, 0x00040000,
+    0x00032cc0, 0x00028514, 0x00020000, 0x00019660, 0x0001428a, 0x00010000, 0x0000cb30, 0x0000a145,
+    0x00008000, 0x00006598, 0x000050a3, 0x00004000, 0x000032cc, 0x00002851, 0x00002000, 0x00001966,
+    0x00001429, 0x00001000, 0x00000cb3, 0x00000a14, 0x00000800, 0x00000659, 0x0000050a,
+];
+
+/// Requantises one sample code to Q23 (the `outBuf` format, 1.0 = full scale).
+///
+/// The ISO formula, C * (s'' + D) with the inverted MSB, works out to (2 * code + 1 - steps) / steps.
+#[inline]
+pub(crate) fn requantise(code: u32, steps: u32, scalefactor: u32) -> i32 {
+    let fraction = 2 * code as i64 + 1 - steps as i64;
+    ((fraction * scalefactor as i64 / steps as i64) >> 7) as i32
+}
+
+/***********************************************************************************************************************
+ * Function:    FrameBytes
+ *
+ * Description: size of a Layer I or II frame, header included
+ *
+ * Inputs:      decoder with the header of this frame unpacked
+ *              buffer starting at the sync word of the frame
+ *              number of header bytes (4, or 6 with CRC)
+ *              flag indicating the input holds exactly one frame (useSize = true)
+ *
+ * Outputs:     bitrate and free bitrate slots, when the first free format frame is seen
+ *
+ * Return:      frame size in bytes, or the reason the frame cannot be decoded
+ *
+ * Notes:       free format frames are measured once, by scanning for the next sync word,
+ *                as for Layer III
+ **********************************************************************************************************************/
+pub(crate) fn frame_bytes(
+    dec: &mut MP3Decoder,
+    input: &[u8],
+    fh_bytes: usize,
+    use_size: bool,
+) -> Result<usize, Mp3Error> {
+    /* Layer I counts in 4-byte slots, Layer II in bytes */
+    let slot_bytes = if dec.m_MP3DecInfo.layer == LayerIndex::Layer1 {
+        4
+    } else {
+        1
+    };
+    let pad = dec.m_FrameHeader.check_pad_bit() as usize * slot_bytes;
+    let frame_bytes = if use_size {
+        input.len()
+    } else if dec.m_MP3DecInfo.bitrate == 0 || dec.m_MP3DecInfo.freeBitrateFlag != 0 {
+        if dec.m_MP3DecInfo.freeBitrateFlag == 0 {
+            let first_header: &[u8; 4] = input[..4].try_into().unwrap();
+            let slots = mp3_find_free_sync(&input[fh_bytes..], first_header)
+                .ok_or(Mp3Error::FreeBitrateSync { offset: 0 })?;
+            /* mp3_find_free_sync leaves out one pad byte, a Layer I pad slot has 4 */
+            let slots = if pad != 0 {
+                (slots + 1).saturating_sub(pad)
+            } else {
+                slots
+            };
+            dec.m_MP3DecInfo.freeBitrateFlag = 1;
+            dec.m_MP3DecInfo.freeBitrateSlots = slots as i32;
+            let free_frame_bytes = dec.m_MP3DecInfo.freeBitrateSlots + fh_bytes as i32;
+            dec.m_MP3DecInfo.bitrate = (free_frame_bytes * dec.m_MP3DecInfo.samprate * 8)
+                / (dec.m_MP3DecInfo.nGrans as i32 * dec.m_MP3DecInfo.nGranSamps);
+        }
+        fh_bytes + dec.m_MP3DecInfo.freeBitrateSlots as usize + pad
+    } else {
+        /* samples per frame / 8 bits per byte, rounded down to whole slots */
+        let samples = dec.m_MP3DecInfo.nGrans as i32 * dec.m_MP3DecInfo.nGranSamps;
+        let slots =
+            samples / 8 * dec.m_MP3DecInfo.bitrate / dec.m_MP3DecInfo.samprate / slot_bytes as i32;
+        slots as usize * slot_bytes + pad
+    };
+    if frame_bytes < fh_bytes || input.len() < frame_bytes {
+        return Err(Mp3Error::InDataUnderflow { offset: fh_bytes });
+    }
+    Ok(frame_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requantises_symmetrically_around_zero() {
+        let full = SCALEFACTORS[0];
+        // 3 steps: -2/3, 0, 2/3 of the scalefactor (2.0), in Q23
+        assert_eq!(requantise(0, 3, full), -(4 << 23) / 3 - 1);
+        assert_eq!(requantise(1, 3, full), 0);
+        assert_eq!(requantise(2, 3, full), (4 << 23) / 3);
+        // 65535 steps: the extreme codes reach just below the scalefactor
+        assert_eq!(requantise(32767, 65535, SCALEFACTORS[3]), 0);
+        assert!(requantise(65534, 65535, SCALEFACTORS[3]) < 1 << 23);
+    }
+}
@@ -1,5 +1,11 @@
 #![no_std]
-#![feature(asm_experimental_arch)]
+#![cfg_attr(feature = "esp32s3", feature(asm_experimental_arch))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod decoders;
+pub mod metadata;
+pub mod mp3;
 pub mod mp3_decoder;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
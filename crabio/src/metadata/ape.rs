@@ -0,0 +1,351 @@
+//! APEv1 and APEv2 tags.
+//!
+//! An APE tag sits at the end of a file, in front of the ID3v1 tag if there is one. It always
+//! ends with a 32 byte footer; APEv2 tags usually start with a header of the same layout too,
+//! which lets a reader going front to back skip them. Between the two are the items, each a
+//! key and a value (UTF-8 text, binary data or a link).
+//!
+//! Tag data is not unsynchronised, so it is full of bytes a sync word search accepts:
+//! [`crate::metadata::audio_end`] says where the audio stops.
+
+use core::str;
+
+use super::ReplayGain;
+
+/// Header and footer size.
+pub const HEADER_BYTES: usize = 32;
+
+const PREAMBLE: &[u8; 8] = b"APETAGEX";
+
+/* header and footer flags */
+const HAS_HEADER: u32 = 1 << 31;
+const HAS_NO_FOOTER: u32 = 1 << 30;
+const IS_HEADER: u32 = 1 << 29;
+
+/* item flags */
+const ITEM_TYPE_MASK: u32 = 0x06;
+
+/// APE tag header or footer, they share the layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// 1000 for APEv1, 2000 for APEv2
+    pub version: u32,
+    /// bytes of items and footer, header excluded
+    pub size: u32,
+    pub item_count: u32,
+    pub flags: u32,
+}
+
+impl Header {
+    /// Parses the header or footer at the start of `data`.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let header = data.get(..HEADER_BYTES)?;
+        if &header[..8] != PREAMBLE {
+            return None;
+        }
+        let word = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+        let header = Header {
+            version: word(8),
+            size: word(12),
+            item_count: word(16),
+            flags: word(20),
+        };
+        if !matches!(header.version, 1000 | 2000) || (header.size as usize) < HEADER_BYTES {
+            return None;
+        }
+        Some(header)
+    }
+
+    /// Whether this is the header at the start of the tag rather than the footer.
+    pub fn is_header(&self) -> bool {
+        self.version == 2000 && self.flags & IS_HEADER != 0
+    }
+
+    /// Whether the tag starts with a header (APEv2 only).
+    pub fn has_header(&self) -> bool {
+        self.version == 2000 && self.flags & HAS_HEADER != 0
+    }
+
+    /// Whether the tag ends with a footer, which all but unusual APEv2 tags do.
+    pub fn has_footer(&self) -> bool {
+        self.version == 1000 || self.flags & HAS_NO_FOOTER == 0
+    }
+
+    /// Bytes in the whole tag: header, items and footer.
+    pub fn tag_size(&self) -> usize {
+        self.size as usize + if self.has_header() { HEADER_BYTES } else { 0 }
+    }
+}
+
+/// APE tag, from its header or footer and the items in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tag<'a> {
+    /// the header, or the footer if the tag was found from its end
+    pub header: Header,
+    /// items, cut short if the buffer holds part of the tag
+    items: &'a [u8],
+}
+
+impl<'a> Tag<'a> {
+    /// Parses the tag starting with a header at the start of `data`.
+    ///
+    /// As with ID3v2 tags, `data` can hold just the start of the tag; items past its end are
+    /// not iterated.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let header = Header::parse(data)?;
+        if !header.is_header() {
+            return None;
+        }
+        let footer = if header.has_footer() { HEADER_BYTES } else { 0 };
+        let end = data.len().min(HEADER_BYTES + header.size as usize - footer);
+        Some(Tag {
+            header,
+            items: &data[HEADER_BYTES..end],
+        })
+    }
+
+    /// Parses the tag whose footer ends `data`, e.g. a file with its ID3v1 tag cut off.
+    pub fn parse_end(data: &'a [u8]) -> Option<Self> {
+        let footer_start = data.len().checked_sub(HEADER_BYTES)?;
+        let header = Header::parse(&data[footer_start..])?;
+        if header.is_header() {
+            return None;
+        }
+        let items_start = footer_start.checked_sub(header.size as usize - HEADER_BYTES)?;
+        if header.has_header() {
+            items_start.checked_sub(HEADER_BYTES)?;
+        }
+        Some(Tag {
+            header,
+            items: &data[items_start..footer_start],
+        })
+    }
+
+    /// Items of the tag, in the order they are stored.
+    pub fn items(&self) -> Items<'a> {
+        Items {
+            data: self.items,
+            left: self.header.item_count,
+        }
+    }
+
+    /// First item with the key `key`; keys are compared ignoring ASCII case as the format
+    /// requires.
+    pub fn item(&self, key: &str) -> Option<Item<'a>> {
+        self.items().find(|item| item.key.eq_ignore_ascii_case(key))
+    }
+
+    /// ReplayGain values from the `REPLAYGAIN_*` items.
+    pub fn replay_gain(&self) -> ReplayGain {
+        let mut gain = ReplayGain::default();
+        for item in self.items() {
+            if let Some(value) = item.text() {
+                gain.set(item.key, value);
+            }
+        }
+        gain
+    }
+}
+
+/// Iterator over the items of a [`Tag`].
+#[derive(Debug, Clone)]
+pub struct Items<'a> {
+    data: &'a [u8],
+    left: u32,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Item<'a>;
+
+    fn next(&mut self) -> Option<Item<'a>> {
+        if self.left == 0 {
+            return None;
+        }
+        let (sizes, rest) = self.data.split_first_chunk::<8>()?;
+        let value_len = u32::from_le_bytes(sizes[..4].try_into().unwrap()) as usize;
+        let flags = u32::from_le_bytes(sizes[4..].try_into().unwrap());
+        /* key: 2 to 255 printable ASCII characters, zero terminated */
+        let key_len = rest.iter().position(|&b| b == 0)?;
+        let (key, rest) = rest.split_at(key_len);
+        if !(2..=255).contains(&key_len) || !key.iter().all(|b| (0x20..0x7f).contains(b)) {
+            self.data = &[];
+            return None;
+        }
+        let Some((value, rest)) = rest[1..].split_at_checked(value_len) else {
+            self.data = &[];
+            return None;
+        };
+        self.data = rest;
+        self.left -= 1;
+        Some(Item {
+            key: str::from_utf8(key).ok()?,
+            flags,
+            value,
+        })
+    }
+}
+
+/// Kind of value an [`Item`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    /// UTF-8 text, several values split by zero bytes
+    Text,
+    Binary,
+    /// UTF-8 link to the data, e.g. a file name or URL
+    Locator,
+    Reserved,
+}
+
+/// One item of an APE tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item<'a> {
+    /// e.g. `"Title"` or `"REPLAYGAIN_TRACK_GAIN"`
+    pub key: &'a str,
+    pub flags: u32,
+    pub value: &'a [u8],
+}
+
+impl<'a> Item<'a> {
+    pub fn kind(&self) -> ItemKind {
+        match (self.flags & ITEM_TYPE_MASK) >> 1 {
+            0 => ItemKind::Text,
+            1 => ItemKind::Binary,
+            2 => ItemKind::Locator,
+            _ => ItemKind::Reserved,
+        }
+    }
+
+    /// The value of a text item, `None` for other kinds or invalid UTF-8.
+    pub fn text(&self) -> Option<&'a str> {
+        match self.kind() {
+            ItemKind::Text => str::from_utf8(self.value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Every value of a text item, for items holding several.
+    pub fn values(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.text().into_iter().flat_map(|text| text.split('\0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn header(size: usize, count: u32, flags: u32) -> Vec<u8> {
+        let mut header = PREAMBLE.to_vec();
+        for word in [2000, size as u32, count, flags] {
+            header.extend(word.to_le_bytes());
+        }
+        header.extend([0; 8]);
+        header
+    }
+
+    fn item(key: &str, flags: u32, value: &[u8]) -> Vec<u8> {
+        let mut item = (value.len() as u32).to_le_bytes().to_vec();
+        item.extend(flags.to_le_bytes());
+        item.extend(key.as_bytes());
+        item.push(0);
+        item.extend(value);
+        item
+    }
+
+    /// A tag with a header and footer holding `items`.
+    fn tag(items: &[Vec<u8>]) -> Vec<u8> {
+        let body = items.concat();
+        let size = body.len() + HEADER_BYTES;
+        let count = items.len() as u32;
+        [
+            header(size, count, HAS_HEADER | IS_HEADER),
+            body,
+            header(size, count, HAS_HEADER),
+        ]
+        .concat()
+    }
+
+    fn items() -> [Vec<u8>; 4] {
+        [
+            item("Title", 0, "Żółw".as_bytes()),
+            item("Artist", 0, b"One\0Two"),
+            item("REPLAYGAIN_TRACK_GAIN", 0, b"-6.48 dB"),
+            item("Cover Art (Front)", 0x02, b"cover.jpg\0\xff\xd8\xff\xe0"),
+        ]
+    }
+
+    #[test]
+    fn parses_items_from_the_header() {
+        let data = tag(&items());
+        let tag = Tag::parse(&data).unwrap();
+        assert_eq!(tag.header.tag_size(), data.len());
+
+        assert_eq!(tag.items().count(), 4);
+        assert_eq!(tag.item("title").unwrap().text(), Some("Żółw"));
+        let artists: Vec<_> = tag.item("ARTIST").unwrap().values().collect();
+        assert_eq!(artists, ["One", "Two"]);
+        let cover = tag.item("Cover Art (Front)").unwrap();
+        assert_eq!(cover.kind(), ItemKind::Binary);
+        assert_eq!(cover.text(), None);
+        assert_eq!(&cover.value[10..], b"\xff\xd8\xff\xe0");
+
+        let gain = tag.replay_gain();
+        assert_eq!(gain.track_gain, Some(-6.48));
+        assert_eq!(gain.album_gain, None);
+    }
+
+    #[test]
+    fn parses_items_from_the_footer() {
+        let data = tag(&items());
+        let tag = Tag::parse_end(&data).unwrap();
+        assert!(!tag.header.is_header());
+        assert_eq!(tag.header.tag_size(), data.len());
+        assert!(tag.items().map(|item| item.key).eq([
+            "Title",
+            "Artist",
+            "REPLAYGAIN_TRACK_GAIN",
+            "Cover Art (Front)"
+        ]));
+
+        // a footer-only tag with the header flags of APEv1
+        let body = items().concat();
+        let mut footer = header(body.len() + HEADER_BYTES, 4, 0);
+        footer[8..12].copy_from_slice(&1000u32.to_le_bytes());
+        let data = [b"audio".to_vec(), body, footer].concat();
+        let tag = Tag::parse_end(&data).unwrap();
+        assert_eq!(tag.header.tag_size(), data.len() - 5);
+        assert_eq!(tag.items().count(), 4);
+        assert_eq!(Tag::parse(&data[5..]), None);
+    }
+
+    #[test]
+    fn audio_ends_before_the_tags() {
+        let audio = [0xff, 0xfb, 0x90, 0x64, 0x00];
+        let ape = tag(&items());
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(crate::metadata::id3::ID3V1_BYTES, 0);
+        for trailer in [
+            ape.clone(),
+            [ape.clone(), id3v1.clone()].concat(),
+            id3v1,
+            Vec::new(),
+        ] {
+            let data = [&audio[..], &trailer].concat();
+            assert_eq!(crate::metadata::audio_end(&data), audio.len());
+        }
+        assert_eq!(crate::metadata::tag_bytes(&ape), Some(ape.len()));
+        assert_eq!(
+            crate::metadata::tag_bytes(&ape[ape.len() - HEADER_BYTES..]),
+            None
+        );
+    }
+
+    #[test]
+    fn stops_at_truncated_items() {
+        let data = tag(&items());
+        let tag = Tag::parse(&data[..HEADER_BYTES + 40]).unwrap();
+        assert_eq!(tag.items().count(), 1);
+    }
+}
@@ -0,0 +1,956 @@
+//! ID3v2 (2.2, 2.3, 2.4) and ID3v1/1.1 tags.
+//!
+//! Nothing here allocates: frames are iterated straight from the tag bytes and text is
+//! decoded char by char. Unsynchronised data (every 0xff followed by an inserted 0x00) is
+//! undone on the fly while reading; a frame body that needs it is copied into a caller
+//! buffer only when its content is asked for as a slice.
+//!
+//! An ID3v2 tag sits in front of the audio, [`Header::tag_size`] (or [`audio_start`]) says
+//! how many bytes to skip before looking for the first sync word. An ID3v1 tag is the last
+//! 128 bytes of the file.
+
+use core::fmt;
+
+/// ID3v2 header and footer size.
+pub const HEADER_BYTES: usize = 10;
+/// ID3v1 tag size.
+pub const ID3V1_BYTES: usize = 128;
+
+/* tag header flags */
+const TAG_UNSYNCHRONISATION: u8 = 0x80;
+const TAG_EXTENDED_HEADER: u8 = 0x40;
+const TAG_FOOTER: u8 = 0x10;
+
+/* ID3v2.3 frame format flags */
+const V23_COMPRESSION: u16 = 0x0080;
+const V23_ENCRYPTION: u16 = 0x0040;
+const V23_GROUPING: u16 = 0x0020;
+/* ID3v2.4 frame format flags */
+const V24_GROUPING: u16 = 0x0040;
+const V24_COMPRESSION: u16 = 0x0008;
+const V24_ENCRYPTION: u16 = 0x0004;
+const V24_UNSYNCHRONISATION: u16 = 0x0002;
+const V24_DATA_LENGTH: u16 = 0x0001;
+
+/// 28-bit integer stored 7 bits per byte, so it never contains a sync word.
+fn syncsafe(bytes: [u8; 4]) -> Option<u32> {
+    if bytes.iter().any(|&b| b & 0x80 != 0) {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |acc, &b| (acc << 7) | b as u32))
+}
+
+/// ID3v2 tag header (`"ID3"`) or footer (`"3DI"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// 2, 3 or 4 for ID3v2.2, 2.3, 2.4
+    pub major: u8,
+    pub revision: u8,
+    pub flags: u8,
+    /// bytes after the header, footer excluded
+    pub size: u32,
+}
+
+impl Header {
+    /// Parses the ID3v2 header at the start of `data`.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        Self::parse_magic(data, b"ID3")
+    }
+
+    /// Parses an ID3v2.4 footer, the last 10 bytes of a tag appended to the end of a file.
+    pub fn parse_footer(data: &[u8]) -> Option<Self> {
+        Self::parse_magic(data, b"3DI").filter(|header| header.major == 4)
+    }
+
+    fn parse_magic(data: &[u8], magic: &[u8; 3]) -> Option<Self> {
+        let header = data.get(..HEADER_BYTES)?;
+        if &header[..3] != magic || !(2..=4).contains(&header[3]) || header[4] == 0xff {
+            return None;
+        }
+        Some(Header {
+            major: header[3],
+            revision: header[4],
+            flags: header[5],
+            size: syncsafe(header[6..10].try_into().unwrap())?,
+        })
+    }
+
+    /// Whether the whole tag body is unsynchronised (ID3v2.4: every frame is).
+    pub fn unsynchronisation(&self) -> bool {
+        self.flags & TAG_UNSYNCHRONISATION != 0
+    }
+
+    /// Whether an extended header follows the header (ID3v2.3 and 2.4 only).
+    pub fn extended_header(&self) -> bool {
+        self.major >= 3 && self.flags & TAG_EXTENDED_HEADER != 0
+    }
+
+    /// Whether the tag ends with a footer (ID3v2.4 only).
+    pub fn footer(&self) -> bool {
+        self.major == 4 && self.flags & TAG_FOOTER != 0
+    }
+
+    /// Bytes in the whole tag: header, body and footer.
+    pub fn tag_size(&self) -> usize {
+        HEADER_BYTES + self.size as usize + if self.footer() { HEADER_BYTES } else { 0 }
+    }
+}
+
+/// Offset of the first byte after the ID3v2 tags at the start of `data`, 0 if there are none.
+///
+/// Some files carry more than one tag in a row, they are all skipped. The offset can be past
+/// the end of `data` if it holds only part of a tag.
+pub fn audio_start(data: &[u8]) -> usize {
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..).and_then(Header::parse) {
+        offset += header.tag_size();
+    }
+    offset
+}
+
+/// Reads bytes, dropping the 0x00 unsynchronisation put after every 0xff.
+#[derive(Debug, Clone)]
+struct Reader<'a> {
+    raw: &'a [u8],
+    unsync: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&b, rest) = self.raw.split_first()?;
+        self.raw = match rest {
+            [0x00, rest @ ..] if self.unsync && b == 0xff => rest,
+            _ => rest,
+        };
+        Some(b)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let mut bytes = [0; N];
+        for b in &mut bytes {
+            *b = self.byte()?;
+        }
+        Some(bytes)
+    }
+
+    /// Splits off the raw bytes holding the next `n` bytes of data.
+    fn take_raw(&mut self, n: usize) -> Option<&'a [u8]> {
+        if !self.unsync {
+            let (taken, rest) = self.raw.split_at_checked(n)?;
+            self.raw = rest;
+            return Some(taken);
+        }
+        let start = self.raw;
+        for _ in 0..n {
+            self.byte()?;
+        }
+        Some(&start[..start.len() - self.raw.len()])
+    }
+}
+
+impl Iterator for Reader<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.byte()
+    }
+}
+
+/// ID3v2 tag at the start of a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tag<'a> {
+    pub header: Header,
+    /// frames and padding as stored, cut short if the buffer holds part of the tag
+    frames: &'a [u8],
+}
+
+impl<'a> Tag<'a> {
+    /// Parses the tag at the start of `data`.
+    ///
+    /// `data` needs to hold the header (and extended header) only: frames past its end are
+    /// not iterated, so the start of a large tag is enough for the text frames in front of a
+    /// picture. Returns `None` if `data` does not start with an ID3v2 tag, or uses ID3v2.2
+    /// compression, which was never specified.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let header = Header::parse(data)?;
+        if header.major == 2 && header.flags & TAG_EXTENDED_HEADER != 0 {
+            return None;
+        }
+        let end = data.len().min(HEADER_BYTES + header.size as usize);
+        let mut body = data.get(HEADER_BYTES..end)?;
+        if header.extended_header() {
+            let mut reader = Reader {
+                raw: body,
+                unsync: header.unsynchronisation() && header.major == 3,
+            };
+            let size = reader.array::<4>()?;
+            /* ID3v2.3: size of the rest of the extended header; ID3v2.4: syncsafe, all of it */
+            let rest = match header.major {
+                3 => u32::from_be_bytes(size) as usize,
+                _ => (syncsafe(size)? as usize).checked_sub(4)?,
+            };
+            reader.take_raw(rest)?;
+            body = reader.raw;
+        }
+        Some(Tag {
+            header,
+            frames: body,
+        })
+    }
+
+    /// Frames of the tag, in the order they are stored.
+    pub fn frames(&self) -> Frames<'a> {
+        Frames {
+            major: self.header.major,
+            tag_unsync: self.header.unsynchronisation(),
+            reader: Reader {
+                raw: self.frames,
+                /* before ID3v2.4 unsynchronisation covers the frame headers too */
+                unsync: self.header.unsynchronisation() && self.header.major < 4,
+            },
+        }
+    }
+
+    /// First frame with the id `id`, e.g. `"TIT2"`.
+    pub fn frame(&self, id: &str) -> Option<Frame<'a>> {
+        self.frames().find(|frame| frame.id == id)
+    }
+}
+
+/// Iterator over the frames of a [`Tag`].
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    major: u8,
+    tag_unsync: bool,
+    reader: Reader<'a>,
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Frame<'a>> {
+        let id_len = if self.major == 2 { 3 } else { 4 };
+        let id_start = self.reader.raw;
+        let (size, flags) = if self.major == 2 {
+            let [_, _, _, s0, s1, s2] = self.reader.array::<6>()?;
+            (u32::from_be_bytes([0, s0, s1, s2]), 0)
+        } else {
+            let [_, _, _, _, s0, s1, s2, s3, f0, f1] = self.reader.array::<10>()?;
+            let size = [s0, s1, s2, s3];
+            let size = if self.major == 4 {
+                syncsafe(size)?
+            } else {
+                u32::from_be_bytes(size)
+            };
+            (size, u16::from_be_bytes([f0, f1]))
+        };
+        /* padding, or garbage: no more frames either way */
+        let id = id_start.get(..id_len)?;
+        if !id
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        {
+            return None;
+        }
+        let id = core::str::from_utf8(id).ok()?;
+
+        let raw = match self.reader.take_raw(size as usize) {
+            Some(raw) => raw,
+            None => {
+                /* truncated, hand out what there is, the content will not parse */
+                self.reader.raw = &[];
+                return None;
+            }
+        };
+        let unsync = if self.major == 4 {
+            self.tag_unsync || flags & V24_UNSYNCHRONISATION != 0
+        } else {
+            self.reader.unsync
+        };
+        let mut body = Reader { raw, unsync };
+
+        /* bytes added in front of the data by the format flags */
+        let (compressed, encrypted) = match self.major {
+            3 => {
+                if flags & V23_COMPRESSION != 0 {
+                    body.array::<4>()?;
+                }
+                if flags & V23_ENCRYPTION != 0 {
+                    body.byte()?;
+                }
+                if flags & V23_GROUPING != 0 {
+                    body.byte()?;
+                }
+                (flags & V23_COMPRESSION != 0, flags & V23_ENCRYPTION != 0)
+            }
+            4 => {
+                if flags & V24_GROUPING != 0 {
+                    body.byte()?;
+                }
+                if flags & V24_ENCRYPTION != 0 {
+                    body.byte()?;
+                }
+                if flags & V24_DATA_LENGTH != 0 {
+                    body.array::<4>()?;
+                }
+                (flags & V24_COMPRESSION != 0, flags & V24_ENCRYPTION != 0)
+            }
+            _ => (false, false),
+        };
+
+        Some(Frame {
+            id,
+            flags,
+            compressed,
+            encrypted,
+            body: Body {
+                raw: body.raw,
+                unsync,
+            },
+            major: self.major,
+        })
+    }
+}
+
+/// One frame of an ID3v2 tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// frame id, 3 characters in ID3v2.2, 4 after
+    pub id: &'a str,
+    /// status and format flags (none in ID3v2.2)
+    pub flags: u16,
+    /// zlib compressed, left to the caller
+    pub compressed: bool,
+    /// encrypted, left to the caller
+    pub encrypted: bool,
+    body: Body<'a>,
+    major: u8,
+}
+
+impl<'a> Frame<'a> {
+    /// Frame data, after the bytes the format flags add in front of it.
+    pub fn body(&self) -> Body<'a> {
+        self.body
+    }
+
+    /// Parses the frame data. Unsynchronised data is copied into `scratch` first, which
+    /// has to be as long as [`Body::len`]; other frames need no scratch space.
+    ///
+    /// Returns `None` for compressed or encrypted frames, if `scratch` is too short, or if
+    /// the data does not parse.
+    pub fn content<'b>(&self, scratch: &'b mut [u8]) -> Option<Content<'b>>
+    where
+        'a: 'b,
+    {
+        if self.compressed || self.encrypted {
+            return None;
+        }
+        let data = match self.body.as_slice() {
+            Some(data) => data,
+            None => self.body.copy_to(scratch)?,
+        };
+        Content::parse(self.id, self.major, data)
+    }
+}
+
+/// Data of a frame, as stored in the tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Body<'a> {
+    raw: &'a [u8],
+    unsync: bool,
+}
+
+impl<'a> Body<'a> {
+    /// Bytes of data, unsynchronisation undone.
+    pub fn len(&self) -> usize {
+        self.bytes().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// The data, unsynchronisation undone.
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        Reader {
+            raw: self.raw,
+            unsync: self.unsync,
+        }
+    }
+
+    /// The data as stored, if there is no unsynchronisation to undo.
+    pub fn as_slice(&self) -> Option<&'a [u8]> {
+        if self.unsync && self.raw.windows(2).any(|w| w == [0xff, 0x00]) {
+            return None;
+        }
+        Some(self.raw)
+    }
+
+    /// Copies the data, unsynchronisation undone, to the start of `buf`; `None` if it does
+    /// not fit.
+    pub fn copy_to<'b>(&self, buf: &'b mut [u8]) -> Option<&'b [u8]> {
+        let mut n = 0;
+        for b in self.bytes() {
+            *buf.get_mut(n)? = b;
+            n += 1;
+        }
+        Some(&buf[..n])
+    }
+}
+
+/// Parsed content of the frames this module knows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Content<'a> {
+    /// text information frame (`T***` but `TXXX`), possibly several values
+    Text(Text<'a>),
+    /// user defined text (`TXXX`)
+    UserText {
+        description: Text<'a>,
+        value: Text<'a>,
+    },
+    /// comment (`COMM`)
+    Comment {
+        /// ISO-639-2 code, e.g. `*b"eng"`
+        language: [u8; 3],
+        description: Text<'a>,
+        text: Text<'a>,
+    },
+    /// attached picture (`APIC`)
+    Picture {
+        /// MIME type, or in ID3v2.2 the image format (`"JPG"`, `"PNG"`)
+        mime: &'a str,
+        /// 3 = front cover, see the ID3v2 specification for the others
+        picture_type: u8,
+        description: Text<'a>,
+        data: &'a [u8],
+    },
+    /// any other frame, data unparsed
+    Other(&'a [u8]),
+}
+
+impl<'a> Content<'a> {
+    fn parse(id: &str, major: u8, data: &'a [u8]) -> Option<Self> {
+        let v22 = major == 2;
+        let (&encoding, rest) = match data.split_first() {
+            Some(split) => split,
+            None => return Some(Content::Other(data)),
+        };
+        let encoding = Encoding::from_byte(encoding);
+        Some(match id {
+            "TXXX" | "TXX" => {
+                let encoding = encoding?;
+                let (description, value) = encoding.split(rest);
+                Content::UserText {
+                    description: Text::new(encoding, description),
+                    value: Text::new(encoding, value),
+                }
+            }
+            _ if id.starts_with('T') => Content::Text(Text::new(encoding?, rest)),
+            "COMM" | "COM" => {
+                let encoding = encoding?;
+                let (language, rest) = rest.split_at_checked(3)?;
+                let (description, text) = encoding.split(rest);
+                Content::Comment {
+                    language: language.try_into().unwrap(),
+                    description: Text::new(encoding, description),
+                    text: Text::new(encoding, text),
+                }
+            }
+            "APIC" | "PIC" => {
+                let encoding = encoding?;
+                let (mime, rest) = if v22 {
+                    rest.split_at_checked(3)?
+                } else {
+                    Encoding::Latin1.split(rest)
+                };
+                let (&picture_type, rest) = rest.split_first()?;
+                let (description, data) = encoding.split(rest);
+                Content::Picture {
+                    mime: core::str::from_utf8(mime).ok()?,
+                    picture_type,
+                    description: Text::new(encoding, description),
+                    data,
+                }
+            }
+            _ => Content::Other(data),
+        })
+    }
+}
+
+/// Text encoding of an ID3 text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// ISO-8859-1
+    Latin1,
+    /// UTF-16 with byte order mark (big-endian without one)
+    Utf16,
+    /// UTF-16 big-endian, ID3v2.4
+    Utf16Be,
+    /// UTF-8, ID3v2.4
+    Utf8,
+}
+
+impl Encoding {
+    fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0 => Some(Encoding::Latin1),
+            1 => Some(Encoding::Utf16),
+            2 => Some(Encoding::Utf16Be),
+            3 => Some(Encoding::Utf8),
+            _ => None,
+        }
+    }
+
+    /// Bytes per character of the string terminator.
+    const fn unit(self) -> usize {
+        match self {
+            Encoding::Utf16 | Encoding::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    /// Splits `bytes` at the first terminator, which is dropped.
+    fn split(self, bytes: &[u8]) -> (&[u8], &[u8]) {
+        let unit = self.unit();
+        match bytes
+            .chunks(unit)
+            .position(|c| c.len() == unit && c.iter().all(|&b| b == 0))
+        {
+            Some(i) => (&bytes[..i * unit], &bytes[(i + 1) * unit..]),
+            None => (bytes, &[]),
+        }
+    }
+}
+
+/// Encoded text of a frame or an ID3v1 field, decoded as it is read.
+///
+/// Text information frames can hold several values, split by a terminator; [`Text::chars`]
+/// and the [`Display`](fmt::Display) implementation give the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text<'a> {
+    pub encoding: Encoding,
+    /// encoded bytes, trailing terminators removed
+    pub bytes: &'a [u8],
+}
+
+impl<'a> Text<'a> {
+    pub fn new(encoding: Encoding, bytes: &'a [u8]) -> Self {
+        let unit = encoding.unit();
+        let mut bytes = bytes;
+        while bytes.len() >= unit && bytes[bytes.len() - unit..].iter().all(|&b| b == 0) {
+            bytes = &bytes[..bytes.len() - unit];
+        }
+        Text { encoding, bytes }
+    }
+
+    /// Whether the (first) value is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chars().next().is_none()
+    }
+
+    /// Characters of the first value; invalid sequences come out as U+FFFD.
+    pub fn chars(&self) -> Chars<'a> {
+        self.values()
+            .next()
+            .unwrap_or(Text::new(self.encoding, &[]))
+            .chars_of_all()
+    }
+
+    /// Every value, for text frames holding several.
+    pub fn values(&self) -> impl Iterator<Item = Text<'a>> + 'a {
+        let encoding = self.encoding;
+        let mut rest = Some(self.bytes);
+        core::iter::from_fn(move || {
+            let (value, tail) = encoding.split(rest?);
+            rest = if tail.is_empty() { None } else { Some(tail) };
+            Some(Text {
+                encoding,
+                bytes: value,
+            })
+        })
+    }
+
+    fn chars_of_all(&self) -> Chars<'a> {
+        let (encoding, bytes) = match (self.encoding, self.bytes) {
+            (Encoding::Utf16, [0xff, 0xfe, rest @ ..]) => (Utf::Le, rest),
+            (Encoding::Utf16, [0xfe, 0xff, rest @ ..]) => (Utf::Be, rest),
+            (Encoding::Utf16 | Encoding::Utf16Be, bytes) => (Utf::Be, bytes),
+            (Encoding::Latin1, bytes) => (Utf::Latin1, bytes),
+            (Encoding::Utf8, bytes) => (Utf::Utf8, bytes),
+        };
+        Chars { encoding, bytes }
+    }
+}
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+        self.chars().try_for_each(|c| f.write_char(c))
+    }
+}
+
+/// Byte order resolved from the byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Utf {
+    Latin1,
+    Le,
+    Be,
+    Utf8,
+}
+
+/// Iterator over the characters of a [`Text`].
+#[derive(Debug, Clone)]
+pub struct Chars<'a> {
+    encoding: Utf,
+    bytes: &'a [u8],
+}
+
+impl Chars<'_> {
+    fn unit(&mut self) -> Option<u16> {
+        let (unit, rest) = self.bytes.split_first_chunk::<2>()?;
+        self.bytes = rest;
+        Some(match self.encoding {
+            Utf::Le => u16::from_le_bytes(*unit),
+            _ => u16::from_be_bytes(*unit),
+        })
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self.encoding {
+            Utf::Latin1 => {
+                let (&b, rest) = self.bytes.split_first()?;
+                self.bytes = rest;
+                Some(b as char)
+            }
+            Utf::Utf8 => {
+                let chunk = self.bytes.utf8_chunks().next()?;
+                match chunk.valid().chars().next() {
+                    Some(c) => {
+                        self.bytes = &self.bytes[c.len_utf8()..];
+                        Some(c)
+                    }
+                    None => {
+                        self.bytes = &self.bytes[chunk.invalid().len()..];
+                        Some(char::REPLACEMENT_CHARACTER)
+                    }
+                }
+            }
+            Utf::Le | Utf::Be => {
+                let first = self.unit()?;
+                if !(0xd800..0xdc00).contains(&first) {
+                    return Some(
+                        char::from_u32(first as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+                    );
+                }
+                /* high surrogate, a low one has to follow */
+                let mut peek = self.clone();
+                match peek.unit() {
+                    Some(second @ 0xdc00..0xe000) => {
+                        *self = peek;
+                        let c =
+                            0x10000 + (((first as u32) & 0x3ff) << 10) + (second as u32 & 0x3ff);
+                        Some(char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+                    }
+                    _ => Some(char::REPLACEMENT_CHARACTER),
+                }
+            }
+        }
+    }
+}
+
+/// ID3v1 or ID3v1.1 tag, the last 128 bytes of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Id3v1<'a> {
+    pub title: Text<'a>,
+    pub artist: Text<'a>,
+    pub album: Text<'a>,
+    pub year: Text<'a>,
+    pub comment: Text<'a>,
+    /// ID3v1.1 track number
+    pub track: Option<u8>,
+    /// index into the ID3v1 genre list, 255 = none
+    pub genre: u8,
+}
+
+impl<'a> Id3v1<'a> {
+    /// Parses the ID3v1 tag in the last 128 bytes of `data`, if they hold one.
+    pub fn parse(data: &'a [u8]) -> Option<Self> {
+        let tag = data.get(data.len().checked_sub(ID3V1_BYTES)?..)?;
+        if &tag[..3] != b"TAG" {
+            return None;
+        }
+        /* fixed-size Latin-1 fields, padded with zeros or spaces */
+        let field = |range: core::ops::Range<usize>| {
+            let bytes = &tag[range];
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            Text::new(Encoding::Latin1, bytes[..end].trim_ascii_end())
+        };
+        /* ID3v1.1: a zero byte before the last comment byte makes that the track number */
+        let track = (tag[125] == 0 && tag[126] != 0).then_some(tag[126]);
+        Some(Id3v1 {
+            title: field(3..33),
+            artist: field(33..63),
+            album: field(63..93),
+            year: field(93..97),
+            comment: field(97..if track.is_some() { 125 } else { 127 }),
+            track,
+            genre: tag[127],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::{string::ToString, vec, vec::Vec};
+
+    use super::*;
+
+    fn syncsafe_bytes(n: usize) -> [u8; 4] {
+        [
+            (n >> 21) as u8 & 0x7f,
+            (n >> 14) as u8 & 0x7f,
+            (n >> 7) as u8 & 0x7f,
+            n as u8 & 0x7f,
+        ]
+    }
+
+    fn tag(major: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+        let mut tag = vec![b'I', b'D', b'3', major, 0, flags];
+        tag.extend_from_slice(&syncsafe_bytes(body.len()));
+        tag.extend_from_slice(body);
+        tag
+    }
+
+    fn frame_v23(id: &[u8; 4], flags: u16, data: &[u8]) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&flags.to_be_bytes());
+        frame.extend_from_slice(data);
+        frame
+    }
+
+    fn frame_v24(id: &[u8; 4], flags: u16, data: &[u8]) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&syncsafe_bytes(data.len()));
+        frame.extend_from_slice(&flags.to_be_bytes());
+        frame.extend_from_slice(data);
+        frame
+    }
+
+    fn unsynchronise(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (i, &b) in data.iter().enumerate() {
+            out.push(b);
+            if b == 0xff
+                && data
+                    .get(i + 1)
+                    .is_none_or(|&next| next == 0 || next & 0xe0 == 0xe0)
+            {
+                out.push(0);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn parses_v23_frames() {
+        let mut body = frame_v23(b"TIT2", 0, b"\x00Caf\xe9\x00");
+        // UTF-16 little-endian with BOM, "Ab" and a surrogate pair (U+1F3B5)
+        body.extend(frame_v23(
+            b"TPE1",
+            0,
+            b"\x01\xff\xfeA\x00b\x00\x3c\xd8\xb5\xdf",
+        ));
+        body.extend(frame_v23(
+            b"TXXX",
+            0,
+            b"\x00REPLAYGAIN_TRACK_GAIN\x00-6.5 dB",
+        ));
+        body.extend(frame_v23(b"COMM", 0, b"\x00engshort\x00long comment"));
+        body.extend(frame_v23(
+            b"APIC",
+            0,
+            b"\x00image/png\x00\x03cover\x00\x89PNG",
+        ));
+        body.extend([0; 32]);
+        let data = tag(3, 0, &body);
+        let tag = Tag::parse(&data).unwrap();
+        assert_eq!(tag.header.tag_size(), data.len());
+        assert_eq!(audio_start(&data), data.len());
+
+        let mut scratch = [0u8; 64];
+        let ids: Vec<_> = tag.frames().map(|f| f.id).collect();
+        assert_eq!(ids, ["TIT2", "TPE1", "TXXX", "COMM", "APIC"]);
+        let text = |id| match tag.frame(id).unwrap().content(&mut [0; 0]).unwrap() {
+            Content::Text(text) => text.to_string(),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(text("TIT2"), "Café");
+        assert_eq!(text("TPE1"), "Ab\u{1f3b5}");
+        match tag.frame("TXXX").unwrap().content(&mut scratch).unwrap() {
+            Content::UserText { description, value } => {
+                assert_eq!(description.to_string(), "REPLAYGAIN_TRACK_GAIN");
+                assert_eq!(value.to_string(), "-6.5 dB");
+            }
+            other => panic!("{other:?}"),
+        }
+        match tag.frame("COMM").unwrap().content(&mut scratch).unwrap() {
+            Content::Comment {
+                language,
+                description,
+                text,
+            } => {
+                assert_eq!(&language, b"eng");
+                assert_eq!(description.to_string(), "short");
+                assert_eq!(text.to_string(), "long comment");
+            }
+            other => panic!("{other:?}"),
+        }
+        match tag.frame("APIC").unwrap().content(&mut scratch).unwrap() {
+            Content::Picture {
+                mime,
+                picture_type,
+                description,
+                data,
+            } => {
+                assert_eq!((mime, picture_type), ("image/png", 3));
+                assert_eq!(description.to_string(), "cover");
+                assert_eq!(data, b"\x89PNG");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn undoes_tag_unsynchronisation() {
+        // v2.3: the whole body is unsynchronised, frame sizes count the original bytes
+        let mut body = frame_v23(
+            b"APIC",
+            0,
+            b"\x00image/jpeg\x00\x03\x00\xff\xd8\xff\xe0\xff",
+        );
+        body.extend(frame_v23(b"TALB", 0, b"\x00Album"));
+        // an extended header in front: 6 bytes of flags and padding size
+        let mut ext = 6u32.to_be_bytes().to_vec();
+        ext.extend([0; 6]);
+        ext.extend(body);
+        let data = tag(
+            3,
+            TAG_UNSYNCHRONISATION | TAG_EXTENDED_HEADER,
+            &unsynchronise(&ext),
+        );
+        let tag = Tag::parse(&data).unwrap();
+
+        let apic = tag.frame("APIC").unwrap();
+        assert_eq!(apic.body().len(), 19);
+        assert_eq!(apic.body().as_slice(), None);
+        assert_eq!(apic.content(&mut [0; 8]), None);
+        let mut scratch = [0u8; 19];
+        match apic.content(&mut scratch).unwrap() {
+            Content::Picture { mime, data, .. } => {
+                assert_eq!(mime, "image/jpeg");
+                assert_eq!(data, b"\xff\xd8\xff\xe0\xff");
+            }
+            other => panic!("{other:?}"),
+        }
+        let album = tag.frame("TALB").unwrap();
+        assert_eq!(
+            album.content(&mut []).unwrap(),
+            Content::Text(Text::new(Encoding::Latin1, b"Album"))
+        );
+    }
+
+    #[test]
+    fn parses_v24_frames() {
+        // UTF-8 with two values, then a frame with grouping, unsynchronisation and data length
+        let mut body = frame_v24(b"TPE1", 0, b"\x03Bj\xc3\xb6rk\x00Guest\x00");
+        let data = b"\x00\xff\xe0";
+        let mut flagged = vec![0x42];
+        flagged.extend(syncsafe_bytes(data.len()));
+        flagged.extend(unsynchronise(data));
+        body.extend(frame_v24(
+            b"PRIV",
+            V24_GROUPING | V24_UNSYNCHRONISATION | V24_DATA_LENGTH,
+            &flagged,
+        ));
+        // extended header of 6 bytes, in total
+        let mut ext = syncsafe_bytes(6).to_vec();
+        ext.extend([1, 0]);
+        ext.extend(body);
+        let mut data = tag(4, TAG_EXTENDED_HEADER | TAG_FOOTER, &ext);
+        let footer: Vec<u8> = [b"3DI".as_slice(), &data[3..10]].concat();
+        data.extend(&footer);
+        data.extend([0xff, 0xfb]);
+
+        let tag = Tag::parse(&data).unwrap();
+        assert!(tag.header.footer());
+        assert_eq!(tag.header.tag_size(), data.len() - 2);
+        assert_eq!(audio_start(&data), data.len() - 2);
+        assert_eq!(Header::parse_footer(&footer), Some(tag.header));
+
+        let artist = match tag.frame("TPE1").unwrap().content(&mut []).unwrap() {
+            Content::Text(text) => text,
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(artist.to_string(), "Björk");
+        let values: Vec<_> = artist.values().map(|v| v.to_string()).collect();
+        assert_eq!(values, ["Björk", "Guest"]);
+
+        let private = tag.frame("PRIV").unwrap();
+        assert!(private.body().bytes().eq([0x00, 0xff, 0xe0]));
+    }
+
+    #[test]
+    fn parses_v22_frames_and_stops_at_truncation() {
+        let mut body = b"TT2\x00\x00\x06\x00Title".to_vec();
+        body.extend(b"PIC\x00\x00\x0a\x00JPG\x03\x00\xff\xd8\xff\xe0");
+        body.extend(b"COM\x00\x00\x20\x00eng");
+        let data = tag(2, 0, &body);
+        let tag = Tag::parse(&data[..data.len() - 4]).unwrap();
+        let mut frames = tag.frames();
+        let title = frames.next().unwrap();
+        assert_eq!(
+            title.content(&mut []),
+            Some(Content::Text(Text::new(Encoding::Latin1, b"Title")))
+        );
+        match frames.next().unwrap().content(&mut []).unwrap() {
+            Content::Picture { mime, data, .. } => {
+                assert_eq!((mime, data), ("JPG", b"\xff\xd8\xff\xe0".as_slice()))
+            }
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(frames.next(), None);
+    }
+
+    #[test]
+    fn parses_id3v1() {
+        let mut data = vec![0xffu8; 300];
+        let mut tag = b"TAG".to_vec();
+        for (field, len) in [
+            (&b"Title"[..], 30),
+            (b"Artist", 30),
+            (b"Album", 30),
+            (b"1999", 4),
+        ] {
+            tag.extend(field);
+            tag.extend(vec![b' '; len - field.len()]);
+        }
+        tag.extend(b"Comment");
+        tag.extend([0; 21]);
+        tag.extend([0, 7, 17]);
+        data.extend(&tag);
+
+        let v1 = Id3v1::parse(&data).unwrap();
+        assert_eq!(v1.title.to_string(), "Title");
+        assert_eq!(v1.artist.to_string(), "Artist");
+        assert_eq!(v1.year.to_string(), "1999");
+        assert_eq!(v1.comment.to_string(), "Comment");
+        assert_eq!((v1.track, v1.genre), (Some(7), 17));
+        assert_eq!(Id3v1::parse(&data[..data.len() - 1]), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Metadata tags found around MPEG audio streams.
+//!
+//! ID3v2 tags come before the audio, APE and ID3v1 tags after it. None of them is made of
+//! frames, but all of them can hold bytes that look like a sync word, so a reader has to skip
+//! them: [`id3::audio_start`] and [`audio_end`] bound the audio of a whole file, and
+//! [`tag_bytes`] recognises a tag where a frame was expected.
+
+pub mod ape;
+pub mod id3;
+
+/// ReplayGain values of a track, from tag items such as `REPLAYGAIN_TRACK_GAIN`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    /// dB
+    pub track_gain: Option<f32>,
+    /// linear, 1.0 = full scale
+    pub track_peak: Option<f32>,
+    /// dB
+    pub album_gain: Option<f32>,
+    /// linear, 1.0 = full scale
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGain {
+    /// Takes the value of the item `key` if it is a ReplayGain one, e.g. `"-6.48 dB"` for
+    /// `REPLAYGAIN_TRACK_GAIN`; returns whether it was.
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let field = if key.eq_ignore_ascii_case("REPLAYGAIN_TRACK_GAIN") {
+            &mut self.track_gain
+        } else if key.eq_ignore_ascii_case("REPLAYGAIN_TRACK_PEAK") {
+            &mut self.track_peak
+        } else if key.eq_ignore_ascii_case("REPLAYGAIN_ALBUM_GAIN") {
+            &mut self.album_gain
+        } else if key.eq_ignore_ascii_case("REPLAYGAIN_ALBUM_PEAK") {
+            &mut self.album_peak
+        } else {
+            return false;
+        };
+        let value = value.trim();
+        let value = match value.len().checked_sub(2) {
+            Some(unit)
+                if value.is_char_boundary(unit) && value[unit..].eq_ignore_ascii_case("dB") =>
+            {
+                value[..unit].trim_end()
+            }
+            _ => value,
+        };
+        *field = value.parse().ok();
+        true
+    }
+}
+
+/// Offset of the end of the audio in a whole file `data`: the APE and ID3v1 tags at its end,
+/// if any, are left out.
+pub fn audio_end(data: &[u8]) -> usize {
+    let mut end = data.len();
+    if id3::Id3v1::parse(data).is_some() {
+        end -= id3::ID3V1_BYTES;
+    }
+    if let Some(tag) = ape::Tag::parse_end(&data[..end]) {
+        end -= tag.header.tag_size();
+    }
+    end
+}
+
+/// Size of the tag starting at the start of `data`, where a frame would be: an ID3v2 tag, an
+/// APEv2 tag with a header, or an ID3v1 tag. The size can be past the end of `data`.
+///
+/// APE tags without a header cannot be recognised from their start, see [`audio_end`].
+pub fn tag_bytes(data: &[u8]) -> Option<usize> {
+    if let Some(header) = id3::Header::parse(data) {
+        return Some(header.tag_size());
+    }
+    if let Some(header) = ape::Header::parse(data).filter(|header| header.is_header()) {
+        return Some(header.tag_size());
+    }
+    data.starts_with(b"TAG").then_some(id3::ID3V1_BYTES)
+}
@@ -0,0 +1,399 @@
+//! Error concealment: something to play in place of a frame that could not be decoded.
+//!
+//! The decoder outputs a lost frame as silence, which is a hard cut into and out of a gap.
+//! [`Concealer`] keeps what it needs of the last good frame and fills the gap from it
+//! instead, with one of the [`ConcealStrategy`] options. Either way the lost frame is run
+//! through the decoder's IMDCT overlap and synthesis filterbank, so their state stays that of
+//! a continuous stream and the next good frame plays without a transient.
+
+use super::imdct::{imdct, imdct_antialiased};
+use crate::mp3_decoder::{
+    BLOCK_SIZE, DecodedFrame, GranuleIndex, LayerIndex, MAX_NCHAN, MAX_NGRAN, MAX_NSAMP,
+    MP3Decoder, MP3FrameInfo, Mp3Error, NBANDS, SAMPLES_PER_FRAME_TAB, SideInfoSub,
+};
+
+/// Samples per channel over which concealed audio is crossfaded with decoded audio.
+pub const CROSSFADE_SAMPLES: usize = 288;
+
+/// Unity gain in Q15.
+const GAIN_ONE: i32 = 1 << 15;
+/// Spectral repetition attenuation per granule, -3 dB in Q15.
+const SPECTRAL_GAIN_STEP: i32 = 23170;
+
+/// What to play in place of a lost frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcealStrategy {
+    /// Repeat the last good frame, 6 dB quieter for every frame lost in a row.
+    Repeat,
+    /// Repeat the last good frame once, fading out over it, then silence; the next good frame
+    /// fades in.
+    FadeOut,
+    /// Repeat the spectrum of the last good granule, 3 dB quieter for every granule, through
+    /// IMDCT and filterbank (Layer III, others fall back to [`ConcealStrategy::Repeat`]). The
+    /// lines get random signs, so repeats of a granule do not add up into a buzz.
+    SpectralRepeat,
+}
+
+/// Result of [`Concealer::decode_frame`].
+#[derive(Debug, Clone, Copy)]
+pub struct ConcealedFrame {
+    pub frame: DecodedFrame,
+    /// why the frame was concealed, `None` if it was decoded
+    pub error: Option<Mp3Error>,
+}
+
+/// Spectrum of the last granule of a good Layer III frame, after stereo processing and
+/// antialiasing, and the side info to transform it again with.
+#[derive(Debug, Clone, Copy)]
+struct Spectrum {
+    coefs: [[i32; MAX_NSAMP]; MAX_NCHAN],
+    non_zero_bound: [i32; MAX_NCHAN],
+    gb: [i32; MAX_NCHAN],
+    side_info: [SideInfoSub; MAX_NCHAN],
+}
+
+/// Decodes frames, concealing the ones that fail to decode.
+#[derive(Debug, Clone)]
+pub struct Concealer {
+    strategy: ConcealStrategy,
+    /// format of the last good frame, `None` before the first one
+    info: Option<MP3FrameInfo>,
+    /// output of the last good frame
+    pcm: [i16; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN],
+    /// last granule of the last good frame, if it was Layer III
+    spectrum: Option<Spectrum>,
+    /// frames concealed since the last good one
+    lost: u32,
+    /// gain (Q15) the repeated frame ended at, to crossfade from into the next good frame
+    fade_from: Option<i32>,
+    /// gain (Q15) of the last spectrally repeated granule
+    spectral_gain: i32,
+    /// random number generator state for the signs of repeated spectral lines
+    seed: u32,
+}
+
+impl Concealer {
+    pub fn new(strategy: ConcealStrategy) -> Self {
+        Concealer {
+            strategy,
+            info: None,
+            pcm: [0; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN],
+            spectrum: None,
+            lost: 0,
+            fade_from: None,
+            spectral_gain: GAIN_ONE,
+            seed: 1,
+        }
+    }
+
+    pub fn strategy(&self) -> ConcealStrategy {
+        self.strategy
+    }
+
+    /// Frames concealed since the last good one.
+    pub fn lost_frames(&self) -> u32 {
+        self.lost
+    }
+
+    /// Forgets the last good frame, e.g. after seeking: there is nothing to conceal from until
+    /// the next one.
+    pub fn reset(&mut self) {
+        *self = Concealer::new(self.strategy);
+    }
+
+    /// Decodes the next frame of `input` like [`MP3Decoder::decode_frame_verified`], concealing
+    /// it if it fails to decode.
+    ///
+    /// A concealed frame consumes its input and fills `out` with as many samples as it would
+    /// have decoded to; [`ConcealedFrame::error`] says why it was lost. Errors of frames that
+    /// cannot be concealed are returned: input or output too short, and headers that do not
+    /// parse, which consume nothing and call for a sync search. A frame lost before the first
+    /// good one, such as one missing its bit reservoir at the start of a stream, is consumed
+    /// without output.
+    pub fn decode_frame(
+        &mut self,
+        dec: &mut MP3Decoder,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<ConcealedFrame, Mp3Error> {
+        let mut consumed = 0;
+        let error = match dec.decode_verified(input, out, false, true, &mut consumed) {
+            Ok(samples) => {
+                if samples > 0 {
+                    self.keep_frame(dec, &mut out[..samples]);
+                }
+                return Ok(ConcealedFrame {
+                    frame: DecodedFrame {
+                        bytes_consumed: consumed,
+                        samples_written: samples,
+                        info: dec.m_MP3FrameInfo,
+                    },
+                    error: None,
+                });
+            }
+            Err(e @ (Mp3Error::InDataUnderflow { .. } | Mp3Error::OutputTooSmall { .. })) => {
+                return Err(e);
+            }
+            Err(e) if consumed == 0 => return Err(e),
+            Err(e) => e,
+        };
+
+        dec.mp3_get_last_frame_info();
+        let info = dec.m_MP3FrameInfo;
+        let samples = if self.info.is_some() {
+            info.outputSamps as usize
+        } else {
+            0
+        };
+        if out.len() < samples {
+            return Err(Mp3Error::OutputTooSmall { needed: samples });
+        }
+        self.conceal(dec, &info, &mut out[..samples])?;
+
+        Ok(ConcealedFrame {
+            frame: DecodedFrame {
+                bytes_consumed: consumed,
+                samples_written: samples,
+                info,
+            },
+            error: Some(error),
+        })
+    }
+
+    /* crossfades out of a repeated frame if needed, and keeps the frame for the next loss */
+    fn keep_frame(&mut self, dec: &MP3Decoder, out: &mut [i16]) {
+        let n_chans = dec.m_MP3FrameInfo.n_chans as usize;
+        let fade_from = self.fade_from.take();
+        for (i, (y, x)) in out.iter_mut().zip(self.pcm.iter_mut()).enumerate() {
+            let good = *y;
+            if let Some(gain) = fade_from
+                && i / n_chans < CROSSFADE_SAMPLES
+            {
+                let w = crossfade_weight(i / n_chans);
+                *y = mix(good, w, scale(*x, gain), GAIN_ONE - w);
+            }
+            *x = good;
+        }
+
+        self.spectrum = (dec.m_MP3DecInfo.layer == LayerIndex::Layer3).then(|| {
+            let gr = dec.m_MP3DecInfo.nGrans as usize - 1;
+            Spectrum {
+                coefs: dec.m_HuffmanInfo.huff_dec_buf,
+                non_zero_bound: dec.m_HuffmanInfo.non_zero_bound,
+                gb: dec.m_HuffmanInfo.gb,
+                side_info: dec.m_SideInfoSub[gr],
+            }
+        });
+        self.info = Some(dec.m_MP3FrameInfo);
+        self.lost = 0;
+        self.spectral_gain = GAIN_ONE;
+    }
+
+    /* fills out with the concealed frame of format info */
+    fn conceal(
+        &mut self,
+        dec: &mut MP3Decoder,
+        info: &MP3FrameInfo,
+        out: &mut [i16],
+    ) -> Result<(), Mp3Error> {
+        let Some(last) = self.info else {
+            return Ok(());
+        };
+        self.lost = self.lost.saturating_add(1);
+        let fits = last.layer == info.layer
+            && last.version == info.version
+            && last.samprate == info.samprate
+            && last.n_chans == info.n_chans;
+        if !fits {
+            /* nothing to repeat in this format, let what is left of the previous frame ring out */
+            self.fade_from = None;
+            return decay(dec, out);
+        }
+
+        if let (ConcealStrategy::SpectralRepeat, Some(spectrum)) = (self.strategy, &self.spectrum) {
+            let n_chans = info.n_chans as usize;
+            for granule in out.chunks_exact_mut(MAX_NSAMP * n_chans) {
+                self.spectral_gain = (self.spectral_gain * SPECTRAL_GAIN_STEP) >> 15;
+                synthesize_granule(
+                    dec,
+                    Some((spectrum, self.spectral_gain)),
+                    &mut self.seed,
+                    granule,
+                )?;
+            }
+            return Ok(());
+        }
+
+        /* frames lost in a row before this one */
+        let lost = self.lost - 1;
+        let (start, end) = match self.strategy {
+            ConcealStrategy::FadeOut if lost == 0 => (GAIN_ONE, 0),
+            ConcealStrategy::FadeOut => (0, 0),
+            _ => (
+                GAIN_ONE.checked_shr(lost).unwrap_or(0),
+                GAIN_ONE.checked_shr(lost + 1).unwrap_or(0),
+            ),
+        };
+
+        /* flushes the decoder, and crossfades from what rings out of it into the repeat */
+        decay(dec, out)?;
+        let n_chans = info.n_chans as usize;
+        let len = out.len() / n_chans;
+        for (i, (y, x)) in out.iter_mut().zip(self.pcm.iter()).enumerate() {
+            let t = i / n_chans;
+            let gain = start + ((end - start) as i64 * t as i64 / len as i64) as i32;
+            let w = crossfade_weight(t);
+            *y = mix(scale(*x, gain), w, *y, GAIN_ONE - w);
+        }
+        self.fade_from = Some(end);
+        Ok(())
+    }
+}
+
+/* crossfade weight (Q15) of the incoming audio t samples into the crossfade */
+fn crossfade_weight(t: usize) -> i32 {
+    (t.min(CROSSFADE_SAMPLES) as i32 * GAIN_ONE) / CROSSFADE_SAMPLES as i32
+}
+
+fn scale(x: i16, gain: i32) -> i16 {
+    ((x as i32 * gain) >> 15) as i16
+}
+
+fn mix(a: i16, wa: i32, b: i16, wb: i32) -> i16 {
+    ((a as i32 * wa + b as i32 * wb) >> 15).clamp(i16::MIN as i32, i16::MAX as i32) as i16
+}
+
+/***********************************************************************************************************************
+ * Function:    Decay
+ *
+ * Description: run one frame of silence through IMDCT and synthesis filterbank
+ *
+ * Inputs:      decoder with the frame header of the frame to replace unpacked
+ *
+ * Outputs:     PCM of the frame, what was left of the previous frame in the overlap and
+ *                filterbank buffers fading out to zero
+ *
+ * Return:      Ok, or the stage that failed
+ **********************************************************************************************************************/
+fn decay(dec: &mut MP3Decoder, out: &mut [i16]) -> Result<(), Mp3Error> {
+    let n_chans = dec.output_chans() as usize;
+    if dec.m_MP3DecInfo.layer == LayerIndex::Layer3 {
+        for granule in out.chunks_exact_mut(MAX_NSAMP * n_chans) {
+            synthesize_granule(dec, None, &mut 0, granule)?;
+        }
+        return Ok(());
+    }
+
+    for buf in dec.m_IMDCTInfo.outBuf.iter_mut() {
+        buf.fill([0; NBANDS]);
+    }
+    dec.m_IMDCTInfo.gb = [31; MAX_NCHAN];
+    let n_blocks = SAMPLES_PER_FRAME_TAB[dec.m_MPEGVersion as usize]
+        [dec.m_MP3DecInfo.layer as usize - 1] as usize
+        / NBANDS;
+    let mut pcm = &mut out[..];
+    let mut left = n_blocks;
+    while left > 0 {
+        let blocks = left.min(BLOCK_SIZE);
+        let (chunk, rest) = pcm.split_at_mut(blocks * NBANDS * n_chans);
+        if dec.subband_blocks(chunk, blocks) < 0 {
+            return Err(Mp3Error::InvalidSubband {
+                granule: GranuleIndex::Granule0,
+            });
+        }
+        pcm = rest;
+        left -= blocks;
+    }
+    Ok(())
+}
+
+/***********************************************************************************************************************
+ * Function:    SynthesizeGranule
+ *
+ * Description: IMDCT and synthesis filterbank of one Layer III granule not decoded from the stream
+ *
+ * Inputs:      saved spectrum and its gain (Q15), or None for silence
+ *              random number generator state, the spectral lines get random signs
+ *
+ * Outputs:     one granule of PCM, interleaved LRLRLR... if stereo
+ *
+ * Return:      Ok, or the stage that failed
+ **********************************************************************************************************************/
+fn synthesize_granule(
+    dec: &mut MP3Decoder,
+    spectrum: Option<(&Spectrum, i32)>,
+    seed: &mut u32,
+    out: &mut [i16],
+) -> Result<(), Mp3Error> {
+    const GR: GranuleIndex = GranuleIndex::Granule0;
+    for ch in dec.m_MP3DecInfo.nChans.channels() {
+        let c = *ch as usize;
+        let status = match spectrum {
+            Some((spectrum, gain)) => {
+                let coefs = spectrum.coefs[c].iter();
+                for (y, &x) in dec.m_HuffmanInfo.huff_dec_buf[c].iter_mut().zip(coefs) {
+                    *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                    let x = if (*seed as i32) < 0 { -x } else { x };
+                    *y = ((x as i64 * gain as i64) >> 15) as i32;
+                }
+                /* scaling down only adds guard bits */
+                dec.m_HuffmanInfo.non_zero_bound[c] = spectrum.non_zero_bound[c];
+                dec.m_HuffmanInfo.gb[c] = spectrum.gb[c];
+                dec.m_SideInfoSub[GR as usize][c] = spectrum.side_info[c];
+                imdct_antialiased(
+                    GR,
+                    *ch,
+                    &dec.m_SFBandTable,
+                    dec.m_MPEGVersion,
+                    &dec.m_SideInfoSub,
+                    &mut dec.m_HuffmanInfo,
+                    &mut dec.m_IMDCTInfo,
+                )
+            }
+            None => {
+                /* all-zero long block: only the overlap of the previous block comes out */
+                dec.m_HuffmanInfo.huff_dec_buf[c].fill(0);
+                dec.m_HuffmanInfo.non_zero_bound[c] = 0;
+                dec.m_HuffmanInfo.gb[c] = 31;
+                dec.m_SideInfoSub[GR as usize][c] = SideInfoSub::default();
+                imdct(
+                    GR,
+                    *ch,
+                    &dec.m_SFBandTable,
+                    dec.m_MPEGVersion,
+                    &dec.m_SideInfoSub,
+                    &mut dec.m_HuffmanInfo,
+                    &mut dec.m_IMDCTInfo,
+                )
+            }
+        };
+        if status < 0 {
+            return Err(Mp3Error::InvalidImdct {
+                granule: GR,
+                channel: *ch,
+            });
+        }
+    }
+    if dec.subband(out) < 0 {
+        return Err(Mp3Error::InvalidSubband { granule: GR });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossfade_keeps_the_level() {
+        assert_eq!(crossfade_weight(0), 0);
+        assert_eq!(crossfade_weight(CROSSFADE_SAMPLES / 2), GAIN_ONE / 2);
+        assert_eq!(crossfade_weight(CROSSFADE_SAMPLES + 1), GAIN_ONE);
+        for t in (0..CROSSFADE_SAMPLES).step_by(7) {
+            let w = crossfade_weight(t);
+            assert!((mix(-12000, w, -12000, GAIN_ONE - w) + 12000).abs() <= 1);
+        }
+        assert_eq!(mix(i16::MAX, GAIN_ONE, i16::MAX, GAIN_ONE), i16::MAX);
+        assert_eq!(scale(-20000, GAIN_ONE / 4), -5000);
+    }
+}
@@ -0,0 +1,550 @@
+use crate::{
+    mp3::stereo::{intensity_proc_mpeg1, intensity_proc_mpeg2, mid_side_proc},
+    mp3_decoder::{
+        BlockType, CriticalBandInfo, ERR_MP3_INVALID_SCALEFACT, FrameHeader, GranuleIndex,
+        IMDCT_SCALE, MAX_NSAMP, MP3Decoder, MPEGVersion, SFBandTable, SQRTHALF, ScaleFactorInfoSub,
+        SideInfoSub, mulshift_32,
+    },
+};
+
+/* pow(2,-i/4) * pow(j,4/3) for i=0..3 j=0..15, Q25 format */
+const POW43_14: [[i32; 16]; 4] = [
+    /* Q28 */
+    [
+        0x00000000, 0x10000000, 0x285145f3, 0x453a5cdb, 0x0cb2ff53, 0x111989d6, 0x15ce31c8,
+        0x1ac7f203, 0x20000000, 0x257106b9, 0x2b16b4a3, 0x30ed74b4, 0x36f23fa5, 0x3d227bd3,
+        0x437be656, 0x49fc823c,
+    ],
+    [
+        0x00000000, 0x0d744fcd, 0x21e71f26, 0x3a36abd9, 0x0aadc084, 0x0e610e6e, 0x12560c1d,
+        0x168523cf, 0x1ae89f99, 0x1f7c03a4, 0x243bae49, 0x29249c67, 0x2e34420f, 0x33686f85,
+        0x38bf3dff, 0x3e370182,
+    ],
+    [
+        0x00000000, 0x0b504f33, 0x1c823e07, 0x30f39a55, 0x08facd62, 0x0c176319, 0x0f6b3522,
+        0x12efe2ad, 0x16a09e66, 0x1a79a317, 0x1e77e301, 0x2298d5b4, 0x26da56fc, 0x2b3a902a,
+        0x2fb7e7e7, 0x3450f650,
+    ],
+    [
+        0x00000000, 0x09837f05, 0x17f910d7, 0x2929c7a9, 0x078d0dfa, 0x0a2ae661, 0x0cf73154,
+        0x0fec91cb, 0x1306fe0a, 0x16434a6c, 0x199ee595, 0x1d17ae3d, 0x20abd76a, 0x2459d551,
+        0x28204fbb, 0x2bfe1808,
+    ],
+];
+
+/* pow(2,-i/4) for i=0..3, Q31 format */
+const POW14: [i32; 4] = [0x7fffffff, 0x6ba27e65, 0x5a82799a, 0x4c1bf829];
+
+/* pow(j,4/3) for j=16..63, Q23 format */
+pub(crate) const POW43: [i32; 48] = [
+    0x1428a2fa, 0x15db1bd6, 0x1796302c, 0x19598d85, 0x1b24e8bb, 0x1cf7fcfa, 0x1ed28af2, 0x20b4582a,
+    0x229d2e6e, 0x248cdb55, 0x26832fda, 0x28800000, 0x2a832287, 0x2c8c70a8, 0x2e9bc5d8, 0x30b0ff99,
+    0x32cbfd4a, 0x34eca001, 0x3712ca62, 0x393e6088, 0x3b6f47e0, 0x3da56717, 0x3fe0a5fc, 0x4220ed72,
+    0x44662758, 0x46b03e7c, 0x48ff1e87, 0x4b52b3f3, 0x4daaebfd, 0x5007b497, 0x5268fc62, 0x54ceb29c,
+    0x5738c721, 0x59a72a59, 0x5c19cd35, 0x5e90a129, 0x610b9821, 0x638aa47f, 0x660db90f, 0x6894c90b,
+    0x6b1fc80c, 0x6daeaa0d, 0x70416360, 0x72d7e8b0, 0x75722ef9, 0x78102b85, 0x7ab1d3ec, 0x7d571e09,
+];
+
+/*
+ * Minimax polynomial approximation to pow(x, 4/3), over the range
+ *  poly43lo: x = [0.5, 0.7071]
+ *  poly43hi: x = [0.7071, 1.0]
+ *
+ * Relative error < 1E-7
+ * Coefs are scaled by 4, 2, 1, 0.5, 0.25
+ */
+pub(crate) const POLY43LO: [u32; 5] = [0x29a0bda9, 0xb02e4828, 0x5957aa1b, 0x236c498d, 0xff581859];
+pub(crate) const POLY43HI: [u32; 5] = [0x10852163, 0xd333f6a4, 0x46e9408b, 0x27c2cef0, 0xfef577b4];
+
+/* pow(2, i*4/3) as exp and frac */
+pub(crate) const POW2EXP: [i32; 8] = [14, 13, 11, 10, 9, 7, 6, 5];
+
+pub(crate) const POW2FRAC: [i32; 8] = [
+    0x6597fa94, 0x50a28be6, 0x7fffffff, 0x6597fa94, 0x50a28be6, 0x7fffffff, 0x6597fa94, 0x50a28be6,
+];
+
+pub fn dequant_block(in_buf: &[i32], out_buf: &mut [i32], num: i32, scale: i32) -> i32 {
+    if num <= 0 {
+        return 0;
+    }
+
+    let mut tab4 = [0i32; 4];
+    let mut mask = 0i32;
+
+    // Pobranie tablicy dla skali ułamkowej
+    let tab16 = &POW43_14[(scale & 0x3) as usize];
+    let scalef = POW14[(scale & 0x3) as usize];
+
+    // scalei = min(scale >> 2, 31)
+    let mut scalei = scale >> 2;
+    if scalei > 31 {
+        scalei = 31;
+    }
+
+    /* Cache first 4 values */
+    let shift_init = (scalei + 3).clamp(0, 31);
+
+    tab4[0] = 0;
+    tab4[1] = tab16[1] >> shift_init;
+    tab4[2] = tab16[2] >> shift_init;
+    tab4[3] = tab16[3] >> shift_init;
+
+    // Przetwarzamy num próbek
+    for i in 0..num {
+        let sx = in_buf[i as usize];
+
+        let x = (sx & 0x7fffffff) as u32; // x = magnitude
+        let mut y: i32;
+        let mut shift: i32;
+
+        if x < 4 {
+            y = tab4[x as usize];
+        } else if x < 16 {
+            y = tab16[x as usize];
+            if scalei < 0 {
+                y <<= -scalei;
+            } else {
+                y >>= scalei;
+            }
+        } else {
+            if x < 64 {
+                y = POW43[(x - 16) as usize];
+                y = mulshift_32(y, scalef);
+                shift = scalei - 3;
+            } else {
+                /* Normalizacja do [0x40000000, 0x7fffffff] */
+                let mut x_norm = x << 17;
+                shift = 0;
+                if x_norm < 0x08000000 {
+                    x_norm <<= 4;
+                    shift += 4;
+                }
+                if x_norm < 0x20000000 {
+                    x_norm <<= 2;
+                    shift += 2;
+                }
+                if x_norm < 0x40000000 {
+                    x_norm <<= 1;
+                    shift += 1;
+                }
+
+                let coef = if x_norm < SQRTHALF {
+                    &POLY43LO
+                } else {
+                    &POLY43HI
+                };
+
+                /* Aproksymacja wielomianowa */
+                y = coef[0] as i32;
+                y = mulshift_32(y, x_norm as i32) + (coef[1] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[2] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[3] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[4] as i32);
+
+                // y = (y * pow2frac[shift]) << 3
+                y = mulshift_32(y, POW2FRAC[shift as usize]) << 3;
+
+                /* Skala ułamkowa */
+                y = mulshift_32(y, scalef);
+                shift = scalei - POW2EXP[shift as usize];
+            }
+
+            /* Skala całkowita z clippingiem */
+            if shift < 0 {
+                shift = -shift;
+                if y > (0x7fffffff >> shift) {
+                    y = 0x7fffffff;
+                } else {
+                    y <<= shift;
+                }
+            } else {
+                y >>= shift;
+            }
+        }
+
+        /* Przywrócenie znaku i zapis */
+        mask |= y;
+        let final_y = if sx < 0 { -y } else { y };
+        out_buf[i as usize] = final_y;
+    }
+
+    mask
+}
+
+pub fn dequant_block_in_place(buf: &mut [i32], num: i32, scale: i32) -> i32 {
+    if num <= 0 {
+        return 0;
+    }
+
+    let mut tab4 = [0i32; 4];
+    let mut mask = 0i32;
+
+    // Pobranie tablicy dla skali ułamkowej
+    let tab16 = &POW43_14[(scale & 0x3) as usize];
+    let scalef = POW14[(scale & 0x3) as usize];
+
+    // scalei = min(scale >> 2, 31)
+    let mut scalei = scale >> 2;
+    if scalei > 31 {
+        scalei = 31;
+    }
+
+    /* Cache first 4 values */
+    let shift_init = (scalei + 3).clamp(0, 31);
+
+    tab4[0] = 0;
+    tab4[1] = tab16[1] >> shift_init;
+    tab4[2] = tab16[2] >> shift_init;
+    tab4[3] = tab16[3] >> shift_init;
+
+    // Przetwarzamy num próbek
+    for i in 0..num {
+        let sx = buf[i as usize];
+
+        let x = (sx & 0x7fffffff) as u32; // x = magnitude
+        let mut y: i32;
+        let mut shift: i32;
+
+        if x < 4 {
+            y = tab4[x as usize];
+        } else if x < 16 {
+            y = tab16[x as usize];
+            if scalei < 0 {
+                y <<= -scalei;
+            } else {
+                y >>= scalei;
+            }
+        } else {
+            if x < 64 {
+                y = POW43[(x - 16) as usize];
+                y = mulshift_32(y, scalef);
+                shift = scalei - 3;
+            } else {
+                /* Normalizacja do [0x40000000, 0x7fffffff] */
+                let mut x_norm = x << 17;
+                shift = 0;
+                if x_norm < 0x08000000 {
+                    x_norm <<= 4;
+                    shift += 4;
+                }
+                if x_norm < 0x20000000 {
+                    x_norm <<= 2;
+                    shift += 2;
+                }
+                if x_norm < 0x40000000 {
+                    x_norm <<= 1;
+                    shift += 1;
+                }
+
+                let coef = if x_norm < SQRTHALF {
+                    &POLY43LO
+                } else {
+                    &POLY43HI
+                };
+
+                /* Aproksymacja wielomianowa */
+                y = coef[0] as i32;
+                y = mulshift_32(y, x_norm as i32) + (coef[1] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[2] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[3] as i32);
+                y = mulshift_32(y, x_norm as i32) + (coef[4] as i32);
+
+                // y = (y * pow2frac[shift]) << 3
+                y = mulshift_32(y, POW2FRAC[shift as usize]) << 3;
+
+                /* Skala ułamkowa */
+                y = mulshift_32(y, scalef);
+                shift = scalei - POW2EXP[shift as usize];
+            }
+
+            /* Skala całkowita z clippingiem */
+            if shift < 0 {
+                shift = -shift;
+                if y > (0x7fffffff >> shift) {
+                    y = 0x7fffffff;
+                } else {
+                    y <<= shift;
+                }
+            } else {
+                y >>= shift;
+            }
+        }
+
+        /* Przywrócenie znaku i zapis */
+        mask |= y;
+        let final_y = if sx < 0 { -y } else { y };
+        buf[i as usize] = final_y;
+    }
+
+    mask
+}
+
+/* optional pre-emphasis for high-frequency scale factor bands */
+const PRE_TAB: [u8; 22] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 3, 2, 0,
+];
+
+#[allow(clippy::too_many_arguments)]
+pub fn dequant_channel(
+    sample_buf: &mut [i32; MAX_NSAMP],
+    work_buf: &mut [i32],
+    non_zero_bound: &mut i32,
+    sis: &SideInfoSub,
+    sfis: &ScaleFactorInfoSub,
+    cbi: &mut CriticalBandInfo,
+    m_frame_header: &FrameHeader,
+    m_sf_band_table: &SFBandTable,
+    m_mpegversion: MPEGVersion,
+) -> i32 {
+    let cb_end_l: i32;
+    let cb_start_s: i32;
+    let cb_end_s: i32;
+
+    // 1. Ustalenie granic dla bloków długich i krótkich
+    if sis.blockType == BlockType::Short {
+        if sis.mixedBlock != 0 {
+            cb_end_l = if m_mpegversion == MPEGVersion::MPEG1 {
+                8
+            } else {
+                6
+            }; // MPEG1 vs MPEG2
+            cb_start_s = 3;
+        } else {
+            cb_end_l = 0;
+            cb_start_s = 0;
+        }
+        cb_end_s = 13;
+    } else {
+        cb_end_l = 22;
+        cb_start_s = 13;
+        cb_end_s = 13;
+    }
+
+    let mut cb_max = [0i32; 3];
+    let mut gb_mask = 0i32;
+    let mut i: usize = 0;
+
+    // sfactMultiplier = 2 lub 4
+    let s_multiplier = 2 * (sis.sfactScale + 1);
+
+    // Obliczenie globalGain z uwzględnieniem MidSide i skali IMDCT
+    let mut global_gain = sis.global_gain;
+    if (m_frame_header.modeExt >> 1) != 0 {
+        global_gain -= IMDCT_SCALE as i32;
+    }
+    global_gain += IMDCT_SCALE as i32;
+
+    // 2. Dekwantyzacja bloków długich
+    for cb in 0..cb_end_l {
+        let n_samps =
+            m_sf_band_table.l[(cb + 1) as usize] - m_sf_band_table.l[cb as usize];
+
+        let pre_val = if sis.preFlag != 0 {
+            PRE_TAB[cb as usize] as i32
+        } else {
+            0
+        };
+        let gain_i = 210 - global_gain + s_multiplier * (sfis.l[cb as usize] as i32 + pre_val);
+
+        let non_zero = dequant_block_in_place(&mut sample_buf[i..], n_samps, gain_i);
+
+        if non_zero != 0 {
+            cb_max[0] = cb;
+        }
+        gb_mask |= non_zero;
+        i += n_samps as usize;
+
+        if i >= (*non_zero_bound) as usize {
+            break;
+        }
+    }
+
+    // Wstępne ustawienie CBI
+    cbi.cbType = 0;
+    cbi.cbEndL = cb_max[0];
+    cbi.cbEndS = [0, 0, 0];
+    cbi.cbEndSMax = 0;
+
+    if cb_start_s >= 12 {
+        return (gb_mask.leading_zeros() as i32) - 1;
+    }
+
+    // 3. Dekwantyzacja bloków krótkich
+    cb_max = [cb_start_s, cb_start_s, cb_start_s];
+    for cb in cb_start_s..cb_end_s {
+        let n_samps =
+            m_sf_band_table.s[(cb + 1) as usize] - m_sf_band_table.s[cb as usize];
+
+        for w in 0..3 {
+            let gain_i = 210 - global_gain
+                + 8 * sis.subBlockGain[w]
+                + s_multiplier * (sfis.s[cb as usize][w] as i32);
+
+            // Dekwantyzujemy do workBuf, aby móc potem bezpiecznie przełożyć dane do sampleBuf
+            let non_zero = dequant_block(
+                &sample_buf[i + (n_samps * w as i32) as usize..],
+                &mut work_buf[(n_samps * w as i32) as usize..],
+                n_samps,
+                gain_i,
+            );
+
+            if non_zero != 0 {
+                cb_max[w] = cb;
+            }
+            gb_mask |= non_zero;
+        }
+
+        // 4. Reordering: Przeplatanie próbek z 3 bloków krótkich
+        // C: buf[j][0] = workBuf[0*nSamps + j]
+        let n = n_samps as usize;
+        let start = i;
+        let end = start + n * 3;
+
+        // Bezpośrednie wycięcie fragmentu (rzuci panic jeśli poza zakresem)
+        let target_slice = &mut sample_buf[start..end];
+        let (rows, _) = target_slice.as_chunks_mut::<3>();
+
+        for (j, row) in rows.iter_mut().enumerate() {
+            // row jest &mut [i32; 3], kompilator usuwa sprawdzenie granic dla row[0,1,2]
+            row[0] = work_buf[j];
+            row[1] = work_buf[n + j];
+            row[2] = work_buf[2 * n + j];
+        }
+
+        i += (3 * n_samps) as usize;
+        if i >= (*non_zero_bound) as usize {
+            break;
+        }
+    }
+
+    // Aktualizacja non_zero_bound i CBI
+    *non_zero_bound = i as i32;
+    cbi.cbType = if sis.mixedBlock != 0 { 2 } else { 1 };
+    cbi.cbEndS = cb_max;
+    cbi.cbEndSMax = cb_max[0].max(cb_max[1]).max(cb_max[2]);
+
+    (gb_mask.leading_zeros() as i32) - 1
+}
+
+/***********************************************************************************************************************
+ * Function:    Dequantize
+ *
+ * Description: dequantize coefficients, decode stereo, reorder short blocks
+ *                (one granule-worth)
+ *
+ * Inputs:      MP3Decoder filled by UnpackFrameHeader(), UnpackSideInfo(),
+ *                UnpackScaleFactors() and DecodeHuffman() (for this granule)
+ *              index of current granule
+ *
+ * Outputs:     dequantized and reordered coefficients in huff_dec_buf
+ *                (one granule-worth, all channels), format = Q26
+ *              updated non_zero_bound for both channels
+ *
+ * Return:      0 on success, ERR_MP3_INVALID_SCALEFACT if an intensity stereo position is
+ *                out of range
+ **********************************************************************************************************************/
+pub fn mp3_dequantize(gr: GranuleIndex, m_mp3_decoder: &mut MP3Decoder) -> i32 {
+    let di = &mut m_mp3_decoder.m_MP3DecInfo;
+    let hi = &mut m_mp3_decoder.m_HuffmanInfo;
+    /* outBuf is dead between the filterbank of the last granule and the IMDCT of this one */
+    #[cfg(not(feature = "small-vbuf"))]
+    let work_buf = &mut m_mp3_decoder.m_DequantInfo.work_buf[..];
+    #[cfg(feature = "small-vbuf")]
+    let work_buf = m_mp3_decoder.m_IMDCTInfo.outBuf.as_flattened_mut().as_flattened_mut();
+    let fh = &mut m_mp3_decoder.m_FrameHeader;
+    let cbi = &mut m_mp3_decoder.m_CriticalBandInfo;
+    let side_info_sub = &mut m_mp3_decoder.m_SideInfoSub;
+    let sf_info_sub = &mut m_mp3_decoder.m_ScaleFactorInfoSub;
+    let sfbt = &mut m_mp3_decoder.m_SFBandTable;
+    let sf_js = &mut m_mp3_decoder.m_ScaleFactorJS;
+
+    let mut m_out = [0i32; 2];
+    let gr_idx = gr as usize;
+
+    // 1. Dekwantyzacja każdego kanału
+    for ch in 0..di.nChans as usize {
+        hi.gb[ch] = dequant_channel(
+            &mut hi.huff_dec_buf[ch],
+            &mut work_buf[..],
+            &mut hi.non_zero_bound[ch],
+            &side_info_sub[gr_idx][ch],
+            &(*sf_info_sub)[gr_idx][ch],
+            &mut cbi[ch],
+            fh,
+            sfbt,
+            m_mp3_decoder.m_MPEGVersion,
+        );
+    }
+
+    // 2. Obsługa rzadkich przypadków braku bitów strażniczych (clipping)
+    if fh.modeExt != 0 && (hi.gb[0] < 1 || hi.gb[1] < 1) {
+        for ch in 0..2 {
+            for i in 0..hi.non_zero_bound[ch] as usize {
+                hi.huff_dec_buf[ch][i] = hi.huff_dec_buf[ch][i].clamp(-0x3fffffff, 0x3fffffff);
+            }
+        }
+    }
+
+    // 3. Proces Mid-Side Stereo
+    if (fh.modeExt >> 1) != 0 {
+        let n_samps: i32;
+        if (fh.modeExt & 0x01) != 0 {
+            /* Intensity stereo włączone - Mid-Side tylko do początku regionu zero prawego kanału */
+            if cbi[1].cbType == 0 {
+                n_samps = sfbt.l[cbi[1].cbEndL as usize + 1];
+            } else {
+                n_samps = 3 * sfbt.s[cbi[1].cbEndSMax as usize + 1];
+            }
+        } else {
+            /* Intensity stereo wyłączone - Mid-Side na całym widmie */
+            n_samps = hi.non_zero_bound[0].max(hi.non_zero_bound[1]);
+        }
+        mid_side_proc(&mut hi.huff_dec_buf, n_samps as usize, &mut m_out);
+    }
+
+    // 4. Proces Intensity Stereo
+    if (fh.modeExt & 0x01) != 0 {
+        let n_samps = hi.non_zero_bound[0];
+        if m_mp3_decoder.m_MPEGVersion == MPEGVersion::MPEG1 {
+            // MPEG1
+            if intensity_proc_mpeg1(
+                &mut hi.huff_dec_buf,
+                n_samps,
+                &sf_info_sub[gr_idx][1],
+                cbi,
+                fh.modeExt >> 1,
+                &mut m_out,
+                sfbt,
+            ) < 0 {
+                return ERR_MP3_INVALID_SCALEFACT as i32;
+            }
+        } else {
+            // MPEG2
+            intensity_proc_mpeg2(
+                &mut hi.huff_dec_buf,
+                n_samps,
+                &(*sf_info_sub)[gr_idx][1],
+                cbi,
+                sf_js,
+                fh.modeExt >> 1,
+                side_info_sub[gr_idx][1].mixedBlock,
+                &mut m_out,
+                sfbt,
+            );
+        }
+    }
+
+    // 5. Aktualizacja Guard Bits i NonZeroBound po procesach stereo
+    if fh.modeExt != 0 {
+        // CLZ (Count Leading Zeros) - w Rust używamy leading_zeros()
+        // Pamiętaj o obsłudze abs() dla m_out
+        hi.gb[0] = (m_out[0].abs().leading_zeros() as i32) - 1;
+        hi.gb[1] = (m_out[1].abs().leading_zeros() as i32) - 1;
+
+        let max_n_samps = hi.non_zero_bound[0].max(hi.non_zero_bound[1]);
+        hi.non_zero_bound[0] = max_n_samps;
+        hi.non_zero_bound[1] = max_n_samps;
+    }
+
+    0
+}
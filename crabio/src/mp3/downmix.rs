@@ -0,0 +1,136 @@
+//! Mono output of stereo streams (see [`crate::mp3_decoder::OutputMode::Mono`]).
+//!
+//! A Layer III granule is mixed down in the frequency domain, after stereo processing: the
+//! two spectra are averaged into channel 0 and only that channel goes through the IMDCT. This
+//! only gives the same result as mixing the decoded channels when both use the same blocks,
+//! which joint stereo streams nearly always do; a granule where they differ goes through both
+//! IMDCTs and is mixed before the filterbank, like Layer I and II frames.
+//!
+//! Either way a single channel is run through the synthesis filterbank. Channel 1 is kept a
+//! copy of channel 0 after a mixed granule, so the concealer and a later granule that needs
+//! both IMDCTs find the state of a continuous stream in both.
+
+use crate::mp3_decoder::{BLOCK_SIZE, IMDCTInfo, MP3Decoder, SideInfoSub};
+
+/* (a + b) / 2 without overflow, exact for a == b */
+fn average(a: i32, b: i32) -> i32 {
+    ((a as i64 + b as i64) >> 1) as i32
+}
+
+/***********************************************************************************************************************
+ * Function:    SpectralDownmixFits
+ *
+ * Description: check whether the IMDCT of the averaged spectra of both channels is the average
+ *                of their IMDCTs
+ *
+ * Inputs:      side info of both channels of the granule
+ *              IMDCT state of both channels
+ *
+ * Outputs:     none
+ *
+ * Return:      true if both channels use the same blocks, now and in the previous granule
+ **********************************************************************************************************************/
+pub fn spectral_downmix_fits(sis: &[SideInfoSub; 2], mi: &IMDCTInfo) -> bool {
+    sis[0].blockType == sis[1].blockType
+        && sis[0].mixedBlock == sis[1].mixedBlock
+        && mi.prevType[0] == mi.prevType[1]
+        && mi.prevWinSwitch[0] == mi.prevWinSwitch[1]
+}
+
+/***********************************************************************************************************************
+ * Function:    DownmixSpectrum
+ *
+ * Description: average the dequantized spectra and the IMDCT overlap of both channels into
+ *                channel 0
+ *
+ * Inputs:      decoder after Dequantize() of the granule, SpectralDownmixFits() true
+ *
+ * Outputs:     mixed spectrum in huffDecBuf[0], nonZeroBound[0] and gb[0] updated
+ *              mixed overlap in overBuf[0], numPrevIMDCT[0] updated
+ *
+ * Return:      none
+ *
+ * Notes:       coefficients past nonZeroBound and overlap past numPrevIMDCT blocks are 0
+ **********************************************************************************************************************/
+pub fn downmix_spectrum(dec: &mut MP3Decoder) {
+    let hi = &mut dec.m_HuffmanInfo;
+    let bound = hi.non_zero_bound[0].max(hi.non_zero_bound[1]);
+    let [x0, x1] = &mut hi.huff_dec_buf;
+    for (a, &b) in x0.iter_mut().zip(x1.iter()).take(bound as usize) {
+        *a = average(*a, b);
+    }
+    hi.non_zero_bound[0] = bound;
+    /* the average is no larger than the larger input */
+    hi.gb[0] = hi.gb[0].min(hi.gb[1]);
+
+    let mi = &mut dec.m_IMDCTInfo;
+    let [prev0, prev1] = &mut mi.overBuf;
+    for (a, &b) in prev0.iter_mut().zip(prev1.iter()) {
+        *a = average(*a, b);
+    }
+    mi.numPrevIMDCT[0] = mi.numPrevIMDCT[0].max(mi.numPrevIMDCT[1]);
+}
+
+/***********************************************************************************************************************
+ * Function:    MirrorChannel0
+ *
+ * Description: copy the spectrum and IMDCT state of channel 0 to channel 1 after the IMDCT of
+ *                a downmixed granule
+ *
+ * Inputs:      Huffman and IMDCT info after IMDCT() of channel 0
+ *
+ * Outputs:     channel 1 identical to channel 0 (the side info already is)
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn mirror_channel0(dec: &mut MP3Decoder) {
+    let hi = &mut dec.m_HuffmanInfo;
+    hi.huff_dec_buf[1] = hi.huff_dec_buf[0];
+    hi.non_zero_bound[1] = hi.non_zero_bound[0];
+    hi.gb[1] = hi.gb[0];
+
+    let mi = &mut dec.m_IMDCTInfo;
+    mi.outBuf[1] = mi.outBuf[0];
+    mi.overBuf[1] = mi.overBuf[0];
+    mi.numPrevIMDCT[1] = mi.numPrevIMDCT[0];
+    mi.prevType[1] = mi.prevType[0];
+    mi.prevWinSwitch[1] = mi.prevWinSwitch[0];
+    mi.gb[1] = mi.gb[0];
+}
+
+/***********************************************************************************************************************
+ * Function:    DownmixSubbands
+ *
+ * Description: average the subband samples of both channels into channel 0, for the mono
+ *                synthesis filterbank
+ *
+ * Inputs:      IMDCT info with outBuf and gb of both channels filled in
+ *              number of time slots to mix
+ *
+ * Outputs:     mixed samples in outBuf[0], gb[0] updated
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn downmix_subbands(mi: &mut IMDCTInfo, n_blocks: usize) {
+    let n = n_blocks.min(BLOCK_SIZE);
+    let [y0, y1] = &mut mi.outBuf;
+    for (a, b) in y0[..n].iter_mut().zip(&y1[..n]) {
+        for (a, &b) in a.iter_mut().zip(b) {
+            *a = average(*a, b);
+        }
+    }
+    mi.gb[0] = mi.gb[0].min(mi.gb[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_is_exact_and_does_not_overflow() {
+        assert_eq!(average(i32::MAX, i32::MAX), i32::MAX);
+        assert_eq!(average(i32::MIN, i32::MIN), i32::MIN);
+        assert_eq!(average(-7, -7), -7);
+        assert_eq!(average(3, -8), -3);
+    }
+}
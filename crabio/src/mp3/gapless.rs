@@ -0,0 +1,188 @@
+//! Gapless playback: trimming the encoder delay and padding off the decoded stream.
+//!
+//! An encoder starts a stream with `delay` samples of silence and pads the last frame with
+//! `padding` more; the decoder adds its own delay on top. [`Gapless`] counts the samples
+//! coming out of the decoder and keeps only the ones of the original track. The last few of
+//! those are still in the decoder when the input ends, [`MP3Decoder::drain`] gets them out.
+//!
+//! [`MP3Decoder::drain`]: crate::mp3_decoder::MP3Decoder::drain
+
+use super::vbr::VbrHeader;
+use crate::mp3_decoder::{DecodedFrame, LayerIndex};
+
+/// Samples per channel the Layer III decoder output lags its input: 288 from the IMDCT
+/// overlap, 241 from the synthesis filterbank.
+pub const DECODER_DELAY_LAYER3: u32 = 529;
+/// Samples per channel the Layer I and II decoder output lags, from the synthesis filterbank.
+pub const DECODER_DELAY_LAYER12: u32 = 241;
+
+/// Trims decoded frames to the samples of the original track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gapless {
+    /// decoded sample (per channel) the track starts at
+    start: u64,
+    /// samples per channel in the track, `None` if the stream length is unknown
+    length: Option<u64>,
+    /// decoded sample (per channel) the next frame starts at
+    position: u64,
+}
+
+impl Gapless {
+    /// Trimming for a stream of `layer` frames which the encoder delayed by `delay` and padded
+    /// with `padding` samples per channel; `total_samples` is the length of the whole stream
+    /// (frames times samples per frame), without it only the start is trimmed.
+    pub fn new(layer: LayerIndex, delay: u32, padding: u32, total_samples: Option<u64>) -> Self {
+        let decoder_delay = match layer {
+            LayerIndex::Layer3 => DECODER_DELAY_LAYER3,
+            _ => DECODER_DELAY_LAYER12,
+        };
+        Gapless {
+            start: (delay + decoder_delay) as u64,
+            length: total_samples.map(|total| total.saturating_sub((delay + padding) as u64)),
+            position: 0,
+        }
+    }
+
+    /// Trimming from the LAME tag of a Xing/Info header or the delay of a VBRI header.
+    ///
+    /// Returns `None` for a Xing/Info header without LAME tag, there is nothing to trim by.
+    pub fn from_vbr_header(vbr: &VbrHeader) -> Option<Self> {
+        let (delay, padding) = match vbr {
+            VbrHeader::Xing(xing) => {
+                let lame = xing.lame?;
+                (lame.encoder_delay, lame.encoder_padding)
+            }
+            VbrHeader::Vbri(vbri) => (vbri.delay, 0),
+        };
+        Some(Gapless::new(
+            LayerIndex::Layer3,
+            delay as u32,
+            padding as u32,
+            vbr.total_samples(),
+        ))
+    }
+
+    /// Samples per channel in the track, if the stream length is known.
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Whether the whole track has been output, so neither more frames nor
+    /// [`MP3Decoder::drain`](crate::mp3_decoder::MP3Decoder::drain) are needed.
+    pub fn is_finished(&self) -> bool {
+        self.length
+            .is_some_and(|length| self.position >= self.start + length)
+    }
+
+    /// Moves to `sample` of the track and returns the decoded sample to seek to, see
+    /// [`MP3Decoder::seek`](crate::mp3_decoder::MP3Decoder::seek).
+    pub fn seek(&mut self, sample: u64) -> u64 {
+        let sample = match self.length {
+            Some(length) => sample.min(length),
+            None => sample,
+        };
+        self.position = self.start + sample;
+        self.position
+    }
+
+    /// Trims `frame`, decoded into `out`, to the samples of the track and moves them to the
+    /// start of `out`. Frames have to be passed in decoding order, the drained one included.
+    pub fn trim(&mut self, out: &mut [i16], frame: DecodedFrame) -> DecodedFrame {
+        let n_chans = frame.info.n_chans as usize;
+        let begin = self.position;
+        let end = begin + (frame.samples_written / n_chans) as u64;
+        self.position = end;
+
+        let keep_from = self.start.clamp(begin, end);
+        let keep_to = match self.length {
+            Some(length) => (self.start + length).clamp(keep_from, end),
+            None => end,
+        };
+        let from = (keep_from - begin) as usize * n_chans;
+        let to = (keep_to - begin) as usize * n_chans;
+        out.copy_within(from..to, 0);
+        DecodedFrame {
+            samples_written: to - from,
+            ..frame
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mp3_decoder::{ChannelCount, MP3FrameInfo, MPEGVersion};
+
+    fn frame(samples_written: usize) -> DecodedFrame {
+        DecodedFrame {
+            bytes_consumed: 0,
+            samples_written,
+            info: MP3FrameInfo {
+                bitrate: 128_000,
+                n_chans: ChannelCount::DualChannel,
+                samprate: 44100,
+                bitsPerSample: 16,
+                outputSamps: samples_written as i32,
+                layer: LayerIndex::Layer3,
+                version: MPEGVersion::MPEG1,
+            },
+        }
+    }
+
+    /// Runs `frames` stereo frames of 1152 samples and a drained granule through `gapless`,
+    /// each sample holding its decoded position, and returns the positions kept.
+    fn kept(gapless: &mut Gapless, frames: usize) -> Vec<i16> {
+        let mut position = 0;
+        let mut kept = Vec::new();
+        for n in core::iter::repeat_n(1152, frames).chain([576]) {
+            let mut out: Vec<i16> = (position..position + n)
+                .flat_map(|s| [s as i16, s as i16])
+                .collect();
+            position += n;
+            let trimmed = gapless.trim(&mut out, frame(2 * n));
+            kept.extend(out[..trimmed.samples_written].iter().step_by(2));
+        }
+        kept
+    }
+
+    #[test]
+    fn trims_delay_and_padding() {
+        // LAME defaults: 576 delay, padding that leaves the track short of the last frame end
+        let mut gapless = Gapless::new(LayerIndex::Layer3, 576, 1800, Some(10 * 1152));
+        assert_eq!(gapless.length(), Some(9144));
+        let kept = kept(&mut gapless, 10);
+        assert_eq!(kept.len(), 9144);
+        assert_eq!(kept[0], 1105);
+        assert_eq!(*kept.last().unwrap(), 1105 + 9143);
+        assert!(gapless.is_finished());
+    }
+
+    #[test]
+    fn drained_granule_holds_the_end_of_short_padding() {
+        // padding under the decoder delay: the track ends after the last frame
+        let mut gapless = Gapless::new(LayerIndex::Layer3, 576, 100, Some(10 * 1152));
+        let kept = kept(&mut gapless, 10);
+        assert_eq!(kept.len(), 11520 - 676);
+        assert_eq!(*kept.last().unwrap(), (11520 + 429 - 1) as i16);
+    }
+
+    #[test]
+    fn unknown_length_trims_the_start_only() {
+        let mut gapless = Gapless::new(LayerIndex::Layer2, 0, 0, None);
+        let kept = kept(&mut gapless, 2);
+        assert_eq!(kept.len(), 2 * 1152 + 576 - 241);
+        assert_eq!(kept[0], 241);
+        assert!(!gapless.is_finished());
+    }
+
+    #[test]
+    fn seeks_in_track_samples() {
+        let mut gapless = Gapless::new(LayerIndex::Layer3, 576, 1800, Some(10 * 1152));
+        assert_eq!(gapless.seek(1000), 2105);
+        assert_eq!(gapless.seek(u64::MAX), 1105 + 9144);
+        assert!(gapless.is_finished());
+    }
+}
@@ -0,0 +1,1145 @@
+use crate::mp3_decoder::{
+    BlockType, ChannelIndex, GranuleIndex, HUFF_PAIRTABS, HuffTabLookup, HuffTabType, HuffmanInfo,
+    MAX_NCHAN, MAX_NGRAN, MAX_NSAMP, MPEGVersion, SFBandTable, SideInfoSub,
+};
+
+/***********************************************************************************************************************
+ * Function:    DecodeHuffmanPairs
+ *
+ * Description: decode 2-way vector Huffman codes in the "bigValues" region of spectrum
+ *
+ * Inputs:      valid BitStreamInfo struct, pointing to start of pair-wise codes
+ *              pointer to xy buffer to received decoded values
+ *              number of codewords to decode
+ *              index of Huffman table to use
+ *              number of bits remaining in bitstream
+ *
+ * Outputs:     pairs of decoded coefficients in vwxy
+ *              updated BitStreamInfo struct
+ *
+ * Return:      number of bits used, or -1 if out of bits
+ *
+ * Notes:       assumes that nVals is an even number
+ *              si_huff.bit tests every Huffman codeword in every table (though not
+ *                necessarily all linBits outputs for x,y > 15)
+ **********************************************************************************************************************/
+// no improvement with section=data
+
+const HUFF_TABLE: [u16; 4242] = [
+    /* huffTable01[9] */
+    0xf003, 0x3112, 0x3101, 0x2011, 0x2011, 0x1000, 0x1000, 0x1000, 0x1000,
+    /* huffTable02[65] */
+    0xf006, 0x6222, 0x6201, 0x5212, 0x5212, 0x5122, 0x5122, 0x5021, 0x5021, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, /* huffTable03[65] */
+    0xf006, 0x6222, 0x6201, 0x5212, 0x5212, 0x5122, 0x5122, 0x5021, 0x5021, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2101, 0x2101, 0x2101,
+    0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101, 0x2101,
+    0x2101, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, /* huffTable05[257] */
+    0xf008, 0x8332, 0x8322, 0x7232, 0x7232, 0x6132, 0x6132, 0x6132, 0x6132, 0x7312, 0x7312, 0x7301,
+    0x7301, 0x7031, 0x7031, 0x7222, 0x7222, 0x6212, 0x6212, 0x6212, 0x6212, 0x6122, 0x6122, 0x6122,
+    0x6122, 0x6201, 0x6201, 0x6201, 0x6201, 0x6021, 0x6021, 0x6021, 0x6021, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, /* huffTable06[129] */
+    0xf007, 0x7332, 0x7301, 0x6322, 0x6322, 0x6232, 0x6232, 0x6031, 0x6031, 0x5312, 0x5312, 0x5312,
+    0x5312, 0x5132, 0x5132, 0x5132, 0x5132, 0x5222, 0x5222, 0x5222, 0x5222, 0x5201, 0x5201, 0x5201,
+    0x5201, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4122, 0x4122, 0x4122,
+    0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4021, 0x4021, 0x4021, 0x4021, 0x4021, 0x4021, 0x4021,
+    0x4021, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000,
+    0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000,
+    /* huffTable07[110] */
+    0xf006, 0x0041, 0x0052, 0x005b, 0x0060, 0x0063, 0x0068, 0x006b, 0x6212, 0x5122, 0x5122, 0x6201,
+    0x6021, 0x4112, 0x4112, 0x4112, 0x4112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0xf004, 0x4552, 0x4542, 0x4452, 0x4352, 0x3532, 0x3532,
+    0x3442, 0x3442, 0x3522, 0x3522, 0x3252, 0x3252, 0x2512, 0x2512, 0x2512, 0x2512, 0xf003, 0x2152,
+    0x2152, 0x3501, 0x3432, 0x2051, 0x2051, 0x3342, 0x3332, 0xf002, 0x2422, 0x2242, 0x1412, 0x1412,
+    0xf001, 0x1142, 0x1041, 0xf002, 0x2401, 0x2322, 0x2232, 0x2301, 0xf001, 0x1312, 0x1132, 0xf001,
+    0x1031, 0x1222, /* huffTable08[280] */
+    0xf008, 0x0101, 0x010a, 0x010f, 0x8512, 0x8152, 0x0112, 0x0115, 0x8422, 0x8242, 0x8412, 0x7142,
+    0x7142, 0x8401, 0x8041, 0x8322, 0x8232, 0x8312, 0x8132, 0x8301, 0x8031, 0x6222, 0x6222, 0x6222,
+    0x6222, 0x6201, 0x6201, 0x6201, 0x6201, 0x6021, 0x6021, 0x6021, 0x6021, 0x4212, 0x4212, 0x4212,
+    0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212,
+    0x4212, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x4122,
+    0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112,
+    0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x2112, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0xf003, 0x3552, 0x3452, 0x2542, 0x2542, 0x1352, 0x1352,
+    0x1352, 0x1352, 0xf002, 0x2532, 0x2442, 0x1522, 0x1522, 0xf001, 0x1252, 0x1501, 0xf001, 0x1432,
+    0x1342, 0xf001, 0x1051, 0x1332, /* huffTable09[93] */
+    0xf006, 0x0041, 0x004a, 0x004f, 0x0052, 0x0057, 0x005a, 0x6412, 0x6142, 0x6322, 0x6232, 0x5312,
+    0x5312, 0x5132, 0x5132, 0x6301, 0x6031, 0x5222, 0x5222, 0x5201, 0x5201, 0x4212, 0x4212, 0x4212,
+    0x4212, 0x4122, 0x4122, 0x4122, 0x4122, 0x4021, 0x4021, 0x4021, 0x4021, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3000, 0x3000, 0x3000,
+    0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0xf003, 0x3552, 0x3542, 0x2532, 0x2532, 0x2352, 0x2352,
+    0x3452, 0x3501, 0xf002, 0x2442, 0x2522, 0x2252, 0x2512, 0xf001, 0x1152, 0x1432, 0xf002, 0x1342,
+    0x1342, 0x2051, 0x2401, 0xf001, 0x1422, 0x1242, 0xf001, 0x1332, 0x1041,
+    /* huffTable10[320] */
+    0xf008, 0x0101, 0x010a, 0x010f, 0x0118, 0x011b, 0x0120, 0x0125, 0x8712, 0x8172, 0x012a, 0x012d,
+    0x0132, 0x8612, 0x8162, 0x8061, 0x0137, 0x013a, 0x013d, 0x8412, 0x8142, 0x8041, 0x8322, 0x8232,
+    0x8301, 0x7312, 0x7312, 0x7132, 0x7132, 0x7031, 0x7031, 0x7222, 0x7222, 0x6212, 0x6212, 0x6212,
+    0x6212, 0x6122, 0x6122, 0x6122, 0x6122, 0x6201, 0x6201, 0x6201, 0x6201, 0x6021, 0x6021, 0x6021,
+    0x6021, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112,
+    0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0xf003, 0x3772, 0x3762, 0x3672, 0x3752, 0x3572, 0x3662,
+    0x2742, 0x2742, 0xf002, 0x2472, 0x2652, 0x2562, 0x2732, 0xf003, 0x2372, 0x2372, 0x2642, 0x2642,
+    0x3552, 0x3452, 0x2362, 0x2362, 0xf001, 0x1722, 0x1272, 0xf002, 0x2462, 0x2701, 0x1071, 0x1071,
+    0xf002, 0x1262, 0x1262, 0x2542, 0x2532, 0xf002, 0x1601, 0x1601, 0x2352, 0x2442, 0xf001, 0x1632,
+    0x1622, 0xf002, 0x2522, 0x2252, 0x1512, 0x1512, 0xf002, 0x1152, 0x1152, 0x2432, 0x2342, 0xf001,
+    0x1501, 0x1051, 0xf001, 0x1422, 0x1242, 0xf001, 0x1332, 0x1401,
+    /* huffTable11[296] */
+    0xf008, 0x0101, 0x0106, 0x010f, 0x0114, 0x0117, 0x8722, 0x8272, 0x011c, 0x7172, 0x7172, 0x8712,
+    0x8071, 0x8632, 0x8362, 0x8061, 0x011f, 0x0122, 0x8512, 0x7262, 0x7262, 0x8622, 0x8601, 0x7612,
+    0x7612, 0x7162, 0x7162, 0x8152, 0x8432, 0x8051, 0x0125, 0x8422, 0x8242, 0x8412, 0x8142, 0x8401,
+    0x8041, 0x7322, 0x7322, 0x7232, 0x7232, 0x6312, 0x6312, 0x6312, 0x6312, 0x6132, 0x6132, 0x6132,
+    0x6132, 0x7301, 0x7301, 0x7031, 0x7031, 0x6222, 0x6222, 0x6222, 0x6222, 0x5122, 0x5122, 0x5122,
+    0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212,
+    0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x5201, 0x5201, 0x5201,
+    0x5201, 0x5201, 0x5201, 0x5201, 0x5201, 0x5021, 0x5021, 0x5021, 0x5021, 0x5021, 0x5021, 0x5021,
+    0x5021, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000,
+    0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0xf002, 0x2772, 0x2762, 0x2672, 0x2572, 0xf003, 0x2662,
+    0x2662, 0x2742, 0x2742, 0x2472, 0x2472, 0x3752, 0x3552, 0xf002, 0x2652, 0x2562, 0x1732, 0x1732,
+    0xf001, 0x1372, 0x1642, 0xf002, 0x2542, 0x2452, 0x2532, 0x2352, 0xf001, 0x1462, 0x1701, 0xf001,
+    0x1442, 0x1522, 0xf001, 0x1252, 0x1501, 0xf001, 0x1342, 0x1332,
+    /* huffTable12[185] */
+    0xf007, 0x0081, 0x008a, 0x008f, 0x0092, 0x0097, 0x009a, 0x009d, 0x00a2, 0x00a5, 0x00a8, 0x7622,
+    0x7262, 0x7162, 0x00ad, 0x00b0, 0x00b3, 0x7512, 0x7152, 0x7432, 0x7342, 0x00b6, 0x7422, 0x7242,
+    0x7412, 0x6332, 0x6332, 0x6142, 0x6142, 0x6322, 0x6322, 0x6232, 0x6232, 0x7041, 0x7301, 0x6031,
+    0x6031, 0x5312, 0x5312, 0x5312, 0x5312, 0x5132, 0x5132, 0x5132, 0x5132, 0x5222, 0x5222, 0x5222,
+    0x5222, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4212, 0x4122, 0x4122, 0x4122,
+    0x4122, 0x4122, 0x4122, 0x4122, 0x4122, 0x5201, 0x5201, 0x5201, 0x5201, 0x5021, 0x5021, 0x5021,
+    0x5021, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3101,
+    0x3101, 0x3101, 0x3101, 0x3101, 0x3101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0xf003, 0x3772, 0x3762,
+    0x2672, 0x2672, 0x2752, 0x2752, 0x2572, 0x2572, 0xf002, 0x2662, 0x2742, 0x2472, 0x2562, 0xf001,
+    0x1652, 0x1732, 0xf002, 0x2372, 0x2552, 0x1722, 0x1722, 0xf001, 0x1272, 0x1642, 0xf001, 0x1462,
+    0x1712, 0xf002, 0x1172, 0x1172, 0x2701, 0x2071, 0xf001, 0x1632, 0x1362, 0xf001, 0x1542, 0x1452,
+    0xf002, 0x1442, 0x1442, 0x2601, 0x2501, 0xf001, 0x1612, 0x1061, 0xf001, 0x1532, 0x1352, 0xf001,
+    0x1522, 0x1252, 0xf001, 0x1051, 0x1401, /* huffTable13[497] */
+    0xf006, 0x0041, 0x0082, 0x00c3, 0x00e4, 0x0105, 0x0116, 0x011f, 0x0130, 0x0139, 0x013e, 0x0143,
+    0x0146, 0x6212, 0x6122, 0x6201, 0x6021, 0x4112, 0x4112, 0x4112, 0x4112, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0xf006, 0x0108, 0x0111, 0x011a, 0x0123, 0x012c, 0x0131,
+    0x0136, 0x013f, 0x0144, 0x0147, 0x014c, 0x0151, 0x0156, 0x015b, 0x6f12, 0x61f2, 0x60f1, 0x0160,
+    0x0163, 0x0166, 0x62e2, 0x0169, 0x6e12, 0x61e2, 0x016c, 0x016f, 0x0172, 0x0175, 0x0178, 0x017b,
+    0x66c2, 0x6d32, 0x017e, 0x6d22, 0x62d2, 0x6d12, 0x67b2, 0x0181, 0x0184, 0x63c2, 0x0187, 0x6b42,
+    0x51d2, 0x51d2, 0x6d01, 0x60d1, 0x6a82, 0x68a2, 0x6c42, 0x64c2, 0x6b62, 0x66b2, 0x5c32, 0x5c32,
+    0x5c22, 0x5c22, 0x52c2, 0x52c2, 0x5b52, 0x5b52, 0x65b2, 0x6982, 0x5c12, 0x5c12, 0xf006, 0x51c2,
+    0x51c2, 0x6892, 0x6c01, 0x50c1, 0x50c1, 0x64b2, 0x6a62, 0x66a2, 0x6972, 0x5b32, 0x5b32, 0x53b2,
+    0x53b2, 0x6882, 0x6a52, 0x5b22, 0x5b22, 0x65a2, 0x6962, 0x54a2, 0x54a2, 0x6872, 0x6782, 0x5492,
+    0x5492, 0x6772, 0x6672, 0x42b2, 0x42b2, 0x42b2, 0x42b2, 0x4b12, 0x4b12, 0x4b12, 0x4b12, 0x41b2,
+    0x41b2, 0x41b2, 0x41b2, 0x5b01, 0x5b01, 0x50b1, 0x50b1, 0x5692, 0x5692, 0x5a42, 0x5a42, 0x5a32,
+    0x5a32, 0x53a2, 0x53a2, 0x5952, 0x5952, 0x5592, 0x5592, 0x4a22, 0x4a22, 0x4a22, 0x4a22, 0x42a2,
+    0x42a2, 0x42a2, 0x42a2, 0xf005, 0x4a12, 0x4a12, 0x41a2, 0x41a2, 0x5a01, 0x5862, 0x40a1, 0x40a1,
+    0x5682, 0x5942, 0x4392, 0x4392, 0x5932, 0x5852, 0x5582, 0x5762, 0x4922, 0x4922, 0x4292, 0x4292,
+    0x5752, 0x5572, 0x4832, 0x4832, 0x4382, 0x4382, 0x5662, 0x5742, 0x5472, 0x5652, 0x5562, 0x5372,
+    0xf005, 0x3912, 0x3912, 0x3912, 0x3912, 0x3192, 0x3192, 0x3192, 0x3192, 0x4901, 0x4901, 0x4091,
+    0x4091, 0x4842, 0x4842, 0x4482, 0x4482, 0x4272, 0x4272, 0x5642, 0x5462, 0x3822, 0x3822, 0x3822,
+    0x3822, 0x3282, 0x3282, 0x3282, 0x3282, 0x3812, 0x3812, 0x3812, 0x3812, 0xf004, 0x4732, 0x4722,
+    0x3712, 0x3712, 0x3172, 0x3172, 0x4552, 0x4701, 0x4071, 0x4632, 0x4362, 0x4542, 0x4452, 0x4622,
+    0x4262, 0x4532, 0xf003, 0x2182, 0x2182, 0x3801, 0x3081, 0x3612, 0x3162, 0x3601, 0x3061, 0xf004,
+    0x4352, 0x4442, 0x3522, 0x3522, 0x3252, 0x3252, 0x3501, 0x3501, 0x2512, 0x2512, 0x2512, 0x2512,
+    0x2152, 0x2152, 0x2152, 0x2152, 0xf003, 0x3432, 0x3342, 0x3051, 0x3422, 0x3242, 0x3332, 0x2412,
+    0x2412, 0xf002, 0x1142, 0x1142, 0x2401, 0x2041, 0xf002, 0x2322, 0x2232, 0x1312, 0x1312, 0xf001,
+    0x1132, 0x1301, 0xf001, 0x1031, 0x1222, 0xf003, 0x0082, 0x008b, 0x008e, 0x0091, 0x0094, 0x0097,
+    0x3ce2, 0x3dd2, 0xf003, 0x0093, 0x3eb2, 0x3be2, 0x3f92, 0x39f2, 0x3ae2, 0x3db2, 0x3bd2, 0xf003,
+    0x3f82, 0x38f2, 0x3cc2, 0x008d, 0x3e82, 0x0090, 0x27f2, 0x27f2, 0xf003, 0x2ad2, 0x2ad2, 0x3da2,
+    0x3cb2, 0x3bc2, 0x36f2, 0x2f62, 0x2f62, 0xf002, 0x28e2, 0x2f52, 0x2d92, 0x29d2, 0xf002, 0x25f2,
+    0x27e2, 0x2ca2, 0x2bb2, 0xf003, 0x2f42, 0x2f42, 0x24f2, 0x24f2, 0x3ac2, 0x36e2, 0x23f2, 0x23f2,
+    0xf002, 0x1f32, 0x1f32, 0x2d82, 0x28d2, 0xf001, 0x1f22, 0x12f2, 0xf002, 0x2e62, 0x2c92, 0x1f01,
+    0x1f01, 0xf002, 0x29c2, 0x2e52, 0x1ba2, 0x1ba2, 0xf002, 0x2d72, 0x27d2, 0x1e42, 0x1e42, 0xf002,
+    0x28c2, 0x26d2, 0x1e32, 0x1e32, 0xf002, 0x19b2, 0x19b2, 0x2b92, 0x2aa2, 0xf001, 0x1ab2, 0x15e2,
+    0xf001, 0x14e2, 0x1c82, 0xf001, 0x1d62, 0x13e2, 0xf001, 0x1e22, 0x1e01, 0xf001, 0x10e1, 0x1d52,
+    0xf001, 0x15d2, 0x1c72, 0xf001, 0x17c2, 0x1d42, 0xf001, 0x1b82, 0x18b2, 0xf001, 0x14d2, 0x1a92,
+    0xf001, 0x19a2, 0x1c62, 0xf001, 0x13d2, 0x1b72, 0xf001, 0x1c52, 0x15c2, 0xf001, 0x1992, 0x1a72,
+    0xf001, 0x17a2, 0x1792, 0xf003, 0x0023, 0x3df2, 0x2de2, 0x2de2, 0x1ff2, 0x1ff2, 0x1ff2, 0x1ff2,
+    0xf001, 0x1fe2, 0x1fd2, 0xf001, 0x1ee2, 0x1fc2, 0xf001, 0x1ed2, 0x1fb2, 0xf001, 0x1bf2, 0x1ec2,
+    0xf002, 0x1cd2, 0x1cd2, 0x2fa2, 0x29e2, 0xf001, 0x1af2, 0x1dc2, 0xf001, 0x1ea2, 0x1e92, 0xf001,
+    0x1f72, 0x1e72, 0xf001, 0x1ef2, 0x1cf2, /* huffTable15[580] */
+    0xf008, 0x0101, 0x0122, 0x0143, 0x0154, 0x0165, 0x0176, 0x017f, 0x0188, 0x0199, 0x01a2, 0x01ab,
+    0x01b4, 0x01bd, 0x01c2, 0x01cb, 0x01d4, 0x01d9, 0x01de, 0x01e3, 0x01e8, 0x01ed, 0x01f2, 0x01f7,
+    0x01fc, 0x0201, 0x0204, 0x0207, 0x020a, 0x020f, 0x0212, 0x0215, 0x021a, 0x021d, 0x0220, 0x8192,
+    0x0223, 0x0226, 0x0229, 0x022c, 0x022f, 0x8822, 0x8282, 0x8812, 0x8182, 0x0232, 0x0235, 0x0238,
+    0x023b, 0x8722, 0x8272, 0x8462, 0x8712, 0x8552, 0x8172, 0x023e, 0x8632, 0x8362, 0x8542, 0x8452,
+    0x8622, 0x8262, 0x8612, 0x0241, 0x8532, 0x7162, 0x7162, 0x8352, 0x8442, 0x7522, 0x7522, 0x7252,
+    0x7252, 0x7512, 0x7512, 0x7152, 0x7152, 0x8501, 0x8051, 0x7432, 0x7432, 0x7342, 0x7342, 0x7422,
+    0x7422, 0x7242, 0x7242, 0x7332, 0x7332, 0x6142, 0x6142, 0x6142, 0x6142, 0x7412, 0x7412, 0x7401,
+    0x7401, 0x6322, 0x6322, 0x6322, 0x6322, 0x6232, 0x6232, 0x6232, 0x6232, 0x7041, 0x7041, 0x7301,
+    0x7301, 0x6312, 0x6312, 0x6312, 0x6312, 0x6132, 0x6132, 0x6132, 0x6132, 0x6031, 0x6031, 0x6031,
+    0x6031, 0x5222, 0x5222, 0x5222, 0x5222, 0x5222, 0x5222, 0x5222, 0x5222, 0x5212, 0x5212, 0x5212,
+    0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122,
+    0x5122, 0x5201, 0x5201, 0x5201, 0x5201, 0x5201, 0x5201, 0x5201, 0x5201, 0x5021, 0x5021, 0x5021,
+    0x5021, 0x5021, 0x5021, 0x5021, 0x5021, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112, 0x3112,
+    0x3112, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011,
+    0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x3000, 0x3000, 0x3000,
+    0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000,
+    0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000,
+    0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0xf005, 0x5ff2, 0x5fe2, 0x5ef2, 0x5fd2, 0x4ee2, 0x4ee2,
+    0x5df2, 0x5fc2, 0x5cf2, 0x5ed2, 0x5de2, 0x5fb2, 0x4bf2, 0x4bf2, 0x5ec2, 0x5ce2, 0x4dd2, 0x4dd2,
+    0x4fa2, 0x4fa2, 0x4af2, 0x4af2, 0x4eb2, 0x4eb2, 0x4be2, 0x4be2, 0x4dc2, 0x4dc2, 0x4cd2, 0x4cd2,
+    0x4f92, 0x4f92, 0xf005, 0x49f2, 0x49f2, 0x4ae2, 0x4ae2, 0x4db2, 0x4db2, 0x4bd2, 0x4bd2, 0x4f82,
+    0x4f82, 0x48f2, 0x48f2, 0x4cc2, 0x4cc2, 0x4e92, 0x4e92, 0x49e2, 0x49e2, 0x4f72, 0x4f72, 0x47f2,
+    0x47f2, 0x4da2, 0x4da2, 0x4ad2, 0x4ad2, 0x4cb2, 0x4cb2, 0x4f62, 0x4f62, 0x5ea2, 0x5f01, 0xf004,
+    0x3bc2, 0x3bc2, 0x36f2, 0x36f2, 0x4e82, 0x48e2, 0x4f52, 0x4d92, 0x35f2, 0x35f2, 0x3e72, 0x3e72,
+    0x37e2, 0x37e2, 0x3ca2, 0x3ca2, 0xf004, 0x3ac2, 0x3ac2, 0x3bb2, 0x3bb2, 0x49d2, 0x4d82, 0x3f42,
+    0x3f42, 0x34f2, 0x34f2, 0x3f32, 0x3f32, 0x33f2, 0x33f2, 0x38d2, 0x38d2, 0xf004, 0x36e2, 0x36e2,
+    0x3f22, 0x3f22, 0x32f2, 0x32f2, 0x4e62, 0x40f1, 0x3f12, 0x3f12, 0x31f2, 0x31f2, 0x3c92, 0x3c92,
+    0x39c2, 0x39c2, 0xf003, 0x3e52, 0x3ba2, 0x3ab2, 0x35e2, 0x3d72, 0x37d2, 0x3e42, 0x34e2, 0xf003,
+    0x3c82, 0x38c2, 0x3e32, 0x3d62, 0x36d2, 0x33e2, 0x3b92, 0x39b2, 0xf004, 0x3e22, 0x3e22, 0x3aa2,
+    0x3aa2, 0x32e2, 0x32e2, 0x3e12, 0x3e12, 0x31e2, 0x31e2, 0x4e01, 0x40e1, 0x3d52, 0x3d52, 0x35d2,
+    0x35d2, 0xf003, 0x3c72, 0x37c2, 0x3d42, 0x3b82, 0x24d2, 0x24d2, 0x38b2, 0x3a92, 0xf003, 0x39a2,
+    0x3c62, 0x36c2, 0x3d32, 0x23d2, 0x23d2, 0x22d2, 0x22d2, 0xf003, 0x3d22, 0x3d01, 0x2d12, 0x2d12,
+    0x2b72, 0x2b72, 0x27b2, 0x27b2, 0xf003, 0x21d2, 0x21d2, 0x3c52, 0x30d1, 0x25c2, 0x25c2, 0x2a82,
+    0x2a82, 0xf002, 0x28a2, 0x2c42, 0x24c2, 0x2b62, 0xf003, 0x26b2, 0x26b2, 0x3992, 0x3c01, 0x2c32,
+    0x2c32, 0x23c2, 0x23c2, 0xf003, 0x2a72, 0x2a72, 0x27a2, 0x27a2, 0x26a2, 0x26a2, 0x30c1, 0x3b01,
+    0xf002, 0x12c2, 0x12c2, 0x2c22, 0x2b52, 0xf002, 0x25b2, 0x2c12, 0x2982, 0x2892, 0xf002, 0x21c2,
+    0x2b42, 0x24b2, 0x2a62, 0xf002, 0x2b32, 0x2972, 0x13b2, 0x13b2, 0xf002, 0x2792, 0x2882, 0x2b22,
+    0x2a52, 0xf002, 0x12b2, 0x12b2, 0x25a2, 0x2b12, 0xf002, 0x11b2, 0x11b2, 0x20b1, 0x2962, 0xf002,
+    0x2692, 0x2a42, 0x24a2, 0x2872, 0xf002, 0x2782, 0x2a32, 0x13a2, 0x13a2, 0xf001, 0x1952, 0x1592,
+    0xf001, 0x1a22, 0x12a2, 0xf001, 0x1a12, 0x11a2, 0xf002, 0x2a01, 0x20a1, 0x1862, 0x1862, 0xf001,
+    0x1682, 0x1942, 0xf001, 0x1492, 0x1932, 0xf002, 0x1392, 0x1392, 0x2772, 0x2901, 0xf001, 0x1852,
+    0x1582, 0xf001, 0x1922, 0x1762, 0xf001, 0x1672, 0x1292, 0xf001, 0x1912, 0x1091, 0xf001, 0x1842,
+    0x1482, 0xf001, 0x1752, 0x1572, 0xf001, 0x1832, 0x1382, 0xf001, 0x1662, 0x1742, 0xf001, 0x1472,
+    0x1801, 0xf001, 0x1081, 0x1652, 0xf001, 0x1562, 0x1732, 0xf001, 0x1372, 0x1642, 0xf001, 0x1701,
+    0x1071, 0xf001, 0x1601, 0x1061, /* huffTable16[651] */
+    0xf008, 0x0101, 0x010a, 0x0113, 0x8ff2, 0x0118, 0x011d, 0x0120, 0x82f2, 0x0131, 0x8f12, 0x81f2,
+    0x0134, 0x0145, 0x0156, 0x0167, 0x0178, 0x0189, 0x019a, 0x01a3, 0x01ac, 0x01b5, 0x01be, 0x01c7,
+    0x01d0, 0x01d9, 0x01de, 0x01e3, 0x01e6, 0x01eb, 0x01f0, 0x8152, 0x01f3, 0x01f6, 0x01f9, 0x01fc,
+    0x8412, 0x8142, 0x01ff, 0x8322, 0x8232, 0x7312, 0x7312, 0x7132, 0x7132, 0x8301, 0x8031, 0x7222,
+    0x7222, 0x6212, 0x6212, 0x6212, 0x6212, 0x6122, 0x6122, 0x6122, 0x6122, 0x6201, 0x6201, 0x6201,
+    0x6201, 0x6021, 0x6021, 0x6021, 0x6021, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112,
+    0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011,
+    0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x3011, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000,
+    0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0xf003, 0x3fe2, 0x3ef2, 0x3fd2, 0x3df2, 0x3fc2, 0x3cf2,
+    0x3fb2, 0x3bf2, 0xf003, 0x2fa2, 0x2fa2, 0x3af2, 0x3f92, 0x39f2, 0x38f2, 0x2f82, 0x2f82, 0xf002,
+    0x2f72, 0x27f2, 0x2f62, 0x26f2, 0xf002, 0x2f52, 0x25f2, 0x1f42, 0x1f42, 0xf001, 0x14f2, 0x13f2,
+    0xf004, 0x10f1, 0x10f1, 0x10f1, 0x10f1, 0x10f1, 0x10f1, 0x10f1, 0x10f1, 0x2f32, 0x2f32, 0x2f32,
+    0x2f32, 0x00e2, 0x00f3, 0x00fc, 0x0105, 0xf001, 0x1f22, 0x1f01, 0xf004, 0x00fa, 0x00ff, 0x0104,
+    0x0109, 0x010c, 0x0111, 0x0116, 0x0119, 0x011e, 0x0123, 0x0128, 0x43e2, 0x012d, 0x0130, 0x0133,
+    0x0136, 0xf004, 0x0128, 0x012b, 0x012e, 0x4d01, 0x0131, 0x0134, 0x0137, 0x4c32, 0x013a, 0x4c12,
+    0x40c1, 0x013d, 0x32e2, 0x32e2, 0x4e22, 0x4e12, 0xf004, 0x43d2, 0x4d22, 0x42d2, 0x41d2, 0x4b32,
+    0x012f, 0x3d12, 0x3d12, 0x44c2, 0x4b62, 0x43c2, 0x47a2, 0x3c22, 0x3c22, 0x42c2, 0x45b2, 0xf004,
+    0x41c2, 0x4c01, 0x4b42, 0x44b2, 0x4a62, 0x46a2, 0x33b2, 0x33b2, 0x4a52, 0x45a2, 0x3b22, 0x3b22,
+    0x32b2, 0x32b2, 0x3b12, 0x3b12, 0xf004, 0x31b2, 0x31b2, 0x4b01, 0x40b1, 0x4962, 0x4692, 0x4a42,
+    0x44a2, 0x4872, 0x4782, 0x33a2, 0x33a2, 0x4a32, 0x4952, 0x3a22, 0x3a22, 0xf004, 0x4592, 0x4862,
+    0x31a2, 0x31a2, 0x4682, 0x4772, 0x3492, 0x3492, 0x4942, 0x4752, 0x3762, 0x3762, 0x22a2, 0x22a2,
+    0x22a2, 0x22a2, 0xf003, 0x2a12, 0x2a12, 0x3a01, 0x30a1, 0x3932, 0x3392, 0x3852, 0x3582, 0xf003,
+    0x2922, 0x2922, 0x2292, 0x2292, 0x3672, 0x3901, 0x2912, 0x2912, 0xf003, 0x2192, 0x2192, 0x3091,
+    0x3842, 0x3482, 0x3572, 0x3832, 0x3382, 0xf003, 0x3662, 0x3822, 0x2282, 0x2282, 0x3742, 0x3472,
+    0x2812, 0x2812, 0xf003, 0x2182, 0x2182, 0x2081, 0x2081, 0x3801, 0x3652, 0x2732, 0x2732, 0xf003,
+    0x2372, 0x2372, 0x3562, 0x3642, 0x2722, 0x2722, 0x2272, 0x2272, 0xf003, 0x3462, 0x3552, 0x2701,
+    0x2701, 0x1712, 0x1712, 0x1712, 0x1712, 0xf002, 0x1172, 0x1172, 0x2071, 0x2632, 0xf002, 0x2362,
+    0x2542, 0x2452, 0x2622, 0xf001, 0x1262, 0x1612, 0xf002, 0x1162, 0x1162, 0x2601, 0x2061, 0xf002,
+    0x1352, 0x1352, 0x2532, 0x2442, 0xf001, 0x1522, 0x1252, 0xf001, 0x1512, 0x1501, 0xf001, 0x1432,
+    0x1342, 0xf001, 0x1051, 0x1422, 0xf001, 0x1242, 0x1332, 0xf001, 0x1401, 0x1041, 0xf004, 0x4ec2,
+    0x0086, 0x3ed2, 0x3ed2, 0x39e2, 0x39e2, 0x4ae2, 0x49d2, 0x2ee2, 0x2ee2, 0x2ee2, 0x2ee2, 0x3de2,
+    0x3de2, 0x3be2, 0x3be2, 0xf003, 0x2eb2, 0x2eb2, 0x2dc2, 0x2dc2, 0x3cd2, 0x3bd2, 0x2ea2, 0x2ea2,
+    0xf003, 0x2cc2, 0x2cc2, 0x3da2, 0x3ad2, 0x3e72, 0x3ca2, 0x2ac2, 0x2ac2, 0xf003, 0x39c2, 0x3d72,
+    0x2e52, 0x2e52, 0x1db2, 0x1db2, 0x1db2, 0x1db2, 0xf002, 0x1e92, 0x1e92, 0x2cb2, 0x2bc2, 0xf002,
+    0x2e82, 0x28e2, 0x2d92, 0x27e2, 0xf002, 0x2bb2, 0x2d82, 0x28d2, 0x2e62, 0xf001, 0x16e2, 0x1c92,
+    0xf002, 0x2ba2, 0x2ab2, 0x25e2, 0x27d2, 0xf002, 0x1e42, 0x1e42, 0x24e2, 0x2c82, 0xf001, 0x18c2,
+    0x1e32, 0xf002, 0x1d62, 0x1d62, 0x26d2, 0x2b92, 0xf002, 0x29b2, 0x2aa2, 0x11e2, 0x11e2, 0xf002,
+    0x14d2, 0x14d2, 0x28b2, 0x29a2, 0xf002, 0x1b72, 0x1b72, 0x27b2, 0x20d1, 0xf001, 0x1e01, 0x10e1,
+    0xf001, 0x1d52, 0x15d2, 0xf001, 0x1c72, 0x17c2, 0xf001, 0x1d42, 0x1b82, 0xf001, 0x1a92, 0x1c62,
+    0xf001, 0x16c2, 0x1d32, 0xf001, 0x1c52, 0x15c2, 0xf001, 0x1a82, 0x18a2, 0xf001, 0x1992, 0x1c42,
+    0xf001, 0x16b2, 0x1a72, 0xf001, 0x1b52, 0x1982, 0xf001, 0x1892, 0x1972, 0xf001, 0x1792, 0x1882,
+    0xf001, 0x1ce2, 0x1dd2, /* huffTable24[705] */
+    0xf009, 0x8fe2, 0x8fe2, 0x8ef2, 0x8ef2, 0x8fd2, 0x8fd2, 0x8df2, 0x8df2, 0x8fc2, 0x8fc2, 0x8cf2,
+    0x8cf2, 0x8fb2, 0x8fb2, 0x8bf2, 0x8bf2, 0x7af2, 0x7af2, 0x7af2, 0x7af2, 0x8fa2, 0x8fa2, 0x8f92,
+    0x8f92, 0x79f2, 0x79f2, 0x79f2, 0x79f2, 0x78f2, 0x78f2, 0x78f2, 0x78f2, 0x8f82, 0x8f82, 0x8f72,
+    0x8f72, 0x77f2, 0x77f2, 0x77f2, 0x77f2, 0x7f62, 0x7f62, 0x7f62, 0x7f62, 0x76f2, 0x76f2, 0x76f2,
+    0x76f2, 0x7f52, 0x7f52, 0x7f52, 0x7f52, 0x75f2, 0x75f2, 0x75f2, 0x75f2, 0x7f42, 0x7f42, 0x7f42,
+    0x7f42, 0x74f2, 0x74f2, 0x74f2, 0x74f2, 0x7f32, 0x7f32, 0x7f32, 0x7f32, 0x73f2, 0x73f2, 0x73f2,
+    0x73f2, 0x7f22, 0x7f22, 0x7f22, 0x7f22, 0x72f2, 0x72f2, 0x72f2, 0x72f2, 0x71f2, 0x71f2, 0x71f2,
+    0x71f2, 0x8f12, 0x8f12, 0x80f1, 0x80f1, 0x9f01, 0x0201, 0x0206, 0x020b, 0x0210, 0x0215, 0x021a,
+    0x021f, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2,
+    0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2,
+    0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x4ff2, 0x0224, 0x0229, 0x0232,
+    0x0237, 0x023a, 0x023f, 0x0242, 0x0245, 0x024a, 0x024d, 0x0250, 0x0253, 0x0256, 0x0259, 0x025c,
+    0x025f, 0x0262, 0x0265, 0x0268, 0x026b, 0x026e, 0x0271, 0x0274, 0x0277, 0x027a, 0x027d, 0x0280,
+    0x0283, 0x0288, 0x028b, 0x028e, 0x0291, 0x0294, 0x0297, 0x029a, 0x029f, 0x94b2, 0x02a4, 0x02a7,
+    0x02aa, 0x93b2, 0x9882, 0x02af, 0x92b2, 0x02b2, 0x02b5, 0x9692, 0x94a2, 0x02b8, 0x9782, 0x9a32,
+    0x93a2, 0x9952, 0x9592, 0x9a22, 0x92a2, 0x91a2, 0x9862, 0x9682, 0x9772, 0x9942, 0x9492, 0x9932,
+    0x9392, 0x9852, 0x9582, 0x9922, 0x9762, 0x9672, 0x9292, 0x9912, 0x9192, 0x9842, 0x9482, 0x9752,
+    0x9572, 0x9832, 0x9382, 0x9662, 0x9822, 0x9282, 0x9812, 0x9742, 0x9472, 0x9182, 0x02bb, 0x9652,
+    0x9562, 0x9712, 0x02be, 0x8372, 0x8372, 0x9732, 0x9722, 0x8272, 0x8272, 0x8642, 0x8642, 0x8462,
+    0x8462, 0x8552, 0x8552, 0x8172, 0x8172, 0x8632, 0x8632, 0x8362, 0x8362, 0x8542, 0x8542, 0x8452,
+    0x8452, 0x8622, 0x8622, 0x8262, 0x8262, 0x8612, 0x8612, 0x8162, 0x8162, 0x9601, 0x9061, 0x8532,
+    0x8532, 0x8352, 0x8352, 0x8442, 0x8442, 0x8522, 0x8522, 0x8252, 0x8252, 0x8512, 0x8512, 0x9501,
+    0x9051, 0x7152, 0x7152, 0x7152, 0x7152, 0x8432, 0x8432, 0x8342, 0x8342, 0x7422, 0x7422, 0x7422,
+    0x7422, 0x7242, 0x7242, 0x7242, 0x7242, 0x7332, 0x7332, 0x7332, 0x7332, 0x7412, 0x7412, 0x7412,
+    0x7412, 0x7142, 0x7142, 0x7142, 0x7142, 0x8401, 0x8401, 0x8041, 0x8041, 0x7322, 0x7322, 0x7322,
+    0x7322, 0x7232, 0x7232, 0x7232, 0x7232, 0x6312, 0x6312, 0x6312, 0x6312, 0x6312, 0x6312, 0x6312,
+    0x6312, 0x6132, 0x6132, 0x6132, 0x6132, 0x6132, 0x6132, 0x6132, 0x6132, 0x7301, 0x7301, 0x7301,
+    0x7301, 0x7031, 0x7031, 0x7031, 0x7031, 0x6222, 0x6222, 0x6222, 0x6222, 0x6222, 0x6222, 0x6222,
+    0x6222, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5212,
+    0x5212, 0x5212, 0x5212, 0x5212, 0x5212, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122,
+    0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x5122, 0x6201, 0x6201, 0x6201,
+    0x6201, 0x6201, 0x6201, 0x6201, 0x6201, 0x6021, 0x6021, 0x6021, 0x6021, 0x6021, 0x6021, 0x6021,
+    0x6021, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112,
+    0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112,
+    0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4112, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4101,
+    0x4101, 0x4101, 0x4101, 0x4101, 0x4101, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011,
+    0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011,
+    0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011, 0x4011,
+    0x4011, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000,
+    0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000,
+    0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0x4000, 0xf002, 0x2ee2, 0x2ed2,
+    0x2de2, 0x2ec2, 0xf002, 0x2ce2, 0x2dd2, 0x2eb2, 0x2be2, 0xf002, 0x2dc2, 0x2cd2, 0x2ea2, 0x2ae2,
+    0xf002, 0x2db2, 0x2bd2, 0x2cc2, 0x2e92, 0xf002, 0x29e2, 0x2da2, 0x2ad2, 0x2cb2, 0xf002, 0x2bc2,
+    0x2e82, 0x28e2, 0x2d92, 0xf002, 0x29d2, 0x2e72, 0x27e2, 0x2ca2, 0xf002, 0x2ac2, 0x2bb2, 0x2d82,
+    0x28d2, 0xf003, 0x3e01, 0x30e1, 0x2d01, 0x2d01, 0x16e2, 0x16e2, 0x16e2, 0x16e2, 0xf002, 0x2e62,
+    0x2c92, 0x19c2, 0x19c2, 0xf001, 0x1e52, 0x1ab2, 0xf002, 0x15e2, 0x15e2, 0x2ba2, 0x2d72, 0xf001,
+    0x17d2, 0x14e2, 0xf001, 0x1c82, 0x18c2, 0xf002, 0x2e42, 0x2e22, 0x1e32, 0x1e32, 0xf001, 0x1d62,
+    0x16d2, 0xf001, 0x13e2, 0x1b92, 0xf001, 0x19b2, 0x1aa2, 0xf001, 0x12e2, 0x1e12, 0xf001, 0x11e2,
+    0x1d52, 0xf001, 0x15d2, 0x1c72, 0xf001, 0x17c2, 0x1d42, 0xf001, 0x1b82, 0x18b2, 0xf001, 0x14d2,
+    0x1a92, 0xf001, 0x19a2, 0x1c62, 0xf001, 0x16c2, 0x1d32, 0xf001, 0x13d2, 0x1d22, 0xf001, 0x12d2,
+    0x1d12, 0xf001, 0x1b72, 0x17b2, 0xf001, 0x11d2, 0x1c52, 0xf001, 0x15c2, 0x1a82, 0xf001, 0x18a2,
+    0x1992, 0xf001, 0x1c42, 0x14c2, 0xf001, 0x1b62, 0x16b2, 0xf002, 0x20d1, 0x2c01, 0x1c32, 0x1c32,
+    0xf001, 0x13c2, 0x1a72, 0xf001, 0x17a2, 0x1c22, 0xf001, 0x12c2, 0x1b52, 0xf001, 0x15b2, 0x1c12,
+    0xf001, 0x1982, 0x1892, 0xf001, 0x11c2, 0x1b42, 0xf002, 0x20c1, 0x2b01, 0x1b32, 0x1b32, 0xf002,
+    0x20b1, 0x2a01, 0x1a12, 0x1a12, 0xf001, 0x1a62, 0x16a2, 0xf001, 0x1972, 0x1792, 0xf002, 0x20a1,
+    0x2901, 0x1091, 0x1091, 0xf001, 0x1b22, 0x1a52, 0xf001, 0x15a2, 0x1b12, 0xf001, 0x11b2, 0x1962,
+    0xf001, 0x1a42, 0x1872, 0xf001, 0x1801, 0x1081, 0xf001, 0x1701, 0x1071,
+];
+
+const HUFF_OFFSET_01: u16 = 0;
+const HUFF_OFFSET_02: u16 = 9 + HUFF_OFFSET_01;
+const HUFF_OFFSET_03: u16 = 65 + HUFF_OFFSET_02;
+const HUFF_OFFSET_05: u16 = 65 + HUFF_OFFSET_03;
+const HUFF_OFFSET_06: u16 = 257 + HUFF_OFFSET_05;
+const HUFF_OFFSET_07: u16 = 129 + HUFF_OFFSET_06;
+const HUFF_OFFSET_08: u16 = 110 + HUFF_OFFSET_07;
+const HUFF_OFFSET_09: u16 = 280 + HUFF_OFFSET_08;
+const HUFF_OFFSET_10: u16 = 93 + HUFF_OFFSET_09;
+const HUFF_OFFSET_11: u16 = 320 + HUFF_OFFSET_10;
+const HUFF_OFFSET_12: u16 = 296 + HUFF_OFFSET_11;
+const HUFF_OFFSET_13: u16 = 185 + HUFF_OFFSET_12;
+const HUFF_OFFSET_15: u16 = 497 + HUFF_OFFSET_13;
+const HUFF_OFFSET_16: u16 = 580 + HUFF_OFFSET_15;
+const HUFF_OFFSET_24: u16 = 651 + HUFF_OFFSET_16;
+
+const HUFF_TAB_OFFSET: [u16; HUFF_PAIRTABS] = [
+    0,
+    HUFF_OFFSET_01,
+    HUFF_OFFSET_02,
+    HUFF_OFFSET_03,
+    0,
+    HUFF_OFFSET_05,
+    HUFF_OFFSET_06,
+    HUFF_OFFSET_07,
+    HUFF_OFFSET_08,
+    HUFF_OFFSET_09,
+    HUFF_OFFSET_10,
+    HUFF_OFFSET_11,
+    HUFF_OFFSET_12,
+    HUFF_OFFSET_13,
+    0,
+    HUFF_OFFSET_15,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_16,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+    HUFF_OFFSET_24,
+];
+
+const HUFF_TAB_LOOKUP: [HuffTabLookup; HUFF_PAIRTABS] = [
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::NoBits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::OneShot,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::OneShot,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::OneShot,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::InvalidTab,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::OneShot,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::OneShot,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::InvalidTab,
+    },
+    HuffTabLookup {
+        lin_bits: 0,
+        tab_type: HuffTabType::LoopNoLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 1,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 2,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 3,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 4,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 6,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 8,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 10,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 13,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 4,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 5,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 6,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 7,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 8,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 9,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 11,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+    HuffTabLookup {
+        lin_bits: 13,
+        tab_type: HuffTabType::LoopLinbits,
+    },
+];
+
+const QUAD_TAB_OFFSET: [i32; 2] = [0, 64];
+const QUAD_TAB_MAX_BITS: [i32; 2] = [6, 4];
+
+pub fn decode_huffman_pairs(
+    mut xy: &mut [i32],
+    tab_idx: i32,
+    mut bits_left: i32,
+    buf: &[u8],
+    bit_offset: i32,
+) -> i32 {
+    let mut x: i32;
+    let mut y: i32;
+    let mut cached_bits: i32;
+    let mut pad_bits: i32;
+    let mut len: i32;
+    let mut max_bits: i32;
+    let mut min_bits: i32;
+    let mut cw: u16;
+    let mut cache: u32;
+
+    let mut buf_idx = 0;
+
+    let n_vals = xy.len();
+
+    if xy.is_empty() {
+        return 0;
+    }
+
+    if bits_left < 0 {
+        return -1;
+    }
+    let start_bits: i32 = bits_left;
+    if tab_idx >= HUFF_PAIRTABS as i32 {
+        return -1;
+    }
+
+    // Uzyskiwanie dostępu do tablic huffmana (zakładam nazwy z Twojego kodu)
+    let t_base = &HUFF_TABLE[HUFF_TAB_OFFSET[tab_idx as usize] as usize..];
+    let t_base_idx = 0;
+    let lin_bits: i32 = HUFF_TAB_LOOKUP[tab_idx as usize].lin_bits;
+    let tab_type: HuffTabType = HUFF_TAB_LOOKUP[tab_idx as usize].tab_type;
+
+    /* Walidacja - zachowanie logiki z log_i */
+    if (n_vals & 0x01) != 0 {
+        return -1;
+    }
+    if tab_idx >= HUFF_PAIRTABS as i32 {
+        return -1;
+    }
+    if tab_idx < 0 {
+        return -1;
+    }
+    if tab_type == HuffTabType::InvalidTab {
+        return -1;
+    }
+
+    /* initially fill cache with any partial byte */
+    cache = 0;
+    cached_bits = (8 - bit_offset) & 0x07;
+    if cached_bits != 0 {
+        cache = (buf[buf_idx] as u32) << (32 - cached_bits);
+        buf_idx += 1;
+    }
+    bits_left -= cached_bits;
+
+    match tab_type {
+        HuffTabType::NoBits => {
+            for i in (0..n_vals).step_by(2) {
+                xy[i] = 0;
+                xy[i + 1] = 0;
+            }
+            0
+        }
+        HuffTabType::OneShot => {
+            max_bits = (t_base[t_base_idx] & 0x000f) as i32;
+            let t_base_one_shot = &t_base[1..];
+            pad_bits = 0;
+
+            while !xy.is_empty() {
+                if bits_left >= 16 {
+                    cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                    buf_idx += 1;
+                    cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+                    buf_idx += 1;
+                    cached_bits += 16;
+                    bits_left -= 16;
+                } else {
+                    if cached_bits + bits_left <= 0 {
+                        return -1;
+                    }
+                    if bits_left > 0 {
+                        cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                        buf_idx += 1;
+                    }
+                    if bits_left > 8 {
+                        cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+                        buf_idx += 1;
+                    }
+                    cached_bits += bits_left;
+                    bits_left = 0;
+
+                    cache &= (0x80000000u32 as i32 >> (cached_bits - 1)) as u32;
+                    pad_bits = 11;
+                    cached_bits += pad_bits;
+                }
+
+                while !xy.is_empty() && cached_bits >= 11 {
+                    cw = t_base_one_shot[(cache >> (32 - max_bits)) as usize];
+
+                    len = ((cw >> 12) & 0x000f) as i32;
+                    cached_bits -= len;
+                    cache <<= len;
+
+                    x = ((cw >> 4) & 0x000f) as i32;
+                    if x != 0 {
+                        x |= (cache & 0x80000000) as i32;
+                        cache <<= 1;
+                        cached_bits -= 1;
+                    }
+
+                    y = ((cw >> 8) & 0x000f) as i32;
+                    if y != 0 {
+                        y |= (cache & 0x80000000) as i32;
+                        cache <<= 1;
+                        cached_bits -= 1;
+                    }
+
+                    if cached_bits < pad_bits {
+                        return -1;
+                    }
+
+                    xy[0] = x;
+                    xy[1] = y;
+                    xy = &mut xy[2..];
+                }
+            }
+            bits_left += cached_bits - pad_bits;
+            start_bits - bits_left
+        }
+        HuffTabType::LoopLinbits | HuffTabType::LoopNoLinbits => {
+            let mut t_curr_idx = 0;
+            pad_bits = 0;
+            while !xy.is_empty() {
+                if bits_left >= 16 {
+                    cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                    buf_idx += 1;
+                    cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+                    buf_idx += 1;
+                    cached_bits += 16;
+                    bits_left -= 16;
+                } else {
+                    if cached_bits + bits_left <= 0 {
+                        return -1;
+                    }
+                    if bits_left > 0 {
+                        cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                        buf_idx += 1;
+                    }
+                    if bits_left > 8 {
+                        cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+                        buf_idx += 1;
+                    }
+                    cached_bits += bits_left;
+                    bits_left = 0;
+                    cache &= (0x80000000u32 as i32 >> (cached_bits - 1)) as u32;
+                    pad_bits = 11;
+                    cached_bits += pad_bits;
+                }
+
+                while !xy.is_empty() && cached_bits >= 11 {
+                    max_bits = (t_base[t_curr_idx] & 0x000f) as i32;
+                    cw = t_base[(((cache >> (32 - max_bits)) + 1) as usize) + t_curr_idx];
+                    len = ((cw >> 12) & 0x000f) as i32;
+
+                    if len == 0 {
+                        cached_bits -= max_bits;
+                        cache <<= max_bits;
+                        t_curr_idx += cw as usize;
+                        continue;
+                    }
+                    cached_bits -= len;
+                    cache <<= len;
+
+                    x = ((cw >> 4) & 0x000f) as i32;
+                    y = ((cw >> 8) & 0x000f) as i32;
+
+                    if x == 15 && tab_type == HuffTabType::LoopLinbits {
+                        min_bits = lin_bits + 1 + (if y != 0 { 1 } else { 0 });
+                        if cached_bits + bits_left < min_bits {
+                            return -1;
+                        }
+                        while cached_bits < min_bits {
+                            cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                            buf_idx += 1;
+                            cached_bits += 8;
+                            bits_left -= 8;
+                        }
+                        if bits_left < 0 {
+                            cached_bits += bits_left;
+                            bits_left = 0;
+                            cache &= (0x80000000u32 as i32 >> (cached_bits - 1)) as u32;
+                        }
+                        x += (cache >> (32 - lin_bits as u32)) as i32;
+                        cached_bits -= lin_bits;
+                        cache <<= lin_bits;
+                    }
+                    if x != 0 {
+                        x |= (cache & 0x80000000) as i32;
+                        cache <<= 1;
+                        cached_bits -= 1;
+                    }
+
+                    if y == 15 && tab_type == HuffTabType::LoopLinbits {
+                        min_bits = lin_bits + 1;
+                        if cached_bits + bits_left < min_bits {
+                            return -1;
+                        }
+                        while cached_bits < min_bits {
+                            cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                            buf_idx += 1;
+                            cached_bits += 8;
+                            bits_left -= 8;
+                        }
+                        if bits_left < 0 {
+                            cached_bits += bits_left;
+                            bits_left = 0;
+                            cache &= (0x80000000u32 as i32 >> (cached_bits - 1)) as u32;
+                        }
+                        y += (cache >> (32 - lin_bits as u32)) as i32;
+                        cached_bits -= lin_bits;
+                        cache <<= lin_bits;
+                    }
+                    if y != 0 {
+                        y |= (cache & 0x80000000) as i32;
+                        cache <<= 1;
+                        cached_bits -= 1;
+                    }
+
+                    if cached_bits < pad_bits {
+                        return -1;
+                    }
+
+                    xy[0] = x;
+                    xy[1] = y;
+                    xy = &mut xy[2..];
+                    t_curr_idx = 0;
+                }
+            }
+            bits_left += cached_bits - pad_bits;
+            start_bits - bits_left
+        }
+        _ => -1,
+    }
+}
+
+/* tables for quadruples
+ * format 0xAB
+ *  A = length of codeword
+ *  B = codeword
+ */
+const QUAD_TABLE: [u8; 64 + 16] = [
+    /* table A */
+    0x6b, 0x6f, 0x6d, 0x6e, 0x67, 0x65, 0x59, 0x59, 0x56, 0x56, 0x53, 0x53, 0x5a, 0x5a, 0x5c, 0x5c,
+    0x42, 0x42, 0x42, 0x42, 0x41, 0x41, 0x41, 0x41, 0x44, 0x44, 0x44, 0x44, 0x48, 0x48, 0x48, 0x48,
+    0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10,
+    0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10,
+    /* table B */
+    0x4f, 0x4e, 0x4d, 0x4c, 0x4b, 0x4a, 0x49, 0x48, 0x47, 0x46, 0x45, 0x44, 0x43, 0x42, 0x41, 0x40,
+];
+
+/***********************************************************************************************************************
+ * Function:    DecodeHuffmanQuads
+ *
+ * Description: decode 4-way vector Huffman codes in the "count1" region of spectrum
+ *
+ * Inputs:      valid BitStreamInfo struct, pointing to start of quadword codes
+ *              pointer to vwxy buffer to received decoded values
+ *              maximum number of codewords to decode
+ *              index of quadword table (0 = table A, 1 = table B)
+ *              number of bits remaining in bitstream
+ *
+ * Outputs:     quadruples of decoded coefficients in vwxy
+ *              updated BitStreamInfo struct
+ *
+ * Return:      index of the first "zero_part" value (index of the first sample
+ *                of the quad word after which all samples are 0)
+ *
+ * Notes:        si_huff.bit tests every vwxy output in both quad tables
+ **********************************************************************************************************************/
+// no improvement with section=data
+pub fn decode_huffman_quads(
+    vwxy: &mut [i32],
+    n_vals: i32,
+    tab_idx: i32,
+    mut bits_left: i32,
+    buf: &[u8],
+    bit_offset: i32,
+) -> i32 {
+    let mut v: i32;
+    let mut w: i32;
+    let mut x: i32;
+    let mut y: i32;
+    let mut len: i32;
+    let mut cached_bits: i32;
+    let mut pad_bits: i32;
+    let mut cache: u32;
+    let mut cw: u8;
+
+    if bits_left <= 0 {
+        return 0;
+    }
+
+    let mut buf_idx = 0;
+
+    let mut vwxy_idx = 0;
+
+    // Pobieranie bazy tabeli i parametrów (zakładamy dostęp do globalnych tablic)
+    // tBase = (unsigned char *) quadTable + quadTabOffset[tabIdx];
+    let t_base = &QUAD_TABLE[QUAD_TAB_OFFSET[tab_idx as usize] as usize..];
+    let max_bits: i32 = QUAD_TAB_MAX_BITS[tab_idx as usize];
+
+    /* Inicjalizacja cache partial byte */
+    cache = 0;
+    cached_bits = (8 - bit_offset) & 0x07;
+    if cached_bits != 0 {
+        cache = (buf[buf_idx] as u32) << (32 - cached_bits);
+        buf_idx += 1;
+    }
+    bits_left -= cached_bits;
+
+    let mut i = 0;
+    pad_bits = 0;
+
+    while i < (n_vals - 3) {
+        /* Uzupełnianie cache - ładowanie 16 bitów */
+        if bits_left >= 16 {
+            cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+            buf_idx += 1;
+            cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+            buf_idx += 1;
+            cached_bits += 16;
+            bits_left -= 16;
+        } else {
+            /* Ostatnia partia bitów, wyrównanie i padBits */
+            if cached_bits + bits_left <= 0 {
+                return i;
+            }
+            if bits_left > 0 {
+                cache |= (buf[buf_idx] as u32) << (24 - cached_bits);
+                buf_idx += 1;
+            }
+            if bits_left > 8 {
+                cache |= (buf[buf_idx] as u32) << (16 - cached_bits);
+                buf_idx += 1;
+            }
+            cached_bits += bits_left;
+            bits_left = 0;
+
+            // cache &= (signed int) 0x80000000 >> (cachedBits - 1);
+            // W Rust przesunięcie i32 jest arytmetyczne (zachowuje bit znaku)
+            let mask = ((0x80000000u32 as i32) >> (cached_bits.wrapping_sub(1))) as u32;
+            cache &= mask;
+
+            pad_bits = 10;
+            cached_bits += pad_bits;
+        }
+
+        /* Dekodowanie kwadratów */
+        while i < (n_vals - 3) && cached_bits >= 10 {
+            // cw = pgm_read_byte(&tBase[cache >> (32 - maxBits)]);
+            cw = t_base[(cache >> (32 - max_bits)) as usize];
+
+            len = ((cw >> 4) & 0x0f) as i32;
+            cached_bits -= len;
+            cache <<= len;
+
+            // V
+            v = ((cw >> 3) & 0x01) as i32;
+            if v != 0 {
+                v |= (cache & 0x80000000) as i32;
+                cache <<= 1;
+                cached_bits -= 1;
+            }
+
+            // W
+            w = ((cw >> 2) & 0x01) as i32;
+            if w != 0 {
+                w |= (cache & 0x80000000) as i32;
+                cache <<= 1;
+                cached_bits -= 1;
+            }
+
+            // X
+            x = ((cw >> 1) & 0x01) as i32;
+            if x != 0 {
+                x |= (cache & 0x80000000) as i32;
+                cache <<= 1;
+                cached_bits -= 1;
+            }
+
+            // Y
+            y = (cw & 0x01) as i32;
+            if y != 0 {
+                y |= (cache & 0x80000000) as i32;
+                cache <<= 1;
+                cached_bits -= 1;
+            }
+
+            if cached_bits < pad_bits {
+                return i;
+            }
+
+            // Zapis do bufora i inkrementacja wskaźnika (jak vwxy++)
+            vwxy[vwxy_idx] = v;
+            vwxy[vwxy_idx + 1] = w;
+            vwxy[vwxy_idx + 2] = x;
+            vwxy[vwxy_idx + 3] = y;
+            i += 4;
+            vwxy_idx += 4;
+        }
+    }
+
+    i
+}
+
+/***********************************************************************************************************************
+ * Function:    DecodeHuffman
+ *
+ * Description: decode one granule, one channel worth of Huffman codes
+ *
+ * Inputs:      MP3DecInfo structure filled by UnpackFrameHeader(), UnpackSideInfo(),
+ *                and UnpackScaleFactors() (for this granule)
+ *              buffer pointing to start of Huffman data in MP3 frame
+ *              pointer to bit offset (0-7) indicating starting bit in buf[0]
+ *              number of bits in the Huffman data section of the frame
+ *                (could include padding bits)
+ *              index of current granule and channel
+ *
+ * Outputs:     decoded coefficients in hi->huffDecBuf[ch] (hi pointer in mp3DecInfo)
+ *              updated bitOffset
+ *
+ * Return:      length (in bytes) of Huffman codes
+ *              bitOffset also returned in parameter (0 = MSB, 7 = LSB of
+ *                byte located at buf + offset)
+ *              -1 if null input pointers, huffBlockBits < 0, or decoder runs
+ *                out of bits prematurely (invalid bitstream)
+ **********************************************************************************************************************/
+
+#[allow(clippy::too_many_arguments)]
+pub fn decode_huffman(
+    mut buf: &[u8],
+    bit_offset: &mut i32,
+    huff_block_bits: i32,
+    gr: GranuleIndex,
+    ch: ChannelIndex,
+    m_huffman_info: &mut HuffmanInfo,
+    m_sfband_table: &SFBandTable, // SFBandTable_t*
+    m_side_info_sub: &mut [[SideInfoSub; MAX_NCHAN]; MAX_NGRAN],
+    m_mpegversion: MPEGVersion,
+) -> i32 {
+    let mut r_end = [0; 4];
+    let r1_start;
+    let r2_start;
+    let w;
+    let mut bits_left;
+
+    let sis = &m_side_info_sub[gr as usize][ch as usize];
+
+    if huff_block_bits < 0 {
+        return -1;
+    }
+
+    let start_buf_len = buf.len();
+
+    if sis.win_switch_flag != 0 && sis.blockType == BlockType::Short {
+        // Short blocks lub mixed blocks
+        if sis.mixedBlock == 0 {
+            // Czyste short blocks
+            r1_start = m_sfband_table.s[((sis.region0Count + 1) / 3) as usize] * 3;
+        } else {
+            // Mixed block
+            if m_mpegversion == MPEGVersion::MPEG1 {
+                r1_start = m_sfband_table.l[(sis.region0Count + 1) as usize];
+            } else {
+                // MPEG2 / MPEG2.5 – spec wymaga specjalnego obliczenia
+                w = m_sfband_table.s[4] - m_sfband_table.s[3];
+                r1_start = m_sfband_table.l[6] + 2 * w;
+            }
+        }
+        r2_start = MAX_NSAMP as i32; // short blocks nie mają regionu 2
+    } else {
+        // Long blocks
+        r1_start = m_sfband_table.l[(sis.region0Count + 1) as usize];
+        r2_start = m_sfband_table.l[(sis.region0Count + 1 + sis.region1Count + 1) as usize];
+    }
+
+    /* offset rEnd index by 1 so first region = rEnd[1] - rEnd[0], etc. */
+    r_end[3] = if MAX_NSAMP < (2 * sis.n_bigvals as usize) {
+        MAX_NSAMP as i32
+    } else {
+        2 * sis.n_bigvals
+    };
+    r_end[2] = if r2_start < r_end[3] {
+        r2_start
+    } else {
+        r_end[3]
+    };
+    r_end[1] = if r1_start < r_end[3] {
+        r1_start
+    } else {
+        r_end[3]
+    };
+    r_end[0] = 0;
+
+    /* rounds up to first all-zero pair (we don't check last pair for (x,y) == (non-zero, zero)) */
+    m_huffman_info.non_zero_bound[ch as usize] = r_end[3];
+
+    /* decode Huffman pairs (rEnd[i] are always even numbers) */
+    bits_left = huff_block_bits;
+
+    let mut bits_used;
+    for i in 0..3 {
+        bits_used = 
+            decode_huffman_pairs(
+                &mut m_huffman_info.huff_dec_buf[ch as usize]
+                    [r_end[i] as usize..r_end[i] as usize + (r_end[i + 1] - r_end[i]) as usize],
+                sis.tableSelect[i],
+                bits_left,
+                buf,
+                *bit_offset,
+            );
+        if bits_used < 0 || bits_used > bits_left
+        {
+            /* error - overran end of bitstream */
+            return -1;
+        }
+
+        /* update bitstream position */
+        buf = &buf[(bits_used + *bit_offset) as usize >> 3..];
+        *bit_offset = (bits_used + *bit_offset) & 0x07;
+        bits_left -= bits_used;
+    }
+
+    /* decode Huffman quads (if any) */
+    m_huffman_info.non_zero_bound[ch as usize] += decode_huffman_quads(
+        &mut m_huffman_info.huff_dec_buf[ch as usize][r_end[3] as usize..],
+        MAX_NSAMP as i32 - r_end[3],
+        sis.count1TableSelect,
+        bits_left,
+        buf,
+        *bit_offset,
+    );
+
+    assert!(m_huffman_info.non_zero_bound[ch as usize] <= MAX_NSAMP as i32);
+
+    for i in m_huffman_info.non_zero_bound[ch as usize]..MAX_NSAMP as i32 {
+        m_huffman_info.huff_dec_buf[ch as usize][i as usize] = 0;
+    }
+    /* If bits used for 576 samples < huffBlockBits, then the extras are considered
+     *  to be stuffing bits (throw away, but need to return correct bitstream position)
+     */
+    buf = &buf[(bits_left + *bit_offset) as usize >> 3..];
+    *bit_offset = (bits_left + *bit_offset) & 0x07;
+
+    start_buf_len as i32- buf.len() as i32
+}
@@ -0,0 +1,588 @@
+use crate::mp3_decoder::{
+    BLOCK_SIZE, BlockType, ChannelIndex, GranuleIndex, HuffmanInfo, IMDCT_WIN, IMDCTInfo,
+    MAX_NSAMP, MPEGVersion, NBANDS, SFBandTable, SideInfoSub, freq_invert_rescale, idct_9,
+    imdct_12, mulshift_32, win_previous,
+};
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct BlockCount {
+    nBlocksLong: i32,
+    nBlocksTotal: i32,
+    nBlocksPrev: i32,
+    prev_type: BlockType,
+    prevWinSwitch: i32,
+    currWinSwitch: i32,
+    gbIn: i32,
+    gbOut: i32,
+}
+
+/***********************************************************************************************************************
+ * Function:    IMDCT36
+ *
+ * Description: 36-point modified DCT, with windowing and overlap-add (50% overlap)
+ *
+ * Inputs:      vector of 18 coefficients (N/2 inputs produces N outputs, by symmetry)
+ *              overlap part of last IMDCT (9 samples - see output comments)
+ *              window type (0,1,2,3) of current and previous block
+ *              current block index (for deciding whether to do frequency inversion)
+ *              number of guard bits in input vector
+ *
+ * Outputs:     18 output samples, after windowing and overlap-add with last frame
+ *              second half of (unwindowed) 36-point IMDCT - save for next time
+ *                only save 9 xPrev samples, using symmetry (see WinPrevious())
+ *
+ * Notes:       this is Ken's hyper-fast algorithm, including symmetric sin window
+ *                optimization, if applicable
+ *              total number of multiplies, general case:
+ *                2*10 (idct9) + 9 (last stage imdct) + 36 (for windowing) = 65
+ *              total number of multiplies, btCurr == 0 && btPrev == 0:
+ *                2*10 (idct9) + 9 (last stage imdct) + 18 (for windowing) = 47
+ *
+ *              blockType == 0 is by far the most common case, so it should be
+ *                possible to use the fast path most of the time
+ *              this is the fastest known algorithm for performing
+ *                long IMDCT + windowing + overlap-add in MP3
+ *
+ * Return:      mOut (OR of abs(y) for all y calculated here)
+ **********************************************************************************************************************/
+// barely faster in RAM
+const C18: [u32; 9] = [
+    0x7f834ed0, 0x7ba3751d, 0x7401e4c1, 0x68d9f964, 0x5a82799a, 0x496af3e2, 0x36185aee, 0x2120fb83,
+    0x0b27eb5c,
+];
+const FAST_WIN36: [u32; 18] = [
+    0x42aace8b, 0xc2e92724, 0x47311c28, 0xc95f619a, 0x4a868feb, 0xd0859d8c, 0x4c913b51, 0xd8243ea0,
+    0x4d413ccc, 0xe0000000, 0x4c913b51, 0xe7dbc161, 0x4a868feb, 0xef7a6275, 0x47311c28, 0xf6a09e67,
+    0x42aace8b, 0xfd16d8dd,
+];
+pub fn imdct36(
+    x_curr: &mut [i32; BLOCK_SIZE],
+    x_prev: &mut [i32; BLOCK_SIZE / 2],
+    y: &mut [i32],
+    bt_curr: BlockType,
+    bt_prev: BlockType,
+    block_idx: i32,
+    gb: i32,
+) -> i32 {
+    let mut acc1 = 0;
+    let mut acc2 = 0;
+    let es;
+    let mut x_buf: [i32; BLOCK_SIZE] = [0; 18];
+    let mut x_prev_win: [i32; BLOCK_SIZE] = [0; 18];
+    let mut c;
+    let mut xo;
+    let mut xe;
+    let mut s;
+    let mut d;
+    let mut t;
+    let mut y_lo;
+    let mut y_hi;
+    let mut x_prev_idx = 0;
+    /* 7 gb is always adequate for antialias + accumulator loop + idct9 */
+    if gb < 7 {
+        /* rarely triggered - 5% to 10% of the time on normal clips (with Q25 input) */
+        es = 7 - gb;
+        for (i, &[x_lo, x_hi]) in x_curr.as_chunks::<2>().0.iter().enumerate().rev() {
+            acc1 = (x_hi >> es) - acc1;
+            acc2 = acc1 - acc2;
+            acc1 = (x_lo >> es) - acc1;
+            x_buf[i + 9] = acc2; /* odd */
+            x_buf[i] = acc1; /* even */
+            x_prev[i] >>= es;
+        }
+    } else {
+        es = 0;
+        /* max gain = 18, assume adequate guard bits */
+        for (i, &[x_lo, x_hi]) in x_curr.as_chunks::<2>().0.iter().enumerate().rev() {
+            acc1 = x_hi - acc1;
+            acc2 = acc1 - acc2;
+            acc1 = x_lo - acc1;
+            x_buf[i + 9] = acc2; /* odd */
+            x_buf[i] = acc1; /* even */
+        }
+    }
+    /* xEven[0] and xOdd[0] scaled by 0.5 */
+    x_buf[9] >>= 1;
+    x_buf[0] >>= 1;
+    let (spliced_buf, _) = x_buf.as_chunks_mut::<9>();
+    /* do 9-point IDCT on even and odd */
+    idct_9(&mut spliced_buf[0]); /* even */
+    idct_9(&mut spliced_buf[1]); /* odd */
+    let mut m_out = 0;
+    if bt_prev == BlockType::Normal && bt_curr == BlockType::Normal {
+        /* fast path - use symmetry of sin window to reduce windowing multiplies to 18 (N/2) */
+        for (i, e) in FAST_WIN36.chunks_exact(2).enumerate() {
+            c = C18[8 - i];
+            xo = x_buf[17 - i];
+            xe = x_buf[8 - i];
+            /* gain 2 int bits here */
+            xo = mulshift_32(c as i32, xo); /* 2*c18*xOdd (mul by 2 implicit in scaling)  */
+            xe >>= 2;
+
+            s = -(x_prev[x_prev_idx]); /* sum from last block (always at least 2 guard bits) */
+            d = -(xe - xo); /* gain 2 int bits, don't shift xo (effective << 1 to eat sign bit, << 1 for mul by 2) */
+            x_prev[x_prev_idx] = xe + xo; /* symmetry - xPrev[i] = xPrev[17-i] for long blocks */
+            x_prev_idx += 1;
+            t = s - d;
+
+            y_lo = d + (mulshift_32(t, e[0] as i32) << 2);
+            y_hi = s + (mulshift_32(t, e[1] as i32) << 2);
+            y[(i) * NBANDS] = y_lo;
+            y[(17 - i) * NBANDS] = y_hi;
+            m_out |= y_lo.abs();
+            m_out |= y_hi.abs();
+        }
+    } else {
+        /* slower method - either prev or curr is using window type != 0 so do full 36-point window
+         * output xPrevWin has at least 3 guard bits (xPrev has 2, gain 1 in WinPrevious)
+         */
+        win_previous(x_prev, &mut x_prev_win, bt_prev);
+
+        let wp = IMDCT_WIN[bt_curr as usize];
+        for i in 0..9 {
+            c = C18[8 - i];
+            xo = x_buf[17 - i];
+            xe = x_buf[8 - i];
+            /* gain 2 int bits here */
+            xo = mulshift_32(c as i32, xo); /* 2*c18*xOdd (mul by 2 implicit in scaling)  */
+            xe >>= 2;
+
+            d = xe - xo;
+            x_prev[x_prev_idx] = xe + xo; /* symmetry - xPrev[i] = xPrev[17-i] for long blocks */
+            x_prev_idx += 1;
+
+            y_lo = (x_prev_win[i] + mulshift_32(d, wp[i] as i32)) << 2;
+            y_hi = (x_prev_win[17 - i] + mulshift_32(d, wp[17 - i] as i32)) << 2;
+            y[(i) * NBANDS] = y_lo;
+            y[(17 - i) * NBANDS] = y_hi;
+            m_out |= y_lo.abs();
+            m_out |= y_hi.abs();
+        }
+    }
+
+    m_out |= freq_invert_rescale(y, x_prev, block_idx, es);
+
+    m_out
+}
+
+/***********************************************************************************************************************
+ * Function:    AntiAlias
+ *
+ * Description: smooth transition across DCT block boundaries (every 18 coefficients)
+ *
+ * Inputs:      vector of dequantized coefficients, length = (nBfly+1) * 18
+ *              number of "butterflies" to perform (one butterfly means one
+ *                inter-block smoothing operation)
+ *
+ * Outputs:     updated coefficient vector x
+ *
+ * Return:      none
+ *
+ * Notes:       weighted average of opposite bands (pairwise) from the 8 samples
+ *                before and after each block boundary
+ *              nBlocks = (nonZeroBound + 7) / 18, since nZB is the first ZERO sample
+ *                above which all other samples are also zero
+ *              max gain per sample = 1.372
+ *                MAX(i) (abs(csa[i][0]) + abs(csa[i][1]))
+ *              bits gained = 0
+ *              assume at least 1 guard bit in x[] to avoid overflow
+ *                (should be guaranteed from dequant, and max gain from stproc * max
+ *                 gain from AntiAlias < 2.0)
+ **********************************************************************************************************************/
+// a little bit faster in RAM (< 1 ms per block)
+/* __attribute__ ((section (".data"))) */
+const CSA: [[u32; 2]; 8] = [
+    [0x6dc253f0, 0xbe2500aa],
+    [0x70dcebe4, 0xc39e4949],
+    [0x798d6e73, 0xd7e33f4a],
+    [0x7ddd40a7, 0xe8b71176],
+    [0x7f6d20b7, 0xf3e4fe2f],
+    [0x7fe47e40, 0xfac1a3c7],
+    [0x7ffcb263, 0xfe2ebdc6],
+    [0x7fffc694, 0xff86c25d],
+];
+
+pub fn anti_alias(x: &mut [i32], n_bfly: usize) {
+    if n_bfly == 0 {
+        return;
+    }
+
+    // Tworzymy slice (bezpieczny widok na pamięć C++)
+    let total_len = (n_bfly * 18) + 8;
+    let samples = &mut x[..total_len];
+
+    for k in 1..=n_bfly {
+        let center = k * 18;
+
+        // Iterujemy bezpośrednio po parach współczynników w CSA
+        // enumerate() daje nam indeks 'i', którego używamy do sięgania w głąb bloku audio
+        for (i, &[c0, c1]) in CSA.iter().enumerate() {
+            // Wyliczamy indeksy próbek wokół granicy (center)
+            let idx_a = center - (i + 1);
+            let idx_b = center + i;
+
+            // Pobieramy próbki - używamy get_unchecked dla maksymalnej wydajności
+            // (wiemy, że indeksy są poprawne dzięki total_len) lub zwykłego indeksowania
+            let a0 = samples[idx_a];
+            let b0 = samples[idx_b];
+
+            // Obliczenia (Q31 butterfly)
+            let tmp1 = mulshift_32(a0, c0 as i32) - mulshift_32(b0, c1 as i32);
+            let tmp2 = mulshift_32(b0, c0 as i32) + mulshift_32(a0, c1 as i32);
+
+            // Zapis z powrotem
+            samples[idx_a] = tmp1 << 1;
+            samples[idx_b] = tmp2 << 1;
+        }
+    }
+}
+
+pub fn hybrid_transform(
+    x_curr: &mut [i32; MAX_NSAMP],
+    x_prev: &mut [i32; MAX_NSAMP / 2],
+    y: &mut [[i32; NBANDS]; BLOCK_SIZE], // Tablica y[18][32] przekazana jako wskaźnik
+    sis: &SideInfoSub,
+    bc: &mut BlockCount,
+) -> i32 {
+    let mut x_prev_win = [0i32; 18];
+    let mut m_out = 0i32;
+    let mut n_blocks_out;
+    let (x_curr, _) = x_curr.as_chunks_mut::<18>();
+    let (x_prev, _) = x_prev.as_chunks_mut::<9>();
+
+    let mut i = 0;
+
+    // 1. Bloky długie (Long Blocks)
+    if bc.nBlocksLong > NBANDS as i32 || bc.nBlocksTotal > NBANDS as i32 || bc.nBlocksPrev > NBANDS as i32 {
+        return -1;
+    }
+    while i < bc.nBlocksLong {
+        let mut curr_win_idx = sis.blockType;
+        if sis.mixedBlock != 0 && i < bc.currWinSwitch {
+            curr_win_idx = BlockType::Normal;
+        }
+
+        let mut prev_win_idx = bc.prev_type;
+        if i < bc.prevWinSwitch {
+            prev_win_idx = BlockType::Normal;
+        }
+
+        // Adresowanie y[0][i] w tablicy y[18][32] to po prostu y + i
+        // ponieważ y[row][col] = y[row * 32 + col]
+        m_out |= imdct36(
+            &mut x_curr[i as usize],
+            &mut x_prev[i as usize],
+            &mut y.as_flattened_mut()[i as usize..],
+            curr_win_idx,
+            prev_win_idx,
+            i,
+            bc.gbIn,
+        );
+
+        i += 1;
+    }
+
+    // 2. Bloki krótkie (Short Blocks)
+    while i < bc.nBlocksTotal {
+        let mut prev_win_idx = bc.prev_type;
+        if i < bc.prevWinSwitch {
+            prev_win_idx = BlockType::Normal;
+        }
+
+        m_out |= imdct12x3(
+            &mut x_curr[i as usize],
+            &mut x_prev[i as usize],
+            &mut y.as_flattened_mut()[i as usize..],
+            prev_win_idx,
+            i,
+            bc.gbIn,
+        );
+
+        i += 1;
+    }
+    n_blocks_out = i;
+
+    // 3. Okienkowanie i Overlap dla pozostałych bloków poprzednich
+    while i < bc.nBlocksPrev {
+        let mut prev_win_idx = bc.prev_type;
+        if i < bc.prevWinSwitch {
+            prev_win_idx = BlockType::Normal;
+        }
+
+        win_previous(&mut x_prev[i as usize], &mut x_prev_win, prev_win_idx);
+
+        let mut non_zero = 0i32;
+        let fi_bit = i << 31;
+
+        for j in 0..9 {
+            // Próbki parzyste (2*j)
+            let mut xp = x_prev_win[2 * j] << 2;
+            non_zero |= xp;
+            y[2 * j][i as usize] = xp;
+            m_out |= xp.abs();
+
+            // Próbki nieparzyste (2*j + 1) + Inwersja Częstotliwości
+            // Logika: (xp ^ -1) + 1 to zmiana znaku (2's complement)
+            xp = x_prev_win[2 * j + 1] << 2;
+            let mask = fi_bit >> 31; // Arytmetyczne przesunięcie: i odd -> 0xFFFFFFFF, i even -> 0
+            xp = (xp ^ mask).wrapping_add(i & 0x01);
+
+            non_zero |= xp;
+            y[2 * j + 1][i as usize] = xp;
+            m_out |= xp.abs();
+
+            x_prev[i as usize][j] = 0;
+        }
+
+        if non_zero != 0 {
+            n_blocks_out = i;
+        }
+        i += 1;
+    }
+
+    // 4. Czyszczenie pozostałych bloków (do 32 pasm)
+    while i < NBANDS as i32 {
+        for row in y.iter_mut().take(BLOCK_SIZE) {
+            row[i as usize] = 0;
+        }
+        i += 1;
+    }
+
+    // Obliczanie Guard Bits dla wyjścia (CLZ - Count Leading Zeros)
+    // m_out.leading_zeros() zwraca u32, musimy rzutować
+    bc.gbOut = (m_out.leading_zeros() as i32) - 1;
+
+    n_blocks_out
+}
+
+pub fn imdct12x3(
+    x_curr: &mut [i32; 18],
+    x_prev: &mut [i32; 9],
+    y: &mut [i32],
+    bt_prev: BlockType,
+    block_idx: i32,
+    gb: i32,
+) -> i32 {
+    let mut x_buf: [i32; BLOCK_SIZE] = [0i32; BLOCK_SIZE];
+    let mut x_prev_win = [0i32; BLOCK_SIZE];
+    let mut es = 0;
+
+    // 1. Skalowanie (Guard Bits)
+    // Jeśli mamy za mało bitów strażniczych, przesuwamy dane w prawo
+    if gb < 7 {
+        es = 7 - gb;
+        for i in 0..9 {
+            // x_curr jest interleafed (3 bloki po 6 próbek)
+            x_curr[i * 2] >>= es;
+            x_curr[i * 2 + 1] >>= es;
+            x_prev[i] >>= es;
+        }
+    }
+
+    // 2. Trzy transformaty IMDCT 12-punktowe
+    // Dane wejściowe są przeplatane: b0[0], b1[0], b2[0], b0[1]...
+    let (c1, _) = x_buf.as_chunks_mut::<6>();
+    imdct_12(&x_curr[..16].try_into().unwrap(), &mut c1[0]); // Block 0
+    imdct_12(&x_curr[1..17].try_into().unwrap(), &mut c1[1]); // Block 1
+    imdct_12(&x_curr[2..18].try_into().unwrap(), &mut c1[2]); // Block 2
+
+    // 3. Okienkowanie poprzedniego bloku (Overlap z poprzedniej ramki)
+    win_previous(x_prev, &mut x_prev_win, bt_prev);
+    // Pobranie wskaźnika do okna krótkiego (index 2)
+    let wp = IMDCT_WIN[2];
+    let mut m_out = 0i32;
+
+    // 4. Nakładanie i dodawanie (Overlap-Add) dla krótkich bloków
+    for i in 0..3 {
+        let mut y_lo: i32;
+
+        // Pierwsze 6 próbek pochodzi tylko z poprzedniego okna (xPrevWin)
+        y_lo = x_prev_win[i] << 2;
+        m_out |= y_lo.abs();
+        y[i * NBANDS] = y_lo;
+
+        y_lo = x_prev_win[3 + i] << 2;
+        m_out |= y_lo.abs();
+        y[(3 + i) * NBANDS] = y_lo;
+
+        // Kolejne próbki to suma poprzedniego okna i nowych danych (xBuf) z oknem wp
+        y_lo = (x_prev_win[6 + i] << 2) + mulshift_32(wp[i] as i32, x_buf[3 + i]);
+        m_out |= y_lo.abs();
+        y[(6 + i) * NBANDS] = y_lo;
+
+        y_lo = (x_prev_win[9 + i] << 2) + mulshift_32(wp[3 + i] as i32, x_buf[5 - i]);
+        m_out |= y_lo.abs();
+        y[(9 + i) * NBANDS] = y_lo;
+
+        // Składanie na stykach bloków wewnętrznych (short block concatenation)
+        y_lo = (x_prev_win[12 + i] << 2)
+            + mulshift_32(wp[6 + i] as i32, x_buf[2 - i])
+            + mulshift_32(wp[i] as i32, x_buf[9 + i]);
+        m_out |= y_lo.abs();
+        y[(12 + i) * NBANDS] = y_lo;
+
+        y_lo = (x_prev_win[15 + i] << 2)
+            + mulshift_32(wp[9 + i] as i32, x_buf[i])
+            + mulshift_32(wp[3 + i] as i32, x_buf[11 - i]);
+        m_out |= y_lo.abs();
+        y[(15 + i) * NBANDS] = y_lo;
+    }
+
+    // 5. Zapisanie części do overlapu na następną ramkę (tylko 9 próbek)
+    // Wykorzystujemy symetrię IMDCT
+    for i in 0..3 {
+        x_prev[i] = x_buf[6 + i] >> 2;
+    }
+    for i in 0..6 {
+        x_prev[3 + i] = x_buf[12 + i] >> 2;
+    }
+
+    // 6. Korekta końcowa: inwersja i skalowanie
+    m_out |= freq_invert_rescale(y, x_prev, block_idx, es);
+
+    m_out
+}
+
+pub fn imdct(
+    gr: GranuleIndex,
+    ch: ChannelIndex,
+    sfb: &SFBandTable,
+    m_mpegversion: MPEGVersion,
+    m_side_info_sub: &[[SideInfoSub; 2]; 2],
+    m_huffman_info: &mut HuffmanInfo,
+    m_imdctinfo: &mut IMDCTInfo,
+) -> i32 {
+    let mut bc = BlockCount {
+        nBlocksLong: 0,
+        nBlocksTotal: 0,
+        nBlocksPrev: 0,
+        prev_type: BlockType::Normal,
+        prevWinSwitch: 0,
+        currWinSwitch: 0,
+        gbIn: 0,
+        gbOut: 0,
+    };
+
+    let n_bfly: i32;
+    let sis = &m_side_info_sub[gr as usize][ch as usize];
+
+    // blockCutoff logic
+    // MPEG1 = 0, inne = MPEG2/2.5
+    let cutoff_idx = if m_mpegversion == MPEGVersion::MPEG1 {
+        8
+    } else {
+        6
+    };
+    let block_cutoff = sfb.l[cutoff_idx] / 18;
+
+    if sis.blockType != BlockType::Short {
+        /* all long transforms */
+        let x = (m_huffman_info.non_zero_bound[ch as usize] + 7) / 18 + 1;
+        bc.nBlocksLong = if x < 32 { x } else { 32 };
+        n_bfly = bc.nBlocksLong - 1;
+    } else if sis.blockType == BlockType::Short && sis.mixedBlock != 0 {
+        /* mixed block */
+        bc.nBlocksLong = block_cutoff;
+        n_bfly = bc.nBlocksLong - 1;
+    } else {
+        /* all short transforms */
+        bc.nBlocksLong = 0;
+        n_bfly = 0;
+    }
+
+    // Wywołanie AntiAlias na buforze konkretnego kanału
+    // huffDecBuf[ch] to tablica 576 intów
+    if let Ok(size) = n_bfly.try_into() {
+        anti_alias(&mut m_huffman_info.huff_dec_buf[ch as usize], size);
+    }
+
+    // Aktualizacja nonZeroBound
+    let x_nz = m_huffman_info.non_zero_bound[ch as usize];
+    let y_nz = n_bfly * 18 + 8;
+    m_huffman_info.non_zero_bound[ch as usize] = if x_nz > y_nz { x_nz } else { y_nz };
+
+    hybrid_transform_channel(ch, sis, block_cutoff, &mut bc, m_huffman_info, m_imdctinfo)
+}
+
+/***********************************************************************************************************************
+ * Function:    IMDCTAntialiased
+ *
+ * Description: IMDCT of a spectrum that already went through IMDCT() once, i.e. is
+ *                antialiased and has nonZeroBound updated (used to repeat a granule)
+ *
+ * Inputs:      same as IMDCT()
+ *
+ * Outputs:     same as IMDCT()
+ *
+ * Return:      0 on success
+ **********************************************************************************************************************/
+pub fn imdct_antialiased(
+    gr: GranuleIndex,
+    ch: ChannelIndex,
+    sfb: &SFBandTable,
+    m_mpegversion: MPEGVersion,
+    m_side_info_sub: &[[SideInfoSub; 2]; 2],
+    m_huffman_info: &mut HuffmanInfo,
+    m_imdctinfo: &mut IMDCTInfo,
+) -> i32 {
+    let mut bc = BlockCount {
+        nBlocksLong: 0,
+        nBlocksTotal: 0,
+        nBlocksPrev: 0,
+        prev_type: BlockType::Normal,
+        prevWinSwitch: 0,
+        currWinSwitch: 0,
+        gbIn: 0,
+        gbOut: 0,
+    };
+    let sis = &m_side_info_sub[gr as usize][ch as usize];
+    let cutoff_idx = if m_mpegversion == MPEGVersion::MPEG1 {
+        8
+    } else {
+        6
+    };
+    let block_cutoff = sfb.l[cutoff_idx] / 18;
+    bc.nBlocksLong = if sis.blockType != BlockType::Short {
+        ((m_huffman_info.non_zero_bound[ch as usize] + 7) / 18 + 1).min(32)
+    } else if sis.mixedBlock != 0 {
+        block_cutoff
+    } else {
+        0
+    };
+
+    hybrid_transform_channel(ch, sis, block_cutoff, &mut bc, m_huffman_info, m_imdctinfo)
+}
+
+/* HybridTransform() of one channel with the block counts set up, and the IMDCT state update */
+fn hybrid_transform_channel(
+    ch: ChannelIndex,
+    sis: &SideInfoSub,
+    block_cutoff: i32,
+    bc: &mut BlockCount,
+    m_huffman_info: &mut HuffmanInfo,
+    m_imdctinfo: &mut IMDCTInfo,
+) -> i32 {
+    // bc setup
+    bc.nBlocksTotal = (m_huffman_info.non_zero_bound[ch as usize] + 17) / 18;
+    bc.nBlocksPrev = m_imdctinfo.numPrevIMDCT[ch as usize];
+    bc.prev_type = m_imdctinfo.prevType[ch as usize];
+    bc.prevWinSwitch = m_imdctinfo.prevWinSwitch[ch as usize];
+    bc.currWinSwitch = if sis.mixedBlock != 0 { block_cutoff } else { 0 };
+    bc.gbIn = m_huffman_info.gb[ch as usize];
+
+    // Wywołanie HybridTransform
+    m_imdctinfo.numPrevIMDCT[ch as usize] = hybrid_transform(
+        &mut m_huffman_info.huff_dec_buf[ch as usize],
+        &mut m_imdctinfo.overBuf[ch as usize],
+        &mut m_imdctinfo.outBuf[ch as usize],
+        sis,
+        bc,
+    );
+
+    m_imdctinfo.prevType[ch as usize] = sis.blockType;
+    m_imdctinfo.prevWinSwitch[ch as usize] = bc.currWinSwitch;
+    m_imdctinfo.gb[ch as usize] = bc.gbOut;
+
+    0
+}
@@ -0,0 +1,13 @@
+pub mod conceal;
+pub mod dequant;
+pub mod downmix;
+pub mod gapless;
+pub mod huffman;
+pub mod imdct;
+pub mod scalefactors;
+pub mod seek;
+pub mod stereo;
+pub mod sync;
+pub mod vbr;
+pub mod vbri;
+pub mod xing;
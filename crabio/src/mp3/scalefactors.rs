@@ -0,0 +1,396 @@
+use crate::{
+    mp3_decoder::{
+        BlockType, ChannelIndex, FrameHeader, GranuleIndex, MAX_NCHAN, MAX_NGRAN, MAX_SCFBD,
+        MPEGVersion, ScaleFactorInfoSub, ScaleFactorJS, SideInfo, SideInfoSub,
+    },
+    utils::bit_stream_cache::BitStreamInfo,
+};
+
+const M_SFLEN_TAB: [[u8; 2]; 16] = [
+    [0, 0],
+    [0, 1],
+    [0, 2],
+    [0, 3],
+    [3, 0],
+    [1, 1],
+    [1, 2],
+    [1, 3],
+    [2, 1],
+    [2, 2],
+    [2, 3],
+    [3, 1],
+    [3, 2],
+    [3, 3],
+    [4, 2],
+    [4, 3],
+];
+
+//----------------------------------------------------------------------------------------------------------------------
+/***********************************************************************************************************************
+ * Function:    UnpackSFMPEG1
+ *
+ * Description: unpack MPEG 1 scalefactors from bitstream
+ *
+ * Inputs:      BitStreamInfo, SideInfoSub, ScaleFactorInfoSub structs for this
+ *                granule/channel
+ *              vector of scfsi flags from side info, length = 4 (MAX_SCFBD)
+ *              index of current granule
+ *              ScaleFactorInfoSub from granule 0 (for granule 1, if scfsi[i] is set,
+ *                then we just replicate the scale factors from granule 0 in the
+ *                i'th set of scalefactor bands)
+ *
+ * Outputs:     updated BitStreamInfo struct
+ *              scalefactors in sfis (short and/or long arrays, as appropriate)
+ *
+ * Return:      none
+ *
+ * Notes:       set order of short blocks to s[band][window] instead of s[window][band]
+ *                so that we index through consectutive memory locations when unpacking
+ *                (make sure dequantizer follows same convention)
+ *              Illegal Intensity Position = 7 (always) for MPEG1 scale factors
+ **********************************************************************************************************************/
+pub fn unpack_sfmpeg1(
+    bsi: &mut BitStreamInfo,
+    sis: &SideInfoSub,
+    m_scale_factor_info_sub: &mut [[ScaleFactorInfoSub; MAX_NCHAN]; MAX_NGRAN],
+    scfsi: &[i32; MAX_SCFBD],
+    gr: GranuleIndex,
+    ch: ChannelIndex,
+) {
+    let sfb: usize;
+
+    /* these can be 0, so make sure GetBits(bsi, 0) returns 0 (no >> 32 or anything) */
+    let slen0: i32 = M_SFLEN_TAB[sis.sf_compress as usize][0] as i32;
+    let slen1: i32 = M_SFLEN_TAB[sis.sf_compress as usize][1] as i32;
+    if sis.blockType == BlockType::Short {
+        /* short block, type 2 (implies winSwitchFlag == 1) */
+        if sis.mixedBlock != 0 {
+            /* do long block portion */
+            for sfb in 0..8 {
+                m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                    bsi.get_bits(slen0 as u32) as u8;
+            }
+            sfb = 3;
+        } else {
+            /* all short blocks */
+            sfb = 0;
+        }
+        for sfb in sfb..6 {
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][0] =
+                bsi.get_bits(slen0 as u32) as u8;
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][1] =
+                bsi.get_bits(slen0 as u32) as u8;
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][2] =
+                bsi.get_bits(slen0 as u32) as u8;
+        }
+        for sfb in 6..12 {
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][0] =
+                bsi.get_bits(slen1 as u32) as u8;
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][1] =
+                bsi.get_bits(slen1 as u32) as u8;
+            m_scale_factor_info_sub[gr as usize][ch as usize].s[sfb][2] =
+                bsi.get_bits(slen1 as u32) as u8;
+        }
+        /* last sf band not transmitted */
+        m_scale_factor_info_sub[gr as usize][ch as usize].s[12][0] = 0;
+        m_scale_factor_info_sub[gr as usize][ch as usize].s[12][1] = 0;
+        m_scale_factor_info_sub[gr as usize][ch as usize].s[12][2] = 0;
+    } else {
+        /* long blocks, type 0, 1, or 3 */
+        if gr == GranuleIndex::Granule0 {
+            /* first granule */
+            for sfb in 0..11 {
+                m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                    bsi.get_bits(slen0 as u32) as u8;
+            }
+            for sfb in 11..21 {
+                m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                    bsi.get_bits(slen1 as u32) as u8;
+            }
+            return;
+        } else {
+            /* second granule
+             * scfsi: 0 = different scalefactors for each granule,
+             *        1 = copy sf's from granule 0 into granule 1
+             * for block type == 2, scfsi is always 0
+             */
+            if scfsi[0] != 0 {
+                for sfb in 0..6 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        m_scale_factor_info_sub[0][ch as usize].l[sfb];
+                }
+            } else {
+                for sfb in 0..6 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        bsi.get_bits(slen0 as u32) as u8;
+                }
+            }
+
+            if scfsi[1] != 0 {
+                for sfb in 6..11 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        m_scale_factor_info_sub[0][ch as usize].l[sfb];
+                }
+            } else {
+                for sfb in 6..11 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        bsi.get_bits(slen0 as u32) as u8;
+                }
+            }
+            if scfsi[2] != 0 {
+                for sfb in 11..16 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        m_scale_factor_info_sub[0][ch as usize].l[sfb];
+                }
+            } else {
+                for sfb in 11..16 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        bsi.get_bits(slen1 as u32) as u8;
+                }
+            }
+
+            if scfsi[3] != 0 {
+                for sfb in 16..21 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        m_scale_factor_info_sub[0][ch as usize].l[sfb];
+                }
+            } else {
+                for sfb in 16..21 {
+                    m_scale_factor_info_sub[gr as usize][ch as usize].l[sfb] =
+                        bsi.get_bits(slen1 as u32) as u8;
+                }
+            }
+        }
+        /* last sf band not transmitted */
+        m_scale_factor_info_sub[gr as usize][ch as usize].l[21] = 0;
+        m_scale_factor_info_sub[gr as usize][ch as usize].l[22] = 0;
+    }
+}
+
+/* NRTab[size + 3*is_right][block type][partition]
+ *   block type index: 0 = (bt0,bt1,bt3), 1 = bt2 non-mixed, 2 = bt2 mixed
+ *   partition: scale factor groups (sfb1 through sfb4)
+ * for block type = 2 (mixed or non-mixed) / by 3 is rolled into this table
+ *   (for 3 short blocks per long block)
+ * see 2.4.3.2 in MPEG 2 (low sample rate) spec
+ * stuff rolled into this table:
+ *   NRTab[x][1][y]   --> (NRTab[x][1][y])   / 3
+ *   NRTab[x][2][>=1] --> (NRTab[x][2][>=1]) / 3  (first partition is long block)
+ */
+const NRTAB: [[[i32; 4]; 3]; 6] = [
+    [[6, 5, 5, 5], [3, 3, 3, 3], [6, 3, 3, 3]],
+    [[6, 5, 7, 3], [3, 3, 4, 2], [6, 3, 4, 2]],
+    [[11, 10, 0, 0], [6, 6, 0, 0], [6, 3, 6, 0]],
+    [[7, 7, 7, 0], [4, 4, 4, 0], [6, 5, 4, 0]],
+    [[6, 6, 6, 3], [4, 3, 3, 2], [6, 4, 3, 2]],
+    [[8, 8, 5, 0], [5, 4, 3, 0], [6, 6, 3, 0]],
+];
+
+pub fn unpack_sfmpeg2(
+    bsi: &mut BitStreamInfo,
+    sis: &mut SideInfoSub,
+    sfis: &mut ScaleFactorInfoSub,
+    _gr: GranuleIndex, // nieużywane, zachowane dla sygnatury
+    ch: ChannelIndex,
+    mode_ext: usize,
+    sfjs: &mut ScaleFactorJS,
+) {
+    let mut sfb: usize;
+    let sfc_idx: usize;
+    let mut bt_idx: usize;
+    let mut nr_idx: usize;
+    let mut slen = [0i32; 4];
+    let mut nr = [0i32; 4];
+
+    let mut sf_compress = sis.sf_compress;
+    let mut pre_flag = 0;
+    let mut intensity_scale = 0;
+
+    /* stereo mode bits (1 = on): bit 1 = mid-side on/off, bit 0 = intensity on/off */
+    if !((mode_ext & 0x01 != 0) && (ch == ChannelIndex::Channel1)) {
+        if sf_compress < 400 {
+            slen[0] = (sf_compress >> 4) / 5;
+            slen[1] = (sf_compress >> 4) % 5;
+            slen[2] = (sf_compress & 0x0f) >> 2;
+            slen[3] = sf_compress & 0x03;
+            sfc_idx = 0;
+        } else if sf_compress < 500 {
+            sf_compress -= 400;
+            slen[0] = (sf_compress >> 2) / 5;
+            slen[1] = (sf_compress >> 2) % 5;
+            slen[2] = sf_compress & 0x03;
+            slen[3] = 0;
+            sfc_idx = 1;
+        } else {
+            sf_compress -= 500;
+            slen[0] = sf_compress / 3;
+            slen[1] = sf_compress % 3;
+            slen[2] = 0;
+            slen[3] = 0;
+            if sis.mixedBlock != 0 {
+                slen[2] = slen[1];
+                slen[1] = slen[0];
+            }
+            pre_flag = 1;
+            sfc_idx = 2;
+        }
+    } else {
+        /* intensity stereo ch = 1 (right) */
+        intensity_scale = sf_compress & 0x01;
+        sf_compress >>= 1;
+        if sf_compress < 180 {
+            slen[0] = sf_compress / 36;
+            slen[1] = (sf_compress % 36) / 6;
+            slen[2] = (sf_compress % 36) % 6;
+            slen[3] = 0;
+            sfc_idx = 3;
+        } else if sf_compress < 244 {
+            sf_compress -= 180;
+            slen[0] = (sf_compress & 0x3f) >> 4;
+            slen[1] = (sf_compress & 0x0f) >> 2;
+            slen[2] = sf_compress & 0x03;
+            slen[3] = 0;
+            sfc_idx = 4;
+        } else {
+            sf_compress -= 244;
+            slen[0] = sf_compress / 3;
+            slen[1] = sf_compress % 3;
+            slen[2] = 0;
+            slen[3] = 0;
+            sfc_idx = 5;
+        }
+    }
+
+    /* btIdx: (0,1,3) --> 0, (2 non-mixed) --> 1, (2 mixed) ---> 2 */
+    bt_idx = 0;
+    if sis.blockType == BlockType::Short {
+        bt_idx = if sis.mixedBlock != 0 { 2 } else { 1 };
+    }
+
+    nr.copy_from_slice(&NRTAB[sfc_idx][bt_idx]);
+
+    /* save intensity stereo scale factor info */
+    if (mode_ext & 0x01 != 0) && (ch == ChannelIndex::Channel1) {
+        sfjs.slen.copy_from_slice(&slen);
+        sfjs.nr.copy_from_slice(&nr);
+        sfjs.intensity_scale = intensity_scale;
+    }
+    sis.preFlag = pre_flag;
+
+    /* Rozpakowywanie skal */
+    if sis.blockType == BlockType::Short {
+        if sis.mixedBlock != 0 {
+            /* Część dla bloków długich (long) */
+            for sfb in 0..6 {
+                sfis.l[sfb] = bsi.get_bits(slen[0] as u32) as u8;
+            }
+            sfb = 3; /* Startowy indeks sfb dla krótkich */
+            nr_idx = 1;
+        } else {
+            sfb = 0;
+            nr_idx = 0;
+        }
+
+        /* Pozostałe bloki krótkie */
+        while nr_idx <= 3 {
+            for _ in 0..nr[nr_idx] {
+                sfis.s[sfb][0] = bsi.get_bits(slen[nr_idx] as u32) as u8;
+                sfis.s[sfb][1] = bsi.get_bits(slen[nr_idx] as u32) as u8;
+                sfis.s[sfb][2] = bsi.get_bits(slen[nr_idx] as u32) as u8;
+                sfb += 1;
+            }
+            nr_idx += 1;
+        }
+        /* Ostatnie pasmo nie jest przesyłane */
+        sfis.s[12][0] = 0;
+        sfis.s[12][1] = 0;
+        sfis.s[12][2] = 0;
+    } else {
+        /* Bloki długie (long) */
+        sfb = 0;
+        for nr_idx in 0..=3 {
+            for _ in 0..nr[nr_idx] {
+                sfis.l[sfb] = bsi.get_bits(slen[nr_idx] as u32) as u8;
+                sfb += 1;
+            }
+        }
+        /* Ostatnie pasma nie są przesyłane */
+        sfis.l[21] = 0;
+        sfis.l[22] = 0;
+    }
+}
+
+/***********************************************************************************************************************
+ * Function:    UnpackScaleFactors
+ *
+ * Description: parse the fields of the MP3 scale factor data section
+ *
+ * Inputs:      MP3DecInfo structure filled by UnpackFrameHeader() and UnpackSideInfo()
+ *              buffer pointing to the MP3 scale factor data
+ *              pointer to bit offset (0-7) indicating starting bit in buf[0]
+ *              number of bits available in data buffer
+ *              index of current granule and channel
+ *
+ * Outputs:     updated platform-specific ScaleFactorInfo struct
+ *              updated bitOffset
+ *
+ * Return:      length (in bytes) of scale factor data, -1 if null input pointers
+ **********************************************************************************************************************/
+
+#[allow(clippy::too_many_arguments)]
+pub fn unpack_scale_factors(
+    buf: &[u8],
+    bit_offset: &mut i32,
+    bits_avail: i32,
+    gr: GranuleIndex,
+    ch: ChannelIndex,
+    m_side_info_sub: &mut SideInfoSub,
+    m_scale_factor_info_sub: &mut [[ScaleFactorInfoSub; 2]; 2],
+    m_side_info: &mut SideInfo,
+    m_frame_header: &mut FrameHeader,
+    m_scale_factor_js: &mut ScaleFactorJS,
+    m_mpegversion: MPEGVersion,
+) -> i32 {
+    /* init GetBits reader */
+    // Safe: Calculate length and clamp it to the actual buffer length to prevent out-of-bounds access.
+    let bytes_needed = (bits_avail as usize + *bit_offset as usize).div_ceil(8);
+    let actual_len = bytes_needed.min(buf.len());
+    let start_buf = &buf[..actual_len];
+
+    let mut bsi = BitStreamInfo::from_slice(start_buf);
+
+    if *bit_offset != 0 {
+        bsi.get_bits(*bit_offset as u32);
+    }
+
+    match m_mpegversion {
+        MPEGVersion::MPEG1 => unpack_sfmpeg1(
+            &mut bsi,
+            m_side_info_sub,
+            m_scale_factor_info_sub,
+            &m_side_info.scfsi[ch as usize],
+            gr,
+            ch,
+        ),
+        MPEGVersion::MPEG2 | MPEGVersion::MPEG25 => unpack_sfmpeg2(
+            &mut bsi,
+            m_side_info_sub,
+            &mut m_scale_factor_info_sub[gr as usize][ch as usize],
+            gr,
+            ch,
+            m_frame_header.modeExt,
+            m_scale_factor_js,
+        ),
+    };
+
+    let bits_used = bsi.calc_bits_used(start_buf, *bit_offset as usize);
+    
+    // Safe: Perform integer arithmetic instead of raw pointer arithmetic.
+    let total_bits_consumed = bits_used + *bit_offset;
+    let bytes_consumed = total_bits_consumed >> 3; // divide by 8
+    
+    *bit_offset = total_bits_consumed & 0x07; // modulo 8
+
+    // Return bytes consumed (equivalent to the previous pointer difference)
+    bytes_consumed
+}
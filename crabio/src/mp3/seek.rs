@@ -0,0 +1,352 @@
+//! Sample-accurate seeking.
+//!
+//! A seek picks the frame holding the target sample and backs up a few frames, so that the bit
+//! reservoir, the IMDCT overlap and the synthesis filterbank are rebuilt before that frame is
+//! output. The caller reads from [`Seek::byte_offset`] and feeds the data through
+//! [`Seek::decode_frame`], which decodes the pre-roll frames without output and trims the
+//! target frame, so the first sample out is the one asked for.
+//!
+//! CBR streams map frames to bytes exactly. VBR streams go through the Xing or VBRI seek
+//! table, which only says roughly where a frame starts: the position is then as accurate as
+//! the table, usually within a frame or two.
+
+use super::vbr::VbrHeader;
+use crate::mp3_decoder::{
+    DecodeError, DecodedFrame, LayerIndex, MP3Decoder, MPEGVersion, Mp3Error,
+};
+
+/// Bytes of main data a Layer III frame can take from earlier frames, by `main_data_begin` width.
+const MAX_RESERVOIR_MPEG1: u64 = 511;
+const MAX_RESERVOIR_MPEG2: u64 = 255;
+/// Output samples per channel it takes the synthesis filterbank to forget its start state.
+const FILTERBANK_HISTORY: u64 = 512;
+
+/// How the frames of a stream are laid out, from its first frames.
+///
+/// Offsets are relative to the start of the input given to [`MP3Decoder::stream_layout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamLayout {
+    /// offset of the first frame, the Xing/Info or VBRI tag frame if there is one
+    pub first_frame: usize,
+    /// offset of the first audio frame, which holds sample 0
+    pub audio_start: usize,
+    /// header of the first audio frame, frames found after a seek have to match it
+    pub header: [u8; 4],
+    pub layer: LayerIndex,
+    pub version: MPEGVersion,
+    pub samprate: u32,
+    /// PCM samples per channel in one frame
+    pub samples_per_frame: u32,
+    /// bitrate of the first audio frame in bits/s, measured for free format
+    pub bitrate: u32,
+    /// unpadded size of a free format frame, 0 if the stream has a bitrate index
+    pub(crate) free_frame_bytes: u32,
+    /// header and side info bytes of a Layer III frame, 0 for Layers I and II
+    pub(crate) side_bytes: u32,
+}
+
+impl StreamLayout {
+    /// Bytes per slot: frames are padded, and Layer I frames sized, in whole slots.
+    const fn slot_bytes(&self) -> u64 {
+        match self.layer {
+            LayerIndex::Layer1 => 4,
+            _ => 1,
+        }
+    }
+
+    /// Offset of audio frame `frame` from the first audio frame, for a CBR stream.
+    ///
+    /// Encoders pad frames so that frame k starts at k times the exact (fractional) frame
+    /// size, rounded down to a slot.
+    pub fn frame_offset(&self, frame: u64) -> u64 {
+        if self.free_frame_bytes != 0 {
+            return frame * self.free_frame_bytes as u64;
+        }
+        let slot = self.slot_bytes();
+        let num = frame * self.samples_per_frame as u64 * self.bitrate as u64;
+        num / (8 * self.samprate as u64 * slot) * slot
+    }
+
+    /// Average frame size in bytes, from the VBR header if it has the numbers.
+    fn average_frame_bytes(&self, vbr: Option<&VbrHeader>) -> u64 {
+        match vbr.and_then(|vbr| Some((vbr.bytes()?, vbr.frames()?))) {
+            Some((bytes, frames)) if frames != 0 => bytes as u64 / frames as u64,
+            _ => self.frame_offset(1).max(1),
+        }
+    }
+
+    /// Frames to decode before the target frame for its output to be exact.
+    ///
+    /// Layer III first needs enough frames to fill the largest bit reservoir, then one more
+    /// (decoded with the full reservoir) for the IMDCT overlap; every layer needs the
+    /// filterbank to have seen [`FILTERBANK_HISTORY`] samples.
+    pub fn preroll_frames(&self, vbr: Option<&VbrHeader>) -> u64 {
+        let filterbank = FILTERBANK_HISTORY.div_ceil(self.samples_per_frame as u64);
+        if self.layer != LayerIndex::Layer3 {
+            return filterbank;
+        }
+        let reservoir = if self.version == MPEGVersion::MPEG1 {
+            MAX_RESERVOIR_MPEG1
+        } else {
+            MAX_RESERVOIR_MPEG2
+        };
+        let main_bytes = self
+            .average_frame_bytes(vbr)
+            .saturating_sub(self.side_bytes as u64)
+            .max(1);
+        reservoir.div_ceil(main_bytes) + filterbank
+    }
+
+    /// Whether `header` can be a frame of this stream: same version, layer, CRC flag and
+    /// sample rate, and a valid bitrate index.
+    fn matches(&self, header: &[u8]) -> bool {
+        header.len() >= 4
+            && header[0] == self.header[0]
+            && header[1] == self.header[1]
+            && (header[2] & 0x0c) == (self.header[2] & 0x0c)
+            && (header[2] >> 4) != 0x0f
+    }
+}
+
+/// A seek in progress, from [`MP3Decoder::seek`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Seek {
+    /// where to continue reading, relative to the same origin as the [`StreamLayout`] offsets
+    pub byte_offset: u64,
+    /// audio frame expected at `byte_offset`, counted from the first audio frame
+    pub frame: u64,
+    /// sample (per channel) the output starts at
+    pub sample: u64,
+    layout: StreamLayout,
+    /// frames left to decode without output
+    preroll: u64,
+    /// samples per channel to drop from the start of the target frame
+    skip: u64,
+    /// whether the input is known to be at a frame of the stream
+    synced: bool,
+}
+
+impl Seek {
+    /// Plans a seek to `sample` (per channel, 0 = first sample of the first audio frame).
+    ///
+    /// With a VBR header the position comes from its seek table and `sample` is clamped to
+    /// the length of the stream; without one the stream is taken to be CBR.
+    pub fn new(layout: &StreamLayout, vbr: Option<&VbrHeader>, sample: u64) -> Self {
+        let sample = match vbr.and_then(VbrHeader::total_samples) {
+            Some(total) => sample.min(total),
+            None => sample,
+        };
+        let spf = layout.samples_per_frame as u64;
+        let target = sample / spf;
+        let frame = target.saturating_sub(layout.preroll_frames(vbr));
+
+        let byte_offset = match vbr.and_then(|vbr| vbr.seek_offset(frame * spf)) {
+            /* seek tables count from the tag frame */
+            Some(offset) if frame != 0 => layout.first_frame as u64 + offset,
+            /* a frame may start one pad slot before the nominal offset, so start looking there */
+            _ if frame != 0 => {
+                layout.audio_start as u64 + layout.frame_offset(frame) - layout.slot_bytes()
+            }
+            _ => layout.audio_start as u64,
+        };
+
+        Seek {
+            byte_offset,
+            frame,
+            sample,
+            layout: *layout,
+            preroll: target - frame,
+            skip: sample % spf,
+            synced: frame == 0,
+        }
+    }
+
+    /// Whether the pre-roll is over and the output trimmed, so frames decode as usual.
+    pub fn is_done(&self) -> bool {
+        self.synced && self.preroll == 0 && self.skip == 0
+    }
+
+    /// Decodes the next frame of the stream read from [`Seek::byte_offset`] on, like
+    /// [`MP3Decoder::decode_frame`] on the decoder the seek was started on.
+    ///
+    /// Until a frame of the stream is found input is consumed without output, and so are the
+    /// pre-roll frames, main data underflows included. The target frame is trimmed to start
+    /// at [`Seek::sample`]; from then on this is just [`MP3Decoder::decode_frame`]. A pre-roll
+    /// frame that fails to decode is returned as an error too, with the bytes it used up.
+    pub fn decode_frame(
+        &mut self,
+        dec: &mut MP3Decoder,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, DecodeError> {
+        let no_output = |dec: &MP3Decoder, bytes_consumed| DecodedFrame {
+            bytes_consumed,
+            samples_written: 0,
+            info: dec.m_MP3FrameInfo,
+        };
+
+        if !self.synced {
+            match input.windows(4).position(|w| self.layout.matches(w)) {
+                Some(0) => self.synced = true,
+                Some(skipped) => {
+                    self.synced = true;
+                    return Ok(no_output(dec, skipped));
+                }
+                /* keep the last bytes, they may start a header */
+                None => return Ok(no_output(dec, input.len().saturating_sub(3))),
+            }
+        }
+        if self.preroll == 0 && self.skip == 0 {
+            return dec.decode_frame(input, out);
+        }
+
+        let mut consumed = 0;
+        let result = dec.decode(input, out, false, &mut consumed);
+        if let Err(e @ (Mp3Error::InDataUnderflow { .. } | Mp3Error::OutputTooSmall { .. })) =
+            result
+        {
+            /* the caller retries the same frame with more input or output */
+            return Err(DecodeError::new(e, consumed));
+        }
+
+        if self.preroll > 0 {
+            return match result {
+                /* tag frame, not an audio frame */
+                Ok(0) => Ok(no_output(dec, consumed)),
+                /* expected until the reservoir is refilled */
+                Ok(_) | Err(Mp3Error::MainDataUnderflow { .. }) => {
+                    self.preroll -= 1;
+                    Ok(no_output(dec, consumed))
+                }
+                Err(e) => {
+                    self.preroll -= 1;
+                    Err(DecodeError::new(e, consumed))
+                }
+            };
+        }
+
+        let samples = result.map_err(|e| DecodeError::new(e, consumed))?;
+        if samples == 0 {
+            return Ok(no_output(dec, consumed));
+        }
+        Ok(self.skip_start(
+            out,
+            DecodedFrame {
+                bytes_consumed: consumed,
+                samples_written: samples,
+                info: dec.m_MP3FrameInfo,
+            },
+        ))
+    }
+
+    /// [`MP3Decoder::drain`] at the end of the stream, for a seek that got there: the last
+    /// samples of the stream only come out of the drained granule, so it may hold the target
+    /// sample. Nothing is output while frames are still missing from the pre-roll.
+    pub fn drain(
+        &mut self,
+        dec: &mut MP3Decoder,
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, Mp3Error> {
+        let frame = dec.drain(out)?;
+        if !self.synced || self.preroll > 0 {
+            return Ok(DecodedFrame {
+                samples_written: 0,
+                ..frame
+            });
+        }
+        Ok(self.skip_start(out, frame))
+    }
+
+    /// Drops the samples before the target from `frame`, the first one after the pre-roll.
+    fn skip_start(&mut self, out: &mut [i16], frame: DecodedFrame) -> DecodedFrame {
+        let n_chans = frame.info.n_chans as usize;
+        let drop = (self.skip as usize * n_chans).min(frame.samples_written);
+        out.copy_within(drop..frame.samples_written, 0);
+        self.skip -= (drop / n_chans) as u64;
+        DecodedFrame {
+            samples_written: frame.samples_written - drop,
+            ..frame
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(
+        layer: LayerIndex,
+        version: MPEGVersion,
+        samprate: u32,
+        bitrate: u32,
+    ) -> StreamLayout {
+        let samples_per_frame = match (layer, version) {
+            (LayerIndex::Layer1, _) => 384,
+            (LayerIndex::Layer3, MPEGVersion::MPEG2 | MPEGVersion::MPEG25) => 576,
+            _ => 1152,
+        };
+        StreamLayout {
+            first_frame: 0,
+            audio_start: 0,
+            header: [0xff, 0xfb, 0x90, 0x44],
+            layer,
+            version,
+            samprate,
+            samples_per_frame,
+            bitrate,
+            free_frame_bytes: 0,
+            side_bytes: if layer == LayerIndex::Layer3 { 36 } else { 0 },
+        }
+    }
+
+    #[test]
+    fn frame_offsets_follow_the_padding_pattern() {
+        // 128 kbit/s at 44.1 kHz: 417.96 bytes per frame, so 417, 418, 418, ...
+        let l3 = layout(LayerIndex::Layer3, MPEGVersion::MPEG1, 44100, 128_000);
+        assert_eq!(l3.frame_offset(1), 417);
+        assert_eq!(l3.frame_offset(2), 835);
+        assert_eq!(l3.frame_offset(1000), 417_959);
+        // Layer I pads in 4-byte slots: 32 kbit/s at 44.1 kHz is 8.7 slots per frame
+        let l1 = layout(LayerIndex::Layer1, MPEGVersion::MPEG1, 44100, 32_000);
+        assert_eq!(l1.frame_offset(1), 32);
+        assert_eq!(l1.frame_offset(10), 348);
+        let free = StreamLayout {
+            free_frame_bytes: 300,
+            ..l3
+        };
+        assert_eq!(free.frame_offset(7), 2100);
+    }
+
+    #[test]
+    fn preroll_covers_reservoir_and_filterbank() {
+        // 128 kbit/s: 381 bytes of main data per frame, two refill the reservoir
+        let l3 = layout(LayerIndex::Layer3, MPEGVersion::MPEG1, 44100, 128_000);
+        assert_eq!(l3.preroll_frames(None), 3);
+        // 32 kbit/s at 48 kHz: 60 bytes of main data per frame
+        let l3 = layout(LayerIndex::Layer3, MPEGVersion::MPEG1, 48000, 32_000);
+        assert_eq!(l3.preroll_frames(None), 10);
+        let l2 = layout(LayerIndex::Layer2, MPEGVersion::MPEG1, 48000, 192_000);
+        assert_eq!(l2.preroll_frames(None), 1);
+        let l1 = layout(LayerIndex::Layer1, MPEGVersion::MPEG1, 48000, 192_000);
+        assert_eq!(l1.preroll_frames(None), 2);
+    }
+
+    #[test]
+    fn plans_cbr_seeks() {
+        let l3 = StreamLayout {
+            first_frame: 10,
+            audio_start: 427,
+            ..layout(LayerIndex::Layer3, MPEGVersion::MPEG1, 44100, 128_000)
+        };
+        let seek = Seek::new(&l3, None, 10 * 1152 + 100);
+        assert_eq!((seek.frame, seek.preroll, seek.skip), (7, 3, 100));
+        assert_eq!(seek.byte_offset, 427 + 2925 - 1);
+        assert!(!seek.is_done());
+        // close to the start there is nothing to pre-roll from
+        let seek = Seek::new(&l3, None, 1500);
+        assert_eq!((seek.frame, seek.preroll, seek.skip), (0, 1, 348));
+        assert_eq!(seek.byte_offset, 427);
+        let seek = Seek::new(&l3, None, 0);
+        assert!(seek.is_done());
+    }
+}
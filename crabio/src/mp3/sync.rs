@@ -0,0 +1,490 @@
+//! Sync acquisition that does not lock onto random data.
+//!
+//! Twelve set bits are not much of a sync word: noise, tag data and damaged frames are full of
+//! them, and some of those pass as a header. [`FrameSync`] only declares lock when a run of
+//! consecutive headers agree on version, layer, sample rate and channel mode, each found
+//! where the previous one says its frame ends. In lock every frame is checked again, its
+//! header and the next one, and a frame that does not fit is reported as lost sync instead of
+//! being decoded.
+
+use crate::{
+    metadata,
+    mp3_decoder::{
+        BITRATE_TAB, DecodeError, DecodedFrame, LayerIndex, MP3Decoder, MPEGVersion, Mp3Error,
+        SAMPLERATE_TAB, SAMPLES_PER_FRAME_TAB, StereoMode,
+    },
+};
+
+/// Consecutive frames [`FrameSync::default`] wants to see before declaring lock.
+pub const DEFAULT_LOCK_FRAMES: usize = 3;
+/// Largest free format frame looked for when measuring its size, 640 kbit/s Layer III at
+/// 32 kHz is 2880 bytes.
+const MAX_FREE_FRAME_BYTES: usize = 4096;
+
+/// Header fields that stay the same through a stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameFormat {
+    pub version: MPEGVersion,
+    pub layer: LayerIndex,
+    pub samprate: u32,
+    pub mode: StereoMode,
+}
+
+impl FrameFormat {
+    /// Whether a frame of format `other` can belong to the same stream. Stereo and joint
+    /// stereo count as the same mode, some encoders pick one of them per frame.
+    pub fn is_consistent(&self, other: &FrameFormat) -> bool {
+        let two_channel = |mode| matches!(mode, StereoMode::Stereo | StereoMode::Joint);
+        self.version == other.version
+            && self.layer == other.layer
+            && self.samprate == other.samprate
+            && (self.mode == other.mode || two_channel(self.mode) && two_channel(other.mode))
+    }
+}
+
+/// Frame header read on its own, without unpacking it into a decoder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeaderFields {
+    pub format: FrameFormat,
+    /// bits/s, 0 for free format
+    pub bitrate: u32,
+    pub padding: bool,
+}
+
+impl HeaderFields {
+    /// Parses the header at the start of `header`. Rejects everything
+    /// [`MP3Decoder::unpack_frame_header`] does, and the reserved emphasis value too.
+    pub fn parse(header: &[u8]) -> Option<Self> {
+        let header = header.get(..4)?;
+        if header[0] != 0xff || header[1] & 0xe0 != 0xe0 {
+            return None;
+        }
+        let version = match (header[1] >> 3) & 0x03 {
+            0 => MPEGVersion::MPEG25,
+            2 => MPEGVersion::MPEG2,
+            3 => MPEGVersion::MPEG1,
+            _ => return None,
+        };
+        let layer = LayerIndex::from_u8(4 - ((header[1] >> 1) & 0x03)).ok()?;
+        let br_idx = (header[2] >> 4) as usize;
+        let sr_idx = ((header[2] >> 2) & 0x03) as usize;
+        if br_idx == 0x0f || sr_idx == 0x03 || header[3] & 0x03 == 0x02 {
+            return None;
+        }
+        let mode = match header[3] >> 6 {
+            0x00 => StereoMode::Stereo,
+            0x01 => StereoMode::Joint,
+            0x02 => StereoMode::Dual,
+            _ => StereoMode::Mono,
+        };
+        Some(HeaderFields {
+            format: FrameFormat {
+                version,
+                layer,
+                samprate: SAMPLERATE_TAB[version as usize][sr_idx] as u32,
+                mode,
+            },
+            bitrate: BITRATE_TAB[version as usize][layer as usize - 1][br_idx] as u32 * 1000,
+            padding: header[2] & 0x02 != 0,
+        })
+    }
+
+    /// Bytes per slot, the unit of frame sizes and padding: 4 for Layer I, 1 for the others.
+    pub fn slot_bytes(&self) -> usize {
+        if self.format.layer == LayerIndex::Layer1 {
+            4
+        } else {
+            1
+        }
+    }
+
+    /// Bytes in the frame, padding included; `free_bytes` is the unpadded size of a free
+    /// format frame.
+    pub fn frame_bytes(&self, free_bytes: usize) -> usize {
+        let slot = self.slot_bytes();
+        let unpadded = if self.bitrate == 0 {
+            free_bytes
+        } else {
+            let samples = SAMPLES_PER_FRAME_TAB[self.format.version as usize]
+                [self.format.layer as usize - 1] as usize;
+            slot * (samples * self.bitrate as usize / (8 * self.format.samprate as usize * slot))
+        };
+        unpadded + if self.padding { slot } else { 0 }
+    }
+}
+
+/// Outcome of checking bytes that may not all be there yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Check<T> {
+    Valid(T),
+    Invalid,
+    /// the input ends before it can tell
+    NeedMore,
+}
+
+/// The stream sync is locked to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Lock {
+    format: FrameFormat,
+    free_format: bool,
+    /// unpadded frame size, for free format
+    free_bytes: usize,
+}
+
+impl Lock {
+    /// Reads the header at the start of `data`, which has to continue the locked stream.
+    fn header(&self, data: &[u8]) -> Check<HeaderFields> {
+        if data.len() < 4 {
+            return Check::NeedMore;
+        }
+        match HeaderFields::parse(data) {
+            Some(fields)
+                if fields.format.is_consistent(&self.format)
+                    && (fields.bitrate == 0) == self.free_format =>
+            {
+                Check::Valid(fields)
+            }
+            _ => Check::Invalid,
+        }
+    }
+
+    /// Checks the frame at the start of `data` and where it says the next one starts: a
+    /// consistent header, a tag, or the end of the input all do.
+    fn check_frame(&self, data: &[u8]) -> Check<usize> {
+        let fields = match self.header(data) {
+            Check::Valid(fields) => fields,
+            Check::Invalid => return Check::Invalid,
+            Check::NeedMore => return Check::NeedMore,
+        };
+        let frame_bytes = fields.frame_bytes(self.free_bytes);
+        let next = match data.get(frame_bytes..) {
+            Some(next) => next,
+            None => return Check::NeedMore,
+        };
+        if metadata::tag_bytes(next).is_some() {
+            return Check::Valid(frame_bytes);
+        }
+        match self.header(next) {
+            Check::Valid(_) => Check::Valid(frame_bytes),
+            Check::Invalid => Check::Invalid,
+            Check::NeedMore => Check::NeedMore,
+        }
+    }
+}
+
+/// What [`FrameSync::decode_frame`] did with the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// not locked, input that cannot start a locked run of frames was consumed
+    Searching,
+    /// lock declared, the frame decoded is the first of the run that confirmed it
+    Locked,
+    /// frame decoded in lock
+    InSync,
+    /// the frame at the start of the input does not continue the locked stream: nothing was
+    /// consumed, the next call searches from there
+    Lost,
+}
+
+/// Result of [`FrameSync::decode_frame`].
+#[derive(Debug, Clone, Copy)]
+pub struct SyncedFrame {
+    pub event: SyncEvent,
+    pub frame: DecodedFrame,
+}
+
+/// Sync state of a stream read through [`FrameSync::decode_frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSync {
+    lock_frames: usize,
+    lock: Option<Lock>,
+    /// no input follows what the caller has, see [`FrameSync::finish`]
+    at_eof: bool,
+}
+
+impl Default for FrameSync {
+    fn default() -> Self {
+        FrameSync::new(DEFAULT_LOCK_FRAMES)
+    }
+}
+
+impl FrameSync {
+    /// Sync that declares lock after `lock_frames` consecutive consistent frames (at least 1,
+    /// which only checks the frame and the header after it).
+    pub fn new(lock_frames: usize) -> Self {
+        FrameSync {
+            lock_frames: lock_frames.max(1),
+            lock: None,
+            at_eof: false,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// Format of the stream sync is locked to.
+    pub fn format(&self) -> Option<FrameFormat> {
+        self.lock.map(|lock| lock.format)
+    }
+
+    /// Drops the lock, e.g. after the caller skipped input.
+    pub fn reset(&mut self) {
+        self.lock = None;
+    }
+
+    /// Marks the end of the stream: the input of the next calls is all that is left of it. A
+    /// run of frames cut short by the end of the input then locks if at least one whole frame
+    /// checks out, so streams shorter than the run, and their tails after a lost sync, are
+    /// decoded too.
+    pub fn finish(&mut self) {
+        self.at_eof = true;
+    }
+
+    /// Checks whether `lock_frames` consistent frames start at the start of `data`.
+    fn check_run(&self, data: &[u8]) -> Check<Lock> {
+        if data.len() < 4 {
+            return if self.at_eof {
+                Check::Invalid
+            } else {
+                Check::NeedMore
+            };
+        }
+        let Some(first) = HeaderFields::parse(data) else {
+            return Check::Invalid;
+        };
+        let mut lock = Lock {
+            format: first.format,
+            free_format: first.bitrate == 0,
+            free_bytes: 0,
+        };
+        if lock.free_format {
+            /* measure the frame up to the next header of the same stream */
+            let pad = if first.padding { first.slot_bytes() } else { 0 };
+            let search = &data[..data.len().min(MAX_FREE_FRAME_BYTES)];
+            match (4..search.len()).find(|&at| matches!(lock.header(&data[at..]), Check::Valid(_)))
+            {
+                Some(next) => lock.free_bytes = next - pad,
+                None if data.len() < MAX_FREE_FRAME_BYTES && !self.at_eof => {
+                    return Check::NeedMore;
+                }
+                None => return Check::Invalid,
+            }
+        }
+
+        let mut offset = 0;
+        for _ in 0..self.lock_frames {
+            let rest = &data[offset..];
+            if offset > 0 && metadata::tag_bytes(rest).is_some() {
+                /* the stream ends before the run does */
+                break;
+            }
+            match lock.check_frame(rest) {
+                Check::Valid(frame_bytes) => offset += frame_bytes,
+                Check::Invalid => return Check::Invalid,
+                Check::NeedMore if self.at_eof => {
+                    /* the stream ends in this frame or right after it */
+                    let whole = match lock.header(rest) {
+                        Check::Valid(fields) => fields.frame_bytes(lock.free_bytes) <= rest.len(),
+                        _ => false,
+                    };
+                    if offset == 0 && !whole {
+                        return Check::Invalid;
+                    }
+                    break;
+                }
+                Check::NeedMore => return Check::NeedMore,
+            }
+        }
+        Check::Valid(lock)
+    }
+
+    /// Decodes the next frame of `input` like [`MP3Decoder::decode_frame`], but only in lock.
+    ///
+    /// Until lock is declared input is consumed without output ([`SyncEvent::Searching`]); a
+    /// run of frames cut short by the end of `input` is an `InDataUnderflow`, to be retried
+    /// with more input, or locks after [`FrameSync::finish`]. In lock each frame is checked
+    /// before it is decoded, one that does not fit drops the lock ([`SyncEvent::Lost`]). Tags
+    /// between frames are consumed as by [`MP3Decoder::decode_frame`], one longer than `input`
+    /// over several calls. A frame in lock that fails to decode is returned as an error with
+    /// the bytes it used up, in [`DecodeError::bytes_consumed`].
+    pub fn decode_frame(
+        &mut self,
+        dec: &mut MP3Decoder,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<SyncedFrame, DecodeError> {
+        let no_output = |dec: &MP3Decoder, event, bytes_consumed| SyncedFrame {
+            event,
+            frame: DecodedFrame {
+                bytes_consumed,
+                samples_written: 0,
+                info: dec.m_MP3FrameInfo,
+            },
+        };
+
+        /* the rest of a tag that started in earlier input, the decoder skips it */
+        if dec.tag_bytes_left() > 0 {
+            let event = if self.lock.is_some() {
+                SyncEvent::InSync
+            } else {
+                SyncEvent::Searching
+            };
+            let frame = dec.decode_frame(input, out)?;
+            return Ok(no_output(dec, event, frame.bytes_consumed));
+        }
+
+        if let Some(lock) = self.lock {
+            if metadata::tag_bytes(input).is_none() && lock.check_frame(input) == Check::Invalid {
+                self.lock = None;
+                /* the bit reservoir holds main data of frames before the gap, not of those after it */
+                dec.m_MP3DecInfo.mainDataBegin = 0;
+                dec.m_MP3DecInfo.mainDataBytes = 0;
+                return Ok(no_output(dec, SyncEvent::Lost, 0));
+            }
+            return self.decode_locked(dec, input, out, SyncEvent::InSync);
+        }
+
+        for start in 0..input.len() {
+            let rest = &input[start..];
+            if metadata::tag_bytes(rest).is_some() {
+                if start > 0 {
+                    return Ok(no_output(dec, SyncEvent::Searching, start));
+                }
+                let frame = dec.decode_frame(input, out)?;
+                return Ok(no_output(dec, SyncEvent::Searching, frame.bytes_consumed));
+            }
+            match self.check_run(rest) {
+                Check::Invalid => continue,
+                Check::NeedMore if start == 0 => {
+                    return Err(DecodeError::new(Mp3Error::InDataUnderflow { offset: 0 }, 0));
+                }
+                Check::Valid(lock) if start == 0 => {
+                    self.lock = Some(lock);
+                    return self.decode_locked(dec, input, out, SyncEvent::Locked);
+                }
+                /* consume up to the candidate, it is checked again at the start of the input */
+                Check::Valid(_) | Check::NeedMore => {
+                    return Ok(no_output(dec, SyncEvent::Searching, start));
+                }
+            }
+        }
+        /* keep the last bytes, they may start a header, unless no more input follows */
+        let searched = if self.at_eof {
+            input.len()
+        } else {
+            input.len().saturating_sub(3)
+        };
+        Ok(no_output(dec, SyncEvent::Searching, searched))
+    }
+
+    /// Decodes a frame in lock. Its main data may have started in input before the lock, a
+    /// frame missing it is consumed without output.
+    fn decode_locked(
+        &self,
+        dec: &mut MP3Decoder,
+        input: &[u8],
+        out: &mut [i16],
+        event: SyncEvent,
+    ) -> Result<SyncedFrame, DecodeError> {
+        let mut consumed = 0;
+        let samples_written = match dec.decode(input, out, false, &mut consumed) {
+            Ok(samples) => samples,
+            Err(Mp3Error::MainDataUnderflow { .. }) => 0,
+            Err(e) => return Err(DecodeError::new(e, consumed)),
+        };
+        Ok(SyncedFrame {
+            event,
+            frame: DecodedFrame {
+                bytes_consumed: consumed,
+                samples_written,
+                info: dec.m_MP3FrameInfo,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* MPEG-1 Layer III, 128 kbit/s, 44.1 kHz, joint stereo */
+    const HEADER: [u8; 4] = [0xff, 0xfb, 0x90, 0x44];
+
+    /// `frames` empty frames with `HEADER`, padded like a 44.1 kHz encoder would.
+    fn stream(frames: usize) -> [u8; 4 * 418] {
+        let mut data = [0u8; 4 * 418];
+        let mut offset = 0;
+        for frame in 0..frames {
+            let mut header = HEADER;
+            let padded = frame % 3 != 0;
+            if padded {
+                header[2] |= 0x02;
+            }
+            data[offset..offset + 4].copy_from_slice(&header);
+            offset += 417 + padded as usize;
+        }
+        data
+    }
+
+    #[test]
+    fn parses_header_fields() {
+        let fields = HeaderFields::parse(&HEADER).unwrap();
+        assert_eq!(fields.format.samprate, 44100);
+        assert_eq!(fields.format.mode, StereoMode::Joint);
+        assert_eq!((fields.bitrate, fields.frame_bytes(0)), (128_000, 417));
+        // Layer I, 32 kbit/s, 48 kHz, padded: 32 bytes and a 4 byte slot
+        let layer1 = HeaderFields::parse(&[0xff, 0xff, 0x16, 0xc0]).unwrap();
+        assert_eq!(layer1.frame_bytes(0), 36);
+        // free format takes the measured size
+        let free = HeaderFields::parse(&[0xff, 0xfb, 0x00, 0x44]).unwrap();
+        assert_eq!(free.frame_bytes(600), 600);
+        // reserved version, layer, bitrate, sample rate and emphasis
+        for bad in [
+            [0xff, 0xeb, 0x90, 0x44],
+            [0xff, 0xf9, 0x90, 0x44],
+            [0xff, 0xfb, 0xf0, 0x44],
+            [0xff, 0xfb, 0x9c, 0x44],
+            [0xff, 0xfb, 0x90, 0x46],
+        ] {
+            assert_eq!(HeaderFields::parse(&bad), None, "{bad:x?}");
+        }
+    }
+
+    #[test]
+    fn locks_on_a_run_of_consistent_frames() {
+        let sync = FrameSync::new(3);
+        let data = stream(4);
+        assert!(matches!(sync.check_run(&data), Check::Valid(_)));
+        // the third header is missing
+        assert_eq!(sync.check_run(&stream(2)), Check::Invalid);
+        // cut short: more input needed to tell
+        assert_eq!(sync.check_run(&data[..900]), Check::NeedMore);
+        // the second frame changes the sample rate
+        let mut changed = data;
+        changed[419] ^= 0x04;
+        assert_eq!(sync.check_run(&changed), Check::Invalid);
+        // stereo and joint stereo mix
+        let mut mixed = data;
+        mixed[420] &= 0x3f;
+        assert!(matches!(sync.check_run(&mixed), Check::Valid(_)));
+        // a stream ending in a tag
+        let mut tagged = stream(2);
+        tagged[835..838].copy_from_slice(b"TAG");
+        assert!(matches!(sync.check_run(&tagged), Check::Valid(_)));
+    }
+
+    #[test]
+    fn locks_on_a_short_run_at_the_end_of_the_stream() {
+        let mut sync = FrameSync::new(3);
+        let data = stream(3);
+        sync.finish();
+        // one or two whole frames, or two and the start of the third
+        assert!(matches!(sync.check_run(&data[..417]), Check::Valid(_)));
+        assert!(matches!(sync.check_run(&data[..835]), Check::Valid(_)));
+        assert!(matches!(sync.check_run(&data[..900]), Check::Valid(_)));
+        // not even one whole frame
+        assert_eq!(sync.check_run(&data[..400]), Check::Invalid);
+        assert_eq!(sync.check_run(&data[..3]), Check::Invalid);
+    }
+}
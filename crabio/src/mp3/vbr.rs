@@ -0,0 +1,79 @@
+//! VBR metadata of a Layer III stream, from whichever header the encoder wrote.
+//!
+//! Both header kinds live in the first frame and answer the same questions: how long the
+//! stream plays and where to seek to. [`VbrHeader`] picks the one that is present.
+
+use super::{vbri::VbriHeader, xing::XingHeader};
+use crate::mp3_decoder::{MPEGVersion, StereoMode};
+
+/// Xing/Info or VBRI header of the first frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VbrHeader<'a> {
+    Xing(XingHeader),
+    Vbri(VbriHeader<'a>),
+}
+
+/// Whether `frame` (starting at its sync word) is a tag frame carrying a Xing/Info or VBRI
+/// header rather than audio.
+pub fn is_tag_frame(frame: &[u8], fh_bytes: usize, si_bytes: usize) -> bool {
+    frame
+        .get(fh_bytes..)
+        .is_some_and(|after_header| super::xing::is_tag_frame(after_header, si_bytes))
+        || super::vbri::is_tag_frame(frame, fh_bytes, si_bytes)
+}
+
+impl<'a> VbrHeader<'a> {
+    /// Parses the Xing/Info or VBRI header of `frame`, which starts at the sync word of a
+    /// Layer III frame; see [`XingHeader::parse`] for the arguments.
+    pub fn parse(
+        frame: &'a [u8],
+        fh_bytes: usize,
+        version: MPEGVersion,
+        mode: StereoMode,
+        samprate: i32,
+    ) -> Option<Self> {
+        XingHeader::parse(frame, fh_bytes, version, mode, samprate)
+            .map(VbrHeader::Xing)
+            .or_else(|| VbriHeader::parse(frame, version, samprate).map(VbrHeader::Vbri))
+    }
+
+    /// Audio frames in the stream, if the header says.
+    pub fn frames(&self) -> Option<u32> {
+        match self {
+            VbrHeader::Xing(xing) => xing.frames,
+            VbrHeader::Vbri(vbri) => Some(vbri.frames),
+        }
+    }
+
+    /// Bytes in the stream from the tag frame on, if the header says.
+    pub fn bytes(&self) -> Option<u32> {
+        match self {
+            VbrHeader::Xing(xing) => xing.bytes,
+            VbrHeader::Vbri(vbri) => Some(vbri.bytes),
+        }
+    }
+
+    /// PCM samples per channel in the whole stream.
+    pub fn total_samples(&self) -> Option<u64> {
+        match self {
+            VbrHeader::Xing(xing) => xing.total_samples(),
+            VbrHeader::Vbri(vbri) => vbri.total_samples(),
+        }
+    }
+
+    /// Playing time in milliseconds.
+    pub fn duration_ms(&self) -> Option<u64> {
+        match self {
+            VbrHeader::Xing(xing) => xing.duration_ms(),
+            VbrHeader::Vbri(vbri) => vbri.duration_ms(),
+        }
+    }
+
+    /// Approximate byte offset, from the tag frame, of the frame holding `sample`.
+    pub fn seek_offset(&self, sample: u64) -> Option<u64> {
+        match self {
+            VbrHeader::Xing(xing) => xing.seek_offset(sample),
+            VbrHeader::Vbri(vbri) => vbri.seek_offset(sample),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! VBRI header, written by Fraunhofer encoders.
+//!
+//! The header sits at a fixed 32 bytes after the 4-byte frame header of the first Layer III
+//! frame, whatever the version and channel mode. Its seek table is a list of chunk sizes:
+//! entry i is the size in bytes of the i-th run of `frames_per_entry` frames.
+
+use crate::mp3_decoder::{MPEGVersion, SAMPLES_PER_FRAME_TAB};
+
+/// Offset of `"VBRI"` from the sync word of the frame.
+pub const VBRI_OFFSET: usize = 4 + 32;
+/// Bytes from `"VBRI"` to the seek table.
+const VBRI_HEADER_BYTES: usize = 26;
+
+/// VBRI header of the first frame of a Layer III stream, borrowing the seek table from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VbriHeader<'a> {
+    pub version: u16,
+    /// encoder delay, in samples
+    pub delay: u16,
+    pub quality: u16,
+    /// bytes in the stream, the VBRI frame included
+    pub bytes: u32,
+    /// frames in the stream
+    pub frames: u32,
+    /// frames covered by one seek table entry
+    pub frames_per_entry: u32,
+    /// raw seek table, `entry_bytes` big-endian bytes per entry
+    pub toc: &'a [u8],
+    /// size of one seek table entry, 1 - 4 bytes
+    pub entry_bytes: usize,
+    /// seek table entries are multiplied by this to get bytes
+    pub scale: u32,
+    pub samprate: u32,
+    /// PCM samples per channel in one frame
+    pub samples_per_frame: u32,
+}
+
+/// Whether `frame` (starting at its sync word) holds a VBRI header after all-zero side info.
+pub fn is_tag_frame(frame: &[u8], fh_bytes: usize, si_bytes: usize) -> bool {
+    match (
+        frame.get(fh_bytes..fh_bytes + si_bytes),
+        frame.get(VBRI_OFFSET..VBRI_OFFSET + 4),
+    ) {
+        (Some(side_info), Some(magic)) => side_info.iter().all(|&b| b == 0) && magic == b"VBRI",
+        _ => false,
+    }
+}
+
+impl<'a> VbriHeader<'a> {
+    /// Parses the VBRI header of `frame`, which starts at the sync word of a Layer III frame.
+    ///
+    /// `version` and `samprate` are the header fields as
+    /// [`MP3Decoder::unpack_frame_header`](crate::mp3_decoder::MP3Decoder::unpack_frame_header)
+    /// unpacks them. Returns `None` if the frame carries no VBRI header.
+    pub fn parse(frame: &'a [u8], version: MPEGVersion, samprate: i32) -> Option<Self> {
+        let header = frame.get(VBRI_OFFSET..VBRI_OFFSET + VBRI_HEADER_BYTES)?;
+        if &header[..4] != b"VBRI" {
+            return None;
+        }
+        let u16_at = |i: usize| u16::from_be_bytes([header[i], header[i + 1]]);
+        let u32_at = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+        let entries = u16_at(18) as usize;
+        let entry_bytes = u16_at(22) as usize;
+        if !(1..=4).contains(&entry_bytes) {
+            return None;
+        }
+        let table_start = VBRI_OFFSET + VBRI_HEADER_BYTES;
+        let toc = frame.get(table_start..table_start + entries * entry_bytes)?;
+
+        Some(VbriHeader {
+            version: u16_at(4),
+            delay: u16_at(6),
+            quality: u16_at(8),
+            bytes: u32_at(10),
+            frames: u32_at(14),
+            frames_per_entry: u16_at(24) as u32,
+            toc,
+            entry_bytes,
+            scale: u16_at(20) as u32,
+            samprate: samprate as u32,
+            samples_per_frame: SAMPLES_PER_FRAME_TAB[version as usize][2] as u32,
+        })
+    }
+
+    /// Seek table entries: the size in bytes of each run of `frames_per_entry` frames.
+    pub fn entries(&self) -> impl Iterator<Item = u32> + 'a {
+        let scale = self.scale;
+        self.toc
+            .chunks_exact(self.entry_bytes)
+            .map(move |bytes| bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u32) * scale)
+    }
+
+    /// PCM samples per channel in the whole stream.
+    pub fn total_samples(&self) -> Option<u64> {
+        Some(self.frames as u64 * self.samples_per_frame as u64)
+    }
+
+    /// Playing time in milliseconds.
+    pub fn duration_ms(&self) -> Option<u64> {
+        Some(self.total_samples()? * 1000 / self.samprate as u64)
+    }
+
+    /// Byte offset from the VBRI frame of the frame `sample` (per channel) falls into,
+    /// approximately: within one seek table entry the offset is interpolated linearly.
+    ///
+    /// Without a usable seek table the offset is interpolated over the whole stream.
+    pub fn seek_offset(&self, sample: u64) -> Option<u64> {
+        let total = self.total_samples()?;
+        if total == 0 {
+            return Some(0);
+        }
+        let sample = sample.min(total);
+        if self.toc.is_empty() || self.frames_per_entry == 0 {
+            return Some(self.bytes as u64 * sample / total);
+        }
+        let entry_samples = self.frames_per_entry as u64 * self.samples_per_frame as u64;
+        let mut offset = 0u64;
+        let mut remaining = sample;
+        for chunk in self.entries() {
+            if remaining < entry_samples {
+                return Some(offset + chunk as u64 * remaining / entry_samples);
+            }
+            offset += chunk as u64;
+            remaining -= entry_samples;
+        }
+        Some(offset.min(self.bytes as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 44.1 kHz frame with a VBRI header: 100 frames in 4 entries of 25, 2-byte entries.
+    fn vbri_frame() -> [u8; 417] {
+        let mut frame = [0u8; 417];
+        frame[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x44]);
+        let header: [u8; 26] = [
+            b'V', b'B', b'R', b'I', //
+            // version 1, delay 1153, quality 75
+            0x00, 0x01, 0x04, 0x81, 0x00, 0x4b, //
+            // 40000 bytes, 100 frames
+            0x00, 0x00, 0x9c, 0x40, 0x00, 0x00, 0x00, 0x64, //
+            // 4 entries, scale 2, 2 bytes per entry, 25 frames per entry
+            0x00, 0x04, 0x00, 0x02, 0x00, 0x02, 0x00, 0x19,
+        ];
+        frame[VBRI_OFFSET..VBRI_OFFSET + 26].copy_from_slice(&header);
+        let table = [0x0f, 0xa0, 0x0f, 0xa0, 0x13, 0x88, 0x0b, 0xb8]; // 4000, 4000, 5000, 3000
+        frame[VBRI_OFFSET + 26..VBRI_OFFSET + 34].copy_from_slice(&table);
+        frame
+    }
+
+    #[test]
+    fn parses_header_and_seek_table() {
+        let frame = vbri_frame();
+        let vbri = VbriHeader::parse(&frame, MPEGVersion::MPEG1, 44100).unwrap();
+        assert_eq!((vbri.version, vbri.delay, vbri.quality), (1, 1153, 75));
+        assert_eq!((vbri.bytes, vbri.frames), (40000, 100));
+        assert_eq!(vbri.frames_per_entry, 25);
+        assert!(vbri.entries().eq([8000, 8000, 10000, 6000]));
+        assert!(is_tag_frame(&frame, 4, 32));
+    }
+
+    #[test]
+    fn duration_and_seek_offsets() {
+        let frame = vbri_frame();
+        let vbri = VbriHeader::parse(&frame, MPEGVersion::MPEG1, 44100).unwrap();
+        assert_eq!(vbri.total_samples(), Some(115_200));
+        assert_eq!(vbri.duration_ms(), Some(2612));
+        assert_eq!(vbri.seek_offset(0), Some(0));
+        // start of the third entry, then half way through it
+        assert_eq!(vbri.seek_offset(50 * 1152), Some(16000));
+        assert_eq!(vbri.seek_offset(50 * 1152 + 25 * 576), Some(21000));
+        assert_eq!(vbri.seek_offset(u64::MAX), Some(32000));
+    }
+
+    #[test]
+    fn rejects_other_frames() {
+        let mut frame = vbri_frame();
+        frame[VBRI_OFFSET] = b'X';
+        assert_eq!(VbriHeader::parse(&frame, MPEGVersion::MPEG1, 44100), None);
+        let mut frame = vbri_frame();
+        frame[10] = 1;
+        assert!(!is_tag_frame(&frame, 4, 32));
+    }
+}
@@ -0,0 +1,387 @@
+//! Xing/Info VBR header and LAME extension.
+//!
+//! Encoders put this tag in the main data of an otherwise empty first Layer III frame: all
+//! side info bits zero, then `"Xing"` (VBR) or `"Info"` (CBR) right after the side info.
+//! It carries the frame and byte counts needed for the stream duration, a 100-entry seek
+//! table, and, when written by LAME or a LAME-compatible encoder, the encoder delay and
+//! padding, ReplayGain values and a CRC over the tag frame.
+
+use crate::mp3_decoder::{
+    MPEGVersion, SAMPLES_PER_FRAME_TAB, SIBYTES_MPEG1_MONO, SIBYTES_MPEG1_STEREO,
+    SIBYTES_MPEG2_MONO, SIBYTES_MPEG2_STEREO, StereoMode,
+};
+
+const FLAG_FRAMES: u32 = 0x0001;
+const FLAG_BYTES: u32 = 0x0002;
+const FLAG_TOC: u32 = 0x0004;
+const FLAG_QUALITY: u32 = 0x0008;
+
+/// Size of the LAME extension, from the encoder string to the tag CRC.
+const LAME_TAG_BYTES: usize = 36;
+
+/// Which of the two tag names was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XingKind {
+    /// `"Xing"`: variable bitrate stream
+    Xing,
+    /// `"Info"`: constant bitrate stream, same layout
+    Info,
+}
+
+/// ReplayGain adjustment stored in the LAME tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayGain {
+    /// who set the value (0 unset, 1 artist, 2 user, 3 automatic)
+    pub originator: u8,
+    /// gain in 1/10 dB
+    pub gain_tenths_db: i16,
+}
+
+/// LAME extension of the Xing/Info tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LameTag {
+    /// encoder name and version, e.g. `b"LAME3.100"`
+    pub encoder: [u8; 9],
+    pub revision: u8,
+    /// 1 CBR, 2 ABR, 3 - 6 VBR methods, 8 - 9 two pass CBR/ABR
+    pub vbr_method: u8,
+    pub lowpass_hz: u32,
+    /// peak signal amplitude, Q23 (1.0 = full scale), 0 if unknown
+    pub peak_amplitude: u32,
+    pub track_gain: Option<ReplayGain>,
+    pub album_gain: Option<ReplayGain>,
+    /// nspsytune, nssafejoint, nogap flags in the high nibble, ATH type in the low one
+    pub encoding_flags: u8,
+    /// ABR target or VBR minimum bitrate in kbps, 255 meaning 255 or more
+    pub bitrate_kbps: u8,
+    /// samples the encoder added in front of the audio
+    pub encoder_delay: u16,
+    /// samples the encoder added after the audio, to fill the last frame
+    pub encoder_padding: u16,
+    /// noise shaping, stereo mode, unwise settings and source sample rate bits
+    pub misc: u8,
+    /// global gain change applied by mp3gain, in 1.5 dB steps
+    pub mp3_gain: i8,
+    /// surround info (bits 11 - 13) and preset (bits 0 - 10)
+    pub preset: u16,
+    /// bytes from the tag frame to the end of the audio
+    pub music_length: u32,
+    pub music_crc: u16,
+    pub tag_crc: u16,
+    /// `tag_crc` matches the bytes of the tag frame in front of it
+    pub tag_crc_valid: bool,
+}
+
+/// Xing/Info tag of the first frame of a Layer III stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XingHeader {
+    pub kind: XingKind,
+    /// audio frames in the stream, not counting the tag frame
+    pub frames: Option<u32>,
+    /// bytes in the stream, the tag frame included
+    pub bytes: Option<u32>,
+    /// TOC[i] * bytes / 256 is the offset of i percent into the stream
+    pub toc: Option<[u8; 100]>,
+    /// encoder quality indicator, 0 best - 100 worst
+    pub quality: Option<u32>,
+    pub lame: Option<LameTag>,
+    pub samprate: u32,
+    /// PCM samples per channel in one frame
+    pub samples_per_frame: u32,
+}
+
+/// Side info bytes in a Layer III frame with this version and mode.
+pub const fn side_info_bytes(version: MPEGVersion, mode: StereoMode) -> usize {
+    match (version, mode) {
+        (MPEGVersion::MPEG1, StereoMode::Mono) => SIBYTES_MPEG1_MONO,
+        (MPEGVersion::MPEG1, _) => SIBYTES_MPEG1_STEREO,
+        (_, StereoMode::Mono) => SIBYTES_MPEG2_MONO,
+        (_, _) => SIBYTES_MPEG2_STEREO,
+    }
+}
+
+/// Whether `after_header` (a Layer III frame past its header and CRC) holds a Xing/Info tag.
+///
+/// Only the magic right behind all-zero side info counts, so a real audio frame can not be
+/// taken for a tag: with zero side info it would decode to silence anyway.
+pub fn is_tag_frame(after_header: &[u8], si_bytes: usize) -> bool {
+    match after_header.get(..si_bytes + 4) {
+        Some(bytes) => {
+            let (side_info, magic) = bytes.split_at(si_bytes);
+            side_info.iter().all(|&b| b == 0) && (magic == b"Xing" || magic == b"Info")
+        }
+        None => false,
+    }
+}
+
+fn read_u32(buf: &[u8], pos: &mut usize) -> Option<u32> {
+    let bytes = buf.get(*pos..*pos + 4)?;
+    *pos += 4;
+    Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// CRC-16 as LAME computes it (polynomial 0x8005, reflected, initial value 0).
+fn lame_crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn replay_gain(field: u16, name: u16) -> Option<ReplayGain> {
+    /* 3 bits name, 3 bits originator, sign bit, 9 bits gain */
+    if field >> 13 != name {
+        return None;
+    }
+    let gain = (field & 0x01ff) as i16;
+    Some(ReplayGain {
+        originator: ((field >> 10) & 0x07) as u8,
+        gain_tenths_db: if field & 0x0200 != 0 { -gain } else { gain },
+    })
+}
+
+impl LameTag {
+    /// Parses the 36 tag bytes at `frame[pos..]`; the CRC covers `frame[..pos + 34]`.
+    fn parse(frame: &[u8], pos: usize) -> Option<Self> {
+        let tag = frame.get(pos..pos + LAME_TAG_BYTES)?;
+        /* LAME, and encoders writing a LAME-compatible tag (FFmpeg: "Lavc"/"Lavf") */
+        if !(tag.starts_with(b"LAME") || tag.starts_with(b"Lav") || tag.starts_with(b"L3.99")) {
+            return None;
+        }
+        let u16_at = |i: usize| u16::from_be_bytes([tag[i], tag[i + 1]]);
+        let tag_crc = u16_at(34);
+        Some(LameTag {
+            encoder: tag[..9].try_into().unwrap(),
+            revision: tag[9] >> 4,
+            vbr_method: tag[9] & 0x0f,
+            lowpass_hz: tag[10] as u32 * 100,
+            peak_amplitude: u32::from_be_bytes(tag[11..15].try_into().unwrap()),
+            track_gain: replay_gain(u16_at(15), 1),
+            album_gain: replay_gain(u16_at(17), 2),
+            encoding_flags: tag[19],
+            bitrate_kbps: tag[20],
+            encoder_delay: ((tag[21] as u16) << 4) | (tag[22] as u16 >> 4),
+            encoder_padding: (((tag[22] & 0x0f) as u16) << 8) | tag[23] as u16,
+            misc: tag[24],
+            mp3_gain: tag[25] as i8,
+            preset: u16_at(26),
+            music_length: u32::from_be_bytes(tag[28..32].try_into().unwrap()),
+            music_crc: u16_at(32),
+            tag_crc,
+            tag_crc_valid: lame_crc16(&frame[..pos + 34]) == tag_crc,
+        })
+    }
+}
+
+impl XingHeader {
+    /// Parses the tag of `frame`, which starts at the sync word of a Layer III frame.
+    ///
+    /// `fh_bytes` is the header size (4, or 6 with CRC) and the other arguments are the
+    /// header fields as [`MP3Decoder::unpack_frame_header`](crate::mp3_decoder::MP3Decoder::unpack_frame_header)
+    /// unpacks them. Returns `None` if the frame carries no tag.
+    pub fn parse(
+        frame: &[u8],
+        fh_bytes: usize,
+        version: MPEGVersion,
+        mode: StereoMode,
+        samprate: i32,
+    ) -> Option<Self> {
+        let si_bytes = side_info_bytes(version, mode);
+        if !is_tag_frame(frame.get(fh_bytes..)?, si_bytes) {
+            return None;
+        }
+        let mut pos = fh_bytes + si_bytes;
+        let kind = if &frame[pos..pos + 4] == b"Xing" {
+            XingKind::Xing
+        } else {
+            XingKind::Info
+        };
+        pos += 4;
+        let flags = read_u32(frame, &mut pos)?;
+        let frames = if flags & FLAG_FRAMES != 0 {
+            Some(read_u32(frame, &mut pos)?)
+        } else {
+            None
+        };
+        let bytes = if flags & FLAG_BYTES != 0 {
+            Some(read_u32(frame, &mut pos)?)
+        } else {
+            None
+        };
+        let toc = if flags & FLAG_TOC != 0 {
+            let toc: [u8; 100] = frame.get(pos..pos + 100)?.try_into().unwrap();
+            pos += 100;
+            Some(toc)
+        } else {
+            None
+        };
+        let quality = if flags & FLAG_QUALITY != 0 {
+            Some(read_u32(frame, &mut pos)?)
+        } else {
+            None
+        };
+
+        Some(XingHeader {
+            kind,
+            frames,
+            bytes,
+            toc,
+            quality,
+            lame: LameTag::parse(frame, pos),
+            samprate: samprate as u32,
+            samples_per_frame: SAMPLES_PER_FRAME_TAB[version as usize][2] as u32,
+        })
+    }
+
+    /// PCM samples per channel in the whole stream, encoder delay and padding included.
+    pub fn total_samples(&self) -> Option<u64> {
+        Some(self.frames? as u64 * self.samples_per_frame as u64)
+    }
+
+    /// Playing time in milliseconds, without the encoder delay and padding if the LAME tag
+    /// gives them.
+    pub fn duration_ms(&self) -> Option<u64> {
+        let mut samples = self.total_samples()?;
+        if let Some(lame) = &self.lame {
+            samples =
+                samples.saturating_sub(lame.encoder_delay as u64 + lame.encoder_padding as u64);
+        }
+        Some(samples * 1000 / self.samprate as u64)
+    }
+
+    /// Byte offset from the tag frame of the frame `sample` (per channel) falls into,
+    /// approximately: the TOC only has a resolution of 1 % of the stream.
+    ///
+    /// Without a TOC the offset is interpolated linearly, as for a CBR stream.
+    pub fn seek_offset(&self, sample: u64) -> Option<u64> {
+        let total = self.total_samples()?;
+        let bytes = self.bytes? as u64;
+        if total == 0 {
+            return Some(0);
+        }
+        let sample = sample.min(total);
+        let Some(toc) = &self.toc else {
+            return Some(bytes * sample / total);
+        };
+        /* percent into the stream, 8 fractional bits, then interpolate between TOC entries */
+        let percent = (sample * 100 * 256 / total).min(100 * 256 - 1);
+        let i = (percent >> 8) as usize;
+        let frac = percent & 0xff;
+        let lo = toc[i] as u64;
+        let hi = if i < 99 { toc[i + 1] as u64 } else { 256 };
+        let position = lo * 256 + (hi.max(lo) - lo) * frac;
+        Some(bytes * position / (256 * 256))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 stereo 128 kbps 44.1 kHz tag frame as LAME 3.100 writes it.
+    fn lame_frame() -> [u8; 417] {
+        let mut frame = [0u8; 417];
+        frame[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x64]);
+        let mut pos = 4 + 32;
+        let mut put = |bytes: &[u8]| {
+            frame[pos..pos + bytes.len()].copy_from_slice(bytes);
+            pos += bytes.len();
+        };
+        put(b"Xing");
+        put(&0x0fu32.to_be_bytes());
+        put(&1000u32.to_be_bytes());
+        put(&400_000u32.to_be_bytes());
+        let toc: [u8; 100] = core::array::from_fn(|i| (i * 256 / 100) as u8);
+        put(&toc);
+        put(&57u32.to_be_bytes());
+        put(b"LAME3.100");
+        put(&[0x03, 160]);
+        put(&0x0080_0000u32.to_be_bytes());
+        put(&[0x2e, 0x3b]); // track gain: automatic, -5.9 dB
+        put(&[0x00, 0x00]);
+        put(&[0x24, 32]);
+        put(&[0x24, 0x00, 0x7c]); // delay 576, padding 124
+        put(&[0x44, 0xfe, 0x00, 0x00]);
+        put(&399_583u32.to_be_bytes());
+        put(&[0x12, 0x34]);
+        let crc = lame_crc16(&frame[..190]);
+        frame[190..192].copy_from_slice(&crc.to_be_bytes());
+        frame
+    }
+
+    fn parse(frame: &[u8]) -> Option<XingHeader> {
+        XingHeader::parse(frame, 4, MPEGVersion::MPEG1, StereoMode::Joint, 44100)
+    }
+
+    #[test]
+    fn parses_xing_and_lame_tag() {
+        let xing = parse(&lame_frame()).unwrap();
+        assert_eq!(xing.kind, XingKind::Xing);
+        assert_eq!(xing.frames, Some(1000));
+        assert_eq!(xing.bytes, Some(400_000));
+        assert_eq!(xing.quality, Some(57));
+        let lame = xing.lame.unwrap();
+        assert_eq!(&lame.encoder, b"LAME3.100");
+        assert_eq!((lame.revision, lame.vbr_method), (0, 3));
+        assert_eq!(lame.lowpass_hz, 16000);
+        assert_eq!(lame.peak_amplitude, 1 << 23);
+        assert_eq!(
+            lame.track_gain,
+            Some(ReplayGain {
+                originator: 3,
+                gain_tenths_db: -59
+            })
+        );
+        assert_eq!(lame.album_gain, None);
+        assert_eq!((lame.encoder_delay, lame.encoder_padding), (576, 124));
+        assert_eq!(lame.mp3_gain, -2);
+        assert_eq!(lame.music_length, 399_583);
+        assert!(lame.tag_crc_valid);
+    }
+
+    #[test]
+    fn duration_and_seek_offsets() {
+        let xing = parse(&lame_frame()).unwrap();
+        assert_eq!(xing.total_samples(), Some(1_152_000));
+        // 1152000 - 576 - 124 samples at 44.1 kHz
+        assert_eq!(xing.duration_ms(), Some(26_106));
+        assert_eq!(xing.seek_offset(0), Some(0));
+        assert_eq!(xing.seek_offset(576_000), Some(200_000));
+        // past the end: just below the last TOC entry, interpolated towards the end
+        assert_eq!(
+            xing.seek_offset(u64::MAX),
+            Some(400_000 * (253 * 256 + 3 * 255) / 65536)
+        );
+    }
+
+    #[test]
+    fn detects_corrupt_lame_tag() {
+        let mut frame = lame_frame();
+        frame[100] ^= 1;
+        assert!(!parse(&frame).unwrap().lame.unwrap().tag_crc_valid);
+    }
+
+    #[test]
+    fn needs_zero_side_info_and_magic() {
+        let mut frame = lame_frame();
+        frame[4..8].copy_from_slice(b"Info");
+        assert_eq!(parse(&frame), None);
+
+        let mut frame = lame_frame();
+        frame[36..40].copy_from_slice(b"Info");
+        frame[40..44].copy_from_slice(&0u32.to_be_bytes());
+        let info = parse(&frame).unwrap();
+        assert_eq!(info.kind, XingKind::Info);
+        assert_eq!((info.frames, info.toc, info.lame), (None, None, None));
+
+        assert!(!is_tag_frame(&lame_frame()[4..], 17));
+    }
+}
@@ -1,11 +1,28 @@
-use crate::utils::{bit_stream_cache::BitStreamInfo, clip_to_short::clip_to_short};
+use crate::{
+    decoders::{layer1::decode_layer1, layer2::decode_layer2},
+    metadata::{self, id3},
+    mp3::{
+        dequant::mp3_dequantize,
+        downmix::{downmix_spectrum, downmix_subbands, mirror_channel0, spectral_downmix_fits},
+        huffman::decode_huffman,
+        imdct::imdct,
+        scalefactors::unpack_scale_factors,
+        seek::{Seek, StreamLayout},
+        vbr::{VbrHeader, is_tag_frame},
+        xing::{XingHeader, side_info_bytes},
+    },
+    utils::{bit_stream_cache::BitStreamInfo, clip_to_short::clip_to_short, crc16::crc16},
+};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::mem::MaybeUninit;
 
 pub const CHANNEL_MONO: usize = 0;
 pub const CHANNEL_LEFT: usize = 0;
 pub const CHANNEL_RIGHT: usize = 1;
 
 pub const SYNCWORDH: u8 = 0xff;
-pub const SYNCWORDL: u8 = 0xf0;
+pub const SYNCWORDL: u8 = 0xe0; // 11 sync bits, so MPEG 2.5 headers are found too
 pub const DQ_FRACBITS_OUT: u8 = 25; // number of fraction bits in output of dequant
 pub const CSHIFT: u8 = 12; // coefficients have 12 leading sign bits for early-terminating mulitplies
 pub const SIBYTES_MPEG1_MONO: usize = 17;
@@ -83,7 +100,7 @@ pub const fn sar_64(x: u64, n: i32) -> u64 {
 
 #[inline(always)]
 pub const fn mulshift_32(x: i32, y: i32) -> i32 {
-    ((x as i64) * (y as i64) >> 32) as i32
+    (((x as i64) * (y as i64)) >> 32) as i32
 }
 
 #[inline(always)]
@@ -223,15 +240,19 @@ pub fn idct_9(x: &mut [i32; 9]) {
     x[8] = a23 - a19;
 }
 
-///
-///  P O L Y P H A S E
-///
+/*
+ *  P O L Y P H A S E
+ */
 
 pub const HUFF_PAIRTABS: usize = 32;
 pub const BLOCK_SIZE: usize = 18;
 pub const NBANDS: usize = 32;
 pub const MAX_REORDER_SAMPS: usize = (192 - 126) * 3; // largest critical band for short blocks (see sfBandTable)
-pub const VBUF_LENGTH: usize = 17 * 2 * NBANDS; // for double-sized vbuf FIFO
+pub const VBUF_LENGTH: usize = 17 * VBUF_ROW; // vbuf half: double-sized rings, or single histories if VBUF_FIFO
+pub const VBUF_FIFO: bool = cfg!(feature = "small-vbuf"); // shift vbuf histories down instead of storing them twice
+pub const VBUF_RING: usize = if VBUF_FIFO { 8 } else { 16 }; // one 8 entry history, replicated unless VBUF_FIFO
+pub const VBUF_CHAN: usize = 2 * VBUF_RING; // offset of the second channel in a vbuf row
+pub const VBUF_ROW: usize = MAX_NCHAN * VBUF_CHAN; // vbuf entries per output sample, both channels
 pub const MAX_SCFBD: usize = 4; // max scalefactor bands per channel
 pub const MAINBUF_SIZE: usize = 1940;
 pub const MAX_NGRAN: usize = 2; // max granules
@@ -251,8 +272,132 @@ pub const ERR_MP3_INVALID_HUFFCODES: i8 = -9;
 pub const ERR_MP3_INVALID_DEQUANTIZE: i8 = -10;
 pub const ERR_MP3_INVALID_IMDCT: i8 = -11;
 pub const ERR_MP3_INVALID_SUBBAND: i8 = -12;
+pub const ERR_MP3_INVALID_CRC: i8 = -13;
 pub const ERR_UNKNOWN: i8 = -127;
 
+/// Reason a frame could not be decoded.
+///
+/// Each variant names the decoding stage that gave up. Offsets are in bytes, relative to the
+/// start of the frame passed to [`MP3Decoder::decode_frame`], except for scalefactor and
+/// Huffman errors where they point into the main data (bit reservoir included).
+/// [`Mp3Error::code`] maps every variant back to one of the `ERR_MP3_*` codes used by the C side.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp3Error {
+    /// input ends before the frame part starting at `offset`
+    InDataUnderflow { offset: usize },
+    /// frame needs `needed` bytes of main data from previous frames, only `available` are buffered
+    MainDataUnderflow { needed: usize, available: usize },
+    /// no matching sync word after the free format frame starting at `offset`
+    FreeBitrateSync { offset: usize },
+    /// output holds fewer than the `needed` samples of one frame
+    OutputTooSmall { needed: usize },
+    /// bad or unsupported value in the header byte at `offset`
+    InvalidFrameHeader { offset: usize },
+    /// side info starting at `offset` does not describe a decodable frame
+    InvalidSideInfo { offset: usize },
+    InvalidScaleFactors {
+        granule: GranuleIndex,
+        channel: ChannelIndex,
+        offset: usize,
+    },
+    InvalidHuffCodes {
+        granule: GranuleIndex,
+        channel: ChannelIndex,
+        offset: usize,
+    },
+    InvalidDequantize { granule: GranuleIndex },
+    InvalidImdct {
+        granule: GranuleIndex,
+        channel: ChannelIndex,
+    },
+    InvalidSubband { granule: GranuleIndex },
+    /// CRC-16 over header and side info does not match the one in the frame
+    CrcMismatch { expected: u16, computed: u16 },
+}
+
+impl Mp3Error {
+    /// Legacy `ERR_MP3_*` value for this error.
+    pub const fn code(&self) -> i8 {
+        match self {
+            Mp3Error::InDataUnderflow { .. } => ERR_MP3_INDATA_UNDERFLOW,
+            Mp3Error::MainDataUnderflow { .. } => ERR_MP3_MAINDATA_UNDERFLOW,
+            Mp3Error::FreeBitrateSync { .. } => ERR_MP3_FREE_BITRATE_SYNC,
+            Mp3Error::OutputTooSmall { .. } => ERR_MP3_OUT_OF_MEMORY,
+            Mp3Error::InvalidFrameHeader { .. } => ERR_MP3_INVALID_FRAMEHEADER,
+            Mp3Error::InvalidSideInfo { .. } => ERR_MP3_INVALID_SIDEINFO,
+            Mp3Error::InvalidScaleFactors { .. } => ERR_MP3_INVALID_SCALEFACT,
+            Mp3Error::InvalidHuffCodes { .. } => ERR_MP3_INVALID_HUFFCODES,
+            Mp3Error::InvalidDequantize { .. } => ERR_MP3_INVALID_DEQUANTIZE,
+            Mp3Error::InvalidImdct { .. } => ERR_MP3_INVALID_IMDCT,
+            Mp3Error::InvalidSubband { .. } => ERR_MP3_INVALID_SUBBAND,
+            Mp3Error::CrcMismatch { .. } => ERR_MP3_INVALID_CRC,
+        }
+    }
+}
+
+impl From<Mp3Error> for i8 {
+    fn from(e: Mp3Error) -> i8 {
+        e.code()
+    }
+}
+
+impl core::fmt::Display for Mp3Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Mp3Error::InDataUnderflow { offset } => {
+                write!(f, "input underflow at byte {offset}")
+            }
+            Mp3Error::MainDataUnderflow { needed, available } => write!(
+                f,
+                "main data underflow: {needed} bytes from previous frames needed, {available} buffered"
+            ),
+            Mp3Error::FreeBitrateSync { offset } => {
+                write!(f, "free bitrate sync not found after byte {offset}")
+            }
+            Mp3Error::OutputTooSmall { needed } => {
+                write!(f, "output buffer too small, {needed} samples needed")
+            }
+            Mp3Error::InvalidFrameHeader { offset } => {
+                write!(f, "invalid frame header at byte {offset}")
+            }
+            Mp3Error::InvalidSideInfo { offset } => write!(f, "invalid side info at byte {offset}"),
+            Mp3Error::InvalidScaleFactors {
+                granule,
+                channel,
+                offset,
+            } => write!(
+                f,
+                "invalid scalefactors in granule {}, channel {} at main data byte {offset}",
+                granule as usize, channel as usize
+            ),
+            Mp3Error::InvalidHuffCodes {
+                granule,
+                channel,
+                offset,
+            } => write!(
+                f,
+                "invalid Huffman codes in granule {}, channel {} at main data byte {offset}",
+                granule as usize, channel as usize
+            ),
+            Mp3Error::InvalidDequantize { granule } => {
+                write!(f, "dequantization failed in granule {}", granule as usize)
+            }
+            Mp3Error::InvalidImdct { granule, channel } => write!(
+                f,
+                "IMDCT failed in granule {}, channel {}",
+                granule as usize, channel as usize
+            ),
+            Mp3Error::InvalidSubband { granule } => {
+                write!(f, "subband synthesis failed in granule {}", granule as usize)
+            }
+            Mp3Error::CrcMismatch { expected, computed } => {
+                write!(f, "CRC mismatch: frame says {expected:#06x}, computed {computed:#06x}")
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(usize)]
 pub enum SampleRateIndex {
@@ -263,6 +408,7 @@ pub enum SampleRateIndex {
 }
 
 impl SampleRateIndex {
+    #[allow(clippy::result_unit_err)]
     pub const fn from_u8(v: u8) -> Result<Self, ()> {
         match v {
             0 => Ok(SampleRateIndex::SampleRate0),
@@ -283,6 +429,7 @@ pub enum LayerIndex {
 }
 
 impl LayerIndex {
+    #[allow(clippy::result_unit_err)]
     pub const fn from_u8(v: u8) -> Result<Self, ()> {
         match v {
             1 => Ok(LayerIndex::Layer1),
@@ -315,6 +462,7 @@ pub enum BitrateIndex {
 }
 
 impl BitrateIndex {
+    #[allow(clippy::result_unit_err)]
     pub const fn from_u8(v: u8) -> Result<Self, ()> {
         match v {
             0 => Ok(BitrateIndex::Bitrate0),
@@ -363,6 +511,7 @@ impl FrameHeader {
 
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
+#[allow(non_snake_case)]
 pub struct SideInfoSub {
     pub part23_length: i32,     /* number of bits in main data */
     pub n_bigvals: i32, /* 2x this = first set of Huffman cw's (maximum amplitude can be > 1) */
@@ -389,6 +538,7 @@ pub struct SideInfo {
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
+#[allow(non_snake_case)]
 pub struct CriticalBandInfo {
     pub cbType: i32,      /* pure long = 0, pure short = 1, mixed = 2 */
     pub cbEndS: [i32; 3], /* number nonzero short cb's, per subbblock */
@@ -427,6 +577,7 @@ pub struct HuffTabLookup {
 }
 
 #[repr(C)]
+#[allow(non_snake_case)]
 pub struct IMDCTInfo {
     pub outBuf: [[[i32; NBANDS]; BLOCK_SIZE]; MAX_NCHAN], /* output of IMDCT */
     pub overBuf: [[i32; MAX_NSAMP / 2]; MAX_NCHAN], /* overlap-add buffer (by symmetry, only need 1/2 size) */
@@ -455,14 +606,17 @@ pub struct ScaleFactorJS {
 /* NOTE - could get by with smaller vbuf if memory is more important than speed
  *  (in Subband, instead of replicating each block in FDCT32 you would do a memmove on the
  *   last 15 blocks to shift them down one, a hardware style FIFO)
+ * The small-vbuf feature does that: each 8 entry history is shifted down by one in
+ *   vbuf_push(), which halves vbuf (see VBUF_FIFO, sizes in footprint_tests)
  */
 #[repr(C)]
 pub struct SubbandInfo {
-    pub vbuf: [i32; MAX_NCHAN * VBUF_LENGTH], /* vbuf for fast DCT-based synthesis PQMF - double size for speed (no modulo indexing) */
+    pub vbuf: [i32; MAX_NCHAN * VBUF_LENGTH], /* vbuf for fast DCT-based synthesis PQMF - double size for speed (no modulo indexing) unless VBUF_FIFO */
     pub vindex: i32,                          /* internal index for tracking position in vbuf */
 }
 
 #[repr(C)]
+#[allow(non_snake_case)]
 pub struct MP3Decoder {
     pub m_MP3DecInfo: MP3DecInfo,
     pub m_FrameHeader: FrameHeader,
@@ -475,10 +629,12 @@ pub struct MP3Decoder {
     pub m_ScaleFactorInfoSub: [[ScaleFactorInfoSub; MAX_NCHAN]; MAX_NGRAN],
     pub m_CriticalBandInfo: [CriticalBandInfo; MAX_NCHAN], /* filled in dequantizer, used in joint stereo reconstruction */
     pub m_HuffmanInfo: HuffmanInfo,
-    pub m_DequantInfo: DequantInfo,
+    #[cfg(not(feature = "small-vbuf"))]
+    pub m_DequantInfo: DequantInfo, /* with small-vbuf, dequantization borrows m_IMDCTInfo.outBuf */
     pub m_IMDCTInfo: IMDCTInfo,
     pub m_sMode: StereoMode,        /* mono/stereo mode */
     pub m_MPEGVersion: MPEGVersion, /* version ID */
+    pub m_OutputMode: OutputMode,   /* channels of the PCM output */
 }
 
 /***********************************************************************************************************************
@@ -554,6 +710,21 @@ pub fn mp3_find_free_sync(buf: &[u8], first_header: &[u8; 4]) -> Option<usize> {
         .map(|pos| if padding { pos.saturating_sub(1) } else { pos })
 }
 
+/***********************************************************************************************************************
+ * Function:    MP3ClearBadFrame
+ *
+ * Description: zero out pcm buffer if error decoding MP3 frame
+ *
+ * Inputs:      pcm output buffer, trimmed to the size of the current frame
+ *
+ * Outputs:     zeroed out pcm buffer
+ *
+ * Return:      none
+ **********************************************************************************************************************/
+pub fn mp3_clear_bad_frame(outbuf: &mut [i16]) {
+    outbuf.fill(0);
+}
+
 /***********************************************************************************************************************
  * Function:    PolyphaseStereo
  *
@@ -575,11 +746,12 @@ pub fn mp3_find_free_sync(buf: &[u8], first_header: &[u8; 4]) -> Option<usize> {
 
 #[unsafe(no_mangle)]
 pub fn polyphase_stereo(pcm: &mut [i16; 64], vbuf: &[i32], coef: &[u32; 264]) {
-    if vbuf.len() < 1064 {
+    const POLY_VBUF_LEN: usize = 16 * VBUF_ROW + VBUF_CHAN + 8;
+    if vbuf.len() < POLY_VBUF_LEN {
         return;
     }
     let rnd_val = 1 << ((DQ_FRACBITS_OUT - 2 - 2 - 15) - 1 + (32 - CSHIFT));
-    let vbuf = &vbuf[..1064];
+    let vbuf = &vbuf[..POLY_VBUF_LEN];
     /* special case, output sample 0 */
     let mut sum1_r: u64 = rnd_val;
     let mut sum1_l: u64 = rnd_val;
@@ -594,36 +766,34 @@ pub fn polyphase_stereo(pcm: &mut [i16; 64], vbuf: &[i32], coef: &[u32; 264]) {
         c1 = c[0];
         c2 = c[1];
         v_lo = vbuf[j];
-        v_hi = vbuf[23 - j];
-        sum1_l = madd_64(sum1_l as u64, v_lo, c1 as i32);
-        sum1_l = madd_64(sum1_l as u64, v_hi, -(c2 as i32));
-        v_lo = vbuf[32 + j];
-        v_hi = vbuf[32 + (23 - j)];
-        sum1_r = madd_64(sum1_r as u64, v_lo, c1 as i32);
-        sum1_r = madd_64(sum1_r as u64, v_hi, -(c2 as i32));
+        v_hi = vbuf[VBUF_RING + 7 - j];
+        sum1_l = madd_64(sum1_l, v_lo, c1 as i32);
+        sum1_l = madd_64(sum1_l, v_hi, -(c2 as i32));
+        v_lo = vbuf[VBUF_CHAN + j];
+        v_hi = vbuf[VBUF_CHAN + VBUF_RING + 7 - j];
+        sum1_r = madd_64(sum1_r, v_lo, c1 as i32);
+        sum1_r = madd_64(sum1_r, v_hi, -(c2 as i32));
     }
 
     pcm[CHANNEL_LEFT] = clip_to_short(
-        sar_64(sum1_l as u64, (32 - CSHIFT) as i32) as i32,
+        sar_64(sum1_l, (32 - CSHIFT) as i32) as i32,
         (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
     );
     pcm[CHANNEL_RIGHT] = clip_to_short(
-        sar_64(sum1_r as u64, (32 - CSHIFT) as i32) as i32,
+        sar_64(sum1_r, (32 - CSHIFT) as i32) as i32,
         (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
     );
 
     /* special case, output sample 16 */
-    let mut coef_idx = 256;
-    let vbuf_idx = 64 * 16;
+    let vbuf_idx = VBUF_ROW * 16;
     sum1_l = rnd_val;
     sum1_r = rnd_val;
 
-    for j in 0..8 {
-        c1 = coef[coef_idx];
-        coef_idx += 1;
+    for (j, &c) in coef[256..264].iter().enumerate() {
+        c1 = c;
         v_lo = vbuf[vbuf_idx + j];
         sum1_l = madd_64(sum1_l, v_lo, c1 as i32);
-        v_lo = vbuf[vbuf_idx + 32 + j];
+        v_lo = vbuf[vbuf_idx + VBUF_CHAN + j];
         sum1_r = madd_64(sum1_r, v_lo, c1 as i32);
     }
     pcm[2 * 16 + CHANNEL_LEFT] = clip_to_short(
@@ -637,7 +807,7 @@ pub fn polyphase_stereo(pcm: &mut [i16; 64], vbuf: &[i32], coef: &[u32; 264]) {
 
     /* main convolution loop: sum1L = samples 1, 2, 3, ... 15   sum2L = samples 31, 30, ... 17 */
     let (coef_chunks, _) = coef[16..].as_chunks::<16>();
-    let (vbuf_chunk, _) = vbuf[64..].as_chunks::<64>();
+    let (vbuf_chunk, _) = vbuf[VBUF_ROW..].as_chunks::<VBUF_ROW>();
 
     let (pcm_head, pcm_tail) = pcm.split_at_mut(32);
     let (pcm_head, _) = &mut pcm_head[2..].as_chunks_mut::<2>();
@@ -660,31 +830,31 @@ pub fn polyphase_stereo(pcm: &mut [i16; 64], vbuf: &[i32], coef: &[u32; 264]) {
         calculate_sums_r(coef, vbuf, &mut sum1_r, &mut sum2_r);
 
         pcm_head[CHANNEL_LEFT] = clip_to_short(
-            sar_64(sum1_l as u64, (32 - CSHIFT) as i32) as i32,
+            sar_64(sum1_l, (32 - CSHIFT) as i32) as i32,
             (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
         );
         pcm_head[CHANNEL_RIGHT] = clip_to_short(
-            sar_64(sum1_r as u64, (32 - CSHIFT) as i32) as i32,
+            sar_64(sum1_r, (32 - CSHIFT) as i32) as i32,
             (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
         );
         pcm_tail[CHANNEL_LEFT] = clip_to_short(
-            sar_64(sum2_l as u64, (32 - CSHIFT) as i32) as i32,
+            sar_64(sum2_l, (32 - CSHIFT) as i32) as i32,
             (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
         );
         pcm_tail[CHANNEL_RIGHT] = clip_to_short(
-            sar_64(sum2_r as u64, (32 - CSHIFT) as i32) as i32,
+            sar_64(sum2_r, (32 - CSHIFT) as i32) as i32,
             (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
         );
     }
 }
 
-fn calculate_sums_l(coef: &[u32; 16], vbuf: &[i32; 64], sum1_l: &mut u64, sum2_l: &mut u64) {
+fn calculate_sums_l(coef: &[u32; 16], vbuf: &[i32; VBUF_ROW], sum1_l: &mut u64, sum2_l: &mut u64) {
     let (coef_chunks, _) = coef.as_chunks::<2>();
     for (j, coef) in coef_chunks.iter().enumerate() {
         let c1 = coef[0];
         let c2 = coef[1];
         let v_lo = vbuf[j];
-        let v_hi = vbuf[23 - j];
+        let v_hi = vbuf[VBUF_RING + 7 - j];
         *sum1_l = madd_64(*sum1_l, v_lo, c1 as i32);
         *sum2_l = madd_64(*sum2_l, v_lo, c2 as i32);
 
@@ -693,13 +863,13 @@ fn calculate_sums_l(coef: &[u32; 16], vbuf: &[i32; 64], sum1_l: &mut u64, sum2_l
     }
 }
 
-fn calculate_sums_r(coef: &[u32; 16], vbuf: &[i32; 64], sum1_r: &mut u64, sum2_r: &mut u64) {
+fn calculate_sums_r(coef: &[u32; 16], vbuf: &[i32; VBUF_ROW], sum1_r: &mut u64, sum2_r: &mut u64) {
     let (coef_chunks, _) = coef.as_chunks::<2>();
     for (j, coef) in coef_chunks.iter().enumerate() {
         let c1 = coef[0];
         let c2 = coef[1];
-        let v_lo = vbuf[32 + j];
-        let v_hi = vbuf[32 + 23 - j];
+        let v_lo = vbuf[VBUF_CHAN + j];
+        let v_hi = vbuf[VBUF_CHAN + VBUF_RING + 7 - j];
         *sum1_r = madd_64(*sum1_r, v_lo, c1 as i32);
         *sum2_r = madd_64(*sum2_r, v_lo, c2 as i32);
         *sum1_r = madd_64(*sum1_r, v_hi, -(c2 as i32));
@@ -726,7 +896,7 @@ pub fn polyphase_mono(mut pcm: &mut [i16], vbuf: &[i32], coef_base: &[u32]) {
         c2 = coef[0];
         coef = &coef[1..];
         v_lo = vb1[j];
-        v_hi = vb1[23 - (j)]; // 0...7
+        v_hi = vb1[VBUF_RING + 7 - j]; // 0...7
         sum1_l = madd_64(sum1_l, v_lo, c1 as i32);
         sum1_l = madd_64(sum1_l, v_hi, -(c2 as i32));
     }
@@ -737,12 +907,12 @@ pub fn polyphase_mono(mut pcm: &mut [i16], vbuf: &[i32], coef_base: &[u32]) {
 
     /* special case, output sample 16 */
     coef = &coef_base[256..];
-    vb1 = &vbuf[64 * 16..];
+    vb1 = &vbuf[VBUF_ROW * 16..];
     sum1_l = rnd_val;
-    for j in 0..8 {
+    for &v in &vb1[..8] {
         c1 = coef[0];
         coef = &coef[1..];
-        v_lo = vb1[j];
+        v_lo = v;
         sum1_l = madd_64(sum1_l, v_lo, c1 as i32); // 0...7
     }
     pcm[16] = clip_to_short(
@@ -752,7 +922,7 @@ pub fn polyphase_mono(mut pcm: &mut [i16], vbuf: &[i32], coef_base: &[u32]) {
 
     /* main convolution loop: sum1L = samples 1, 2, 3, ... 15   sum2L = samples 31, 30, ... 17 */
     coef = &coef_base[16..];
-    vb1 = &vbuf[64..];
+    vb1 = &vbuf[VBUF_ROW..];
     pcm = &mut pcm[1..];
 
     /* right now, the compiler creates bad asm from this... */
@@ -765,13 +935,13 @@ pub fn polyphase_mono(mut pcm: &mut [i16], vbuf: &[i32], coef_base: &[u32]) {
             c2 = coef[0];
             coef = &coef[1..];
             v_lo = vb1[j];
-            v_hi = vb1[23 - j];
+            v_hi = vb1[VBUF_RING + 7 - j];
             sum1_l = madd_64(sum1_l, v_lo, c1 as i32);
             sum2_l = madd_64(sum2_l, v_lo, c2 as i32);
             sum1_l = madd_64(sum1_l, v_hi, -(c2 as i32));
             sum2_l = madd_64(sum2_l, v_hi, c1 as i32);
         }
-        vb1 = &vb1[64..];
+        vb1 = &vb1[VBUF_ROW..];
         pcm[0] = clip_to_short(
             sar_64(sum1_l, (32 - CSHIFT) as i32) as i32,
             (DQ_FRACBITS_OUT - 2 - 2 - 15) as i32,
@@ -879,10 +1049,10 @@ fn fdct_32_first_pass(buf_slice: &mut [i32; NBANDS], cptr0: &[i32; 24]) {
         let b0 = a0 + a3;
         let b3 = mulshift_32(cptr0[base], a0 - a3) << 1;
         let b1 = a1 + a2;
-        let b2 = mulshift_32(cptr0[base + 1], a1 - a2) << (*fh as i32);
+        let b2 = mulshift_32(cptr0[base + 1], a1 - a2) << *fh;
 
         let coeff = cptr0[base + 2];
-        let shift_idx = *ft as i32;
+        let shift_idx = *ft;
 
         buf_slice[i] = b0 + b1;
         buf_slice[15 - i] = mulshift_32(coeff, b0 - b1) << shift_idx;
@@ -891,6 +1061,18 @@ fn fdct_32_first_pass(buf_slice: &mut [i32; NBANDS], cptr0: &[i32; 24]) {
     }
 }
 
+/* store a new entry at the head of one 8 entry history in vbuf, newest first */
+#[inline(always)]
+fn vbuf_push(ring: &mut [i32], s: i32) {
+    if VBUF_FIFO {
+        ring.copy_within(0..7, 1);
+        ring[0] = s;
+    } else {
+        ring[0] = s;
+        ring[8] = s;
+    }
+}
+
 pub fn fdct_32(
     buf_slice: &mut [i32; NBANDS],
     dest_slice: &mut [i32],
@@ -898,8 +1080,8 @@ pub fn fdct_32(
     odd_block: i32,
     gb: i32,
 ) {
-    const EXPECTED_LENGTH: usize = MAX_NCHAN * VBUF_LENGTH - 32;
-    let dest_slice = &mut dest_slice[0..MAX_NCHAN * VBUF_LENGTH - 32];
+    const EXPECTED_LENGTH: usize = MAX_NCHAN * VBUF_LENGTH - VBUF_CHAN;
+    let dest_slice = &mut dest_slice[0..EXPECTED_LENGTH];
     if dest_slice.len() < EXPECTED_LENGTH {
         return;
     }
@@ -968,140 +1150,111 @@ pub fn fdct_32(
     // Obliczanie bazowych offsetów dla d[]
     let v_toggle = if odd_block != 0 { 0 } else { VBUF_LENGTH };
     let v_toggle_inv = v_toggle ^ VBUF_LENGTH;
-    let off_8 = ((offset - odd_block) & 7) as usize;
+    /* the FIFO keeps the newest entry of every history first, the rings move it around */
+    let (offset, off_8) = if VBUF_FIFO {
+        (0, 0)
+    } else {
+        (offset as usize, ((offset - odd_block) & 7) as usize)
+    };
 
     /* sample 0 - delayed block */
-    let d0_idx = 64 * 16 + off_8 + v_toggle;
+    let d0_idx = VBUF_ROW * 16 + off_8 + v_toggle;
     if let Some(d0_slot) = dest_slice.get_mut(d0_idx..d0_idx + 9) {
-        let s = buf_slice[0];
-        d0_slot[0] = s;
-        d0_slot[8] = s;
+        vbuf_push(d0_slot, buf_slice[0]);
     }
 
     /* samples 16 to 31 */
-    let d16_base = offset as usize + v_toggle_inv;
-    if let Some(d16_slice) = dest_slice.get_mut(d16_base..d16_base + 1024) {
-        let (d, _) = d16_slice.as_chunks_mut::<64>();
+    let d16_base = offset + v_toggle_inv;
+    if let Some(d16_slice) = dest_slice.get_mut(d16_base..d16_base + 16 * VBUF_ROW) {
+        let (d, _) = d16_slice.as_chunks_mut::<VBUF_ROW>();
 
         let mut s = buf_slice[1];
-        d[0][0] = s;
-        d[0][8] = s;
+        vbuf_push(&mut d[0], s);
 
         let tmp1 = buf_slice[25] + buf_slice[29];
         s = buf_slice[17] + tmp1;
-        d[1][0] = s;
-        d[1][8] = s;
+        vbuf_push(&mut d[1], s);
         s = buf_slice[9] + buf_slice[13];
-        d[2][0] = s;
-        d[2][8] = s;
+        vbuf_push(&mut d[2], s);
         s = buf_slice[21] + tmp1;
-        d[3][0] = s;
-        d[3][8] = s;
+        vbuf_push(&mut d[3], s);
 
         let tmp2 = buf_slice[29] + buf_slice[27];
         s = buf_slice[5];
-        d[4][0] = s;
-        d[4][8] = s;
+        vbuf_push(&mut d[4], s);
         s = buf_slice[21] + tmp2;
-        d[5][0] = s;
-        d[5][8] = s;
+        vbuf_push(&mut d[5], s);
         s = buf_slice[13] + buf_slice[11];
-        d[6][0] = s;
-        d[6][8] = s;
+        vbuf_push(&mut d[6], s);
         s = buf_slice[19] + tmp2;
-        d[7][0] = s;
-        d[7][8] = s;
+        vbuf_push(&mut d[7], s);
 
         let tmp3 = buf_slice[27] + buf_slice[31];
         s = buf_slice[3];
-        d[8][0] = s;
-        d[8][8] = s;
+        vbuf_push(&mut d[8], s);
         s = buf_slice[19] + tmp3;
-        d[9][0] = s;
-        d[9][8] = s;
+        vbuf_push(&mut d[9], s);
         s = buf_slice[11] + buf_slice[15];
-        d[10][0] = s;
-        d[10][8] = s;
+        vbuf_push(&mut d[10], s);
         s = buf_slice[23] + tmp3;
-        d[11][0] = s;
-        d[11][8] = s;
+        vbuf_push(&mut d[11], s);
 
         let tmp4 = buf_slice[31];
         s = buf_slice[7];
-        d[12][0] = s;
-        d[12][8] = s;
+        vbuf_push(&mut d[12], s);
         s = buf_slice[23] + tmp4;
-        d[13][0] = s;
-        d[13][8] = s;
+        vbuf_push(&mut d[13], s);
         s = buf_slice[15];
-        d[14][0] = s;
-        d[14][8] = s;
+        vbuf_push(&mut d[14], s);
         s = tmp4;
-        d[15][0] = s;
-        d[15][8] = s;
+        vbuf_push(&mut d[15], s);
     }
 
     /* samples 1 to 16 */
-    let d1_base = 16 + off_8 + v_toggle;
-    if let Some(d1_slice) = dest_slice.get_mut(d1_base..d1_base + 1024) {
-        let (d, _) = d1_slice.as_chunks_mut::<64>();
+    let d1_base = VBUF_RING + off_8 + v_toggle;
+    if let Some(d1_slice) = dest_slice.get_mut(d1_base..d1_base + 16 * VBUF_ROW) {
+        let (d, _) = d1_slice.as_chunks_mut::<VBUF_ROW>();
 
         let mut s = buf_slice[1];
-        d[0][0] = s;
-        d[0][8] = s;
+        vbuf_push(&mut d[0], s);
 
         let tmp1 = buf_slice[30] + buf_slice[25];
         s = buf_slice[17] + tmp1;
-        d[1][0] = s;
-        d[1][8] = s;
+        vbuf_push(&mut d[1], s);
         s = buf_slice[14] + buf_slice[9];
-        d[2][0] = s;
-        d[2][8] = s;
+        vbuf_push(&mut d[2], s);
         s = buf_slice[22] + tmp1;
-        d[3][0] = s;
-        d[3][8] = s;
+        vbuf_push(&mut d[3], s);
         s = buf_slice[6];
-        d[4][0] = s;
-        d[4][8] = s;
+        vbuf_push(&mut d[4], s);
 
         let tmp2 = buf_slice[26] + buf_slice[30];
         s = buf_slice[22] + tmp2;
-        d[5][0] = s;
-        d[5][8] = s;
+        vbuf_push(&mut d[5], s);
         s = buf_slice[10] + buf_slice[14];
-        d[6][0] = s;
-        d[6][8] = s;
+        vbuf_push(&mut d[6], s);
         s = buf_slice[18] + tmp2;
-        d[7][0] = s;
-        d[7][8] = s;
+        vbuf_push(&mut d[7], s);
         s = buf_slice[2];
-        d[8][0] = s;
-        d[8][8] = s;
+        vbuf_push(&mut d[8], s);
 
         let tmp3 = buf_slice[28] + buf_slice[26];
         s = buf_slice[18] + tmp3;
-        d[9][0] = s;
-        d[9][8] = s;
+        vbuf_push(&mut d[9], s);
         s = buf_slice[12] + buf_slice[10];
-        d[10][0] = s;
-        d[10][8] = s;
+        vbuf_push(&mut d[10], s);
         s = buf_slice[20] + tmp3;
-        d[11][0] = s;
-        d[11][8] = s;
+        vbuf_push(&mut d[11], s);
         s = buf_slice[4];
-        d[12][0] = s;
-        d[12][8] = s;
+        vbuf_push(&mut d[12], s);
 
         let tmp4 = buf_slice[24] + buf_slice[28];
         s = buf_slice[20] + tmp4;
-        d[13][0] = s;
-        d[13][8] = s;
+        vbuf_push(&mut d[13], s);
         s = buf_slice[8] + buf_slice[12];
-        d[14][0] = s;
-        d[14][8] = s;
+        vbuf_push(&mut d[14], s);
         s = buf_slice[16] + tmp4;
-        d[15][0] = s;
-        d[15][8] = s;
+        vbuf_push(&mut d[15], s);
     }
 
     /* final rescale + clip if es > 0 */
@@ -1112,24 +1265,30 @@ pub fn fdct_32(
         if let Some(val) = dest_slice.get_mut(d0_idx) {
             let s = (clip_2n(*val, n_clip)) << es;
             *val = s;
-            dest_slice[d0_idx + 8] = s;
+            if !VBUF_FIFO {
+                dest_slice[d0_idx + 8] = s;
+            }
         }
 
         // Re-use logic for 16-31
-        if let Some(d16_slice) = dest_slice.get_mut(d16_base..d16_base + 1024) {
-            for i in d16_slice.chunks_exact_mut(64).take(16) {
+        if let Some(d16_slice) = dest_slice.get_mut(d16_base..d16_base + 16 * VBUF_ROW) {
+            for i in d16_slice.chunks_exact_mut(VBUF_ROW).take(16) {
                 let s = (clip_2n(i[0], n_clip)) << es;
                 i[0] = s;
-                i[8] = s;
+                if !VBUF_FIFO {
+                    i[8] = s;
+                }
             }
         }
 
         // Re-use logic for 1-16
-        if let Some(d1_slice) = dest_slice.get_mut(d1_base..d1_base + 1024) {
-            for i in d1_slice.chunks_exact_mut(64).take(16) {
+        if let Some(d1_slice) = dest_slice.get_mut(d1_base..d1_base + 16 * VBUF_ROW) {
+            for i in d1_slice.chunks_exact_mut(VBUF_ROW).take(16) {
                 let s = (clip_2n(i[0], n_clip)) << es;
                 i[0] = s;
-                i[8] = s;
+                if !VBUF_FIFO {
+                    i[8] = s;
+                }
             }
         }
     }
@@ -1200,7 +1359,7 @@ pub fn freq_invert_rescale(y_full: &mut [i32], x_prev: &mut [i32], block_idx: i3
  *                sign bit, short blocks can have one addition but max gain < 1.0)
  **********************************************************************************************************************/
 
-const IMDCT_WIN: [[u32; 36]; 4] = [
+pub const IMDCT_WIN: [[u32; 36]; 4] = [
     [
         0x02aace8b, 0x07311c28, 0x0a868fec, 0x0c913b52, 0x0d413ccd, 0x0c913b52, 0x0a868fec,
         0x07311c28, 0x02aace8b, 0xfd16d8dd, 0xf6a09e66, 0xef7a6275, 0xe7dbc161, 0xe0000000,
@@ -1277,18 +1436,12 @@ pub fn win_previous(
             let wp_lo = &win[18..36]; // 18 elements forward
             let wp_hi = &win[18..36][..18]; // same range, but we will iterate backwards
 
-            let mut lo_idx = 0;
-            let mut hi_idx = 17;
-
-            for &x in x_prev.iter() {
+            for (lo_idx, &x) in x_prev.iter().enumerate() {
                 let w_lo = wp_lo[lo_idx];
-                let w_hi = wp_hi[hi_idx];
+                let w_hi = wp_hi[17 - lo_idx];
 
                 x_prev_win[lo_idx] = mulshift_32(w_lo as i32, x);
                 x_prev_win[17 - lo_idx] = mulshift_32(w_hi as i32, x);
-
-                lo_idx += 1;
-                hi_idx -= 1;
             }
         }
     }
@@ -1361,6 +1514,8 @@ pub struct MP3DecInfo {
     pub mainDataBegin: i32,
     pub mainDataBytes: i32,
     pub part23Length: [[i32; MAX_NCHAN]; MAX_NGRAN],
+
+    pub tagBytesLeft: i32, /* rest of a tag longer than the input it was found in */
 }
 
 pub const SAMPLERATE_TAB: [[i32; 3]; 3] = [
@@ -1476,6 +1631,8 @@ pub enum MPEGVersion {
 }
 
 #[repr(C)]
+#[derive(Debug, Clone, Copy)]
+#[allow(non_snake_case)]
 pub struct MP3FrameInfo {
     pub bitrate: i32,
     pub n_chans: ChannelCount,
@@ -1486,6 +1643,50 @@ pub struct MP3FrameInfo {
     pub version: MPEGVersion,
 }
 
+/// Result of a successful [`MP3Decoder::decode_frame`] call.
+#[derive(Debug, Clone, Copy)]
+pub struct DecodedFrame {
+    /// bytes of input used by the frame (header, side info and main data)
+    pub bytes_consumed: usize,
+    /// number of i16 values written to the output, interleaved LRLR... if stereo
+    pub samples_written: usize,
+    pub info: MP3FrameInfo,
+}
+
+/// Error of a failed [`MP3Decoder::decode_frame`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub error: Mp3Error,
+    /// bytes of input used by the frame before it was given up, e.g. all of a frame whose main
+    /// data is not buffered; the next frame starts after them. 0 for
+    /// [`Mp3Error::InDataUnderflow`] and [`Mp3Error::OutputTooSmall`], which are retried on the
+    /// same input with more of it or more output.
+    pub bytes_consumed: usize,
+}
+
+impl DecodeError {
+    /// Error of a frame `decode` gave up on after using `consumed` bytes of it.
+    pub(crate) fn new(error: Mp3Error, consumed: usize) -> Self {
+        let bytes_consumed = match error {
+            Mp3Error::InDataUnderflow { .. } | Mp3Error::OutputTooSmall { .. } => 0,
+            _ => consumed,
+        };
+        DecodeError { error, bytes_consumed }
+    }
+}
+
+impl From<DecodeError> for Mp3Error {
+    fn from(e: DecodeError) -> Mp3Error {
+        e.error
+    }
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({} bytes consumed)", self.error, self.bytes_consumed)
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct SFBandTable {
@@ -1503,6 +1704,16 @@ pub enum StereoMode {
     Mono = 0x03,  /* one channel */
 }
 
+/// Channels of the PCM output.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OutputMode {
+    /// the channels of the stream
+    Native = 0,
+    /// stereo streams mixed down to one channel, (L + R) / 2
+    Mono = 1,
+}
+
 impl StereoMode {
     pub const fn get_channel_count(&self) -> ChannelCount {
         match &self {
@@ -1517,7 +1728,7 @@ impl StereoMode {
  *   - bitrate index == 0 is "free" mode (bitrate determined on the fly by
  *       counting bits between successive sync words)
  */
-const BITRATE_TAB: [[[i16; 15]; 3]; 3] = [
+pub(crate) const BITRATE_TAB: [[[i16; 15]; 3]; 3] = [
     [
         /* MPEG-1 */
         [
@@ -1591,15 +1802,84 @@ const SLOT_TAB: [[[i16; 15]; 3]; 3] = [
     ],
 ];
 
+impl Default for MP3Decoder {
+    fn default() -> Self {
+        MP3Decoder::new()
+    }
+}
+
 impl MP3Decoder {
-    pub fn subband(&mut self, mut pcm_buf: &mut [i16]) -> i32 {
-        if self.m_MP3DecInfo.nChans == ChannelCount::DualChannel {
+    /// A decoder ready for the first frame of a stream.
+    ///
+    /// This is a `const fn`, so a decoder can be a `static`. The struct is large (the bit
+    /// reservoir, IMDCT and filterbank buffers); on targets with a small stack build it in
+    /// place with [`MP3Decoder::init`] instead of returning it by value.
+    pub const fn new() -> Self {
+        let mut dec = MaybeUninit::<MP3Decoder>::zeroed();
+        // SAFETY: the memory is zeroed, init_zeroed() sets the fields zero is not valid for
+        unsafe {
+            Self::init_zeroed(dec.as_mut_ptr());
+            dec.assume_init()
+        }
+    }
+
+    /// Initialises the decoder in `slot` in place, the way [`MP3Decoder::new`] would, e.g. in
+    /// PSRAM or a `static` left uninitialised; the decoder is never copied through the stack.
+    pub fn init(slot: &mut MaybeUninit<MP3Decoder>) -> &mut MP3Decoder {
+        // SAFETY: as in new(), write_bytes() zeroes the whole struct
+        unsafe {
+            slot.as_mut_ptr().write_bytes(0, 1);
+            Self::init_zeroed(slot.as_mut_ptr());
+            slot.assume_init_mut()
+        }
+    }
+
+    /// A decoder on the heap, built in place.
+    #[cfg(feature = "alloc")]
+    pub fn new_boxed() -> Box<MP3Decoder> {
+        let mut dec = Box::new_uninit();
+        MP3Decoder::init(&mut dec);
+        // SAFETY: initialised by init()
+        unsafe { dec.assume_init() }
+    }
+
+    /* every field is an integer, an array of them or an enum; all-zero bytes are the initial
+     * state, as memset() made it on the C side, but not a valid value of the enums without a
+     * 0 variant, so those get their first variant
+     *
+     * SAFETY: dec points to a zeroed MP3Decoder
+     */
+    const unsafe fn init_zeroed(dec: *mut MP3Decoder) {
+        unsafe {
+            (&raw mut (*dec).m_MP3DecInfo.nChans).write(ChannelCount::SingleChannel);
+            (&raw mut (*dec).m_MP3DecInfo.nGrans).write(GranuleCount::Mpeg2Granule);
+            (&raw mut (*dec).m_MP3DecInfo.layer).write(LayerIndex::Layer1);
+            (&raw mut (*dec).m_FrameHeader.layer).write(LayerIndex::Layer1);
+            (&raw mut (*dec).m_MP3FrameInfo.n_chans).write(ChannelCount::SingleChannel);
+            (&raw mut (*dec).m_MP3FrameInfo.layer).write(LayerIndex::Layer1);
+        }
+    }
+
+    pub fn subband(&mut self, pcm_buf: &mut [i16]) -> i32 {
+        self.subband_blocks(pcm_buf, BLOCK_SIZE)
+    }
+
+    /// Runs the synthesis filterbank over the first `n_blocks` time slots of `m_IMDCTInfo.outBuf`.
+    ///
+    /// Layer III always synthesises [`BLOCK_SIZE`] slots per granule; Layer I frames hold 12.
+    /// `n_blocks` must be even so the `vbuf` double buffering stays in step across calls.
+    pub fn subband_blocks(&mut self, mut pcm_buf: &mut [i16], n_blocks: usize) -> i32 {
+        debug_assert!(n_blocks.is_multiple_of(2) && n_blocks <= BLOCK_SIZE);
+        if self.m_MP3DecInfo.nChans != self.output_chans() {
+            downmix_subbands(&mut self.m_IMDCTInfo, n_blocks);
+        }
+        if self.output_chans() == ChannelCount::DualChannel {
             /* stereo */
             let (chunks, _) = pcm_buf.as_chunks_mut::<64>();
-            if chunks.len() < BLOCK_SIZE {
+            if chunks.len() < n_blocks {
                 return -1;
             }
-            for (b, chunk) in chunks.iter_mut().take(BLOCK_SIZE).enumerate() {
+            for (b, chunk) in chunks.iter_mut().take(n_blocks).enumerate() {
                 fdct_32(
                     &mut self.m_IMDCTInfo.outBuf[0][b],
                     &mut self.m_SubbandInfo.vbuf,
@@ -1609,7 +1889,7 @@ impl MP3Decoder {
                 );
                 fdct_32(
                     &mut self.m_IMDCTInfo.outBuf[1][b],
-                    &mut self.m_SubbandInfo.vbuf[1 * 32..],
+                    &mut self.m_SubbandInfo.vbuf[VBUF_CHAN..],
                     self.m_SubbandInfo.vindex,
                     b as i32 & 0x01,
                     self.m_IMDCTInfo.gb[1],
@@ -1620,11 +1900,16 @@ impl MP3Decoder {
                         + VBUF_LENGTH * (b as i32 & 0x01) as usize..],
                     &POLY_COEF,
                 );
-                self.m_SubbandInfo.vindex = (self.m_SubbandInfo.vindex - (b as i32 & 0x01)) & 7;
+                if !VBUF_FIFO {
+                    self.m_SubbandInfo.vindex = (self.m_SubbandInfo.vindex - (b as i32 & 0x01)) & 7;
+                }
             }
         } else {
             /* mono */
-            for b in 0..BLOCK_SIZE {
+            if pcm_buf.len() < n_blocks * NBANDS {
+                return -1;
+            }
+            for b in 0..n_blocks {
                 fdct_32(
                     &mut self.m_IMDCTInfo.outBuf[0][b],
                     &mut self.m_SubbandInfo.vbuf,
@@ -1638,26 +1923,32 @@ impl MP3Decoder {
                         [self.m_SubbandInfo.vindex as usize + VBUF_LENGTH * (b & 0x01)..],
                     &POLY_COEF,
                 );
-                self.m_SubbandInfo.vindex = (self.m_SubbandInfo.vindex - (b as i32 & 0x01)) & 7;
-                pcm_buf = &mut pcm_buf[NBANDS as usize..];
+                if !VBUF_FIFO {
+                    self.m_SubbandInfo.vindex = (self.m_SubbandInfo.vindex - (b as i32 & 0x01)) & 7;
+                }
+                pcm_buf = &mut pcm_buf[NBANDS..];
             }
         }
 
-        return 0;
+        0
     }
 
-    pub fn unpack_frame_header(&mut self, buf: &[u8]) -> Result<usize, i8> {
+    pub fn unpack_frame_header(&mut self, buf: &[u8]) -> Result<usize, Mp3Error> {
         /* validate pointers and sync word */
         if buf.len() < 4 {
-            return Err(ERR_MP3_INVALID_FRAMEHEADER);
+            return Err(Mp3Error::InDataUnderflow { offset: 0 });
         }
         if (buf[0] & SYNCWORDH) != SYNCWORDH || (buf[1] & SYNCWORDL) != SYNCWORDL {
-            return Err(ERR_MP3_INVALID_FRAMEHEADER);
+            return Err(Mp3Error::InvalidFrameHeader { offset: 0 });
         }
         let m_frame_header = &mut self.m_FrameHeader;
         let m_mp3_dec_info = &mut self.m_MP3DecInfo;
         /* read header fields - use bitmasks instead of GetBits() for speed, since format never varies */
         let ver_idx = (buf[1] >> 3) & 0x03;
+        if ver_idx == 0x01 {
+            /* reserved version id */
+            return Err(Mp3Error::InvalidFrameHeader { offset: 1 });
+        }
         self.m_MPEGVersion = if ver_idx == 0 {
             MPEGVersion::MPEG25
         } else if ver_idx & 0x01 == 0x01 {
@@ -1667,30 +1958,30 @@ impl MP3Decoder {
         };
         m_frame_header.layer = match LayerIndex::from_u8(4 - ((buf[1] >> 1) & 0x03)) {
             Ok(v) => v,
-            Err(_) => { return Err(ERR_MP3_INVALID_FRAMEHEADER); }
+            Err(_) => return Err(Mp3Error::InvalidFrameHeader { offset: 1 }),
         }; /* easy mapping of index to layer number, 4 = error */
-        m_frame_header.crc = 1 - ((buf[1] as i32 >> 0) & 0x01);
+        m_frame_header.crc = 1 - ((buf[1] as i32) & 0x01);
         m_frame_header.br_idx = match BitrateIndex::from_u8((buf[2] >> 4) & 0x0f) {
             Ok(v) => v,
-            Err(_) => return Err(ERR_MP3_INVALID_FRAMEHEADER),
+            Err(_) => return Err(Mp3Error::InvalidFrameHeader { offset: 2 }),
         };
         m_frame_header.sr_idx = match SampleRateIndex::from_u8((buf[2] >> 2) & 0x03) {
             Ok(v) => v,
-            Err(_) => {return Err(ERR_MP3_INVALID_FRAMEHEADER); }
+            Err(_) => return Err(Mp3Error::InvalidFrameHeader { offset: 2 }),
         };
         m_frame_header.paddingBit = (buf[2] as i32 >> 1) & 0x01;
-        m_frame_header.privateBit = (buf[2] as i32 >> 0) & 0x01;
+        m_frame_header.privateBit = (buf[2] as i32) & 0x01;
         self.m_sMode = match (buf[3] >> 6) & 0x03 {
             0x00 => StereoMode::Stereo,
             0x01 => StereoMode::Joint,
             0x02 => StereoMode::Dual,
             0x03 => StereoMode::Mono,
-            _ => return Err(ERR_MP3_INVALID_FRAMEHEADER),
+            _ => return Err(Mp3Error::InvalidFrameHeader { offset: 3 }),
         }; /* maps to correct enum (see definition) */
         m_frame_header.modeExt = (buf[3] as usize >> 4) & 0x03;
         m_frame_header.copyFlag = (buf[3] as i32 >> 3) & 0x01;
         m_frame_header.origFlag = (buf[3] as i32 >> 2) & 0x01;
-        m_frame_header.emphasis = (buf[3] as i32 >> 0) & 0x03;
+        m_frame_header.emphasis = (buf[3] as i32) & 0x03;
         /* check parameters to avoid indexing tables with bad values */
         /* for readability (we reference sfBandTable many times in decoder) */
         self.m_SFBandTable =
@@ -1709,7 +2000,7 @@ impl MP3Decoder {
             GranuleCount::Mpeg2Granule
         };
         m_mp3_dec_info.nGranSamps = (SAMPLES_PER_FRAME_TAB[self.m_MPEGVersion as usize]
-            [(m_frame_header.layer as usize - 1) as usize])
+            [m_frame_header.layer as usize - 1])
             / m_mp3_dec_info.nGrans as i32;
         m_mp3_dec_info.layer = m_frame_header.layer;
 
@@ -1739,13 +2030,13 @@ impl MP3Decoder {
         /* load crc word, if enabled, and return length of frame header (in bytes) */
         if m_frame_header.crc != 0 {
             if buf.len() < 6 {
-                return Err(ERR_MP3_INVALID_FRAMEHEADER);
+                return Err(Mp3Error::InDataUnderflow { offset: 4 });
             }
-            m_frame_header.CRCWord = (buf[4] as i32) << 8 | (buf[5] as i32) << 0;
-            return Ok(6);
+            m_frame_header.CRCWord = (buf[4] as i32) << 8 | (buf[5] as i32);
+            Ok(6)
         } else {
             m_frame_header.CRCWord = 0;
-            return Ok(4);
+            Ok(4)
         }
     }
 
@@ -1765,25 +2056,15 @@ impl MP3Decoder {
      **********************************************************************************************************************/
 
     pub fn mp3_get_last_frame_info(&mut self) {
-        if self.m_MP3DecInfo.layer != LayerIndex::Layer3 {
-            self.m_MP3FrameInfo.bitrate = 0;
-            self.m_MP3FrameInfo.n_chans = ChannelCount::SingleChannel;
-            self.m_MP3FrameInfo.samprate = 0;
-            self.m_MP3FrameInfo.bitsPerSample = 0;
-            self.m_MP3FrameInfo.outputSamps = 0;
-            self.m_MP3FrameInfo.layer = LayerIndex::Layer1;
-            self.m_MP3FrameInfo.version = MPEGVersion::MPEG1;
-        } else {
-            self.m_MP3FrameInfo.bitrate = self.m_MP3DecInfo.bitrate;
-            self.m_MP3FrameInfo.n_chans = self.m_MP3DecInfo.nChans;
-            self.m_MP3FrameInfo.samprate = self.m_MP3DecInfo.samprate;
-            self.m_MP3FrameInfo.bitsPerSample = 16;
-            self.m_MP3FrameInfo.outputSamps = self.m_MP3DecInfo.nChans as i32
-                * SAMPLES_PER_FRAME_TAB[self.m_MPEGVersion as usize]
-                    [self.m_MP3DecInfo.layer as usize - 1] as i32;
-            self.m_MP3FrameInfo.layer = self.m_MP3DecInfo.layer;
-            self.m_MP3FrameInfo.version = self.m_MPEGVersion;
-        }
+        self.m_MP3FrameInfo.bitrate = self.m_MP3DecInfo.bitrate;
+        self.m_MP3FrameInfo.n_chans = self.output_chans();
+        self.m_MP3FrameInfo.samprate = self.m_MP3DecInfo.samprate;
+        self.m_MP3FrameInfo.bitsPerSample = 16;
+        self.m_MP3FrameInfo.outputSamps = self.output_chans() as i32
+            * SAMPLES_PER_FRAME_TAB[self.m_MPEGVersion as usize]
+                [self.m_MP3DecInfo.layer as usize - 1];
+        self.m_MP3FrameInfo.layer = self.m_MP3DecInfo.layer;
+        self.m_MP3FrameInfo.version = self.m_MPEGVersion;
     }
 
     pub fn unpack_side_info(&mut self, buf: &[u8]) -> usize {
@@ -1826,9 +2107,9 @@ impl MP3Decoder {
             m_side_info.private_bits =
                 bsi.get_bits(if m_s_mode == StereoMode::Mono { 1 } else { 2 }) as i32;
         }
-        for gr in 0..m_mp3_dec_info.nGrans as usize {
-            for ch in 0..m_mp3_dec_info.nChans as usize {
-                let sis = &mut m_side_info_sub[gr as usize][ch as usize]; /* side info subblock for this granule, channel */
+        for sis_gr in m_side_info_sub.iter_mut().take(m_mp3_dec_info.nGrans as usize) {
+            for sis in sis_gr.iter_mut().take(m_mp3_dec_info.nChans as usize) {
+                /* side info subblock for this granule, channel */
                 sis.part23_length = bsi.get_bits(12) as i32;
                 sis.n_bigvals = bsi.get_bits(9) as i32;
                 sis.global_gain = bsi.get_bits(8) as i32;
@@ -1890,103 +2171,698 @@ impl MP3Decoder {
         // assert(nBytes == CalcBitsUsed(bsi, buf, 0) >> 3);
         n_bytes
     }
-}
 
-#[cfg(test)]
-mod unpack_frame_header_test {
-    use crate::mp3_decoder::{
-        BLOCK_SIZE, CriticalBandInfo, DequantInfo, FrameHeader, HuffmanInfo, IMDCTInfo,
-        MAINBUF_SIZE, MAX_NCHAN, MAX_NGRAN, MAX_NSAMP, MAX_REORDER_SAMPS, MAX_SCFBD, MP3Decoder,
-        MP3FrameInfo, MPEGVersion, NBANDS, SFBandTable, ScaleFactorInfoSub, ScaleFactorJS,
-        SideInfo, SideInfoSub, StereoMode, SubbandInfo, VBUF_LENGTH,
-    };
+    /// Decodes one Layer I, II or III frame from `input` into `out`.
+    ///
+    /// `input` has to start at a sync word (see [`mp3_find_sync_word`]) and `out` must have room
+    /// for a whole frame, i.e. `MAX_NGRAN * MAX_NSAMP * MAX_NCHAN` samples is always enough.
+    /// Stereo output is interleaved LRLRLR...
+    ///
+    /// A Xing/Info or VBRI tag frame is consumed without output (`samples_written == 0`), see
+    /// [`MP3Decoder::parse_vbr_header`] for reading it. So is a tag found where a frame should
+    /// start (see [`metadata::tag_bytes`]); `bytes_consumed` is then the size of the tag, or all
+    /// of `input` if the tag is longer, and the next calls consume the rest of it the same way
+    /// (see [`MP3Decoder::tag_bytes_left`]). At the end of the stream
+    /// [`MP3Decoder::drain`] outputs the tail of the last frame.
+    ///
+    /// A frame that fails to decode still reports the input it used up, see
+    /// [`DecodeError::bytes_consumed`].
+    pub fn decode_frame(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, DecodeError> {
+        let mut bytes_consumed = 0;
+        match self.decode(input, out, false, &mut bytes_consumed) {
+            Ok(samples_written) => Ok(DecodedFrame {
+                bytes_consumed,
+                samples_written,
+                info: self.m_MP3FrameInfo,
+            }),
+            Err(error) => Err(DecodeError::new(error, bytes_consumed)),
+        }
+    }
 
-    fn make_decoder() -> MP3Decoder {
-        let m_FrameHeader = FrameHeader::default();
-        let m_MP3DecInfo = super::MP3DecInfo {
-            bitrate: 0,
-            freeBitrateFlag: 0,
-            freeBitrateSlots: 0,
-            layer: 0,
-            mainBuf: [0; MAINBUF_SIZE],
-            mainDataBegin: 0,
-            mainDataBytes: 0,
-            nChans: ChannelCount::SingleChannel,
-            nGranSamps: 0,
-            nGrans: 0,
-            nSlots: 0,
-            part23Length: [[0; MAX_NCHAN]; MAX_NGRAN],
-            samprate: 0,
+    /// [`MP3Decoder::decode_frame`] that checks the CRC-16 of protected Layer III frames (the
+    /// protection bit of the header is clear) over header and side info.
+    ///
+    /// A frame failing the check is consumed and output as silence, and
+    /// [`Mp3Error::CrcMismatch`] is returned with the size of the frame in
+    /// [`DecodeError::bytes_consumed`], so callers can skip it.
+    pub fn decode_frame_verified(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+    ) -> Result<DecodedFrame, DecodeError> {
+        let mut bytes_consumed = 0;
+        match self.decode_verified(input, out, false, true, &mut bytes_consumed) {
+            Ok(samples_written) => Ok(DecodedFrame {
+                bytes_consumed,
+                samples_written,
+                info: self.m_MP3FrameInfo,
+            }),
+            Err(error) => Err(DecodeError::new(error, bytes_consumed)),
+        }
+    }
+
+    /// Reads the Xing/Info tag (and LAME extension) of the Layer III frame at the start of
+    /// `input`, usually the first frame of a file, for the duration and seek table.
+    ///
+    /// Returns `None` if `input` does not start with a Layer III tag frame. The header is
+    /// unpacked like in [`MP3Decoder::decode_frame`], so call it between frames.
+    pub fn parse_xing_header(&mut self, input: &[u8]) -> Option<XingHeader> {
+        let fh_bytes = self.unpack_frame_header(input).ok()?;
+        if self.m_MP3DecInfo.layer != LayerIndex::Layer3 {
+            return None;
+        }
+        XingHeader::parse(
+            input,
+            fh_bytes,
+            self.m_MPEGVersion,
+            self.m_sMode,
+            self.m_MP3DecInfo.samprate,
+        )
+    }
+
+    /// Finds the first frame in `input` and reads its Xing/Info or VBRI header, whichever the
+    /// encoder wrote, for the duration and seek table of a VBR stream.
+    ///
+    /// Returns the offset of that frame in `input`, which seek offsets are relative to, and
+    /// the header; `None` if the first frame is not a Layer III tag frame. ID3v2 tags in front
+    /// of the frame are skipped.
+    pub fn parse_vbr_header<'a>(&mut self, input: &'a [u8]) -> Option<(usize, VbrHeader<'a>)> {
+        let frame = mp3_find_sync_word(input.get(id3::audio_start(input)..)?)?;
+        let offset = input.len() - frame.len();
+        let fh_bytes = self.unpack_frame_header(frame).ok()?;
+        if self.m_MP3DecInfo.layer != LayerIndex::Layer3 {
+            return None;
+        }
+        let header = VbrHeader::parse(
+            frame,
+            fh_bytes,
+            self.m_MPEGVersion,
+            self.m_sMode,
+            self.m_MP3DecInfo.samprate,
+        )?;
+        Some((offset, header))
+    }
+
+    /// Finds the first frame in `input` and works out how the stream is laid out: where its
+    /// first audio frame starts (behind a Xing/Info or VBRI tag frame, if any), its format and
+    /// frame size. This is what [`MP3Decoder::seek`] needs besides the VBR header.
+    ///
+    /// ID3v2 tags in front of the first frame are skipped whole, so sync-like bytes in them
+    /// (a JPEG picture has plenty) are not taken for a frame. `input` has to hold the tags and
+    /// the first audio frame, and for free format the header after it.
+    pub fn stream_layout(&mut self, input: &[u8]) -> Option<StreamLayout> {
+        let frame = mp3_find_sync_word(input.get(id3::audio_start(input)..)?)?;
+        let first_frame = input.len() - frame.len();
+        let mut audio_start = first_frame;
+        let mut fh_bytes = self.unpack_frame_header(frame).ok()?;
+        if self.m_MP3DecInfo.layer == LayerIndex::Layer3
+            && is_tag_frame(
+                frame,
+                fh_bytes,
+                side_info_bytes(self.m_MPEGVersion, self.m_sMode),
+            )
+        {
+            let pad = self.m_FrameHeader.check_pad_bit() as usize;
+            audio_start += self.unpadded_frame_bytes(frame, fh_bytes)? + pad;
+            fh_bytes = self.unpack_frame_header(input.get(audio_start..)?).ok()?;
+        }
+
+        let frame = &input[audio_start..];
+        let samples_per_frame = SAMPLES_PER_FRAME_TAB[self.m_MPEGVersion as usize]
+            [self.m_MP3DecInfo.layer as usize - 1] as u32;
+        let samprate = self.m_MP3DecInfo.samprate as u32;
+        let (bitrate, free_frame_bytes) = if self.m_FrameHeader.br_idx == BitrateIndex::Bitrate0 {
+            let bytes = self.unpadded_frame_bytes(frame, fh_bytes)? as u32;
+            (bytes * 8 * samprate / samples_per_frame, bytes)
+        } else {
+            (self.m_MP3DecInfo.bitrate as u32, 0)
         };
-        let m_MP3FrameInfo = MP3FrameInfo {
-            bitrate: 0,
-            bitsPerSample: 0,
-            layer: 0,
-            n_chans: 0,
-            outputSamps: 0,
-            samprate: 0,
-            version: MPEGVersion::MPEG1,
+        let side_bytes = if self.m_MP3DecInfo.layer == LayerIndex::Layer3 {
+            (fh_bytes + side_info_bytes(self.m_MPEGVersion, self.m_sMode)) as u32
+        } else {
+            0
         };
-        let m_SideInfo = SideInfo {
-            main_data_begin: 0,
-            private_bits: 0,
-            scfsi: [[0; MAX_SCFBD]; MAX_NCHAN],
+        Some(StreamLayout {
+            first_frame,
+            audio_start,
+            header: frame[..4].try_into().unwrap(),
+            layer: self.m_MP3DecInfo.layer,
+            version: self.m_MPEGVersion,
+            samprate,
+            samples_per_frame,
+            bitrate,
+            free_frame_bytes,
+            side_bytes,
+        })
+    }
+
+    /// Size of the frame at the start of `frame` without its pad slot, from the header just
+    /// unpacked; free format frames are measured up to the next sync word.
+    fn unpadded_frame_bytes(&self, frame: &[u8], fh_bytes: usize) -> Option<usize> {
+        let slot_bytes = if self.m_MP3DecInfo.layer == LayerIndex::Layer1 {
+            4
+        } else {
+            1
         };
-        let m_SFBandTable = SFBandTable {
-            l: [0; 23],
-            s: [0; 14],
+        if self.m_FrameHeader.br_idx != BitrateIndex::Bitrate0 {
+            let samples = SAMPLES_PER_FRAME_TAB[self.m_MPEGVersion as usize]
+                [self.m_MP3DecInfo.layer as usize - 1];
+            let slots = samples / 8 * self.m_MP3DecInfo.bitrate / self.m_MP3DecInfo.samprate
+                / slot_bytes;
+            return Some((slots * slot_bytes) as usize);
+        }
+        let first_header: &[u8; 4] = frame.get(..4)?.try_into().unwrap();
+        let slots = mp3_find_free_sync(frame.get(fh_bytes..)?, first_header)?;
+        /* mp3_find_free_sync leaves out one pad byte, a Layer I pad slot has 4 */
+        let pad = self.m_FrameHeader.check_pad_bit() as usize;
+        (fh_bytes + slots + pad).checked_sub(pad * slot_bytes as usize)
+    }
+
+    /// Starts a seek to `sample` (per channel, counted from the first audio frame) of the
+    /// stream described by `layout` and, for VBR streams, `vbr`.
+    ///
+    /// The bit reservoir and the filterbank state are cleared here; the caller then reads
+    /// from [`Seek::byte_offset`] and decodes with [`Seek::decode_frame`] instead of
+    /// [`MP3Decoder::decode_frame`], which rebuilds them and starts the output at `sample`.
+    pub fn seek(&mut self, layout: &StreamLayout, vbr: Option<&VbrHeader>, sample: u64) -> Seek {
+        self.clear_stream_state();
+        Seek::new(layout, vbr, sample)
+    }
+
+    /// Appends the main data of a frame that is not decoded to the bit reservoir, keeping as
+    /// much of the old data as fits: a later frame may still reach back into either.
+    fn keep_main_data(&mut self, main_data: &[u8]) {
+        let n_slots = main_data.len().min(MAINBUF_SIZE);
+        let main_data_bytes = self.m_MP3DecInfo.mainDataBytes as usize;
+        let keep = main_data_bytes.min(MAINBUF_SIZE - n_slots);
+        let main_buf = &mut self.m_MP3DecInfo.mainBuf;
+        main_buf.copy_within(main_data_bytes - keep..main_data_bytes, 0);
+        main_buf[keep..keep + n_slots].copy_from_slice(&main_data[main_data.len() - n_slots..]);
+        self.m_MP3DecInfo.mainDataBytes = (keep + n_slots) as i32;
+    }
+
+    /// Puts the decoder back in the state it starts in, for a new track or stream: what was
+    /// carried over between frames and what was learnt about the stream are both forgotten.
+    ///
+    /// This is what the C side used to get by zeroing the whole struct, without relying on
+    /// its layout.
+    ///
+    /// The [`OutputMode`] is a setting rather than stream state and is kept.
+    pub fn reset(&mut self) {
+        let output_mode = self.m_OutputMode;
+        let slot = self as *mut MP3Decoder as *mut MaybeUninit<MP3Decoder>;
+        // SAFETY: MaybeUninit<T> has the layout of T, and init() leaves a valid decoder
+        MP3Decoder::init(unsafe { &mut *slot }).m_OutputMode = output_mode;
+    }
+
+    /// Selects the channels of the PCM output, e.g. [`OutputMode::Mono`] for a device with a
+    /// single speaker. Takes effect with the next frame.
+    ///
+    /// Mono output of a stereo stream costs about as much as decoding a mono stream: Layer III
+    /// is mixed down before the IMDCT where the blocks of both channels allow it (see
+    /// [`crate::mp3::downmix`]) and a single channel goes through the synthesis filterbank.
+    /// [`DecodedFrame::info`] and `m_MP3FrameInfo` describe the output, not the stream.
+    pub fn set_output_mode(&mut self, mode: OutputMode) {
+        self.m_OutputMode = mode;
+    }
+
+    pub fn output_mode(&self) -> OutputMode {
+        self.m_OutputMode
+    }
+
+    /// Bytes of a tag the next calls to [`MP3Decoder::decode_frame`] still skip, when the
+    /// tag was longer than the input it was found in. They consume at most their input each.
+    pub fn tag_bytes_left(&self) -> usize {
+        self.m_MP3DecInfo.tagBytesLeft as usize
+    }
+
+    /// Channels of the PCM output for the current stream.
+    pub fn output_chans(&self) -> ChannelCount {
+        match self.m_OutputMode {
+            OutputMode::Native => self.m_MP3DecInfo.nChans,
+            OutputMode::Mono => ChannelCount::SingleChannel,
+        }
+    }
+
+    /// Drops the bit reservoir, the measured free format frame size and the rest of a tag
+    /// being skipped, for a stream reconnect or a station change: the next frame must not
+    /// reach back into main data of the old stream.
+    ///
+    /// With `keep_overlap` the IMDCT overlap and the filterbank history stay, so the tail of
+    /// the old stream crossfades into the first frame of the new one; without it the new
+    /// stream starts from silence as after [`MP3Decoder::reset`].
+    pub fn flush(&mut self, keep_overlap: bool) {
+        self.m_MP3DecInfo.tagBytesLeft = 0;
+        /* free format is only noticed when the bitrate is 0, the header leaves it alone then */
+        self.m_MP3DecInfo.bitrate = 0;
+        self.m_MP3DecInfo.freeBitrateFlag = 0;
+        self.m_MP3DecInfo.freeBitrateSlots = 0;
+        if keep_overlap {
+            self.m_MP3DecInfo.mainDataBegin = 0;
+            self.m_MP3DecInfo.mainDataBytes = 0;
+        } else {
+            self.clear_stream_state();
+        }
+    }
+
+    /// Forgets what was carried over from earlier frames: the bit reservoir, the IMDCT
+    /// overlap and the filterbank history. The measured free format frame size is kept.
+    fn clear_stream_state(&mut self) {
+        self.m_MP3DecInfo.mainDataBegin = 0;
+        self.m_MP3DecInfo.mainDataBytes = 0;
+        let imdct = &mut self.m_IMDCTInfo;
+        imdct.overBuf = [[0; MAX_NSAMP / 2]; MAX_NCHAN];
+        imdct.numPrevIMDCT = [0; MAX_NCHAN];
+        imdct.prevType = [BlockType::Normal; MAX_NCHAN];
+        imdct.prevWinSwitch = [0; MAX_NCHAN];
+        self.m_SubbandInfo.vbuf.fill(0);
+        self.m_SubbandInfo.vindex = 0;
+    }
+
+    /***********************************************************************************************************************
+     * Function:    MP3Drain
+     *
+     * Description: push the tail of the last frame out at the end of the stream
+     *
+     * Inputs:      PCM output buffer, big enough for one granule
+     *
+     * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+     *                number of output samples = MAX_NSAMP * nChans
+     *
+     * Return:      number of PCM samples written (bytes_consumed is 0)
+     *
+     * Notes:       the IMDCT overlap and the synthesis filterbank still hold up to 529 samples
+     *                (per channel) of the last frame; this decodes one silent granule to output them
+     *              decoding more frames afterwards is fine, they just follow the silent granule
+     **********************************************************************************************************************/
+    pub fn drain(&mut self, out: &mut [i16]) -> Result<DecodedFrame, Mp3Error> {
+        let n_samps = MAX_NSAMP * self.output_chans() as usize;
+        if out.len() < n_samps {
+            return Err(Mp3Error::OutputTooSmall { needed: n_samps });
+        }
+        let out = &mut out[..n_samps];
+
+        if self.m_MP3DecInfo.layer == LayerIndex::Layer3 {
+            /* all-zero long block: only the overlap of the previous block comes out */
+            for ch in self.m_MP3DecInfo.nChans.channels() {
+                let c = *ch as usize;
+                self.m_HuffmanInfo.huff_dec_buf[c].fill(0);
+                self.m_HuffmanInfo.non_zero_bound[c] = 0;
+                self.m_HuffmanInfo.gb[c] = 31;
+                self.m_SideInfoSub[GranuleIndex::Granule0 as usize][c] = SideInfoSub::default();
+                imdct(
+                    GranuleIndex::Granule0,
+                    *ch,
+                    &self.m_SFBandTable,
+                    self.m_MPEGVersion,
+                    &self.m_SideInfoSub,
+                    &mut self.m_HuffmanInfo,
+                    &mut self.m_IMDCTInfo,
+                );
+            }
+        } else {
+            for buf in self.m_IMDCTInfo.outBuf.iter_mut() {
+                buf.fill([0; NBANDS]);
+            }
+            self.m_IMDCTInfo.gb = [31; MAX_NCHAN];
+        }
+        if self.subband(out) < 0 {
+            mp3_clear_bad_frame(out);
+            return Err(Mp3Error::InvalidSubband {
+                granule: GranuleIndex::Granule0,
+            });
+        }
+
+        Ok(DecodedFrame {
+            bytes_consumed: 0,
+            samples_written: n_samps,
+            info: self.m_MP3FrameInfo,
+        })
+    }
+
+    /// Decodes one frame like [`MP3Decoder::decode_verified`], without CRC verification. This is
+    /// the entry point the C side calls.
+    pub fn decode(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+        use_size: bool,
+        consumed: &mut usize,
+    ) -> Result<usize, Mp3Error> {
+        self.decode_verified(input, out, use_size, false, consumed)
+    }
+
+    /***********************************************************************************************************************
+     * Function:    MP3DecodeVerified
+     *
+     * Description: decode one frame of MP3 data
+     *
+     * Inputs:      buffer starting at a sync word
+     *              PCM output buffer, big enough for one frame
+     *              flag indicating whether MP3 data is normal MPEG format (useSize = false)
+     *                or reformatted as "self-contained" frames (useSize = true)
+     *              flag to verify the CRC-16 of protected Layer III frames
+     *
+     * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+     *                number of output samples = nGrans * nGranSamps * nChans
+     *              number of bytes of input used, also when an error is returned
+     *
+     * Return:      number of PCM samples written, or the reason the frame was dropped
+     *
+     * Notes:       switching useSize on and off between frames in the same stream
+     *                is not supported (bit reservoir is not maintained if useSize on)
+     *              a Layer III frame failing the CRC check is consumed and output as silence,
+     *                its side info is not used; Layer I and II frames are not checked
+     *              a tag where a frame should start (ID3v2, APEv2 with header, ID3v1) is
+     *                consumed without output; never more than the input holds, the rest of a
+     *                longer tag is consumed by the next calls
+     **********************************************************************************************************************/
+    pub fn decode_verified(
+        &mut self,
+        input: &[u8],
+        out: &mut [i16],
+        use_size: bool,
+        verify_crc: bool,
+        consumed: &mut usize,
+    ) -> Result<usize, Mp3Error> {
+        *consumed = 0;
+
+        /* tag data is not unsynchronised, searching it for a sync word would decode noise */
+        let tag_bytes = match self.tag_bytes_left() {
+            0 => metadata::tag_bytes(input),
+            left => Some(left),
         };
-        let m_SideInfoSub = [[SideInfoSub::default(); MAX_NCHAN]; MAX_NGRAN];
-        MP3Decoder {
-            m_FrameHeader,
-            m_MP3DecInfo,
-            m_MP3FrameInfo,
-            m_SideInfo,
-            m_SFBandTable,
-            m_SideInfoSub,
-            m_ScaleFactorJS: ScaleFactorJS {
-                intensity_scale: 0,
-                nr: [0; 4],
-                slen: [0; 4],
-            },
-            m_SubbandInfo: SubbandInfo {
-                vbuf: [0; MAX_NCHAN * VBUF_LENGTH],
-                vindex: 0,
-            },
-            m_ScaleFactorInfoSub: [[ScaleFactorInfoSub {
-                l: [0; 23],
-                s: [[0; 3]; 13],
-            }; MAX_NCHAN]; MAX_NGRAN],
-            m_CriticalBandInfo: [CriticalBandInfo {
-                cbEndL: 0,
-                cbEndS: [0; 3],
-                cbEndSMax: 0,
-                cbType: 0,
-            }; MAX_NCHAN],
-            m_HuffmanInfo: HuffmanInfo {
-                gb: [0; MAX_NCHAN],
-                huff_dec_buf: [[0; MAX_NSAMP]; MAX_NCHAN],
-                non_zero_bound: [0; MAX_NCHAN],
-            },
-            m_DequantInfo: DequantInfo {
-                work_buf: [0; MAX_REORDER_SAMPS],
-            },
-            m_IMDCTInfo: IMDCTInfo {
-                gb: [0; MAX_NCHAN],
-                outBuf: [[[0; NBANDS]; BLOCK_SIZE]; MAX_NCHAN],
-                overBuf: [[0; MAX_NSAMP / 2]; MAX_NCHAN],
-                numPrevIMDCT: [0; MAX_NCHAN],
-                prevType: [0; MAX_NCHAN],
-                prevWinSwitch: [0; MAX_NCHAN],
-            },
-            m_sMode: StereoMode::Stereo,
-            m_MPEGVersion: MPEGVersion::MPEG1,
+        if let Some(tag_bytes) = tag_bytes {
+            *consumed = tag_bytes.min(input.len());
+            self.m_MP3DecInfo.tagBytesLeft = (tag_bytes - *consumed) as i32;
+            self.m_MP3FrameInfo.outputSamps = 0;
+            return Ok(0);
+        }
+
+        /* unpack frame header */
+        let fh_bytes = self.unpack_frame_header(input)?;
+        match self.m_MP3DecInfo.layer {
+            LayerIndex::Layer1 => {
+                return decode_layer1(self, input, out, fh_bytes, use_size, consumed);
+            }
+            LayerIndex::Layer2 => {
+                return decode_layer2(self, input, out, fh_bytes, use_size, consumed);
+            }
+            LayerIndex::Layer3 => {}
+        }
+
+        let n_samps = self.m_MP3DecInfo.nGrans as usize
+            * self.m_MP3DecInfo.nGranSamps as usize
+            * self.output_chans() as usize;
+        if out.len() < n_samps {
+            return Err(Mp3Error::OutputTooSmall { needed: n_samps });
+        }
+        let out = &mut out[..n_samps];
+
+        /* unpack side info */
+        let si_bytes = self.unpack_side_info(input.get(fh_bytes..).unwrap_or(&[]));
+        if input.len() < fh_bytes + si_bytes {
+            mp3_clear_bad_frame(out);
+            return Err(Mp3Error::InDataUnderflow { offset: fh_bytes });
+        }
+        let buf_after_si = &input[fh_bytes + si_bytes..];
+        *consumed = fh_bytes + si_bytes;
+
+        /* if free mode, need to calculate bitrate and nSlots manually, based on frame size */
+        if self.m_MP3DecInfo.bitrate == 0 || self.m_MP3DecInfo.freeBitrateFlag != 0 {
+            if self.m_MP3DecInfo.freeBitrateFlag == 0 {
+                /* first time through, need to scan for next sync word and figure out frame size */
+                self.m_MP3DecInfo.freeBitrateFlag = 1;
+                let first_header: &[u8; 4] = input[..4].try_into().unwrap();
+                match mp3_find_free_sync(buf_after_si, first_header) {
+                    Some(slots) => self.m_MP3DecInfo.freeBitrateSlots = slots as i32,
+                    None => {
+                        mp3_clear_bad_frame(out);
+                        self.m_MP3DecInfo.freeBitrateFlag = 0;
+                        return Err(Mp3Error::FreeBitrateSync { offset: 0 });
+                    }
+                }
+                let free_frame_bytes =
+                    self.m_MP3DecInfo.freeBitrateSlots + (fh_bytes + si_bytes) as i32;
+                self.m_MP3DecInfo.bitrate = (free_frame_bytes * self.m_MP3DecInfo.samprate * 8)
+                    / (self.m_MP3DecInfo.nGrans as i32 * self.m_MP3DecInfo.nGranSamps);
+            }
+            self.m_MP3DecInfo.nSlots =
+                self.m_MP3DecInfo.freeBitrateSlots + self.m_FrameHeader.check_pad_bit();
+        }
+
+        /* Xing/Info or VBRI tag frame: stream info for the player, no audio, so consume it silently */
+        if is_tag_frame(input, fh_bytes, si_bytes) {
+            let n_slots = self.m_MP3DecInfo.nSlots as usize;
+            if !use_size && n_slots > buf_after_si.len() {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InDataUnderflow {
+                    offset: fh_bytes + si_bytes,
+                });
+            }
+            *consumed = if use_size { input.len() } else { *consumed + n_slots };
+            self.mp3_get_last_frame_info();
+            self.m_MP3FrameInfo.outputSamps = 0;
+            return Ok(0);
+        }
+
+        /* corrupted side info would send the Huffman decoder off into the wrong bits, drop the frame */
+        if verify_crc && self.m_FrameHeader.crc != 0 {
+            let expected = self.m_FrameHeader.CRCWord as u16;
+            let computed = crc16(crc16(0xffff, &input[2..4]), &input[fh_bytes..fh_bytes + si_bytes]);
+            if computed != expected {
+                mp3_clear_bad_frame(out);
+                if use_size {
+                    *consumed = input.len();
+                } else {
+                    let n_slots = self.m_MP3DecInfo.nSlots as usize;
+                    let main_data = buf_after_si.get(..n_slots).ok_or(Mp3Error::InDataUnderflow {
+                        offset: fh_bytes + si_bytes,
+                    })?;
+                    self.keep_main_data(main_data);
+                    *consumed += n_slots;
+                }
+                return Err(Mp3Error::CrcMismatch { expected, computed });
+            }
         }
+
+        /* useSize != 0 means we're getting reformatted (RTP) packets (see RFC 3119)
+         *  - calling function assembles "self-contained" MP3 frames by shifting any main_data
+         *      from the bit reservoir (in previous frames) to AFTER the sync word and side info
+         *  - calling function should set mainDataBegin to 0, and tell us exactly how large this
+         *      frame is (in bytesLeft)
+         */
+        let main_data_is_internal;
+        if use_size {
+            self.m_MP3DecInfo.nSlots = buf_after_si.len() as i32;
+            if self.m_MP3DecInfo.mainDataBegin != 0 || self.m_MP3DecInfo.nSlots <= 0 {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidSideInfo { offset: fh_bytes });
+            }
+            /* can operate in-place on reformatted frames */
+            self.m_MP3DecInfo.mainDataBytes = self.m_MP3DecInfo.nSlots;
+            *consumed = input.len();
+            main_data_is_internal = false;
+        } else {
+            /* out of data - assume last or truncated frame */
+            let n_slots = self.m_MP3DecInfo.nSlots as usize;
+            if n_slots > buf_after_si.len() {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InDataUnderflow {
+                    offset: fh_bytes + si_bytes,
+                });
+            }
+            let main_data_begin = self.m_MP3DecInfo.mainDataBegin as usize;
+            let main_data_bytes = self.m_MP3DecInfo.mainDataBytes as usize;
+
+            /* fill main data buffer with enough new data for this frame */
+            if main_data_bytes >= main_data_begin && main_data_begin + n_slots <= MAINBUF_SIZE {
+                /* adequate "old" main data available (i.e. bit reservoir) */
+                let src_start = main_data_bytes - main_data_begin;
+                let main_buf = &mut self.m_MP3DecInfo.mainBuf;
+                main_buf.copy_within(src_start..main_data_bytes, 0);
+                main_buf[main_data_begin..main_data_begin + n_slots]
+                    .copy_from_slice(&buf_after_si[..n_slots]);
+                self.m_MP3DecInfo.mainDataBytes = (main_data_begin + n_slots) as i32;
+                *consumed += n_slots;
+                main_data_is_internal = true;
+            } else {
+                /* not enough data in bit reservoir from previous frames (perhaps starting in middle of file) */
+                if main_data_bytes + n_slots <= MAINBUF_SIZE {
+                    self.m_MP3DecInfo.mainBuf[main_data_bytes..main_data_bytes + n_slots]
+                        .copy_from_slice(&buf_after_si[..n_slots]);
+                    self.m_MP3DecInfo.mainDataBytes += n_slots as i32;
+                }
+                *consumed += n_slots;
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::MainDataUnderflow {
+                    needed: main_data_begin,
+                    available: main_data_bytes,
+                });
+            }
+        }
+
+        /* the regions of long blocks must end within the scale factor band table */
+        for gr in self.m_MP3DecInfo.nGrans.granules() {
+            for ch in self.m_MP3DecInfo.nChans.channels() {
+                let sis = &self.m_SideInfoSub[*gr as usize][*ch as usize];
+                let region2_band = sis.region0Count + 1 + sis.region1Count + 1;
+                if region2_band >= self.m_SFBandTable.l.len() as i32 {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidSideInfo { offset: fh_bytes });
+                }
+            }
+        }
+
+        let mut bit_offset = 0;
+        let mut main_bits = self.m_MP3DecInfo.mainDataBytes * 8;
+        let mut main_data_offset = 0;
+
+        /* decode one complete frame */
+        for gr in self.m_MP3DecInfo.nGrans.granules() {
+            let mut sf_offsets = [0; MAX_NCHAN];
+            for ch in self.m_MP3DecInfo.nChans.channels() {
+                let main_data = if main_data_is_internal {
+                    &self.m_MP3DecInfo.mainBuf[..]
+                } else {
+                    buf_after_si
+                };
+                sf_offsets[*ch as usize] = main_data_offset;
+
+                /* unpack scale factors and compute size of scale factor block */
+                let prev_bit_offset = bit_offset;
+                let sf_bytes = unpack_scale_factors(
+                    main_data.get(main_data_offset..).unwrap_or(&[]),
+                    &mut bit_offset,
+                    main_bits,
+                    *gr,
+                    *ch,
+                    &mut self.m_SideInfoSub[*gr as usize][*ch as usize],
+                    &mut self.m_ScaleFactorInfoSub,
+                    &mut self.m_SideInfo,
+                    &mut self.m_FrameHeader,
+                    &mut self.m_ScaleFactorJS,
+                    self.m_MPEGVersion,
+                );
+                let part23_length = self.m_SideInfoSub[*gr as usize][*ch as usize].part23_length;
+                self.m_MP3DecInfo.part23Length[*gr as usize][*ch as usize] = part23_length;
+
+                let sf_block_bits = 8 * sf_bytes - prev_bit_offset + bit_offset;
+                let huff_block_bits = part23_length - sf_block_bits;
+                if sf_bytes < 0 || main_bits < huff_block_bits {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidScaleFactors {
+                        granule: *gr,
+                        channel: *ch,
+                        offset: main_data_offset,
+                    });
+                }
+                main_data_offset += sf_bytes as usize;
+                main_bits -= sf_block_bits;
+
+                /* decode Huffman code words */
+                let prev_bit_offset = bit_offset;
+                let huff_bytes = decode_huffman(
+                    main_data.get(main_data_offset..).unwrap_or(&[]),
+                    &mut bit_offset,
+                    huff_block_bits,
+                    *gr,
+                    *ch,
+                    &mut self.m_HuffmanInfo,
+                    &self.m_SFBandTable,
+                    &mut self.m_SideInfoSub,
+                    self.m_MPEGVersion,
+                );
+                if huff_bytes < 0 {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidHuffCodes {
+                        granule: *gr,
+                        channel: *ch,
+                        offset: main_data_offset,
+                    });
+                }
+                main_data_offset += huff_bytes as usize;
+                main_bits -= 8 * huff_bytes - prev_bit_offset + bit_offset;
+            }
+
+            /* dequantize coefficients, decode stereo, reorder short blocks */
+            match mp3_dequantize(*gr, self) {
+                0 => {}
+                err if err == ERR_MP3_INVALID_SCALEFACT as i32 => {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidScaleFactors {
+                        granule: *gr,
+                        channel: ChannelIndex::Channel1,
+                        offset: sf_offsets[1],
+                    });
+                }
+                _ => {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidDequantize { granule: *gr });
+                }
+            }
+
+            /* mono output: a single IMDCT of the mixed spectra, if the blocks of both channels allow */
+            let spectral_downmix = self.m_MP3DecInfo.nChans != self.output_chans()
+                && spectral_downmix_fits(&self.m_SideInfoSub[*gr as usize], &self.m_IMDCTInfo);
+            let channels = if spectral_downmix {
+                downmix_spectrum(self);
+                ChannelCount::SingleChannel.channels()
+            } else {
+                self.m_MP3DecInfo.nChans.channels()
+            };
+
+            /* alias reduction, inverse MDCT, overlap-add, frequency inversion */
+            for ch in channels {
+                if imdct(
+                    *gr,
+                    *ch,
+                    &self.m_SFBandTable,
+                    self.m_MPEGVersion,
+                    &self.m_SideInfoSub,
+                    &mut self.m_HuffmanInfo,
+                    &mut self.m_IMDCTInfo,
+                ) < 0
+                {
+                    mp3_clear_bad_frame(out);
+                    return Err(Mp3Error::InvalidImdct {
+                        granule: *gr,
+                        channel: *ch,
+                    });
+                }
+            }
+
+            if spectral_downmix {
+                mirror_channel0(self);
+            }
+
+            /* subband transform - if stereo, interleaves pcm LRLRLR */
+            let pcm_offset =
+                *gr as usize * self.m_MP3DecInfo.nGranSamps as usize * self.output_chans() as usize;
+            if self.subband(&mut out[pcm_offset..]) < 0 {
+                mp3_clear_bad_frame(out);
+                return Err(Mp3Error::InvalidSubband { granule: *gr });
+            }
+        }
+
+        self.mp3_get_last_frame_info();
+        Ok(n_samps)
     }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod unpack_frame_header_test {
+    use crate::mp3_decoder::{MP3Decoder, MPEGVersion};
+
     #[test]
     fn test_unpack_frame() {
         let buf: [u8; 4] = [0xFF, 0xFB, 0x92, 0x64];
-        let mut m_MP3Decoder = make_decoder();
+        let mut m_MP3Decoder = MP3Decoder::new();
         let res = m_MP3Decoder.unpack_frame_header(&buf);
 
         assert_eq!(m_MP3Decoder.m_MP3DecInfo.bitrate, 128000);
@@ -1996,7 +2872,7 @@ mod unpack_frame_header_test {
     #[test]
     fn test_unpack_frame_mpeg2() {
         let buf: [u8; 6] = [0xFF, 0xF2, 0x20, 0xC4, 0x8E, 0xF6];
-        let mut m_MP3Decoder = make_decoder();
+        let mut m_MP3Decoder = MP3Decoder::new();
         let res = m_MP3Decoder.unpack_frame_header(&buf);
         assert_eq!(m_MP3Decoder.m_MP3DecInfo.bitrate, 16000);
         assert_eq!(m_MP3Decoder.m_MP3DecInfo.samprate, 22050);
@@ -2088,3 +2964,77 @@ mod clip_2n_tests {
         let _ = clip_2n(123, 40); // Should NOT panic
     }
 }
+
+#[cfg(test)]
+mod mp3_error_tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn maps_to_legacy_codes() {
+        assert_eq!(Mp3Error::InDataUnderflow { offset: 4 }.code(), ERR_MP3_INDATA_UNDERFLOW);
+        assert_eq!(
+            Mp3Error::MainDataUnderflow {
+                needed: 10,
+                available: 0
+            }
+            .code(),
+            ERR_MP3_MAINDATA_UNDERFLOW
+        );
+        assert_eq!(
+            i8::from(Mp3Error::InvalidHuffCodes {
+                granule: GranuleIndex::Granule1,
+                channel: ChannelIndex::Channel0,
+                offset: 0,
+            }),
+            ERR_MP3_INVALID_HUFFCODES
+        );
+        assert_eq!(
+            Mp3Error::InvalidSubband {
+                granule: GranuleIndex::Granule0
+            }
+            .code(),
+            ERR_MP3_INVALID_SUBBAND
+        );
+        assert_eq!(
+            Mp3Error::CrcMismatch {
+                expected: 0x1234,
+                computed: 0x4321
+            }
+            .code(),
+            ERR_MP3_INVALID_CRC
+        );
+    }
+
+    #[test]
+    fn display_includes_context() {
+        let e = Mp3Error::InvalidScaleFactors {
+            granule: GranuleIndex::Granule1,
+            channel: ChannelIndex::Channel1,
+            offset: 42,
+        };
+        assert_eq!(
+            e.to_string(),
+            "invalid scalefactors in granule 1, channel 1 at main data byte 42"
+        );
+        assert_eq!(
+            Mp3Error::InvalidFrameHeader { offset: 2 }.to_string(),
+            "invalid frame header at byte 2"
+        );
+    }
+}
+
+#[cfg(test)]
+mod footprint_tests {
+    use super::*;
+
+    #[test]
+    fn decoder_sizes_of_both_layouts() {
+        /* MP3Decoder_t of include/rust_mod.h with and without the small-vbuf feature */
+        let expected = if VBUF_FIFO { 18928 } else { 24072 };
+        assert_eq!(size_of::<MP3Decoder>(), expected);
+        assert_eq!(size_of::<SubbandInfo>(), 4 * MAX_NCHAN * VBUF_LENGTH + 4);
+    }
+}
@@ -1,8 +1,11 @@
 
+#[derive(Clone)]
 pub struct BitStreamInfo<'a> {
     pub bytes: &'a [u8],
     pub cache: u32,
     pub cached_bits: i32,
+    /// bits read past the end of the data (they read as 0), counted by calc_bits_used()
+    pub overrun: u32,
 }
 
 impl<'a> BitStreamInfo<'a> {
@@ -11,6 +14,7 @@ impl<'a> BitStreamInfo<'a> {
             bytes,
             cache: 0,
             cached_bits: 0,
+            overrun: 0,
         }
     }
     pub fn get_bits(&mut self, mut n_bits: u32) -> u32 {
@@ -45,11 +49,44 @@ impl<'a> BitStreamInfo<'a> {
                 self.cached_bits -= take as i32;
             }
             // If no more data (EOF), low bits stay 0 — correct behavior
+            self.overrun += needed - take;
         }
 
         data
     }
 
+    /// The next `n_bits` bits (at most 32), without consuming them. Past the end of the data
+    /// they read as 0.
+    pub fn get_bits_no_advance(&self, n_bits: u32) -> u32 {
+        let n_bits = n_bits.min(32);
+        if n_bits == 0 {
+            return 0;
+        }
+
+        // bits below cached_bits are 0 in the cache, the next bytes go there
+        let mut data = self.cache.wrapping_shr(32 - n_bits);
+        let low_bits = n_bits as i32 - self.cached_bits;
+        if low_bits > 0 {
+            let mut next = [0u8; 4];
+            let n = self.bytes.len().min(4);
+            next[..n].copy_from_slice(&self.bytes[..n]);
+            data |= u32::from_be_bytes(next).wrapping_shr(32 - low_bits as u32);
+        }
+
+        data
+    }
+
+    /// Skips `n_bits` bits (at most 32), usually after [`BitStreamInfo::get_bits_no_advance`].
+    pub fn advance_bitstream(&mut self, n_bits: u32) {
+        self.get_bits(n_bits);
+    }
+
+    /// Skips to the start of the next byte, relative to the start of `bytes` when the reader
+    /// was created. Does nothing if already there.
+    pub fn byte_align_bitstream(&mut self) {
+        self.get_bits((self.cached_bits & 0x07) as u32);
+    }
+
     pub fn refill_bitstream_cache(&mut self) {
         let len = self.bytes.len();
         if len == 0 {
@@ -65,7 +102,7 @@ impl<'a> BitStreamInfo<'a> {
                 self.cache = (self.cache << 8) | (byte as u32);
             }
             let shift = 8 * (4-len);
-            self.cache = self.cache << shift;
+            self.cache <<= shift;
             self.cached_bits = (8 * len) as i32;
             self.bytes = &[];
         }
@@ -78,6 +115,7 @@ impl<'a> BitStreamInfo<'a> {
     ) -> i32 {
         let mut bits_used = (start.len() as i32 - self.bytes.len() as i32) * 8;
         bits_used -= self.cached_bits;
+        bits_used += self.overrun as i32;
         bits_used -= start_offset as i32;
         bits_used
     }
@@ -92,6 +130,7 @@ mod tests {
             bytes: data,
             cache: 0,
             cached_bits: 0,
+            overrun: 0,
         }
     }
 
@@ -295,4 +334,44 @@ mod tests {
         assert_eq!(bsi.cache, 0);
         assert_eq!(bsi.cached_bits, 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn no_advance_peeks_across_refill() {
+        let data = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc];
+        let mut bsi = make_bsi(&data);
+
+        assert_eq!(bsi.get_bits(4), 0x1);
+        assert_eq!(bsi.get_bits_no_advance(12), 0x234);
+        assert_eq!(bsi.get_bits_no_advance(32), 0x23456789);
+        assert_eq!(bsi.get_bits(28), 0x2345678);
+        assert_eq!(bsi.get_bits_no_advance(20), 0x9abc0);
+        bsi.advance_bitstream(12);
+        assert_eq!(bsi.get_bits(4), 0xc);
+    }
+
+    #[test]
+    fn byte_align_skips_to_next_byte() {
+        let data = [0xff, 0x5a, 0x0f];
+        let mut bsi = make_bsi(&data);
+
+        bsi.byte_align_bitstream();
+        assert_eq!(bsi.get_bits(3), 0b111);
+        bsi.byte_align_bitstream();
+        assert_eq!(bsi.calc_bits_used(&data, 0), 8);
+        assert_eq!(bsi.get_bits(8), 0x5a);
+        bsi.byte_align_bitstream();
+        assert_eq!(bsi.get_bits(8), 0x0f);
+    }
+
+    #[test]
+    fn bits_past_the_end_count_as_used() {
+        let data = [0xab, 0xcd];
+        let mut bsi = make_bsi(&data);
+
+        assert_eq!(bsi.get_bits(12), 0xabc);
+        assert_eq!(bsi.get_bits(8), 0xd0);
+        assert_eq!(bsi.overrun, 4);
+        bsi.advance_bitstream(9);
+        assert_eq!(bsi.calc_bits_used(&data, 0), 29);
+    }
+}
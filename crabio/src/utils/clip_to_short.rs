@@ -2,7 +2,7 @@
 pub fn clip_to_short(mut x: i32, frac_bits: i32) -> i16 {
     x >>= frac_bits;
 
-    #[cfg(target_arch = "xtensa")]
+    #[cfg(all(feature = "esp32s3", target_arch = "xtensa"))]
     unsafe {
         core::arch::asm!(
             "clamps {0}, {0}, 15",
@@ -11,7 +11,7 @@ pub fn clip_to_short(mut x: i32, frac_bits: i32) -> i16 {
         );
     }
 
-    #[cfg(not(target_arch = "xtensa"))]
+    #[cfg(not(all(feature = "esp32s3", target_arch = "xtensa")))]
     {
         x = x.clamp(i16::MIN as i32, i16::MAX as i32);
     }
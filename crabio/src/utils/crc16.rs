@@ -0,0 +1,62 @@
+/// CRC-16 of the MPEG audio error check: polynomial x^16 + x^15 + x^2 + 1 (0x8005), MSB
+/// first, no final xor. Start with `crc` = 0xffff and feed the protected bytes in stream order.
+pub fn crc16(mut crc: u16, data: &[u8]) -> u16 {
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// [`crc16`] over `n_bits` bits of `data` from bit `start` on, MSB first, for CRCs that protect
+/// fields not byte aligned. Bits past the end of `data` count as zero.
+pub fn crc16_bits(mut crc: u16, data: &[u8], start: usize, n_bits: usize) -> u16 {
+    for bit in start..start + n_bits {
+        let value = data
+            .get(bit >> 3)
+            .map_or(0, |&byte| (byte >> (7 - (bit & 7))) & 1);
+        crc = if ((crc >> 15) as u8 ^ value) != 0 {
+            (crc << 1) ^ 0x8005
+        } else {
+            crc << 1
+        };
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::crc16::{crc16, crc16_bits};
+
+    #[test]
+    fn test_check_value() {
+        // CRC-16/CMS check value (same polynomial, initial value and bit order)
+        assert_eq!(crc16(0xffff, b"123456789"), 0xaee7);
+    }
+
+    #[test]
+    fn test_split_input() {
+        let whole = crc16(0xffff, b"\x90\x44\x12\x34\x56");
+        assert_eq!(crc16(crc16(0xffff, b"\x90\x44"), b"\x12\x34\x56"), whole);
+        assert_eq!(crc16(0xffff, &[]), 0xffff);
+    }
+
+    #[test]
+    fn test_bits() {
+        let data = b"\x90\x44\x12\x34\x56";
+        assert_eq!(crc16_bits(0xffff, data, 0, 40), crc16(0xffff, data));
+        // a field straddling bytes, the same bits shifted to a byte boundary
+        assert_eq!(
+            crc16_bits(0xffff, b"\x04\x8d\x00", 5, 12),
+            crc16_bits(0xffff, b"\x91\xa0", 0, 12)
+        );
+        // zero padding past the end
+        assert_eq!(crc16_bits(0xffff, data, 32, 16), crc16(0xffff, b"\x56\x00"));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod bit_stream_cache;
-pub mod clip_to_short;
\ No newline at end of file
+pub mod clip_to_short;
+pub mod crc16;
@@ -0,0 +1,188 @@
+//! AAC in ADTS frames, found and kept in sync by [`AdtsSync`].
+
+mod common;
+
+use common::*;
+use crabio::decoders::aac::{
+    AACDecoder, AacError, SAMP_RATE_TAB,
+    adts::{self, AdtsSync, SyncEvent},
+};
+
+/// `aac` in unprotected ADTS frames of `blocks_per_frame` raw data blocks each.
+fn adts_stream(aac: &AacStream, blocks_per_frame: usize) -> Vec<u8> {
+    let samp_rate_idx = SAMP_RATE_TAB
+        .iter()
+        .position(|&rate| rate == aac.samp_rate)
+        .unwrap() as u8;
+    let channel_config = aac.n_chans as u8;
+    let mut stream = Vec::new();
+    for blocks in raw_blocks(aac).chunks(blocks_per_frame) {
+        let frame_bytes = adts::ADTS_HEADER_BYTES + blocks.iter().map(|b| b.len()).sum::<usize>();
+        stream.extend_from_slice(&[
+            0xff,
+            0xf1,
+            0x40 | (samp_rate_idx << 2) | (channel_config >> 2),
+            (channel_config << 6) | (frame_bytes >> 11) as u8,
+            (frame_bytes >> 3) as u8,
+            ((frame_bytes as u8 & 0x07) << 5) | 0x1f,
+            0xfc | (blocks.len() as u8 - 1),
+        ]);
+        blocks
+            .iter()
+            .for_each(|block| stream.extend_from_slice(block));
+    }
+    stream
+}
+
+/// Where each frame of `adts_stream(aac, 1)` ends.
+fn frame_ends(aac: &AacStream) -> Vec<usize> {
+    raw_blocks(aac)
+        .iter()
+        .scan(0, |end, block| {
+            *end += adts::ADTS_HEADER_BYTES + block.len();
+            Some(*end)
+        })
+        .collect()
+}
+
+/// Decodes the ADTS stream `bitstream` through `sync` up to the end; returns the PCM and the
+/// events other than [`SyncEvent::InSync`].
+fn decode_adts_synced(sync: &mut AdtsSync, bitstream: &[u8]) -> (Vec<i16>, Vec<SyncEvent>) {
+    decode_adts_in_pieces(sync, bitstream, |_| usize::MAX)
+}
+
+/// As [`decode_adts_synced`], with at most `piece(offset)` bytes of the input at `offset` in
+/// each call, which must not consume more than that.
+fn decode_adts_in_pieces(
+    sync: &mut AdtsSync,
+    bitstream: &[u8],
+    piece: impl Fn(usize) -> usize,
+) -> (Vec<i16>, Vec<SyncEvent>) {
+    let mut decoder = Box::<AACDecoder>::default();
+    let mut events = Vec::new();
+    let mut offset = 0;
+    let pcm = decode_all(bitstream, 0, |input, out| {
+        let input = window(input, piece(offset));
+        let synced = sync.decode_frame(&mut decoder, input, out)?;
+        assert_eq!(synced.error, None, "frame at byte {}", offset);
+        assert!(synced.frame.bytes_consumed <= input.len());
+        if events.last() != Some(&synced.event) && synced.event != SyncEvent::InSync {
+            events.push(synced.event);
+        }
+        offset += synced.frame.bytes_consumed;
+        Ok::<_, AacError>((synced.frame.bytes_consumed, synced.frame.samples_written))
+    });
+    (pcm, events)
+}
+
+#[test]
+fn adts_streams_decode_like_raw_blocks() {
+    for aac in AAC_STREAMS.iter().chain(&HE_AAC_STREAMS) {
+        let bitstream = adts_stream(aac, 1);
+        let mut noisy = junk(2000, &bitstream);
+        noisy.extend_from_slice(&bitstream);
+        let (pcm, events) = decode_adts_synced(&mut AdtsSync::default(), &noisy);
+        assert_eq!(
+            events,
+            [SyncEvent::Searching, SyncEvent::Locked],
+            "{}",
+            aac.stream.name
+        );
+        assert!(pcm == decode_aac_stream(aac), "{}", aac.stream.name);
+    }
+
+    // several blocks to a frame
+    let aac = &HE_AAC_STREAMS[1];
+    let (pcm, events) = decode_adts_synced(&mut AdtsSync::default(), &adts_stream(aac, 3));
+    assert_eq!(events, [SyncEvent::Locked]);
+    assert!(pcm == decode_aac_stream(aac));
+}
+
+#[test]
+fn adts_sync_loss_is_reported() {
+    let aac = &AAC_STREAMS[0];
+    let bitstream = adts_stream(aac, 1);
+    // a burst of noise after frame 3
+    let frame_ends = frame_ends(aac);
+    let mut damaged = bitstream[..frame_ends[3]].to_vec();
+    damaged.extend(junk(500, &bitstream));
+    damaged.extend_from_slice(&bitstream[frame_ends[3]..]);
+
+    let mut sync = AdtsSync::default();
+    let (pcm, events) = decode_adts_synced(&mut sync, &damaged);
+    assert_eq!(
+        events,
+        [
+            SyncEvent::Locked,
+            SyncEvent::Lost,
+            SyncEvent::Searching,
+            SyncEvent::Locked
+        ]
+    );
+    assert!(sync.is_locked());
+    // frame 3 is dropped with the noise after it
+    let n_samps = 2 * 1024;
+    let linear = decode_aac_stream(aac);
+    assert!(pcm[..3 * n_samps] == linear[..3 * n_samps]);
+    assert_eq!(pcm.len(), linear.len() - n_samps);
+}
+
+#[test]
+fn adts_tag_longer_than_the_input_is_skipped_in_pieces() {
+    let aac = &AAC_STREAMS[0];
+    let bitstream = adts_stream(aac, 1);
+    let frame_ends = frame_ends(aac);
+    // 10 kB ID3v2 tags before the stream, while searching, and after frame 3, in lock
+    let tag = id3_tag(&[], 10_000);
+    let mut tagged = tag.clone();
+    tagged.extend_from_slice(&bitstream[..frame_ends[3]]);
+    let second_tag = tagged.len();
+    tagged.extend_from_slice(&tag);
+    tagged.extend_from_slice(&bitstream[frame_ends[3]..]);
+    let in_tag = |offset: usize| {
+        offset < tag.len() || (second_tag..second_tag + tag.len()).contains(&offset)
+    };
+
+    // fed in 1600 byte pieces inside the tags, like the C side does
+    let (pcm, events) = decode_adts_in_pieces(&mut AdtsSync::default(), &tagged, |offset| {
+        if in_tag(offset) { 1600 } else { usize::MAX }
+    });
+    assert_eq!(events, [SyncEvent::Searching, SyncEvent::Locked]);
+    assert!(pcm == decode_aac_stream(aac));
+}
+
+#[test]
+fn adts_short_run_locks_at_the_end_of_the_stream() {
+    let aac = &AAC_STREAMS[0];
+    let bitstream = adts_stream(aac, 1);
+    let frame_ends = frame_ends(aac);
+    let n_samps = 2 * 1024;
+    let linear = decode_aac_stream(aac);
+
+    // two frames, one short of the run to lock on, all there is
+    let mut sync = AdtsSync::new(3);
+    sync.finish();
+    let (pcm, events) = decode_adts_synced(&mut sync, &bitstream[..frame_ends[1]]);
+    assert_eq!(events, [SyncEvent::Locked]);
+    assert!(pcm == linear[..2 * n_samps]);
+
+    // and frames 4 and 5 after a burst of noise that lost the sync after frame 3
+    let mut damaged = bitstream[..frame_ends[3]].to_vec();
+    damaged.extend(junk(500, &bitstream));
+    damaged.extend_from_slice(&bitstream[frame_ends[3]..frame_ends[5]]);
+    let mut sync = AdtsSync::new(3);
+    sync.finish();
+    let (pcm, events) = decode_adts_synced(&mut sync, &damaged);
+    assert_eq!(
+        events,
+        [
+            SyncEvent::Locked,
+            SyncEvent::Lost,
+            SyncEvent::Searching,
+            SyncEvent::Locked
+        ]
+    );
+    assert!(sync.is_locked());
+    assert!(pcm[..3 * n_samps] == linear[..3 * n_samps]);
+    assert_eq!(pcm.len(), 5 * n_samps);
+}
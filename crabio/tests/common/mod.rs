@@ -0,0 +1,255 @@
+//! Streams and decode helpers shared by the integration tests.
+//!
+//! The streams and references are produced by `tests/data/conformance/generate.py`,
+//! see there for what each stream exercises.
+
+#![allow(dead_code)]
+
+use std::fmt::Display;
+
+use crabio::decoders::aac::{
+    AAC_MAX_NCHANS, AAC_MAX_SBR_NSAMPS, AAC_PROFILE_LC, AACDecoder, AacError,
+};
+use crabio::mp3::seek::Seek;
+use crabio::mp3_decoder::{MAX_NCHAN, MAX_NGRAN, MAX_NSAMP, MP3Decoder};
+
+pub struct Stream {
+    pub name: &'static str,
+    pub bitstream: &'static [u8],
+    pub reference: &'static [u8],
+}
+
+impl Stream {
+    /// The same stream with a changed `bitstream`, e.g. with tags or junk around it.
+    pub fn with_bitstream(&self, bitstream: Vec<u8>) -> Stream {
+        Stream {
+            name: self.name,
+            bitstream: Vec::leak(bitstream),
+            reference: self.reference,
+        }
+    }
+}
+
+macro_rules! streams {
+    ($ext:literal: $($name:literal),* $(,)?) => {
+        [$(Stream {
+            name: $name,
+            bitstream: include_bytes!(concat!("../data/conformance/", $name, $ext)),
+            reference: include_bytes!(concat!("../data/conformance/", $name, ".pcm")),
+        }),*]
+    };
+}
+
+pub const LAYER3_STREAMS: [Stream; 23] = streams![
+    ".mp3":
+    "mpeg1_44100_stereo",
+    "mpeg1_48000_joint_ms",
+    "mpeg1_32000_mono",
+    "mpeg2_22050_dual",
+    "mpeg2_24000_stereo",
+    "mpeg2_16000_mono",
+    "mpeg25_11025_joint_ms",
+    "mpeg25_12000_stereo",
+    "mpeg25_8000_mono",
+    "mpeg1_44100_dual",
+    "mpeg2_22050_joint_ms",
+    "mpeg1_44100_intensity",
+    "mpeg1_48000_intensity_short",
+    "mpeg1_32000_ms_intensity",
+    "mpeg2_22050_intensity",
+    "mpeg2_24000_ms_intensity",
+    "mpeg25_11025_intensity",
+    "mpeg1_44100_mixed",
+    "mpeg2_22050_mixed",
+    "mpeg1_48000_linbits",
+    "mpeg1_44100_free_format",
+    "mpeg2_24000_free_format",
+    "mpeg1_44100_crc",
+];
+
+pub const LAYER1_STREAMS: [Stream; 7] = streams![
+    ".mp1":
+    "mp1_mpeg1_44100_stereo",
+    "mp1_mpeg1_48000_joint",
+    "mp1_mpeg1_32000_mono",
+    "mp1_mpeg1_44100_dual",
+    "mp1_mpeg2_22050_joint",
+    "mp1_mpeg2_16000_mono",
+    "mp1_mpeg1_44100_free_format",
+];
+
+pub const LAYER2_STREAMS: [Stream; 11] = streams![
+    ".mp2":
+    "mp2_mpeg1_48000_stereo",
+    "mp2_mpeg1_44100_stereo_256",
+    "mp2_mpeg1_44100_joint",
+    "mp2_mpeg1_48000_joint_bound4",
+    "mp2_mpeg1_44100_mono_32",
+    "mp2_mpeg1_32000_mono_48",
+    "mp2_mpeg1_44100_dual",
+    "mp2_mpeg2_22050_stereo",
+    "mp2_mpeg2_16000_joint",
+    "mp2_mpeg1_44100_free_format",
+    "mp2_mpeg1_48000_crc",
+];
+
+/// AAC raw data blocks carry no configuration, the sample rate and channel count
+/// come from outside as they would from an MP4 `esds` box.
+pub struct AacStream {
+    pub stream: Stream,
+    pub samp_rate: i32,
+    pub n_chans: i32,
+}
+
+macro_rules! aac_streams {
+    ($(($name:literal, $samp_rate:literal, $n_chans:literal)),* $(,)?) => {
+        [$(AacStream {
+            stream: Stream {
+                name: $name,
+                bitstream: include_bytes!(concat!("../data/conformance/", $name, ".aac")),
+                reference: include_bytes!(concat!("../data/conformance/", $name, ".pcm")),
+            },
+            samp_rate: $samp_rate,
+            n_chans: $n_chans,
+        }),*]
+    };
+}
+
+pub const AAC_STREAMS: [AacStream; 8] = aac_streams![
+    ("aac_lc_44100_stereo", 44100, 2),
+    ("aac_lc_48000_mono", 48000, 1),
+    ("aac_lc_32000_stereo_intensity", 32000, 2),
+    ("aac_lc_22050_stereo_indep", 22050, 2),
+    ("aac_lc_16000_dual_sce", 16000, 2),
+    ("aac_lc_8000_mono_short", 8000, 1),
+    ("aac_lc_96000_stereo_ms_all", 96000, 2),
+    ("aac_lc_48000_sce_lfe", 48000, 2),
+];
+
+pub const AAC_PNS_STREAMS: [AacStream; 1] = aac_streams![("aac_lc_24000_pns", 24000, 2)];
+
+/// HE-AAC with implicit signalling: the rate given is the core's, SBR doubles it. The
+/// HE-AACv2 stream has a mono core and comes out in stereo from its parametric stereo data.
+pub const HE_AAC_STREAMS: [AacStream; 3] = aac_streams![
+    ("aac_he_44100_mono", 22050, 1),
+    ("aac_he_48000_stereo", 24000, 2),
+    ("aac_hev2_44100_ps", 22050, 1),
+];
+
+/// Room for the output of one call of any of the decoders.
+pub const OUT_SAMPLES: usize = AAC_MAX_SBR_NSAMPS * AAC_MAX_NCHANS;
+const _: () = assert!(OUT_SAMPLES >= MAX_NGRAN * MAX_NSAMP * MAX_NCHAN);
+
+/// Decodes `bitstream[offset..]` up to the end, one call of `decode` per frame on the input
+/// from where the last call stopped, and collects the PCM. `decode` returns the bytes it
+/// consumed and the samples it wrote to the buffer it is given.
+pub fn decode_all<E: Display>(
+    bitstream: &[u8],
+    mut offset: usize,
+    mut decode: impl FnMut(&[u8], &mut [i16]) -> Result<(usize, usize), E>,
+) -> Vec<i16> {
+    let mut out = [0i16; OUT_SAMPLES];
+    let mut pcm = Vec::new();
+    let mut frame = 0;
+    while offset < bitstream.len() {
+        let (consumed, samples) = decode(&bitstream[offset..], &mut out)
+            .unwrap_or_else(|e| panic!("frame {} at byte {}: {}", frame, offset, e));
+        pcm.extend_from_slice(&out[..samples]);
+        offset += consumed;
+        frame += 1;
+    }
+    pcm
+}
+
+pub fn decode_stream(stream: &Stream) -> Vec<i16> {
+    decode_stream_with(&mut Box::<MP3Decoder>::default(), stream)
+}
+
+/// Decodes `stream` with `decoder`, in whatever state earlier streams left it.
+pub fn decode_stream_with(decoder: &mut MP3Decoder, stream: &Stream) -> Vec<i16> {
+    decode_all(stream.bitstream, 0, |input, out| {
+        decoder
+            .decode_frame(input, out)
+            .map(|frame| (frame.bytes_consumed, frame.samples_written))
+            .map_err(|e| format!("{}: {}", stream.name, e))
+    })
+}
+
+/// Decodes `bitstream[seek.byte_offset..]` through `seek` up to the end.
+pub fn decode_after_seek(decoder: &mut MP3Decoder, mut seek: Seek, bitstream: &[u8]) -> Vec<i16> {
+    decode_all(bitstream, seek.byte_offset as usize, |input, out| {
+        seek.decode_frame(decoder, input, out)
+            .map(|frame| (frame.bytes_consumed, frame.samples_written))
+    })
+}
+
+/// Size of the first frame of `bitstream`, which must decode.
+pub fn first_frame_bytes(bitstream: &[u8]) -> usize {
+    Box::<MP3Decoder>::default()
+        .decode_frame(bitstream, &mut [0; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN])
+        .unwrap()
+        .bytes_consumed
+}
+
+fn aac_decoder(aac: &AacStream) -> Box<AACDecoder> {
+    let mut decoder = Box::<AACDecoder>::default();
+    decoder
+        .set_raw_block_params(false, aac.n_chans, aac.samp_rate, AAC_PROFILE_LC)
+        .unwrap();
+    decoder
+}
+
+pub fn decode_aac_stream(aac: &AacStream) -> Vec<i16> {
+    let mut decoder = aac_decoder(aac);
+    decode_all(aac.stream.bitstream, 0, |input, out| {
+        decoder
+            .decode_frame(input, out)
+            .map(|frame| (frame.bytes_consumed, frame.samples_written))
+            .map_err(|e| format!("{}: {}", aac.stream.name, e))
+    })
+}
+
+/// The raw data blocks of `aac`, as the decoder delimits them.
+pub fn raw_blocks(aac: &AacStream) -> Vec<&'static [u8]> {
+    let bitstream = aac.stream.bitstream;
+    let mut decoder = aac_decoder(aac);
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    decode_all(bitstream, 0, |input, out| {
+        let block_bytes = decoder.decode_frame(input, out)?.bytes_consumed;
+        blocks.push(&bitstream[offset..offset + block_bytes]);
+        offset += block_bytes;
+        Ok::<_, AacError>((block_bytes, 0))
+    });
+    blocks
+}
+
+/// At most `len` bytes of `input`, as a caller reading into a buffer of that size has.
+pub fn window(input: &[u8], len: usize) -> &[u8] {
+    &input[..input.len().min(len)]
+}
+
+/// An ID3v2.3 tag of `frames` and `padding` zero bytes.
+pub fn id3_tag(frames: &[u8], padding: usize) -> Vec<u8> {
+    let size = frames.len() + padding;
+    let mut tag = b"ID3\x03\x00\x00".to_vec();
+    tag.extend([21, 14, 7, 0].map(|shift| (size >> shift) as u8 & 0x7f));
+    tag.extend_from_slice(frames);
+    tag.resize(10 + size, 0);
+    tag
+}
+
+/// Pseudo-random bytes with copies of `header` spread through them, false syncs that parse.
+pub fn junk(len: usize, header: &[u8]) -> Vec<u8> {
+    let mut state = 0x1234_5678u32;
+    let mut junk: Vec<u8> = (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u8
+        })
+        .collect();
+    for at in (0..len.saturating_sub(4)).step_by(97) {
+        junk[at..at + 4].copy_from_slice(&header[..4]);
+    }
+    junk
+}
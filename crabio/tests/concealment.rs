@@ -0,0 +1,147 @@
+//! CRC verification and concealment of frames that fail to decode.
+
+mod common;
+
+use common::*;
+use crabio::mp3::conceal::{ConcealStrategy, Concealer};
+use crabio::mp3_decoder::{MAX_NCHAN, MAX_NGRAN, MAX_NSAMP, MP3Decoder, Mp3Error};
+
+/// Decodes `bitstream` with CRC verification; returns the PCM and the frames that failed.
+fn decode_verified(bitstream: &[u8]) -> (Vec<i16>, Vec<usize>) {
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut failed = Vec::new();
+    let mut frame = 0;
+    let pcm = decode_all(bitstream, 0, |input, out| {
+        let decoded = match decoder.decode_frame_verified(input, out) {
+            Ok(frame) => (frame.bytes_consumed, frame.samples_written),
+            Err(e) if matches!(e.error, Mp3Error::CrcMismatch { .. }) => {
+                failed.push(frame);
+                (e.bytes_consumed, 2 * 1152)
+            }
+            Err(e) => return Err(e),
+        };
+        frame += 1;
+        Ok(decoded)
+    });
+    (pcm, failed)
+}
+
+#[test]
+fn crc_is_verified() {
+    let stream = &LAYER3_STREAMS[22];
+    let linear = decode_stream(stream);
+    let (pcm, failed) = decode_verified(stream.bitstream);
+    assert!(failed.is_empty());
+    assert!(pcm == linear);
+
+    // flip a bit of the second frame's side info (part2_3_length of granule 0)
+    let first_frame = first_frame_bytes(stream.bitstream);
+    let mut damaged = stream.bitstream.to_vec();
+    damaged[first_frame + 6 + 2] ^= 0x10;
+    let (pcm, failed) = decode_verified(&damaged);
+    assert_eq!(failed, [1]);
+    assert_eq!(pcm.len(), linear.len());
+    assert!(pcm[..2 * 1152] == linear[..2 * 1152]);
+    assert!(pcm[2 * 1152..4 * 1152].iter().all(|&s| s == 0));
+}
+
+/// Decodes `bitstream` through `concealer`; returns the PCM and the frames concealed.
+fn decode_concealed(concealer: &mut Concealer, bitstream: &[u8]) -> (Vec<i16>, Vec<usize>) {
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut concealed = Vec::new();
+    let mut frame = 0;
+    let pcm = decode_all(bitstream, 0, |input, out| {
+        let decoded = concealer.decode_frame(&mut decoder, input, out)?;
+        if decoded.error.is_some() {
+            concealed.push(frame);
+        }
+        frame += 1;
+        Ok::<_, Mp3Error>((decoded.frame.bytes_consumed, decoded.frame.samples_written))
+    });
+    (pcm, concealed)
+}
+
+#[test]
+fn lost_frame_is_concealed() {
+    let stream = &LAYER3_STREAMS[22];
+    let linear = decode_stream(stream);
+    let peak = linear.iter().map(|s| s.unsigned_abs()).max().unwrap();
+
+    let first_frame = first_frame_bytes(stream.bitstream);
+    let mut damaged = stream.bitstream.to_vec();
+    damaged[first_frame + 6 + 2] ^= 0x10;
+
+    for strategy in [
+        ConcealStrategy::Repeat,
+        ConcealStrategy::FadeOut,
+        ConcealStrategy::SpectralRepeat,
+    ] {
+        let mut concealer = Concealer::new(strategy);
+        let (pcm, concealed) = decode_concealed(&mut concealer, stream.bitstream);
+        assert!(concealed.is_empty());
+        assert!(pcm == linear);
+
+        let (pcm, concealed) = decode_concealed(&mut concealer, &damaged);
+        assert_eq!(concealed, [1], "{:?}", strategy);
+        assert_eq!(pcm.len(), linear.len());
+        let lost = &pcm[2 * 1152..4 * 1152];
+        assert!(lost.iter().any(|&s| s != 0), "{:?}", strategy);
+        assert!(
+            lost.iter().all(|s| s.unsigned_abs() <= peak),
+            "{:?}",
+            strategy
+        );
+        // the decoder state is that of a continuous stream again once the next frame has
+        // filled the IMDCT overlap (one granule) and the filterbank history (16 blocks)
+        assert!(
+            pcm[6 * 1152 - 2 * 64..] == linear[6 * 1152 - 2 * 64..],
+            "{:?}",
+            strategy
+        );
+
+        // nothing to conceal the first two frames from, they are dropped
+        let mut damaged = damaged.clone();
+        damaged[6 + 2] ^= 0x10;
+        let (pcm, concealed) = decode_concealed(&mut Concealer::new(strategy), &damaged);
+        assert_eq!(concealed, [0, 1], "{:?}", strategy);
+        assert_eq!(pcm.len(), 2 * 1152);
+    }
+}
+
+#[test]
+fn corrupt_side_info_is_concealed() {
+    let stream = &LAYER3_STREAMS[0];
+    let linear = decode_stream(stream);
+
+    let first_frame = first_frame_bytes(stream.bitstream);
+    // no CRC, the MPEG-1 stereo side info follows the header
+    assert_eq!(stream.bitstream[1] & 0x01, 1);
+    let side_info = first_frame + 4;
+    let mut damaged = stream.bitstream.to_vec();
+    let mut put_bit = |bit: usize, one: bool| {
+        let mask = 0x80 >> (bit % 8);
+        let byte = &mut damaged[side_info + bit / 8];
+        *byte = if one { *byte | mask } else { *byte & !mask };
+    };
+    // granule 0, channel 0: a long block whose region 2 starts past the last scale factor band
+    put_bit(53, false);
+    (69..76).for_each(|bit| put_bit(bit, true));
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut out = [0; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN];
+    decoder.decode_frame(&damaged, &mut out).unwrap();
+    let e = decoder
+        .decode_frame(&damaged[first_frame..], &mut out)
+        .unwrap_err();
+    assert!(matches!(e.error, Mp3Error::InvalidSideInfo { .. }));
+    // the damaged frame is used up, decoding goes on after it
+    let third_frame = first_frame + e.bytes_consumed;
+    decoder
+        .decode_frame(&damaged[third_frame..], &mut out)
+        .unwrap();
+
+    let (pcm, concealed) = decode_concealed(&mut Concealer::new(ConcealStrategy::Repeat), &damaged);
+    assert_eq!(concealed, [1]);
+    assert_eq!(pcm.len(), linear.len());
+    assert!(pcm[2 * 1152..4 * 1152].iter().any(|&s| s != 0));
+}
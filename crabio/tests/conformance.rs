@@ -0,0 +1,132 @@
+//! Layer I, II, III, AAC-LC and HE-AAC conformance tests against stored reference PCM.
+//!
+//! Each stream in `tests/data/conformance` is decoded frame by frame with
+//! [`MP3Decoder::decode_frame`] (or [`AACDecoder::decode_frame`]) and compared with a double precision reference
+//! decode of the same stream (signed 24-bit little-endian, interleaved), in the
+//! style of the ISO/IEC 11172-4 compliance test: the RMS and the peak of the
+//! difference, relative to full scale, must stay under fixed bounds. The
+//! reference is rounded to 16 bits before comparing, since that is the
+//! decoder's output resolution.
+//!
+//! The streams and references are produced by `tests/data/conformance/generate.py`,
+//! see there for what each stream exercises.
+
+mod common;
+
+use common::*;
+
+/// ISO/IEC 11172-4 "full accuracy" RMS bound, 2^-15 / sqrt(12), relative to full scale.
+const RMS_BOUND: f64 = 8.809_698_5e-6;
+/// Largest single-sample difference accepted: 2^-14 relative to full scale, i.e. 2 LSB.
+const PEAK_BOUND: f64 = 1.0 / 16384.0;
+/// Perceptual noise substitution is only specified by the energy of each band
+/// (14496-26 checks nothing else). The reference draws the same random values as
+/// the decoder, but the decoder's 1/sqrt(energy) is good to about 4e-4, which
+/// leaves a few LSB near -6 dBFS: allow 1 LSB RMS and 8 LSB peak there.
+const PNS_RMS_BOUND: f64 = 1.0 / 32768.0;
+const PNS_PEAK_BOUND: f64 = 8.0 / 32768.0;
+/// SBR adds two 32-bit QMF filterbanks and a fixed-point gain calculation after the
+/// core, which costs a little under half an LSB RMS: allow 1 LSB, the peak bound is
+/// unchanged.
+const SBR_RMS_BOUND: f64 = 1.0 / 32768.0;
+
+/// (RMS, peak) of the difference to the reference, relative to full scale.
+fn compare(stream: &Stream, pcm: &[i16]) -> (f64, f64) {
+    let (reference, _) = stream.reference.as_chunks::<3>();
+    assert_eq!(
+        pcm.len(),
+        reference.len(),
+        "{}: decoded {} samples, reference has {}",
+        stream.name,
+        pcm.len(),
+        reference.len()
+    );
+    let mut sum = 0.0;
+    let mut peak: f64 = 0.0;
+    for (&s, r) in pcm.iter().zip(reference) {
+        // Round the 24-bit reference to the decoder's 16-bit output first, so
+        // the unavoidable requantisation to 16 bits is not counted as error.
+        let r = i32::from_le_bytes([0, r[0], r[1], r[2]]) >> 8;
+        let r = ((r + 0x80) >> 8).clamp(i16::MIN.into(), i16::MAX.into());
+        let diff = f64::from(i32::from(s) - r) / 32768.0;
+        sum += diff * diff;
+        peak = peak.max(diff.abs());
+    }
+    ((sum / pcm.len() as f64).sqrt(), peak)
+}
+
+/// Checks each decoded stream against its reference.
+fn check<'a>(decoded: impl IntoIterator<Item = (&'a Stream, Vec<i16>)>) {
+    check_within(decoded, RMS_BOUND, PEAK_BOUND);
+}
+
+fn check_within<'a>(
+    decoded: impl IntoIterator<Item = (&'a Stream, Vec<i16>)>,
+    rms_bound: f64,
+    peak_bound: f64,
+) {
+    let mut failures = Vec::new();
+    for (stream, pcm) in decoded {
+        let (rms, peak) = compare(stream, &pcm);
+        println!(
+            "{:<32} rms {:.3} LSB, peak {:.3} LSB",
+            stream.name,
+            rms * 32768.0,
+            peak * 32768.0
+        );
+        if rms >= rms_bound || peak > peak_bound {
+            failures.push(stream.name);
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "outside the accuracy bounds: {:?}",
+        failures
+    );
+}
+
+#[test]
+fn layer3_conformance() {
+    check(LAYER3_STREAMS.iter().map(|s| (s, decode_stream(s))));
+}
+
+#[test]
+fn layer2_conformance() {
+    check(LAYER2_STREAMS.iter().map(|s| (s, decode_stream(s))));
+}
+
+#[test]
+fn layer1_conformance() {
+    check(LAYER1_STREAMS.iter().map(|s| (s, decode_stream(s))));
+}
+
+#[test]
+fn aac_conformance() {
+    check(
+        AAC_STREAMS
+            .iter()
+            .map(|s| (&s.stream, decode_aac_stream(s))),
+    );
+}
+
+#[test]
+fn aac_pns_conformance() {
+    check_within(
+        AAC_PNS_STREAMS
+            .iter()
+            .map(|s| (&s.stream, decode_aac_stream(s))),
+        PNS_RMS_BOUND,
+        PNS_PEAK_BOUND,
+    );
+}
+
+#[test]
+fn he_aac_conformance() {
+    check_within(
+        HE_AAC_STREAMS
+            .iter()
+            .map(|s| (&s.stream, decode_aac_stream(s))),
+        SBR_RMS_BOUND,
+        PEAK_BOUND,
+    );
+}
@@ -0,0 +1,79 @@
+//! Decoder state across streams: reset, flush, where the decoder lives and its output mode.
+
+mod common;
+
+use std::{mem::MaybeUninit, sync::Mutex};
+
+use common::*;
+use crabio::mp3_decoder::{ChannelCount, MP3Decoder, OutputMode};
+
+#[test]
+fn reset_and_flush_forget_the_previous_stream() {
+    // free format, so the measured frame size has to be forgotten too
+    let (first, second) = (&LAYER3_STREAMS[20], &LAYER3_STREAMS[1]);
+    let linear = decode_stream(second);
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    decode_stream_with(&mut decoder, first);
+    decoder.reset();
+    assert!(decode_stream_with(&mut decoder, second) == linear);
+    decoder.reset();
+    assert!(decode_stream_with(&mut decoder, first) == decode_stream(first));
+
+    decoder.flush(false);
+    assert!(decode_stream_with(&mut decoder, second) == linear);
+
+    // the tail of the second stream overlaps the start of the first, from one granule and
+    // the filterbank history on the output is the same
+    let linear = decode_stream(first);
+    decoder.flush(true);
+    let pcm = decode_stream_with(&mut decoder, first);
+    assert_eq!(pcm.len(), linear.len());
+    assert!(pcm[..1152] != linear[..1152]);
+    assert!(pcm[2 * 1152..] == linear[2 * 1152..]);
+}
+
+#[test]
+fn decoder_can_live_anywhere() {
+    static DECODER: Mutex<MP3Decoder> = Mutex::new(MP3Decoder::new());
+
+    let stream = &LAYER3_STREAMS[1];
+    let linear = decode_stream(stream);
+    assert!(decode_stream_with(&mut DECODER.lock().unwrap(), stream) == linear);
+
+    // built in memory full of garbage, as a fresh heap or PSRAM block may be
+    let mut slot = Box::new(MaybeUninit::<MP3Decoder>::uninit());
+    unsafe { slot.as_mut_ptr().write_bytes(0xa5, 1) };
+    let decoder = MP3Decoder::init(&mut slot);
+    assert!(decode_stream_with(decoder, stream) == linear);
+
+    #[cfg(feature = "alloc")]
+    assert!(decode_stream_with(&mut MP3Decoder::new_boxed(), stream) == linear);
+}
+
+#[test]
+fn mono_output_is_the_downmix() {
+    let mut decoder = Box::<MP3Decoder>::default();
+    decoder.set_output_mode(OutputMode::Mono);
+    for stream in LAYER3_STREAMS
+        .iter()
+        .chain(&LAYER1_STREAMS)
+        .chain(&LAYER2_STREAMS)
+    {
+        decoder.reset();
+        let mono = decode_stream_with(&mut decoder, stream);
+        assert_eq!(decoder.m_MP3FrameInfo.n_chans, ChannelCount::SingleChannel);
+        let stereo = decode_stream(stream);
+        if stereo.len() == mono.len() {
+            assert_eq!(stereo, mono, "{}", stream.name);
+            continue;
+        }
+
+        /* within rounding of mixing the decoded channels */
+        assert_eq!(stereo.len(), 2 * mono.len(), "{}", stream.name);
+        for (lr, &m) in stereo.chunks_exact(2).zip(&mono) {
+            let mix = (lr[0] as i32 + lr[1] as i32) / 2;
+            assert!((mix - m as i32).abs() <= 1, "{}", stream.name);
+        }
+    }
+}
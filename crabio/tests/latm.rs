@@ -0,0 +1,159 @@
+//! AAC in LATM: LOAS streams and AudioMuxElements with an out-of-band StreamMuxConfig.
+
+mod common;
+
+use common::*;
+use crabio::decoders::aac::{AACDecoder, AacError, SAMP_RATE_TAB, latm::LatmDemuxer};
+
+/// Bits written MSB first, for the LATM streams.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: usize, n_bits: u32) {
+        for b in (0..n_bits).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> b) & 1 != 0 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|&b| self.put(b as usize, 8));
+    }
+}
+
+/// StreamMuxConfig of one program and layer carrying `aac`, `subframes` raw data blocks to
+/// an AudioMuxElement.
+fn stream_mux_config(
+    w: &mut BitWriter,
+    aac: &AacStream,
+    audio_mux_version: usize,
+    subframes: usize,
+) {
+    let samp_rate_idx = SAMP_RATE_TAB
+        .iter()
+        .position(|&rate| rate == aac.samp_rate)
+        .unwrap();
+    w.put(audio_mux_version, 1);
+    if audio_mux_version == 1 {
+        w.put(0, 1); // audioMuxVersionA
+        w.put(0, 2); // taraBufferFullness
+        w.put(0xff, 8);
+    }
+    w.put(1, 1); // allStreamsSameTimeFraming
+    w.put(subframes - 1, 6);
+    w.put(0, 4 + 3); // numProgram, numLayer
+    if audio_mux_version == 1 {
+        w.put(0, 2); // ASC length
+        w.put(16, 8);
+    }
+    w.put(2, 5); // AAC-LC, the HE-AAC streams signal SBR implicitly
+    w.put(samp_rate_idx, 4);
+    w.put(aac.n_chans as usize, 4);
+    w.put(0, 3);
+    w.put(0, 3); // frameLengthType
+    w.put(0xff, 8);
+    w.put(0, 1 + 1); // otherDataPresent, crcCheckPresent
+}
+
+/// PayloadLengthInfo and PayloadMux of one raw data block.
+fn put_payload(w: &mut BitWriter, block: &[u8]) {
+    let mut len = block.len();
+    while len >= 255 {
+        w.put(255, 8);
+        len -= 255;
+    }
+    w.put(len, 8);
+    w.put_bytes(block);
+}
+
+/// `aac` as LOAS frames of `subframes` raw data blocks each; the StreamMuxConfig is in the
+/// first frame and in the last if that has fewer blocks.
+fn loas_stream(aac: &AacStream, audio_mux_version: usize, subframes: usize) -> Vec<u8> {
+    let mut stream = Vec::new();
+    for (i, blocks) in raw_blocks(aac).chunks(subframes).enumerate() {
+        let mut w = BitWriter::default();
+        let new_config = i == 0 || blocks.len() != subframes;
+        w.put(!new_config as usize, 1); // useSameStreamMux
+        if new_config {
+            stream_mux_config(&mut w, aac, audio_mux_version, blocks.len());
+        }
+        blocks.iter().for_each(|block| put_payload(&mut w, block));
+        let element_bytes = w.bytes.len();
+        stream.extend_from_slice(&[0x56, 0xe0 | (element_bytes >> 8) as u8, element_bytes as u8]);
+        stream.extend_from_slice(&w.bytes);
+    }
+    stream
+}
+
+/// Decodes the LOAS stream `bitstream` up to the end, the access units of the last
+/// AudioMuxElement included.
+fn decode_loas_stream(bitstream: &[u8]) -> Vec<i16> {
+    let mut decoder = Box::<AACDecoder>::default();
+    let mut demux = Box::<LatmDemuxer>::default();
+    let mut pcm = decode_all(bitstream, 0, |input, out| {
+        let mut consumed = 0;
+        let samples = demux.decode_loas(&mut decoder, input, out, &mut consumed)?;
+        Ok::<_, AacError>((consumed, samples))
+    });
+    let mut out = [0i16; OUT_SAMPLES];
+    while demux.pending_access_units() > 0 {
+        let decoded = demux.decode_access_unit(&mut decoder, &mut out).unwrap();
+        pcm.extend_from_slice(&out[..decoded.samples_written]);
+    }
+    pcm
+}
+
+#[test]
+fn loas_streams_decode_like_raw_blocks() {
+    for aac in AAC_STREAMS.iter().chain(&HE_AAC_STREAMS) {
+        let pcm = decode_loas_stream(&loas_stream(aac, 0, 1));
+        assert!(pcm == decode_aac_stream(aac), "{}", aac.stream.name);
+    }
+
+    // version 1, several blocks to a frame
+    let aac = &HE_AAC_STREAMS[1];
+    let pcm = decode_loas_stream(&loas_stream(aac, 1, 3));
+    assert!(pcm == decode_aac_stream(aac));
+}
+
+#[test]
+fn latm_config_can_come_out_of_band() {
+    let aac = &AAC_STREAMS[1];
+    let mut config = BitWriter::default();
+    stream_mux_config(&mut config, aac, 0, 2);
+    let mut decoder = Box::<AACDecoder>::default();
+    let mut demux = Box::<LatmDemuxer>::default();
+    demux.set_stream_mux_config(&config.bytes).unwrap();
+    assert_eq!(
+        demux.stream_mux_config().unwrap().audio_specific_config(),
+        [0x11, 0x88]
+    );
+
+    let mut out = [0i16; OUT_SAMPLES];
+    let mut pcm = Vec::new();
+    let blocks = raw_blocks(aac);
+    for blocks in blocks.chunks_exact(2) {
+        let mut element = BitWriter::default();
+        blocks
+            .iter()
+            .for_each(|block| put_payload(&mut element, block));
+        assert_eq!(demux.parse_audio_mux_element(&element.bytes, false), Ok(2));
+        while demux.pending_access_units() > 0 {
+            let decoded = demux.decode_access_unit(&mut decoder, &mut out).unwrap();
+            pcm.extend_from_slice(&out[..decoded.samples_written]);
+        }
+    }
+    assert_eq!(decoder.samp_rate(), 48000);
+    let linear = decode_aac_stream(aac);
+    assert!(pcm == linear[..pcm.len()]);
+    assert_eq!(pcm.len(), blocks.len() / 2 * 2 * 1024);
+}
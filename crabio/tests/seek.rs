@@ -0,0 +1,157 @@
+//! Seeking, draining the decoder delay and gapless trimming.
+
+mod common;
+
+use common::*;
+use crabio::mp3::gapless::Gapless;
+use crabio::mp3_decoder::{LayerIndex, MAX_NCHAN, MAX_NGRAN, MAX_NSAMP, MP3Decoder, Mp3Error};
+
+#[test]
+fn seek_is_sample_accurate() {
+    // four copies of streams without padding (48 kHz or free format), so still CBR and long
+    // enough to pre-roll
+    let streams = [
+        &LAYER3_STREAMS[1],
+        &LAYER2_STREAMS[0],
+        &LAYER1_STREAMS[1],
+        &LAYER3_STREAMS[20],
+        &LAYER1_STREAMS[6],
+    ];
+    for stream in streams {
+        let bitstream = stream.bitstream.repeat(4);
+        let linear = decode_stream(&stream.with_bitstream(bitstream.clone()));
+
+        let mut decoder = Box::<MP3Decoder>::default();
+        let layout = decoder.stream_layout(&bitstream).unwrap();
+        let n_chans = if stream.name.contains("mono") { 1 } else { 2 };
+        let total = (linear.len() / n_chans) as u64;
+        // every seek but the first starts from the state left by decoding to the end
+        for sample in [0, 1, 383, 1000, 1152, 2500, 4000, total / 2 + 17, total - 1] {
+            let seek = decoder.seek(&layout, None, sample);
+            let pcm = decode_after_seek(&mut decoder, seek, &bitstream);
+            assert!(
+                pcm == linear[sample as usize * n_chans..],
+                "{}: seek to sample {} (from frame {})",
+                stream.name,
+                sample,
+                seek.frame
+            );
+        }
+    }
+}
+
+#[test]
+fn damaged_preroll_frame_is_skipped() {
+    let stream = &LAYER3_STREAMS[1];
+    let bitstream = stream.bitstream.repeat(4);
+    let linear = decode_stream(&stream.with_bitstream(bitstream.clone()));
+    let sample = 4000;
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    let layout = decoder.stream_layout(&bitstream).unwrap();
+    let mut seek = decoder.seek(&layout, None, sample);
+    // damaged side info in the second pre-roll frame, the first one has no reservoir to fail on
+    assert_eq!(bitstream[1] & 0x01, 1);
+    let side_info = seek.byte_offset as usize + first_frame_bytes(&bitstream) + 4;
+    let mut damaged = bitstream.clone();
+    damaged[side_info + 53 / 8] &= !(0x80 >> (53 % 8));
+    (69..76).for_each(|bit| damaged[side_info + bit / 8] |= 0x80 >> (bit % 8));
+
+    let mut errors = 0;
+    let pcm = decode_all(
+        &damaged,
+        seek.byte_offset as usize,
+        |input, out| match seek.decode_frame(&mut decoder, input, out) {
+            Ok(frame) => Ok::<_, Mp3Error>((frame.bytes_consumed, frame.samples_written)),
+            Err(e) => {
+                errors += 1;
+                Ok((e.bytes_consumed, 0))
+            }
+        },
+    );
+    assert_eq!(errors, 1);
+    // the damaged frame is close enough to the target to show at the start of the output, one
+    // frame on it is that of the undamaged stream
+    let linear = &linear[2 * sample as usize..];
+    assert_eq!(pcm.len(), linear.len());
+    assert!(pcm[2 * 1152..] == linear[2 * 1152..]);
+}
+
+/// The first frame of `stream` with its padding bit cleared and everything after the header
+/// zeroed: no bit allocation or all-zero side info, so a frame of silence.
+fn silent_frame(stream: &Stream) -> Vec<u8> {
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut header: [u8; 4] = stream.bitstream[..4].try_into().unwrap();
+    header[2] &= !0x02;
+    decoder.unpack_frame_header(&header).unwrap();
+    let info = &decoder.m_MP3DecInfo;
+    let frame_bytes = match info.layer {
+        LayerIndex::Layer1 => (12 * info.bitrate / info.samprate * 4) as usize,
+        _ => (info.nGrans as i32 * info.nGranSamps / 8 * info.bitrate / info.samprate) as usize,
+    };
+    let mut frame = vec![0u8; frame_bytes];
+    frame[..4].copy_from_slice(&header);
+    frame
+}
+
+#[test]
+fn drain_matches_a_silent_frame() {
+    for stream in [&LAYER3_STREAMS[1], &LAYER2_STREAMS[0], &LAYER1_STREAMS[1]] {
+        let silent = silent_frame(stream);
+        let mut padded = stream.bitstream.to_vec();
+        padded.extend_from_slice(&silent);
+        padded.extend_from_slice(&silent);
+        let linear = decode_stream(&stream.with_bitstream(padded));
+
+        let mut decoder = Box::<MP3Decoder>::default();
+        let end = decode_stream_with(&mut decoder, stream).len();
+        let mut out = [0i16; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN];
+        let drained = decoder.drain(&mut out).unwrap();
+        assert_eq!(
+            out[..drained.samples_written],
+            linear[end..end + drained.samples_written],
+            "{}",
+            stream.name
+        );
+    }
+}
+
+#[test]
+fn gapless_trims_delay_and_padding() {
+    let stream = &LAYER3_STREAMS[1];
+    let bitstream = stream.bitstream.repeat(4);
+    let mut decoder = Box::<MP3Decoder>::default();
+    let layout = decoder.stream_layout(&bitstream).unwrap();
+    let total = 16 * 1152;
+    // padding under the decoder delay, so the track ends in the drained granule
+    let gapless = Gapless::new(LayerIndex::Layer3, 576, 300, Some(total));
+    let length = gapless.length().unwrap() as usize;
+
+    // everything the decoder puts out, drained tail included
+    let seek = decoder.seek(&layout, None, 0);
+    let mut untrimmed = decode_after_seek(&mut decoder, seek, &bitstream);
+    let mut out = [0i16; MAX_NGRAN * MAX_NSAMP * MAX_NCHAN];
+    let drained = decoder.drain(&mut out).unwrap();
+    untrimmed.extend_from_slice(&out[..drained.samples_written]);
+    let track = &untrimmed[2 * (576 + 529)..][..2 * length];
+
+    for sample in [0, 700, 5000, length as u64 - 10] {
+        let mut gapless = gapless;
+        let mut seek = decoder.seek(&layout, None, gapless.seek(sample));
+        let mut pcm = decode_all(&bitstream, seek.byte_offset as usize, |input, out| {
+            let frame = seek.decode_frame(&mut decoder, input, out)?;
+            let frame = gapless.trim(out, frame);
+            Ok::<_, Mp3Error>((frame.bytes_consumed, frame.samples_written))
+        });
+        assert!(!gapless.is_finished());
+        let drained = seek.drain(&mut decoder, &mut out).unwrap();
+        let frame = gapless.trim(&mut out, drained);
+        pcm.extend_from_slice(&out[..frame.samples_written]);
+        assert!(gapless.is_finished());
+        assert!(
+            pcm == track[2 * sample as usize..],
+            "seek to sample {}",
+            sample
+        );
+    }
+}
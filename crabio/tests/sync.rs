@@ -0,0 +1,133 @@
+//! Frame sync over junk, false headers and lost frames.
+
+mod common;
+
+use common::*;
+use crabio::mp3::sync::{FrameSync, HeaderFields, SyncEvent};
+use crabio::mp3_decoder::{DecodeError, MP3Decoder};
+
+/// Decodes `bitstream` through `sync` up to the end; returns the PCM and the events other than
+/// [`SyncEvent::InSync`].
+fn decode_synced(sync: &mut FrameSync, bitstream: &[u8]) -> (Vec<i16>, Vec<SyncEvent>) {
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut events = Vec::new();
+    let pcm = decode_all(bitstream, 0, |input, out| {
+        let synced = sync.decode_frame(&mut decoder, input, out)?;
+        if events.last() != Some(&synced.event) && synced.event != SyncEvent::InSync {
+            events.push(synced.event);
+        }
+        Ok::<_, DecodeError>((synced.frame.bytes_consumed, synced.frame.samples_written))
+    });
+    (pcm, events)
+}
+
+#[test]
+fn sync_skips_false_headers() {
+    let stream = &LAYER3_STREAMS[1];
+    let bitstream = stream.bitstream.repeat(4);
+    let linear = decode_stream(&stream.with_bitstream(bitstream.clone()));
+
+    let mut noisy = junk(3000, stream.bitstream);
+    noisy.extend_from_slice(&bitstream);
+    let (pcm, events) = decode_synced(&mut FrameSync::default(), &noisy);
+    assert_eq!(events, [SyncEvent::Searching, SyncEvent::Locked]);
+    assert!(pcm == linear);
+}
+
+#[test]
+fn sync_loss_is_reported() {
+    let stream = &LAYER3_STREAMS[1];
+    let bitstream = stream.bitstream.repeat(4);
+    let frame_bytes = stream.bitstream.len() / 4;
+    // a burst of noise between frames 6 and 7
+    let mut damaged = bitstream[..7 * frame_bytes].to_vec();
+    damaged.extend(junk(500, stream.bitstream));
+    damaged.extend_from_slice(&bitstream[7 * frame_bytes..]);
+
+    let mut sync = FrameSync::default();
+    let (pcm, events) = decode_synced(&mut sync, &damaged);
+    assert_eq!(
+        events,
+        [
+            SyncEvent::Locked,
+            SyncEvent::Lost,
+            SyncEvent::Searching,
+            SyncEvent::Locked
+        ]
+    );
+    assert!(sync.is_locked());
+    // frame 6 is dropped with the noise after it; the frames after it lose their reservoir
+    let n_samps = 2 * 1152;
+    let linear = decode_stream(&stream.with_bitstream(bitstream));
+    assert!(pcm[..6 * n_samps] == linear[..6 * n_samps]);
+    assert!(pcm.len() > 12 * n_samps && pcm.len() < 16 * n_samps);
+}
+
+#[test]
+fn short_run_locks_at_the_end_of_the_stream() {
+    let stream = &LAYER3_STREAMS[0];
+    let frame_end = |offset: usize| {
+        offset
+            + HeaderFields::parse(&stream.bitstream[offset..])
+                .unwrap()
+                .frame_bytes(0)
+    };
+    let clip = &stream.bitstream[..frame_end(frame_end(0))];
+    let linear = decode_stream(&stream.with_bitstream(clip.to_vec()));
+
+    // two frames, one short of the run to lock on, all there is
+    let mut sync = FrameSync::new(3);
+    sync.finish();
+    let (pcm, events) = decode_synced(&mut sync, clip);
+    assert_eq!(events, [SyncEvent::Locked]);
+    assert!(pcm == linear);
+
+    // and the same two frames after a burst of noise that lost the sync
+    let mut damaged = stream.bitstream.to_vec();
+    damaged.extend(junk(500, stream.bitstream));
+    damaged.extend_from_slice(clip);
+    let mut sync = FrameSync::new(3);
+    sync.finish();
+    let (pcm, events) = decode_synced(&mut sync, &damaged);
+    assert_eq!(
+        events,
+        [
+            SyncEvent::Locked,
+            SyncEvent::Lost,
+            SyncEvent::Searching,
+            SyncEvent::Locked
+        ]
+    );
+    assert!(sync.is_locked());
+    assert!(pcm.len() > linear.len());
+}
+
+#[test]
+fn failed_frame_in_lock_is_skipped() {
+    let stream = &LAYER3_STREAMS[0];
+    let first_frame = first_frame_bytes(stream.bitstream);
+    // granule 0, channel 0 of the second frame: region 2 past the last scale factor band
+    let mut damaged = stream.bitstream.to_vec();
+    damaged[first_frame + 4 + 6] &= !0x04;
+    damaged[first_frame + 4 + 8] |= 0x07;
+    damaged[first_frame + 4 + 9] |= 0xf0;
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut sync = FrameSync::default();
+    let mut errors = 0;
+    let pcm = decode_all(&damaged, 0, |input, out| {
+        match sync.decode_frame(&mut decoder, input, out) {
+            Ok(synced) => Ok((synced.frame.bytes_consumed, synced.frame.samples_written)),
+            Err(e) if e.bytes_consumed > 0 => {
+                errors += 1;
+                Ok((e.bytes_consumed, 0))
+            }
+            Err(e) => Err(e),
+        }
+    });
+    assert_eq!(errors, 1);
+    assert!(sync.is_locked());
+    let linear = decode_stream(stream);
+    assert_eq!(pcm.len(), linear.len() - 2 * 1152);
+    assert!(pcm[..2 * 1152] == linear[..2 * 1152]);
+}
@@ -0,0 +1,185 @@
+//! Tags around Layer III streams: Xing/Info and VBRI frames, ID3v2 in front, APEv2 and ID3v1
+//! behind.
+
+mod common;
+
+use common::*;
+use crabio::metadata::{
+    self,
+    id3::{self, Content, Tag},
+};
+use crabio::mp3::sync::{FrameSync, SyncEvent};
+use crabio::mp3_decoder::{MP3Decoder, Mp3Error};
+
+#[test]
+fn xing_tag_frame_is_skipped() {
+    let stream = &LAYER3_STREAMS[0];
+    let mut decoder = Box::<MP3Decoder>::default();
+    // an Info tag frame with the header of the stream's first frame, unpadded
+    let mut header: [u8; 4] = stream.bitstream[..4].try_into().unwrap();
+    header[2] &= !0x02;
+    decoder.unpack_frame_header(&header).unwrap();
+    let frame_bytes = (144 * decoder.m_MP3DecInfo.bitrate / decoder.m_MP3DecInfo.samprate) as usize;
+    let mut tagged = vec![0u8; frame_bytes];
+    tagged[..4].copy_from_slice(&header);
+    tagged[36..40].copy_from_slice(b"Info");
+    tagged[40..44].copy_from_slice(&3u32.to_be_bytes());
+    tagged[44..48].copy_from_slice(&4u32.to_be_bytes());
+    tagged[48..52].copy_from_slice(&((frame_bytes + stream.bitstream.len()) as u32).to_be_bytes());
+    tagged.extend_from_slice(stream.bitstream);
+
+    let xing = decoder
+        .parse_xing_header(&tagged)
+        .expect("tag frame not recognised");
+    assert_eq!(xing.frames, Some(4));
+    assert_eq!(xing.total_samples(), Some(4 * 1152));
+
+    let tagged = stream.with_bitstream(tagged);
+    assert_eq!(decode_stream(&tagged), decode_stream(stream));
+}
+
+#[test]
+fn vbri_tag_frame_is_skipped() {
+    let stream = &LAYER3_STREAMS[0];
+    let mut decoder = Box::<MP3Decoder>::default();
+    // a VBRI frame with the header of the stream's first frame, unpadded, behind 3 junk bytes
+    let mut header: [u8; 4] = stream.bitstream[..4].try_into().unwrap();
+    header[2] &= !0x02;
+    decoder.unpack_frame_header(&header).unwrap();
+    let frame_bytes = (144 * decoder.m_MP3DecInfo.bitrate / decoder.m_MP3DecInfo.samprate) as usize;
+    let mut frame = vec![0u8; frame_bytes];
+    frame[..4].copy_from_slice(&header);
+    frame[36..40].copy_from_slice(b"VBRI");
+    frame[40..42].copy_from_slice(&1u16.to_be_bytes());
+    frame[46..50].copy_from_slice(&((frame_bytes + stream.bitstream.len()) as u32).to_be_bytes());
+    frame[50..54].copy_from_slice(&4u32.to_be_bytes());
+    frame[58..60].copy_from_slice(&1u16.to_be_bytes());
+    frame.extend_from_slice(stream.bitstream);
+    let mut tagged = vec![0x00, 0x12, 0x34];
+    tagged.extend_from_slice(&frame);
+
+    let (offset, vbr) = decoder
+        .parse_vbr_header(&tagged)
+        .expect("VBRI frame not recognised");
+    assert_eq!(offset, 3);
+    assert_eq!(vbr.frames(), Some(4));
+    assert_eq!(vbr.total_samples(), Some(4 * 1152));
+    assert_eq!(vbr.seek_offset(4 * 1152), vbr.bytes().map(u64::from));
+
+    let tagged = stream.with_bitstream(frame);
+    assert_eq!(decode_stream(&tagged), decode_stream(stream));
+}
+
+#[test]
+fn id3_tag_is_skipped() {
+    let stream = &LAYER3_STREAMS[1];
+    // an ID3v2.3 tag whose picture starts with the stream's own frame header
+    let mut apic = b"\x00image/jpeg\x00\x03\x00".to_vec();
+    apic.extend_from_slice(&stream.bitstream[..4]);
+    apic.extend_from_slice(&[0x55; 60]);
+    let mut frame = b"APIC".to_vec();
+    frame.extend_from_slice(&(apic.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&[0, 0]);
+    frame.extend_from_slice(&apic);
+    let mut tagged = id3_tag(&frame, 20);
+    let tag_bytes = tagged.len();
+    tagged.extend_from_slice(stream.bitstream);
+
+    let tag = Tag::parse(&tagged).unwrap();
+    assert_eq!(tag.header.tag_size(), tag_bytes);
+    assert!(matches!(
+        tag.frame("APIC").unwrap().content(&mut []),
+        Some(Content::Picture {
+            picture_type: 3,
+            ..
+        })
+    ));
+    assert_eq!(id3::audio_start(&tagged), tag_bytes);
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    let layout = decoder.stream_layout(&tagged).unwrap();
+    assert_eq!(
+        (layout.first_frame, layout.audio_start),
+        (tag_bytes, tag_bytes)
+    );
+    let seek = decoder.seek(&layout, None, 0);
+    assert_eq!(
+        decode_after_seek(&mut decoder, seek, &tagged),
+        decode_stream(stream)
+    );
+}
+
+#[test]
+fn tag_longer_than_the_input_is_skipped_in_pieces() {
+    let stream = &LAYER3_STREAMS[1];
+    // a 10 kB ID3v2 tag, fed in 1600 byte pieces like the C side does
+    let mut tagged = id3_tag(&[], 10_000);
+    let tag_bytes = tagged.len();
+    tagged.extend_from_slice(stream.bitstream);
+    let linear = decode_stream(stream);
+
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut offset = 0;
+    let pcm = decode_all(&tagged, 0, |input, out| {
+        let input = if offset < tag_bytes {
+            window(input, 1600)
+        } else {
+            input
+        };
+        let frame = decoder.decode_frame(input, out)?;
+        assert!(frame.bytes_consumed <= input.len());
+        offset += frame.bytes_consumed;
+        Ok::<_, Mp3Error>((frame.bytes_consumed, frame.samples_written))
+    });
+    assert_eq!(decoder.tag_bytes_left(), 0);
+    assert!(pcm == linear);
+
+    // the same through frame sync, which must not search the rest of the tag
+    let mut decoder = Box::<MP3Decoder>::default();
+    let mut sync = FrameSync::default();
+    let mut offset = 0;
+    let pcm = decode_all(&tagged, 0, |input, out| {
+        let input = if offset < tag_bytes {
+            window(input, 1600)
+        } else {
+            input
+        };
+        let synced = sync.decode_frame(&mut decoder, input, out)?;
+        assert!(synced.frame.bytes_consumed <= input.len());
+        if offset < tag_bytes {
+            assert_eq!(synced.event, SyncEvent::Searching);
+        }
+        offset += synced.frame.bytes_consumed;
+        Ok::<_, Mp3Error>((synced.frame.bytes_consumed, synced.frame.samples_written))
+    });
+    assert!(pcm == linear);
+}
+
+#[test]
+fn trailing_tags_are_skipped() {
+    let stream = &LAYER3_STREAMS[1];
+    // an APEv2 tag with header and footer around one binary item that holds a frame header
+    let mut item = 8u32.to_le_bytes().to_vec();
+    item.extend_from_slice(&2u32.to_le_bytes());
+    item.extend_from_slice(b"Cover Art (Front)\0");
+    item.extend_from_slice(&stream.bitstream[..4]);
+    item.extend_from_slice(&stream.bitstream[..4]);
+    let ape_header = |flags: u32| {
+        let mut header = b"APETAGEX".to_vec();
+        for word in [2000, item.len() as u32 + 32, 1, flags] {
+            header.extend_from_slice(&word.to_le_bytes());
+        }
+        header.extend_from_slice(&[0; 8]);
+        header
+    };
+    let mut tagged = stream.bitstream.to_vec();
+    tagged.extend(ape_header(0xa000_0000));
+    tagged.extend_from_slice(&item);
+    tagged.extend(ape_header(0x8000_0000));
+    tagged.extend_from_slice(b"TAG");
+    tagged.resize(tagged.len() + 125, 0);
+
+    assert_eq!(metadata::audio_end(&tagged), stream.bitstream.len());
+    let tagged = stream.with_bitstream(tagged);
+    assert_eq!(decode_stream(&tagged), decode_stream(stream));
+}
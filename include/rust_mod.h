@@ -8,7 +8,11 @@ static const uint8_t  m_HUFF_PAIRTABS          =32;
 static const uint8_t  m_BLOCK_SIZE             =18;
 static const uint8_t  m_NBANDS                 =32;
 static const uint8_t  m_MAX_REORDER_SAMPS      =(192-126)*3;      // largest critical band for short blocks (see sfBandTable)
+#ifdef CRABIO_SMALL_VBUF                                 // rust_mod built with the small-vbuf feature
+static const uint16_t m_VBUF_LENGTH            =17* m_NBANDS;      // vbuf histories shifted down, not replicated
+#else
 static const uint16_t m_VBUF_LENGTH            =17*2* m_NBANDS;    // for double-sized vbuf FIFO
+#endif
 static const uint8_t  m_MAX_SCFBD              =4;     // max scalefactor bands per channel
 static const uint16_t m_MAINBUF_SIZE           =1940;
 static const uint8_t  m_MAX_NGRAN              =2;     // max granules
@@ -114,6 +118,8 @@ typedef struct MP3DecInfo {
     int mainDataBegin;
     int mainDataBytes;
     int part23Length[m_MAX_NGRAN][m_MAX_NCHAN];
+
+    int tagBytesLeft;       /* rest of a tag longer than the input it was found in */
 } MP3DecInfo_t;
 
 /* indexing = [version][layer][bitrate index]
@@ -243,6 +249,11 @@ typedef enum {          /* map these to the corresponding 2-bit values in the fr
     Mono = 0x03         /* one channel */
 } StereoMode_t;
 
+typedef enum {          /* channels of the PCM output */
+    OutputNative = 0,   /* the channels of the stream */
+    OutputMono = 1      /* stereo streams mixed down to one channel */
+} OutputMode_t;
+
 
 int CLIP_2N(int y, uint32_t n);
 
@@ -285,10 +296,13 @@ typedef struct MP3Decoder {
     ScaleFactorInfoSub_t m_ScaleFactorInfoSub[m_MAX_NGRAN][m_MAX_NCHAN];
     CriticalBandInfo_t m_CriticalBandInfo[m_MAX_NCHAN];  /* filled in dequantizer, used in joint stereo reconstruction */
     HuffmanInfo_t m_HuffmanInfo;
-    DequantInfo_t m_DequantInfo;
+#ifndef CRABIO_SMALL_VBUF
+    DequantInfo_t m_DequantInfo;   /* with CRABIO_SMALL_VBUF the dequantizer borrows m_IMDCTInfo.outBuf */
+#endif
     IMDCTInfo_t m_IMDCTInfo;
     StereoMode_t m_sMode;  /* mono/stereo mode */
     MPEGVersion_t m_MPEGVersion;  /* version ID */
+    OutputMode_t m_OutputMode;    /* channels of the PCM output */
 
 } MP3Decoder_t;
 
@@ -385,6 +399,29 @@ int8_t MP3DecodeHelper(
     MP3Decoder_t *m_MP3Decoder
 );
 
+void MP3DecoderInit(MP3Decoder_t *m_MP3Decoder);
+void MP3DecoderReset(MP3Decoder_t *m_MP3Decoder);
+void MP3DecoderFlush(MP3Decoder_t *m_MP3Decoder, int keepOverlap);
+void MP3DecoderSetMonoOutput(MP3Decoder_t *m_MP3Decoder, int mono);
+
+/* AAC decoder (crabio::decoders::aac), only handled through a pointer to AACDecoderSize() bytes */
+typedef struct AACDecoder AACDecoder_t;
+
+int ADTSFindSyncWord(const uint8_t *buf, int nBytes);
+size_t AACDecoderSize(void);
+void AACDecoderReset(AACDecoder_t *m_AACDecoder);
+void AACDecoderFlush(AACDecoder_t *m_AACDecoder);
+int AACDecoderSetRawBlockParams(AACDecoder_t *m_AACDecoder, int copyLast, int nChans, int sampRate, int profile);
+int AACDecoderSetAudioSpecificConfig(AACDecoder_t *m_AACDecoder, const uint8_t *asc, int len);
+void AACDecoderSetParametricStereo(AACDecoder_t *m_AACDecoder, int enable);
+int AACDecodeHelper(uint8_t *inbuf, int *bytesLeft, short *outbuf, AACDecoder_t *m_AACDecoder);
+int AACDecoderGetSampRate(const AACDecoder_t *m_AACDecoder);
+int AACDecoderGetChannels(const AACDecoder_t *m_AACDecoder);
+int AACDecoderGetProfile(const AACDecoder_t *m_AACDecoder);
+int AACDecoderGetFormat(const AACDecoder_t *m_AACDecoder);
+int AACDecoderGetBitrate(const AACDecoder_t *m_AACDecoder);
+int AACDecoderGetOutputSamps(const AACDecoder_t *m_AACDecoder);
+
 #ifdef __cplusplus
 }
 #endif
\ No newline at end of file
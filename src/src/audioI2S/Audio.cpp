@@ -3870,6 +3870,7 @@ bool Audio:: initializeDecoder(){
     switch(this->m_codec){
         case CODEC_MP3:
             if(!MP3Decoder_AllocateBuffers()) goto exit;
+            MP3Decoder_SetMonoOutput(m_f_forceMono); // per stream, the channels are only read once
             AUDIO_INFO("MP3Decoder has been initialized, free Heap: %u bytes", ESP.getFreeHeap());
             InBuff.changeMaxBlockSize(this->m_frameSizeMP3);
             break;
@@ -4553,7 +4554,7 @@ bool Audio::setFilePos(uint32_t pos) {
 //    if(!m_avr_bitrate) return false;
     if(m_codec == CODEC_M4A) return false;
     m_f_playing = false;
-    if(m_codec == CODEC_MP3) MP3Decoder_ClearBuffer();
+    if(m_codec == CODEC_MP3) MP3Decoder_Flush(false); // the bit reservoir belongs to the old position
     if(m_codec == CODEC_WAV) {while((pos % 4) != 0) pos++;} // must be divisible by four
     if(m_codec == CODEC_FLAC) FLACDecoderReset();
     InBuff.resetBuffer();
@@ -6,6 +6,8 @@
  *  Updated on: 27.05.2022
  ************************************************************************************/
 
+#ifndef CRABIO_AAC  // replaced by the native decoder in aac_decoder_crabio.cpp
+
 #include "aac_decoder.h"
 
 const uint32_t SQRTHALF             = 0x5a82799a;    /* sqrt(0.5), format = Q31 */
@@ -10218,3 +10220,5 @@ void UnpackSBRChannelPair(int chBase) {
         }
     }
 }
+
+#endif // CRABIO_AAC
@@ -0,0 +1,128 @@
+/*
+ * aac_decoder_crabio.cpp
+ * AAC-LC, HE-AAC (SBR) and HE-AACv2 (PS) decoding with the native decoder in rust_mod (crabio::decoders::aac),
+ * built with -DCRABIO_AAC instead of the helix decoder in aac_decoder.cpp; -DCRABIO_AAC_NO_PS plays HE-AACv2 in
+ * mono on slow targets
+ *
+ * The decoder takes raw data blocks (m4a) and, until AACSetRawBlockParams() is called, ADTS frames (aac), which
+ * set it up from their header.
+ */
+#ifdef CRABIO_AAC
+
+#include "aac_decoder.h"
+#include "../../../../include/rust_mod.h"
+
+AACDecoder_t *m_AACDecoder;
+
+#ifdef CONFIG_IDF_TARGET_ESP32S3
+    // ESP32-S3: If there is PSRAM, prefer it
+    #define __malloc_heap_psram(size) \
+        heap_caps_malloc_prefer(size, 2, MALLOC_CAP_DEFAULT|MALLOC_CAP_SPIRAM, MALLOC_CAP_DEFAULT|MALLOC_CAP_INTERNAL)
+#else
+    // ESP32, PSRAM is too slow, prefer SRAM
+    #define __malloc_heap_psram(size) \
+        heap_caps_malloc_prefer(size, 2, MALLOC_CAP_DEFAULT|MALLOC_CAP_INTERNAL, MALLOC_CAP_DEFAULT|MALLOC_CAP_SPIRAM)
+#endif
+
+/***********************************************************************************************************************
+ * Function:    AACDecoder_AllocateBuffers
+ *
+ * Description: allocate all the memory needed for the AAC decoder
+ *
+ * Inputs:      none
+ *
+ * Outputs:     none
+ *
+ * Return:      true if successful, false if there is not enough memory
+ **********************************************************************************************************************/
+bool AACDecoder_AllocateBuffers(void){
+    if(!m_AACDecoder) {m_AACDecoder = (AACDecoder_t*) __malloc_heap_psram(AACDecoderSize());}
+    if(!m_AACDecoder) {
+        log_e("not enough memory to allocate aacdecoder buffers");
+        return false;
+    }
+    AACDecoderReset(m_AACDecoder);
+#ifdef CRABIO_AAC_NO_PS
+    AACDecoderSetParametricStereo(m_AACDecoder, 0); // HE-AACv2 in mono, saves CPU time
+#endif
+    return true;
+}
+/***********************************************************************************************************************
+ * Function:    AACFlushCodec
+ *
+ * Description: flush internal codec state (after seeking, for example)
+ *
+ * Inputs:      none
+ *
+ * Outputs:     none
+ *
+ * Return:      0 if successful, error code (< 0) if error
+ **********************************************************************************************************************/
+int AACFlushCodec(){
+    if(!m_AACDecoder) return ERR_AAC_NULL_POINTER;
+    AACDecoderFlush(m_AACDecoder);
+    return ERR_AAC_NONE;
+}
+/***********************************************************************************************************************
+ * Function:    AACDecoder_FreeBuffers
+ *
+ * Description: frees all the memory used by the AAC decoder
+ *
+ * Notes:       safe to call even if the decoder was not allocated
+ **********************************************************************************************************************/
+void AACDecoder_FreeBuffers(void) {
+    if(m_AACDecoder) {free(m_AACDecoder); m_AACDecoder = NULL;}
+}
+
+bool AACDecoder_IsInit(void) {
+    return m_AACDecoder != NULL;
+}
+/***********************************************************************************************************************
+ * Function:    AACFindSyncWord
+ *
+ * Description: locate the next byte-aligned ADTS sync word in the AAC stream
+ *
+ * Inputs:      buffer to search for sync word
+ *              max number of bytes to search in buffer
+ *
+ * Return:      offset to first sync word (bytes from start of buf)
+ *              -1 if sync not found after searching nBytes
+ **********************************************************************************************************************/
+int AACFindSyncWord(uint8_t *buf, int nBytes){
+    return ADTSFindSyncWord(buf, nBytes);
+}
+//**************************************************************************************
+int AACGetSampRate(){return AACDecoderGetSampRate(m_AACDecoder);}
+int AACGetChannels(){return AACDecoderGetChannels(m_AACDecoder);}
+int AACGetBitsPerSample(){return 16;}
+int AACGetID() {return 0;} // 0-MPEG4, 1-MPEG2
+uint8_t AACGetProfile() {return (uint8_t)AACDecoderGetProfile(m_AACDecoder);} // 0-Main, 1-LC, 2-SSR, 3-reserved
+uint8_t AACGetFormat() {return (uint8_t)AACDecoderGetFormat(m_AACDecoder);}   // 0-unknown 1-ADTS 2-ADIF, 3-RAW
+int AACGetOutputSamps(){return AACDecoderGetOutputSamps(m_AACDecoder);}
+int AACGetBitrate() {return AACDecoderGetBitrate(m_AACDecoder);}
+
+int AACSetRawBlockParams(int copyLast, int nChans, int sampRateCore, int profile){
+    if(!m_AACDecoder) return ERR_AAC_NULL_POINTER;
+    return AACDecoderSetRawBlockParams(m_AACDecoder, copyLast, nChans, sampRateCore, profile);
+}
+/***********************************************************************************************************************
+ * Function:    AACDecode
+ *
+ * Description: decode one raw data block
+ *
+ * Inputs:      buffer starting at the block, or at an ADTS header (see AACDecodeHelper)
+ *              pointer to number of valid bytes remaining in inbuf
+ *              pointer to outbuf, big enough to hold one frame of decoded PCM samples
+ *
+ * Outputs:     PCM data in outbuf, interleaved LRLRLR... if stereo
+ *                number of output samples = 1024 per channel, 2048 with SBR
+ *              updated bytesLeft
+ *
+ * Return:      0 if successful, error code (< 0) if error
+ **********************************************************************************************************************/
+int AACDecode(uint8_t *inbuf, int *bytesLeft, short *outbuf){
+    if(!m_AACDecoder) return ERR_AAC_NULL_POINTER;
+    return AACDecodeHelper(inbuf, bytesLeft, outbuf, m_AACDecoder);
+}
+
+#endif // CRABIO_AAC
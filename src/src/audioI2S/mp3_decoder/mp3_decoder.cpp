@@ -67,11 +67,44 @@ int MP3Decode( unsigned char *inbuf, size_t inbuf_len, int *bytesLeft, short *ou
 void MP3Decoder_ClearBuffer(void) {
 
     /* important to do this - DSP primitives assume a bunch of state variables are 0 on first use */
-    memset( m_MP3Decoder,         0, sizeof(MP3Decoder_t));                                    //Clear MP3DecInfo
+    MP3DecoderReset(m_MP3Decoder);
 
     return;
 
 }
+/***********************************************************************************************************************
+ * Function:    MP3Decoder_Flush
+ *
+ * Description: drop the bit reservoir after a seek, a stream reconnect or a station change
+ *
+ * Inputs:      flag to keep the IMDCT overlap and filterbank history, so the old stream
+ *                crossfades into the new one
+ *
+ * Outputs:     none
+ *
+ * Return:      none
+ *
+ **********************************************************************************************************************/
+void MP3Decoder_Flush(bool keepOverlap) {
+    MP3DecoderFlush(m_MP3Decoder, keepOverlap);
+}
+/***********************************************************************************************************************
+ * Function:    MP3Decoder_SetMonoOutput
+ *
+ * Description: mix stereo streams down to one channel, for a single speaker
+ *
+ * Inputs:      true for mono output, false for the channels of the stream
+ *
+ * Outputs:     none
+ *
+ * Return:      none
+ *
+ * Notes:       MP3GetChannels() and MP3GetOutputSamps() describe the output; call after
+ *                MP3Decoder_AllocateBuffers(), the setting survives MP3Decoder_ClearBuffer()
+ **********************************************************************************************************************/
+void MP3Decoder_SetMonoOutput(bool mono) {
+    if(m_MP3Decoder) MP3DecoderSetMonoOutput(m_MP3Decoder, mono);
+}
 /***********************************************************************************************************************
  * Function:    MP3Decoder_AllocateBuffers
  *
@@ -106,7 +139,8 @@ bool MP3Decoder_AllocateBuffers(void) {
         log_e("not enough memory to allocate mp3decoder buffers");
         return false;
     }
-    MP3Decoder_ClearBuffer();
+    /* important to do this - DSP primitives assume a bunch of state variables are 0 on first use */
+    MP3DecoderInit(m_MP3Decoder);
     return true;
 }
 /***********************************************************************************************************************
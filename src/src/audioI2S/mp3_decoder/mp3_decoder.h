@@ -204,6 +204,8 @@ const uint32_t csa[8][2] PROGMEM = {
 // prototypes
 bool MP3Decoder_AllocateBuffers(void);
 void MP3Decoder_FreeBuffers();
+void MP3Decoder_Flush(bool keepOverlap);
+void MP3Decoder_SetMonoOutput(bool mono);
 int  MP3Decode( unsigned char *inbuf, size_t inbuf_len, int *bytesLeft, short *outbuf, int useSize);
 int  MP3FindSyncWord(unsigned char *buf, int nBytes);
 int  MP3GetSampRate();